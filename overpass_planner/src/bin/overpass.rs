@@ -0,0 +1,167 @@
+//! `overpass` command line tool.
+//!
+//! Usage:
+//!   overpass passes --norad 25544 --lat 38.89 --lon -77.17 [--alt 0] [--hours 24] [--min-el 0] [--json]
+//!   overpass tle --norad 25544
+
+use chrono::Duration;
+use overpass_planner::tle::fetch_tle;
+use overpass_planner::{get_overpasses, get_satellite_name, ObserverLocation, Overpass};
+use std::collections::HashMap;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage:
+  overpass passes --norad <ID> --lat <DEG> --lon <DEG> [--alt <M>] [--hours <H>] [--min-el <DEG>] [--json]
+  overpass tle --norad <ID>";
+
+/// Parsed `--flag value` options plus boolean switches.
+struct Options {
+    values: HashMap<String, String>,
+    switches: Vec<String>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        let mut switches = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                return Err(format!("Unexpected argument: {arg}"));
+            };
+            if name == "json" {
+                switches.push(name.to_string());
+                continue;
+            }
+            let value = iter
+                .next()
+                .ok_or_else(|| format!("Missing value for --{name}"))?;
+            values.insert(name.to_string(), value.clone());
+        }
+        Ok(Self { values, switches })
+    }
+
+    fn has(&self, name: &str) -> bool {
+        self.switches.iter().any(|s| s == name)
+    }
+
+    fn get<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.values
+            .get(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("Invalid value for --{name}: {v}"))
+            })
+            .transpose()
+    }
+
+    fn require<T: std::str::FromStr>(&self, name: &str) -> Result<T, String> {
+        self.get(name)?
+            .ok_or_else(|| format!("Missing required option --{name}"))
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    let result = match Options::parse(rest) {
+        Ok(options) => match command.as_str() {
+            "passes" => passes(&options).await,
+            "tle" => tle(&options).await,
+            _ => Err(format!("Unknown command: {command}\n\n{USAGE}")),
+        },
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Prints the overpasses of a satellite for an observer location.
+async fn passes(options: &Options) -> Result<(), String> {
+    let norad_id: u32 = options.require("norad")?;
+    let location = ObserverLocation {
+        latitude: options.require("lat")?,
+        longitude: options.require("lon")?,
+        altitude: options.get("alt")?.unwrap_or(0.0),
+    };
+    let hours: i64 = options.get("hours")?.unwrap_or(24);
+    let min_elevation: f64 = options.get("min-el")?.unwrap_or(0.0);
+
+    let overpasses: Vec<Overpass> = get_overpasses(norad_id, location, Duration::hours(hours))
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|o| o.max_elevation >= min_elevation)
+        .collect();
+
+    if options.has("json") {
+        let entries: Vec<serde_json::Value> = overpasses
+            .iter()
+            .map(|o| {
+                serde_json::json!({
+                    "start_time": o.start_time.to_rfc3339(),
+                    "end_time": o.end_time.to_rfc3339(),
+                    "midpoint_time": o.midpoint_time.to_rfc3339(),
+                    "duration_seconds": (o.end_time - o.start_time).num_seconds(),
+                    "max_elevation": o.max_elevation,
+                    "is_night": o.is_night,
+                    "is_lit": o.is_lit,
+                    "kind": format!("{:?}", o.kind),
+                    "truncated_start": o.truncated_start,
+                    "truncated_end": o.truncated_end,
+                })
+            })
+            .collect();
+        let output = serde_json::json!({
+            "norad_id": norad_id,
+            "latitude": location.latitude,
+            "longitude": location.longitude,
+            "altitude": location.altitude,
+            "overpasses": entries,
+        });
+        println!("{output:#}");
+        return Ok(());
+    }
+
+    let name = get_satellite_name(norad_id)
+        .await
+        .unwrap_or_else(|_| norad_id.to_string());
+    println!(
+        "{} overpass(es) of {} in the next {} hours (min elevation {:.0}°):",
+        overpasses.len(),
+        name,
+        hours,
+        min_elevation
+    );
+    for o in &overpasses {
+        println!(
+            "  {}  ->  {}  {:>5.1} min  max {:>5.1}°  night: {:<3}  lit: {}",
+            o.start_time.format("%Y-%m-%d %H:%M:%S UTC"),
+            o.end_time.format("%H:%M:%S"),
+            (o.end_time - o.start_time).num_seconds() as f64 / 60.0,
+            o.max_elevation,
+            if o.is_night { "yes" } else { "no" },
+            if o.is_lit { "yes" } else { "no" },
+        );
+    }
+    Ok(())
+}
+
+/// Prints the TLE of a satellite.
+async fn tle(options: &Options) -> Result<(), String> {
+    let norad_id: u32 = options.require("norad")?;
+    let tle = fetch_tle(norad_id).await.map_err(|e| e.to_string())?;
+    println!("{tle}");
+    Ok(())
+}
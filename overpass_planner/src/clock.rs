@@ -0,0 +1,105 @@
+//! Clock module.
+//!
+//! Every "now" used by the planner (the start of search windows and TLE cache
+//! expiry) comes from the process-wide [`Clock`], so tests and simulations can
+//! pin or advance time instead of depending on the wall clock.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Process-wide clock used by [`now`]; `None` means the system clock.
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+    /// The current time in UTC.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that stays at a fixed time until it is moved explicitly.
+#[derive(Debug)]
+pub struct FixedClock {
+    time: Mutex<DateTime<Utc>>,
+}
+
+impl FixedClock {
+    /// Creates a clock stopped at `time`.
+    pub fn new(time: DateTime<Utc>) -> Self {
+        Self {
+            time: Mutex::new(time),
+        }
+    }
+
+    /// Moves the clock to `time`.
+    pub fn set(&self, time: DateTime<Utc>) {
+        if let Ok(mut guard) = self.time.lock() {
+            *guard = time;
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        if let Ok(mut guard) = self.time.lock() {
+            *guard += duration;
+        }
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.time.lock().map(|t| *t).unwrap_or_else(|_| Utc::now())
+    }
+}
+
+/// Replaces the clock used by the planner for the rest of the process.
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    if let Ok(mut guard) = CLOCK.write() {
+        *guard = Some(clock);
+    }
+}
+
+/// Restores the system clock.
+pub fn reset_clock() {
+    if let Ok(mut guard) = CLOCK.write() {
+        *guard = None;
+    }
+}
+
+/// The current time according to the configured clock.
+pub fn now() -> DateTime<Utc> {
+    if let Ok(guard) = CLOCK.read() {
+        if let Some(clock) = guard.as_ref() {
+            return clock.now();
+        }
+    }
+    Utc::now()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_fixed_clock() {
+        let start = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let clock = FixedClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::minutes(90));
+        assert_eq!(clock.now(), start + Duration::minutes(90));
+
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
@@ -0,0 +1,266 @@
+//! Constellation coverage module.
+//!
+//! This module answers "when is at least one satellite of a group visible above
+//! a given elevation?", e.g. to schedule downlinks from any NOAA weather
+//! satellite or to check Starlink coverage. The visibility windows of every
+//! satellite in the group are merged into a single timeline.
+
+use crate::planning::{ObserverLocation, PropagationContext};
+use crate::propagator::Propagator;
+use crate::tle::find_satellites;
+use crate::{clock, OverpassPlannerError, OverpassPlannerResult};
+use chrono::{DateTime, Duration, Utc};
+
+/// A window during which at least one satellite of the group is visible.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageWindow {
+    /// Start time of the window
+    pub start_time: DateTime<Utc>,
+    /// End time of the window
+    pub end_time: DateTime<Utc>,
+    /// NORAD IDs of the satellites visible at some point during the window
+    pub satellites: Vec<u32>,
+}
+
+/// Start and end of a span during which one satellite is visible.
+pub type VisibilityInterval = (DateTime<Utc>, DateTime<Utc>);
+
+/// Merged visibility of a satellite group over a search period.
+#[derive(Debug, Clone)]
+pub struct ConstellationCoverage {
+    /// Windows during which any satellite is above the minimum elevation, in time order
+    pub windows: Vec<CoverageWindow>,
+    /// Fraction of the search period covered by the windows (0.0 to 1.0)
+    pub coverage_fraction: f64,
+    /// NORAD IDs of the satellites included in the analysis
+    pub satellites: Vec<u32>,
+    /// NORAD IDs of the group members left out because their TLE could not be
+    /// used (decayed, stale or failing to propagate)
+    pub skipped: Vec<u32>,
+}
+
+/// Compute the merged coverage of every satellite whose name starts with `group`.
+///
+/// # Arguments
+/// * `group` - Satellite name prefix, e.g. `"STARLINK"` or `"NOAA"` (case-insensitive)
+/// * `location` - Observer's location on Earth
+/// * `time_from_now` - Duration from now to analyze
+/// * `min_elevation` - Elevation above which a satellite counts as visible (degrees)
+///
+/// # Returns
+/// The coverage windows and the fraction of the period covered. Group members
+/// that cannot be propagated are listed in `skipped` rather than failing the
+/// whole analysis.
+pub async fn get_constellation_coverage(
+    group: &str,
+    location: ObserverLocation,
+    time_from_now: Duration,
+    min_elevation: f64,
+) -> OverpassPlannerResult<ConstellationCoverage> {
+    let members = find_satellites(group).await?;
+    if members.is_empty() {
+        return Err(OverpassPlannerError::InvalidInput(format!(
+            "No satellites found with names starting with '{group}'"
+        )));
+    }
+
+    let start_time = clock::now();
+    let end_time = start_time + time_from_now;
+
+    let mut contexts = Vec::new();
+    let mut skipped = Vec::new();
+    for (norad_id, tle) in members {
+        match PropagationContext::from_tle(&tle).and_then(|ctx| {
+            ctx.check_not_decayed(start_time)?;
+            Ok(ctx)
+        }) {
+            Ok(ctx) => contexts.push((norad_id, ctx)),
+            Err(_) => skipped.push(norad_id),
+        }
+    }
+
+    let propagators: Vec<(u32, &dyn Propagator)> = contexts
+        .iter()
+        .map(|(norad_id, ctx)| (*norad_id, ctx as &dyn Propagator))
+        .collect();
+    let mut coverage = find_coverage(&propagators, location, start_time, end_time, min_elevation);
+    coverage.skipped.extend(skipped);
+    coverage.skipped.sort_unstable();
+    Ok(coverage)
+}
+
+/// Compute the merged coverage of a set of satellites between two instants.
+///
+/// Satellites that fail to propagate are listed in `skipped`.
+pub fn find_coverage(
+    satellites: &[(u32, &dyn Propagator)],
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    min_elevation: f64,
+) -> ConstellationCoverage {
+    let mut per_satellite = Vec::new();
+    let mut included = Vec::new();
+    let mut skipped = Vec::new();
+    for &(norad_id, propagator) in satellites {
+        match visibility_intervals(propagator, location, start_time, end_time, min_elevation) {
+            Ok(intervals) => {
+                included.push(norad_id);
+                per_satellite.push((norad_id, intervals));
+            }
+            Err(_) => skipped.push(norad_id),
+        }
+    }
+
+    let windows = merge_coverage_windows(&per_satellite);
+    let covered_seconds: i64 = windows
+        .iter()
+        .map(|w| (w.end_time - w.start_time).num_seconds())
+        .sum();
+    let period_seconds = (end_time - start_time).num_seconds().max(1);
+
+    ConstellationCoverage {
+        windows,
+        coverage_fraction: (covered_seconds as f64 / period_seconds as f64).clamp(0.0, 1.0),
+        satellites: included,
+        skipped,
+    }
+}
+
+/// Merge per-satellite visibility intervals into windows during which any
+/// satellite is visible.
+///
+/// Overlapping or touching intervals are joined, and each window lists every
+/// satellite that contributed to it.
+pub fn merge_coverage_windows(
+    per_satellite: &[(u32, Vec<VisibilityInterval>)],
+) -> Vec<CoverageWindow> {
+    let mut intervals: Vec<(VisibilityInterval, u32)> = per_satellite
+        .iter()
+        .flat_map(|(norad_id, intervals)| {
+            intervals.iter().map(move |&interval| (interval, *norad_id))
+        })
+        .collect();
+    intervals.sort_by_key(|&(interval, _)| interval);
+
+    let mut windows: Vec<CoverageWindow> = Vec::new();
+    for ((start, end), norad_id) in intervals {
+        if let Some(last) = windows.last_mut() {
+            if start <= last.end_time {
+                last.end_time = last.end_time.max(end);
+                if !last.satellites.contains(&norad_id) {
+                    last.satellites.push(norad_id);
+                }
+                continue;
+            }
+        }
+
+        windows.push(CoverageWindow {
+            start_time: start,
+            end_time: end,
+            satellites: vec![norad_id],
+        });
+    }
+
+    for window in &mut windows {
+        window.satellites.sort_unstable();
+    }
+    windows
+}
+
+/// Find the intervals during which a satellite is above `min_elevation`.
+fn visibility_intervals(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    min_elevation: f64,
+) -> OverpassPlannerResult<Vec<VisibilityInterval>> {
+    // Search step: 1 minute intervals, crossings refined to 1 second
+    let search_step = Duration::minutes(1);
+    let is_visible = |time: DateTime<Utc>| -> OverpassPlannerResult<bool> {
+        Ok(propagator.alt_az(location, time)?.0 >= min_elevation)
+    };
+
+    let mut intervals = Vec::new();
+    let mut rise = is_visible(start_time)?.then_some(start_time);
+    let mut previous_time = start_time;
+    let mut was_visible = rise.is_some();
+
+    while previous_time < end_time {
+        let current_time = (previous_time + search_step).min(end_time);
+        let visible = is_visible(current_time)?;
+
+        if visible != was_visible {
+            let crossing = find_crossing(&is_visible, previous_time, current_time, was_visible)?;
+            match rise.take() {
+                Some(start) => intervals.push((start, crossing)),
+                None => rise = Some(crossing),
+            }
+        }
+
+        was_visible = visible;
+        previous_time = current_time;
+    }
+
+    if let Some(start) = rise {
+        intervals.push((start, end_time));
+    }
+    Ok(intervals)
+}
+
+/// Bisect to the second at which visibility changes from `visible_before`.
+fn find_crossing(
+    is_visible: &dyn Fn(DateTime<Utc>) -> OverpassPlannerResult<bool>,
+    mut low: DateTime<Utc>,
+    mut high: DateTime<Utc>,
+    visible_before: bool,
+) -> OverpassPlannerResult<DateTime<Utc>> {
+    while (high - low).num_seconds() > 1 {
+        let mid = low + (high - low) / 2;
+        if is_visible(mid)? == visible_before {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok(high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn interval(start_min: i64, end_min: i64) -> VisibilityInterval {
+        let base = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        (
+            base + Duration::minutes(start_min),
+            base + Duration::minutes(end_min),
+        )
+    }
+
+    #[test]
+    fn test_merge_coverage_windows() {
+        let per_satellite = vec![
+            (33591, vec![interval(0, 10), interval(100, 110)]),
+            (28654, vec![interval(5, 20)]),
+            (25338, vec![interval(20, 25), interval(200, 205)]),
+        ];
+
+        let windows = merge_coverage_windows(&per_satellite);
+        assert_eq!(windows.len(), 3);
+
+        assert_eq!(
+            (windows[0].start_time, windows[0].end_time),
+            interval(0, 25)
+        );
+        assert_eq!(windows[0].satellites, vec![25338, 28654, 33591]);
+        assert_eq!(
+            (windows[1].start_time, windows[1].end_time),
+            interval(100, 110)
+        );
+        assert_eq!(windows[1].satellites, vec![33591]);
+        assert_eq!(windows[2].satellites, vec![25338]);
+    }
+}
@@ -0,0 +1,303 @@
+//! High-accuracy ephemeris module.
+//!
+//! This module loads precise satellite positions from SP3 (IGS precise orbit) and
+//! CPF (ILRS consolidated prediction format) files and interpolates them, giving
+//! laser-ranging-grade pointing where a TLE would be off by kilometers.
+
+use crate::propagator::Propagator;
+use crate::{OverpassPlannerError, OverpassPlannerResult};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use std::path::Path;
+
+/// Number of samples used for Lagrange interpolation.
+const INTERPOLATION_ORDER: usize = 9;
+/// Orbital radius range (km) treated as geosynchronous.
+const GEOSYNCHRONOUS_RADIUS_KM: std::ops::Range<f64> = 41_000.0..43_500.0;
+/// GPS time is ahead of UTC by this many seconds (since 2017).
+const GPS_UTC_OFFSET_SECONDS: i64 = 18;
+/// TAI is ahead of UTC by this many seconds (since 2017).
+const TAI_UTC_OFFSET_SECONDS: i64 = 37;
+
+/// Tabulated satellite positions loaded from an ephemeris file.
+#[derive(Debug, Clone)]
+pub struct Ephemeris {
+    name: String,
+    /// Sample times and ITRF positions in meters, sorted by time
+    samples: Vec<(DateTime<Utc>, [f64; 3])>,
+}
+
+impl Ephemeris {
+    /// Loads an SP3 or CPF file, detecting the format from its first line.
+    ///
+    /// For SP3 files the first satellite in the file is used; call
+    /// [`Ephemeris::from_sp3`] to pick another.
+    pub fn from_file(path: impl AsRef<Path>) -> OverpassPlannerResult<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            OverpassPlannerError::ParseError(format!(
+                "Failed to read ephemeris file {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        let first_line = data.lines().next().unwrap_or("").trim_start();
+        if first_line.starts_with('#') {
+            Self::from_sp3(&data, None)
+        } else if first_line.starts_with("H1") || first_line.starts_with("h1") {
+            Self::from_cpf(&data)
+        } else {
+            Err(OverpassPlannerError::ParseError(format!(
+                "Unrecognized ephemeris format in {}",
+                path.display()
+            )))
+        }
+    }
+
+    /// Parses SP3-c/SP3-d data for one satellite.
+    ///
+    /// # Arguments
+    /// * `data` - Contents of the SP3 file
+    /// * `satellite_id` - SP3 satellite identifier (e.g. `"L51"`), or `None` for the
+    ///   first satellite in the file
+    pub fn from_sp3(data: &str, satellite_id: Option<&str>) -> OverpassPlannerResult<Self> {
+        let mut time_offset = Duration::seconds(GPS_UTC_OFFSET_SECONDS);
+        let mut seen_time_system = false;
+        let mut satellite_id = satellite_id.map(|s| s.to_string());
+        let mut current_epoch: Option<DateTime<Utc>> = None;
+        let mut samples = Vec::new();
+
+        for line in data.lines() {
+            if line.starts_with("%c") && !seen_time_system {
+                // Only the first %c line carries the time system
+                seen_time_system = true;
+                time_offset = match line.split_whitespace().nth(3) {
+                    Some("GPS") | Some("GAL") | None => Duration::seconds(GPS_UTC_OFFSET_SECONDS),
+                    Some("TAI") => Duration::seconds(TAI_UTC_OFFSET_SECONDS),
+                    Some("UTC") | Some("GLO") => Duration::zero(),
+                    Some(other) => {
+                        return Err(OverpassPlannerError::ParseError(format!(
+                            "Unsupported SP3 time system: {other}"
+                        )))
+                    }
+                };
+            } else if let Some(rest) = line.strip_prefix('*') {
+                current_epoch = Some(parse_sp3_epoch(rest)? - time_offset);
+            } else if let Some(rest) = line.strip_prefix('P') {
+                let Some(epoch) = current_epoch else {
+                    continue;
+                };
+                let id = rest.get(0..3).unwrap_or("").trim().to_string();
+                let wanted = satellite_id.get_or_insert_with(|| id.clone());
+                if *wanted != id {
+                    continue;
+                }
+
+                let values: Vec<f64> = rest
+                    .get(3..)
+                    .unwrap_or("")
+                    .split_whitespace()
+                    .take(3)
+                    .map(|v| v.parse::<f64>())
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| {
+                        OverpassPlannerError::ParseError(format!("Invalid SP3 position: {e}"))
+                    })?;
+                // SP3 marks missing positions with zeros
+                if values.len() == 3 && values.iter().any(|v| *v != 0.0) {
+                    samples.push((
+                        epoch,
+                        [values[0] * 1000.0, values[1] * 1000.0, values[2] * 1000.0],
+                    ));
+                }
+            }
+        }
+
+        Self::from_samples(satellite_id.unwrap_or_default(), samples)
+    }
+
+    /// Parses CPF (v1 or v2) prediction data.
+    ///
+    /// CPF positions are in the ITRF frame, in meters, with UTC epochs.
+    pub fn from_cpf(data: &str) -> OverpassPlannerResult<Self> {
+        let mut name = String::new();
+        let mut samples = Vec::new();
+
+        for line in data.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.first().map(|f| f.to_ascii_uppercase()) {
+                Some(record) if record == "H1" => {
+                    name = fields.last().unwrap_or(&"").to_string();
+                }
+                Some(record) if record == "10" => {
+                    if fields.len() < 8 {
+                        return Err(OverpassPlannerError::ParseError(format!(
+                            "Incomplete CPF position record: {line}"
+                        )));
+                    }
+                    let parse = |s: &str| {
+                        s.parse::<f64>().map_err(|e| {
+                            OverpassPlannerError::ParseError(format!(
+                                "Invalid CPF position record '{line}': {e}"
+                            ))
+                        })
+                    };
+                    let mjd = parse(fields[2])?;
+                    let seconds_of_day = parse(fields[3])?;
+                    let epoch = mjd_to_datetime(mjd, seconds_of_day)?;
+                    samples.push((
+                        epoch,
+                        [parse(fields[5])?, parse(fields[6])?, parse(fields[7])?],
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        Self::from_samples(name, samples)
+    }
+
+    fn from_samples(
+        name: String,
+        mut samples: Vec<(DateTime<Utc>, [f64; 3])>,
+    ) -> OverpassPlannerResult<Self> {
+        if samples.len() < 2 {
+            return Err(OverpassPlannerError::ParseError(
+                "Ephemeris must contain at least 2 positions".to_string(),
+            ));
+        }
+        samples.sort_by_key(|(t, _)| *t);
+        samples.dedup_by_key(|(t, _)| *t);
+        Ok(Self { name, samples })
+    }
+
+    /// Satellite name or identifier from the file header.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// First and last time covered by the ephemeris.
+    pub fn time_span(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        (self.samples[0].0, self.samples[self.samples.len() - 1].0)
+    }
+}
+
+impl Propagator for Ephemeris {
+    fn position_itrf(&self, timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+        let (first, last) = self.time_span();
+        if timestamp < first || timestamp > last {
+            return Err(OverpassPlannerError::CalculationError(format!(
+                "{} is outside the ephemeris span {} - {}",
+                timestamp, first, last
+            )));
+        }
+
+        // Pick the samples centered on the requested time
+        let index = self.samples.partition_point(|(t, _)| *t < timestamp);
+        let order = INTERPOLATION_ORDER.min(self.samples.len());
+        let start = index
+            .saturating_sub(order / 2)
+            .min(self.samples.len() - order);
+        let window = &self.samples[start..start + order];
+
+        let seconds = |t: DateTime<Utc>| (t - first).num_milliseconds() as f64 / 1000.0;
+        let x = seconds(timestamp);
+        let mut position = [0.0; 3];
+        for (i, (ti, pi)) in window.iter().enumerate() {
+            let mut weight = 1.0;
+            for (j, (tj, _)) in window.iter().enumerate() {
+                if i != j {
+                    weight *= (x - seconds(*tj)) / (seconds(*ti) - seconds(*tj));
+                }
+            }
+            for (axis, value) in position.iter_mut().zip(pi) {
+                *axis += weight * value;
+            }
+        }
+        Ok(position)
+    }
+
+    fn is_geosynchronous(&self) -> bool {
+        let p = self.samples[0].1;
+        let radius_km = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt() / 1000.0;
+        GEOSYNCHRONOUS_RADIUS_KM.contains(&radius_km)
+    }
+}
+
+/// Parses the date part of an SP3 epoch line (after the leading `*`).
+fn parse_sp3_epoch(rest: &str) -> OverpassPlannerResult<DateTime<Utc>> {
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let invalid = || OverpassPlannerError::ParseError(format!("Invalid SP3 epoch: *{rest}"));
+    if fields.len() < 6 {
+        return Err(invalid());
+    }
+
+    let year: i32 = fields[0].parse().map_err(|_| invalid())?;
+    let month: u32 = fields[1].parse().map_err(|_| invalid())?;
+    let day: u32 = fields[2].parse().map_err(|_| invalid())?;
+    let hour: u32 = fields[3].parse().map_err(|_| invalid())?;
+    let minute: u32 = fields[4].parse().map_err(|_| invalid())?;
+    let second: f64 = fields[5].parse().map_err(|_| invalid())?;
+
+    let date = NaiveDate::from_ymd_opt(year, month, day).ok_or_else(invalid)?;
+    let time = date.and_hms_opt(hour, minute, 0).ok_or_else(invalid)?;
+    Ok(time.and_utc() + Duration::milliseconds((second * 1000.0).round() as i64))
+}
+
+/// Converts a Modified Julian Date plus seconds of day to UTC.
+fn mjd_to_datetime(mjd: f64, seconds_of_day: f64) -> OverpassPlannerResult<DateTime<Utc>> {
+    let mjd_epoch: NaiveDateTime = NaiveDate::from_ymd_opt(1858, 11, 17)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .ok_or_else(|| OverpassPlannerError::ParseError("Invalid MJD epoch".to_string()))?;
+    Ok(mjd_epoch.and_utc()
+        + Duration::days(mjd as i64)
+        + Duration::milliseconds((seconds_of_day * 1000.0).round() as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_from_cpf_interpolates_linear_motion() {
+        let cpf = "H1 CPF  2  SGF 2024 01 01 00  1   1 lageos1
+10 0 60310      0.000000 0  7000000.000 0.000 0.000
+10 0 60310     60.000000 0  7000060.000 120.000 0.000
+10 0 60310    120.000000 0  7000120.000 240.000 0.000
+99";
+        let ephemeris = Ephemeris::from_cpf(cpf).unwrap();
+        assert_eq!(ephemeris.name(), "lageos1");
+
+        let t = Utc.with_ymd_and_hms(2024, 1, 1, 0, 1, 30).unwrap();
+        let p = ephemeris.position_itrf(t).unwrap();
+        assert!((p[0] - 7_000_090.0).abs() < 1e-6);
+        assert!((p[1] - 180.0).abs() < 1e-6);
+
+        let outside = Utc.with_ymd_and_hms(2024, 1, 1, 0, 5, 0).unwrap();
+        assert!(ephemeris.position_itrf(outside).is_err());
+    }
+
+    #[test]
+    fn test_from_sp3_selects_satellite_and_time_system() {
+        let sp3 = "#dP2024  1  1  0  0  0.00000000       2 ORBIT IGS20 FIT  XXX
+%c L  cc GPS ccc cccc cccc cccc cccc ccccc ccccc ccccc ccccc
+*  2024  1  1  0  0  0.00000000
+PL51   7000.000000      0.000000      0.000000 999999.999999
+PL52   8000.000000      0.000000      0.000000 999999.999999
+*  2024  1  1  0  1  0.00000000
+PL51   7000.000000    100.000000      0.000000 999999.999999
+PL52   8000.000000    100.000000      0.000000 999999.999999
+EOF";
+        let first = Ephemeris::from_sp3(sp3, None).unwrap();
+        assert_eq!(first.name(), "L51");
+        // GPS epochs are shifted to UTC
+        let (start, _) = first.time_span();
+        assert_eq!(
+            start,
+            Utc.with_ymd_and_hms(2023, 12, 31, 23, 59, 42).unwrap()
+        );
+
+        let second = Ephemeris::from_sp3(sp3, Some("L52")).unwrap();
+        assert!((second.position_itrf(start).unwrap()[0] - 8_000_000.0).abs() < 1e-6);
+    }
+}
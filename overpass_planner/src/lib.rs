@@ -2,19 +2,45 @@
 //!
 //! A crate for planning satellite overpasses.
 
-use chrono::{DateTime, Duration, FixedOffset, Utc};
+use chrono::{DateTime, Duration, Utc};
 use thiserror::Error;
 
+pub mod clock;
+pub mod coverage;
+pub mod ephemeris;
+pub mod network;
 pub mod planning;
+pub mod precession;
+pub mod propagator;
+pub mod scoring;
+pub mod skyplot;
+pub mod small_body;
+pub mod timezone;
 pub mod tle;
+pub mod transit;
 
-pub use planning::ObserverLocation;
+pub use clock::{reset_clock, set_clock, Clock, FixedClock, SystemClock};
+pub use coverage::{get_constellation_coverage, ConstellationCoverage, CoverageWindow};
+pub use ephemeris::Ephemeris;
+pub use network::{get_network_overpasses, MutualVisibilityWindow, NetworkOverpasses};
 use planning::{
-    calculate_alt_az, find_max_elevation, find_rise_time, find_set_time, is_night_at_location,
-    is_satellite_lit,
+    classify_geosynchronous, find_max_elevation, find_rise_time, find_set_time,
+    is_night_at_location, moon_separation,
+};
+pub use planning::{
+    body_position, ground_track, is_satellite_lit, subsolar_point, sunlit_ground_track_fraction,
+    terminator, BodyPosition, CelestialBody, GeoPoint, ObserverLocation, PropagationContext,
+};
+pub use propagator::Propagator;
+pub use scoring::{get_overpasses_by_score, sort_by_score, ScoreWeights};
+pub use skyplot::{sky_plot, PassMarker, PolarPoint, SkyPlot};
+pub use small_body::{
+    fetch_mpc_elements, parse_mpc_elements, small_body_position, MpcSubset, OrbitalElements,
+    SmallBodyKind, SmallBodyPosition,
 };
 use tle::fetch_tle;
-pub use tle::get_satellite_name;
+pub use tle::{get_satellite_name, TleField};
+pub use transit::{find_transits, Transit};
 
 /// Result type alias for overpass planner operations.
 pub type OverpassPlannerResult<T> = Result<T, OverpassPlannerError>;
@@ -32,6 +58,21 @@ pub enum OverpassPlannerError {
     ParseError(String),
     #[error("InvalidInput: {0}")]
     InvalidInput(String),
+    #[error("Decayed: {0}")]
+    Decayed(String),
+    #[error("InvalidTle: {0}: {1}")]
+    InvalidTle(TleField, String),
+}
+
+/// Kind of visibility window an [`Overpass`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassKind {
+    /// A regular pass: the satellite rises above and sets below the horizon.
+    Normal,
+    /// A geosynchronous satellite that stays above the horizon for the whole search window.
+    AlwaysVisible,
+    /// A geosynchronous satellite that stays below the horizon for the whole search window.
+    NeverVisible,
 }
 
 /// Represents a satellite overpass with timing and elevation information.
@@ -49,6 +90,20 @@ pub struct Overpass {
     pub is_night: bool,
     /// Whether the satellite is illuminated by the sun during the overpass
     pub is_lit: bool,
+    /// Whether this is a regular pass or a geosynchronous visibility window
+    pub kind: PassKind,
+    /// Angular separation between the satellite and the Moon at the midpoint (degrees),
+    /// or `None` if the lunar ephemeris is unavailable
+    pub moon_separation: Option<f64>,
+    /// Whether the satellite was already up at the start of the search window, so
+    /// `start_time` is the window start rather than the real rise time
+    pub truncated_start: bool,
+    /// Whether the satellite was still up at the end of the search window, so
+    /// `end_time` is the window end rather than the real set time
+    pub truncated_end: bool,
+    /// Sampled alt/az path of the satellite from start to end, if requested
+    /// (see [`get_overpasses_with_trajectory`]); empty otherwise
+    pub trajectory: Vec<SatellitePosition>,
 }
 
 /// Represents a satellite position at a specific time.
@@ -62,6 +117,17 @@ pub struct SatellitePosition {
     pub azimuth: f64,
 }
 
+/// Fetch and parse the TLE for a satellite, rejecting decayed satellites.
+pub(crate) async fn load_propagation_context(
+    norad_id: u32,
+    at: DateTime<Utc>,
+) -> OverpassPlannerResult<PropagationContext> {
+    let tle = fetch_tle(norad_id).await?;
+    let ctx = PropagationContext::from_tle(&tle)?;
+    ctx.check_not_decayed(at)?;
+    Ok(ctx)
+}
+
 /// Get all overpasses for a satellite within a specified time window.
 ///
 /// # Arguments
@@ -71,36 +137,266 @@ pub struct SatellitePosition {
 ///
 /// # Returns
 /// A vector of overpasses, each containing start time, end time, max elevation, and midpoint time.
+///
+/// Geosynchronous satellites that stay on one side of the horizon for the whole
+/// window are reported as a single overpass spanning the window, with `kind` set to
+/// [`PassKind::AlwaysVisible`] or [`PassKind::NeverVisible`].
 pub async fn get_overpasses(
     norad_id: u32,
     location: ObserverLocation,
     time_from_now: Duration,
 ) -> OverpassPlannerResult<Vec<Overpass>> {
-    // Fetch TLE data
-    let tle = fetch_tle(norad_id).await?;
+    let start_time = clock::now();
+    let end_time = start_time + time_from_now;
+
+    // Parse once and reuse the SGP4 state for every time step
+    let ctx = load_propagation_context(norad_id, start_time).await?;
+
+    search_overpasses(&ctx, location, start_time, end_time, &|_| {})
+}
+
+/// Get all overpasses for a satellite, reporting search progress.
+///
+/// Behaves like [`get_overpasses`], but calls `progress` with the fraction of the
+/// time window scanned so far (0.0 to 1.0) as the search advances. The callback
+/// runs on the searching thread, once per search step.
+pub async fn get_overpasses_with_progress(
+    norad_id: u32,
+    location: ObserverLocation,
+    time_from_now: Duration,
+    progress: impl Fn(f32),
+) -> OverpassPlannerResult<Vec<Overpass>> {
+    let start_time = clock::now();
+    let end_time = start_time + time_from_now;
 
-    let start_time = Utc::now();
+    // Parse once and reuse the SGP4 state for every time step
+    let ctx = load_propagation_context(norad_id, start_time).await?;
+
+    search_overpasses(&ctx, location, start_time, end_time, &progress)
+}
+
+/// Get all overpasses with a sampled alt/az trajectory attached to each.
+///
+/// # Arguments
+/// * `norad_id` - The NORAD ID of the satellite
+/// * `location` - Observer's location on Earth
+/// * `time_from_now` - Duration from now to search for overpasses
+/// * `sample_interval` - Time between trajectory samples
+///
+/// # Returns
+/// The same overpasses as [`get_overpasses`], with `trajectory` filled in from
+/// `start_time` to `end_time` (both included).
+pub async fn get_overpasses_with_trajectory(
+    norad_id: u32,
+    location: ObserverLocation,
+    time_from_now: Duration,
+    sample_interval: Duration,
+) -> OverpassPlannerResult<Vec<Overpass>> {
+    let start_time = clock::now();
     let end_time = start_time + time_from_now;
 
+    let ctx = load_propagation_context(norad_id, start_time).await?;
+
+    search_overpasses_with_trajectory(&ctx, location, start_time, end_time, sample_interval)
+}
+
+/// Search for overpasses between two instants and sample each one's trajectory
+/// every `sample_interval`.
+pub(crate) fn search_overpasses_with_trajectory(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    sample_interval: Duration,
+) -> OverpassPlannerResult<Vec<Overpass>> {
+    let mut overpasses = search_overpasses(propagator, location, start_time, end_time, &|_| {})?;
+    for overpass in &mut overpasses {
+        overpass.trajectory = propagator.trajectory(
+            location,
+            overpass.start_time,
+            overpass.end_time,
+            sample_interval,
+        )?;
+    }
+    Ok(overpasses)
+}
+
+/// Find overpasses between two instants for any [`Propagator`].
+///
+/// Use this with an [`Ephemeris`] loaded from an SP3 or CPF file, or with a
+/// [`PropagationContext`] built from a TLE obtained elsewhere.
+pub fn find_overpasses(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> OverpassPlannerResult<Vec<Overpass>> {
+    search_overpasses(propagator, location, start_time, end_time, &|_| {})
+}
+
+/// Length of each window scanned by [`find_next_overpass`].
+const NEXT_PASS_SEARCH_CHUNK: Duration = Duration::days(1);
+
+/// Conditions an overpass must meet to be returned by [`find_next_overpass`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PassCriteria {
+    /// Minimum maximum elevation of the pass (degrees)
+    pub min_elevation: f64,
+    /// Only accept passes that occur at night
+    pub require_night: bool,
+    /// Only accept passes where the satellite is sunlit
+    pub require_lit: bool,
+}
+
+impl PassCriteria {
+    /// Whether an overpass meets these criteria.
+    pub fn matches(&self, overpass: &Overpass) -> bool {
+        overpass.kind != PassKind::NeverVisible
+            && overpass.max_elevation >= self.min_elevation
+            && (!self.require_night || overpass.is_night)
+            && (!self.require_lit || overpass.is_lit)
+    }
+}
+
+/// Find the next upcoming overpass that meets the given criteria.
+///
+/// # Arguments
+/// * `norad_id` - The NORAD ID of the satellite
+/// * `location` - Observer's location on Earth
+/// * `criteria` - Conditions the pass must meet
+/// * `max_search` - How far ahead to keep searching before giving up
+///
+/// # Returns
+/// The first matching overpass that rises after now, or `None` if there is none
+/// within `max_search`. A pass already in progress is not considered upcoming.
+pub async fn find_next_overpass(
+    norad_id: u32,
+    location: ObserverLocation,
+    criteria: &PassCriteria,
+    max_search: Duration,
+) -> OverpassPlannerResult<Option<Overpass>> {
+    let start_time = clock::now();
+
+    let ctx = load_propagation_context(norad_id, start_time).await?;
+
+    search_next_overpass(&ctx, location, start_time, criteria, max_search)
+}
+
+/// Scan forward one day at a time for the first overpass meeting `criteria`.
+pub(crate) fn search_next_overpass(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    criteria: &PassCriteria,
+    max_search: Duration,
+) -> OverpassPlannerResult<Option<Overpass>> {
+    let search_end = start_time + max_search;
+    let mut chunk_start = start_time;
+
+    while chunk_start < search_end {
+        let chunk_end = (chunk_start + NEXT_PASS_SEARCH_CHUNK).min(search_end);
+        let mut next_start = chunk_end;
+
+        for overpass in search_overpasses(propagator, location, chunk_start, chunk_end, &|_| {})? {
+            // Geosynchronous windows span the whole chunk, so take them as they are
+            if overpass.kind != PassKind::Normal {
+                if criteria.matches(&overpass) {
+                    return Ok(Some(overpass));
+                }
+                continue;
+            }
+
+            if overpass.truncated_start && chunk_start == start_time {
+                continue;
+            }
+
+            // Clipped by the end of the chunk, so rescan it whole in the next chunk
+            if overpass.truncated_end && chunk_end < search_end {
+                let rescan_from = overpass.start_time - Duration::minutes(1);
+                if rescan_from > chunk_start {
+                    next_start = rescan_from;
+                    break;
+                }
+            }
+
+            if criteria.matches(&overpass) {
+                return Ok(Some(overpass));
+            }
+        }
+
+        chunk_start = next_start;
+    }
+
+    Ok(None)
+}
+
+/// Search for overpasses of an already-parsed satellite between two instants.
+///
+/// `progress` receives the fraction of the window scanned so far.
+pub(crate) fn search_overpasses(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    progress: &dyn Fn(f32),
+) -> OverpassPlannerResult<Vec<Overpass>> {
+    let window_seconds = (end_time - start_time).num_seconds().max(1) as f32;
+    let report_progress = |time: DateTime<Utc>| {
+        let fraction = (time - start_time).num_seconds() as f32 / window_seconds;
+        progress(fraction.clamp(0.0, 1.0));
+    };
+
+    // Geosynchronous satellites barely move in the sky, so skip the minute-by-minute scan
+    if propagator.is_geosynchronous() {
+        if let Some((kind, max_elevation)) =
+            classify_geosynchronous(propagator, location, start_time, end_time)?
+        {
+            let midpoint_time = start_time + (end_time - start_time) / 2;
+            let is_night = is_night_at_location(location, start_time)?
+                || is_night_at_location(location, midpoint_time)?
+                || is_night_at_location(location, end_time)?;
+            let is_lit = is_satellite_lit(propagator, midpoint_time)?;
+            let moon_separation = moon_separation(propagator, location, midpoint_time);
+
+            report_progress(end_time);
+            return Ok(vec![Overpass {
+                start_time,
+                end_time,
+                max_elevation,
+                midpoint_time,
+                is_night,
+                is_lit,
+                kind,
+                moon_separation,
+                truncated_start: true,
+                truncated_end: true,
+                trajectory: Vec::new(),
+            }]);
+        }
+    }
+
     // Search step: 1 minute intervals for initial detection
     let search_step = Duration::minutes(1);
     // Refinement step: 1 second for finding exact rise/set times
     let refine_step = Duration::seconds(1);
 
     let mut overpasses = Vec::new();
-    let mut current_overpass: Option<(DateTime<Utc>, f64)> = None; // (start_time, max_elevation)
+    // (start_time, max_elevation, truncated_start)
+    let mut current_overpass: Option<(DateTime<Utc>, f64, bool)> = None;
 
     // Initial check at start time
-    let (altitude, _) = calculate_alt_az(&tle, location, start_time)?;
+    let (altitude, _) = propagator.alt_az(location, start_time)?;
     let mut was_above_horizon = altitude > 0.0;
     if was_above_horizon {
-        current_overpass = Some((start_time, altitude));
+        // Already in progress, so the real rise time is before the window
+        current_overpass = Some((start_time, altitude, true));
     }
 
     // Search through the time window
     let mut current_time = start_time + search_step;
     while current_time <= end_time {
-        let (altitude, _) = match calculate_alt_az(&tle, location, current_time) {
+        report_progress(current_time);
+        let (altitude, _) = match propagator.alt_az(location, current_time) {
             Ok(result) => result,
             Err(_) => {
                 // If calculation fails, skip this time point
@@ -114,18 +410,18 @@ pub async fn get_overpasses(
         if is_above_horizon && !was_above_horizon {
             // Satellite rising above horizon - start of overpass
             let rise_time = find_rise_time(
-                &tle,
+                propagator,
                 location,
                 current_time - search_step,
                 current_time,
                 refine_step,
             )?;
-            current_overpass = Some((rise_time, altitude));
+            current_overpass = Some((rise_time, altitude, false));
         } else if !is_above_horizon && was_above_horizon {
             // Satellite setting below horizon - end of overpass
-            if let Some((start, _)) = current_overpass.take() {
+            if let Some((start, _, truncated_start)) = current_overpass.take() {
                 let set_time = find_set_time(
-                    &tle,
+                    propagator,
                     location,
                     current_time - search_step,
                     current_time,
@@ -134,7 +430,7 @@ pub async fn get_overpasses(
 
                 // Find maximum elevation during this overpass
                 let max_elevation =
-                    find_max_elevation(&tle, location, start, set_time, refine_step)?;
+                    find_max_elevation(propagator, location, start, set_time, refine_step)?;
 
                 let midpoint_time = start + (set_time - start) / 2;
 
@@ -147,7 +443,8 @@ pub async fn get_overpasses(
                 let is_night = is_night_start || is_night_mid || is_night_end;
 
                 // For satellite illumination, check at midpoint (most representative)
-                let is_lit = is_satellite_lit(&tle, midpoint_time)?;
+                let is_lit = is_satellite_lit(propagator, midpoint_time)?;
+                let moon_separation = moon_separation(propagator, location, midpoint_time);
 
                 overpasses.push(Overpass {
                     start_time: start,
@@ -156,12 +453,17 @@ pub async fn get_overpasses(
                     midpoint_time,
                     is_night,
                     is_lit,
+                    kind: PassKind::Normal,
+                    moon_separation,
+                    truncated_start,
+                    truncated_end: false,
+                    trajectory: Vec::new(),
                 });
             }
         }
 
         // Update max elevation if we're in an overpass
-        if let Some((_, ref mut max_elev)) = current_overpass {
+        if let Some((_, ref mut max_elev, _)) = current_overpass {
             if altitude > *max_elev {
                 *max_elev = altitude;
             }
@@ -171,45 +473,61 @@ pub async fn get_overpasses(
         current_time += search_step;
     }
 
-    // Handle overpass that extends beyond end_time
-    if let Some((start, max_elev)) = current_overpass {
-        // Find when it sets (might be after end_time, but we'll use end_time as limit)
-        let set_time = find_set_time(
-            &tle,
-            location,
-            end_time - search_step,
-            end_time,
-            refine_step,
-        )
-        .unwrap_or(end_time);
+    // Handle overpass still in progress at the last search step
+    if let Some((start, max_elev, truncated_start)) = current_overpass {
+        // If the satellite is still up at end_time the pass is clipped by the window,
+        // otherwise it set between the last search step and end_time
+        let still_up = propagator
+            .alt_az(location, end_time)
+            .map(|(altitude, _)| altitude > 0.0)
+            .unwrap_or(true);
+        let (set_time, truncated_end) = if still_up {
+            (end_time, true)
+        } else {
+            let set_time = find_set_time(
+                propagator,
+                location,
+                end_time - search_step,
+                end_time,
+                refine_step,
+            )
+            .unwrap_or(end_time);
+            (set_time.min(end_time), false)
+        };
 
-        let max_elevation =
-            find_max_elevation(&tle, location, start, set_time.min(end_time), refine_step)
-                .unwrap_or(max_elev);
+        let max_elevation = find_max_elevation(propagator, location, start, set_time, refine_step)
+            .unwrap_or(max_elev);
 
-        let midpoint_time = start + (set_time.min(end_time) - start) / 2;
+        let midpoint_time = start + (set_time - start) / 2;
 
         // Calculate if overpass occurs at night and if satellite is lit
         // Check multiple points: start, midpoint, and end to catch transitions
         let is_night_start = is_night_at_location(location, start)?;
         let is_night_mid = is_night_at_location(location, midpoint_time)?;
-        let is_night_end = is_night_at_location(location, set_time.min(end_time))?;
+        let is_night_end = is_night_at_location(location, set_time)?;
         // Consider it night if any part of the overpass is at night
         let is_night = is_night_start || is_night_mid || is_night_end;
 
         // For satellite illumination, check at midpoint (most representative)
-        let is_lit = is_satellite_lit(&tle, midpoint_time)?;
+        let is_lit = is_satellite_lit(propagator, midpoint_time)?;
+        let moon_separation = moon_separation(propagator, location, midpoint_time);
 
         overpasses.push(Overpass {
             start_time: start,
-            end_time: set_time.min(end_time),
+            end_time: set_time,
             max_elevation,
             midpoint_time,
             is_night,
             is_lit,
+            kind: PassKind::Normal,
+            moon_separation,
+            truncated_start,
+            truncated_end,
+            trajectory: Vec::new(),
         });
     }
 
+    report_progress(end_time);
     Ok(overpasses)
 }
 
@@ -234,76 +552,306 @@ pub fn get_satellite_positions(
     Ok(vec![])
 }
 
+/// Held by tests that swap the process-wide clock or TLE source, so tests
+/// running in parallel don't swap them under each other.
+#[cfg(test)]
+pub(crate) static GLOBALS_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tle::{set_tle_source, TleSource};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    /// ISS elements from 2008-09-20
+    const ISS_TLE: &str = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+
+    /// Washington DC
+    const LOCATION: ObserverLocation = ObserverLocation {
+        latitude: 38.8892,
+        longitude: -77.1664,
+        altitude: 0.0,
+    };
+
+    /// Stand-in satellite that rises every `period` from `first_rise`, stays up
+    /// for `up` and peaks at `peak` degrees halfway through, without needing
+    /// SGP4 or satkit's data files.
+    struct PeriodicPasses {
+        first_rise: DateTime<Utc>,
+        period: Duration,
+        up: Duration,
+        peak: f64,
+    }
+
+    impl Propagator for PeriodicPasses {
+        fn position_itrf(&self, _timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+            Ok([7.0e6, 0.0, 0.0])
+        }
+
+        fn position_inertial(&self, _timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+            Ok([7000.0, 0.0, 0.0])
+        }
+
+        fn alt_az(
+            &self,
+            _location: ObserverLocation,
+            timestamp: DateTime<Utc>,
+        ) -> OverpassPlannerResult<(f64, f64)> {
+            let since_rise = (timestamp - self.first_rise)
+                .num_milliseconds()
+                .rem_euclid(self.period.num_milliseconds());
+            let phase = since_rise as f64 / self.up.num_milliseconds() as f64;
+            let altitude = if phase < 1.0 {
+                self.peak * (std::f64::consts::PI * phase).sin()
+            } else {
+                -10.0
+            };
+            Ok((altitude, 180.0))
+        }
+    }
+
+    /// A 10 minute, 45° pass every 90 minutes from 2024-03-01 12:00
+    fn leo_passes() -> PeriodicPasses {
+        PeriodicPasses {
+            first_rise: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap(),
+            period: Duration::minutes(90),
+            up: Duration::minutes(10),
+            peak: 45.0,
+        }
+    }
+
+    #[test]
+    fn test_pass_criteria_matches() {
+        let start_time = Utc::now();
+        let mut overpass = Overpass {
+            start_time,
+            end_time: start_time + Duration::minutes(6),
+            max_elevation: 45.0,
+            midpoint_time: start_time + Duration::minutes(3),
+            is_night: false,
+            is_lit: true,
+            kind: PassKind::Normal,
+            moon_separation: None,
+            truncated_start: false,
+            truncated_end: false,
+            trajectory: Vec::new(),
+        };
+
+        assert!(PassCriteria::default().matches(&overpass));
+        let criteria = PassCriteria {
+            min_elevation: 30.0,
+            require_night: true,
+            require_lit: false,
+        };
+        assert!(!criteria.matches(&overpass));
+        overpass.is_night = true;
+        assert!(criteria.matches(&overpass));
+        overpass.max_elevation = 20.0;
+        assert!(!criteria.matches(&overpass));
+    }
 
-    #[tokio::test]
-    async fn test_iss_overpasses_washington_dc() {
-        // Washington DC location: 38.8892°N, 77.1664°W
+    #[test]
+    fn test_iss_overpasses_washington_dc() {
+        // get_overpasses reads the process-wide clock and TLE source, so pin
+        // both for the length of the search and put them back afterwards
+        let _globals = GLOBALS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let start = Utc.with_ymd_and_hms(2008, 9, 20, 12, 0, 0).unwrap();
+        set_clock(Arc::new(FixedClock::new(start)));
+        set_tle_source(TleSource::Local(Arc::new(HashMap::from([(
+            25544,
+            ISS_TLE.to_owned(),
+        )]))));
+
+        // Washington DC
         let location = ObserverLocation {
             latitude: 38.8892,
             longitude: -77.1664,
-            altitude: 0.0, // Sea level
+            altitude: 0.0,
         };
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(get_overpasses(25544, location, Duration::hours(24)));
+        reset_clock();
+        set_tle_source(TleSource::celestrak().unwrap());
+        let overpasses = result.unwrap();
+
+        // (rise, set, max elevation), UTC
+        let expected = [
+            ((20, 22, 48, 49), (20, 22, 57, 13), 12.8),
+            ((21, 0, 23, 9), (21, 0, 32, 56), 48.5),
+            ((21, 1, 59, 51), (21, 2, 8, 4), 10.9),
+            ((21, 3, 36, 47), (21, 3, 44, 8), 7.2),
+            ((21, 5, 12, 23), (21, 5, 21, 13), 15.6),
+            ((21, 6, 47, 32), (21, 6, 57, 19), 69.3),
+            ((21, 8, 24, 8), (21, 8, 30, 13), 4.6),
+        ];
+        assert_eq!(overpasses.len(), expected.len());
+        let at = |(day, hour, minute, second)| {
+            Utc.with_ymd_and_hms(2008, 9, day, hour, minute, second)
+                .unwrap()
+        };
+        for (overpass, &(rise, set, max_elevation)) in overpasses.iter().zip(&expected) {
+            // a few seconds for the Earth orientation data the frames use
+            assert!((overpass.start_time - at(rise)).num_seconds().abs() <= 3);
+            assert!((overpass.end_time - at(set)).num_seconds().abs() <= 3);
+            assert!((overpass.max_elevation - max_elevation).abs() < 0.5);
+            assert_eq!(overpass.kind, PassKind::Normal);
+            assert!(!overpass.truncated_start && !overpass.truncated_end);
+        }
+    }
 
-        // ISS NORAD ID
-        let iss_norad_id = 25544;
+    #[test]
+    fn test_search_progress_is_monotonic() {
+        let satellite = leo_passes();
+        let start_time = satellite.first_rise - Duration::minutes(30);
+        let fractions = std::cell::RefCell::new(Vec::new());
+        let overpasses = search_overpasses(
+            &satellite,
+            LOCATION,
+            start_time,
+            start_time + Duration::hours(6),
+            &|fraction| fractions.borrow_mut().push(fraction),
+        )
+        .unwrap();
+        assert_eq!(overpasses.len(), 4);
+
+        let fractions = fractions.into_inner();
+        assert!(!fractions.is_empty());
+        assert!(fractions.iter().all(|f| (0.0..=1.0).contains(f)));
+        assert!(fractions.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(fractions.last(), Some(&1.0));
+    }
 
-        // Search for next 24 hours
-        let time_window = Duration::hours(24);
+    #[test]
+    fn test_pass_truncated_at_window_start() {
+        let satellite = leo_passes();
+        // starts four minutes into the first pass
+        let start_time = satellite.first_rise + Duration::minutes(4);
+        let overpasses = find_overpasses(
+            &satellite,
+            LOCATION,
+            start_time,
+            start_time + Duration::hours(1),
+        )
+        .unwrap();
+
+        assert_eq!(overpasses.len(), 1);
+        let pass = &overpasses[0];
+        assert!(pass.truncated_start && !pass.truncated_end);
+        assert_eq!(pass.start_time, start_time);
+        let set_time = satellite.first_rise + satellite.up;
+        assert!((pass.end_time - set_time).num_seconds().abs() <= 1);
+    }
 
-        println!("Fetching ISS overpasses for Washington DC (38.8892°N, 77.1664°W)...");
-        println!("Searching for next 24 hours...\n");
+    #[test]
+    fn test_pass_truncated_at_window_end() {
+        let satellite = leo_passes();
+        // ends four minutes into the first pass
+        let end_time = satellite.first_rise + Duration::minutes(4);
+        let overpasses = find_overpasses(
+            &satellite,
+            LOCATION,
+            end_time - Duration::hours(1),
+            end_time,
+        )
+        .unwrap();
 
-        // Fetch and print the TLE being used
-        match fetch_tle(iss_norad_id).await {
-            Ok(tle) => {
-                println!("Using TLE:\n{}", tle);
-                println!();
-            }
-            Err(e) => {
-                println!("Warning: Failed to fetch TLE: {}", e);
-                println!();
-            }
+        assert_eq!(overpasses.len(), 1);
+        let pass = &overpasses[0];
+        assert!(!pass.truncated_start && pass.truncated_end);
+        assert_eq!(pass.end_time, end_time);
+        assert!((pass.start_time - satellite.first_rise).num_seconds().abs() <= 1);
+    }
+
+    #[test]
+    fn test_trajectory_samples() {
+        let satellite = leo_passes();
+        let start_time = satellite.first_rise - Duration::minutes(30);
+        let interval = Duration::seconds(30);
+        let overpasses = search_overpasses_with_trajectory(
+            &satellite,
+            LOCATION,
+            start_time,
+            start_time + Duration::hours(3),
+            interval,
+        )
+        .unwrap();
+        assert_eq!(overpasses.len(), 2);
+
+        for pass in &overpasses {
+            let trajectory = &pass.trajectory;
+            let (first, last) = (&trajectory[0], &trajectory[trajectory.len() - 1]);
+            // from rise to set, so on the horizon at both ends
+            assert_eq!(first.timestamp, pass.start_time);
+            assert_eq!(last.timestamp, pass.end_time);
+            assert!(first.altitude.abs() < 0.5 && last.altitude.abs() < 0.5);
+
+            // evenly spaced, bar the last step up to the set time
+            let steps: Vec<Duration> = trajectory
+                .windows(2)
+                .map(|pair| pair[1].timestamp - pair[0].timestamp)
+                .collect();
+            assert!(steps.iter().all(|&step| step > Duration::zero()));
+            assert!(steps[..steps.len() - 1]
+                .iter()
+                .all(|&step| step == interval));
+            assert!(steps[steps.len() - 1] <= interval);
         }
+    }
 
-        match get_overpasses(iss_norad_id, location, time_window).await {
-            Ok(overpasses) => {
-                println!("Found {} overpass(es):\n", overpasses.len());
-
-                // EST is UTC-5
-                let est_offset = FixedOffset::east_opt(-5 * 3600).unwrap();
-
-                for (i, overpass) in overpasses.iter().enumerate() {
-                    let start_est = overpass.start_time.with_timezone(&est_offset);
-                    let end_est = overpass.end_time.with_timezone(&est_offset);
-                    let midpoint_est = overpass.midpoint_time.with_timezone(&est_offset);
-
-                    println!("Overpass #{}:", i + 1);
-                    println!("  Start:    {} / {} EST", overpass.start_time, start_est);
-                    println!("  End:      {} / {} EST", overpass.end_time, end_est);
-                    println!(
-                        "  Duration: {:.1} minutes",
-                        (overpass.end_time - overpass.start_time).num_seconds() as f64 / 60.0
-                    );
-                    println!("  Max Elevation: {:.2}°", overpass.max_elevation);
-                    println!(
-                        "  Midpoint: {} / {} EST",
-                        overpass.midpoint_time, midpoint_est
-                    );
-                    println!();
-                }
+    #[test]
+    fn test_next_pass_straddling_chunk_boundary() {
+        let start_time = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        // one pass, rising three minutes before the first day's chunk ends
+        let satellite = PeriodicPasses {
+            first_rise: start_time + NEXT_PASS_SEARCH_CHUNK - Duration::minutes(3),
+            period: Duration::days(30),
+            up: Duration::minutes(10),
+            peak: 45.0,
+        };
 
-                // Assert that we found at least some overpasses (ISS typically has multiple per day)
-                assert!(
-                    !overpasses.is_empty(),
-                    "Should find at least one overpass in 24 hours"
-                );
-            }
-            Err(e) => {
-                panic!("Failed to get overpasses: {}", e);
-            }
-        }
+        let pass = search_next_overpass(
+            &satellite,
+            LOCATION,
+            start_time,
+            &PassCriteria::default(),
+            Duration::days(3),
+        )
+        .unwrap()
+        .expect("the pass rises within the search");
+
+        // found whole, not cut at the chunk boundary
+        assert!(!pass.truncated_start && !pass.truncated_end);
+        assert!((pass.start_time - satellite.first_rise).num_seconds().abs() <= 1);
+        let set_time = satellite.first_rise + satellite.up;
+        assert!((pass.end_time - set_time).num_seconds().abs() <= 1);
+        assert!((pass.max_elevation - satellite.peak).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_no_next_pass_within_search() {
+        let start_time = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+        let satellite = PeriodicPasses {
+            first_rise: start_time + Duration::days(5),
+            period: Duration::days(30),
+            up: Duration::minutes(10),
+            peak: 45.0,
+        };
+
+        let next = search_next_overpass(
+            &satellite,
+            LOCATION,
+            start_time,
+            &PassCriteria::default(),
+            Duration::days(3),
+        )
+        .unwrap();
+        assert!(next.is_none());
     }
 }
@@ -0,0 +1,184 @@
+//! Ground station network planning module.
+//!
+//! This module computes overpasses for several observer locations in one call
+//! and finds the windows during which a satellite is visible from multiple
+//! sites at the same time.
+
+use crate::planning::ObserverLocation;
+use crate::{
+    clock, load_propagation_context, search_overpasses, Overpass, OverpassPlannerError,
+    OverpassPlannerResult, PassKind,
+};
+use chrono::{DateTime, Duration, Utc};
+
+/// A window during which a satellite is simultaneously visible from several sites.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MutualVisibilityWindow {
+    /// Start time of the window
+    pub start_time: DateTime<Utc>,
+    /// End time of the window
+    pub end_time: DateTime<Utc>,
+    /// Indices (into the observer list) of the sites that can see the satellite
+    pub sites: Vec<usize>,
+}
+
+/// Overpasses for a network of observer locations.
+#[derive(Debug, Clone)]
+pub struct NetworkOverpasses {
+    /// Overpasses for each site, in the same order as the observer list
+    pub per_site: Vec<Vec<Overpass>>,
+    /// Windows during which at least `min_sites` sites see the satellite at once
+    pub mutual_windows: Vec<MutualVisibilityWindow>,
+}
+
+/// Get overpasses for every site in a ground station network.
+///
+/// # Arguments
+/// * `norad_id` - The NORAD ID of the satellite
+/// * `locations` - Observer locations of the network sites
+/// * `time_from_now` - Duration from now to search for overpasses
+/// * `min_sites` - Minimum number of sites that must see the satellite for a mutual window
+///
+/// # Returns
+/// The per-site overpasses and the mutual visibility windows.
+pub async fn get_network_overpasses(
+    norad_id: u32,
+    locations: &[ObserverLocation],
+    time_from_now: Duration,
+    min_sites: usize,
+) -> OverpassPlannerResult<NetworkOverpasses> {
+    if locations.is_empty() {
+        return Err(OverpassPlannerError::InvalidInput(
+            "At least one observer location is required".to_string(),
+        ));
+    }
+    if min_sites == 0 {
+        return Err(OverpassPlannerError::InvalidInput(
+            "min_sites must be at least 1".to_string(),
+        ));
+    }
+
+    // Use a common search window so the per-site results line up
+    let start_time = clock::now();
+    let end_time = start_time + time_from_now;
+
+    let ctx = load_propagation_context(norad_id, start_time).await?;
+
+    let per_site = locations
+        .iter()
+        .map(|location| search_overpasses(&ctx, *location, start_time, end_time, &|_| {}))
+        .collect::<OverpassPlannerResult<Vec<_>>>()?;
+
+    let mutual_windows = find_mutual_windows(&per_site, min_sites);
+
+    Ok(NetworkOverpasses {
+        per_site,
+        mutual_windows,
+    })
+}
+
+/// Find the windows during which at least `min_sites` sites see the satellite.
+///
+/// A new window starts whenever the set of visible sites changes, so every window
+/// has a constant site list.
+pub fn find_mutual_windows(
+    per_site: &[Vec<Overpass>],
+    min_sites: usize,
+) -> Vec<MutualVisibilityWindow> {
+    // Collect all interval boundaries across every site
+    let mut boundaries: Vec<DateTime<Utc>> = per_site
+        .iter()
+        .flatten()
+        .filter(|o| o.kind != PassKind::NeverVisible)
+        .flat_map(|o| [o.start_time, o.end_time])
+        .collect();
+    boundaries.sort();
+    boundaries.dedup();
+
+    let mut windows: Vec<MutualVisibilityWindow> = Vec::new();
+    for pair in boundaries.windows(2) {
+        let (start, end) = (pair[0], pair[1]);
+
+        let sites: Vec<usize> = per_site
+            .iter()
+            .enumerate()
+            .filter(|(_, overpasses)| {
+                overpasses.iter().any(|o| {
+                    o.kind != PassKind::NeverVisible && o.start_time <= start && o.end_time >= end
+                })
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        if sites.len() < min_sites {
+            continue;
+        }
+
+        // Extend the previous window if it is contiguous and has the same sites
+        if let Some(last) = windows.last_mut() {
+            if last.end_time == start && last.sites == sites {
+                last.end_time = end;
+                continue;
+            }
+        }
+
+        windows.push(MutualVisibilityWindow {
+            start_time: start,
+            end_time: end,
+            sites,
+        });
+    }
+
+    windows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn pass(start_min: i64, end_min: i64) -> Overpass {
+        let base = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let start_time = base + Duration::minutes(start_min);
+        let end_time = base + Duration::minutes(end_min);
+        Overpass {
+            start_time,
+            end_time,
+            max_elevation: 45.0,
+            midpoint_time: start_time + (end_time - start_time) / 2,
+            is_night: true,
+            is_lit: true,
+            kind: PassKind::Normal,
+            moon_separation: None,
+            truncated_start: false,
+            truncated_end: false,
+            trajectory: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_find_mutual_windows() {
+        let base = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let per_site = vec![
+            vec![pass(0, 10), pass(100, 110)],
+            vec![pass(5, 15)],
+            vec![pass(8, 20)],
+        ];
+
+        let windows = find_mutual_windows(&per_site, 2);
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].start_time, base + Duration::minutes(5));
+        assert_eq!(windows[0].end_time, base + Duration::minutes(8));
+        assert_eq!(windows[0].sites, vec![0, 1]);
+        assert_eq!(windows[1].sites, vec![0, 1, 2]);
+        assert_eq!(windows[1].end_time, base + Duration::minutes(10));
+        assert_eq!(windows[2].start_time, base + Duration::minutes(10));
+        assert_eq!(windows[2].end_time, base + Duration::minutes(15));
+        assert_eq!(windows[2].sites, vec![1, 2]);
+
+        // Nothing is seen by all three sites outside 8-10 minutes
+        let all = find_mutual_windows(&per_site, 3);
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].start_time, base + Duration::minutes(8));
+    }
+}
@@ -3,10 +3,12 @@
 //! This module provides functionality to calculate satellite positions
 //! and plan overpasses using SGP4 propagation.
 
-use crate::{OverpassPlannerError, OverpassPlannerResult};
+use crate::propagator::Propagator;
+use crate::{OverpassPlannerError, OverpassPlannerResult, PassKind};
 use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
 use satkit::{frametransform, types::Vec3, ITRFCoord, Instant};
 use sgp4::{Elements, Prediction};
+use std::fmt;
 
 /// Observer location on Earth.
 #[derive(Debug, Clone, Copy)]
@@ -19,8 +21,178 @@ pub struct ObserverLocation {
     pub altitude: f64,
 }
 
+/// Mean motion of a geosynchronous orbit (revolutions per day).
+const GEOSYNCHRONOUS_MEAN_MOTION: f64 = 1.0027;
+/// Allowed deviation from the geosynchronous mean motion (revolutions per day).
+const GEOSYNCHRONOUS_TOLERANCE: f64 = 0.01;
+
+/// Mean motion above which the perigee is low enough that reentry is imminent (rev/day).
+const REENTRY_MEAN_MOTION: f64 = 16.4;
+/// Mean motion above which an orbit is treated as low Earth orbit (rev/day).
+const LEO_MEAN_MOTION: f64 = 11.25;
+/// Age after which a LEO element set is too stale to trust (days).
+const MAX_LEO_TLE_AGE_DAYS: i64 = 30;
+/// B* drag term above which the orbit decays within days (1/earth radii).
+const HIGH_DRAG_TERM: f64 = 0.01;
+/// Age after which a high-drag element set is too stale to trust (days).
+const MAX_HIGH_DRAG_TLE_AGE_DAYS: i64 = 7;
+
+/// Parsed SGP4 state for a single TLE.
+///
+/// Parsing a TLE and building the SGP4 constants is far more expensive than
+/// propagating, so the context is created once per TLE and reused for every
+/// time step of a pass search.
+pub struct PropagationContext {
+    elements: Elements,
+    constants: sgp4::Constants<'static>,
+    epoch: DateTime<Utc>,
+}
+
+// sgp4's constants don't implement Debug
+impl fmt::Debug for PropagationContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PropagationContext")
+            .field("norad_id", &self.elements.norad_id)
+            .field("epoch", &self.epoch)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PropagationContext {
+    /// Parses a TLE string (containing name, line 1, and line 2) and prepares
+    /// it for propagation.
+    ///
+    /// # Errors
+    /// Returns `OverpassPlannerError` if the TLE lines are missing or cannot be
+    /// parsed, or if the SGP4 constants cannot be derived from the elements.
+    pub fn from_tle(tle: &str) -> OverpassPlannerResult<Self> {
+        // Parse TLE string into lines
+        let lines: Vec<&str> = tle
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        if lines.len() < 3 {
+            return Err(OverpassPlannerError::ParseError(
+                "TLE must contain at least 3 lines (name, line1, line2)".to_string(),
+            ));
+        }
+
+        // Find TLE lines (they start with "1 " and "2 ")
+        let mut line1 = None;
+        let mut line2 = None;
+
+        for line in &lines {
+            if line.starts_with("1 ") {
+                line1 = Some(*line);
+            } else if line.starts_with("2 ") {
+                line2 = Some(*line);
+            }
+        }
+
+        let line1 = line1
+            .ok_or_else(|| OverpassPlannerError::ParseError("TLE line 1 not found".to_string()))?;
+
+        let line2 = line2
+            .ok_or_else(|| OverpassPlannerError::ParseError("TLE line 2 not found".to_string()))?;
+
+        // Parse TLE using sgp4
+        let elements = Elements::from_tle(None, line1.as_bytes(), line2.as_bytes())
+            .map_err(|e| OverpassPlannerError::TLEError(format!("Failed to parse TLE: {e}")))?;
+
+        // Create constants for propagation
+        let constants = sgp4::Constants::from_elements(&elements).map_err(|e| {
+            OverpassPlannerError::CalculationError(format!("Failed to create constants: {e}"))
+        })?;
+
+        let epoch = elements.datetime.and_utc();
+
+        Ok(Self {
+            elements,
+            constants,
+            epoch,
+        })
+    }
+
+    /// The parsed orbital elements.
+    pub fn elements(&self) -> &Elements {
+        &self.elements
+    }
+
+    /// Checks whether the TLE looks like it belongs to a decayed or reentering
+    /// satellite when used at `at`.
+    ///
+    /// # Errors
+    /// Returns `OverpassPlannerError::Decayed` if the mean motion indicates
+    /// imminent reentry, or if a low or high-drag orbit's TLE is too old to
+    /// describe a satellite that is still in orbit.
+    pub fn check_not_decayed(&self, at: DateTime<Utc>) -> OverpassPlannerResult<()> {
+        let age_days = (at - self.epoch).num_days();
+        let mean_motion = self.elements.mean_motion;
+
+        if mean_motion >= REENTRY_MEAN_MOTION {
+            return Err(OverpassPlannerError::Decayed(format!(
+                "Mean motion of {mean_motion:.2} rev/day indicates the satellite is reentering"
+            )));
+        }
+        if mean_motion >= LEO_MEAN_MOTION && age_days > MAX_LEO_TLE_AGE_DAYS {
+            return Err(OverpassPlannerError::Decayed(format!(
+                "TLE epoch is {age_days} days old; the satellite has likely decayed"
+            )));
+        }
+        if self.elements.drag_term >= HIGH_DRAG_TERM && age_days > MAX_HIGH_DRAG_TLE_AGE_DAYS {
+            return Err(OverpassPlannerError::Decayed(format!(
+                "TLE epoch is {age_days} days old with a very high drag term ({:.5}); the satellite has likely decayed",
+                self.elements.drag_term
+            )));
+        }
+        Ok(())
+    }
+
+    /// The TLE epoch in UTC.
+    pub fn epoch(&self) -> DateTime<Utc> {
+        self.epoch
+    }
+
+    /// Propagates the satellite to `timestamp`, returning its TEME state.
+    pub fn propagate(&self, timestamp: DateTime<Utc>) -> OverpassPlannerResult<Prediction> {
+        // Calculate minutes since TLE epoch (with millisecond precision)
+        let duration = timestamp.signed_duration_since(self.epoch);
+        let minutes_since_epoch = duration.num_milliseconds() as f64 / 60_000.0;
+
+        self.constants
+            .propagate(minutes_since_epoch)
+            .map_err(|e| OverpassPlannerError::CalculationError(format!("Propagation failed: {e}")))
+    }
+}
+
+impl Propagator for PropagationContext {
+    /// Whether the orbit is geosynchronous, judged from its mean motion.
+    ///
+    /// This covers both geostationary and inclined geosynchronous orbits, whose
+    /// position in the sky drifts slowly rather than rising and setting.
+    fn is_geosynchronous(&self) -> bool {
+        (self.elements.mean_motion - GEOSYNCHRONOUS_MEAN_MOTION).abs() < GEOSYNCHRONOUS_TOLERANCE
+    }
+
+    fn position_itrf(&self, timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+        let prediction = self.propagate(timestamp)?;
+        teme_to_itrf(prediction.position, timestamp)
+    }
+
+    fn position_inertial(&self, timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+        // SGP4 already works in the (inertial) TEME frame
+        Ok(self.propagate(timestamp)?.position)
+    }
+}
+
 /// Calculates the altitude and azimuth of a satellite at a given time.
 ///
+/// This parses the TLE on every call; when evaluating many time steps for the
+/// same satellite, build a [`PropagationContext`] once and use
+/// [`Propagator::alt_az`] instead.
+///
 /// # Arguments
 /// * `tle` - The TLE string (containing name, line 1, and line 2)
 /// * `location` - Observer's location on Earth
@@ -41,131 +213,109 @@ pub fn calculate_alt_az(
     location: ObserverLocation,
     timestamp: DateTime<Utc>,
 ) -> OverpassPlannerResult<(f64, f64)> {
-    // Parse TLE string into lines
-    let lines: Vec<&str> = tle
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty())
-        .collect();
-
-    if lines.len() < 3 {
-        return Err(OverpassPlannerError::ParseError(
-            "TLE must contain at least 3 lines (name, line1, line2)".to_string(),
-        ));
-    }
-
-    // Find TLE lines (they start with "1 " and "2 ")
-    let mut line1 = None;
-    let mut line2 = None;
-
-    for line in &lines {
-        if line.starts_with("1 ") {
-            line1 = Some(*line);
-        } else if line.starts_with("2 ") {
-            line2 = Some(*line);
-        }
-    }
-
-    let line1 = line1
-        .ok_or_else(|| OverpassPlannerError::ParseError("TLE line 1 not found".to_string()))?;
-
-    let line2 = line2
-        .ok_or_else(|| OverpassPlannerError::ParseError("TLE line 2 not found".to_string()))?;
-
-    // Parse TLE using sgp4
-    let elements = Elements::from_tle(None, line1.as_bytes(), line2.as_bytes())
-        .map_err(|e| OverpassPlannerError::TLEError(format!("Failed to parse TLE: {e}")))?;
+    PropagationContext::from_tle(tle)?.alt_az(location, timestamp)
+}
 
-    // Create constants for propagation
-    let constants = sgp4::Constants::from_elements(&elements).map_err(|e| {
-        OverpassPlannerError::CalculationError(format!("Failed to create constants: {e}"))
-    })?;
+/// Converts a satellite position from the TEME (True Equator Mean Equinox) inertial
+/// frame in km, as returned by SGP4, to the ITRF (Earth-fixed) frame in meters.
+fn teme_to_itrf(sat_pos: [f64; 3], timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+    let instant = to_instant(timestamp);
 
-    // Calculate minutes since TLE epoch (with fractional precision)
-    let tle_epoch = elements.datetime.and_utc();
-    let duration = timestamp.signed_duration_since(tle_epoch);
-    let minutes_since_epoch = duration.num_seconds() as f64 / 60.0;
+    // SGP4 returns positions in km, convert to meters for satkit
+    // Create position vector in TEME frame (in meters)
+    let pos_teme_m = Vec3::new(
+        sat_pos[0] * 1000.0,
+        sat_pos[1] * 1000.0,
+        sat_pos[2] * 1000.0,
+    );
 
-    // Propagate satellite position
-    let prediction = constants
-        .propagate(minutes_since_epoch)
-        .map_err(|e| OverpassPlannerError::CalculationError(format!("Propagation failed: {e}")))?;
+    // Convert TEME to ITRF using satkit's frame transformation
+    // This handles all the Earth rotation automatically
+    require_eop(&instant)?;
+    let q_teme2itrf = frametransform::qteme2itrf(&instant);
 
-    // Convert satellite position (in ECI/TEME frame) to alt/az
-    let (altitude, azimuth) = eci_to_alt_az(prediction, location, timestamp)?;
+    // Apply quaternion rotation to convert TEME to ITRF
+    // Use to_rotation_matrix() to ensure proper matrix multiplication
+    let rot_matrix = q_teme2itrf.to_rotation_matrix();
+    let pos_itrf_m = rot_matrix * pos_teme_m;
 
-    Ok((altitude, azimuth))
+    Ok([pos_itrf_m[0], pos_itrf_m[1], pos_itrf_m[2]])
 }
 
-/// Converts satellite position from ECI (Earth-Centered Inertial) coordinates to alt/az.
-///
-/// This function performs the coordinate transformation from ECI to topocentric
-/// (observer-centered) coordinates and then calculates altitude and azimuth.
-fn eci_to_alt_az(
-    prediction: Prediction,
-    location: ObserverLocation,
+/// Converts an ITRF (Earth-fixed) position in meters to an inertial (GCRF)
+/// position in km.
+pub(crate) fn itrf_to_inertial(
+    pos_itrf_m: [f64; 3],
     timestamp: DateTime<Utc>,
-) -> OverpassPlannerResult<(f64, f64)> {
-    // Get satellite position in TEME frame (km) - position is [f64; 3]
-    let sat_pos = prediction.position;
+) -> OverpassPlannerResult<[f64; 3]> {
+    let instant = to_instant(timestamp);
+    require_eop(&instant)?;
+
+    let rot_matrix = frametransform::qitrf2gcrf(&instant).to_rotation_matrix();
+    let pos_gcrf_m = rot_matrix * Vec3::new(pos_itrf_m[0], pos_itrf_m[1], pos_itrf_m[2]);
+
+    Ok([
+        pos_gcrf_m[0] / 1000.0,
+        pos_gcrf_m[1] / 1000.0,
+        pos_gcrf_m[2] / 1000.0,
+    ])
+}
 
-    // Convert chrono DateTime to satkit Instant
+/// Converts a chrono UTC timestamp to a satkit `Instant`.
+pub(crate) fn to_instant(timestamp: DateTime<Utc>) -> Instant {
     let naive = timestamp.naive_utc();
-    let instant = Instant::from_datetime(
+    Instant::from_datetime(
         naive.year(),
         naive.month() as i32,
         naive.day() as i32,
         naive.hour() as i32,
         naive.minute() as i32,
         naive.second() as f64 + naive.nanosecond() as f64 / 1e9,
-    );
-
-    // Create observer location as ITRFCoord
-    // satkit uses meters for altitude
-    let observer =
-        ITRFCoord::from_geodetic_deg(location.latitude, location.longitude, location.altitude);
-
-    // SGP4 returns positions in km, convert to meters for satkit
-    // Create position vector in TEME frame (in meters)
-    let pos_teme_m = Vec3::new(
-        sat_pos[0] * 1000.0,
-        sat_pos[1] * 1000.0,
-        sat_pos[2] * 1000.0,
-    );
+    )
+}
 
-    // Convert TEME to ITRF using satkit's frame transformation
-    // This handles all the Earth rotation automatically
-    // Note: qteme2itrf requires EOP data files - if missing, it will panic
-    // Check if EOP data is available before calling
+/// Ensures Earth Orientation Parameters are available for `instant`.
+///
+/// satkit's frame transformations require EOP data files and panic if they are
+/// missing, so this must be checked before calling them.
+pub(crate) fn require_eop(instant: &Instant) -> OverpassPlannerResult<()> {
     use satkit::earth_orientation_params;
-    if earth_orientation_params::get(&instant).is_none() {
+    if earth_orientation_params::get(instant).is_none() {
         return Err(OverpassPlannerError::CalculationError(
             "Earth Orientation Parameters (EOP) data not available. Please run satkit::utils::update_datafiles() first.".to_string(),
         ));
     }
-    let q_teme2itrf = frametransform::qteme2itrf(&instant);
+    Ok(())
+}
 
-    // Apply quaternion rotation to convert TEME to ITRF
-    // Use to_rotation_matrix() to ensure proper matrix multiplication
-    let rot_matrix = q_teme2itrf.to_rotation_matrix();
-    let pos_itrf_m = rot_matrix * pos_teme_m;
+/// Converts an ITRF (ECEF) position in meters to topocentric alt/az for the observer.
+///
+/// Returns (altitude_degrees, azimuth_degrees, range_meters).
+pub(crate) fn itrf_to_alt_az(
+    pos_itrf_m: [f64; 3],
+    location: ObserverLocation,
+) -> OverpassPlannerResult<(f64, f64, f64)> {
+    // Create observer location as ITRFCoord
+    // satkit uses meters for altitude
+    let observer =
+        ITRFCoord::from_geodetic_deg(location.latitude, location.longitude, location.altitude);
 
     // Create ITRFCoord from the converted position (in meters)
-    let sat_itrf = ITRFCoord::from_slice(pos_itrf_m.as_slice()).map_err(|e| {
+    let target_itrf = ITRFCoord::from_slice(&pos_itrf_m).map_err(|e| {
         OverpassPlannerError::CalculationError(format!("Failed to create ITRFCoord: {e}"))
     })?;
 
-    // Compute observer→satellite vector in ITRF (ECEF) frame
-    // This is the relative vector from observer to satellite
-    let rel_itrf = sat_itrf.itrf - observer.itrf;
+    // Compute observer→target vector in ITRF (ECEF) frame
+    // This is the relative vector from observer to target
+    let rel_itrf = target_itrf.itrf - observer.itrf;
 
     // Convert relative vector to ENU frame at observer's location
-    // Use observer's ENU frame rotation (not satellite's!)
+    // Use observer's ENU frame rotation (not target's!)
     let q_enu2itrf_obs = observer.q_enu2itrf();
     let enu = q_enu2itrf_obs.conjugate() * rel_itrf;
 
     // ENU components: [0] = East, [1] = North, [2] = Up (meters)
-    // Now 'up' can be negative when satellite is below horizon
+    // Now 'up' can be negative when target is below horizon
     let east = enu[0];
     let north = enu[1];
     let up = enu[2];
@@ -173,7 +323,7 @@ fn eci_to_alt_az(
     // Calculate horizontal range (distance in horizontal plane)
     let horizontal_range = (east * east + north * north).sqrt();
 
-    // Calculate total range (distance from observer to satellite)
+    // Calculate total range (distance from observer to target)
     let range = (horizontal_range * horizontal_range + up * up).sqrt();
 
     if range < 1e-6 {
@@ -183,7 +333,7 @@ fn eci_to_alt_az(
     }
 
     // Calculate altitude (elevation angle) - angle above horizon
-    // Positive when satellite is above horizon, negative when below
+    // Positive when target is above horizon, negative when below
     // Use atan2(up, horizontal_range) for proper quadrant handling
     let altitude = up.atan2(horizontal_range).to_degrees();
 
@@ -201,12 +351,145 @@ fn eci_to_alt_az(
         azimuth
     };
 
-    Ok((altitude, azimuth))
+    Ok((altitude, azimuth, range))
+}
+
+/// Solar system bodies whose apparent position can be computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CelestialBody {
+    Sun,
+    Moon,
+    Mercury,
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+}
+
+impl CelestialBody {
+    /// The planets, in order from the Sun.
+    pub const PLANETS: [CelestialBody; 7] = [
+        CelestialBody::Mercury,
+        CelestialBody::Venus,
+        CelestialBody::Mars,
+        CelestialBody::Jupiter,
+        CelestialBody::Saturn,
+        CelestialBody::Uranus,
+        CelestialBody::Neptune,
+    ];
+
+    /// Display name of the body.
+    pub fn name(self) -> &'static str {
+        match self {
+            CelestialBody::Sun => "Sun",
+            CelestialBody::Moon => "Moon",
+            CelestialBody::Mercury => "Mercury",
+            CelestialBody::Venus => "Venus",
+            CelestialBody::Mars => "Mars",
+            CelestialBody::Jupiter => "Jupiter",
+            CelestialBody::Saturn => "Saturn",
+            CelestialBody::Uranus => "Uranus",
+            CelestialBody::Neptune => "Neptune",
+        }
+    }
+
+    /// Mean radius of the body in km.
+    fn radius_km(self) -> f64 {
+        match self {
+            CelestialBody::Sun => 695_700.0,
+            CelestialBody::Moon => 1_737.4,
+            CelestialBody::Mercury => 2_439.7,
+            CelestialBody::Venus => 6_051.8,
+            CelestialBody::Mars => 3_389.5,
+            CelestialBody::Jupiter => 69_911.0,
+            CelestialBody::Saturn => 58_232.0,
+            CelestialBody::Uranus => 25_362.0,
+            CelestialBody::Neptune => 24_622.0,
+        }
+    }
+
+    fn solar_system_body(self) -> satkit::SolarSystem {
+        match self {
+            CelestialBody::Sun => satkit::SolarSystem::Sun,
+            CelestialBody::Moon => satkit::SolarSystem::Moon,
+            CelestialBody::Mercury => satkit::SolarSystem::Mercury,
+            CelestialBody::Venus => satkit::SolarSystem::Venus,
+            CelestialBody::Mars => satkit::SolarSystem::Mars,
+            CelestialBody::Jupiter => satkit::SolarSystem::Jupiter,
+            CelestialBody::Saturn => satkit::SolarSystem::Saturn,
+            CelestialBody::Uranus => satkit::SolarSystem::Uranus,
+            CelestialBody::Neptune => satkit::SolarSystem::Neptune,
+        }
+    }
+}
+
+/// Apparent (topocentric) position of a celestial body.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyPosition {
+    /// Altitude angle (degrees, negative below the horizon)
+    pub altitude: f64,
+    /// Azimuth angle (degrees, 0-360)
+    pub azimuth: f64,
+    /// Distance from the observer (km)
+    pub distance_km: f64,
+    /// Apparent angular radius of the disc (degrees)
+    pub angular_radius: f64,
+}
+
+/// Calculates the apparent position of the Sun, Moon or a planet as seen by the observer.
+///
+/// Uses satkit's JPL ephemeris, which needs the same data files as the EOP lookup
+/// (see `satkit::utils::update_datafiles()`). The topocentric correction matters
+/// for the Moon, whose parallax is close to a degree. Planet positions are
+/// geometric (no light-time correction), which is good to a few arcseconds.
+pub fn body_position(
+    body: CelestialBody,
+    location: ObserverLocation,
+    timestamp: DateTime<Utc>,
+) -> OverpassPlannerResult<BodyPosition> {
+    let instant = to_instant(timestamp);
+    require_eop(&instant)?;
+
+    let pos_gcrf_m =
+        satkit::jplephem::geocentric_pos(body.solar_system_body(), &instant).map_err(|e| {
+            OverpassPlannerError::CalculationError(format!("Ephemeris lookup failed: {e}"))
+        })?;
+
+    let rot_matrix = frametransform::qgcrf2itrf(&instant).to_rotation_matrix();
+    let pos_itrf_m = rot_matrix * pos_gcrf_m;
+
+    let (altitude, azimuth, range_m) =
+        itrf_to_alt_az([pos_itrf_m[0], pos_itrf_m[1], pos_itrf_m[2]], location)?;
+    let distance_km = range_m / 1000.0;
+
+    Ok(BodyPosition {
+        altitude,
+        azimuth,
+        distance_km,
+        angular_radius: (body.radius_km() / distance_km).asin().to_degrees(),
+    })
+}
+
+/// Angular separation between two alt/az directions (all angles in degrees).
+pub fn angular_separation(alt1: f64, az1: f64, alt2: f64, az2: f64) -> f64 {
+    // Haversine formula, numerically stable for the small angles used in transits
+    let (alt1, az1, alt2, az2) = (
+        alt1.to_radians(),
+        az1.to_radians(),
+        alt2.to_radians(),
+        az2.to_radians(),
+    );
+    let d_alt = alt2 - alt1;
+    let d_az = az2 - az1;
+    let h = (d_alt / 2.0).sin().powi(2) + alt1.cos() * alt2.cos() * (d_az / 2.0).sin().powi(2);
+    (2.0 * h.sqrt().min(1.0).asin()).to_degrees()
 }
 
 /// Find the exact time when satellite rises above horizon using binary search.
 pub(crate) fn find_rise_time(
-    tle: &str,
+    propagator: &dyn Propagator,
     location: ObserverLocation,
     time_before: DateTime<Utc>,
     time_after: DateTime<Utc>,
@@ -218,7 +501,7 @@ pub(crate) fn find_rise_time(
     // Binary search for rise time
     while (high - low).num_seconds() > step.num_seconds() {
         let mid = low + (high - low) / 2;
-        let (altitude, _) = calculate_alt_az(tle, location, mid)?;
+        let (altitude, _) = propagator.alt_az(location, mid)?;
 
         if altitude > 0.0 {
             high = mid;
@@ -232,7 +515,7 @@ pub(crate) fn find_rise_time(
 
 /// Find the exact time when satellite sets below horizon using binary search.
 pub(crate) fn find_set_time(
-    tle: &str,
+    propagator: &dyn Propagator,
     location: ObserverLocation,
     time_before: DateTime<Utc>,
     time_after: DateTime<Utc>,
@@ -244,7 +527,7 @@ pub(crate) fn find_set_time(
     // Binary search for set time
     while (high - low).num_seconds() > step.num_seconds() {
         let mid = low + (high - low) / 2;
-        let (altitude, _) = calculate_alt_az(tle, location, mid)?;
+        let (altitude, _) = propagator.alt_az(location, mid)?;
 
         if altitude > 0.0 {
             low = mid;
@@ -258,7 +541,7 @@ pub(crate) fn find_set_time(
 
 /// Find the maximum elevation during an overpass using golden section search.
 pub(crate) fn find_max_elevation(
-    tle: &str,
+    propagator: &dyn Propagator,
     location: ObserverLocation,
     start_time: DateTime<Utc>,
     end_time: DateTime<Utc>,
@@ -270,7 +553,7 @@ pub(crate) fn find_max_elevation(
     let mut current_time = start_time;
 
     while current_time <= end_time {
-        let (altitude, _) = calculate_alt_az(tle, location, current_time)?;
+        let (altitude, _) = propagator.alt_az(location, current_time)?;
         if altitude > max_elevation {
             max_elevation = altitude;
             max_time = current_time;
@@ -304,8 +587,8 @@ pub(crate) fn find_max_elevation(
     let mut iterations = 0;
     while (c - d).num_seconds().abs() > 1 && iterations < max_iterations {
         iterations += 1;
-        let (alt_c, _) = calculate_alt_az(tle, location, c)?;
-        let (alt_d, _) = calculate_alt_az(tle, location, d)?;
+        let (alt_c, _) = propagator.alt_az(location, c)?;
+        let (alt_d, _) = propagator.alt_az(location, d)?;
 
         if alt_c > alt_d {
             b = d;
@@ -330,13 +613,73 @@ pub(crate) fn find_max_elevation(
 
     // Final check at midpoint
     let midpoint = a + (b - a) / 2;
-    let (alt_mid, _) = calculate_alt_az(tle, location, midpoint)?;
+    let (alt_mid, _) = propagator.alt_az(location, midpoint)?;
     Ok(alt_mid.max(max_elevation))
 }
 
-/// Calculate sun elevation at observer location.
-/// Returns sun elevation in degrees (negative when below horizon).
-fn calculate_sun_elevation(location: ObserverLocation, timestamp: DateTime<Utc>) -> f64 {
+/// Classify a geosynchronous satellite's visibility over a time window.
+///
+/// Samples the elevation every hour (plus the window end) instead of every minute.
+/// Returns the visibility kind and the maximum sampled elevation when the satellite
+/// stays on one side of the horizon for the whole window, or `None` when it crosses
+/// the horizon and needs the regular pass search.
+pub(crate) fn classify_geosynchronous(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> OverpassPlannerResult<Option<(PassKind, f64)>> {
+    let sample_step = Duration::hours(1);
+    let mut max_elevation = f64::NEG_INFINITY;
+    let mut any_above = false;
+    let mut any_below = false;
+
+    let mut current_time = start_time;
+    loop {
+        let (altitude, _) = propagator.alt_az(location, current_time)?;
+        max_elevation = max_elevation.max(altitude);
+        if altitude > 0.0 {
+            any_above = true;
+        } else {
+            any_below = true;
+        }
+
+        if current_time >= end_time {
+            break;
+        }
+        current_time = (current_time + sample_step).min(end_time);
+    }
+
+    Ok(match (any_above, any_below) {
+        (true, false) => Some((PassKind::AlwaysVisible, max_elevation)),
+        (false, true) => Some((PassKind::NeverVisible, max_elevation)),
+        _ => None,
+    })
+}
+
+/// Angular separation between the satellite and the Moon (degrees).
+///
+/// Returns `None` rather than an error when the lunar ephemeris cannot be
+/// evaluated, since the separation is only used to rank passes.
+pub(crate) fn moon_separation(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    timestamp: DateTime<Utc>,
+) -> Option<f64> {
+    let (sat_alt, sat_az) = propagator.alt_az(location, timestamp).ok()?;
+    let moon = body_position(CelestialBody::Moon, location, timestamp).ok()?;
+    Some(angular_separation(
+        sat_alt,
+        sat_az,
+        moon.altitude,
+        moon.azimuth,
+    ))
+}
+
+/// Approximate apparent position of the Sun.
+///
+/// Returns (right_ascension_radians, declination_radians, gmst_degrees).
+fn sun_equatorial(timestamp: DateTime<Utc>) -> (f64, f64, f64) {
     // Calculate Julian Date
     let unix = timestamp.timestamp() as f64;
     let sub = timestamp.timestamp_subsec_nanos() as f64 * 1e-9;
@@ -370,11 +713,21 @@ fn calculate_sun_elevation(location: ObserverLocation, timestamp: DateTime<Utc>)
     let alpha = (lambda_rad.sin() * epsilon_rad.cos()).atan2(lambda_rad.cos());
     let delta = (lambda_rad.sin() * epsilon_rad.sin()).asin();
 
-    // Local sidereal time
+    // Greenwich mean sidereal time
     let gmst =
         (280.46061837 + 360.98564736629 * (jd - 2451545.0) + 0.000387933 * (n / 36525.0).powi(2)
             - (n / 36525.0).powi(3) / 38710000.0)
             % 360.0;
+
+    (alpha, delta, gmst)
+}
+
+/// Calculate sun elevation at observer location.
+/// Returns sun elevation in degrees (negative when below horizon).
+fn calculate_sun_elevation(location: ObserverLocation, timestamp: DateTime<Utc>) -> f64 {
+    let (alpha, delta, gmst) = sun_equatorial(timestamp);
+
+    // Local sidereal time
     let lst = (gmst + location.longitude).to_radians();
 
     // Hour angle
@@ -386,65 +739,137 @@ fn calculate_sun_elevation(location: ObserverLocation, timestamp: DateTime<Utc>)
     sin_alt.asin().to_degrees()
 }
 
-/// Check if it's night at the observer location (sun below -6° horizon for astronomical twilight).
-pub(crate) fn is_night_at_location(
-    location: ObserverLocation,
-    timestamp: DateTime<Utc>,
-) -> OverpassPlannerResult<bool> {
-    let sun_elevation = calculate_sun_elevation(location, timestamp);
-    Ok(sun_elevation < -6.0)
+/// A point on the Earth's surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    /// Latitude in degrees (-90 to 90)
+    pub latitude: f64,
+    /// Longitude in degrees (-180 to 180)
+    pub longitude: f64,
 }
 
-/// Check if satellite is illuminated by the sun (not in Earth's shadow).
-pub(crate) fn is_satellite_lit(tle: &str, timestamp: DateTime<Utc>) -> OverpassPlannerResult<bool> {
-    // Parse TLE
-    let lines: Vec<&str> = tle
-        .lines()
-        .map(|l| l.trim())
-        .filter(|l| !l.is_empty())
-        .collect();
-
-    if lines.len() < 3 {
-        return Err(OverpassPlannerError::ParseError(
-            "TLE must contain at least 3 lines".to_string(),
+/// The point on Earth where the Sun is directly overhead.
+pub fn subsolar_point(timestamp: DateTime<Utc>) -> GeoPoint {
+    let (alpha, delta, gmst) = sun_equatorial(timestamp);
+    GeoPoint {
+        latitude: delta.to_degrees(),
+        longitude: normalize_longitude(alpha.to_degrees() - gmst),
+    }
+}
+
+/// The day/night terminator (where the Sun's center is on the horizon) as a
+/// polyline of `points` points, ordered by longitude from -180° to 180°.
+///
+/// The night side is the hemisphere away from [`subsolar_point`]. Atmospheric
+/// refraction and the size of the solar disc are ignored.
+pub fn terminator(timestamp: DateTime<Utc>, points: usize) -> Vec<GeoPoint> {
+    let subsolar = subsolar_point(timestamp);
+    // The terminator degenerates to a meridian at the equinoxes; keep it a
+    // function of longitude by nudging the declination off zero
+    let declination = subsolar.latitude.to_radians();
+    let tan_declination = if declination.abs() < 1e-9 {
+        1e-9
+    } else {
+        declination.tan()
+    };
+
+    let steps = points.max(2) - 1;
+    (0..=steps)
+        .map(|i| {
+            let longitude = -180.0 + 360.0 * i as f64 / steps as f64;
+            let hour_angle = (longitude - subsolar.longitude).to_radians();
+            let latitude = (-hour_angle.cos() / tan_declination).atan().to_degrees();
+            GeoPoint {
+                latitude,
+                longitude,
+            }
+        })
+        .collect()
+}
+
+/// Samples the satellite's sub-satellite point every `interval` from
+/// `start_time` to `end_time` (both included).
+pub fn ground_track(
+    propagator: &dyn Propagator,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    interval: Duration,
+) -> OverpassPlannerResult<Vec<GeoPoint>> {
+    if interval <= Duration::zero() {
+        return Err(OverpassPlannerError::InvalidInput(
+            "Ground track sample interval must be positive".to_string(),
         ));
     }
 
-    let mut line1 = None;
-    let mut line2 = None;
-    for line in &lines {
-        if line.starts_with("1 ") {
-            line1 = Some(*line);
-        } else if line.starts_with("2 ") {
-            line2 = Some(*line);
+    let mut track = Vec::new();
+    let mut current_time = start_time;
+    loop {
+        let pos_itrf_m = propagator.position_itrf(current_time)?;
+        let coord = ITRFCoord::from_slice(&pos_itrf_m).map_err(|e| {
+            OverpassPlannerError::CalculationError(format!("Failed to create ITRFCoord: {e}"))
+        })?;
+        let (latitude, longitude, _) = coord.to_geodetic_deg();
+        track.push(GeoPoint {
+            latitude,
+            longitude,
+        });
+
+        if current_time >= end_time {
+            break;
         }
+        current_time = (current_time + interval).min(end_time);
     }
 
-    let line1 = line1
-        .ok_or_else(|| OverpassPlannerError::ParseError("TLE line 1 not found".to_string()))?;
-    let line2 = line2
-        .ok_or_else(|| OverpassPlannerError::ParseError("TLE line 2 not found".to_string()))?;
+    Ok(track)
+}
 
-    // Parse TLE using sgp4
-    let elements = Elements::from_tle(None, line1.as_bytes(), line2.as_bytes())
-        .map_err(|e| OverpassPlannerError::TLEError(format!("Failed to parse TLE: {e}")))?;
+/// Fraction (0.0 to 1.0) of the satellite's ground track between two instants
+/// that lies on the day side of the terminator.
+pub fn sunlit_ground_track_fraction(
+    propagator: &dyn Propagator,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    interval: Duration,
+) -> OverpassPlannerResult<f64> {
+    let track = ground_track(propagator, start_time, end_time, interval)?;
+    let mut current_time = start_time;
+    let mut sunlit = 0;
+    for point in &track {
+        let location = ObserverLocation {
+            latitude: point.latitude,
+            longitude: point.longitude,
+            altitude: 0.0,
+        };
+        if calculate_sun_elevation(location, current_time) > 0.0 {
+            sunlit += 1;
+        }
+        current_time = (current_time + interval).min(end_time);
+    }
 
-    let constants = sgp4::Constants::from_elements(&elements).map_err(|e| {
-        OverpassPlannerError::CalculationError(format!("Failed to create constants: {e}"))
-    })?;
+    Ok(sunlit as f64 / track.len() as f64)
+}
 
-    // Calculate minutes since TLE epoch
-    let tle_epoch = elements.datetime.and_utc();
-    let duration = timestamp.signed_duration_since(tle_epoch);
-    let minutes_since_epoch = duration.num_seconds() as f64 / 60.0;
+/// Wraps a longitude in degrees to the range [-180, 180).
+fn normalize_longitude(longitude: f64) -> f64 {
+    (longitude + 180.0).rem_euclid(360.0) - 180.0
+}
 
-    // Propagate satellite position
-    let prediction = constants
-        .propagate(minutes_since_epoch)
-        .map_err(|e| OverpassPlannerError::CalculationError(format!("Propagation failed: {e}")))?;
+/// Check if it's night at the observer location (sun below -6° horizon for astronomical twilight).
+pub(crate) fn is_night_at_location(
+    location: ObserverLocation,
+    timestamp: DateTime<Utc>,
+) -> OverpassPlannerResult<bool> {
+    let sun_elevation = calculate_sun_elevation(location, timestamp);
+    Ok(sun_elevation < -6.0)
+}
 
-    // Satellite position in km (TEME frame)
-    let sat_pos = prediction.position;
+/// Check if satellite is illuminated by the sun (not in Earth's shadow).
+pub fn is_satellite_lit(
+    propagator: &dyn Propagator,
+    timestamp: DateTime<Utc>,
+) -> OverpassPlannerResult<bool> {
+    // Satellite position in km (inertial frame)
+    let sat_pos = propagator.position_inertial(timestamp)?;
 
     // Earth radius in km
     const EARTH_RADIUS_KM: f64 = 6378.137;
@@ -508,3 +933,85 @@ pub(crate) fn is_satellite_lit(tle: &str, timestamp: DateTime<Utc>) -> OverpassP
     // Otherwise, satellite is lit
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISS_TLE: &str = r#"ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537"#;
+
+    #[test]
+    fn test_propagation_context_from_tle() {
+        let ctx = PropagationContext::from_tle(ISS_TLE).unwrap();
+        assert_eq!(ctx.epoch().format("%Y-%m-%d").to_string(), "2008-09-20");
+
+        // Propagating the same context repeatedly must be stable
+        let t = ctx.epoch() + Duration::minutes(90);
+        let a = ctx.propagate(t).unwrap();
+        let b = ctx.propagate(t).unwrap();
+        assert_eq!(a.position, b.position);
+    }
+
+    #[test]
+    fn test_check_not_decayed() {
+        let ctx = PropagationContext::from_tle(ISS_TLE).unwrap();
+        assert!(ctx
+            .check_not_decayed(ctx.epoch() + Duration::days(1))
+            .is_ok());
+        assert!(matches!(
+            ctx.check_not_decayed(ctx.epoch() + Duration::days(365)),
+            Err(OverpassPlannerError::Decayed(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_geosynchronous() {
+        let geo_tle = r#"GOES 16
+1 41866U 16071A   24001.50000000 -.00000100  00000-0  00000-0 0  9994
+2 41866   0.0500  90.0000 0001000 270.0000  90.0000  1.00270000250007"#;
+
+        assert!(PropagationContext::from_tle(geo_tle)
+            .unwrap()
+            .is_geosynchronous());
+        assert!(!PropagationContext::from_tle(ISS_TLE)
+            .unwrap()
+            .is_geosynchronous());
+    }
+
+    #[test]
+    fn test_terminator_is_perpendicular_to_subsolar_point() {
+        let timestamp = DateTime::parse_from_rfc3339("2024-06-21T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let subsolar = subsolar_point(timestamp);
+        // Near the June solstice the Sun is over the Tropic of Cancer
+        assert!((subsolar.latitude - 23.44).abs() < 0.1);
+
+        let points = terminator(timestamp, 73);
+        assert_eq!(points.len(), 73);
+        assert_eq!(points[0].longitude, -180.0);
+        assert_eq!(points[72].longitude, 180.0);
+        for point in points {
+            let location = ObserverLocation {
+                latitude: point.latitude,
+                longitude: point.longitude,
+                altitude: 0.0,
+            };
+            assert!(calculate_sun_elevation(location, timestamp).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_angular_separation() {
+        assert!((angular_separation(10.0, 20.0, 10.0, 20.0)).abs() < 1e-12);
+        assert!((angular_separation(0.0, 0.0, 90.0, 123.0) - 90.0).abs() < 1e-9);
+        assert!((angular_separation(0.0, 359.5, 0.0, 0.5) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_propagation_context_invalid() {
+        assert!(PropagationContext::from_tle("Not a TLE").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Precession module.
+//!
+//! Star catalogs, plate solvers and the planetarium give positions for the
+//! J2000 equinox, while mounts point in the equinox of date. These convert
+//! between the two with the annual precession rates, which are good to
+//! arcseconds for a few decades either side of 2000, away from the poles.
+
+use chrono::{DateTime, Utc};
+
+/// 2000-01-01 12:00, as a Unix timestamp
+const J2000_UNIX: i64 = 946_728_000;
+const SECONDS_PER_YEAR: f64 = 365.25 * 86_400.0;
+
+/// Precession (hours of RA, degrees of Dec) of (`ra_hours`, `dec_deg`) over
+/// `years`.
+fn drift(ra_hours: f64, dec_deg: f64, years: f64) -> (f64, f64) {
+    let (ra, dec) = ((ra_hours * 15.0).to_radians(), dec_deg.to_radians());
+    // annual precession, in seconds of RA and arcseconds of Dec
+    let ra_seconds = 3.075 + 1.336 * ra.sin() * dec.tan();
+    let dec_arcsec = 20.04 * ra.cos();
+    (ra_seconds * years / 3600.0, dec_arcsec * years / 3600.0)
+}
+
+fn years_since_j2000(time: DateTime<Utc>) -> f64 {
+    (time.timestamp() - J2000_UNIX) as f64 / SECONDS_PER_YEAR
+}
+
+/// J2000 (`ra_hours`, `dec_deg`) precessed to the equinox of `time`.
+pub fn to_date(ra_hours: f64, dec_deg: f64, time: DateTime<Utc>) -> (f64, f64) {
+    let (ra_drift, dec_drift) = drift(ra_hours, dec_deg, years_since_j2000(time));
+    (
+        (ra_hours + ra_drift).rem_euclid(24.0),
+        (dec_deg + dec_drift).clamp(-90.0, 90.0),
+    )
+}
+
+/// (`ra_hours`, `dec_deg`) for the equinox of `time` taken back to J2000; the
+/// inverse of [`to_date`].
+pub fn to_j2000(ra_hours: f64, dec_deg: f64, time: DateTime<Utc>) -> (f64, f64) {
+    let years = years_since_j2000(time);
+    // The rates depend on the J2000 position being solved for, so refine it
+    // from the of-date one; twice is well under an arcsecond.
+    let mut j2000 = (ra_hours, dec_deg);
+    for _ in 0..2 {
+        let (ra_drift, dec_drift) = drift(j2000.0, j2000.1, years);
+        j2000 = (
+            (ra_hours - ra_drift).rem_euclid(24.0),
+            (dec_deg - dec_drift).clamp(-90.0, 90.0),
+        );
+    }
+    j2000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_no_precession_at_j2000() {
+        let j2000 = Utc.with_ymd_and_hms(2000, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(to_date(6.0, 30.0, j2000), (6.0, 30.0));
+        assert_eq!(to_j2000(6.0, 30.0, j2000), (6.0, 30.0));
+    }
+
+    #[test]
+    fn test_to_date_rates() {
+        // 50 years on: 3.075 s/yr of RA and 20.04"/yr of Dec at RA 0, Dec 0
+        let time = Utc.with_ymd_and_hms(2050, 1, 1, 12, 0, 0).unwrap();
+        let years = years_since_j2000(time);
+        let (ra, dec) = to_date(0.0, 0.0, time);
+        assert!((ra - 3.075 * years / 3600.0).abs() < 1e-12);
+        assert!((dec - 20.04 * years / 3600.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let time = Utc.with_ymd_and_hms(2026, 10, 17, 0, 0, 0).unwrap();
+        for &(ra, dec) in &[(0.05, 10.0), (5.92, 7.4), (18.6, 38.8), (23.98, -60.0)] {
+            let (date_ra, date_dec) = to_date(ra, dec, time);
+            let (back_ra, back_dec) = to_j2000(date_ra, date_dec, time);
+            // one arcsecond, wrapping RA across 0h
+            let ra_error = ((back_ra - ra + 12.0).rem_euclid(24.0) - 12.0) * 15.0;
+            assert!(
+                ra_error.abs() < 1.0 / 3600.0,
+                "RA {ra} came back as {back_ra}"
+            );
+            assert!(
+                (back_dec - dec).abs() < 1.0 / 3600.0,
+                "Dec {dec} came back as {back_dec}"
+            );
+        }
+    }
+}
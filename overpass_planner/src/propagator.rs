@@ -0,0 +1,82 @@
+//! Satellite propagator abstraction.
+//!
+//! The pass search and alt/az code work against the [`Propagator`] trait, so
+//! satellites can be propagated from a TLE with SGP4 ([`PropagationContext`]) or
+//! interpolated from a high-accuracy ephemeris file ([`crate::ephemeris::Ephemeris`]).
+//!
+//! [`PropagationContext`]: crate::planning::PropagationContext
+
+use crate::planning::{itrf_to_alt_az, itrf_to_inertial, ObserverLocation};
+use crate::{OverpassPlannerError, OverpassPlannerResult, SatellitePosition};
+use chrono::{DateTime, Duration, Utc};
+
+/// A source of satellite positions over time.
+pub trait Propagator: Send + Sync {
+    /// Position of the satellite in the ITRF (Earth-fixed) frame, in meters.
+    fn position_itrf(&self, timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]>;
+
+    /// Position of the satellite in an Earth-centered inertial frame, in km.
+    ///
+    /// Only used for Earth-shadow checks, so TEME and GCRF are both fine. The
+    /// default implementation rotates [`Propagator::position_itrf`] to GCRF.
+    fn position_inertial(&self, timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+        itrf_to_inertial(self.position_itrf(timestamp)?, timestamp)
+    }
+
+    /// Whether the orbit is geosynchronous, in which case the pass search samples
+    /// hourly instead of scanning for rise and set times.
+    fn is_geosynchronous(&self) -> bool {
+        false
+    }
+
+    /// Calculates the altitude and azimuth of the satellite at a given time.
+    ///
+    /// Returns (altitude_degrees, azimuth_degrees), see
+    /// [`crate::planning::calculate_alt_az`] for the conventions.
+    fn alt_az(
+        &self,
+        location: ObserverLocation,
+        timestamp: DateTime<Utc>,
+    ) -> OverpassPlannerResult<(f64, f64)> {
+        let (altitude, azimuth, _) = itrf_to_alt_az(self.position_itrf(timestamp)?, location)?;
+        Ok((altitude, azimuth))
+    }
+
+    /// Samples the satellite's alt/az every `interval` from `start_time` to
+    /// `end_time`; the end time is always included as the last sample.
+    ///
+    /// # Errors
+    /// Returns `OverpassPlannerError::InvalidInput` if `interval` is not positive,
+    /// or any error from [`Propagator::alt_az`].
+    fn trajectory(
+        &self,
+        location: ObserverLocation,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        interval: Duration,
+    ) -> OverpassPlannerResult<Vec<SatellitePosition>> {
+        if interval <= Duration::zero() {
+            return Err(OverpassPlannerError::InvalidInput(
+                "Trajectory sample interval must be positive".to_string(),
+            ));
+        }
+
+        let mut positions = Vec::new();
+        let mut current_time = start_time;
+        loop {
+            let (altitude, azimuth) = self.alt_az(location, current_time)?;
+            positions.push(SatellitePosition {
+                timestamp: current_time,
+                altitude,
+                azimuth,
+            });
+
+            if current_time >= end_time {
+                break;
+            }
+            current_time = (current_time + interval).min(end_time);
+        }
+
+        Ok(positions)
+    }
+}
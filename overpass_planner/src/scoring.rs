@@ -0,0 +1,151 @@
+//! Pass quality scoring module.
+//!
+//! This module ranks overpasses by how good they are to observe, combining
+//! elevation, duration, sky darkness, satellite illumination and distance from
+//! the Moon into a single score between 0 and 1.
+
+use crate::planning::ObserverLocation;
+use crate::{get_overpasses, Overpass, OverpassPlannerResult, PassKind};
+use chrono::Duration;
+
+/// Duration at which a pass gets the full duration score (minutes).
+const FULL_DURATION_MINUTES: f64 = 10.0;
+/// Moon separation at which a pass gets the full Moon score (degrees).
+const FULL_MOON_SEPARATION: f64 = 90.0;
+
+/// Relative weights of the components of a pass score.
+///
+/// Weights do not need to sum to 1; the score is normalized by their total.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreWeights {
+    /// Weight of the maximum elevation (90° scores 1)
+    pub elevation: f64,
+    /// Weight of the pass duration (10 minutes or more scores 1)
+    pub duration: f64,
+    /// Weight of the sky being dark during the pass
+    pub darkness: f64,
+    /// Weight of the satellite being sunlit during the pass
+    pub illumination: f64,
+    /// Weight of the separation from the Moon (90° or more scores 1)
+    pub moon_separation: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            elevation: 0.35,
+            duration: 0.15,
+            darkness: 0.2,
+            illumination: 0.2,
+            moon_separation: 0.1,
+        }
+    }
+}
+
+impl Overpass {
+    /// Score the pass between 0 (worst) and 1 (best) using the given weights.
+    ///
+    /// Geosynchronous windows where the satellite never rises score 0. A missing
+    /// Moon separation is not penalized.
+    pub fn score(&self, weights: &ScoreWeights) -> f64 {
+        if self.kind == PassKind::NeverVisible {
+            return 0.0;
+        }
+
+        let total_weight = weights.elevation
+            + weights.duration
+            + weights.darkness
+            + weights.illumination
+            + weights.moon_separation;
+        if total_weight <= 0.0 {
+            return 0.0;
+        }
+
+        let elevation = (self.max_elevation / 90.0).clamp(0.0, 1.0);
+        let duration_minutes = (self.end_time - self.start_time).num_seconds() as f64 / 60.0;
+        let duration = (duration_minutes / FULL_DURATION_MINUTES).clamp(0.0, 1.0);
+        let darkness = if self.is_night { 1.0 } else { 0.0 };
+        let illumination = if self.is_lit { 1.0 } else { 0.0 };
+        let moon_separation = self
+            .moon_separation
+            .map_or(1.0, |sep| (sep / FULL_MOON_SEPARATION).clamp(0.0, 1.0));
+
+        (weights.elevation * elevation
+            + weights.duration * duration
+            + weights.darkness * darkness
+            + weights.illumination * illumination
+            + weights.moon_separation * moon_separation)
+            / total_weight
+    }
+}
+
+/// Sort overpasses from best to worst score.
+pub fn sort_by_score(overpasses: &mut [Overpass], weights: &ScoreWeights) {
+    overpasses.sort_by(|a, b| b.score(weights).total_cmp(&a.score(weights)));
+}
+
+/// Get all overpasses within a time window, sorted from best to worst score.
+///
+/// # Arguments
+/// * `norad_id` - The NORAD ID of the satellite
+/// * `location` - Observer's location on Earth
+/// * `time_from_now` - Duration from now to search for overpasses
+/// * `weights` - Weights of the score components
+pub async fn get_overpasses_by_score(
+    norad_id: u32,
+    location: ObserverLocation,
+    time_from_now: Duration,
+    weights: &ScoreWeights,
+) -> OverpassPlannerResult<Vec<Overpass>> {
+    let mut overpasses = get_overpasses(norad_id, location, time_from_now).await?;
+    sort_by_score(&mut overpasses, weights);
+    Ok(overpasses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn pass(max_elevation: f64, minutes: i64, is_night: bool) -> Overpass {
+        let start_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end_time = start_time + Duration::minutes(minutes);
+        Overpass {
+            start_time,
+            end_time,
+            max_elevation,
+            midpoint_time: start_time + (end_time - start_time) / 2,
+            is_night,
+            is_lit: true,
+            kind: PassKind::Normal,
+            moon_separation: None,
+            truncated_start: false,
+            truncated_end: false,
+            trajectory: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_score_range() {
+        let weights = ScoreWeights::default();
+        let best = pass(90.0, 12, true);
+        assert!((best.score(&weights) - 1.0).abs() < 1e-9);
+
+        let mut never = pass(-10.0, 60, true);
+        never.kind = PassKind::NeverVisible;
+        assert_eq!(never.score(&weights), 0.0);
+    }
+
+    #[test]
+    fn test_sort_by_score() {
+        let weights = ScoreWeights::default();
+        let mut overpasses = vec![
+            pass(20.0, 4, false),
+            pass(80.0, 6, true),
+            pass(40.0, 5, true),
+        ];
+        sort_by_score(&mut overpasses, &weights);
+        let elevations: Vec<f64> = overpasses.iter().map(|o| o.max_elevation).collect();
+        assert_eq!(elevations, vec![80.0, 40.0, 20.0]);
+    }
+}
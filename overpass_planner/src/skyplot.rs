@@ -0,0 +1,132 @@
+//! Polar sky plot module.
+//!
+//! Converts a pass trajectory into the coordinates of the classic pass chart: a
+//! circle with the zenith at the center, the horizon at the edge and north at the
+//! top, with markers at rise, culmination and set.
+
+use crate::{Overpass, SatellitePosition};
+use chrono::{DateTime, Utc};
+
+/// A trajectory sample projected onto the polar sky plot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PolarPoint {
+    /// Time of the sample
+    pub timestamp: DateTime<Utc>,
+    /// Normalized zenith distance, (90° - altitude) / 90°: 0 at the zenith, 1 on
+    /// the horizon (samples below the horizon are clamped to 1)
+    pub r: f64,
+    /// Azimuth (radians, clockwise from north)
+    pub theta: f64,
+}
+
+impl PolarPoint {
+    /// Projects an alt/az sample onto the plot.
+    pub fn from_position(position: &SatellitePosition) -> Self {
+        Self {
+            timestamp: position.timestamp,
+            r: ((90.0 - position.altitude) / 90.0).clamp(0.0, 1.0),
+            theta: position.azimuth.to_radians(),
+        }
+    }
+
+    /// Cartesian coordinates in the unit circle, with x pointing east and y
+    /// pointing north. Flip y for screen coordinates.
+    pub fn to_xy(&self) -> (f64, f64) {
+        (self.r * self.theta.sin(), self.r * self.theta.cos())
+    }
+}
+
+/// Notable moments of a pass marked on the plot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassMarker {
+    /// The satellite rises above the horizon
+    Rise,
+    /// The satellite reaches its maximum elevation
+    Culmination,
+    /// The satellite sets below the horizon
+    Set,
+}
+
+/// A pass trajectory projected onto the polar sky plot.
+#[derive(Debug, Clone, Default)]
+pub struct SkyPlot {
+    /// Projected trajectory, in time order
+    pub path: Vec<PolarPoint>,
+    /// Rise, culmination and set positions
+    pub markers: Vec<(PassMarker, PolarPoint)>,
+}
+
+/// Projects a trajectory onto the polar sky plot.
+///
+/// The first and last samples are marked as rise and set, and the highest sample
+/// as culmination. Returns an empty plot for an empty trajectory.
+pub fn sky_plot(trajectory: &[SatellitePosition]) -> SkyPlot {
+    let path: Vec<PolarPoint> = trajectory.iter().map(PolarPoint::from_position).collect();
+    let (Some(first), Some(last)) = (path.first(), path.last()) else {
+        return SkyPlot::default();
+    };
+
+    let culmination = path
+        .iter()
+        .min_by(|a, b| a.r.total_cmp(&b.r))
+        .copied()
+        .unwrap_or(*first);
+    let markers = vec![
+        (PassMarker::Rise, *first),
+        (PassMarker::Culmination, culmination),
+        (PassMarker::Set, *last),
+    ];
+
+    SkyPlot { path, markers }
+}
+
+impl Overpass {
+    /// Projects the pass trajectory onto the polar sky plot.
+    ///
+    /// The trajectory must have been sampled (see
+    /// [`crate::get_overpasses_with_trajectory`]). Rise and set markers are left
+    /// out when the pass is truncated by the search window, since the satellite
+    /// was not actually on the horizon there.
+    pub fn sky_plot(&self) -> SkyPlot {
+        let mut plot = sky_plot(&self.trajectory);
+        plot.markers.retain(|(marker, _)| match marker {
+            PassMarker::Rise => !self.truncated_start,
+            PassMarker::Set => !self.truncated_end,
+            PassMarker::Culmination => true,
+        });
+        plot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn test_sky_plot_markers() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let trajectory: Vec<SatellitePosition> = [(0.0, 270.0), (60.0, 0.0), (-1.0, 90.0)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(altitude, azimuth))| SatellitePosition {
+                timestamp: start + Duration::minutes(i as i64),
+                altitude,
+                azimuth,
+            })
+            .collect();
+
+        let plot = sky_plot(&trajectory);
+        assert_eq!(plot.path.len(), 3);
+        assert_eq!(plot.path[2].r, 1.0);
+
+        let (marker, culmination) = plot.markers[1];
+        assert_eq!(marker, PassMarker::Culmination);
+        assert!((culmination.r - 1.0 / 3.0).abs() < 1e-9);
+        let (x, y) = culmination.to_xy();
+        assert!(x.abs() < 1e-9 && (y - 1.0 / 3.0).abs() < 1e-9);
+
+        let (x, y) = plot.path[0].to_xy();
+        assert!((x + 1.0).abs() < 1e-9 && y.abs() < 1e-9);
+    }
+}
@@ -0,0 +1,468 @@
+//! Comet and asteroid module.
+//!
+//! This module reads Minor Planet Center orbital elements, in the one-line
+//! MPCORB (asteroid) and CometEls.txt (comet) formats, propagates the two-body
+//! orbit around the Sun and gives the body's apparent position the same way
+//! [`crate::body_position`] does for the planets.
+
+use crate::planning::{itrf_to_alt_az, require_eop, to_instant, ObserverLocation};
+use crate::{OverpassPlannerError, OverpassPlannerResult};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use satkit::frametransform;
+use std::f64::consts::PI;
+
+/// Gaussian gravitational constant (radians per day, for distances in AU).
+const GAUSS_K: f64 = 0.017_202_098_95;
+/// Astronomical unit in meters.
+const AU_M: f64 = 149_597_870_700.0;
+/// Obliquity of the ecliptic at J2000 (degrees).
+const OBLIQUITY_J2000: f64 = 23.439_291_1;
+/// Eccentricities this close to 1 are treated as parabolic.
+const PARABOLIC_TOLERANCE: f64 = 1e-6;
+/// Comet magnitude slope assumed when the elements leave it blank.
+const DEFAULT_COMET_SLOPE: f64 = 4.0;
+
+const MPC_COMETS_URL: &str = "https://minorplanetcenter.net/iau/MPCORB/CometEls.txt";
+const MPC_NEA_URL: &str = "https://minorplanetcenter.net/iau/MPCORB/NEA.txt";
+
+/// Whether a body's magnitude follows the asteroid or the comet law.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmallBodyKind {
+    Asteroid,
+    Comet,
+}
+
+/// Heliocentric orbit of a comet or asteroid, referred to the J2000 ecliptic.
+#[derive(Debug, Clone)]
+pub struct OrbitalElements {
+    /// Name or designation, e.g. `"(1) Ceres"` or `"1P/Halley"`
+    pub name: String,
+    pub kind: SmallBodyKind,
+    /// Perihelion distance (AU)
+    pub perihelion_distance: f64,
+    pub eccentricity: f64,
+    /// Inclination (degrees)
+    pub inclination: f64,
+    /// Longitude of the ascending node (degrees)
+    pub ascending_node: f64,
+    /// Argument of perihelion (degrees)
+    pub argument_of_perihelion: f64,
+    /// Time of perihelion passage (TT, used as UTC)
+    pub perihelion_time: DateTime<Utc>,
+    /// Absolute magnitude H (asteroids) or total absolute magnitude (comets)
+    pub absolute_magnitude: Option<f64>,
+    /// Slope parameter G (asteroids) or K (comets)
+    pub slope: Option<f64>,
+}
+
+/// Apparent (topocentric) position of a comet or asteroid.
+#[derive(Debug, Clone, Copy)]
+pub struct SmallBodyPosition {
+    /// Altitude angle (degrees, negative below the horizon)
+    pub altitude: f64,
+    /// Azimuth angle (degrees, 0-360)
+    pub azimuth: f64,
+    /// Distance from the observer (km)
+    pub distance_km: f64,
+    /// Distance from the Sun (AU)
+    pub sun_distance_au: f64,
+    /// Estimated visual magnitude, if the elements give an absolute magnitude
+    pub magnitude: Option<f64>,
+}
+
+/// Subsets of the MPC orbit database small enough to download on demand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpcSubset {
+    /// Every comet with a current orbit (CometEls.txt)
+    Comets,
+    /// Near-Earth asteroids (NEA.txt)
+    NearEarthAsteroids,
+}
+
+/// Trimmed text of 1-based, inclusive columns `start..=end`, if the line is long
+/// enough to reach `start`.
+fn columns(line: &str, start: usize, end: usize) -> Option<&str> {
+    let end = end.min(line.len());
+    line.get(start - 1..end).map(str::trim)
+}
+
+fn number(line: &str, start: usize, end: usize, what: &str) -> OverpassPlannerResult<f64> {
+    columns(line, start, end)
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| OverpassPlannerError::ParseError(format!("Invalid {what} in: {line}")))
+}
+
+/// Optional number, blank or missing columns giving `None`.
+fn optional_number(line: &str, start: usize, end: usize) -> Option<f64> {
+    columns(line, start, end).and_then(|text| text.parse().ok())
+}
+
+/// Midnight of a calendar date plus a fractional number of days.
+fn date_with_fraction(year: i32, month: u32, day: f64) -> Option<DateTime<Utc>> {
+    let midnight =
+        NaiveDate::from_ymd_opt(year, month, day.floor() as u32)?.and_hms_opt(0, 0, 0)?;
+    let fraction = Duration::milliseconds((day.fract() * 86_400_000.0).round() as i64);
+    Some((midnight + fraction).and_utc())
+}
+
+/// Value of one packed MPC digit: `0`-`9`, then `A`-`V` for 10-31.
+fn unpack_digit(c: char) -> Option<u32> {
+    match c {
+        '0'..='9' => c.to_digit(10),
+        'A'..='V' => Some(c as u32 - 'A' as u32 + 10),
+        _ => None,
+    }
+}
+
+/// Decodes a packed MPC date such as `K2555` (2025-05-05).
+fn unpack_date(packed: &str) -> Option<DateTime<Utc>> {
+    let chars: Vec<char> = packed.chars().collect();
+    let [century, y1, y2, month, day] = chars.as_slice() else {
+        return None;
+    };
+    let century = match century {
+        'I' => 1800,
+        'J' => 1900,
+        'K' => 2000,
+        _ => return None,
+    };
+    let year = century + y1.to_digit(10)? * 10 + y2.to_digit(10)?;
+    date_with_fraction(
+        year as i32,
+        unpack_digit(*month)?,
+        unpack_digit(*day)? as f64,
+    )
+}
+
+/// Solves Kepler's equation `E - e sin E = M` for the eccentric anomaly.
+fn eccentric_anomaly(mean_anomaly: f64, e: f64) -> f64 {
+    let m = (mean_anomaly + PI).rem_euclid(2.0 * PI) - PI;
+    // Danby's starting value converges for every eccentricity below 1
+    let mut anomaly = m + 0.85 * e * m.sin().signum();
+    for _ in 0..50 {
+        let step = (anomaly - e * anomaly.sin() - m) / (1.0 - e * anomaly.cos());
+        anomaly -= step;
+        if step.abs() < 1e-12 {
+            break;
+        }
+    }
+    anomaly
+}
+
+/// Solves the hyperbolic Kepler equation `e sinh H - H = M`.
+fn hyperbolic_anomaly(mean_anomaly: f64, e: f64) -> f64 {
+    let mut anomaly = (mean_anomaly / e).asinh();
+    for _ in 0..50 {
+        let step = (e * anomaly.sinh() - anomaly - mean_anomaly) / (e * anomaly.cosh() - 1.0);
+        anomaly -= step;
+        if step.abs() < 1e-12 {
+            break;
+        }
+    }
+    anomaly
+}
+
+impl OrbitalElements {
+    /// Parses one line of MPCORB.DAT (or one of its subsets such as NEA.txt).
+    ///
+    /// The perihelion time is worked out from the mean anomaly at the epoch.
+    pub fn from_mpcorb_line(line: &str) -> OverpassPlannerResult<Self> {
+        let epoch = columns(line, 21, 25)
+            .and_then(unpack_date)
+            .ok_or_else(|| OverpassPlannerError::ParseError(format!("Invalid epoch in: {line}")))?;
+        let mean_anomaly = number(line, 27, 35, "mean anomaly")?;
+        let eccentricity = number(line, 71, 79, "eccentricity")?;
+        let semi_major_axis = number(line, 93, 103, "semi-major axis")?;
+        if !(0.0..1.0).contains(&eccentricity) || semi_major_axis <= 0.0 {
+            return Err(OverpassPlannerError::ParseError(format!(
+                "Not an elliptical orbit: {line}"
+            )));
+        }
+
+        // Count back to the nearest perihelion, before or after the epoch
+        let motion_deg_per_day = GAUSS_K.to_degrees() / semi_major_axis.powf(1.5);
+        let mean_anomaly = (mean_anomaly + 180.0).rem_euclid(360.0) - 180.0;
+        let days_since_perihelion = mean_anomaly / motion_deg_per_day;
+
+        let name = columns(line, 167, 194)
+            .filter(|name| !name.is_empty())
+            .or_else(|| columns(line, 1, 7))
+            .unwrap_or_default();
+
+        Ok(Self {
+            name: name.to_string(),
+            kind: SmallBodyKind::Asteroid,
+            perihelion_distance: semi_major_axis * (1.0 - eccentricity),
+            eccentricity,
+            inclination: number(line, 60, 68, "inclination")?,
+            ascending_node: number(line, 49, 57, "ascending node")?,
+            argument_of_perihelion: number(line, 38, 46, "argument of perihelion")?,
+            perihelion_time: epoch
+                - Duration::milliseconds((days_since_perihelion * 86_400_000.0) as i64),
+            absolute_magnitude: optional_number(line, 9, 13),
+            slope: optional_number(line, 15, 19),
+        })
+    }
+
+    /// Parses one line of the MPC's CometEls.txt.
+    pub fn from_comet_line(line: &str) -> OverpassPlannerResult<Self> {
+        let year = number(line, 15, 18, "perihelion year")?;
+        let month = number(line, 20, 21, "perihelion month")?;
+        let day = number(line, 23, 29, "perihelion day")?;
+        let perihelion_time =
+            date_with_fraction(year as i32, month as u32, day).ok_or_else(|| {
+                OverpassPlannerError::ParseError(format!("Invalid perihelion date in: {line}"))
+            })?;
+
+        let name = columns(line, 103, 158)
+            .filter(|name| !name.is_empty())
+            .or_else(|| columns(line, 1, 12))
+            .unwrap_or_default();
+
+        Ok(Self {
+            name: name.to_string(),
+            kind: SmallBodyKind::Comet,
+            perihelion_distance: number(line, 31, 39, "perihelion distance")?,
+            eccentricity: number(line, 42, 49, "eccentricity")?,
+            inclination: number(line, 72, 79, "inclination")?,
+            ascending_node: number(line, 62, 69, "ascending node")?,
+            argument_of_perihelion: number(line, 52, 59, "argument of perihelion")?,
+            perihelion_time,
+            absolute_magnitude: optional_number(line, 92, 95),
+            slope: optional_number(line, 97, 100),
+        })
+    }
+
+    /// Parses a line in either MPC format; comet lines carry the orbit type
+    /// (`P`, `C`, `D`, `X`, `I` or `A`) in column 5.
+    pub fn from_mpc_line(line: &str) -> OverpassPlannerResult<Self> {
+        match line.chars().nth(4) {
+            Some('P' | 'C' | 'D' | 'X' | 'I' | 'A') => Self::from_comet_line(line),
+            _ => Self::from_mpcorb_line(line),
+        }
+    }
+
+    /// True anomaly (radians) and distance from the Sun (AU), `days` after perihelion.
+    fn anomaly_and_radius(&self, days: f64) -> (f64, f64) {
+        let q = self.perihelion_distance;
+        let e = self.eccentricity;
+
+        if (e - 1.0).abs() < PARABOLIC_TOLERANCE {
+            // Barker's equation s³ + 3s = W, solved in closed form
+            let w = 3.0 * GAUSS_K / (2.0 * q.powi(3)).sqrt() * days;
+            let y = (w / 2.0 + (w * w / 4.0 + 1.0).sqrt()).cbrt();
+            let s = y - 1.0 / y;
+            (2.0 * s.atan(), q * (1.0 + s * s))
+        } else if e < 1.0 {
+            let a = q / (1.0 - e);
+            let anomaly = eccentric_anomaly(GAUSS_K / a.powf(1.5) * days, e);
+            let true_anomaly = 2.0
+                * ((1.0 + e).sqrt() * (anomaly / 2.0).sin())
+                    .atan2((1.0 - e).sqrt() * (anomaly / 2.0).cos());
+            (true_anomaly, a * (1.0 - e * anomaly.cos()))
+        } else {
+            let a = q / (e - 1.0);
+            let anomaly = hyperbolic_anomaly(GAUSS_K / a.powf(1.5) * days, e);
+            let true_anomaly =
+                2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (anomaly / 2.0).tanh()).atan();
+            (true_anomaly, a * (e * anomaly.cosh() - 1.0))
+        }
+    }
+
+    /// Heliocentric position in AU, on the J2000 equator and equinox.
+    ///
+    /// Pure two-body motion: planetary perturbations are ignored, which is fine
+    /// for a few months either side of the elements' epoch.
+    pub fn heliocentric_position(&self, timestamp: DateTime<Utc>) -> [f64; 3] {
+        let days = (timestamp - self.perihelion_time).num_milliseconds() as f64 / 86_400_000.0;
+        let (true_anomaly, r) = self.anomaly_and_radius(days);
+
+        let node = self.ascending_node.to_radians();
+        let inclination = self.inclination.to_radians();
+        let u = self.argument_of_perihelion.to_radians() + true_anomaly;
+
+        // Orbital plane to ecliptic
+        let x = r * (node.cos() * u.cos() - node.sin() * u.sin() * inclination.cos());
+        let y = r * (node.sin() * u.cos() + node.cos() * u.sin() * inclination.cos());
+        let z = r * u.sin() * inclination.sin();
+
+        // Ecliptic to equator
+        let obliquity = OBLIQUITY_J2000.to_radians();
+        [
+            x,
+            y * obliquity.cos() - z * obliquity.sin(),
+            y * obliquity.sin() + z * obliquity.cos(),
+        ]
+    }
+
+    /// Visual magnitude at `sun_distance` and `earth_distance` (both AU).
+    ///
+    /// Asteroids ignore the phase angle, so they read a little bright away from
+    /// opposition; comets use the MPC's total magnitude law.
+    pub fn magnitude(&self, sun_distance: f64, earth_distance: f64) -> Option<f64> {
+        let h = self.absolute_magnitude?;
+        Some(match self.kind {
+            SmallBodyKind::Asteroid => h + 5.0 * (sun_distance * earth_distance).log10(),
+            SmallBodyKind::Comet => {
+                let k = self.slope.unwrap_or(DEFAULT_COMET_SLOPE);
+                h + 5.0 * earth_distance.log10() + 2.5 * k * sun_distance.log10()
+            }
+        })
+    }
+}
+
+/// Parses every line of MPC element data that is in either one-line format,
+/// skipping headers and anything else that doesn't parse.
+pub fn parse_mpc_elements(data: &str) -> Vec<OrbitalElements> {
+    data.lines()
+        .filter_map(|line| OrbitalElements::from_mpc_line(line).ok())
+        .collect()
+}
+
+/// Downloads and parses one of the MPC's element files.
+pub async fn fetch_mpc_elements(subset: MpcSubset) -> OverpassPlannerResult<Vec<OrbitalElements>> {
+    let url = match subset {
+        MpcSubset::Comets => MPC_COMETS_URL,
+        MpcSubset::NearEarthAsteroids => MPC_NEA_URL,
+    };
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(60))
+        .build()
+        .map_err(|e| {
+            OverpassPlannerError::NetworkError(format!("Failed to create HTTP client: {e}"))
+        })?;
+    let data = client
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| OverpassPlannerError::NetworkError(format!("Failed to fetch {url}: {e}")))?
+        .text()
+        .await
+        .map_err(|e| OverpassPlannerError::NetworkError(format!("Failed to read {url}: {e}")))?;
+    Ok(parse_mpc_elements(&data))
+}
+
+/// Calculates the apparent position of a comet or asteroid as seen by the observer.
+///
+/// Like [`crate::body_position`] this needs satkit's data files, here for the
+/// Sun's position. Positions are geometric (no light-time correction), which
+/// is good to a few arcseconds for all but the closest approaches.
+pub fn small_body_position(
+    elements: &OrbitalElements,
+    location: ObserverLocation,
+    timestamp: DateTime<Utc>,
+) -> OverpassPlannerResult<SmallBodyPosition> {
+    let instant = to_instant(timestamp);
+    require_eop(&instant)?;
+
+    let sun_gcrf_m =
+        satkit::jplephem::geocentric_pos(satkit::SolarSystem::Sun, &instant).map_err(|e| {
+            OverpassPlannerError::CalculationError(format!("Ephemeris lookup failed: {e}"))
+        })?;
+    let helio_au = elements.heliocentric_position(timestamp);
+    let mut pos_gcrf_m = sun_gcrf_m;
+    for (axis, au) in helio_au.iter().enumerate() {
+        pos_gcrf_m[axis] += au * AU_M;
+    }
+
+    let rot_matrix = frametransform::qgcrf2itrf(&instant).to_rotation_matrix();
+    let pos_itrf_m = rot_matrix * pos_gcrf_m;
+    let (altitude, azimuth, range_m) =
+        itrf_to_alt_az([pos_itrf_m[0], pos_itrf_m[1], pos_itrf_m[2]], location)?;
+
+    let sun_distance_au = helio_au.iter().map(|c| c * c).sum::<f64>().sqrt();
+    Ok(SmallBodyPosition {
+        altitude,
+        azimuth,
+        distance_km: range_m / 1000.0,
+        sun_distance_au,
+        magnitude: elements.magnitude(sun_distance_au, range_m / AU_M),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const CERES: &str = "00001    3.34  0.15 K2555 188.70269   73.27343   80.25221   10.58780  0.0794013  0.21424651   2.7660512  0 E2024-V47  7330 125 1801-2024 0.65 M-v 30k MPCLINUX   4000 (1) Ceres                   20241101";
+    const HALLEY: &str = "0001P         1986 02 09.4589  0.585978  0.967143  111.8657   59.1116  162.1906  20250101   5.5  8.0  1P/Halley                                                98, 1107";
+
+    fn distance(p: [f64; 3]) -> f64 {
+        p.iter().map(|c| c * c).sum::<f64>().sqrt()
+    }
+
+    #[test]
+    fn test_from_mpcorb_line() {
+        let ceres = OrbitalElements::from_mpc_line(CERES).unwrap();
+        assert_eq!(ceres.name, "(1) Ceres");
+        assert_eq!(ceres.kind, SmallBodyKind::Asteroid);
+        assert!((ceres.perihelion_distance - 2.7660512 * (1.0 - 0.0794013)).abs() < 1e-9);
+        assert_eq!(ceres.absolute_magnitude, Some(3.34));
+
+        // At perihelion the body is q from the Sun
+        let r = distance(ceres.heliocentric_position(ceres.perihelion_time));
+        assert!((r - ceres.perihelion_distance).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_from_comet_line() {
+        let halley = OrbitalElements::from_mpc_line(HALLEY).unwrap();
+        assert_eq!(halley.name, "1P/Halley");
+        assert_eq!(halley.kind, SmallBodyKind::Comet);
+        let expected = Utc.with_ymd_and_hms(1986, 2, 9, 11, 1, 0).unwrap();
+        assert!((halley.perihelion_time - expected).num_seconds().abs() < 60);
+        assert_eq!(halley.slope, Some(8.0));
+    }
+
+    #[test]
+    fn test_elliptical_orbit_reaches_aphelion_after_half_a_period() {
+        let ceres = OrbitalElements::from_mpc_line(CERES).unwrap();
+        let a: f64 = 2.7660512;
+        let half_period_days = PI / (GAUSS_K / a.powf(1.5));
+        let t = ceres.perihelion_time + Duration::seconds((half_period_days * 86_400.0) as i64);
+        let r = distance(ceres.heliocentric_position(t));
+        assert!((r - a * (1.0 + 0.0794013)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_parabolic_orbit_follows_barker() {
+        let comet = OrbitalElements {
+            eccentricity: 1.0,
+            ..OrbitalElements::from_mpc_line(HALLEY).unwrap()
+        };
+        // W = 4 gives s = 1: a true anomaly of 90° at twice the perihelion distance
+        let q = comet.perihelion_distance;
+        let days = 4.0 * (2.0 * q.powi(3)).sqrt() / (3.0 * GAUSS_K);
+        let (anomaly, r) = comet.anomaly_and_radius(days);
+        assert!((anomaly - PI / 2.0).abs() < 1e-9);
+        assert!((r - 2.0 * q).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_hyperbolic_orbit_is_close_to_parabolic() {
+        let comet = OrbitalElements::from_mpc_line(HALLEY).unwrap();
+        let parabolic = OrbitalElements {
+            eccentricity: 1.0,
+            ..comet.clone()
+        };
+        let hyperbolic = OrbitalElements {
+            eccentricity: 1.0001,
+            ..comet
+        };
+        let (_, r_parabolic) = parabolic.anomaly_and_radius(30.0);
+        let (_, r_hyperbolic) = hyperbolic.anomaly_and_radius(30.0);
+        assert!((r_parabolic - r_hyperbolic).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_parse_mpc_elements_skips_other_lines() {
+        let data = format!("Header text\n{CERES}\n----------\n{HALLEY}\n");
+        let names: Vec<String> = parse_mpc_elements(&data)
+            .into_iter()
+            .map(|e| e.name)
+            .collect();
+        assert_eq!(names, ["(1) Ceres", "1P/Halley"]);
+    }
+}
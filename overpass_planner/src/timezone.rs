@@ -0,0 +1,119 @@
+//! Timezone formatting module.
+//!
+//! Helpers for showing overpass times in the observer's local time rather than
+//! UTC. Any chrono [`TimeZone`] works (e.g. `chrono::Local` or a `chrono_tz::Tz`);
+//! when no timezone is known, [`ObserverLocation::approximate_timezone`] derives a
+//! fixed offset from the longitude.
+
+use crate::planning::ObserverLocation;
+use crate::Overpass;
+use chrono::{DateTime, FixedOffset, TimeZone, Utc};
+use std::fmt::Display;
+
+impl ObserverLocation {
+    /// Approximate local offset from the longitude (one hour per 15°).
+    ///
+    /// This is the nautical/solar timezone and ignores political boundaries and
+    /// daylight saving time, so prefer an explicit timezone when one is available.
+    pub fn approximate_timezone(&self) -> FixedOffset {
+        longitude_offset(self.longitude)
+    }
+}
+
+/// Fixed UTC offset for a longitude in degrees (one hour per 15°, rounded).
+pub fn longitude_offset(longitude: f64) -> FixedOffset {
+    let hours = (longitude.clamp(-180.0, 180.0) / 15.0).round() as i32;
+    FixedOffset::east_opt(hours * 3600).unwrap_or(FixedOffset::east_opt(0).unwrap())
+}
+
+/// Pass times converted to a timezone.
+#[derive(Debug, Clone)]
+pub struct LocalPassTimes<Tz: TimeZone> {
+    pub start_time: DateTime<Tz>,
+    pub midpoint_time: DateTime<Tz>,
+    pub end_time: DateTime<Tz>,
+}
+
+impl Overpass {
+    /// The start, midpoint and end times of the pass in `tz`.
+    pub fn local_times<Tz: TimeZone>(&self, tz: &Tz) -> LocalPassTimes<Tz> {
+        LocalPassTimes {
+            start_time: self.start_time.with_timezone(tz),
+            midpoint_time: self.midpoint_time.with_timezone(tz),
+            end_time: self.end_time.with_timezone(tz),
+        }
+    }
+}
+
+/// Formats a UTC time as `HH:MM:SS` in `tz`.
+pub fn format_time<Tz: TimeZone>(dt: DateTime<Utc>, tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    dt.with_timezone(tz).format("%H:%M:%S").to_string()
+}
+
+/// Formats a UTC time as `YYYY-MM-DD` in `tz`.
+pub fn format_date<Tz: TimeZone>(dt: DateTime<Utc>, tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    dt.with_timezone(tz).format("%Y-%m-%d").to_string()
+}
+
+/// Formats a UTC time as `YYYY-MM-DD HH:MM:SS ±HH:MM` in `tz`.
+pub fn format_datetime<Tz: TimeZone>(dt: DateTime<Utc>, tz: &Tz) -> String
+where
+    Tz::Offset: Display,
+{
+    dt.with_timezone(tz)
+        .format("%Y-%m-%d %H:%M:%S %:z")
+        .to_string()
+}
+
+/// Short label for a fixed offset, e.g. `UTC-5` or `UTC+5:30`.
+pub fn offset_label(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let hours = seconds.abs() / 3600;
+    let minutes = (seconds.abs() % 3600) / 60;
+    match (seconds, minutes) {
+        (0, _) => "UTC".to_string(),
+        (_, 0) => format!("UTC{sign}{hours}"),
+        _ => format!("UTC{sign}{hours}:{minutes:02}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longitude_offset() {
+        assert_eq!(longitude_offset(-77.17).local_minus_utc(), -5 * 3600);
+        assert_eq!(longitude_offset(0.0).local_minus_utc(), 0);
+        assert_eq!(longitude_offset(139.7).local_minus_utc(), 9 * 3600);
+    }
+
+    #[test]
+    fn test_offset_label() {
+        assert_eq!(offset_label(FixedOffset::east_opt(0).unwrap()), "UTC");
+        assert_eq!(
+            offset_label(FixedOffset::east_opt(-5 * 3600).unwrap()),
+            "UTC-5"
+        );
+        assert_eq!(
+            offset_label(FixedOffset::east_opt(19800).unwrap()),
+            "UTC+5:30"
+        );
+    }
+
+    #[test]
+    fn test_format_time() {
+        use chrono::TimeZone;
+        let dt = Utc.with_ymd_and_hms(2024, 6, 1, 2, 30, 0).unwrap();
+        let est = FixedOffset::east_opt(-5 * 3600).unwrap();
+        assert_eq!(format_time(dt, &est), "21:30:00");
+        assert_eq!(format_date(dt, &est), "2024-05-31");
+    }
+}
@@ -5,14 +5,177 @@
 //!
 //! The module implements caching to reduce API calls. TLE data for all active
 //! satellites is fetched once and cached for 2 hours.
+//!
+//! The HTTP client used to reach CelesTrak can be replaced with [`set_tle_source`],
+//! e.g. to go through a proxy or trust a custom TLS root.
 
-use crate::{OverpassPlannerError, OverpassPlannerResult};
+use crate::{clock, OverpassPlannerError, OverpassPlannerResult};
 use chrono::{DateTime, Duration, Utc};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
 
 const CACHE_FILE_NAME: &str = "tle_cache.txt";
 const TIMESTAMP_FILE_NAME: &str = "tle_cache_timestamp.txt";
-const CACHE_DURATION_HOURS: i64 = 2;
+/// How long downloaded TLEs are trusted before CelesTrak is asked again
+pub const CACHE_DURATION_HOURS: i64 = 2;
+const CELESTRAK_ACTIVE_URL: &str =
+    "https://celestrak.org/NORAD/elements/gp.php?GROUP=active&FORMAT=TLE";
+
+/// Process-wide TLE source used by [`fetch_tle`] and everything built on it.
+static TLE_SOURCE: RwLock<Option<TleSource>> = RwLock::new(None);
+
+/// Where TLE data is loaded from.
+#[derive(Debug, Clone)]
+pub enum TleSource {
+    /// CelesTrak's active satellite catalog, cached on disk and downloaded with
+    /// the given HTTP client.
+    CelesTrak(reqwest::Client),
+    /// TLEs loaded from local files, keyed by NORAD ID.
+    Local(Arc<HashMap<u32, String>>),
+}
+
+impl TleSource {
+    /// CelesTrak with the default HTTP client (30 second timeout).
+    pub fn celestrak() -> OverpassPlannerResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| {
+                OverpassPlannerError::NetworkError(format!("Failed to create HTTP client: {e}"))
+            })?;
+        Ok(Self::CelesTrak(client))
+    }
+
+    /// CelesTrak with a preconfigured HTTP client.
+    ///
+    /// Use this to route requests through a proxy, add custom TLS roots, or share a
+    /// connection pool with the rest of the application.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use overpass_planner::tle::{set_tle_source, TleSource};
+    ///
+    /// # fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = reqwest::Client::builder()
+    ///     .proxy(reqwest::Proxy::all("http://proxy.observatory.lan:3128")?)
+    ///     .build()?;
+    /// set_tle_source(TleSource::with_client(client));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self::CelesTrak(client)
+    }
+
+    /// Loads a standard TLE file (name line followed by lines 1 and 2, as exported
+    /// by CelesTrak, Space-Track and most tracking tools).
+    ///
+    /// Files in the 2-line format without name lines are accepted too; the NORAD ID
+    /// is then used as the satellite name.
+    pub fn from_file(path: impl AsRef<Path>) -> OverpassPlannerResult<Self> {
+        let mut catalog = HashMap::new();
+        load_tle_file(path.as_ref(), &mut catalog)?;
+        Ok(Self::Local(Arc::new(catalog)))
+    }
+
+    /// Loads every `.tle` and `.txt` file in a directory.
+    ///
+    /// When the same NORAD ID appears in several files, the TLE with the newest
+    /// epoch wins.
+    pub fn from_dir(path: impl AsRef<Path>) -> OverpassPlannerResult<Self> {
+        let path = path.as_ref();
+        let entries = std::fs::read_dir(path).map_err(|e| {
+            OverpassPlannerError::TLEError(format!(
+                "Failed to read TLE directory {}: {e}",
+                path.display()
+            ))
+        })?;
+
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| {
+                            ext.eq_ignore_ascii_case("tle") || ext.eq_ignore_ascii_case("txt")
+                        })
+            })
+            .collect();
+        files.sort();
+
+        let mut catalog = HashMap::new();
+        for file in files {
+            load_tle_file(&file, &mut catalog)?;
+        }
+        Ok(Self::Local(Arc::new(catalog)))
+    }
+
+    /// Fetches the TLE for a satellite from this source.
+    ///
+    /// See [`fetch_tle`] for the returned format.
+    pub async fn fetch_tle(&self, norad_id: u32) -> OverpassPlannerResult<String> {
+        match self {
+            TleSource::CelesTrak(client) => fetch_tle_from_celestrak(client, norad_id).await,
+            TleSource::Local(catalog) => catalog.get(&norad_id).cloned().ok_or_else(|| {
+                OverpassPlannerError::ParseError(format!(
+                    "TLE for NORAD ID {} not found in local TLE files",
+                    norad_id
+                ))
+            }),
+        }
+    }
+
+    /// Finds every satellite in this source whose name starts with `name_prefix`
+    /// (case-insensitive), e.g. `"STARLINK"` or `"NOAA"`.
+    ///
+    /// Returns (NORAD ID, TLE) pairs sorted by NORAD ID.
+    pub async fn find_satellites(
+        &self,
+        name_prefix: &str,
+    ) -> OverpassPlannerResult<Vec<(u32, String)>> {
+        let mut entries = match self {
+            TleSource::CelesTrak(client) => {
+                if !is_cache_valid().await || read_cache().await.is_err() {
+                    update_cache(client).await?;
+                }
+                parse_tle_catalog(&read_cache().await?)
+            }
+            TleSource::Local(catalog) => catalog
+                .iter()
+                .map(|(norad_id, tle)| (*norad_id, tle.clone()))
+                .collect(),
+        };
+
+        let prefix = name_prefix.to_uppercase();
+        entries.retain(|(_, tle)| {
+            tle.lines()
+                .next()
+                .is_some_and(|name| name.trim().to_uppercase().starts_with(&prefix))
+        });
+        entries.sort_by_key(|(norad_id, _)| *norad_id);
+        Ok(entries)
+    }
+}
+
+/// Replaces the TLE source used by [`fetch_tle`] for the rest of the process.
+pub fn set_tle_source(source: TleSource) {
+    if let Ok(mut guard) = TLE_SOURCE.write() {
+        *guard = Some(source);
+    }
+}
+
+/// Gets the configured TLE source, falling back to CelesTrak with the default client.
+fn current_tle_source() -> OverpassPlannerResult<TleSource> {
+    if let Ok(guard) = TLE_SOURCE.read() {
+        if let Some(source) = guard.as_ref() {
+            return Ok(source.clone());
+        }
+    }
+    TleSource::celestrak()
+}
 
 /// Gets the cache directory path for storing TLE data.
 fn get_cache_dir() -> OverpassPlannerResult<PathBuf> {
@@ -65,7 +228,7 @@ async fn is_cache_valid() -> bool {
         None => return false,
     };
 
-    let now = Utc::now();
+    let now = clock::now();
     let age = now.signed_duration_since(cache_time);
 
     age < Duration::hours(CACHE_DURATION_HOURS)
@@ -79,8 +242,8 @@ async fn write_cache_timestamp() -> OverpassPlannerResult<()> {
     })?;
 
     let timestamp_path = get_timestamp_file_path()?;
-    let timestamp = Utc::now().timestamp();
-    tokio::fs::write(&timestamp_path, timestamp.to_string())
+    let timestamp = clock::now().timestamp();
+    write_atomically(&timestamp_path, timestamp.to_string().as_bytes())
         .await
         .map_err(|e| {
             OverpassPlannerError::NetworkError(format!("Failed to write timestamp file: {e}"))
@@ -89,19 +252,58 @@ async fn write_cache_timestamp() -> OverpassPlannerResult<()> {
     Ok(())
 }
 
-/// Fetches all active satellites from CelesTrak API.
-async fn fetch_all_active_satellites() -> OverpassPlannerResult<String> {
-    let url = "https://celestrak.org/NORAD/elements/gp.php?GROUP=active&FORMAT=TLE";
+/// Writes a file by writing a temporary file next to it and renaming it into place.
+///
+/// The cache is shared by every process using this crate (e.g. sidereal_gui and
+/// the planetarium), so a reader must never see a half-written file. The rename
+/// is atomic, and concurrent writers simply replace each other's complete file.
+async fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("cache");
+    let temp_path = path.with_file_name(format!(
+        ".{file_name}.{}.{}.tmp",
+        std::process::id(),
+        NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = async {
+        tokio::fs::write(&temp_path, contents).await?;
+        // Flush to disk before the rename so a crash cannot leave a renamed but empty file
+        tokio::fs::OpenOptions::new()
+            .write(true)
+            .open(&temp_path)
+            .await?
+            .sync_all()
+            .await?;
+        tokio::fs::rename(&temp_path, path).await
+    }
+    .await;
 
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| {
-            OverpassPlannerError::NetworkError(format!("Failed to create HTTP client: {e}"))
-        })?;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+    }
+    result
+}
 
+/// Checks that cached TLE data ends with a complete line 2.
+///
+/// Caches written before writes were atomic, or cut short by a full disk, can end
+/// in the middle of an entry.
+fn is_cache_complete(data: &str) -> bool {
+    data.lines()
+        .map(|l| l.trim())
+        .rfind(|l| !l.is_empty())
+        .is_some_and(|last| last.starts_with("2 ") && last.len() == TLE_LINE_LENGTH)
+}
+
+/// Fetches all active satellites from CelesTrak API.
+async fn fetch_all_active_satellites(client: &reqwest::Client) -> OverpassPlannerResult<String> {
     let response = client
-        .get(url)
+        .get(CELESTRAK_ACTIVE_URL)
         .send()
         .await
         .map_err(|e| OverpassPlannerError::NetworkError(format!("HTTP request failed: {e}")))?;
@@ -122,8 +324,14 @@ async fn fetch_all_active_satellites() -> OverpassPlannerResult<String> {
 }
 
 /// Updates the cache by fetching fresh data from the API.
-async fn update_cache() -> OverpassPlannerResult<()> {
-    let tle_data = fetch_all_active_satellites().await?;
+async fn update_cache(client: &reqwest::Client) -> OverpassPlannerResult<()> {
+    let tle_data = fetch_all_active_satellites(client).await?;
+    // Keep the previous cache rather than replacing it with a cut-off download
+    if !is_cache_complete(&tle_data) {
+        return Err(OverpassPlannerError::NetworkError(
+            "Incomplete TLE data received from CelesTrak".to_string(),
+        ));
+    }
 
     // Ensure cache directory exists before writing
     let cache_dir = get_cache_dir()?;
@@ -132,7 +340,7 @@ async fn update_cache() -> OverpassPlannerResult<()> {
     })?;
 
     let cache_file_path = get_cache_file_path()?;
-    tokio::fs::write(&cache_file_path, &tle_data)
+    write_atomically(&cache_file_path, tle_data.as_bytes())
         .await
         .map_err(|e| {
             OverpassPlannerError::NetworkError(format!("Failed to write cache file: {e}"))
@@ -144,6 +352,8 @@ async fn update_cache() -> OverpassPlannerResult<()> {
 }
 
 /// Reads the cached TLE data from disk.
+///
+/// A truncated cache is reported as an error so callers download a fresh copy.
 async fn read_cache() -> OverpassPlannerResult<String> {
     let cache_file_path = get_cache_file_path()?;
     let tle_data = tokio::fs::read_to_string(&cache_file_path)
@@ -152,6 +362,12 @@ async fn read_cache() -> OverpassPlannerResult<String> {
             OverpassPlannerError::NetworkError(format!("Failed to read cache file: {e}"))
         })?;
 
+    if !is_cache_complete(&tle_data) {
+        return Err(OverpassPlannerError::NetworkError(
+            "Cache file is truncated".to_string(),
+        ));
+    }
+
     Ok(tle_data)
 }
 
@@ -213,6 +429,97 @@ fn parse_tle_from_cache(cache_data: &str, norad_id: u32) -> OverpassPlannerResul
     )))
 }
 
+/// Reads a TLE file and adds its entries to `catalog`.
+fn load_tle_file(path: &Path, catalog: &mut HashMap<u32, String>) -> OverpassPlannerResult<()> {
+    let data = std::fs::read_to_string(path).map_err(|e| {
+        OverpassPlannerError::TLEError(format!("Failed to read TLE file {}: {e}", path.display()))
+    })?;
+
+    for (norad_id, tle) in parse_tle_catalog(&data) {
+        match catalog.get(&norad_id) {
+            Some(existing) if tle_epoch_key(existing) >= tle_epoch_key(&tle) => {}
+            _ => {
+                catalog.insert(norad_id, tle);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses every TLE in a 2-line or 3-line TLE file.
+///
+/// Entries that are incomplete or fail [`validate_tle`] are skipped with a
+/// warning, so one corrupt entry doesn't lose the rest of the catalog.
+fn parse_tle_catalog(data: &str) -> Vec<(u32, String)> {
+    let lines: Vec<&str> = data
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    let mut i = 0;
+    while i < lines.len() {
+        let line1 = lines[i];
+        if !(line1.starts_with("1 ") && line1.len() > 7) {
+            i += 1;
+            continue;
+        }
+
+        let Some(line2) = lines.get(i + 1).filter(|l| l.starts_with("2 ")) else {
+            tracing::warn!("Skipping TLE entry with no line 2: {line1}");
+            skipped += 1;
+            i += 1;
+            continue;
+        };
+
+        let Ok(norad_id) = line1[2..7].trim().parse::<u32>() else {
+            tracing::warn!("Skipping TLE entry with an invalid NORAD ID: {line1}");
+            skipped += 1;
+            i += 2;
+            continue;
+        };
+
+        let name = if i > 0 && !lines[i - 1].starts_with("1 ") && !lines[i - 1].starts_with("2 ") {
+            lines[i - 1].trim_start_matches("0 ").to_string()
+        } else {
+            norad_id.to_string()
+        };
+
+        let tle = format!("{}\n{}\n{}", name, line1, line2);
+        match validate_tle(&tle) {
+            Ok(()) => entries.push((norad_id, tle)),
+            Err(e) => {
+                tracing::warn!("Skipping invalid TLE for NORAD ID {norad_id}: {e}");
+                skipped += 1;
+            }
+        }
+        i += 2;
+    }
+
+    if skipped > 0 {
+        tracing::warn!(
+            "Skipped {skipped} invalid TLE entries, kept {}",
+            entries.len()
+        );
+    }
+    entries
+}
+
+/// Sort key for the epoch field of line 1 (two-digit year then day of year).
+fn tle_epoch_key(tle: &str) -> (u32, f64) {
+    let line1 = tle.lines().find(|l| l.starts_with("1 ")).unwrap_or("");
+    let year: u32 = line1.get(18..20).and_then(|y| y.parse().ok()).unwrap_or(0);
+    let day: f64 = line1
+        .get(20..32)
+        .and_then(|d| d.trim().parse().ok())
+        .unwrap_or(0.0);
+    // Two-digit years 57-99 are 1957-1999
+    let year = if year >= 57 { 1900 + year } else { 2000 + year };
+    (year, day)
+}
+
 /// Gets the satellite name for a given NORAD ID.
 ///
 /// This function fetches the TLE and extracts the name from the first line.
@@ -240,9 +547,17 @@ pub async fn get_satellite_name(norad_id: u32) -> OverpassPlannerResult<String>
     ))
 }
 
-/// Fetches the TLE for a satellite from CelesTrak API with caching.
+/// Finds every satellite whose name starts with `name_prefix` in the configured
+/// [`TleSource`].
 ///
-/// This function checks the cache first. If the cache is valid (less than 2 hours old),
+/// See [`TleSource::find_satellites`].
+pub async fn find_satellites(name_prefix: &str) -> OverpassPlannerResult<Vec<(u32, String)>> {
+    current_tle_source()?.find_satellites(name_prefix).await
+}
+
+/// Fetches the TLE for a satellite from the configured [`TleSource`].
+///
+/// By default this is the CelesTrak API with caching. It checks the cache first. If the cache is valid (less than 2 hours old),
 /// it returns the TLE from cache. Otherwise, it fetches fresh data from the API.
 ///
 /// # Arguments
@@ -269,6 +584,14 @@ pub async fn get_satellite_name(norad_id: u32) -> OverpassPlannerResult<String>
 /// # }
 /// ```
 pub async fn fetch_tle(norad_id: u32) -> OverpassPlannerResult<String> {
+    current_tle_source()?.fetch_tle(norad_id).await
+}
+
+/// Fetches a TLE from CelesTrak through the on-disk cache.
+async fn fetch_tle_from_celestrak(
+    client: &reqwest::Client,
+    norad_id: u32,
+) -> OverpassPlannerResult<String> {
     // Check if cache is valid
     if is_cache_valid().await {
         // Try to read from cache
@@ -288,22 +611,85 @@ pub async fn fetch_tle(norad_id: u32) -> OverpassPlannerResult<String> {
     }
 
     // Cache is invalid or TLE not found, update cache
-    update_cache().await?;
+    update_cache(client).await?;
 
-    // Read from updated cache
+    // Read from updated cache; the active catalog drops satellites once they decay
     let cache_data = read_cache().await?;
-    parse_tle_from_cache(&cache_data, norad_id)
+    parse_tle_from_cache(&cache_data, norad_id).map_err(|e| match e {
+        OverpassPlannerError::ParseError(_) => OverpassPlannerError::Decayed(format!(
+            "NORAD ID {} is not in CelesTrak's active catalog; it may have decayed or been deorbited",
+            norad_id
+        )),
+        e => e,
+    })
+}
+
+/// A TLE field checked by [`validate_tle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TleField {
+    /// The name, line 1 or line 2 is missing
+    Structure,
+    /// Line length (69 characters)
+    LineLength { line: u8 },
+    /// Mod-10 checksum in column 69
+    Checksum { line: u8 },
+    /// Satellite catalog number (columns 3-7)
+    SatelliteNumber { line: u8 },
+    /// Classification (column 8 of line 1)
+    Classification,
+    /// Epoch year and day (columns 19-32 of line 1)
+    Epoch,
+    /// Inclination (columns 9-16 of line 2)
+    Inclination,
+    /// Right ascension of the ascending node (columns 18-25 of line 2)
+    RightAscension,
+    /// Eccentricity (columns 27-33 of line 2)
+    Eccentricity,
+    /// Argument of perigee (columns 35-42 of line 2)
+    ArgumentOfPerigee,
+    /// Mean anomaly (columns 44-51 of line 2)
+    MeanAnomaly,
+    /// Mean motion (columns 53-63 of line 2)
+    MeanMotion,
 }
 
+impl std::fmt::Display for TleField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TleField::Structure => write!(f, "structure"),
+            TleField::LineLength { line } => write!(f, "line {line} length"),
+            TleField::Checksum { line } => write!(f, "line {line} checksum"),
+            TleField::SatelliteNumber { line } => write!(f, "line {line} satellite number"),
+            TleField::Classification => write!(f, "classification"),
+            TleField::Epoch => write!(f, "epoch"),
+            TleField::Inclination => write!(f, "inclination"),
+            TleField::RightAscension => write!(f, "right ascension of the ascending node"),
+            TleField::Eccentricity => write!(f, "eccentricity"),
+            TleField::ArgumentOfPerigee => write!(f, "argument of perigee"),
+            TleField::MeanAnomaly => write!(f, "mean anomaly"),
+            TleField::MeanMotion => write!(f, "mean motion"),
+        }
+    }
+}
+
+/// Length of a TLE data line.
+const TLE_LINE_LENGTH: usize = 69;
+
 /// Validates that the response contains valid TLE data.
 ///
 /// Expects the format:
-/// ```
+/// ```text
 /// SATELLITE NAME
-/// 1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1234
-/// 2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12345
+/// 1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1239
+/// 2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12346
 /// ```
-fn validate_tle(text: &str) -> OverpassPlannerResult<()> {
+///
+/// Both data lines must be 69 characters long with a valid mod-10 checksum, and
+/// the orbital elements must be in range.
+///
+/// # Errors
+/// Returns `OverpassPlannerError::InvalidTle` naming the first field that failed.
+pub fn validate_tle(text: &str) -> OverpassPlannerResult<()> {
     let lines: Vec<&str> = text
         .lines()
         .map(|l| l.trim())
@@ -311,39 +697,147 @@ fn validate_tle(text: &str) -> OverpassPlannerResult<()> {
         .collect();
 
     if lines.len() < 3 {
-        return Err(OverpassPlannerError::ParseError(format!(
-            "Expected at least 3 lines in TLE response, got {}",
-            lines.len()
-        )));
+        return Err(invalid(
+            TleField::Structure,
+            format!(
+                "Expected at least 3 lines in TLE response, got {}",
+                lines.len()
+            ),
+        ));
     }
 
     // Check that we have TLE lines (they start with "1 " and "2 ")
-    let mut has_line1 = false;
-    let mut has_line2 = false;
-
-    for line in lines {
-        if line.starts_with("1 ") {
-            has_line1 = true;
-        } else if line.starts_with("2 ") {
-            has_line2 = true;
-        }
+    let line1 = lines
+        .iter()
+        .find(|l| l.starts_with("1 "))
+        .ok_or_else(|| invalid(TleField::Structure, "TLE line 1 not found in response"))?;
+    let line2 = lines
+        .iter()
+        .find(|l| l.starts_with("2 "))
+        .ok_or_else(|| invalid(TleField::Structure, "TLE line 2 not found in response"))?;
+
+    validate_tle_line(line1, 1)?;
+    validate_tle_line(line2, 2)?;
+
+    let satellite_number = &line1[2..7];
+    if !satellite_number
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == ' ')
+        || satellite_number.trim().is_empty()
+    {
+        return Err(invalid(
+            TleField::SatelliteNumber { line: 1 },
+            format!("'{satellite_number}' is not a catalog number"),
+        ));
+    }
+    if &line2[2..7] != satellite_number {
+        return Err(invalid(
+            TleField::SatelliteNumber { line: 2 },
+            format!(
+                "'{}' does not match line 1 ('{satellite_number}')",
+                &line2[2..7]
+            ),
+        ));
     }
 
-    if !has_line1 {
-        return Err(OverpassPlannerError::ParseError(
-            "TLE line 1 not found in response".to_string(),
+    if !matches!(&line1[7..8], "U" | "C" | "S" | " ") {
+        return Err(invalid(
+            TleField::Classification,
+            format!("'{}' is not U, C or S", &line1[7..8]),
         ));
     }
 
-    if !has_line2 {
-        return Err(OverpassPlannerError::ParseError(
-            "TLE line 2 not found in response".to_string(),
+    let epoch_year = &line1[18..20];
+    if !epoch_year.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid(
+            TleField::Epoch,
+            format!("'{epoch_year}' is not a two-digit year"),
         ));
     }
+    parse_field(line1, 20..32, TleField::Epoch, 1.0..=367.0)?;
+
+    parse_field(line2, 8..16, TleField::Inclination, 0.0..=180.0)?;
+    parse_field(line2, 17..25, TleField::RightAscension, 0.0..=360.0)?;
+    let eccentricity = &line2[26..33];
+    if !eccentricity.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid(
+            TleField::Eccentricity,
+            format!("'{eccentricity}' is not a decimal fraction"),
+        ));
+    }
+    parse_field(line2, 34..42, TleField::ArgumentOfPerigee, 0.0..=360.0)?;
+    parse_field(line2, 43..51, TleField::MeanAnomaly, 0.0..=360.0)?;
+    parse_field(
+        line2,
+        52..63,
+        TleField::MeanMotion,
+        f64::MIN_POSITIVE..=20.0,
+    )?;
 
     Ok(())
 }
 
+/// Checks the length and checksum of a TLE data line.
+fn validate_tle_line(line: &str, number: u8) -> OverpassPlannerResult<()> {
+    if line.len() != TLE_LINE_LENGTH || !line.is_ascii() {
+        return Err(invalid(
+            TleField::LineLength { line: number },
+            format!(
+                "Expected {TLE_LINE_LENGTH} ASCII characters, got {}",
+                line.chars().count()
+            ),
+        ));
+    }
+
+    let expected = tle_checksum(line);
+    let found = line[68..69].parse::<u32>().ok();
+    if found != Some(expected) {
+        return Err(invalid(
+            TleField::Checksum { line: number },
+            format!("Expected {expected}, found '{}'", &line[68..69]),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Mod-10 checksum of the first 68 columns of a TLE line: the sum of all digits,
+/// with each minus sign counting as 1.
+fn tle_checksum(line: &str) -> u32 {
+    line.chars()
+        .take(TLE_LINE_LENGTH - 1)
+        .map(|c| match c {
+            '-' => 1,
+            c => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>()
+        % 10
+}
+
+/// Parses a numeric TLE field and checks that it lies within `range`.
+fn parse_field(
+    line: &str,
+    columns: std::ops::Range<usize>,
+    field: TleField,
+    range: std::ops::RangeInclusive<f64>,
+) -> OverpassPlannerResult<()> {
+    let text = line[columns].trim();
+    let value: f64 = text
+        .parse()
+        .map_err(|_| invalid(field, format!("'{text}' is not a number")))?;
+    if !range.contains(&value) {
+        return Err(invalid(
+            field,
+            format!("{value} is outside [{}, {}]", range.start(), range.end()),
+        ));
+    }
+    Ok(())
+}
+
+fn invalid(field: TleField, message: impl Into<String>) -> OverpassPlannerError {
+    OverpassPlannerError::InvalidTle(field, message.into())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -351,8 +845,8 @@ mod tests {
     #[test]
     fn test_validate_tle() {
         let tle_text = r#"ISS (ZARYA)
-1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1234
-2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12345"#;
+1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1239
+2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12346"#;
 
         assert!(validate_tle(tle_text).is_ok());
     }
@@ -363,14 +857,50 @@ mod tests {
         assert!(validate_tle(invalid_text).is_err());
     }
 
+    #[test]
+    fn test_validate_tle_reports_field() {
+        let valid = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537";
+        assert!(validate_tle(valid).is_ok());
+
+        let bad_checksum = valid.replace("0  2927", "0  2928");
+        assert!(matches!(
+            validate_tle(&bad_checksum),
+            Err(OverpassPlannerError::InvalidTle(
+                TleField::Checksum { line: 1 },
+                _
+            ))
+        ));
+
+        let truncated = valid.replace("15.72125391563537", "15.7212539156353");
+        assert!(matches!(
+            validate_tle(&truncated),
+            Err(OverpassPlannerError::InvalidTle(
+                TleField::LineLength { line: 2 },
+                _
+            ))
+        ));
+
+        // Offset the digit changes so the checksum stays valid
+        let mismatched = valid.replace("2 25544  51.6416", "2 25545  51.6415");
+        assert!(matches!(
+            validate_tle(&mismatched),
+            Err(OverpassPlannerError::InvalidTle(
+                TleField::SatelliteNumber { line: 2 },
+                _
+            ))
+        ));
+    }
+
     #[test]
     fn test_parse_tle_from_cache() {
         let cache_data = r#"ISS (ZARYA)
-1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1234
-2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12345
+1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1239
+2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12346
 ANOTHER SATELLITE
-1 25551U 98067B   12345.67890123  .00001234  00000-0  12345-4 0  1234
-2 25551  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12345"#;
+1 25551U 98067B   12345.67890123  .00001234  00000-0  12345-4 0  1237
+2 25551  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12344"#;
 
         let result = parse_tle_from_cache(cache_data, 25544);
         assert!(result.is_ok());
@@ -390,10 +920,79 @@ ANOTHER SATELLITE
         assert!(result3.is_err());
     }
 
-    #[tokio::test]
-    async fn test_fetch_tle_api() {
+    #[test]
+    fn test_is_cache_complete() {
+        let cache_data = "ISS (ZARYA)
+1 25544U 98067A   08264.51782528 -.00002182  00000-0 -11606-4 0  2927
+2 25544  51.6416 247.4627 0006703 130.5360 325.0288 15.72125391563537
+";
+        assert!(is_cache_complete(cache_data));
+        assert!(!is_cache_complete(&cache_data[..cache_data.len() - 10]));
+        assert!(!is_cache_complete(&cache_data[..80]));
+        assert!(!is_cache_complete(""));
+    }
+
+    #[test]
+    fn test_parse_tle_catalog() {
+        let data = r#"ISS (ZARYA)
+1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1239
+2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12346
+
+1 25551U 98067B   12345.67890123  .00001234  00000-0  12345-4 0  1237
+2 25551  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12344"#;
+
+        let entries = parse_tle_catalog(data);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, 25544);
+        assert!(entries[0].1.starts_with("ISS (ZARYA)\n"));
+        // 2-line entries fall back to the NORAD ID as the name
+        assert_eq!(entries[1].0, 25551);
+        assert!(entries[1].1.starts_with("25551\n"));
+    }
+
+    #[test]
+    fn test_parse_tle_catalog_skips_invalid() {
+        // a bad line 2 checksum, then a good entry, then a line 1 with no line 2
+        let data = r#"BAD CHECKSUM
+1 25544U 98067A   12345.67890123  .00001234  00000-0  12345-4 0  1239
+2 25544  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12340
+GOOD
+1 25551U 98067B   12345.67890123  .00001234  00000-0  12345-4 0  1237
+2 25551  51.6450 123.4567 0001234 234.5678 123.4567 15.12345678 12344
+CUT SHORT
+1 25552U 98067B   12345.67890123  .00001234  00000-0  12345-4 0  1238"#;
+
+        let entries = parse_tle_catalog(data);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].0, 25551);
+        assert!(entries[0].1.starts_with("GOOD\n"));
+
+        // the single-TLE lookup still refuses the corrupt one
+        assert!(parse_tle_from_cache(data, 25544).is_err());
+    }
+
+    #[test]
+    fn test_tle_epoch_key() {
+        let older =
+            "X\n1 25544U 98067A   98345.67890123  .00001234  00000-0  12345-4 0  1234\n2 25544";
+        let newer =
+            "X\n1 25544U 98067A   12001.00000000  .00001234  00000-0  12345-4 0  1234\n2 25544";
+        assert!(tle_epoch_key(newer) > tle_epoch_key(older));
+    }
+
+    #[test]
+    fn test_fetch_tle_api() {
+        // Another test may have swapped in a local TLE source; wait it out
+        let _globals = crate::GLOBALS_LOCK
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
         // Test with ISS NORAD ID (25544)
-        let result = fetch_tle(25544).await;
+        let result = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(fetch_tle(25544));
 
         assert!(
             result.is_ok(),
@@ -0,0 +1,378 @@
+//! Solar and lunar transit prediction module.
+//!
+//! This module finds the moments when a satellite crosses (or passes close to)
+//! the disc of the Sun or Moon as seen from the observer, e.g. for ISS transit
+//! photography. Transits last around a second, so passes are sampled every second
+//! and each close approach is refined to millisecond precision.
+
+use crate::planning::{angular_separation, body_position, CelestialBody, ObserverLocation};
+use crate::propagator::Propagator;
+use crate::{clock, load_propagation_context, search_overpasses, OverpassPlannerResult, PassKind};
+use chrono::{DateTime, Duration, Utc};
+
+/// Extra separation (degrees) beyond the limb that a coarse sample may be at and
+/// still bracket a close approach. LEO satellites move up to ~1.5°/s across the sky.
+const COARSE_SEPARATION_SLACK: f64 = 2.0;
+
+/// A close approach of a satellite to the solar or lunar disc.
+#[derive(Debug, Clone)]
+pub struct Transit {
+    /// The body being transited
+    pub body: CelestialBody,
+    /// Time of closest approach
+    pub time: DateTime<Utc>,
+    /// Center-to-center separation at closest approach (degrees)
+    pub separation: f64,
+    /// Apparent angular radius of the body's disc (degrees)
+    pub body_radius: f64,
+    /// Whether the satellite actually crosses the disc
+    pub is_transit: bool,
+    /// Time the satellite enters the disc, if it crosses it
+    pub ingress_time: Option<DateTime<Utc>>,
+    /// Time the satellite leaves the disc, if it crosses it
+    pub egress_time: Option<DateTime<Utc>>,
+    /// Altitude of the body at closest approach (degrees)
+    pub altitude: f64,
+    /// Azimuth of the body at closest approach (degrees)
+    pub azimuth: f64,
+}
+
+/// Find solar or lunar transits of a satellite within a specified time window.
+///
+/// # Arguments
+/// * `norad_id` - The NORAD ID of the satellite
+/// * `location` - Observer's location on Earth
+/// * `time_from_now` - Duration from now to search for transits
+/// * `body` - Whether to look for solar or lunar transits
+/// * `limb_margin` - Also report near misses passing within this many degrees of the limb
+///
+/// # Returns
+/// A vector of close approaches ordered by time; `is_transit` tells real transits
+/// apart from near misses.
+pub async fn find_transits(
+    norad_id: u32,
+    location: ObserverLocation,
+    time_from_now: Duration,
+    body: CelestialBody,
+    limb_margin: f64,
+) -> OverpassPlannerResult<Vec<Transit>> {
+    let start_time = clock::now();
+    let end_time = start_time + time_from_now;
+
+    let ctx = load_propagation_context(norad_id, start_time).await?;
+
+    search_transits(&ctx, location, start_time, end_time, body, limb_margin)
+}
+
+/// Search for transits of an already-parsed satellite between two instants.
+pub(crate) fn search_transits(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    body: CelestialBody,
+    limb_margin: f64,
+) -> OverpassPlannerResult<Vec<Transit>> {
+    let sample_step = Duration::seconds(1);
+    let mut transits = Vec::new();
+
+    for overpass in search_overpasses(propagator, location, start_time, end_time, &|_| {})? {
+        if overpass.kind == PassKind::NeverVisible {
+            continue;
+        }
+
+        // Skip passes where the body stays below the horizon
+        let body_up = [
+            overpass.start_time,
+            overpass.midpoint_time,
+            overpass.end_time,
+        ]
+        .into_iter()
+        .map(|t| body_position(body, location, t).map(|p| p.altitude > 0.0))
+        .collect::<OverpassPlannerResult<Vec<_>>>()?;
+        if !body_up.into_iter().any(|up| up) {
+            continue;
+        }
+
+        // Coarse scan at 1 second resolution
+        let mut samples = Vec::new();
+        let mut current_time = overpass.start_time;
+        while current_time <= overpass.end_time {
+            let (separation, radius) = separation_at(propagator, location, body, current_time)?;
+            samples.push((current_time, separation, radius));
+            current_time += sample_step;
+        }
+
+        // Refine every local minimum that is close enough to matter
+        for i in 0..samples.len() {
+            let (time, separation, radius) = samples[i];
+            let prev = if i > 0 { samples[i - 1].1 } else { f64::MAX };
+            let next = samples.get(i + 1).map_or(f64::MAX, |s| s.1);
+            if separation > prev
+                || separation > next
+                || separation > radius + limb_margin + COARSE_SEPARATION_SLACK
+            {
+                continue;
+            }
+
+            let lo = (time - sample_step).max(overpass.start_time);
+            let hi = (time + sample_step).min(overpass.end_time);
+            let closest = find_closest_approach(propagator, location, body, lo, hi)?;
+            let (separation, radius) = separation_at(propagator, location, body, closest)?;
+            if separation > radius + limb_margin {
+                continue;
+            }
+
+            let position = body_position(body, location, closest)?;
+            if position.altitude <= 0.0 {
+                continue;
+            }
+
+            let is_transit = separation < radius;
+            let (ingress_time, egress_time) = if is_transit {
+                (
+                    Some(find_limb_crossing(
+                        propagator,
+                        location,
+                        body,
+                        closest,
+                        overpass.start_time,
+                    )?),
+                    Some(find_limb_crossing(
+                        propagator,
+                        location,
+                        body,
+                        closest,
+                        overpass.end_time,
+                    )?),
+                )
+            } else {
+                (None, None)
+            };
+
+            transits.push(Transit {
+                body,
+                time: closest,
+                separation,
+                body_radius: radius,
+                is_transit,
+                ingress_time,
+                egress_time,
+                altitude: position.altitude,
+                azimuth: position.azimuth,
+            });
+        }
+    }
+
+    Ok(transits)
+}
+
+/// Separation between the satellite and the body's center, and the body's radius (degrees).
+fn separation_at(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    body: CelestialBody,
+    timestamp: DateTime<Utc>,
+) -> OverpassPlannerResult<(f64, f64)> {
+    let (sat_alt, sat_az) = propagator.alt_az(location, timestamp)?;
+    let position = body_position(body, location, timestamp)?;
+    let separation = angular_separation(sat_alt, sat_az, position.altitude, position.azimuth);
+    Ok((separation, position.angular_radius))
+}
+
+/// Find the time of minimum separation in `[lo, hi]` using golden section search.
+fn find_closest_approach(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    body: CelestialBody,
+    lo: DateTime<Utc>,
+    hi: DateTime<Utc>,
+) -> OverpassPlannerResult<DateTime<Utc>> {
+    let inv_phi = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let mut a = lo;
+    let mut b = hi;
+
+    // Iterate down to millisecond resolution
+    while (b - a).num_milliseconds() > 1 {
+        let span_ms = (b - a).num_milliseconds() as f64;
+        let c = b - Duration::milliseconds((span_ms * inv_phi) as i64);
+        let d = a + Duration::milliseconds((span_ms * inv_phi) as i64);
+        let (sep_c, _) = separation_at(propagator, location, body, c)?;
+        let (sep_d, _) = separation_at(propagator, location, body, d)?;
+
+        if sep_c < sep_d {
+            b = d;
+        } else {
+            a = c;
+        }
+    }
+
+    Ok(a + (b - a) / 2)
+}
+
+/// Find when the satellite crosses the limb between `inside` (on the disc) and
+/// `outside`, searching outward from closest approach using bisection.
+fn find_limb_crossing(
+    propagator: &dyn Propagator,
+    location: ObserverLocation,
+    body: CelestialBody,
+    inside: DateTime<Utc>,
+    limit: DateTime<Utc>,
+) -> OverpassPlannerResult<DateTime<Utc>> {
+    let forward = limit > inside;
+    let mut inside = inside;
+
+    // Step outward until the satellite is off the disc, doubling each time
+    let mut step = Duration::milliseconds(100);
+    let mut outside = inside;
+    loop {
+        let candidate = if forward {
+            (outside + step).min(limit)
+        } else {
+            (outside - step).max(limit)
+        };
+        let (separation, radius) = separation_at(propagator, location, body, candidate)?;
+        if separation >= radius || candidate == limit {
+            outside = candidate;
+            break;
+        }
+        inside = candidate;
+        outside = candidate;
+        step = step * 2;
+    }
+
+    // Bisect down to millisecond resolution
+    while (outside - inside).num_milliseconds().abs() > 1 {
+        let mid = inside + (outside - inside) / 2;
+        let (separation, radius) = separation_at(propagator, location, body, mid)?;
+        if separation < radius {
+            inside = mid;
+        } else {
+            outside = mid;
+        }
+    }
+
+    Ok(inside)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Washington DC
+    const LOCATION: ObserverLocation = ObserverLocation {
+        latitude: 38.8892,
+        longitude: -77.1664,
+        altitude: 0.0,
+    };
+
+    /// Sun well up over Washington DC
+    fn midday() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 20, 16, 0, 0).unwrap()
+    }
+
+    /// Stand-in satellite that passes `offset` degrees above the centre of
+    /// `body` at `closest`, moving across the sky at `rate` degrees a second,
+    /// and is only above the horizon for the three minutes either side.
+    struct Crossing {
+        body: CelestialBody,
+        closest: DateTime<Utc>,
+        offset: f64,
+        rate: f64,
+    }
+
+    impl Propagator for Crossing {
+        fn position_itrf(&self, _timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+            Ok([7.0e6, 0.0, 0.0])
+        }
+
+        fn position_inertial(&self, _timestamp: DateTime<Utc>) -> OverpassPlannerResult<[f64; 3]> {
+            Ok([7000.0, 0.0, 0.0])
+        }
+
+        fn alt_az(
+            &self,
+            location: ObserverLocation,
+            timestamp: DateTime<Utc>,
+        ) -> OverpassPlannerResult<(f64, f64)> {
+            let seconds = (timestamp - self.closest).num_milliseconds() as f64 / 1000.0;
+            if seconds.abs() > 180.0 {
+                return Ok((-10.0, 0.0));
+            }
+            let body = body_position(self.body, location, timestamp)?;
+            let altitude = body.altitude + self.offset;
+            // level with the body, so the separation is `offset` at `closest`
+            let azimuth = body.azimuth + self.rate * seconds / altitude.to_radians().cos();
+            Ok((altitude, azimuth))
+        }
+    }
+
+    #[test]
+    fn test_solar_transit() {
+        let closest = midday();
+        let satellite = Crossing {
+            body: CelestialBody::Sun,
+            closest,
+            offset: 0.1,
+            rate: 0.5,
+        };
+        let transits = search_transits(
+            &satellite,
+            LOCATION,
+            closest - Duration::minutes(10),
+            closest + Duration::minutes(10),
+            CelestialBody::Sun,
+            0.0,
+        )
+        .unwrap();
+
+        assert_eq!(transits.len(), 1);
+        let transit = &transits[0];
+        assert!(transit.is_transit);
+        assert!((transit.time - closest).num_milliseconds().abs() <= 10);
+        assert!((transit.separation - 0.1).abs() < 0.001);
+
+        // on the disc for the chord 0.1° off centre, crossed at 0.5°/s
+        let half_chord_ms =
+            ((transit.body_radius.powi(2) - 0.1_f64.powi(2)).sqrt() / 0.5 * 1000.0) as i64;
+        let ingress = transit.ingress_time.unwrap();
+        let egress = transit.egress_time.unwrap();
+        assert!(((closest - ingress).num_milliseconds() - half_chord_ms).abs() <= 20);
+        assert!(((egress - closest).num_milliseconds() - half_chord_ms).abs() <= 20);
+    }
+
+    #[test]
+    fn test_solar_near_miss() {
+        let closest = midday();
+        let satellite = Crossing {
+            body: CelestialBody::Sun,
+            closest,
+            offset: 1.0,
+            rate: 0.5,
+        };
+        let search = |limb_margin| {
+            search_transits(
+                &satellite,
+                LOCATION,
+                closest - Duration::minutes(10),
+                closest + Duration::minutes(10),
+                CelestialBody::Sun,
+                limb_margin,
+            )
+            .unwrap()
+        };
+
+        // reported as a miss when the margin reaches it
+        let misses = search(1.0);
+        assert_eq!(misses.len(), 1);
+        let miss = &misses[0];
+        assert!(!miss.is_transit);
+        assert!((miss.time - closest).num_milliseconds().abs() <= 10);
+        assert!((miss.separation - 1.0).abs() < 0.001);
+        assert!(miss.ingress_time.is_none() && miss.egress_time.is_none());
+
+        // and not at all when it doesn't
+        assert!(search(0.5).is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+// src/all_satellites.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use overpass_planner::{is_satellite_lit, tle::find_satellites, PropagationContext, Propagator};
+use std::sync::{
+    mpsc::{channel, Receiver},
+    Mutex,
+};
+
+use crate::{
+    all_sky::perspective_view,
+    colors,
+    satellite::{observer_location, PATH_DISTANCE},
+    starfield::{alt_az_direction, SkyLayers, StarfieldState},
+};
+
+/// Seconds between recomputing where every satellite is; they crawl across
+/// the sky slowly enough that a marker a few seconds stale is still useful
+const REFRESH_SECS: f32 = 3.0;
+/// How close (pixels) the pointer must be to a marker to name it
+const HOVER_RADIUS: f32 = 10.0;
+
+/// One satellite from the TLE catalog
+struct CatalogSatellite {
+    norad_id: u32,
+    name: String,
+    propagator: PropagationContext,
+}
+
+/// A satellite above the horizon at the last refresh
+struct VisibleSatellite {
+    /// Index into `AllSatellites::satellites`
+    index: usize,
+    altitude: f64,
+    azimuth: f64,
+    lit: bool,
+}
+
+type LoadResult = Result<Vec<CatalogSatellite>, String>;
+
+/// Every satellite in the cached TLE catalog, or one group of it, for the
+/// View > All Satellites layer
+#[derive(Resource)]
+pub struct AllSatellites {
+    /// Name prefix to limit the layer to, e.g. "STARLINK"; empty for all
+    pub group: String,
+    satellites: Vec<CatalogSatellite>,
+    visible: Vec<VisibleSatellite>,
+    /// Load the catalog again, for a new group, once the layer is on
+    reload: bool,
+    /// Catalog being read on a background thread
+    loading: Option<Mutex<Receiver<LoadResult>>>,
+    pub error: Option<String>,
+    refresh: Timer,
+    /// Place newly loaded satellites without waiting for the next refresh
+    stale: bool,
+}
+
+impl Default for AllSatellites {
+    fn default() -> Self {
+        AllSatellites {
+            group: String::new(),
+            satellites: Vec::new(),
+            visible: Vec::new(),
+            reload: true,
+            loading: None,
+            error: None,
+            refresh: Timer::from_seconds(REFRESH_SECS, TimerMode::Repeating),
+            stale: false,
+        }
+    }
+}
+
+impl AllSatellites {
+    /// Read the catalog again for the current group
+    pub fn request_reload(&mut self) {
+        self.reload = true;
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// (satellites loaded, how many are above the horizon)
+    pub fn counts(&self) -> (usize, usize) {
+        (self.satellites.len(), self.visible.len())
+    }
+}
+
+pub struct AllSatellitesPlugin;
+impl Plugin for AllSatellitesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AllSatellites>().add_systems(
+            Update,
+            (
+                load_all_satellites,
+                update_all_satellites,
+                draw_all_satellites.run_if(perspective_view),
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Read the TLE catalog off the main thread the first time the layer is
+/// shown, and again whenever the group changes
+fn load_all_satellites(layers: Res<SkyLayers>, mut all: ResMut<AllSatellites>) {
+    if let Some(receiver) = &all.loading {
+        let result = match receiver.lock() {
+            Ok(receiver) => match receiver.try_recv() {
+                Ok(result) => result,
+                Err(_) => return,
+            },
+            Err(_) => return,
+        };
+        all.loading = None;
+        match result {
+            Ok(satellites) => {
+                all.satellites = satellites;
+                all.error = None;
+            }
+            Err(e) => {
+                all.satellites.clear();
+                all.error = Some(e);
+            }
+        }
+        all.visible.clear();
+        all.stale = true;
+        return;
+    }
+
+    if !layers.all_satellites || !all.reload {
+        return;
+    }
+    all.reload = false;
+
+    let group = all.group.trim().to_string();
+    let (tx, rx) = channel();
+    all.loading = Some(Mutex::new(rx));
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt
+            .block_on(find_satellites(&group))
+            .map_err(|e| e.to_string())
+            .and_then(|found| {
+                if found.is_empty() {
+                    return Err(format!("No satellites named {group}..."));
+                }
+                // decayed or malformed elements are left out
+                Ok(found
+                    .into_iter()
+                    .filter_map(|(norad_id, tle)| {
+                        let name = tle.lines().next().unwrap_or_default().trim().to_string();
+                        let propagator = PropagationContext::from_tle(&tle).ok()?;
+                        Some(CatalogSatellite {
+                            norad_id,
+                            name,
+                            propagator,
+                        })
+                    })
+                    .collect())
+            });
+        let _ = tx.send(result);
+    });
+}
+
+/// Propagate every loaded satellite to the simulated time every few seconds
+fn update_all_satellites(
+    time: Res<Time>,
+    layers: Res<SkyLayers>,
+    state: Res<StarfieldState>,
+    mut all: ResMut<AllSatellites>,
+) {
+    if !layers.all_satellites {
+        return;
+    }
+    if !all.refresh.tick(time.delta()).just_finished() && !all.stale {
+        return;
+    }
+    all.stale = false;
+
+    let location = observer_location(&state);
+    let now = state.current_utc();
+    let visible = all
+        .satellites
+        .iter()
+        .enumerate()
+        .filter_map(|(index, sat)| {
+            let (altitude, azimuth) = sat.propagator.alt_az(location, now).ok()?;
+            (altitude > 0.0).then(|| VisibleSatellite {
+                index,
+                altitude,
+                azimuth,
+                lit: is_satellite_lit(&sat.propagator, now).unwrap_or(false),
+            })
+        })
+        .collect();
+    all.visible = visible;
+}
+
+/// Faint dots for the satellites above the horizon, sunlit ones in green,
+/// with the one under the pointer named
+fn draw_all_satellites(
+    layers: Res<SkyLayers>,
+    all: Res<AllSatellites>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if !layers.all_satellites || all.visible.is_empty() {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+    let pointer = ctx.pointer_hover_pos();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+
+    let mut hovered: Option<(f32, egui::Pos2, &CatalogSatellite)> = None;
+    for sat in &all.visible {
+        let world =
+            cam_gtf.translation() + alt_az_direction(sat.altitude, sat.azimuth) * PATH_DISTANCE;
+        let Ok(pos) = camera.world_to_viewport(cam_gtf, world) else {
+            continue;
+        };
+        let center = egui::pos2(pos.x, pos.y);
+        let color = if sat.lit {
+            colors::egui::GREEN_TEXT
+        } else {
+            colors::egui::SATELLITE_SHADOW
+        };
+        painter.circle_filled(center, 1.5, color.gamma_multiply(0.6));
+
+        if let Some(distance) = pointer.map(|p| p.distance(center)) {
+            if distance < HOVER_RADIUS && hovered.is_none_or(|(best, _, _)| distance < best) {
+                hovered = Some((distance, center, &all.satellites[sat.index]));
+            }
+        }
+    }
+
+    if let Some((_, center, sat)) = hovered {
+        painter.circle_stroke(
+            center,
+            4.0,
+            egui::Stroke::new(1.0, colors::egui::WINDOW_TITLE_COLOR),
+        );
+        painter.text(
+            center + egui::vec2(8.0, -8.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{} ({})", sat.name, sat.norad_id),
+            egui::FontId::proportional(12.0),
+            colors::egui::WINDOW_TITLE_COLOR,
+        );
+    }
+}
@@ -0,0 +1,250 @@
+// src/all_sky.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use std::f32::consts::FRAC_PI_2;
+
+use crate::{
+    colors,
+    planets::Planet,
+    satellite::{PredictedPath, TrackedSatellite, TrackedSatellitePosition},
+    slew_limits::ForbiddenCells,
+    small_bodies::SmallBody,
+    starfield::{
+        alt_az_direction, star_direction, Constellations, SkyLayers, StarIndex, StarfieldState,
+    },
+    twilight::Twilight,
+};
+
+/// Gap (pixels) between the horizon circle and the edge of the window
+const CHART_MARGIN: f32 = 40.0;
+
+/// Faintest star plotted; the chart is small, so fainter ones are just noise
+const CHART_STAR_LIMIT: f32 = 6.0;
+
+/// Altitude circles drawn inside the horizon (degrees)
+const ALTITUDE_RINGS: [f64; 2] = [30.0, 60.0];
+
+/// Whole-sky circular view with the zenith at the center, in place of the
+/// perspective camera. North is up and east is left, as seen looking up and
+/// as an all-sky camera frames it.
+#[derive(Resource, Default)]
+pub struct AllSkyView {
+    pub active: bool,
+}
+
+pub struct AllSkyPlugin;
+impl Plugin for AllSkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AllSkyView>()
+            .add_systems(Update, draw_all_sky.run_if(all_sky_view));
+    }
+}
+
+/// Run condition for the all-sky chart
+fn all_sky_view(view: Res<AllSkyView>) -> bool {
+    view.active
+}
+
+/// Run condition for overlays placed through the perspective camera, which
+/// the all-sky chart covers
+pub fn perspective_view(view: Res<AllSkyView>) -> bool {
+    !view.active
+}
+
+/// Zenith-centered azimuthal equidistant projection: altitude falls off
+/// linearly from the center to the horizon circle of `radius`
+struct Chart {
+    center: egui::Pos2,
+    radius: f32,
+}
+
+impl Chart {
+    /// Chart position of a world direction, `None` below the horizon
+    fn point(&self, dir: Vec3) -> Option<egui::Pos2> {
+        if dir.y < 0.0 {
+            return None;
+        }
+        let altitude = dir.y.clamp(-1.0, 1.0).asin();
+        // inverse of `alt_az_direction`: x grows to the east, -z to the north
+        let azimuth = dir.x.atan2(-dir.z);
+        let r = (1.0 - altitude / FRAC_PI_2) * self.radius;
+        Some(self.center + egui::vec2(-r * azimuth.sin(), -r * azimuth.cos()))
+    }
+
+    fn alt_az(&self, altitude: f64, azimuth: f64) -> Option<egui::Pos2> {
+        self.point(alt_az_direction(altitude, azimuth))
+    }
+}
+
+/// Painted as the bottom egui layer, under the menu bar and the windows
+#[allow(clippy::too_many_arguments)]
+fn draw_all_sky(
+    layers: Res<SkyLayers>,
+    twilight: Res<Twilight>,
+    state: Res<StarfieldState>,
+    stars: Res<StarIndex>,
+    constellations: Res<Constellations>,
+    planets: Query<&Planet>,
+    small_bodies: Query<&SmallBody>,
+    tracked: Res<TrackedSatellite>,
+    position: Res<TrackedSatellitePosition>,
+    path: Res<PredictedPath>,
+    forbidden: Res<ForbiddenCells>,
+    windows: Query<&Window>,
+    mut cam_q: Query<&mut EguiContext, With<Camera3d>>,
+) {
+    let (Ok(window), Ok(mut egui_context)) = (windows.single(), cam_q.single_mut()) else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+    let screen = egui::Rect::from_min_size(
+        egui::Pos2::ZERO,
+        egui::vec2(window.width(), window.height()),
+    );
+    let chart = Chart {
+        center: screen.center(),
+        radius: (screen.width().min(screen.height()) / 2.0 - CHART_MARGIN).max(1.0),
+    };
+
+    egui::Area::new(egui::Id::new("all_sky_view"))
+        .order(egui::Order::Background)
+        .fixed_pos(egui::Pos2::ZERO)
+        .interactable(false)
+        .show(ctx, |ui| {
+            let painter = ui.painter();
+            // hide the perspective scene underneath
+            painter.rect_filled(screen, 0.0, egui::Color32::BLACK);
+            painter.circle_filled(chart.center, chart.radius, colors::egui::ALL_SKY_BACKGROUND);
+
+            if layers.slew_limits {
+                for cell in &forbidden.0 {
+                    let corners: Option<Vec<egui::Pos2>> = cell
+                        .corners()
+                        .iter()
+                        .map(|&(alt, az)| chart.alt_az(alt, az))
+                        .collect();
+                    if let Some(corners) = corners {
+                        painter.add(egui::Shape::convex_polygon(
+                            corners,
+                            colors::egui::SLEW_LIMIT_SHADE,
+                            egui::Stroke::NONE,
+                        ));
+                    }
+                }
+            }
+
+            let grid = egui::Stroke::new(1.0, colors::egui::ALL_SKY_GRID);
+            painter.circle_stroke(chart.center, chart.radius, grid);
+            for altitude in ALTITUDE_RINGS {
+                let r = (1.0 - altitude as f32 / 90.0) * chart.radius;
+                painter.circle_stroke(chart.center, r, grid);
+            }
+            if layers.meridian {
+                // a straight line through the center on this projection
+                if let (Some(north), Some(south)) =
+                    (chart.alt_az(0.0, 0.0), chart.alt_az(0.0, 180.0))
+                {
+                    painter.line_segment(
+                        [north, south],
+                        egui::Stroke::new(1.0, colors::egui::MERIDIAN),
+                    );
+                }
+            }
+            for (label, azimuth) in [("N", 0.0), ("E", 90.0), ("S", 180.0), ("W", 270.0)] {
+                let Some(edge) = chart.alt_az(0.0, azimuth) else {
+                    continue;
+                };
+                let outward = (edge - chart.center).normalized();
+                painter.text(
+                    edge + outward * 14.0,
+                    egui::Align2::CENTER_CENTER,
+                    label,
+                    egui::FontId::proportional(14.0),
+                    colors::egui::ACCENT_YELLOW,
+                );
+            }
+
+            let now = state.current_utc();
+            let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+            let direction = |(ra, dec): (f64, f64)| star_direction(now, lat, lon, ra, dec);
+
+            if layers.constellation_lines {
+                let stroke = egui::Stroke::new(1.0, colors::egui::CONSTELLATION_LABEL);
+                for [a, b] in constellations.0.iter().flat_map(|c| &c.segments) {
+                    if let (Some(a), Some(b)) =
+                        (chart.point(direction(*a)), chart.point(direction(*b)))
+                    {
+                        painter.line_segment([a, b], stroke);
+                    }
+                }
+            }
+
+            let limit = layers.star_limit(&twilight).min(CHART_STAR_LIMIT);
+            for (hr, &position) in &stars.positions {
+                let Some(&magnitude) = stars.magnitudes.get(hr).filter(|&&m| m <= limit) else {
+                    continue;
+                };
+                if let Some(pos) = chart.point(direction(position)) {
+                    let size = ((CHART_STAR_LIMIT + 0.5 - magnitude) * 0.5).clamp(0.5, 4.0);
+                    painter.circle_filled(pos, size, colors::egui::STAR_LABEL);
+                }
+            }
+
+            let mark = |pos: egui::Pos2, name: &str, color: egui::Color32, size: f32| {
+                painter.circle_filled(pos, size, color);
+                painter.text(
+                    pos + egui::vec2(size + 4.0, 0.0),
+                    egui::Align2::LEFT_CENTER,
+                    name,
+                    egui::FontId::proportional(12.0),
+                    color,
+                );
+            };
+            for planet in &planets {
+                if let Some(pos) = planet
+                    .altitude
+                    .zip(planet.azimuth)
+                    .and_then(|(alt, az)| chart.alt_az(alt, az))
+                {
+                    mark(pos, planet.body.name(), colors::egui::ACCENT_YELLOW, 3.5);
+                }
+            }
+            for body in &small_bodies {
+                if let Some(pos) = body
+                    .altitude
+                    .zip(body.azimuth)
+                    .and_then(|(alt, az)| chart.alt_az(alt, az))
+                {
+                    mark(
+                        pos,
+                        &body.elements.name,
+                        colors::egui::SMALL_BODY_LABEL,
+                        2.5,
+                    );
+                }
+            }
+
+            let Some(sat) = &tracked.0 else {
+                return;
+            };
+            let ahead: Vec<egui::Pos2> = path
+                .0
+                .iter()
+                .filter_map(|p| chart.alt_az(p.altitude, p.azimuth))
+                .collect();
+            painter.extend(egui::Shape::dotted_line(
+                &ahead,
+                colors::egui::GREEN_TEXT,
+                6.0,
+                1.0,
+            ));
+            if let Some(pos) = position.0.and_then(|(alt, az)| chart.alt_az(alt, az)) {
+                let name = sat
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("NORAD {}", sat.norad_id));
+                mark(pos, &name, colors::egui::GREEN_TEXT, 3.5);
+            }
+        });
+}
@@ -1,8 +1,27 @@
+use bevy::camera::visibility::RenderLayers;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 use bevy_egui::EguiContext;
 
+use crate::satellite::PASS_LAYER;
+use crate::starfield::{
+    alt_az_direction, rotate_starfield_system, star_direction, StarfieldRoot, StarfieldState,
+};
+
+/// Keyboard pan speed, in fields of view per second
+const KEY_PAN_RATE: f32 = 0.6;
+/// Keyboard zoom speed, as the fraction of the FOV changed per second
+const KEY_ZOOM_RATE: f32 = 1.0;
+/// Altitude (radians) of the N/S/E/W views, high enough to clear the horizon
+const CARDINAL_PITCH: f32 = 0.35;
+/// Highest pitch the camera can reach, just short of the zenith
+pub const MAX_PITCH: f32 = 1.54;
+/// Narrowest field of view (radians), about 17', enough to frame a planet's disc
+pub const MIN_FOV: f32 = 0.005;
+/// Widest field of view (radians)
+pub const MAX_FOV: f32 = std::f32::consts::PI - 0.01;
+
 #[derive(Component)]
 pub struct RotatingCamera {
     pub yaw: f32,
@@ -13,11 +32,71 @@ pub struct RotatingCamera {
 #[derive(Component, Default)]
 pub struct PanAnchor(pub Option<Vec3>);
 
+/// Something in the sky the camera can be pointed at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CameraTarget {
+    /// Fixed equatorial coordinates (radians)
+    Equatorial { ra: f64, dec: f64 },
+    /// An entity placed in the sky, e.g. a planet
+    Entity(Entity),
+    /// A point fixed to the horizon (degrees), e.g. where a pass rises
+    Horizontal { altitude: f64, azimuth: f64 },
+}
+
+/// Points the camera at a target, either once or continuously as the sky turns.
+#[derive(Resource, Default)]
+pub struct CameraFollow {
+    pub target: Option<CameraTarget>,
+    /// Keep following the target instead of centering it once
+    pub tracking: bool,
+    /// Most recent tracked target, for resuming after a manual pan
+    pub last_tracked: Option<CameraTarget>,
+}
+
+impl CameraFollow {
+    /// Center the view on `target` on the next frame
+    pub fn center(&mut self, target: CameraTarget) {
+        self.target = Some(target);
+        self.tracking = false;
+    }
+
+    /// Keep `target` centered until the user drags the view or stops tracking
+    pub fn track(&mut self, target: CameraTarget) {
+        self.target = Some(target);
+        self.tracking = true;
+        self.last_tracked = Some(target);
+    }
+
+    pub fn stop(&mut self) {
+        self.target = None;
+        self.tracking = false;
+    }
+
+    pub fn is_tracking(&self, target: CameraTarget) -> bool {
+        self.tracking && self.target == Some(target)
+    }
+}
+
 pub struct CameraPlugin;
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_camera)
-            .add_systems(Update, (camera_rotation_system, camera_zoom_system));
+        app.init_resource::<CameraFollow>()
+            .add_systems(Startup, setup_camera)
+            .add_systems(
+                Update,
+                (
+                    camera_rotation_system,
+                    camera_zoom_system,
+                    camera_keyboard_system,
+                ),
+            )
+            .add_systems(
+                Update,
+                camera_follow_system
+                    .after(camera_rotation_system)
+                    .after(camera_keyboard_system)
+                    .after(rotate_starfield_system),
+            );
     }
 }
 
@@ -35,6 +114,7 @@ pub fn setup_camera(mut commands: Commands) {
         },
         PanAnchor::default(),
         Msaa::Sample4,
+        RenderLayers::from_layers(&[0, PASS_LAYER]),
         EguiContext::default(), // Attach egui context to camera
     ));
 }
@@ -62,6 +142,7 @@ fn wrap_pi(a: f32) -> f32 {
 pub fn camera_rotation_system(
     mouse_buttons: Res<ButtonInput<MouseButton>>,
     windows: Query<&Window, With<PrimaryWindow>>,
+    mut follow: ResMut<CameraFollow>,
     mut q: Query<
         (
             &mut RotatingCamera,
@@ -94,6 +175,10 @@ pub fn camera_rotation_system(
 
     // Capture world direction under cursor on press (exact)
     if mouse_buttons.just_pressed(MouseButton::Left) {
+        // Dragging the view takes over from any tracked target
+        if follow.target.is_some() {
+            follow.stop();
+        }
         if let Some(p) = window.cursor_position() {
             if let Ok(ray) = camera.viewport_to_world(gtf, p) {
                 anchor.0 = Some(ray.direction.normalize());
@@ -159,7 +244,7 @@ pub fn camera_rotation_system(
         let sin = right.dot(u_n.cross(v_n));
         let cos = u_n.dot(v_n);
         let pitch_delta = sin.atan2(cos);
-        rc.pitch = (rc.pitch + pitch_delta).clamp(-1.54, 1.54);
+        rc.pitch = (rc.pitch + pitch_delta).clamp(-MAX_PITCH, MAX_PITCH);
     }
 
     // Apply (no roll)
@@ -225,7 +310,9 @@ pub fn camera_zoom_system(
         };
 
         // --- 2) Change FOV (zoom) ---
-        let new_fov = (persp.fov - ev.y * 0.05).clamp(0.1, std::f32::consts::PI - 0.01);
+        // in proportion below 0.5 rad, so zooming in on a planet doesn't overshoot
+        let step = 0.05 * (persp.fov * 2.0).min(1.0);
+        let new_fov = (persp.fov - ev.y * step).clamp(MIN_FOV, MAX_FOV);
         persp.fov = new_fov;
 
         // We want the point under the cursor to stay fixed on screen BOTH ways (in/out),
@@ -266,9 +353,141 @@ pub fn camera_zoom_system(
         let w_after_yaw = (rot_after_yaw * dir_cam_new).normalize();
 
         let pitch_delta = signed_angle_around_axis(w_after_yaw, target_dir, right);
-        rc.pitch = (rc.pitch + pitch_delta).clamp(-1.54, 1.54);
+        rc.pitch = (rc.pitch + pitch_delta).clamp(-MAX_PITCH, MAX_PITCH);
 
         // --- 7) Apply final rotation (no roll) ---
         t.rotation = Quat::from_euler(EulerRot::YXZ, rc.yaw, rc.pitch, 0.0);
     }
 }
+
+/// Arrow keys pan, +/- zoom, Z looks at the zenith, N/S/E/W face the cardinal
+/// points and Space pauses or resumes tracking
+pub fn camera_keyboard_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    mut follow: ResMut<CameraFollow>,
+    mut q: Query<
+        (
+            &mut RotatingCamera,
+            &mut Transform,
+            &mut Projection,
+            &mut EguiContext,
+        ),
+        With<Camera3d>,
+    >,
+) {
+    let Ok((mut rc, mut t, mut projection, mut egui_ctx)) = q.single_mut() else {
+        return;
+    };
+
+    // Typing into a text field isn't navigation
+    if egui_ctx.get_mut().wants_keyboard_input() {
+        return;
+    }
+    let Projection::Perspective(ref mut persp) = *projection else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Space) {
+        if follow.tracking {
+            follow.stop();
+        } else if let Some(target) = follow.last_tracked {
+            follow.track(target);
+        }
+    }
+
+    let dt = time.delta_secs();
+    let pan = KEY_PAN_RATE * persp.fov * dt;
+    let axis = |positive: KeyCode, negative: KeyCode| {
+        keys.pressed(positive) as i32 as f32 - keys.pressed(negative) as i32 as f32
+    };
+    let yaw_input = axis(KeyCode::ArrowLeft, KeyCode::ArrowRight);
+    let pitch_input = axis(KeyCode::ArrowUp, KeyCode::ArrowDown);
+    let zoom_input =
+        axis(KeyCode::Equal, KeyCode::Minus) + axis(KeyCode::NumpadAdd, KeyCode::NumpadSubtract);
+
+    // Facing a fixed direction, where None keeps the current value
+    let jump: Option<(Option<f32>, f32)> = if keys.just_pressed(KeyCode::KeyZ) {
+        Some((None, MAX_PITCH))
+    } else if keys.just_pressed(KeyCode::KeyN) {
+        Some((Some(0.0), CARDINAL_PITCH))
+    } else if keys.just_pressed(KeyCode::KeyE) {
+        Some((Some(-std::f32::consts::FRAC_PI_2), CARDINAL_PITCH))
+    } else if keys.just_pressed(KeyCode::KeyS) {
+        Some((Some(std::f32::consts::PI), CARDINAL_PITCH))
+    } else if keys.just_pressed(KeyCode::KeyW) {
+        Some((Some(std::f32::consts::FRAC_PI_2), CARDINAL_PITCH))
+    } else {
+        None
+    };
+
+    if yaw_input == 0.0 && pitch_input == 0.0 && zoom_input == 0.0 && jump.is_none() {
+        return;
+    }
+
+    // Moving the view by hand takes over from any tracked target
+    if follow.target.is_some() && (yaw_input != 0.0 || pitch_input != 0.0 || jump.is_some()) {
+        follow.stop();
+    }
+
+    if let Some((yaw, pitch)) = jump {
+        rc.yaw = yaw.unwrap_or(rc.yaw);
+        rc.pitch = pitch;
+    }
+    rc.yaw = wrap_pi(rc.yaw + yaw_input * pan);
+    rc.pitch = (rc.pitch + pitch_input * pan).clamp(-MAX_PITCH, MAX_PITCH);
+    persp.fov = (persp.fov * (1.0 - zoom_input * KEY_ZOOM_RATE * dt)).clamp(MIN_FOV, MAX_FOV);
+
+    t.rotation = Quat::from_euler(EulerRot::YXZ, rc.yaw, rc.pitch, 0.0);
+}
+
+/// Point the camera at the followed target, tracking it as the starfield rotates
+pub fn camera_follow_system(
+    mut follow: ResMut<CameraFollow>,
+    state: Res<StarfieldState>,
+    root_q: Query<&Transform, (With<StarfieldRoot>, Without<Camera3d>)>,
+    targets: Query<&GlobalTransform, Without<Camera3d>>,
+    mut cam_q: Query<(&mut RotatingCamera, &mut Transform), With<Camera3d>>,
+) {
+    let Some(target) = follow.target else {
+        return;
+    };
+    let (Ok(root_tf), Ok((mut rc, mut t))) = (root_q.single(), cam_q.single_mut()) else {
+        return;
+    };
+
+    // Stars sit at their spawn-time positions under the rotating root
+    let dir = match target {
+        CameraTarget::Equatorial { ra, dec } => {
+            root_tf.rotation
+                * star_direction(
+                    state.spawn_utc,
+                    state.lat_deg.to_radians(),
+                    state.lon_deg.to_radians(),
+                    ra,
+                    dec,
+                )
+        }
+        CameraTarget::Horizontal { altitude, azimuth } => alt_az_direction(altitude, azimuth),
+        CameraTarget::Entity(entity) => {
+            let Ok(target_tf) = targets.get(entity) else {
+                // Target was despawned
+                follow.stop();
+                return;
+            };
+            match (target_tf.translation() - t.translation).try_normalize() {
+                Some(dir) => dir,
+                None => return,
+            }
+        }
+    };
+
+    // Forward is -Z rotated by yaw (around Y) then pitch (around X)
+    rc.pitch = dir.y.clamp(-1.0, 1.0).asin().clamp(-MAX_PITCH, MAX_PITCH);
+    rc.yaw = (-dir.x).atan2(-dir.z);
+    t.rotation = Quat::from_euler(EulerRot::YXZ, rc.yaw, rc.pitch, 0.0);
+
+    if !follow.tracking {
+        follow.target = None;
+    }
+}
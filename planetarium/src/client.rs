@@ -1,11 +1,61 @@
 // src/client.rs
 
+use chrono::{DateTime, Utc};
 use prost_types::Timestamp;
-use protos::protos::{sidereal_client::SiderealClient, GenericTrack, SetTrackingTargetRequest};
+use protos::protos::{
+    planetarium_message::Payload, set_tracking_target_request::TrackingType, GenericTrack,
+    PassTrack, PlanetariumMessage, SatTrack, SetTrackingTargetRequest, SunWarning,
+};
+use std::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
+use tonic::Status;
 
-pub async fn send_event(_payload: String) -> Result<(), Box<dyn std::error::Error>> {
-    let mut client = SiderealClient::connect("http://[::1]:50052").await?;
-    println!("SENDING");
+/// Sending half of the GUI's link stream, set once the GUI connects
+static GUI_LINK: Mutex<Option<UnboundedSender<Result<PlanetariumMessage, Status>>>> =
+    Mutex::new(None);
+
+/// What the GUI should point the mount at
+#[derive(Debug, Clone, Copy)]
+pub enum SlewTarget {
+    /// A fixed point on the sky
+    Equatorial { ra_hours: f32, dec_degrees: f32 },
+    /// The tracked satellite, at its current position
+    Satellite { ra_hours: f32, dec_degrees: f32 },
+}
+
+/// Route commands for the GUI down this link from now on
+pub fn attach_gui(link: UnboundedSender<Result<PlanetariumMessage, Status>>) {
+    if let Ok(mut current) = GUI_LINK.lock() {
+        *current = Some(link);
+    }
+}
+
+/// Forget `link` if it is still the current one; false when a newer GUI
+/// connection has already replaced it
+pub fn detach_gui(link: &UnboundedSender<Result<PlanetariumMessage, Status>>) -> bool {
+    let Ok(mut current) = GUI_LINK.lock() else {
+        return false;
+    };
+    if current.as_ref().is_some_and(|c| c.same_channel(link)) {
+        *current = None;
+        true
+    } else {
+        false
+    }
+}
+
+/// Queue a message for the GUI on the open link
+fn send_to_gui(payload: Payload) -> Result<(), String> {
+    let current = GUI_LINK.lock().map_err(|e| e.to_string())?;
+    let link = current.as_ref().ok_or("GUI is not connected")?;
+    link.send(Ok(PlanetariumMessage {
+        payload: Some(payload),
+    }))
+    .map_err(|_| "GUI link is closed".to_string())
+}
+
+/// Ask sidereal_gui to slew the mount to `target`
+pub fn send_slew(target: SlewTarget) -> Result<(), String> {
     // current UTC time
     let now = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -16,20 +66,50 @@ pub async fn send_event(_payload: String) -> Result<(), Box<dyn std::error::Erro
         nanos: now.subsec_nanos() as i32,
     };
 
-    // construct the GenericTrack
-    let generic = GenericTrack {
-        ra_hours: 5.0,      // e.g., 5h RA
-        dec_degrees: -30.0, // e.g., -30° Dec
-        time: Some(ts),
+    let tracking_type = match target {
+        SlewTarget::Equatorial {
+            ra_hours,
+            dec_degrees,
+        } => TrackingType::GenericTrack(GenericTrack {
+            ra_hours,
+            dec_degrees,
+            time: Some(ts),
+        }),
+        SlewTarget::Satellite {
+            ra_hours,
+            dec_degrees,
+        } => TrackingType::SatTrack(SatTrack {
+            ra_hours,
+            dec_degrees,
+        }),
     };
 
-    let request = SetTrackingTargetRequest {
-        tracking_type: Some(
-            protos::protos::set_tracking_target_request::TrackingType::GenericTrack(generic),
-        ),
-    };
+    send_to_gui(Payload::Slew(SetTrackingTargetRequest {
+        tracking_type: Some(tracking_type),
+    }))
+}
+
+/// Protobuf timestamp of `time`
+pub fn timestamp(time: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: time.timestamp(),
+        nanos: time.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// Hand sidereal_gui a pass to arm mount tracking for
+pub fn send_pass_track(track: PassTrack) -> Result<(), String> {
+    send_to_gui(Payload::TrackPass(track))
+}
+
+/// Tell sidereal_gui the Sun avoidance cone was entered or cleared
+pub fn send_sun_warning(warning: SunWarning) -> Result<(), String> {
+    send_to_gui(Payload::SunWarning(warning))
+}
 
-    client.set_tracking_target(request).await?;
-    println!("SENT");
-    Ok(())
+/// Send a slew request to the GUI; failures are only logged
+pub fn request_slew(target: SlewTarget) {
+    if let Err(e) = send_slew(target) {
+        eprintln!("Slew request failed: {e}");
+    }
 }
@@ -42,6 +42,9 @@ pub const TRACKING_TARGET_COLOR: Color =
     Color::srgb(ACCENT_YELLOW_R, ACCENT_YELLOW_G, ACCENT_YELLOW_B); // Accent yellow
 pub const MOUNT_TARGET_COLOR: Color = Color::srgb(0.475, 0.941, 0.475); // Green
 
+/// Sky the mount can't reach
+pub const SLEW_LIMIT_SHADE: Color = Color::srgba(0.8, 0.15, 0.15, 0.22);
+
 // egui Color32 constants (for use in UI code)
 pub mod egui {
     use bevy_egui::egui;
@@ -66,4 +69,39 @@ pub mod egui {
         (0.184 * 255.0) as u8,
         (0.184 * 255.0) as u8,
     );
+
+    /// Constellation name labels: the line color, brightened to stay legible
+    pub const CONSTELLATION_LABEL: egui::Color32 = egui::Color32::from_rgb(120, 150, 210);
+
+    /// Star name labels
+    pub const STAR_LABEL: egui::Color32 = egui::Color32::from_rgb(200, 205, 220);
+
+    /// Meteor shower radiant markers and labels
+    pub const METEOR_RADIANT: egui::Color32 = egui::Color32::from_rgb(230, 140, 90);
+
+    /// Deep-sky object markers and labels
+    pub const DSO_MARKER: egui::Color32 = egui::Color32::from_rgb(170, 150, 220);
+
+    /// All-sky chart disk and its horizon and altitude circles
+    pub const ALL_SKY_BACKGROUND: egui::Color32 = egui::Color32::from_rgb(4, 6, 16);
+    pub const ALL_SKY_GRID: egui::Color32 = egui::Color32::from_rgb(60, 70, 95);
+
+    /// Meridian line and zenith mark
+    pub const MERIDIAN: egui::Color32 = egui::Color32::from_rgb(110, 190, 200);
+
+    /// Unreachable sky on the all-sky chart (matches SLEW_LIMIT_SHADE)
+    pub const SLEW_LIMIT_SHADE: egui::Color32 =
+        egui::Color32::from_rgba_unmultiplied_const(204, 38, 38, 56);
+
+    /// Comet and asteroid name labels
+    pub const SMALL_BODY_LABEL: egui::Color32 = egui::Color32::from_rgb(150, 220, 180);
+
+    /// Predicted satellite path while the satellite is in Earth's shadow
+    pub const SATELLITE_SHADOW: egui::Color32 = egui::Color32::from_rgb(110, 115, 130);
+
+    /// Green text for egui (matches GREEN_TEXT)
+    pub const GREEN_TEXT: egui::Color32 = egui::Color32::from_rgb(110, 247, 110);
+
+    /// Red text for egui (matches RED_TEXT)
+    pub const RED_TEXT: egui::Color32 = egui::Color32::from_rgb(247, 110, 110);
 }
@@ -0,0 +1,118 @@
+// src/compass.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::{
+    all_sky::perspective_view,
+    colors,
+    starfield::{alt_az_direction, SkyLayers},
+};
+
+/// Compass points along the horizon; the intercardinals are drawn smaller
+const COMPASS_POINTS: [(&str, f64); 8] = [
+    ("N", 0.0),
+    ("NE", 45.0),
+    ("E", 90.0),
+    ("SE", 135.0),
+    ("S", 180.0),
+    ("SW", 225.0),
+    ("W", 270.0),
+    ("NW", 315.0),
+];
+
+/// Altitude step (degrees) between the points of the drawn meridian
+const MERIDIAN_STEP: f64 = 2.0;
+
+pub struct CompassPlugin;
+impl Plugin for CompassPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, draw_compass.run_if(perspective_view));
+    }
+}
+
+/// The meridian from the north point up through the zenith and down to the
+/// south point, as (altitude, azimuth) samples
+fn meridian_points() -> impl Iterator<Item = (f64, f64)> {
+    let steps = (90.0 / MERIDIAN_STEP) as usize;
+    let north = (0..=steps).map(|i| (i as f64 * MERIDIAN_STEP, 0.0));
+    let south = (0..steps).rev().map(|i| (i as f64 * MERIDIAN_STEP, 180.0));
+    north.chain(south)
+}
+
+/// Compass points on the horizon and the meridian, painted behind the UI
+/// windows, so the rendered sky can be lined up with the real one
+fn draw_compass(
+    layers: Res<SkyLayers>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if !layers.cardinal_points && !layers.meridian {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let to_screen = |altitude: f64, azimuth: f64| {
+        let world = cam_gtf.translation() + alt_az_direction(altitude, azimuth) * 100.0;
+        camera
+            .world_to_viewport(cam_gtf, world)
+            .ok()
+            .map(|pos| egui::pos2(pos.x, pos.y))
+    };
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+
+    if layers.meridian {
+        // segments with an end behind the camera are skipped
+        let stroke = egui::Stroke::new(1.0, colors::egui::MERIDIAN);
+        let points: Vec<_> = meridian_points()
+            .map(|(alt, az)| to_screen(alt, az))
+            .collect();
+        for pair in points.windows(2) {
+            if let [Some(from), Some(to)] = *pair {
+                painter.line_segment([from, to], stroke);
+            }
+        }
+
+        if let Some(zenith) = to_screen(90.0, 0.0) {
+            let arm = egui::vec2(5.0, 5.0);
+            painter.line_segment([zenith - arm, zenith + arm], stroke);
+            painter.line_segment(
+                [
+                    zenith + egui::vec2(-arm.x, arm.y),
+                    zenith + egui::vec2(arm.x, -arm.y),
+                ],
+                stroke,
+            );
+            painter.text(
+                zenith + egui::vec2(8.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                "Zenith",
+                egui::FontId::proportional(12.0),
+                colors::egui::MERIDIAN,
+            );
+        }
+    }
+
+    if layers.cardinal_points {
+        for (label, azimuth) in COMPASS_POINTS {
+            let Some(pos) = to_screen(0.0, azimuth) else {
+                continue;
+            };
+            let cardinal = label.len() == 1;
+            // sitting just above the horizon line
+            painter.text(
+                pos - egui::vec2(0.0, 4.0),
+                egui::Align2::CENTER_BOTTOM,
+                label,
+                egui::FontId::proportional(if cardinal { 18.0 } else { 13.0 }),
+                if cardinal {
+                    colors::egui::ACCENT_YELLOW
+                } else {
+                    colors::egui::WINDOW_TITLE_COLOR
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,186 @@
+// src/custom_catalog.rs
+
+use bevy::camera::primitives::MeshAabb;
+use bevy::prelude::*;
+use planetarium_core::{
+    star_catalog::{parse_catalog, parse_star_csv},
+    star_cloud::{split_into_tiles, CloudStar, StarChunk, DEFAULT_COLOR_INDEX},
+};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    site::config_file,
+    starfield::{asset_base, BrightStars, SkyLayers, StarfieldRoot, StarfieldState},
+    twilight::Twilight,
+};
+
+/// More stars than this would stall the frame they're loaded in; Tycho-2 and
+/// friends belong in the faint star layer instead
+const MAX_STARS: usize = 200_000;
+
+/// Which catalog the bright stars are drawn from. Star names and constellation
+/// figures always come from the bundled BSC5, as they go by HR number.
+#[derive(Resource, Default)]
+pub struct StarCatalog {
+    /// The custom catalog drawn and how many stars it has; `None` for BSC5
+    pub loaded: Option<(PathBuf, usize)>,
+    /// Why the last catalog couldn't be loaded
+    pub error: Option<String>,
+    /// Catalog to switch to on the next frame; `Some(None)` for BSC5
+    requested: Option<Option<PathBuf>>,
+}
+
+impl StarCatalog {
+    /// Switch to the catalog at `path`, or back to BSC5 for `None`
+    pub fn request(&mut self, path: Option<PathBuf>) {
+        self.requested = Some(path);
+    }
+}
+
+fn catalog_choice_path() -> Option<PathBuf> {
+    config_file("planetarium_star_catalog.txt")
+}
+
+/// The custom catalog chosen last run, if any
+fn load_choice() -> Option<PathBuf> {
+    let data = fs::read_to_string(catalog_choice_path()?).ok()?;
+    let path = data.trim();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+fn save_choice(path: Option<&Path>) -> io::Result<()> {
+    let file = catalog_choice_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+    if let Some(dir) = file.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let line = path.map_or_else(String::new, |path| path.display().to_string());
+    fs::write(file, line + "\n")
+}
+
+/// Stars from a `ra,dec,magnitude[,b-v]` text file (`.csv` or `.txt`) or a
+/// catalog in the BSC5 binary format
+fn read_stars(path: &Path) -> io::Result<Vec<CloudStar>> {
+    let text = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv") || ext.eq_ignore_ascii_case("txt"));
+
+    let stars: Vec<CloudStar> = if text {
+        parse_star_csv(&fs::read_to_string(path)?)?
+            .into_iter()
+            .map(|star| CloudStar {
+                ra: star.ra,
+                dec: star.dec,
+                magnitude: star.magnitude,
+                color_index: star.color_index.unwrap_or(DEFAULT_COLOR_INDEX),
+            })
+            .collect()
+    } else {
+        let (_hdr, entries) = parse_catalog(path.to_path_buf())?;
+        entries
+            .iter()
+            .map(|star| CloudStar {
+                ra: star.ra,
+                dec: star.dec,
+                magnitude: star.magnitudes[0],
+                color_index: star.color_index().unwrap_or(DEFAULT_COLOR_INDEX),
+            })
+            .collect()
+    };
+
+    if stars.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no stars in file",
+        ));
+    }
+    if stars.len() > MAX_STARS {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} stars, at most {MAX_STARS} are supported", stars.len()),
+        ));
+    }
+    Ok(stars)
+}
+
+pub struct CustomCatalogPlugin;
+impl Plugin for CustomCatalogPlugin {
+    fn build(&self, app: &mut App) {
+        // the saved catalog replaces the BSC5 stars on the first frame
+        app.insert_resource(StarCatalog {
+            requested: load_choice().map(Some),
+            ..default()
+        })
+        .add_systems(Update, switch_star_catalog);
+    }
+}
+
+/// Swap the bright star chunks for those of the requested catalog. A file that
+/// fails to load leaves the current stars up and reports why.
+#[allow(clippy::too_many_arguments)]
+fn switch_star_catalog(
+    mut commands: Commands,
+    mut catalog: ResMut<StarCatalog>,
+    layers: Res<SkyLayers>,
+    twilight: Res<Twilight>,
+    state: Res<StarfieldState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    root_q: Query<Entity, With<StarfieldRoot>>,
+    chunks: Query<(Entity, &MeshMaterial3d<StandardMaterial>), With<BrightStars>>,
+) {
+    if catalog.requested.is_none() {
+        return;
+    }
+    let (Ok(root), Some((_, material))) = (root_q.single(), chunks.iter().next()) else {
+        return;
+    };
+    let material = material.0.clone();
+    let Some(requested) = catalog.requested.take() else {
+        return;
+    };
+
+    let path = requested
+        .clone()
+        .unwrap_or_else(|| asset_base().join("assets").join("BSC5"));
+    let stars = match read_stars(&path) {
+        Ok(stars) => stars,
+        Err(e) => {
+            catalog.error = Some(format!("{}: {e}", path.display()));
+            return;
+        }
+    };
+    if let Err(e) = save_choice(requested.as_deref()) {
+        eprintln!("Failed to save star catalog choice: {e}");
+    }
+    catalog.error = None;
+    catalog.loaded = requested.map(|path| (path, stars.len()));
+
+    for (entity, _) in &chunks {
+        commands.entity(entity).despawn();
+    }
+    let limit = layers.star_limit(&twilight);
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    commands.entity(root).with_children(|p| {
+        for tile in split_into_tiles(stars) {
+            let chunk = StarChunk { stars: tile };
+            let mesh = chunk.mesh(limit, state.spawn_utc, lat, lon);
+            let visibility = if mesh.compute_aabb().is_some() {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            p.spawn((
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(material.clone()),
+                Transform::default(),
+                visibility,
+                chunk,
+                BrightStars,
+            ));
+        }
+    });
+}
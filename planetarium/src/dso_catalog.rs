@@ -0,0 +1,231 @@
+use bevy::prelude::Resource;
+use std::{
+    f64::consts::PI,
+    io::{self, Error, ErrorKind},
+    path::Path,
+};
+
+use crate::starfield::asset_base;
+
+/// Messier objects, bundled so DSO search works without any downloads.
+const MESSIER_CSV: &str = include_str!("../assets/messier.csv");
+
+/// Optional OpenNGC catalog (https://github.com/mattiaverga/OpenNGC) dropped
+/// next to the other assets to add the NGC and IC objects.
+const OPENNGC_FILE_NAME: &str = "NGC.csv";
+
+/// Broad class of a deep-sky object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DsoKind {
+    Galaxy,
+    GlobularCluster,
+    OpenCluster,
+    Nebula,
+    PlanetaryNebula,
+    SupernovaRemnant,
+    Other,
+}
+
+impl DsoKind {
+    /// Map an OpenNGC object type code.
+    fn from_openngc(code: &str) -> Self {
+        match code {
+            "G" | "GPair" | "GTrpl" | "GGroup" => DsoKind::Galaxy,
+            "GCl" => DsoKind::GlobularCluster,
+            "OCl" | "Cl+N" => DsoKind::OpenCluster,
+            "Neb" | "HII" | "EmN" | "RfN" | "DrkN" => DsoKind::Nebula,
+            "PN" => DsoKind::PlanetaryNebula,
+            "SNR" => DsoKind::SupernovaRemnant,
+            _ => DsoKind::Other,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DsoKind::Galaxy => "Galaxy",
+            DsoKind::GlobularCluster => "Globular cluster",
+            DsoKind::OpenCluster => "Open cluster",
+            DsoKind::Nebula => "Nebula",
+            DsoKind::PlanetaryNebula => "Planetary nebula",
+            DsoKind::SupernovaRemnant => "Supernova remnant",
+            DsoKind::Other => "Other",
+        }
+    }
+}
+
+/// one deep-sky object
+#[derive(Debug, Clone)]
+pub struct DsoEntry {
+    /// Display designation, e.g. "M31" or "NGC 7000"
+    pub designation: String,
+    /// Catalog name in the source file, e.g. "NGC224"
+    pub catalog_name: String,
+    pub kind: DsoKind,
+    pub ra: f64,  // radians
+    pub dec: f64, // radians
+    pub magnitude: Option<f32>,
+    /// Major axis in arcminutes
+    pub size_arcmin: Option<f32>,
+    pub constellation: String,
+    pub common_name: Option<String>,
+}
+
+impl DsoEntry {
+    /// Case-insensitive match against the designation, catalog name, common
+    /// name and constellation. Spaces are ignored so "m 31" finds M31.
+    pub fn matches(&self, query: &str) -> bool {
+        let query: String = query.split_whitespace().collect::<String>().to_lowercase();
+        if query.is_empty() {
+            return true;
+        }
+        let squash = |s: &str| s.split_whitespace().collect::<String>().to_lowercase();
+        squash(&self.designation).contains(&query)
+            || squash(&self.catalog_name).contains(&query)
+            || self
+                .common_name
+                .as_deref()
+                .is_some_and(|n| squash(n).contains(&query))
+            || self.constellation.to_lowercase() == query
+    }
+}
+
+/// All deep-sky objects known to the planetarium.
+#[derive(Resource, Default)]
+pub struct DsoCatalog {
+    pub objects: Vec<DsoEntry>,
+}
+
+impl DsoCatalog {
+    /// The bundled Messier catalog plus OpenNGC's NGC/IC objects if `NGC.csv` is
+    /// present in the assets folder.
+    pub fn load() -> Self {
+        let mut objects = parse_catalog(MESSIER_CSV).expect("bundled Messier catalog is valid");
+
+        let openngc_path = asset_base().join("assets").join(OPENNGC_FILE_NAME);
+        if openngc_path.is_file() {
+            match load_catalog_file(&openngc_path) {
+                // Messier objects are already bundled
+                Ok(extra) => objects.extend(
+                    extra
+                        .into_iter()
+                        .filter(|o| !o.designation.starts_with('M')),
+                ),
+                Err(e) => eprintln!("Failed to load {}: {e}", openngc_path.display()),
+            }
+        }
+
+        Self { objects }
+    }
+}
+
+pub fn load_catalog_file(path: &Path) -> io::Result<Vec<DsoEntry>> {
+    parse_catalog(&std::fs::read_to_string(path)?)
+}
+
+/// Parse a semicolon separated catalog in the OpenNGC layout. Columns are found
+/// by header name; only Name, Type, RA and Dec are required.
+pub fn parse_catalog(data: &str) -> io::Result<Vec<DsoEntry>> {
+    let mut lines = data
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "empty DSO catalog"))?
+        .split(';')
+        .collect();
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let required = |name: &str| {
+        column(name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("DSO catalog is missing the {name} column"),
+            )
+        })
+    };
+
+    let name_col = required("Name")?;
+    let type_col = required("Type")?;
+    let ra_col = required("RA")?;
+    let dec_col = required("Dec")?;
+    let const_col = column("Const");
+    let size_col = column("MajAx");
+    let vmag_col = column("V-Mag");
+    let bmag_col = column("B-Mag");
+    let messier_col = column("M");
+    let common_col = column("Common names");
+
+    let mut objects = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(';').collect();
+        let field = |col: Option<usize>| {
+            col.and_then(|c| fields.get(c))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+
+        let type_code = field(Some(type_col)).unwrap_or("");
+        // OpenNGC keeps duplicate and non-existent entries for cross-referencing
+        if type_code == "Dup" || type_code == "NonEx" {
+            continue;
+        }
+
+        let (Some(catalog_name), Some(ra), Some(dec)) = (
+            field(Some(name_col)),
+            field(Some(ra_col)).and_then(parse_sexagesimal),
+            field(Some(dec_col)).and_then(parse_sexagesimal),
+        ) else {
+            continue;
+        };
+
+        let designation = match field(messier_col).and_then(|m| m.parse::<u32>().ok()) {
+            Some(m) => format!("M{m}"),
+            None => display_designation(catalog_name),
+        };
+
+        objects.push(DsoEntry {
+            designation,
+            catalog_name: catalog_name.to_string(),
+            kind: DsoKind::from_openngc(type_code),
+            ra: ra * 15.0 * PI / 180.0,
+            dec: dec.to_radians(),
+            magnitude: field(vmag_col)
+                .or(field(bmag_col))
+                .and_then(|m| m.parse().ok()),
+            size_arcmin: field(size_col).and_then(|s| s.parse().ok()),
+            constellation: field(const_col).unwrap_or("").to_string(),
+            common_name: field(common_col)
+                .map(|n| n.split(',').next().unwrap_or(n).trim().to_string()),
+        });
+    }
+
+    Ok(objects)
+}
+
+/// "NGC0224" → "NGC 224", "IC0434" → "IC 434"
+fn display_designation(name: &str) -> String {
+    let split = name
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(name.len());
+    let (prefix, number) = name.split_at(split);
+    let number = number.trim_start_matches('0');
+    if prefix.is_empty() || number.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix} {number}")
+    }
+}
+
+/// "HH:MM:SS.s" or "±DD:MM:SS.s" → decimal hours or degrees
+fn parse_sexagesimal(s: &str) -> Option<f64> {
+    let (sign, rest) = match s.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, s.trim_start_matches('+')),
+    };
+    let mut value = 0.0;
+    let mut scale = 1.0;
+    for part in rest.split(':') {
+        value += part.parse::<f64>().ok()? / scale;
+        scale *= 60.0;
+    }
+    Some(sign * value)
+}
@@ -0,0 +1,241 @@
+// src/dso_markers.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use std::f32::consts::TAU;
+
+use crate::{
+    all_sky::perspective_view,
+    colors,
+    dso_catalog::{DsoCatalog, DsoEntry, DsoKind},
+    measure::MeasureTool,
+    starfield::{star_direction, SkyLayers, StarfieldState},
+};
+
+/// Smallest glyph radius (pixels), so compact objects stay clickable
+const MIN_MARKER_RADIUS: f32 = 5.0;
+
+/// Largest glyph radius (pixels); M31 zoomed in would otherwise fill the screen
+const MAX_MARKER_RADIUS: f32 = 250.0;
+
+/// Size (arcminutes) assumed for objects whose catalog entry has none
+const DEFAULT_SIZE_ARCMIN: f32 = 2.0;
+
+/// Minor to major axis ratio of the galaxy ellipse
+const GALAXY_AXIS_RATIO: f32 = 0.5;
+
+/// Points around a drawn circle or ellipse
+const GLYPH_SEGMENTS: usize = 24;
+
+/// How close (pixels) a click must be to a small marker to select it
+const SNAP_RADIUS: f32 = 10.0;
+
+/// Cursor travel (pixels) beyond which a press is a camera drag, not a click
+const CLICK_SLOP: f32 = 4.0;
+
+/// Index into `DsoCatalog::objects` of the clicked marker
+#[derive(Resource, Default)]
+pub struct SelectedDso(pub Option<usize>);
+
+/// Markers drawn last frame as (catalog index, screen center, radius), for picking
+#[derive(Resource, Default)]
+struct DrawnMarkers(Vec<(usize, egui::Pos2, f32)>);
+
+pub struct DsoMarkerPlugin;
+impl Plugin for DsoMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedDso>()
+            .init_resource::<DrawnMarkers>()
+            .add_systems(
+                Update,
+                (pick_dso_marker, draw_dso_markers)
+                    .chain()
+                    .run_if(perspective_view),
+            );
+    }
+}
+
+/// Whether an object is bright enough for the marker layer. Objects without a
+/// catalog magnitude are mostly faint NGC/IC entries and are left out.
+pub fn marker_shown(object: &DsoEntry, layers: &SkyLayers) -> bool {
+    object.magnitude.is_some_and(|m| m <= layers.dso_magnitude)
+}
+
+/// Points around an ellipse with radii `rx` and `ry`
+fn ellipse(center: egui::Pos2, rx: f32, ry: f32) -> Vec<egui::Pos2> {
+    (0..GLYPH_SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / GLYPH_SEGMENTS as f32 * TAU;
+            center + egui::vec2(rx * angle.cos(), ry * angle.sin())
+        })
+        .collect()
+}
+
+/// The conventional chart symbol for each kind of object
+fn draw_glyph(
+    painter: &egui::Painter,
+    kind: DsoKind,
+    center: egui::Pos2,
+    radius: f32,
+    stroke: egui::Stroke,
+) {
+    match kind {
+        DsoKind::Galaxy => {
+            let points = ellipse(center, radius, radius * GALAXY_AXIS_RATIO);
+            painter.add(egui::Shape::closed_line(points, stroke));
+        }
+        DsoKind::GlobularCluster => {
+            painter.circle_stroke(center, radius, stroke);
+            for axis in [egui::vec2(radius, 0.0), egui::vec2(0.0, radius)] {
+                painter.line_segment([center - axis, center + axis], stroke);
+            }
+        }
+        DsoKind::OpenCluster => {
+            let mut points = ellipse(center, radius, radius);
+            points.push(points[0]);
+            painter.extend(egui::Shape::dashed_line(&points, stroke, 3.0, 3.0));
+        }
+        DsoKind::Nebula => {
+            let rect = egui::Rect::from_center_size(center, egui::Vec2::splat(radius * 2.0));
+            let corners = vec![
+                rect.left_top(),
+                rect.right_top(),
+                rect.right_bottom(),
+                rect.left_bottom(),
+            ];
+            painter.add(egui::Shape::closed_line(corners, stroke));
+        }
+        DsoKind::PlanetaryNebula => {
+            painter.circle_stroke(center, radius, stroke);
+            for (dx, dy) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+                let ray = egui::vec2(dx, dy);
+                painter.line_segment(
+                    [center + ray * radius, center + ray * (radius + 4.0)],
+                    stroke,
+                );
+            }
+        }
+        DsoKind::SupernovaRemnant => {
+            let diamond = vec![
+                center + egui::vec2(0.0, -radius),
+                center + egui::vec2(radius, 0.0),
+                center + egui::vec2(0.0, radius),
+                center + egui::vec2(-radius, 0.0),
+            ];
+            painter.add(egui::Shape::closed_line(diamond, stroke));
+        }
+        DsoKind::Other => {
+            painter.circle_stroke(center, radius, stroke);
+        }
+    }
+}
+
+/// Select the marker under a click (not drag), or clear the selection when
+/// the click lands on empty sky
+#[allow(clippy::too_many_arguments)]
+fn pick_dso_marker(
+    mut selected: ResMut<SelectedDso>,
+    mut press: Local<Option<Vec2>>,
+    drawn: Res<DrawnMarkers>,
+    layers: Res<SkyLayers>,
+    measure: Res<MeasureTool>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    mut cam_q: Query<&mut EguiContext, With<Camera3d>>,
+) {
+    // measurement clicks belong to the measure tool
+    if !layers.dso_markers || measure.active {
+        return;
+    }
+    let Ok(mut egui_context) = cam_q.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    let cursor = windows.single().ok().and_then(|w| w.cursor_position());
+    if buttons.just_pressed(MouseButton::Left) {
+        *press = cursor.filter(|_| !ctx.is_pointer_over_area());
+    }
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some((start, cursor)) = press.take().zip(cursor) else {
+        return;
+    };
+    if start.distance(cursor) > CLICK_SLOP {
+        return;
+    }
+
+    // the closest center wins when large markers overlap
+    let cursor = egui::pos2(cursor.x, cursor.y);
+    selected.0 = drawn
+        .0
+        .iter()
+        .filter(|(_, center, radius)| center.distance(cursor) <= radius.max(SNAP_RADIUS))
+        .min_by(|a, b| a.1.distance(cursor).total_cmp(&b.1.distance(cursor)))
+        .map(|&(index, _, _)| index);
+}
+
+/// Chart symbols for the catalog objects above the horizon, sized by their
+/// apparent extent and painted behind the UI windows
+fn draw_dso_markers(
+    layers: Res<SkyLayers>,
+    state: Res<StarfieldState>,
+    catalog: Res<DsoCatalog>,
+    selected: Res<SelectedDso>,
+    mut drawn: ResMut<DrawnMarkers>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &Projection, &mut EguiContext), With<Camera3d>>,
+) {
+    drawn.0.clear();
+    if !layers.dso_markers {
+        return;
+    }
+    let Ok((camera, cam_gtf, projection, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let (Projection::Perspective(persp), Some(viewport)) =
+        (projection, camera.logical_viewport_size())
+    else {
+        return;
+    };
+    // pixels per radian near the center of the view
+    let scale = viewport.y / (2.0 * (persp.fov / 2.0).tan());
+
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+    let now = state.current_utc();
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+
+    for (index, object) in catalog.objects.iter().enumerate() {
+        if !marker_shown(object, &layers) {
+            continue;
+        }
+        let dir = star_direction(now, lat, lon, object.ra, object.dec);
+        if dir.y < 0.0 {
+            continue; // below the horizon
+        }
+        let Ok(pos) = camera.world_to_viewport(cam_gtf, cam_gtf.translation() + dir * 100_000.0)
+        else {
+            continue;
+        };
+
+        let size = object.size_arcmin.unwrap_or(DEFAULT_SIZE_ARCMIN);
+        let radius =
+            ((size / 2.0 / 60.0).to_radians() * scale).clamp(MIN_MARKER_RADIUS, MAX_MARKER_RADIUS);
+        let color = if selected.0 == Some(index) {
+            colors::egui::ACCENT_YELLOW
+        } else {
+            colors::egui::DSO_MARKER
+        };
+        let center = egui::pos2(pos.x, pos.y);
+        draw_glyph(
+            &painter,
+            object.kind,
+            center,
+            radius,
+            egui::Stroke::new(1.2, color),
+        );
+        drawn.0.push((index, center, radius));
+    }
+}
@@ -1,11 +1,16 @@
 use bevy::prelude::Message;
 use chrono::{DateTime, Utc};
 
+use crate::export::ExportRequest;
+use crate::slew_limits::SlewLimits;
+use crate::stellarium::ObjectReply;
+
 #[derive(Message, Debug, Clone)]
 pub enum PlanetariumEvent {
     SetSiteLocation {
         lat_deg: f64,
         lon_deg: f64,
+        alt_m: f64,
     },
     #[allow(dead_code)]
     SetTime {
@@ -15,4 +20,21 @@ pub enum PlanetariumEvent {
         ra_hours: f32,
         dec_deg: f32,
     },
+    /// Where the GUI is sending the mount
+    SetSelectedTarget {
+        ra_hours: f32,
+        dec_deg: f32,
+    },
+    ExportView(ExportRequest),
+    /// Where the mount can point, from the GUI's config
+    SetMountLimits(SlewLimits),
+    /// sidereal_gui opened or lost its link
+    GuiLink {
+        connected: bool,
+    },
+    /// A Stellarium RemoteControl client focusing an object by name
+    FindObject {
+        name: String,
+        reply: ObjectReply,
+    },
 }
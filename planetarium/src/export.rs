@@ -0,0 +1,341 @@
+// src/export.rs
+
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::visibility::RenderLayers;
+use bevy::camera::RenderTarget;
+use bevy::image::BevyDefault;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+use bevy::render::view::screenshot::{save_to_disk, Screenshot, ScreenshotCaptured};
+use chrono::{DateTime, Utc};
+use std::{io::Cursor, path::PathBuf, time::Instant};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    colors,
+    events::PlanetariumEvent,
+    satellite::{SelectedPass, TrackedSatellite, PASS_LAYER},
+    starfield::{star_direction, StarfieldState},
+    ui::TimeDisplay,
+};
+
+/// Largest width or height accepted for an export
+pub const MAX_EXPORT_SIZE: u32 = 8192;
+
+/// Frames the offscreen camera renders before it is captured, so its target
+/// texture exists on the GPU
+const CAPTURE_DELAY_FRAMES: u32 = 2;
+
+/// Where a rendered PNG goes instead of a file
+pub type PngReply = UnboundedSender<Result<Vec<u8>, String>>;
+
+/// Save the current view to a PNG
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    /// Output file; a timestamped name in the working directory if `None`
+    pub path: Option<PathBuf>,
+    pub width: u32,
+    pub height: u32,
+    /// Draw the selected pass path and caption its times
+    pub include_pass: bool,
+    /// Caption the image with the simulated time and site
+    pub include_timestamp: bool,
+    /// Render this view rather than the main camera's
+    pub view: Option<ExportView>,
+    /// Send the PNG here instead of writing `path`
+    pub reply: Option<PngReply>,
+}
+
+/// A view picked by the requester rather than copied from the window
+#[derive(Debug, Clone, Copy)]
+pub struct ExportView {
+    pub ra_hours: f32,
+    pub dec_deg: f32,
+    /// Vertical field of view
+    pub fov_deg: f32,
+    /// Sky at this instant instead of the current simulated time
+    pub time: Option<DateTime<Utc>>,
+}
+
+/// Simulated clock to put back after rendering at another time
+#[derive(Clone, Copy)]
+struct SavedClock {
+    base_utc: DateTime<Utc>,
+    base_instant: Instant,
+    paused: bool,
+}
+
+impl SavedClock {
+    fn save(state: &StarfieldState) -> Self {
+        SavedClock {
+            base_utc: state.base_utc,
+            base_instant: state.base_instant,
+            paused: state.paused,
+        }
+    }
+
+    fn restore(self, state: &mut StarfieldState) {
+        state.base_utc = self.base_utc;
+        state.base_instant = self.base_instant;
+        state.paused = self.paused;
+    }
+}
+
+/// Offscreen camera rendering one export; despawned once the image is saved
+#[derive(Component)]
+struct ExportCamera {
+    image: Handle<Image>,
+    path: PathBuf,
+    reply: Option<PngReply>,
+    caption: Option<Entity>,
+    /// Set while the whole sky is held at the requested time
+    clock: Option<SavedClock>,
+    frames: u32,
+}
+
+pub struct ExportPlugin;
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (start_exports, capture_exports));
+    }
+}
+
+fn render_target(width: u32, height: u32) -> Image {
+    let size = Extent3d {
+        width,
+        height,
+        ..default()
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::bevy_default(),
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage = TextureUsages::TEXTURE_BINDING
+        | TextureUsages::COPY_SRC
+        | TextureUsages::COPY_DST
+        | TextureUsages::RENDER_ATTACHMENT;
+    image
+}
+
+/// Text along the bottom of the export
+fn caption_text(
+    request: &ExportRequest,
+    state: &StarfieldState,
+    selected: &SelectedPass,
+    tracked: &TrackedSatellite,
+    time_display: &TimeDisplay,
+) -> Option<String> {
+    let mut lines = Vec::new();
+    if request.include_pass {
+        if let Some(overpass) = &selected.0 {
+            let name = tracked
+                .0
+                .as_ref()
+                .map(|sat| {
+                    sat.name
+                        .clone()
+                        .unwrap_or_else(|| format!("NORAD {}", sat.norad_id))
+                })
+                .unwrap_or_else(|| "Pass".to_string());
+            lines.push(format!(
+                "{name}: rise {}, max {:.0}° at {}, set {}",
+                time_display.time(overpass.start_time),
+                overpass.max_elevation,
+                time_display.time(overpass.midpoint_time),
+                time_display.time(overpass.end_time)
+            ));
+        }
+    }
+    if request.include_timestamp {
+        lines.push(format!(
+            "{} UTC, {:.4}° {:.4}°",
+            state.current_utc().format("%Y-%m-%d %H:%M:%S"),
+            state.lat_deg,
+            state.lon_deg
+        ));
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Camera pose and projection for a requested view, from the main camera's
+/// position
+fn view_camera(view: &ExportView, state: &StarfieldState, at: Vec3) -> (Transform, Projection) {
+    let direction = star_direction(
+        state.current_utc(),
+        state.lat_deg.to_radians(),
+        state.lon_deg.to_radians(),
+        (view.ra_hours as f64 * 15.0).to_radians(),
+        (view.dec_deg as f64).to_radians(),
+    );
+    let projection = Projection::from(PerspectiveProjection {
+        fov: view.fov_deg.to_radians(),
+        ..default()
+    });
+    (
+        Transform::from_translation(at).looking_to(direction, Vec3::Y),
+        projection,
+    )
+}
+
+/// PNG bytes of a captured frame, dropping alpha as `save_to_disk` does
+fn encode_png(frame: &Image) -> Result<Vec<u8>, String> {
+    let rgb = frame
+        .clone()
+        .try_into_dynamic()
+        .map_err(|e| e.to_string())?
+        .to_rgb8();
+    let mut png = Vec::new();
+    rgb.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    Ok(png)
+}
+
+/// Spawn an offscreen copy of the main camera for each export request
+#[allow(clippy::too_many_arguments)]
+fn start_exports(
+    mut commands: Commands,
+    mut ev: MessageReader<PlanetariumEvent>,
+    mut images: ResMut<Assets<Image>>,
+    mut state: ResMut<StarfieldState>,
+    selected: Res<SelectedPass>,
+    tracked: Res<TrackedSatellite>,
+    time_display: Res<TimeDisplay>,
+    exports: Query<&ExportCamera>,
+    cam_q: Query<(&Transform, &Projection), (With<Camera3d>, Without<ExportCamera>)>,
+) {
+    // only one render can hold the sky at another time
+    let mut clock_held = exports.iter().any(|export| export.clock.is_some());
+    for evt in ev.read() {
+        let PlanetariumEvent::ExportView(request) = evt else {
+            continue;
+        };
+        let Ok((transform, projection)) = cam_q.single() else {
+            continue;
+        };
+
+        // The scene has one clock, so a render at another time briefly
+        // shows that time in the window too
+        let time = request.view.and_then(|view| view.time);
+        let clock = match time {
+            Some(_) if clock_held => {
+                if let Some(reply) = &request.reply {
+                    let _ = reply.send(Err("Another render is holding the time".to_string()));
+                }
+                continue;
+            }
+            Some(time) => {
+                let saved = SavedClock::save(&state);
+                state.set_time(time);
+                state.paused = true;
+                clock_held = true;
+                Some(saved)
+            }
+            None => None,
+        };
+        let (transform, projection) = match &request.view {
+            Some(view) => view_camera(view, &state, transform.translation),
+            None => (*transform, projection.clone()),
+        };
+
+        let path = request.path.clone().unwrap_or_else(|| {
+            let now = chrono::Local::now();
+            PathBuf::from(format!("planetarium-{}.png", now.format("%Y%m%d-%H%M%S")))
+        });
+        let image = images.add(render_target(request.width, request.height));
+        let layers = if request.include_pass {
+            RenderLayers::from_layers(&[0, PASS_LAYER])
+        } else {
+            RenderLayers::layer(0)
+        };
+
+        let camera = commands
+            .spawn((
+                Camera3d::default(),
+                Camera {
+                    target: RenderTarget::Image(image.clone().into()),
+                    order: -1,
+                    ..default()
+                },
+                projection,
+                transform,
+                Msaa::Sample4,
+                layers,
+            ))
+            .id();
+
+        let text = caption_text(request, &state, &selected, &tracked, &time_display);
+        let caption = text.map(|text| {
+            commands
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(2.0),
+                        bottom: Val::Percent(2.0),
+                        ..default()
+                    },
+                    Text::new(text),
+                    TextFont {
+                        font_size: request.height as f32 / 45.0,
+                        ..default()
+                    },
+                    TextColor(colors::TEXT_COLOR_BRIGHT),
+                    UiTargetCamera(camera),
+                ))
+                .id()
+        });
+
+        commands.entity(camera).insert(ExportCamera {
+            image,
+            path,
+            reply: request.reply.clone(),
+            caption,
+            clock,
+            frames: 0,
+        });
+    }
+}
+
+/// Capture each export once it has rendered, then clean it up
+fn capture_exports(mut commands: Commands, mut q: Query<(Entity, &mut ExportCamera)>) {
+    for (camera, mut export) in &mut q {
+        export.frames += 1;
+        if export.frames != CAPTURE_DELAY_FRAMES {
+            continue;
+        }
+
+        let path = export.path.clone();
+        let caption = export.caption;
+        let clock = export.clock;
+        let mut screenshot = commands.spawn(Screenshot::image(export.image.clone()));
+        match export.reply.clone() {
+            Some(reply) => {
+                screenshot.observe(move |captured: On<ScreenshotCaptured>| {
+                    let _ = reply.send(encode_png(&captured.image));
+                });
+            }
+            None => {
+                screenshot.observe(save_to_disk(path.clone()));
+            }
+        }
+        let saved_to_disk = export.reply.is_none();
+        screenshot.observe(
+            move |_: On<ScreenshotCaptured>,
+                  mut commands: Commands,
+                  mut state: ResMut<StarfieldState>| {
+                if saved_to_disk {
+                    println!("Exported view to {}", path.display());
+                }
+                if let Some(clock) = clock {
+                    clock.restore(&mut state);
+                }
+                commands.entity(camera).despawn();
+                if let Some(caption) = caption {
+                    commands.entity(caption).despawn();
+                }
+            },
+        );
+    }
+}
@@ -0,0 +1,275 @@
+// src/faint_stars.rs
+
+use bevy::camera::primitives::MeshAabb;
+use bevy::prelude::*;
+use planetarium_core::{
+    star_catalog::{read_tycho2, CatalogStar},
+    star_cloud::{split_into_tiles, star_material, CloudStar, StarChunk, DEFAULT_COLOR_INDEX},
+};
+use std::{
+    fs,
+    sync::{
+        mpsc::{channel, Receiver},
+        Mutex,
+    },
+};
+
+use crate::{
+    starfield::{asset_base, spawn_starfield, SkyLayers, StarfieldRoot, StarfieldState},
+    twilight::Twilight,
+};
+
+/// Stars brighter than this already come from BSC5
+const BRIGHT_CATALOG_LIMIT: f32 = 6.5;
+/// Faintest star streamed in from Tycho-2
+pub const FAINTEST_MAGNITUDE: f32 = 12.0;
+/// Width of one level-of-detail band
+const BAND_WIDTH: f32 = 0.5;
+const BAND_COUNT: usize = ((FAINTEST_MAGNITUDE - BRIGHT_CATALOG_LIMIT) / BAND_WIDTH) as usize;
+
+/// Stars per batch sent from the loader thread
+const BATCH_SIZE: usize = 10_000;
+/// Cap on stars spawned per frame so streaming doesn't stall rendering
+const SPAWN_PER_FRAME: usize = 20_000;
+/// Field of view (degrees) at which only the bright catalog is shown
+const BASE_FOV_DEG: f32 = 60.0;
+
+/// Marker on the extended catalog's star chunks, children of their band
+#[derive(Component)]
+pub struct FaintStars;
+
+/// Parent of every faint star in one magnitude band, shown or hidden as a whole
+#[derive(Component)]
+struct MagnitudeBand(usize);
+
+/// Batches of stars read by the loader thread
+#[derive(Resource)]
+struct FaintStarStream(Mutex<Receiver<Vec<CatalogStar>>>);
+
+/// Stars received but not spawned yet, by band. Bands are only spawned once the
+/// view is zoomed in far enough to show them.
+#[derive(Resource, Default)]
+struct PendingStars(Vec<Vec<CatalogStar>>);
+
+/// Shared handle so every faint star chunk draws with the same material
+#[derive(Resource)]
+struct FaintStarAssets {
+    material: Handle<StandardMaterial>,
+}
+
+pub struct FaintStarPlugin;
+impl Plugin for FaintStarPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PendingStars(vec![Vec::new(); BAND_COUNT]))
+            .add_systems(Startup, start_faint_stars.after(spawn_starfield))
+            .add_systems(
+                Update,
+                (
+                    receive_faint_stars,
+                    spawn_faint_stars,
+                    update_band_visibility,
+                    reposition_faint_stars,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Band a star belongs to, `None` if BSC5 already has it or it is too faint
+fn band_index(magnitude: f32) -> Option<usize> {
+    if magnitude <= BRIGHT_CATALOG_LIMIT || magnitude > FAINTEST_MAGNITUDE {
+        return None;
+    }
+    let band = ((magnitude - BRIGHT_CATALOG_LIMIT) / BAND_WIDTH) as usize;
+    Some(band.min(BAND_COUNT - 1))
+}
+
+/// Faintest magnitude worth drawing at this field of view: each 10x zoom
+/// reaches 5 magnitudes deeper, but never past `star_limit`
+fn lod_limit(fov: f32, star_limit: f32) -> f32 {
+    let limit = BRIGHT_CATALOG_LIMIT + 5.0 * (BASE_FOV_DEG / fov.to_degrees()).log10();
+    limit
+        .clamp(BRIGHT_CATALOG_LIMIT, FAINTEST_MAGNITUDE)
+        .min(star_limit)
+}
+
+/// Spawn the band parents and start reading `assets/tycho2` in the background.
+/// The whole catalog (`catalog.dat`) or its `tyc2.dat.NN` parts both work.
+fn start_faint_stars(
+    mut commands: Commands,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+    root_q: Query<Entity, With<StarfieldRoot>>,
+) {
+    let Ok(root) = root_q.single() else {
+        return;
+    };
+    commands.entity(root).with_children(|p| {
+        for band in 0..BAND_COUNT {
+            p.spawn((
+                Transform::default(),
+                Visibility::Hidden,
+                MagnitudeBand(band),
+            ));
+        }
+    });
+
+    let texture = assets.load("star.png");
+    commands.insert_resource(FaintStarAssets {
+        material: star_material(&mut mats, &texture),
+    });
+
+    let dir = asset_base().join("assets").join("tycho2");
+    let mut files: Vec<_> = match fs::read_dir(&dir) {
+        Ok(entries) => entries.filter_map(|e| Some(e.ok()?.path())).collect(),
+        Err(_) => {
+            println!("No Tycho-2 catalog in {}, showing BSC5 only", dir.display());
+            return;
+        }
+    };
+    files.sort();
+
+    let (tx, rx) = channel();
+    commands.insert_resource(FaintStarStream(Mutex::new(rx)));
+    std::thread::spawn(move || {
+        for file in files {
+            let result = read_tycho2(&file, BATCH_SIZE, |batch| {
+                let batch: Vec<CatalogStar> = batch
+                    .into_iter()
+                    .filter(|s| band_index(s.magnitude).is_some())
+                    .collect();
+                let _ = tx.send(batch);
+            });
+            if let Err(e) = result {
+                eprintln!("Failed to read {}: {e}", file.display());
+            }
+        }
+    });
+}
+
+/// Sort whatever the loader has read so far into the pending bands
+fn receive_faint_stars(stream: Option<Res<FaintStarStream>>, mut pending: ResMut<PendingStars>) {
+    let Some(stream) = stream else {
+        return;
+    };
+    let Ok(receiver) = stream.0.lock() else {
+        return;
+    };
+    while let Ok(batch) = receiver.try_recv() {
+        for star in batch {
+            if let Some(band) = band_index(star.magnitude) {
+                pending.0[band].push(star);
+            }
+        }
+    }
+}
+
+/// Spawn pending stars for the bands the current zoom shows, brightest first,
+/// as one chunk per sky tile in each batch
+#[allow(clippy::too_many_arguments)]
+fn spawn_faint_stars(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    faint_assets: Option<Res<FaintStarAssets>>,
+    state: Res<StarfieldState>,
+    layers: Res<SkyLayers>,
+    twilight: Res<Twilight>,
+    mut pending: ResMut<PendingStars>,
+    cam_q: Query<&Projection, With<Camera3d>>,
+    band_q: Query<(Entity, &MagnitudeBand)>,
+) {
+    let (Some(faint_assets), Ok(Projection::Perspective(persp))) = (faint_assets, cam_q.single())
+    else {
+        return;
+    };
+    let limit = lod_limit(persp.fov, layers.star_limit(&twilight));
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+
+    let mut budget = SPAWN_PER_FRAME;
+    for band in 0..BAND_COUNT {
+        let faintest = BRIGHT_CATALOG_LIMIT + (band + 1) as f32 * BAND_WIDTH;
+        if faintest > limit + BAND_WIDTH || budget == 0 {
+            break;
+        }
+        let Some((parent, _)) = band_q.iter().find(|(_, b)| b.0 == band) else {
+            continue;
+        };
+
+        let count = pending.0[band].len().min(budget);
+        budget -= count;
+        let stars = pending.0[band].drain(..count).map(|star| CloudStar {
+            ra: star.ra,
+            dec: star.dec,
+            magnitude: star.magnitude,
+            color_index: star.color_index.unwrap_or(DEFAULT_COLOR_INDEX),
+        });
+        let tiles = split_into_tiles(stars);
+        commands.entity(parent).with_children(|p| {
+            for tile in tiles {
+                let chunk = StarChunk { stars: tile };
+                let mesh = chunk.mesh(FAINTEST_MAGNITUDE, state.spawn_utc, lat, lon);
+                p.spawn((
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(faint_assets.material.clone()),
+                    Transform::default(),
+                    Visibility::default(),
+                    chunk,
+                    FaintStars,
+                ));
+            }
+        });
+    }
+}
+
+/// Show the bands down to the limiting magnitude for the current zoom
+fn update_band_visibility(
+    layers: Res<SkyLayers>,
+    twilight: Res<Twilight>,
+    cam_q: Query<Ref<Projection>, With<Camera3d>>,
+    mut band_q: Query<(&MagnitudeBand, &mut Visibility)>,
+) {
+    let Ok(projection) = cam_q.single() else {
+        return;
+    };
+    if !projection.is_changed() && !layers.is_changed() && !twilight.is_changed() {
+        return;
+    }
+    let Projection::Perspective(persp) = &*projection else {
+        return;
+    };
+    let limit = lod_limit(persp.fov, layers.star_limit(&twilight));
+    for (band, mut vis) in &mut band_q {
+        let brightest = BRIGHT_CATALOG_LIMIT + band.0 as f32 * BAND_WIDTH;
+        *vis = if brightest < limit {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Move the faint stars when the observer moves, like the BSC5 stars
+fn reposition_faint_stars(
+    mut commands: Commands,
+    state: Res<StarfieldState>,
+    mut last_site: Local<Option<(f64, f64)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    q: Query<(Entity, &StarChunk, &Mesh3d), With<FaintStars>>,
+) {
+    let site = (state.lat_deg, state.lon_deg);
+    if *last_site == Some(site) {
+        return;
+    }
+    *last_site = Some(site);
+
+    let (lat, lon) = (site.0.to_radians(), site.1.to_radians());
+    for (entity, chunk, mesh) in &q {
+        let rebuilt = chunk.mesh(FAINTEST_MAGNITUDE, state.spawn_utc, lat, lon);
+        if let Some(aabb) = rebuilt.compute_aabb() {
+            commands.entity(entity).insert(aabb);
+        }
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = rebuilt;
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// src/field_rotation.rs
+
+use std::f64::consts::PI;
+
+use crate::starfield::{hour_angle_from_horizontal, SIDEREAL_RATE};
+
+/// How fast (degrees per second) the field turns under an alt-az mount
+/// pointed at `altitude`/`azimuth` (degrees, azimuth from north through east)
+/// from latitude `lat_deg`. Positive is the parallactic angle increasing; it
+/// grows without bound towards the zenith.
+pub fn field_rotation_rate(lat_deg: f64, altitude: f64, azimuth: f64) -> f64 {
+    let (lat, alt, az) = (
+        lat_deg.to_radians(),
+        altitude.to_radians(),
+        azimuth.to_radians(),
+    );
+    (-SIDEREAL_RATE * lat.cos() * az.cos() / alt.cos()).to_degrees()
+}
+
+/// Angle (radians) between north and the zenith as seen at the target
+fn parallactic_angle(lat: f64, ha: f64, dec: f64) -> f64 {
+    ha.sin().atan2(lat.tan() * dec.cos() - dec.sin() * ha.cos())
+}
+
+/// How far (degrees) the field turns during an exposure of `seconds` that
+/// starts at `altitude`/`azimuth`. Unlike the rate times the exposure, this
+/// follows the target as it moves, so it stays finite through the zenith.
+pub fn accumulated_rotation(lat_deg: f64, altitude: f64, azimuth: f64, seconds: f64) -> f64 {
+    let lat = lat_deg.to_radians();
+    let (ha, dec) = hour_angle_from_horizontal(lat, altitude, azimuth);
+    let start = parallactic_angle(lat, ha, dec);
+    let end = parallactic_angle(lat, ha + SIDEREAL_RATE * seconds, dec);
+    ((end - start + PI).rem_euclid(2.0 * PI) - PI).to_degrees()
+}
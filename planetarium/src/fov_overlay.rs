@@ -0,0 +1,87 @@
+// src/fov_overlay.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::{all_sky::perspective_view, colors};
+
+/// Camera sensor field of view drawn around the center of the view
+#[derive(Resource)]
+pub struct FovOverlay {
+    pub visible: bool,
+    pub width_arcmin: f64,
+    pub height_arcmin: f64,
+}
+
+impl Default for FovOverlay {
+    fn default() -> Self {
+        // an APS-C sensor behind a 600 mm lens
+        let (width_arcmin, height_arcmin) = FovOverlay::sensor_fov(600.0, 23.5, 15.6);
+        FovOverlay {
+            visible: false,
+            width_arcmin,
+            height_arcmin,
+        }
+    }
+}
+
+impl FovOverlay {
+    /// Field of view (arcmin) of a `width` x `height` mm sensor at `focal_length` mm
+    pub fn sensor_fov(focal_length: f64, width: f64, height: f64) -> (f64, f64) {
+        let angle = |size: f64| (2.0 * (size / (2.0 * focal_length)).atan()).to_degrees() * 60.0;
+        (angle(width), angle(height))
+    }
+}
+
+pub struct FovOverlayPlugin;
+impl Plugin for FovOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FovOverlay>()
+            .add_systems(Update, draw_fov_overlay.run_if(perspective_view));
+    }
+}
+
+/// Sensor frame centered on the current pointing, painted behind the UI windows
+fn draw_fov_overlay(
+    overlay: Res<FovOverlay>,
+    mut cam_q: Query<(&Camera, &Projection, &mut EguiContext), With<Camera3d>>,
+) {
+    if !overlay.visible {
+        return;
+    }
+    let Ok((camera, Projection::Perspective(persp), mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let Some(viewport) = camera.logical_viewport_size() else {
+        return;
+    };
+
+    // Pixels per unit of tangent-plane distance at the center of the view
+    let scale = viewport.y / (2.0 * (persp.fov / 2.0).tan());
+    let extent = |arcmin: f64| {
+        let half = (arcmin / 120.0).to_radians() as f32;
+        2.0 * half.tan() * scale
+    };
+    let size = egui::vec2(extent(overlay.width_arcmin), extent(overlay.height_arcmin));
+    let rect = egui::Rect::from_center_size(egui::pos2(viewport.x, viewport.y) / 2.0, size);
+
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+    painter.rect_stroke(
+        rect,
+        0.0,
+        egui::Stroke::new(1.5, colors::egui::ACCENT_YELLOW),
+        egui::StrokeKind::Middle,
+    );
+    painter.text(
+        rect.left_top() + egui::vec2(0.0, -4.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!(
+            "{:.0}' x {:.0}'",
+            overlay.width_arcmin, overlay.height_arcmin
+        ),
+        egui::FontId::proportional(12.0),
+        colors::egui::ACCENT_YELLOW,
+    );
+}
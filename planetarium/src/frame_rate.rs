@@ -0,0 +1,111 @@
+// src/frame_rate.rs
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::winit::{UpdateMode, WinitSettings};
+use std::time::{Duration, Instant};
+
+use crate::{
+    camera::CameraFollow, events::PlanetariumEvent, satellite::TrackedSatellite,
+    starfield::StarfieldState,
+};
+
+/// How long without input before the planetarium drops to the idle rate
+const IDLE_AFTER: Duration = Duration::from_secs(5);
+/// Redraws per second while idle; enough for the sky to turn and for
+/// messages from the GUI to be picked up. Input wakes it straight away.
+const IDLE_FPS: f64 = 2.0;
+
+/// How hard the planetarium drives the GPU. It mostly sits in the background
+/// during imaging sessions, where every frame is wasted.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct FrameRateSettings {
+    /// Most frames per second; 0 for no cap
+    pub max_fps: u32,
+    /// Drop to `IDLE_FPS` when nothing is moving and nobody is using it
+    pub power_saving: bool,
+}
+
+impl Default for FrameRateSettings {
+    fn default() -> Self {
+        FrameRateSettings {
+            max_fps: 60,
+            power_saving: true,
+        }
+    }
+}
+
+/// Whether the app is currently running at the idle rate
+#[derive(Resource, Default)]
+pub struct Idle(pub bool);
+
+pub struct FrameRatePlugin;
+impl Plugin for FrameRatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameRateSettings>()
+            .init_resource::<Idle>()
+            .insert_resource(WinitSettings::continuous())
+            .add_systems(Update, detect_idle)
+            .add_systems(Last, limit_frame_rate);
+    }
+}
+
+/// Switch between continuous updates and waiting for input. Anything that
+/// moves on its own (a tracked object, time running fast) keeps it awake.
+#[allow(clippy::too_many_arguments)]
+fn detect_idle(
+    settings: Res<FrameRateSettings>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut cursor: MessageReader<CursorMoved>,
+    mut wheel: MessageReader<MouseWheel>,
+    mut events: MessageReader<PlanetariumEvent>,
+    follow: Res<CameraFollow>,
+    tracked: Res<TrackedSatellite>,
+    state: Res<StarfieldState>,
+    mut last_activity: Local<Option<Instant>>,
+    mut idle: ResMut<Idle>,
+    mut winit: ResMut<WinitSettings>,
+) {
+    // every reader is drained, so old input isn't seen again
+    let messages = cursor.read().count() + wheel.read().count() + events.read().count();
+    let input = messages > 0
+        || keys.get_pressed().next().is_some()
+        || buttons.get_pressed().next().is_some();
+    let moving =
+        follow.tracking || tracked.0.is_some() || (!state.paused && state.time_rate != 1.0);
+
+    let now = Instant::now();
+    if input || moving || last_activity.is_none() {
+        *last_activity = Some(now);
+    }
+    let quiet = last_activity.is_some_and(|t| now.duration_since(t) >= IDLE_AFTER);
+    let should_idle = settings.power_saving && quiet;
+    if idle.0 == should_idle {
+        return;
+    }
+
+    idle.0 = should_idle;
+    *winit = if should_idle {
+        let wait = UpdateMode::reactive_low_power(Duration::from_secs_f64(1.0 / IDLE_FPS));
+        WinitSettings {
+            focused_mode: wait,
+            unfocused_mode: wait,
+        }
+    } else {
+        WinitSettings::continuous()
+    };
+}
+
+/// Sleep off the rest of the frame so it takes at least 1 / `max_fps`
+fn limit_frame_rate(settings: Res<FrameRateSettings>, mut frame_start: Local<Option<Instant>>) {
+    if settings.max_fps > 0 {
+        if let Some(start) = *frame_start {
+            let frame = Duration::from_secs_f64(1.0 / settings.max_fps as f64);
+            if let Some(rest) = frame.checked_sub(start.elapsed()) {
+                std::thread::sleep(rest);
+            }
+        }
+    }
+    *frame_start = Some(Instant::now());
+}
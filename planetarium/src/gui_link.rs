@@ -0,0 +1,28 @@
+// src/gui_link.rs
+
+use bevy::prelude::*;
+
+use crate::events::PlanetariumEvent;
+
+/// Whether sidereal_gui currently has a link open, shown in the menu bar.
+/// Without it mount positions stop arriving and slews go nowhere.
+#[derive(Resource, Default)]
+pub struct GuiLinkStatus {
+    pub connected: bool,
+}
+
+pub struct GuiLinkPlugin;
+impl Plugin for GuiLinkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GuiLinkStatus>()
+            .add_systems(Update, track_gui_link);
+    }
+}
+
+fn track_gui_link(mut ev: MessageReader<PlanetariumEvent>, mut status: ResMut<GuiLinkStatus>) {
+    for evt in ev.read() {
+        if let PlanetariumEvent::GuiLink { connected } = *evt {
+            status.connected = connected;
+        }
+    }
+}
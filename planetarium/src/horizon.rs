@@ -0,0 +1,177 @@
+// src/horizon.rs
+
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use std::{
+    fs,
+    io::{self, Error, ErrorKind},
+};
+
+use crate::starfield::{alt_az_direction, asset_base};
+
+/// Inside the satellite paths, so everything in the sky can be hidden by the ground
+const HORIZON_DISTANCE: f32 = 60_000.0;
+
+/// Optional horizon profile: `azimuth altitude` pairs in degrees, one per line
+const HORIZON_PROFILE: &str = "horizon.txt";
+/// Optional 360° landscape: equirectangular in alt/az with north at the left edge,
+/// east a quarter of the way across, the zenith at the top and a transparent sky
+const LANDSCAPE_TEXTURE: &str = "landscape.png";
+
+/// Azimuth samples around the horizon
+const AZIMUTH_STEPS: usize = 360;
+
+/// Marker on the ground mesh
+#[derive(Component)]
+pub struct Horizon;
+
+pub struct HorizonPlugin;
+impl Plugin for HorizonPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_horizon);
+    }
+}
+
+/// Parse a horizon profile into (azimuth, altitude) points sorted by azimuth.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn parse_horizon_profile(data: &str) -> io::Result<Vec<(f64, f64)>> {
+    let mut points = data
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|line| {
+            let mut fields = line.split_whitespace().map(str::parse::<f64>);
+            match (fields.next(), fields.next()) {
+                (Some(Ok(az)), Some(Ok(alt))) => Ok((az.rem_euclid(360.0), alt)),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("bad horizon line: {line}"),
+                )),
+            }
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(points)
+}
+
+/// Horizon altitude at `az`, interpolated between the profile points and
+/// wrapping around north. A flat horizon without points.
+fn profile_altitude(points: &[(f64, f64)], az: f64) -> f64 {
+    let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+        return 0.0;
+    };
+    let after = points.iter().position(|&(p, _)| p >= az);
+    // around north, interpolate across the wrap
+    let ((az0, alt0), (az1, alt1)) = match after {
+        Some(0) => ((last.0 - 360.0, last.1), first),
+        None => (last, (first.0 + 360.0, first.1)),
+        Some(i) => (points[i - 1], points[i]),
+    };
+    if az1 - az0 < f64::EPSILON {
+        return alt0;
+    }
+    alt0 + (alt1 - alt0) * (az - az0) / (az1 - az0)
+}
+
+/// Opaque ground from the nadir up to the horizon profile
+fn ground_mesh(points: &[(f64, f64)]) -> Mesh {
+    let mut positions = vec![(alt_az_direction(-90.0, 0.0) * HORIZON_DISTANCE).to_array()];
+    for i in 0..=AZIMUTH_STEPS {
+        let az = i as f64 * 360.0 / AZIMUTH_STEPS as f64;
+        let alt = profile_altitude(points, az % 360.0);
+        positions.push((alt_az_direction(alt, az) * HORIZON_DISTANCE).to_array());
+    }
+
+    let steps = AZIMUTH_STEPS as u32;
+    let indices: Vec<u32> = (1..=steps).flat_map(|i| [0, i, i + 1]).collect();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Sphere with the landscape panorama mapped by alt/az
+fn landscape_mesh() -> Mesh {
+    const STACKS: u32 = 90;
+    let sectors = AZIMUTH_STEPS as u32;
+
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    for i in 0..=STACKS {
+        let v = i as f32 / STACKS as f32;
+        let alt = 90.0 - v as f64 * 180.0;
+        for j in 0..=sectors {
+            let u = j as f32 / sectors as f32;
+            let dir = alt_az_direction(alt, u as f64 * 360.0);
+            positions.push((dir * HORIZON_DISTANCE).to_array());
+            uvs.push([u, v]);
+        }
+    }
+
+    let mut indices = Vec::new();
+    for i in 0..STACKS {
+        for j in 0..sectors {
+            let a = i * (sectors + 1) + j;
+            let b = a + sectors + 1;
+            indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Spawn the landscape panorama if one is installed, otherwise solid ground up to
+/// the horizon profile (or a flat horizon)
+fn spawn_horizon(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let assets_dir = asset_base().join("assets");
+
+    let (mesh, material) = if assets_dir.join(LANDSCAPE_TEXTURE).exists() {
+        let material = StandardMaterial {
+            base_color_texture: Some(assets.load(LANDSCAPE_TEXTURE)),
+            alpha_mode: AlphaMode::Mask(0.5),
+            unlit: true,
+            cull_mode: None, // seen from inside
+            ..default()
+        };
+        (landscape_mesh(), material)
+    } else {
+        let points = match fs::read_to_string(assets_dir.join(HORIZON_PROFILE)) {
+            Ok(data) => parse_horizon_profile(&data).unwrap_or_else(|e| {
+                eprintln!("Failed to load horizon profile: {e}");
+                Vec::new()
+            }),
+            Err(_) => Vec::new(), // flat horizon
+        };
+        let material = StandardMaterial {
+            base_color: Color::srgb(0.1, 0.4, 0.1),
+            perceptual_roughness: 1.0,
+            unlit: true,
+            cull_mode: None,
+            ..default()
+        };
+        (ground_mesh(&points), material)
+    };
+
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(material)),
+        Transform::default(),
+        Visibility::default(),
+        Horizon,
+    ));
+}
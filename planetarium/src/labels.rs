@@ -0,0 +1,192 @@
+// src/labels.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::{
+    all_sky::perspective_view,
+    colors,
+    dso_catalog::DsoCatalog,
+    dso_markers::marker_shown,
+    planets::{update_planet_positions, Planet},
+    satellite::{
+        update_satellite_marker, TrackedSatellite, TrackedSatellitePosition, PATH_DISTANCE,
+    },
+    small_bodies::{update_small_body_positions, SmallBody},
+    starfield::{alt_az_direction, star_direction, SkyLayers, StarIndex, StarfieldState},
+    twilight::Twilight,
+};
+
+/// Gap (pixels) between an object and the corner of its label
+const LABEL_OFFSET: f32 = 10.0;
+
+/// A label waiting to be placed
+struct Label {
+    /// Screen position of the object
+    anchor: egui::Pos2,
+    text: String,
+    size: f32,
+    color: egui::Color32,
+}
+
+pub struct LabelPlugin;
+impl Plugin for LabelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            draw_labels
+                .after(update_planet_positions)
+                .after(update_small_body_positions)
+                .after(update_satellite_marker)
+                .run_if(perspective_view),
+        );
+    }
+}
+
+/// Name labels painted behind the UI windows. They are placed in priority order,
+/// tracked satellite first, then planets, comets and asteroids, then stars from
+/// the brightest, then deep-sky markers, and a label that would overlap one
+/// already placed is dropped.
+#[allow(clippy::too_many_arguments)]
+fn draw_labels(
+    layers: Res<SkyLayers>,
+    twilight: Res<Twilight>,
+    state: Res<StarfieldState>,
+    stars: Res<StarIndex>,
+    dso_catalog: Res<DsoCatalog>,
+    planets: Query<(&Planet, &GlobalTransform)>,
+    small_bodies: Query<(&SmallBody, &GlobalTransform)>,
+    tracked: Res<TrackedSatellite>,
+    position: Res<TrackedSatellitePosition>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if !layers.labels {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let to_screen = |world: Vec3| {
+        camera
+            .world_to_viewport(cam_gtf, world)
+            .ok()
+            .map(|pos| egui::pos2(pos.x, pos.y))
+    };
+
+    let mut labels = Vec::new();
+
+    // Name and live alt/az, so acquisition can be confirmed at a glance
+    if let (Some(sat), Some((alt, az))) = (&tracked.0, position.0) {
+        let world = cam_gtf.translation() + alt_az_direction(alt, az) * PATH_DISTANCE;
+        if let Some(anchor) = to_screen(world) {
+            let name = sat
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("NORAD {}", sat.norad_id));
+            labels.push(Label {
+                anchor,
+                text: format!("{name}  {alt:.1}° / {az:.1}°"),
+                size: 13.0,
+                color: colors::egui::GREEN_TEXT,
+            });
+        }
+    }
+
+    for (planet, gtf) in &planets {
+        if planet.altitude.is_none_or(|alt| alt < 0.0) {
+            continue;
+        }
+        if let Some(anchor) = to_screen(gtf.translation()) {
+            labels.push(Label {
+                anchor,
+                text: planet.body.name().to_string(),
+                size: 13.0,
+                color: colors::egui::ACCENT_YELLOW,
+            });
+        }
+    }
+
+    for (body, gtf) in &small_bodies {
+        if body.altitude.is_none_or(|alt| alt < 0.0) {
+            continue;
+        }
+        if let Some(anchor) = to_screen(gtf.translation()) {
+            labels.push(Label {
+                anchor,
+                text: body.elements.name.clone(),
+                size: 12.0,
+                color: colors::egui::SMALL_BODY_LABEL,
+            });
+        }
+    }
+
+    // Only stars that are actually drawn
+    let limit = layers.label_magnitude.min(layers.star_limit(&twilight));
+    let mut named: Vec<(f32, &str, (f64, f64))> = stars
+        .names
+        .iter()
+        .filter_map(|(name, hr)| {
+            let magnitude = *stars.magnitudes.get(hr)?;
+            let position = *stars.positions.get(hr)?;
+            (magnitude <= limit).then_some((magnitude, name.as_str(), position))
+        })
+        .collect();
+    named.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let now = state.current_utc();
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    for (_, name, (ra, dec)) in named {
+        let dir = star_direction(now, lat, lon, ra, dec);
+        if dir.y < 0.0 {
+            continue; // below the horizon
+        }
+        if let Some(anchor) = to_screen(cam_gtf.translation() + dir * 100_000.0) {
+            labels.push(Label {
+                anchor,
+                text: name.to_string(),
+                size: 12.0,
+                color: colors::egui::STAR_LABEL,
+            });
+        }
+    }
+
+    if layers.dso_markers {
+        for object in dso_catalog
+            .objects
+            .iter()
+            .filter(|o| marker_shown(o, &layers))
+        {
+            let dir = star_direction(now, lat, lon, object.ra, object.dec);
+            if dir.y < 0.0 {
+                continue;
+            }
+            if let Some(anchor) = to_screen(cam_gtf.translation() + dir * 100_000.0) {
+                labels.push(Label {
+                    anchor,
+                    text: object.designation.clone(),
+                    size: 11.0,
+                    color: colors::egui::DSO_MARKER,
+                });
+            }
+        }
+    }
+
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+    let mut placed: Vec<egui::Rect> = Vec::new();
+    for label in labels {
+        let galley = painter.layout_no_wrap(
+            label.text,
+            egui::FontId::proportional(label.size),
+            label.color,
+        );
+        let corner = label.anchor + egui::vec2(LABEL_OFFSET, -LABEL_OFFSET);
+        let rect = egui::Align2::LEFT_BOTTOM.anchor_size(corner, galley.size());
+        if placed.iter().any(|other| other.intersects(rect)) {
+            continue;
+        }
+        placed.push(rect);
+        painter.galley(rect.min, galley, label.color);
+    }
+}
@@ -0,0 +1,273 @@
+// src/light_pollution.rs
+
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use std::{fs, io, path::PathBuf};
+
+use crate::{
+    site::config_file,
+    starfield::{alt_az_direction, SkyLayers},
+};
+
+/// Zenith sky brightness (mag/arcsec²) for Bortle classes 1 to 9
+const BORTLE_ZENITH: [f64; 9] = [21.99, 21.89, 21.69, 20.49, 19.50, 18.94, 18.38, 18.0, 17.5];
+
+/// Altitude and azimuth steps (degrees) of the overlay grid
+const ALT_STEP: f64 = 3.0;
+const AZ_STEP: f64 = 5.0;
+
+/// Just inside the slew limit shading
+const OVERLAY_DISTANCE: f32 = 49_000.0;
+
+/// How far (degrees) a light dome spreads along the horizon and up the sky
+const DOME_WIDTH: f64 = 35.0;
+const DOME_HEIGHT: f64 = 15.0;
+
+/// Overlay colors from the darkest sky to the brightest, with the sky
+/// brightness (mag/arcsec²) each one stands for
+const BRIGHTNESS_SCALE: [(f64, [f32; 3]); 5] = [
+    (22.0, [0.05, 0.05, 0.35]),
+    (21.0, [0.1, 0.45, 0.2]),
+    (20.0, [0.75, 0.7, 0.1]),
+    (19.0, [0.85, 0.35, 0.1]),
+    (18.0, [0.95, 0.95, 0.95]),
+];
+const OVERLAY_ALPHA: f32 = 0.3;
+
+/// A town's glow on the horizon, e.g. read off a VIIRS light pollution map
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightDome {
+    /// Direction of the glow, from north through east
+    pub azimuth_deg: f64,
+    /// How many magnitudes brighter than the zenith the horizon is there
+    pub magnitudes: f64,
+}
+
+/// Estimated sky brightness at the site, remembered between runs
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct LightPollution {
+    /// 1 (excellent dark site) to 9 (inner city)
+    pub bortle: u8,
+    pub domes: Vec<LightDome>,
+}
+
+impl Default for LightPollution {
+    fn default() -> Self {
+        LightPollution {
+            bortle: 4,
+            domes: Vec::new(),
+        }
+    }
+}
+
+fn light_pollution_path() -> Option<PathBuf> {
+    config_file("planetarium_light_pollution.txt")
+}
+
+/// Parse `bortle <class>` and `dome <azimuth> <magnitudes>` lines
+fn parse_light_pollution(data: &str) -> LightPollution {
+    let mut pollution = LightPollution::default();
+    for line in data.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["bortle", class] => {
+                if let Ok(class @ 1..=9) = class.parse::<u8>() {
+                    pollution.bortle = class;
+                }
+            }
+            ["dome", azimuth, magnitudes] => {
+                if let (Ok(azimuth_deg), Ok(magnitudes)) = (azimuth.parse(), magnitudes.parse()) {
+                    pollution.domes.push(LightDome {
+                        azimuth_deg,
+                        magnitudes,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    pollution
+}
+
+impl LightPollution {
+    /// The saved estimate, if there is one
+    pub fn load() -> Option<LightPollution> {
+        Some(parse_light_pollution(
+            &fs::read_to_string(light_pollution_path()?).ok()?,
+        ))
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = light_pollution_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut lines = vec![format!("bortle {}", self.bortle)];
+        lines.extend(
+            self.domes
+                .iter()
+                .map(|dome| format!("dome {} {}", dome.azimuth_deg, dome.magnitudes)),
+        );
+        fs::write(path, lines.join("\n") + "\n")
+    }
+
+    /// Estimated sky brightness (mag/arcsec²) at an altitude and azimuth in
+    /// degrees; smaller is brighter
+    pub fn sky_brightness(&self, altitude: f64, azimuth: f64) -> f64 {
+        let zenith = BORTLE_ZENITH[(self.bortle.clamp(1, 9) - 1) as usize];
+
+        // Natural and artificial glow both thicken with the airmass
+        let sin_alt = altitude.max(0.0).to_radians().sin();
+        let airmass = 1.0 / (sin_alt + 0.025 * (-11.0 * sin_alt).exp());
+        let mut flux = airmass.sqrt();
+
+        for dome in &self.domes {
+            let off = (azimuth - dome.azimuth_deg + 180.0).rem_euclid(360.0) - 180.0;
+            let spread = (-(off / DOME_WIDTH).powi(2)).exp() * (-altitude / DOME_HEIGHT).exp();
+            flux += 10f64.powf(0.4 * dome.magnitudes) * spread;
+        }
+        zenith - 2.5 * flux.log10()
+    }
+
+    /// The darkest direction at `altitude` degrees, as (azimuth, mag/arcsec²)
+    pub fn darkest_azimuth(&self, altitude: f64) -> (f64, f64) {
+        (0..360)
+            .map(|az| (az as f64, self.sky_brightness(altitude, az as f64)))
+            .fold(
+                (0.0, f64::MIN),
+                |best, next| {
+                    if next.1 > best.1 {
+                        next
+                    } else {
+                        best
+                    }
+                },
+            )
+    }
+}
+
+/// Overlay color for a sky brightness, blended along `BRIGHTNESS_SCALE`
+fn brightness_color(brightness: f64) -> [f32; 4] {
+    let i = BRIGHTNESS_SCALE
+        .iter()
+        .position(|&(mag, _)| brightness >= mag)
+        .unwrap_or(BRIGHTNESS_SCALE.len());
+    let rgb = match i {
+        0 => BRIGHTNESS_SCALE[0].1,
+        i if i == BRIGHTNESS_SCALE.len() => BRIGHTNESS_SCALE[i - 1].1,
+        i => {
+            let (m0, c0) = BRIGHTNESS_SCALE[i - 1];
+            let (m1, c1) = BRIGHTNESS_SCALE[i];
+            let f = ((m0 - brightness) / (m0 - m1)) as f32;
+            [0, 1, 2].map(|k| c0[k] + (c1[k] - c0[k]) * f)
+        }
+    };
+    [rgb[0], rgb[1], rgb[2], OVERLAY_ALPHA]
+}
+
+/// Marker on the overlay mesh
+#[derive(Component)]
+struct SkyBrightnessOverlay;
+
+pub struct LightPollutionPlugin;
+impl Plugin for LightPollutionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LightPollution::load().unwrap_or_default())
+            .add_systems(
+                Update,
+                (update_brightness_overlay, show_brightness_overlay).chain(),
+            )
+            .add_systems(Last, save_light_pollution_on_exit);
+    }
+}
+
+fn overlay_mesh(pollution: &LightPollution) -> Mesh {
+    let (stacks, sectors) = ((90.0 / ALT_STEP) as u32, (360.0 / AZ_STEP) as u32);
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    for i in 0..=stacks {
+        for j in 0..=sectors {
+            let (alt, az) = (i as f64 * ALT_STEP, j as f64 * AZ_STEP);
+            positions.push((alt_az_direction(alt, az) * OVERLAY_DISTANCE).to_array());
+            colors.push(brightness_color(pollution.sky_brightness(alt, az)));
+        }
+    }
+
+    let row = sectors + 1;
+    let mut indices = Vec::new();
+    for i in 0..stacks {
+        for j in 0..sectors {
+            let (a, b) = (i * row + j, (i + 1) * row + j);
+            indices.extend([a, a + 1, b + 1, a, b + 1, b]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Rebuild the overlay whenever the estimate changes
+fn update_brightness_overlay(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    pollution: Res<LightPollution>,
+    layers: Res<SkyLayers>,
+    overlays: Query<Entity, With<SkyBrightnessOverlay>>,
+) {
+    if !pollution.is_changed() {
+        return;
+    }
+    for entity in &overlays {
+        commands.entity(entity).despawn();
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(overlay_mesh(&pollution))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::WHITE,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            cull_mode: None, // seen from inside
+            ..default()
+        })),
+        Transform::default(),
+        if layers.sky_brightness {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        },
+        SkyBrightnessOverlay,
+    ));
+}
+
+fn show_brightness_overlay(
+    layers: Res<SkyLayers>,
+    mut overlays: Query<&mut Visibility, With<SkyBrightnessOverlay>>,
+) {
+    for mut visibility in &mut overlays {
+        visibility.set_if_neq(if layers.sky_brightness {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+/// Saved on exit rather than on every change, as the settings sliders change
+/// it every frame while dragged
+fn save_light_pollution_on_exit(mut exit: MessageReader<AppExit>, pollution: Res<LightPollution>) {
+    if exit.read().next().is_none() {
+        return;
+    }
+    if let Err(e) = pollution.save() {
+        eprintln!("Failed to save light pollution estimate: {e}");
+    }
+}
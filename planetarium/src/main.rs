@@ -1,26 +1,78 @@
 // Cargo.toml
 // src/main.rs
+mod all_satellites;
+mod all_sky;
 mod camera;
 mod client;
 mod colors;
+mod compass;
+mod custom_catalog;
+mod dso_catalog;
+mod dso_markers;
 mod events;
-mod scene;
+mod export;
+mod faint_stars;
+mod field_rotation;
+mod fov_overlay;
+mod frame_rate;
+mod gui_link;
+mod horizon;
+mod labels;
+mod light_pollution;
+mod measure;
+mod meteor_showers;
+mod night_vision;
+mod observing_list;
+mod planets;
+mod satellite;
 mod server;
-mod star_catalog;
+mod site;
+mod slew_limits;
+mod small_bodies;
 mod starfield;
+mod stellarium;
+mod sun_avoidance;
 mod target;
+mod twilight;
 mod ui;
+mod view_state;
 use crate::events::PlanetariumEvent;
 use crate::target::TargetPlugin;
 use crate::ui::MenuPlugin;
 
+use all_satellites::AllSatellitesPlugin;
+use all_sky::AllSkyPlugin;
 use bevy::prelude::*;
 use bevy_egui::EguiPlugin;
 use camera::CameraPlugin;
-use scene::ScenePlugin;
+use compass::CompassPlugin;
+use custom_catalog::CustomCatalogPlugin;
+use dso_markers::DsoMarkerPlugin;
+use export::ExportPlugin;
+use faint_stars::FaintStarPlugin;
+use fov_overlay::FovOverlayPlugin;
+use frame_rate::FrameRatePlugin;
+use gui_link::GuiLinkPlugin;
+use horizon::HorizonPlugin;
+use labels::LabelPlugin;
+use light_pollution::LightPollutionPlugin;
+use measure::MeasurePlugin;
+use meteor_showers::MeteorShowerPlugin;
+use night_vision::NightVisionPlugin;
+use observing_list::ObservingListPlugin;
+use planetarium_core::scene::ScenePlugin;
+use planetarium_core::sky::SkyPlugin;
+use planets::PlanetPlugin;
+use satellite::SatellitePlugin;
+use slew_limits::SlewLimitPlugin;
+use small_bodies::SmallBodyPlugin;
 use starfield::StarfieldPlugin;
+use stellarium::StellariumPlugin;
+use sun_avoidance::SunAvoidancePlugin;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::Mutex;
+use twilight::TwilightPlugin;
+use view_state::ViewStatePlugin;
 
 #[derive(Component)]
 struct Star;
@@ -36,23 +88,12 @@ fn main() {
     // build the std channel
     let (event_tx, event_rx): (Sender<PlanetariumEvent>, Receiver<PlanetariumEvent>) = channel();
 
-    // spawn gRPC client for testing
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async move {
-            // wait a bit for server to come up
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-
-            if let Err(e) = client::send_event("hello from client".to_string()).await {
-                eprintln!("Client error: {e:?}");
-            }
-        });
-    });
-
     // spawn gRPC server, handing off loc_tx…
     std::thread::spawn(move || {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async move {
+            // Stellarium and compatible apps can send gotos alongside the GUI
+            tokio::spawn(stellarium::run(event_tx.clone()));
             server::run(event_tx).await.expect("gRPC server failed");
         });
     });
@@ -74,11 +115,37 @@ fn main() {
         }))
         .add_plugins(EguiPlugin::default())
         .add_plugins(CameraPlugin)
+        .add_plugins(SkyPlugin)
         .add_plugins(StarfieldPlugin)
+        .add_plugins(FaintStarPlugin)
+        .add_plugins(TwilightPlugin)
+        .add_plugins(PlanetPlugin)
+        .add_plugins(SmallBodyPlugin)
+        .add_plugins(SatellitePlugin)
+        .add_plugins(FovOverlayPlugin)
+        .add_plugins(LabelPlugin)
+        .add_plugins(MeasurePlugin)
+        .add_plugins(MeteorShowerPlugin)
+        .add_plugins(DsoMarkerPlugin)
+        .add_plugins(CompassPlugin)
+        .add_plugins(AllSkyPlugin)
         .add_plugins(ScenePlugin)
+        .add_plugins(HorizonPlugin)
+        .add_plugins(NightVisionPlugin)
+        .add_plugins(ExportPlugin)
+        .add_plugins(GuiLinkPlugin)
         .add_plugins(MenuPlugin)
+        .add_plugins(ViewStatePlugin)
         .add_systems(Update, event_listener_system)
         .add_plugins(TargetPlugin)
+        .add_plugins(SlewLimitPlugin)
+        .add_plugins(LightPollutionPlugin)
+        .add_plugins(FrameRatePlugin)
+        .add_plugins(StellariumPlugin)
+        .add_plugins(ObservingListPlugin)
+        .add_plugins(CustomCatalogPlugin)
+        .add_plugins(AllSatellitesPlugin)
+        .add_plugins(SunAvoidancePlugin)
         .run();
 }
 fn event_listener_system(
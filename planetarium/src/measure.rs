@@ -0,0 +1,234 @@
+// src/measure.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use std::f64::consts::PI;
+
+use crate::{
+    all_sky::perspective_view,
+    colors,
+    planets::Planet,
+    starfield::{equatorial_from_direction, star_direction, StarIndex, StarfieldState},
+};
+
+/// How close (pixels) a click must be to an object to snap to it
+const SNAP_RADIUS: f32 = 12.0;
+
+/// Cursor travel (pixels) beyond which a press is a camera drag, not a click
+const CLICK_SLOP: f32 = 4.0;
+
+/// Points along the drawn great-circle arc
+const ARC_SEGMENTS: usize = 32;
+
+/// One end of a measurement
+#[derive(Debug, Clone)]
+pub struct MeasurePoint {
+    /// (ra, dec) in radians
+    pub position: (f64, f64),
+    /// Name of the object clicked, if the click snapped to one
+    pub label: Option<String>,
+}
+
+/// Click two points on the sky to read their separation and position angle
+#[derive(Resource, Default)]
+pub struct MeasureTool {
+    pub active: bool,
+    /// Up to two points; a third click starts over
+    pub points: Vec<MeasurePoint>,
+}
+
+impl MeasureTool {
+    pub fn toggle(&mut self) {
+        self.active = !self.active;
+        self.points.clear();
+    }
+}
+
+pub struct MeasurePlugin;
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MeasureTool>().add_systems(
+            Update,
+            (pick_measure_points, draw_measurement)
+                .chain()
+                .run_if(perspective_view),
+        );
+    }
+}
+
+/// Great-circle distance (radians) between two (ra, dec) positions
+pub fn angular_separation(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (ra1, dec1) = a;
+    let (ra2, dec2) = b;
+    let h = ((dec2 - dec1) / 2.0).sin().powi(2)
+        + dec1.cos() * dec2.cos() * ((ra2 - ra1) / 2.0).sin().powi(2);
+    2.0 * h.sqrt().min(1.0).asin()
+}
+
+/// Position angle (radians) of `b` as seen from `a`, north through east
+pub fn position_angle(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (ra1, dec1) = a;
+    let (ra2, dec2) = b;
+    let d_ra = ra2 - ra1;
+    let y = d_ra.sin() * dec2.cos();
+    let x = dec1.cos() * dec2.sin() - dec1.sin() * dec2.cos() * d_ra.cos();
+    y.atan2(x).rem_euclid(2.0 * PI)
+}
+
+/// 12° 34' 56"
+fn format_angle(radians: f64) -> String {
+    let arcsec = (radians.to_degrees() * 3600.0).round() as i64;
+    format!(
+        "{}° {:02}' {:02}\"",
+        arcsec / 3600,
+        arcsec / 60 % 60,
+        arcsec % 60
+    )
+}
+
+/// Add a point on each click (not drag) in the sky, snapping to planets and named stars
+#[allow(clippy::too_many_arguments)]
+fn pick_measure_points(
+    mut tool: ResMut<MeasureTool>,
+    mut press: Local<Option<Vec2>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    state: Res<StarfieldState>,
+    stars: Res<StarIndex>,
+    planets: Query<(&Planet, &GlobalTransform)>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if !tool.active {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+    if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+        tool.toggle();
+        return;
+    }
+
+    let cursor = windows.single().ok().and_then(|w| w.cursor_position());
+    if buttons.just_pressed(MouseButton::Left) {
+        *press = cursor.filter(|_| !ctx.is_pointer_over_area());
+    }
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some((start, cursor)) = press.take().zip(cursor) else {
+        return;
+    };
+    if start.distance(cursor) > CLICK_SLOP {
+        return;
+    }
+    let Ok(ray) = camera.viewport_to_world(cam_gtf, cursor) else {
+        return;
+    };
+
+    let now = state.current_utc();
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    let near_cursor = |dir: Vec3| {
+        camera
+            .world_to_viewport(cam_gtf, cam_gtf.translation() + dir * 1_000.0)
+            .is_ok_and(|pos| pos.distance(cursor) < SNAP_RADIUS)
+    };
+
+    let planet = planets
+        .iter()
+        .map(|(planet, gtf)| (planet, gtf.translation() - cam_gtf.translation()))
+        .find(|&(_, dir)| near_cursor(dir.normalize()))
+        .map(|(planet, dir)| MeasurePoint {
+            position: equatorial_from_direction(now, lat, lon, dir),
+            label: Some(planet.body.name().to_string()),
+        });
+    let star = || {
+        stars.names.iter().find_map(|(name, hr)| {
+            let &(ra, dec) = stars.positions.get(hr)?;
+            near_cursor(star_direction(now, lat, lon, ra, dec)).then(|| MeasurePoint {
+                position: (ra, dec),
+                label: Some(name.clone()),
+            })
+        })
+    };
+    let point = planet.or_else(star).unwrap_or_else(|| MeasurePoint {
+        position: equatorial_from_direction(now, lat, lon, ray.direction.as_vec3()),
+        label: None,
+    });
+
+    if tool.points.len() >= 2 {
+        tool.points.clear();
+    }
+    tool.points.push(point);
+}
+
+/// Markers, the connecting arc and the readout, painted behind the UI windows
+fn draw_measurement(
+    tool: Res<MeasureTool>,
+    state: Res<StarfieldState>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if !tool.active || tool.points.is_empty() {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+
+    let now = state.current_utc();
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    let direction = |(ra, dec): (f64, f64)| star_direction(now, lat, lon, ra, dec);
+    let to_screen = |dir: Vec3| {
+        camera
+            .world_to_viewport(cam_gtf, cam_gtf.translation() + dir * 1_000.0)
+            .ok()
+            .map(|pos| egui::pos2(pos.x, pos.y))
+    };
+
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+    let stroke = egui::Stroke::new(1.5, colors::egui::ACCENT_YELLOW);
+
+    for point in &tool.points {
+        if let Some(center) = to_screen(direction(point.position)) {
+            painter.circle_stroke(center, 6.0, stroke);
+        }
+    }
+
+    let [a, b] = tool.points.as_slice() else {
+        return;
+    };
+    let (dir_a, dir_b) = (direction(a.position), direction(b.position));
+
+    // Sampled great circle; segments with an end behind the camera are skipped
+    let arc: Vec<Option<egui::Pos2>> = (0..=ARC_SEGMENTS)
+        .map(|i| to_screen(dir_a.slerp(dir_b, i as f32 / ARC_SEGMENTS as f32)))
+        .collect();
+    for pair in arc.windows(2) {
+        if let [Some(from), Some(to)] = *pair {
+            painter.line_segment([from, to], stroke);
+        }
+    }
+
+    let Some(anchor) = arc[ARC_SEGMENTS / 2] else {
+        return;
+    };
+    let mut text = format!(
+        "Sep {}\nPA {:.1}°",
+        format_angle(angular_separation(a.position, b.position)),
+        position_angle(a.position, b.position).to_degrees()
+    );
+    if a.label.is_some() || b.label.is_some() {
+        let name = |p: &MeasurePoint| p.label.clone().unwrap_or_else(|| "point".to_string());
+        text = format!("{} → {}\n{text}", name(a), name(b));
+    }
+    painter.text(
+        anchor + egui::vec2(8.0, -8.0),
+        egui::Align2::LEFT_BOTTOM,
+        text,
+        egui::FontId::proportional(12.0),
+        colors::egui::ACCENT_YELLOW,
+    );
+}
@@ -0,0 +1,177 @@
+// src/meteor_showers.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use chrono::{Datelike, NaiveDate};
+use std::io::{self, Error, ErrorKind};
+
+use crate::{
+    all_sky::perspective_view,
+    colors,
+    starfield::{star_direction, SkyLayers, StarfieldState},
+};
+
+/// IMO shower calendar, bundled so the layer works offline
+const METEOR_SHOWERS_CSV: &str = include_str!("../assets/meteor_showers.csv");
+
+/// An annual meteor shower
+#[derive(Debug, Clone)]
+pub struct MeteorShower {
+    pub name: String,
+    /// (month, day) of the first and last active days and of the peak
+    pub start: (u32, u32),
+    pub end: (u32, u32),
+    pub peak: (u32, u32),
+    /// Radiant at the peak, J2000 degrees
+    pub ra_deg: f64,
+    pub dec_deg: f64,
+    /// Daily radiant drift, degrees
+    pub ra_drift: f64,
+    pub dec_drift: f64,
+    /// Zenithal hourly rate at the peak
+    pub zhr: u32,
+}
+
+/// Days from `month_day` to `date`, taking whichever year puts them within
+/// half a year of each other
+fn days_since(month_day: (u32, u32), date: NaiveDate) -> Option<i64> {
+    let (month, day) = month_day;
+    let anchor = NaiveDate::from_ymd_opt(date.year(), month, day)?;
+    Some(match (date - anchor).num_days() {
+        d if d > 182 => d - 365,
+        d if d < -182 => d + 365,
+        d => d,
+    })
+}
+
+impl MeteorShower {
+    /// Days from the peak to `date` (negative before it), or `None` if the
+    /// shower isn't active then
+    pub fn days_from_peak(&self, date: NaiveDate) -> Option<i64> {
+        // any common year will do for the fixed calendar dates
+        let in_year = |(month, day): (u32, u32)| NaiveDate::from_ymd_opt(2001, month, day);
+        let first = days_since(self.peak, in_year(self.start)?)?;
+        let last = days_since(self.peak, in_year(self.end)?)?;
+        let days = days_since(self.peak, date)?;
+        (first..=last).contains(&days).then_some(days)
+    }
+
+    /// Radiant `days` after the peak, (ra, dec) in radians
+    pub fn radiant(&self, days: i64) -> (f64, f64) {
+        let ra = self.ra_deg + self.ra_drift * days as f64;
+        let dec = self.dec_deg + self.dec_drift * days as f64;
+        (ra.rem_euclid(360.0).to_radians(), dec.to_radians())
+    }
+}
+
+/// The bundled shower calendar
+#[derive(Resource)]
+pub struct MeteorShowers(pub Vec<MeteorShower>);
+
+impl MeteorShowers {
+    pub fn load() -> Self {
+        Self(parse_showers(METEOR_SHOWERS_CSV).expect("bundled meteor shower calendar is valid"))
+    }
+}
+
+fn invalid(line: &str) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!("bad meteor shower line: {line}"),
+    )
+}
+
+/// "MM-DD"
+fn parse_month_day(s: &str) -> Option<(u32, u32)> {
+    let (month, day) = s.trim().split_once('-')?;
+    Some((month.parse().ok()?, day.parse().ok()?))
+}
+
+/// Parse the semicolon separated calendar; the first non-comment line is the header
+pub fn parse_showers(data: &str) -> io::Result<Vec<MeteorShower>> {
+    data.lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .skip(1)
+        .map(|line| {
+            let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+            let [_code, name, start, end, peak, ra, dec, d_ra, d_dec, zhr] = fields[..] else {
+                return Err(invalid(line));
+            };
+            let number = |s: &str| s.parse::<f64>().map_err(|_| invalid(line));
+            let month_day = |s: &str| parse_month_day(s).ok_or_else(|| invalid(line));
+            Ok(MeteorShower {
+                name: name.to_string(),
+                start: month_day(start)?,
+                end: month_day(end)?,
+                peak: month_day(peak)?,
+                ra_deg: number(ra)?,
+                dec_deg: number(dec)?,
+                ra_drift: number(d_ra)?,
+                dec_drift: number(d_dec)?,
+                zhr: zhr.parse().map_err(|_| invalid(line))?,
+            })
+        })
+        .collect()
+}
+
+pub struct MeteorShowerPlugin;
+impl Plugin for MeteorShowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(MeteorShowers::load())
+            .add_systems(Update, draw_meteor_radiants.run_if(perspective_view));
+    }
+}
+
+/// Mark the radiants of the showers active on the simulated date, painted
+/// behind the UI windows
+fn draw_meteor_radiants(
+    layers: Res<SkyLayers>,
+    state: Res<StarfieldState>,
+    showers: Res<MeteorShowers>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if !layers.meteor_radiants {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+    let stroke = egui::Stroke::new(1.5, colors::egui::METEOR_RADIANT);
+
+    let now = state.current_utc();
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    for shower in &showers.0 {
+        let Some(days) = shower.days_from_peak(now.date_naive()) else {
+            continue;
+        };
+        let (ra, dec) = shower.radiant(days);
+        let dir = star_direction(now, lat, lon, ra, dec);
+        if dir.y < 0.0 {
+            continue; // below the horizon
+        }
+        let world = cam_gtf.translation() + dir * 100_000.0;
+        let Ok(pos) = camera.world_to_viewport(cam_gtf, world) else {
+            continue;
+        };
+
+        // A small burst: a ring with four rays
+        let center = egui::pos2(pos.x, pos.y);
+        painter.circle_stroke(center, 4.0, stroke);
+        for (dx, dy) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+            let ray = egui::vec2(dx, dy);
+            painter.line_segment([center + ray * 6.0, center + ray * 11.0], stroke);
+        }
+
+        let peak = if days == 0 { "peak, " } else { "" };
+        painter.text(
+            center + egui::vec2(14.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            format!("{} ({peak}ZHR {})", shower.name, shower.zhr),
+            egui::FontId::proportional(12.0),
+            colors::egui::METEOR_RADIANT,
+        );
+    }
+}
@@ -0,0 +1,101 @@
+// src/night_vision.rs
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, epaint::shape_transform::adjust_colors};
+use bevy_egui::{EguiFullOutput, EguiPostUpdateSet};
+
+use crate::camera::setup_camera;
+
+/// Brightest red left on screen; kept below full to protect dark adaptation
+const RED_LEVEL: f32 = 0.8;
+
+/// Dim red rendering of the sky and the UI, for use at the telescope
+#[derive(Resource, Default)]
+pub struct NightVision(pub bool);
+
+/// Screen-filling quad in front of the camera that multiplies the scene down to red
+#[derive(Component)]
+struct RedFilter;
+
+pub struct NightVisionPlugin;
+impl Plugin for NightVisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NightVision>()
+            .add_systems(Startup, spawn_red_filter.after(setup_camera))
+            .add_systems(Update, toggle_red_filter)
+            .add_systems(
+                PostUpdate,
+                tint_ui
+                    .after(EguiPostUpdateSet::EndPass)
+                    .before(EguiPostUpdateSet::ProcessOutput),
+            );
+    }
+}
+
+/// The filter is the nearest transparent object, so it's blended last over
+/// the whole scene, sky background included
+fn spawn_red_filter(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    cam_q: Query<Entity, With<Camera3d>>,
+) {
+    let Ok(camera) = cam_q.single() else {
+        return;
+    };
+
+    let filter = commands
+        .spawn((
+            // Far larger than the view at any zoom
+            Mesh3d(meshes.add(Rectangle::new(100.0, 100.0))),
+            MeshMaterial3d(mats.add(StandardMaterial {
+                base_color: Color::linear_rgb(RED_LEVEL, 0.0, 0.0),
+                unlit: true,
+                alpha_mode: AlphaMode::Multiply,
+                ..default()
+            })),
+            Transform::from_xyz(0.0, 0.0, -1.0),
+            Visibility::Hidden,
+            RedFilter,
+        ))
+        .id();
+    commands.entity(camera).add_child(filter);
+}
+
+fn toggle_red_filter(
+    night_vision: Res<NightVision>,
+    mut q: Query<&mut Visibility, With<RedFilter>>,
+) {
+    if !night_vision.is_changed() {
+        return;
+    }
+    for mut vis in &mut q {
+        *vis = if night_vision.0 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Luminance of an egui color, as red of the same brightness
+fn to_red(color: &mut egui::Color32) {
+    let [r, g, b, a] = color.to_array();
+    let luminance = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+    *color = egui::Color32::from_rgba_premultiplied((luminance * RED_LEVEL) as u8, 0, 0, a);
+}
+
+/// Egui is drawn after the scene, so its shapes are recolored before tessellation
+fn tint_ui(night_vision: Res<NightVision>, mut outputs: Query<&mut EguiFullOutput>) {
+    if !night_vision.0 {
+        return;
+    }
+    for mut output in &mut outputs {
+        let Some(full_output) = output.0.as_mut() else {
+            continue;
+        };
+        for clipped in &mut full_output.shapes {
+            adjust_colors(&mut clipped.shape, to_red);
+        }
+    }
+}
@@ -0,0 +1,190 @@
+// src/observing_list.rs
+
+use bevy::prelude::*;
+use chrono::{DateTime, Duration, Utc};
+use overpass_planner::{CelestialBody, PropagationContext};
+use std::f64::consts::PI;
+use std::sync::Arc;
+use std::{fs, io, path::PathBuf};
+
+use crate::{
+    site::config_file,
+    starfield::{hour_angle_from_horizontal, local_sidereal_time, StarfieldState, SIDEREAL_RATE},
+};
+
+/// Something the user wants to come back to
+#[derive(Clone)]
+pub enum ObservingItem {
+    /// A deep-sky object, fixed in RA/Dec (radians)
+    Dso {
+        name: String,
+        ra: f64,
+        dec: f64,
+    },
+    Planet(CelestialBody),
+    /// A satellite, with the TLE it was added with
+    Satellite {
+        norad_id: u32,
+        name: String,
+        tle: String,
+        /// `None` if the TLE doesn't parse
+        propagator: Option<Arc<PropagationContext>>,
+    },
+}
+
+impl ObservingItem {
+    pub fn satellite(norad_id: u32, name: String, tle: String) -> Self {
+        let propagator = PropagationContext::from_tle(&tle).ok().map(Arc::new);
+        ObservingItem::Satellite {
+            norad_id,
+            name,
+            tle,
+            propagator,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            ObservingItem::Dso { name, .. } | ObservingItem::Satellite { name, .. } => name,
+            ObservingItem::Planet(body) => body.name(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ObservingItem::Dso { .. } => "DSO",
+            ObservingItem::Planet(_) => "Planet",
+            ObservingItem::Satellite { .. } => "Satellite",
+        }
+    }
+
+    /// Whether both are the same object, so nothing is listed twice
+    pub fn same_object(&self, other: &ObservingItem) -> bool {
+        match (self, other) {
+            (ObservingItem::Dso { name: a, .. }, ObservingItem::Dso { name: b, .. }) => a == b,
+            (ObservingItem::Planet(a), ObservingItem::Planet(b)) => a == b,
+            (
+                ObservingItem::Satellite { norad_id: a, .. },
+                ObservingItem::Satellite { norad_id: b, .. },
+            ) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Tab-separated, as names have spaces in them
+    fn to_line(&self) -> String {
+        match self {
+            ObservingItem::Dso { name, ra, dec } => format!("dso\t{name}\t{ra}\t{dec}"),
+            ObservingItem::Planet(body) => format!("planet\t{}", body.name()),
+            ObservingItem::Satellite {
+                norad_id,
+                name,
+                tle,
+                ..
+            } => {
+                let tle: Vec<&str> = tle.lines().map(str::trim).collect();
+                format!("satellite\t{norad_id}\t{name}\t{}", tle.join("\t"))
+            }
+        }
+    }
+
+    fn from_line(line: &str) -> Option<ObservingItem> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields.as_slice() {
+            ["dso", name, ra, dec] => Some(ObservingItem::Dso {
+                name: name.to_string(),
+                ra: ra.parse().ok()?,
+                dec: dec.parse().ok()?,
+            }),
+            ["planet", name] => CelestialBody::PLANETS
+                .into_iter()
+                .chain([CelestialBody::Moon])
+                .find(|body| body.name() == *name)
+                .map(ObservingItem::Planet),
+            ["satellite", norad_id, name, tle @ ..] if !tle.is_empty() => Some(
+                ObservingItem::satellite(norad_id.parse().ok()?, name.to_string(), tle.join("\n")),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// The observing list, remembered between runs
+#[derive(Resource, Default, Clone)]
+pub struct ObservingList {
+    pub items: Vec<ObservingItem>,
+}
+
+fn observing_list_path() -> Option<PathBuf> {
+    config_file("planetarium_observing_list.txt")
+}
+
+impl ObservingList {
+    /// The saved list, if there is one
+    pub fn load() -> Option<ObservingList> {
+        let data = fs::read_to_string(observing_list_path()?).ok()?;
+        Some(ObservingList {
+            items: data.lines().filter_map(ObservingItem::from_line).collect(),
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = observing_list_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let lines: Vec<String> = self.items.iter().map(ObservingItem::to_line).collect();
+        fs::write(path, lines.join("\n") + "\n")
+    }
+
+    pub fn contains(&self, item: &ObservingItem) -> bool {
+        self.items.iter().any(|listed| listed.same_object(item))
+    }
+
+    /// Add `item` unless it is already listed
+    pub fn add(&mut self, item: ObservingItem) {
+        if !self.contains(&item) {
+            self.items.push(item);
+        }
+    }
+}
+
+/// The meridian transit nearest `now` of an object at hour angle `ha`
+/// (radians); in the past if it has already crossed
+fn nearest_transit(now: DateTime<Utc>, ha: f64) -> DateTime<Utc> {
+    let to_go = (PI - ha).rem_euclid(2.0 * PI) - PI;
+    now + Duration::milliseconds((to_go / SIDEREAL_RATE * 1000.0) as i64)
+}
+
+/// Meridian transit of a fixed RA/Dec (radians)
+pub fn fixed_transit(state: &StarfieldState, ra: f64) -> DateTime<Utc> {
+    let now = state.current_utc();
+    let ha = local_sidereal_time(now, state.lon_deg.to_radians()) - ra;
+    nearest_transit(now, ha)
+}
+
+/// Meridian transit of something now at `altitude`/`azimuth` (degrees),
+/// close enough for a planet whose RA barely moves in a day
+pub fn horizontal_transit(state: &StarfieldState, altitude: f64, azimuth: f64) -> DateTime<Utc> {
+    let (ha, _) = hour_angle_from_horizontal(state.lat_deg.to_radians(), altitude, azimuth);
+    nearest_transit(state.current_utc(), ha)
+}
+
+pub struct ObservingListPlugin;
+impl Plugin for ObservingListPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ObservingList::load().unwrap_or_default())
+            .add_systems(Last, save_observing_list);
+    }
+}
+
+/// Saved on every change; items are only added and removed by clicks
+fn save_observing_list(list: Res<ObservingList>) {
+    if !list.is_changed() || list.is_added() {
+        return;
+    }
+    if let Err(e) = list.save() {
+        eprintln!("Failed to save observing list: {e}");
+    }
+}
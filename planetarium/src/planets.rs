@@ -0,0 +1,345 @@
+// src/planets.rs
+
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use overpass_planner::{body_position, CelestialBody, ObserverLocation};
+use std::f32::consts::{PI, TAU};
+
+use crate::starfield::{alt_az_direction, magnitude_to_scale, StarfieldState};
+
+/// Planets sit just inside the star sphere so they draw over the stars
+const PLANET_DISTANCE: f32 = 90_000.0;
+
+/// Planets barely move against the sky, so ephemeris lookups run once a second
+const UPDATE_INTERVAL_SECS: f32 = 1.0;
+
+/// On-screen diameter (pixels) from which a body is drawn as a disc
+const DISC_MIN_PIXELS: f32 = 6.0;
+/// Points around the limb of a disc
+const DISC_SEGMENTS: u32 = 64;
+/// Unlit side of a disc, dark enough to hide the stars behind it
+const NIGHT_SIDE_COLOR: [f32; 4] = [0.02, 0.02, 0.025, 1.0];
+
+/// A planet, or the Moon, in the sky, with its last computed apparent position
+#[derive(Component)]
+pub struct Planet {
+    pub body: CelestialBody,
+    /// Altitude (degrees), `None` until the ephemeris has been evaluated
+    pub altitude: Option<f64>,
+    /// Azimuth (degrees)
+    pub azimuth: Option<f64>,
+    /// Apparent radius of the disc (degrees)
+    pub angular_radius: Option<f64>,
+    /// Sun-body-observer angle (radians): 0 when full, π when new
+    pub phase_angle: f64,
+    /// Direction on the sky from the center of the disc towards the Sun
+    pub bright_limb: Vec3,
+}
+
+/// The body's disc, shown instead of the point once it is big enough on
+/// screen; holds the `Planet` entity it belongs to
+#[derive(Component)]
+struct PlanetDisc(Entity);
+
+#[derive(Resource)]
+pub(crate) struct PlanetUpdateTimer(Timer);
+
+/// Last ephemeris error, so it is reported once instead of every second
+#[derive(Resource, Default)]
+pub struct PlanetEphemerisError(pub Option<String>);
+
+pub struct PlanetPlugin;
+impl Plugin for PlanetPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlanetUpdateTimer(Timer::from_seconds(
+            UPDATE_INTERVAL_SECS,
+            TimerMode::Repeating,
+        )))
+        .init_resource::<PlanetEphemerisError>()
+        .add_systems(Startup, spawn_planets)
+        .add_systems(
+            Update,
+            (
+                update_planet_positions,
+                update_planet_discs,
+                billboard_planets,
+                show_planet_discs,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Rough typical brightness, used only to size the sprite
+fn typical_magnitude(body: CelestialBody) -> f32 {
+    match body {
+        CelestialBody::Mercury => 0.0,
+        CelestialBody::Venus => -4.0,
+        CelestialBody::Mars => 0.5,
+        CelestialBody::Jupiter => -2.5,
+        CelestialBody::Saturn => 0.5,
+        CelestialBody::Uranus => 5.7,
+        CelestialBody::Neptune => 7.8,
+        CelestialBody::Sun | CelestialBody::Moon => -4.0,
+    }
+}
+
+fn planet_color(body: CelestialBody) -> Vec3 {
+    match body {
+        CelestialBody::Mercury => Vec3::new(0.8, 0.75, 0.7),
+        CelestialBody::Venus => Vec3::new(1.0, 0.95, 0.8),
+        CelestialBody::Mars => Vec3::new(1.0, 0.5, 0.3),
+        CelestialBody::Jupiter => Vec3::new(1.0, 0.85, 0.65),
+        CelestialBody::Saturn => Vec3::new(0.95, 0.85, 0.55),
+        CelestialBody::Uranus => Vec3::new(0.6, 0.9, 0.95),
+        CelestialBody::Neptune => Vec3::new(0.45, 0.6, 1.0),
+        CelestialBody::Moon => Vec3::new(0.85, 0.85, 0.8),
+        CelestialBody::Sun => Vec3::ONE,
+    }
+}
+
+pub(crate) fn spawn_planets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let quad = meshes.add(Mesh::from(Rectangle::new(1.0, 1.0)));
+    let texture = assets.load("star.png");
+    let disc_material = mats.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        cull_mode: None,
+        ..default()
+    });
+
+    for body in CelestialBody::PLANETS
+        .into_iter()
+        .chain([CelestialBody::Moon])
+    {
+        let mix = planet_color(body);
+        let color = Color::linear_rgb(mix.x * 100.0, mix.y * 100.0, mix.z * 100.0);
+        let mat = mats.add(StandardMaterial {
+            base_color_texture: Some(texture.clone()),
+            base_color: color,
+            emissive: color.into(),
+            alpha_mode: AlphaMode::Add,
+            unlit: true,
+            ..default()
+        });
+
+        // Scale relative to the star sphere so planets look like stars of their magnitude
+        let scale = magnitude_to_scale(typical_magnitude(body)) * (PLANET_DISTANCE / 100_000.0);
+
+        let planet = commands
+            .spawn((
+                Mesh3d(quad.clone()),
+                MeshMaterial3d(mat),
+                Transform::from_scale(Vec3::splat(scale)),
+                // hidden until the first ephemeris lookup succeeds
+                Visibility::Hidden,
+                Planet {
+                    body,
+                    altitude: None,
+                    azimuth: None,
+                    angular_radius: None,
+                    phase_angle: 0.0,
+                    bright_limb: Vec3::X,
+                },
+            ))
+            .id();
+
+        commands.spawn((
+            Mesh3d(meshes.add(disc_mesh(body, 0.0))),
+            MeshMaterial3d(disc_material.clone()),
+            Transform::default(),
+            Visibility::Hidden,
+            PlanetDisc(planet),
+        ));
+    }
+}
+
+/// Unit disc facing +Z with the Sun towards +X: the night side, and over it
+/// the lit part between the bright limb and the terminator, a half ellipse
+/// that narrows as the phase angle grows
+fn disc_mesh(body: CelestialBody, phase_angle: f64) -> Mesh {
+    let mix = planet_color(body);
+    let lit = [mix.x, mix.y, mix.z, 1.0];
+    let mut positions = vec![[0.0, 0.0, -0.01]];
+    let mut colors = vec![NIGHT_SIDE_COLOR];
+    let mut indices = Vec::new();
+
+    for k in 0..=DISC_SEGMENTS {
+        let angle = TAU * k as f32 / DISC_SEGMENTS as f32;
+        positions.push([angle.cos(), angle.sin(), -0.01]);
+        colors.push(NIGHT_SIDE_COLOR);
+    }
+    indices.extend((1..=DISC_SEGMENTS).flat_map(|k| [0, k, k + 1]));
+
+    // rows from the south cusp to the north one, terminator then limb
+    let first = positions.len() as u32;
+    let terminator = -(phase_angle.cos() as f32);
+    let rows = DISC_SEGMENTS / 2;
+    for k in 0..=rows {
+        let t = -PI / 2.0 + PI * k as f32 / rows as f32;
+        positions.push([terminator * t.cos(), t.sin(), 0.0]);
+        positions.push([t.cos(), t.sin(), 0.0]);
+        colors.extend([lit, lit]);
+    }
+    indices.extend((0..rows).flat_map(|k| {
+        let a = first + 2 * k;
+        [a, a + 1, a + 3, a, a + 3, a + 2]
+    }));
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Once a second: evaluate the ephemeris for the time and place the sky is showing
+pub(crate) fn update_planet_positions(
+    time: Res<Time>,
+    mut timer: ResMut<PlanetUpdateTimer>,
+    mut last_error: ResMut<PlanetEphemerisError>,
+    state: Res<StarfieldState>,
+    mut q: Query<(&mut Planet, &mut Transform, &mut Visibility)>,
+) {
+    // Also update right away on the first frame and whenever the site or time changes
+    if !timer.0.tick(time.delta()).just_finished() && !state.is_changed() {
+        return;
+    }
+
+    let location = ObserverLocation {
+        latitude: state.lat_deg,
+        longitude: state.lon_deg,
+        altitude: state.alt_m,
+    };
+    let now = state.current_utc();
+    // lights the discs; without it they are drawn full
+    let sun = body_position(CelestialBody::Sun, location, now).ok();
+
+    for (mut planet, mut tf, mut vis) in &mut q {
+        match body_position(planet.body, location, now) {
+            Ok(position) => {
+                planet.altitude = Some(position.altitude);
+                planet.azimuth = Some(position.azimuth);
+                planet.angular_radius = Some(position.angular_radius);
+                if let Some(sun) = sun {
+                    let toward_body =
+                        alt_az_direction(position.altitude, position.azimuth).as_dvec3();
+                    let toward_sun = alt_az_direction(sun.altitude, sun.azimuth).as_dvec3();
+                    let body_km = toward_body * position.distance_km;
+                    let sun_km = toward_sun * sun.distance_km;
+                    planet.phase_angle = (sun_km - body_km).angle_between(-body_km);
+                    planet.bright_limb = (toward_sun - toward_body * toward_body.dot(toward_sun))
+                        .try_normalize()
+                        .unwrap_or_else(|| toward_body.any_orthonormal_vector())
+                        .as_vec3();
+                }
+                tf.translation =
+                    alt_az_direction(position.altitude, position.azimuth) * PLANET_DISTANCE;
+                *vis = Visibility::Inherited;
+                last_error.0 = None;
+            }
+            Err(e) => {
+                let message = e.to_string();
+                if last_error.0.as_ref() != Some(&message) {
+                    eprintln!("Planet ephemeris unavailable: {message}");
+                    last_error.0 = Some(message);
+                }
+                planet.altitude = None;
+                planet.azimuth = None;
+                planet.angular_radius = None;
+                *vis = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+/// Move each disc onto its body, sized and turned so the lit side faces the
+/// Sun, whenever the ephemeris is evaluated
+fn update_planet_discs(
+    planets: Query<Ref<Planet>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut discs: Query<(&PlanetDisc, &Mesh3d, &mut Transform)>,
+) {
+    for (disc, mesh, mut tf) in &mut discs {
+        let Ok(planet) = planets.get(disc.0) else {
+            continue;
+        };
+        if !planet.is_changed() {
+            continue;
+        }
+        let (Some(alt), Some(az), Some(radius)) =
+            (planet.altitude, planet.azimuth, planet.angular_radius)
+        else {
+            continue;
+        };
+
+        let toward_body = alt_az_direction(alt, az);
+        // +Z back at the observer, +X towards the Sun
+        let z = -toward_body;
+        let x = planet.bright_limb;
+        *tf = Transform {
+            translation: toward_body * PLANET_DISTANCE,
+            rotation: Quat::from_mat3(&Mat3::from_cols(x, z.cross(x), z)),
+            scale: Vec3::splat(radius.to_radians().tan() as f32 * PLANET_DISTANCE),
+        };
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = disc_mesh(planet.body, planet.phase_angle);
+        }
+    }
+}
+
+/// Swap each body's point for its disc once the disc is `DISC_MIN_PIXELS`
+/// across, which for most planets takes a deep zoom
+fn show_planet_discs(
+    cam_q: Query<&Projection, With<Camera3d>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut planets: Query<(&Planet, &mut Visibility), Without<PlanetDisc>>,
+    mut discs: Query<(&PlanetDisc, &mut Visibility)>,
+) {
+    let (Ok(Projection::Perspective(persp)), Ok(window)) = (cam_q.single(), windows.single())
+    else {
+        return;
+    };
+    let pixels_per_radian = window.height() / persp.fov;
+
+    for (disc, mut disc_vis) in &mut discs {
+        let Ok((planet, mut vis)) = planets.get_mut(disc.0) else {
+            continue;
+        };
+        let Some(radius) = planet.angular_radius else {
+            disc_vis.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        let pixels = 2.0 * radius.to_radians() as f32 * pixels_per_radian;
+        let (point, shape) = if pixels >= DISC_MIN_PIXELS {
+            (Visibility::Hidden, Visibility::Inherited)
+        } else {
+            (Visibility::Inherited, Visibility::Hidden)
+        };
+        vis.set_if_neq(point);
+        disc_vis.set_if_neq(shape);
+    }
+}
+
+/// Make every planet quad face the camera
+fn billboard_planets(
+    cam_q: Query<&GlobalTransform, With<Camera>>,
+    mut q: Query<&mut Transform, With<Planet>>,
+) {
+    let Ok(cam_tf) = cam_q.single() else {
+        return;
+    };
+    let cam_rot = cam_tf.compute_transform().rotation;
+    for mut tf in &mut q {
+        tf.rotation = cam_rot;
+    }
+}
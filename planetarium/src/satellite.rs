@@ -0,0 +1,504 @@
+// src/satellite.rs
+
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::visibility::RenderLayers;
+use bevy::mesh::PrimitiveTopology;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use chrono::{DateTime, Duration, Utc};
+use overpass_planner::{
+    is_satellite_lit, ObserverLocation, Overpass, PassMarker, PropagationContext, Propagator,
+};
+use std::sync::Arc;
+
+use crate::{
+    all_sky::perspective_view,
+    camera::{camera_follow_system, CameraFollow, CameraTarget},
+    colors,
+    starfield::{alt_az_direction, StarfieldState},
+    ui::TimeDisplay,
+};
+
+/// Pass paths sit inside the star sphere and the planets
+pub const PATH_DISTANCE: f32 = 80_000.0;
+
+/// Render layer of the pass path, so exports can leave it out
+pub const PASS_LAYER: usize = 1;
+
+/// How far back the trail behind the live marker reaches
+const TRAIL_LENGTH_SECS: i64 = 60;
+/// Time between trail samples
+const TRAIL_STEP_SECS: i64 = 5;
+
+/// Longest predicted path the satellite window offers
+pub const MAX_PREDICTION_MINUTES: u32 = 60;
+/// Time between predicted path samples
+const PREDICTION_STEP_SECS: i64 = 10;
+/// Gap (pixels) between the dots of the predicted path
+const PREDICTION_DOT_SPACING: f32 = 6.0;
+
+/// The satellite loaded in the satellite window, followed live in the sky
+#[derive(Resource, Default)]
+pub struct TrackedSatellite(pub Option<TrackedSatelliteInfo>);
+
+pub struct TrackedSatelliteInfo {
+    pub norad_id: u32,
+    pub name: Option<String>,
+    pub propagator: Arc<PropagationContext>,
+}
+
+/// Live position of the tracked satellite, `None` when it is below the horizon
+/// or cannot be propagated
+#[derive(Resource, Default)]
+pub struct TrackedSatellitePosition(pub Option<(f64, f64)>);
+
+/// Marker on the live satellite quad
+#[derive(Component)]
+pub(crate) struct SatelliteMarker;
+
+/// Marker on the trail line behind the live satellite
+#[derive(Component)]
+struct SatelliteTrail;
+
+#[derive(Resource)]
+struct TrailUpdateTimer(Timer);
+
+/// The overpass selected in the satellite window, with its trajectory sampled
+#[derive(Resource, Default)]
+pub struct SelectedPass(pub Option<Overpass>);
+
+/// The pass chosen with Track. The camera waits at its rise point and then
+/// follows the satellite across, until the pass is over.
+#[derive(Resource, Default)]
+pub struct TrackedPass(pub Option<Overpass>);
+
+/// Marker on the pass path line mesh
+#[derive(Component)]
+struct PassPath;
+
+/// How far ahead (minutes) the tracked satellite's predicted path reaches; 0 hides it
+#[derive(Resource)]
+pub struct TrailPrediction {
+    pub minutes: u32,
+}
+
+impl Default for TrailPrediction {
+    fn default() -> Self {
+        TrailPrediction { minutes: 10 }
+    }
+}
+
+/// One sample along the predicted path
+pub(crate) struct PredictedPoint {
+    pub time: DateTime<Utc>,
+    pub altitude: f64,
+    pub azimuth: f64,
+    /// Sunlit rather than in Earth's shadow
+    pub lit: bool,
+}
+
+/// The tracked satellite's path over the next `TrailPrediction::minutes`
+#[derive(Resource, Default)]
+pub(crate) struct PredictedPath(pub Vec<PredictedPoint>);
+
+pub struct SatellitePlugin;
+impl Plugin for SatellitePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectedPass>()
+            .init_resource::<TrackedPass>()
+            .init_resource::<TrackedSatellite>()
+            .init_resource::<TrackedSatellitePosition>()
+            .init_resource::<TrailPrediction>()
+            .init_resource::<PredictedPath>()
+            .insert_resource(TrailUpdateTimer(Timer::from_seconds(
+                1.0,
+                TimerMode::Repeating,
+            )))
+            .add_systems(Startup, spawn_satellite_marker)
+            .add_systems(
+                Update,
+                (update_pass_path, draw_pass_markers.run_if(perspective_view)),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_satellite_marker,
+                    update_satellite_trail,
+                    update_predicted_path,
+                    draw_predicted_path.run_if(perspective_view),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                steer_camera_along_pass
+                    .after(update_satellite_marker)
+                    .before(camera_follow_system),
+            );
+    }
+}
+
+/// Rebuild the pass path whenever the selection changes
+fn update_pass_path(
+    mut commands: Commands,
+    selected: Res<SelectedPass>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    existing: Query<Entity, With<PassPath>>,
+) {
+    if !selected.is_changed() {
+        return;
+    }
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+
+    let Some(overpass) = &selected.0 else {
+        return;
+    };
+    if overpass.trajectory.len() < 2 {
+        return;
+    }
+
+    // The path is fixed in alt/az, so it lives in world space rather than under
+    // the rotating starfield root
+    let positions: Vec<[f32; 3]> = overpass
+        .trajectory
+        .iter()
+        .map(|p| (alt_az_direction(p.altitude, p.azimuth) * PATH_DISTANCE).to_array())
+        .collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(mats.add(StandardMaterial {
+            base_color: colors::TRACKING_TARGET_COLOR,
+            unlit: true,
+            ..default()
+        })),
+        Transform::default(),
+        Visibility::default(),
+        RenderLayers::layer(PASS_LAYER),
+        PassPath,
+    ));
+}
+
+/// Rise, culmination and set markers with their times, painted behind the UI windows
+fn draw_pass_markers(
+    selected: Res<SelectedPass>,
+    time_display: Res<TimeDisplay>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    let Some(overpass) = &selected.0 else {
+        return;
+    };
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+
+    for (marker, point) in overpass.sky_plot().markers {
+        // back from the polar plot to alt/az
+        let altitude = 90.0 * (1.0 - point.r);
+        let azimuth = point.theta.to_degrees();
+        let world = cam_gtf.translation() + alt_az_direction(altitude, azimuth) * PATH_DISTANCE;
+        let Ok(pos) = camera.world_to_viewport(cam_gtf, world) else {
+            continue;
+        };
+
+        let label = match marker {
+            PassMarker::Rise => "Rise",
+            PassMarker::Culmination => "Max",
+            PassMarker::Set => "Set",
+        };
+        let center = egui::pos2(pos.x, pos.y);
+        painter.circle_stroke(
+            center,
+            5.0,
+            egui::Stroke::new(1.5, colors::egui::ACCENT_YELLOW),
+        );
+        painter.text(
+            center + egui::vec2(8.0, -8.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{label} {}", time_display.time(point.timestamp)),
+            egui::FontId::proportional(12.0),
+            colors::egui::ACCENT_YELLOW,
+        );
+    }
+}
+
+fn spawn_satellite_marker(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let color = Color::linear_rgb(30.0, 100.0, 30.0);
+    commands.spawn((
+        Mesh3d(meshes.add(Mesh::from(Rectangle::new(1.0, 1.0)))),
+        MeshMaterial3d(mats.add(StandardMaterial {
+            base_color_texture: Some(assets.load("star.png")),
+            base_color: color,
+            emissive: color.into(),
+            alpha_mode: AlphaMode::Add,
+            unlit: true,
+            ..default()
+        })),
+        Transform::from_scale(Vec3::splat(1_500.0)),
+        Visibility::Hidden,
+        SatelliteMarker,
+    ));
+
+    let mut trail = Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::default());
+    trail.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0_f32; 3]; 2]);
+    commands.spawn((
+        Mesh3d(meshes.add(trail)),
+        MeshMaterial3d(mats.add(StandardMaterial {
+            base_color: colors::MOUNT_TARGET_COLOR.with_alpha(0.6),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        })),
+        Transform::default(),
+        Visibility::Hidden,
+        SatelliteTrail,
+    ));
+}
+
+pub(crate) fn observer_location(state: &StarfieldState) -> ObserverLocation {
+    ObserverLocation {
+        latitude: state.lat_deg,
+        longitude: state.lon_deg,
+        altitude: state.alt_m,
+    }
+}
+
+/// Each frame: propagate the tracked satellite and move the marker there
+pub(crate) fn update_satellite_marker(
+    tracked: Res<TrackedSatellite>,
+    state: Res<StarfieldState>,
+    mut position: ResMut<TrackedSatellitePosition>,
+    cam_q: Query<&GlobalTransform, With<Camera3d>>,
+    mut q: Query<(&mut Transform, &mut Visibility), With<SatelliteMarker>>,
+) {
+    let Ok((mut tf, mut vis)) = q.single_mut() else {
+        return;
+    };
+
+    let alt_az = tracked.0.as_ref().and_then(|sat| {
+        sat.propagator
+            .alt_az(observer_location(&state), state.current_utc())
+            .ok()
+    });
+    position.0 = alt_az.filter(|&(alt, _)| alt > 0.0);
+
+    match position.0 {
+        Some((alt, az)) => {
+            tf.translation = alt_az_direction(alt, az) * PATH_DISTANCE;
+            if let Ok(cam_tf) = cam_q.single() {
+                tf.rotation = cam_tf.compute_transform().rotation;
+            }
+            *vis = Visibility::Inherited;
+        }
+        None => *vis = Visibility::Hidden,
+    }
+}
+
+/// Keep the camera on the tracked pass: on the rise point until it starts,
+/// then on the satellite. Panning away hands the view back to the user.
+fn steer_camera_along_pass(
+    mut tracked_pass: ResMut<TrackedPass>,
+    state: Res<StarfieldState>,
+    position: Res<TrackedSatellitePosition>,
+    mut follow: ResMut<CameraFollow>,
+) {
+    let Some(pass) = &tracked_pass.0 else {
+        return;
+    };
+    let following =
+        follow.tracking && matches!(follow.target, Some(CameraTarget::Horizontal { .. }));
+    let now = state.current_utc();
+
+    if now > pass.end_time {
+        tracked_pass.0 = None;
+        if following {
+            follow.stop();
+        }
+        return;
+    }
+    if !following {
+        return;
+    }
+
+    let aim = if now < pass.start_time {
+        pass.trajectory.first().map(|p| (p.altitude, p.azimuth))
+    } else {
+        position.0
+    };
+    if let Some((altitude, azimuth)) = aim {
+        follow.track(CameraTarget::Horizontal { altitude, azimuth });
+    }
+}
+
+/// Once a second: resample the trail over the last minute
+fn update_satellite_trail(
+    time: Res<Time>,
+    mut timer: ResMut<TrailUpdateTimer>,
+    tracked: Res<TrackedSatellite>,
+    state: Res<StarfieldState>,
+    position: Res<TrackedSatellitePosition>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q: Query<(&Mesh3d, &mut Visibility), With<SatelliteTrail>>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() && !tracked.is_changed() {
+        return;
+    }
+    let Ok((mesh, mut vis)) = q.single_mut() else {
+        return;
+    };
+    let (Some(sat), Some(_)) = (&tracked.0, position.0) else {
+        *vis = Visibility::Hidden;
+        return;
+    };
+
+    let location = observer_location(&state);
+    let now = state.current_utc();
+    let positions: Vec<[f32; 3]> = (0..=TRAIL_LENGTH_SECS / TRAIL_STEP_SECS)
+        .rev()
+        .filter_map(|i| {
+            let t = now - Duration::seconds(i * TRAIL_STEP_SECS);
+            let (alt, az) = sat.propagator.alt_az(location, t).ok()?;
+            Some((alt_az_direction(alt, az) * PATH_DISTANCE).to_array())
+        })
+        .collect();
+    if positions.len() < 2 {
+        *vis = Visibility::Hidden;
+        return;
+    }
+
+    if let Some(mesh) = meshes.get_mut(&mesh.0) {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    }
+    *vis = Visibility::Inherited;
+}
+
+/// With the trail: resample the path ahead, whether or not the satellite is up
+fn update_predicted_path(
+    timer: Res<TrailUpdateTimer>,
+    tracked: Res<TrackedSatellite>,
+    prediction: Res<TrailPrediction>,
+    state: Res<StarfieldState>,
+    mut path: ResMut<PredictedPath>,
+) {
+    if !timer.0.just_finished() && !tracked.is_changed() && !prediction.is_changed() {
+        return;
+    }
+    path.0.clear();
+    let Some(sat) = &tracked.0 else {
+        return;
+    };
+
+    let location = observer_location(&state);
+    let now = state.current_utc();
+    let steps = prediction.minutes as i64 * 60 / PREDICTION_STEP_SECS;
+    path.0.extend((0..=steps).filter_map(|i| {
+        let time = now + Duration::seconds(i * PREDICTION_STEP_SECS);
+        let (altitude, azimuth) = sat.propagator.alt_az(location, time).ok()?;
+        let lit = is_satellite_lit(sat.propagator.as_ref(), time).ok()?;
+        Some(PredictedPoint {
+            time,
+            altitude,
+            azimuth,
+            lit,
+        })
+    }));
+}
+
+/// Dotted predicted path above the horizon, dimmed where the satellite is in
+/// shadow, with the meridian crossings and shadow entries marked
+fn draw_predicted_path(
+    path: Res<PredictedPath>,
+    time_display: Res<TimeDisplay>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if path.0.len() < 2 {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+    let to_screen = |p: &PredictedPoint| {
+        if p.altitude <= 0.0 {
+            return None;
+        }
+        let world = cam_gtf.translation() + alt_az_direction(p.altitude, p.azimuth) * PATH_DISTANCE;
+        let pos = camera.world_to_viewport(cam_gtf, world).ok()?;
+        Some(egui::pos2(pos.x, pos.y))
+    };
+    let color = |lit: bool| {
+        if lit {
+            colors::egui::GREEN_TEXT
+        } else {
+            colors::egui::SATELLITE_SHADOW
+        }
+    };
+
+    // Each run of visible samples in the same light is one dotted line
+    let draw_run = |run: &[egui::Pos2], lit: bool| {
+        if run.len() > 1 {
+            painter.extend(egui::Shape::dotted_line(
+                run,
+                color(lit),
+                PREDICTION_DOT_SPACING,
+                1.2,
+            ));
+        }
+    };
+    let mut run: Vec<egui::Pos2> = Vec::new();
+    let mut run_lit = path.0[0].lit;
+    for point in &path.0 {
+        let screen = to_screen(point);
+        if screen.is_none() || point.lit != run_lit {
+            draw_run(&run, run_lit);
+            // carry the last point over so runs in different light join up
+            let last = run.last().copied().filter(|_| screen.is_some());
+            run = last.into_iter().collect();
+            run_lit = point.lit;
+        }
+        run.extend(screen);
+    }
+    draw_run(&run, run_lit);
+
+    for pair in path.0.windows(2) {
+        let [before, after] = pair else {
+            continue;
+        };
+        // The meridian runs through north (0°) and south (180°), where the
+        // azimuth's sine changes sign
+        let label = if before.azimuth.to_radians().sin().signum()
+            != after.azimuth.to_radians().sin().signum()
+        {
+            "Meridian"
+        } else if before.lit && !after.lit {
+            "Shadow"
+        } else {
+            continue;
+        };
+        let Some(center) = to_screen(after) else {
+            continue;
+        };
+        painter.circle_stroke(center, 4.0, egui::Stroke::new(1.5, color(after.lit)));
+        painter.text(
+            center + egui::vec2(8.0, -8.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!("{label} {}", time_display.time(after.time)),
+            egui::FontId::proportional(12.0),
+            color(after.lit),
+        );
+    }
+}
@@ -1,14 +1,30 @@
 // src/server.rs
 
+use chrono::DateTime;
+use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::mpsc::Sender;
-use tonic::{transport::Server, Request, Response, Status};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tonic::{Request, Response, Status, Streaming};
 
+use protos::link;
 use protos::protos::planetarium_server::{Planetarium, PlanetariumServer};
-use protos::protos::SetMountLocationRequest;
-use protos::protos::SetMountLocationResponse;
-use protos::protos::{SetLocationRequest, SetLocationResponse};
+use protos::protos::{gui_message::Payload, planetarium_message};
+use protos::protos::{
+    Ack, ExportViewRequest, GuiMessage, PlanetariumMessage, RenderViewRequest, RenderViewResponse,
+};
 
+use crate::client;
 use crate::events::PlanetariumEvent;
+use crate::export::{ExportRequest, ExportView, PngReply, MAX_EXPORT_SIZE};
+use crate::slew_limits::SlewLimits;
+
+/// How long a render may take before the caller gives up on it
+const RENDER_TIMEOUT: Duration = Duration::from_secs(10);
+
 /// Our gRPC service, holding the channel sender
 #[derive(Clone)]
 pub struct MyPlanetariumServer {
@@ -21,70 +37,265 @@ impl MyPlanetariumServer {
     }
 }
 
+/// Link settings sidereal_gui passes down when it launches us; without them
+/// we listen on localhost in plaintext with no token, as before
+struct LinkSettings {
+    address: String,
+    token: Option<String>,
+    /// Certificate and key paths
+    tls: Option<(PathBuf, PathBuf)>,
+}
+
+impl LinkSettings {
+    fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        LinkSettings {
+            address: var(link::ADDRESS_ENV).unwrap_or_else(|| link::DEFAULT_ADDRESS.to_owned()),
+            token: var(link::TOKEN_ENV),
+            tls: var(link::TLS_CERT_ENV)
+                .zip(var(link::TLS_KEY_ENV))
+                .map(|(cert, key)| (cert.into(), key.into())),
+        }
+    }
+}
+
+/// Refuse requests that don't carry the configured token
+fn check_token(expected: Option<&str>, request: Request<()>) -> Result<Request<()>, Status> {
+    let Some(expected) = expected else {
+        return Ok(request);
+    };
+    let presented = request
+        .metadata()
+        .get(link::TOKEN_METADATA_KEY)
+        .and_then(|value| value.to_str().ok());
+    if presented == Some(expected) {
+        Ok(request)
+    } else {
+        Err(Status::unauthenticated("missing or wrong link token"))
+    }
+}
+
 /// Launch the gRPC server, giving it the channel sender.
 pub async fn run(sender: Sender<PlanetariumEvent>) -> Result<(), Box<dyn std::error::Error>> {
-    let addr = "[::1]:50051".parse()?;
+    let settings = LinkSettings::from_env();
+    let addr = settings.address.parse()?;
     let service = MyPlanetariumServer::new(sender);
 
+    // ping the GUI so a connection that died without closing is dropped
+    let mut builder = Server::builder()
+        .http2_keepalive_interval(Some(link::KEEPALIVE_INTERVAL))
+        .http2_keepalive_timeout(Some(link::KEEPALIVE_TIMEOUT));
+    if let Some((cert, key)) = &settings.tls {
+        let identity = Identity::from_pem(std::fs::read(cert)?, std::fs::read(key)?);
+        builder = builder.tls_config(ServerTlsConfig::new().identity(identity))?;
+    }
+
     println!("gRPC server listening on {}", addr);
 
-    Server::builder()
-        .add_service(PlanetariumServer::new(service))
+    let token = settings.token.map(|token| format!("Bearer {token}"));
+    let interceptor = move |request| check_token(token.as_deref(), request);
+
+    builder
+        .add_service(PlanetariumServer::with_interceptor(service, interceptor))
         .serve(addr)
         .await?;
 
     Ok(())
 }
 
+/// Turn one message from the GUI into a Bevy event, returning the ack text
+fn handle_gui_message(
+    sender: &Sender<PlanetariumEvent>,
+    payload: Payload,
+) -> Result<String, String> {
+    let (evt, description) = match payload {
+        Payload::Location(location) => (
+            PlanetariumEvent::SetSiteLocation {
+                lat_deg: location.latitude as f64,
+                lon_deg: location.longitude as f64,
+                alt_m: location.altitude as f64,
+            },
+            format!(
+                "Location set: lat={}°, lon={}°",
+                location.latitude, location.longitude
+            ),
+        ),
+        Payload::MountPosition(mount) => (
+            PlanetariumEvent::SetMountPosition {
+                ra_hours: mount.ra,
+                dec_deg: mount.dec,
+            },
+            format!("Mount Position set: ra={}°, dec={}°", mount.ra, mount.dec),
+        ),
+        Payload::SelectedTarget(target) => (
+            PlanetariumEvent::SetSelectedTarget {
+                ra_hours: target.ra_hours,
+                dec_deg: target.dec_degrees,
+            },
+            format!(
+                "Target set: ra={}h, dec={}°",
+                target.ra_hours, target.dec_degrees
+            ),
+        ),
+        Payload::ExportView(export) => export_event(export)?,
+        Payload::MountLimits(limits) => (
+            PlanetariumEvent::SetMountLimits(SlewLimits {
+                min_altitude_deg: limits.min_altitude_degrees as f64,
+                max_altitude_deg: limits.max_altitude_degrees as f64,
+                min_dec_deg: limits.min_dec_degrees as f64,
+                max_dec_deg: limits.max_dec_degrees as f64,
+                max_hour_angle_h: limits.max_hour_angle_hours as f64,
+            }),
+            format!(
+                "Mount limits set: alt {}° to {}°, dec {}° to {}°, ±{}h",
+                limits.min_altitude_degrees,
+                limits.max_altitude_degrees,
+                limits.min_dec_degrees,
+                limits.max_dec_degrees,
+                limits.max_hour_angle_hours
+            ),
+        ),
+    };
+
+    // Send it into your Bevy channel
+    sender
+        .send(evt)
+        .map_err(|e| format!("Channel send error: {}", e))?;
+    Ok(description)
+}
+
+fn export_event(contents: ExportViewRequest) -> Result<(PlanetariumEvent, String), String> {
+    check_export_size(contents.width, contents.height)?;
+
+    let description = format!(
+        "Exporting {}x{} view to {}",
+        contents.width,
+        contents.height,
+        if contents.path.is_empty() {
+            "a timestamped file"
+        } else {
+            contents.path.as_str()
+        }
+    );
+    let evt = PlanetariumEvent::ExportView(ExportRequest {
+        path: (!contents.path.is_empty()).then(|| contents.path.clone().into()),
+        width: contents.width,
+        height: contents.height,
+        include_pass: contents.include_pass,
+        include_timestamp: contents.include_timestamp,
+        view: None,
+        reply: None,
+    });
+    Ok((evt, description))
+}
+
+/// Check a render request's size, like an export's
+fn check_export_size(width: u32, height: u32) -> Result<(), String> {
+    for size in [width, height] {
+        if !(1..=MAX_EXPORT_SIZE).contains(&size) {
+            return Err(format!(
+                "Export size must be between 1 and {} pixels",
+                MAX_EXPORT_SIZE
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Export request for an offscreen render whose PNG comes back on `reply`
+fn render_request(contents: RenderViewRequest, reply: PngReply) -> Result<ExportRequest, Status> {
+    check_export_size(contents.width, contents.height).map_err(Status::invalid_argument)?;
+    if !(0.1..=180.0).contains(&contents.fov_degrees) {
+        return Err(Status::invalid_argument(
+            "Field of view must be between 0.1 and 180 degrees",
+        ));
+    }
+    let time = contents
+        .time
+        .map(|ts| {
+            DateTime::from_timestamp(ts.seconds, ts.nanos.max(0) as u32)
+                .ok_or_else(|| Status::invalid_argument("Time is out of range"))
+        })
+        .transpose()?;
+
+    Ok(ExportRequest {
+        path: None,
+        width: contents.width,
+        height: contents.height,
+        include_pass: contents.include_pass,
+        include_timestamp: contents.include_timestamp,
+        view: Some(ExportView {
+            ra_hours: contents.ra_hours,
+            dec_deg: contents.dec_degrees,
+            fov_deg: contents.fov_degrees,
+            time,
+        }),
+        reply: Some(reply),
+    })
+}
+
+type LinkStream = Pin<Box<dyn Stream<Item = Result<PlanetariumMessage, Status>> + Send>>;
+
 #[tonic::async_trait]
 impl Planetarium for MyPlanetariumServer {
-    async fn set_location(
+    type LinkStream = LinkStream;
+
+    async fn link(
         &self,
-        request: Request<SetLocationRequest>,
-    ) -> Result<Response<SetLocationResponse>, Status> {
-        let contents = request.into_inner();
+        request: Request<Streaming<GuiMessage>>,
+    ) -> Result<Response<Self::LinkStream>, Status> {
+        let mut inbound = request.into_inner();
+        let (tx, rx) = mpsc::unbounded_channel();
+        // the newest GUI connection is the one slews go to
+        client::attach_gui(tx.clone());
 
-        // Build the event
-        let evt = PlanetariumEvent::SetSiteLocation {
-            lat_deg: contents.latitude as f64,
-            lon_deg: contents.longitude as f64,
-        };
+        let sender = self.sender.clone();
+        let _ = sender.send(PlanetariumEvent::GuiLink { connected: true });
+        tokio::spawn(async move {
+            // ends when the GUI closes its side or the connection drops
+            while let Ok(Some(message)) = inbound.message().await {
+                let Some(payload) = message.payload else {
+                    continue;
+                };
+                let (ok, description) = match handle_gui_message(&sender, payload) {
+                    Ok(description) => (true, description),
+                    Err(e) => (false, e),
+                };
+                let ack = PlanetariumMessage {
+                    payload: Some(planetarium_message::Payload::Ack(Ack {
+                        id: message.id,
+                        ok,
+                        description,
+                    })),
+                };
+                if tx.send(Ok(ack)).is_err() {
+                    break;
+                }
+            }
+            if client::detach_gui(&tx) {
+                let _ = sender.send(PlanetariumEvent::GuiLink { connected: false });
+            }
+        });
 
-        // Send it into your Bevy channel
-        self.sender
-            .send(evt)
-            .map_err(|e| Status::internal(format!("Channel send error: {}", e)))?;
-
-        // Reply to the gRPC client
-        let reply = SetLocationResponse {
-            description: format!(
-                "Location set: lat={}°, lon={}°",
-                contents.latitude, contents.longitude
-            ),
-        };
-        Ok(Response::new(reply))
+        Ok(Response::new(Box::pin(UnboundedReceiverStream::new(rx))))
     }
 
-    async fn set_mount_location(
+    async fn render_view(
         &self,
-        request: Request<SetMountLocationRequest>,
-    ) -> Result<Response<SetMountLocationResponse>, Status> {
-        let contents = request.into_inner();
-        // Build the event
-        let evt = PlanetariumEvent::SetMountPosition {
-            ra_hours: contents.ra,
-            dec_deg: contents.dec,
-        };
+        request: Request<RenderViewRequest>,
+    ) -> Result<Response<RenderViewResponse>, Status> {
+        let (reply, mut rendered) = mpsc::unbounded_channel();
+        let export = render_request(request.into_inner(), reply)?;
         self.sender
-            .send(evt)
-            .map_err(|e| Status::internal(format!("Channel send error: {}", e)))?;
+            .send(PlanetariumEvent::ExportView(export))
+            .map_err(|e| Status::unavailable(format!("Channel send error: {}", e)))?;
 
-        let reply = SetMountLocationResponse {
-            description: format!(
-                "Mount Position set: ra={}°, dec={}°",
-                contents.ra, contents.dec
-            ),
-        };
-        Ok(Response::new(reply))
+        // normally done within a few frames
+        let png = tokio::time::timeout(RENDER_TIMEOUT, rendered.recv())
+            .await
+            .map_err(|_| Status::deadline_exceeded("Render timed out"))?
+            .ok_or_else(|| Status::internal("Render was dropped"))?
+            .map_err(Status::internal)?;
+        Ok(Response::new(RenderViewResponse { png }))
     }
 }
@@ -0,0 +1,63 @@
+// src/site.rs
+
+use std::{fs, io, path::PathBuf};
+
+/// Observer site, remembered between runs so the sky is right without the GUI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SiteLocation {
+    pub lat_deg: f64,
+    pub lon_deg: f64,
+    /// Meters above sea level
+    pub alt_m: f64,
+}
+
+impl Default for SiteLocation {
+    fn default() -> Self {
+        // New York City
+        SiteLocation {
+            lat_deg: 40.7128,
+            lon_deg: -74.0060,
+            alt_m: 10.0,
+        }
+    }
+}
+
+/// `~/.config/sidereal/<name>`, next to the GUI's config
+pub(crate) fn config_file(name: &str) -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("sidereal").join(name))
+}
+
+fn site_path() -> Option<PathBuf> {
+    config_file("planetarium_site.txt")
+}
+
+/// Parse "lat lon alt" (degrees, degrees, meters)
+fn parse_site(data: &str) -> Option<SiteLocation> {
+    let mut fields = data.split_whitespace().map(|f| f.parse::<f64>().ok());
+    let site = SiteLocation {
+        lat_deg: fields.next()??,
+        lon_deg: fields.next()??,
+        alt_m: fields.next()??,
+    };
+    ((-90.0..=90.0).contains(&site.lat_deg) && (-180.0..=180.0).contains(&site.lon_deg))
+        .then_some(site)
+}
+
+impl SiteLocation {
+    /// The saved site, if there is one and it parses
+    pub fn load() -> Option<SiteLocation> {
+        parse_site(&fs::read_to_string(site_path()?).ok()?)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = site_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            path,
+            format!("{} {} {}\n", self.lat_deg, self.lon_deg, self.alt_m),
+        )
+    }
+}
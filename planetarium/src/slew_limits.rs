@@ -0,0 +1,196 @@
+// src/slew_limits.rs
+
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+
+use crate::{
+    colors,
+    events::PlanetariumEvent,
+    starfield::{alt_az_direction, hour_angle_declination, SkyLayers, StarfieldState},
+};
+
+/// Size (degrees) of the alt/az cells the sky is tested in
+const CELL_SIZE: f64 = 3.0;
+
+/// Inside the horizon, so the ground never covers the shading
+const SHADE_DISTANCE: f32 = 50_000.0;
+
+/// Where the mount may point, as configured in sidereal_gui
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SlewLimits {
+    pub min_altitude_deg: f64,
+    pub max_altitude_deg: f64,
+    pub min_dec_deg: f64,
+    pub max_dec_deg: f64,
+    /// Furthest either side of the meridian
+    pub max_hour_angle_h: f64,
+}
+
+impl SlewLimits {
+    /// Whether the mount can point along world direction `dir` from latitude
+    /// `lat` (radians)
+    pub fn allows(&self, lat: f64, dir: Vec3) -> bool {
+        let altitude = (dir.normalize().y as f64)
+            .clamp(-1.0, 1.0)
+            .asin()
+            .to_degrees();
+        let (ha, dec) = hour_angle_declination(lat, dir);
+        (self.min_altitude_deg..=self.max_altitude_deg).contains(&altitude)
+            && (self.min_dec_deg..=self.max_dec_deg).contains(&dec.to_degrees())
+            && ha.to_degrees().abs() / 15.0 <= self.max_hour_angle_h
+    }
+}
+
+/// The mount's limits, once the GUI has sent them
+#[derive(Resource, Default)]
+pub struct MountLimits(pub Option<SlewLimits>);
+
+/// One unreachable patch of sky: altitude and azimuth ranges in degrees
+#[derive(Debug, Clone, Copy)]
+pub struct LimitCell {
+    pub altitude: (f64, f64),
+    pub azimuth: (f64, f64),
+}
+
+impl LimitCell {
+    /// Corners in order around the cell
+    pub fn corners(&self) -> [(f64, f64); 4] {
+        let (alt0, alt1) = self.altitude;
+        let (az0, az1) = self.azimuth;
+        [(alt0, az0), (alt0, az1), (alt1, az1), (alt1, az0)]
+    }
+}
+
+/// Cells above the horizon the mount can't reach, for the shading and the
+/// all-sky chart
+#[derive(Resource, Default)]
+pub struct ForbiddenCells(pub Vec<LimitCell>);
+
+/// Marker on the shading mesh
+#[derive(Component)]
+struct LimitShade;
+
+pub struct SlewLimitPlugin;
+impl Plugin for SlewLimitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MountLimits>()
+            .init_resource::<ForbiddenCells>()
+            .add_systems(
+                Update,
+                (
+                    handle_mount_limit_events,
+                    update_limit_shade,
+                    show_limit_shade,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn handle_mount_limit_events(
+    mut ev: MessageReader<PlanetariumEvent>,
+    mut limits: ResMut<MountLimits>,
+) {
+    for evt in ev.read() {
+        if let PlanetariumEvent::SetMountLimits(new_limits) = *evt {
+            limits.0 = Some(new_limits);
+        }
+    }
+}
+
+fn forbidden_cells(limits: &SlewLimits, lat: f64) -> Vec<LimitCell> {
+    let (stacks, sectors) = ((90.0 / CELL_SIZE) as usize, (360.0 / CELL_SIZE) as usize);
+    let mut cells = Vec::new();
+    for i in 0..stacks {
+        for j in 0..sectors {
+            let cell = LimitCell {
+                altitude: (i as f64 * CELL_SIZE, (i + 1) as f64 * CELL_SIZE),
+                azimuth: (j as f64 * CELL_SIZE, (j + 1) as f64 * CELL_SIZE),
+            };
+            // judged at the center, so boundaries are good to half a cell
+            let center = alt_az_direction(
+                (cell.altitude.0 + cell.altitude.1) / 2.0,
+                (cell.azimuth.0 + cell.azimuth.1) / 2.0,
+            );
+            if !limits.allows(lat, center) {
+                cells.push(cell);
+            }
+        }
+    }
+    cells
+}
+
+fn shade_mesh(cells: &[LimitCell]) -> Mesh {
+    let mut positions = Vec::with_capacity(cells.len() * 4);
+    let mut indices = Vec::with_capacity(cells.len() * 6);
+    for cell in cells {
+        let first = positions.len() as u32;
+        positions.extend(
+            cell.corners()
+                .map(|(alt, az)| (alt_az_direction(alt, az) * SHADE_DISTANCE).to_array()),
+        );
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Rebuild the shading when the limits arrive or the site moves; the limits
+/// are fixed to the horizon and the meridian, so time doesn't matter
+#[allow(clippy::too_many_arguments)]
+fn update_limit_shade(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    limits: Res<MountLimits>,
+    state: Res<StarfieldState>,
+    mut cells: ResMut<ForbiddenCells>,
+    mut shaded_lat: Local<Option<f64>>,
+    shades: Query<Entity, With<LimitShade>>,
+) {
+    let Some(slew_limits) = &limits.0 else {
+        return;
+    };
+    if !limits.is_changed() && *shaded_lat == Some(state.lat_deg) {
+        return;
+    }
+    *shaded_lat = Some(state.lat_deg);
+
+    for entity in &shades {
+        commands.entity(entity).despawn();
+    }
+    cells.0 = forbidden_cells(slew_limits, state.lat_deg.to_radians());
+    if cells.0.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(shade_mesh(&cells.0))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: colors::SLEW_LIMIT_SHADE,
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            cull_mode: None, // seen from inside
+            ..default()
+        })),
+        Transform::default(),
+        Visibility::default(),
+        LimitShade,
+    ));
+}
+
+fn show_limit_shade(layers: Res<SkyLayers>, mut shades: Query<&mut Visibility, With<LimitShade>>) {
+    for mut visibility in &mut shades {
+        visibility.set_if_neq(if layers.slew_limits {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
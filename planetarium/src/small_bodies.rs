@@ -0,0 +1,200 @@
+// src/small_bodies.rs
+
+use bevy::prelude::*;
+use overpass_planner::{small_body_position, ObserverLocation, OrbitalElements, SmallBodyKind};
+
+use crate::starfield::{alt_az_direction, magnitude_to_scale, StarfieldState};
+
+/// Comets and asteroids sit at the same depth as the planets
+const SMALL_BODY_DISTANCE: f32 = 90_000.0;
+
+/// Orbits are smooth over a second, like the planets
+const UPDATE_INTERVAL_SECS: f32 = 1.0;
+
+/// Magnitude drawn for a body whose elements give no absolute magnitude
+const UNKNOWN_MAGNITUDE: f64 = 10.0;
+
+/// A comet or asteroid loaded from MPC elements, with its last computed position
+#[derive(Component)]
+pub struct SmallBody {
+    pub elements: OrbitalElements,
+    /// Altitude (degrees), `None` until the orbit has been evaluated
+    pub altitude: Option<f64>,
+    /// Azimuth (degrees)
+    pub azimuth: Option<f64>,
+    /// Estimated visual magnitude
+    pub magnitude: Option<f64>,
+}
+
+/// Elements added in the comet window, spawned on the next frame
+#[derive(Resource, Default)]
+pub struct PendingSmallBodies(pub Vec<OrbitalElements>);
+
+/// Last position error, so it is reported once instead of every second
+#[derive(Resource, Default)]
+pub struct SmallBodyError(pub Option<String>);
+
+#[derive(Resource)]
+pub(crate) struct SmallBodyUpdateTimer(Timer);
+
+/// Shared mesh and per-kind materials
+#[derive(Resource)]
+struct SmallBodyAssets {
+    quad: Handle<Mesh>,
+    comet: Handle<StandardMaterial>,
+    asteroid: Handle<StandardMaterial>,
+}
+
+pub struct SmallBodyPlugin;
+impl Plugin for SmallBodyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SmallBodyUpdateTimer(Timer::from_seconds(
+            UPDATE_INTERVAL_SECS,
+            TimerMode::Repeating,
+        )))
+        .init_resource::<PendingSmallBodies>()
+        .init_resource::<SmallBodyError>()
+        .add_systems(Startup, setup_small_body_assets)
+        .add_systems(
+            Update,
+            (
+                spawn_small_bodies,
+                update_small_body_positions,
+                billboard_small_bodies,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn setup_small_body_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+) {
+    let texture = assets.load("star.png");
+    let mut material = |mix: Vec3| {
+        let color = Color::linear_rgb(mix.x * 100.0, mix.y * 100.0, mix.z * 100.0);
+        mats.add(StandardMaterial {
+            base_color_texture: Some(texture.clone()),
+            base_color: color,
+            emissive: color.into(),
+            alpha_mode: AlphaMode::Add,
+            unlit: true,
+            ..default()
+        })
+    };
+    commands.insert_resource(SmallBodyAssets {
+        quad: meshes.add(Mesh::from(Rectangle::new(1.0, 1.0))),
+        // comae are usually green from C2 emission
+        comet: material(Vec3::new(0.55, 1.0, 0.75)),
+        asteroid: material(Vec3::new(0.85, 0.8, 0.75)),
+    });
+}
+
+fn spawn_small_bodies(
+    mut commands: Commands,
+    mut pending: ResMut<PendingSmallBodies>,
+    mut timer: ResMut<SmallBodyUpdateTimer>,
+    small_assets: Option<Res<SmallBodyAssets>>,
+    existing: Query<&SmallBody>,
+) {
+    let Some(small_assets) = small_assets else {
+        return;
+    };
+    if pending.0.is_empty() {
+        return;
+    }
+
+    for elements in pending.0.drain(..) {
+        // the same body loaded twice would just draw twice
+        if existing.iter().any(|b| b.elements.name == elements.name) {
+            continue;
+        }
+        let material = match elements.kind {
+            SmallBodyKind::Comet => small_assets.comet.clone(),
+            SmallBodyKind::Asteroid => small_assets.asteroid.clone(),
+        };
+        commands.spawn((
+            Mesh3d(small_assets.quad.clone()),
+            MeshMaterial3d(material),
+            Transform::default(),
+            // hidden until the first position is computed
+            Visibility::Hidden,
+            SmallBody {
+                elements,
+                altitude: None,
+                azimuth: None,
+                magnitude: None,
+            },
+        ));
+    }
+
+    // place the new bodies right away rather than up to a second later
+    let duration = timer.0.duration();
+    timer.0.set_elapsed(duration);
+}
+
+/// Once a second: propagate every orbit for the time and place the sky is showing
+pub(crate) fn update_small_body_positions(
+    time: Res<Time>,
+    mut timer: ResMut<SmallBodyUpdateTimer>,
+    mut last_error: ResMut<SmallBodyError>,
+    state: Res<StarfieldState>,
+    mut q: Query<(&mut SmallBody, &mut Transform, &mut Visibility)>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() && !state.is_changed() {
+        return;
+    }
+
+    let location = ObserverLocation {
+        latitude: state.lat_deg,
+        longitude: state.lon_deg,
+        altitude: state.alt_m,
+    };
+    let now = state.current_utc();
+
+    for (mut body, mut tf, mut vis) in &mut q {
+        match small_body_position(&body.elements, location, now) {
+            Ok(position) => {
+                body.altitude = Some(position.altitude);
+                body.azimuth = Some(position.azimuth);
+                body.magnitude = position.magnitude;
+
+                // Scale relative to the star sphere so they look like stars of their magnitude
+                let magnitude = position.magnitude.unwrap_or(UNKNOWN_MAGNITUDE) as f32;
+                let scale = magnitude_to_scale(magnitude) * (SMALL_BODY_DISTANCE / 100_000.0);
+                tf.translation =
+                    alt_az_direction(position.altitude, position.azimuth) * SMALL_BODY_DISTANCE;
+                tf.scale = Vec3::splat(scale);
+                *vis = Visibility::Inherited;
+                last_error.0 = None;
+            }
+            Err(e) => {
+                let message = e.to_string();
+                if last_error.0.as_ref() != Some(&message) {
+                    eprintln!("Small body position unavailable: {message}");
+                    last_error.0 = Some(message);
+                }
+                body.altitude = None;
+                body.azimuth = None;
+                *vis = Visibility::Hidden;
+            }
+        }
+    }
+}
+
+/// Make every comet and asteroid quad face the camera
+fn billboard_small_bodies(
+    cam_q: Query<&GlobalTransform, With<Camera>>,
+    mut q: Query<&mut Transform, With<SmallBody>>,
+) {
+    let Ok(cam_tf) = cam_q.single() else {
+        return;
+    };
+    let cam_rot = cam_tf.compute_transform().rotation;
+    for mut tf in &mut q {
+        tf.rotation = cam_rot;
+    }
+}
@@ -1,126 +1,169 @@
 // src/starfield.rs
 
+use bevy::asset::RenderAssetUsages;
+use bevy::camera::primitives::MeshAabb;
+use bevy::mesh::{Indices, PrimitiveTopology};
 use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
 use chrono::{DateTime, Utc};
-use rand::Rng;
-use std::{f64::consts::PI, path::PathBuf, time::Instant};
-
-use crate::{events::PlanetariumEvent, star_catalog::parse_catalog};
-
-/// Marker on the root entity
-#[derive(Component)]
-pub struct StarfieldRoot;
-
-/// Per‐star data so we can recompute positions on location change
+use planetarium_core::{
+    constellations::{parse_constellation_lines, CONSTELLATION_LINES},
+    star_catalog::{parse_catalog, parse_star_names, STAR_NAMES},
+    star_cloud::{split_into_tiles, star_material, CloudStar, StarChunk, DEFAULT_COLOR_INDEX},
+};
+use std::{collections::HashMap, f64::consts::PI, path::PathBuf};
+
+use crate::{
+    all_sky::perspective_view, colors, events::PlanetariumEvent, faint_stars::FAINTEST_MAGNITUDE,
+    site::SiteLocation, twilight::Twilight,
+};
+
+// The coordinate math and the sky clock live in planetarium_core; the rest of
+// the app reaches them through here
+pub use planetarium_core::coords::{
+    alt_az_direction, equatorial_from_direction, horizontal_coordinates,
+    horizontal_from_hour_angle, hour_angle_declination, hour_angle_from_horizontal, julian_date,
+    local_sidereal_time, star_direction, SIDEREAL_RATE,
+};
+pub use planetarium_core::sky::{rotate_starfield_system, StarfieldRoot, StarfieldState};
+pub use planetarium_core::star_cloud::magnitude_to_scale;
+
+/// Marker on the bright star chunks (children of the root), from BSC5 or the
+/// custom catalog picked in Settings
 #[derive(Component)]
-pub struct StarData {
-    pub ra: f64,
-    pub dec: f64,
-}
+pub struct BrightStars;
 
+/// What the View menu controls: optional overlays and star density
 #[derive(Resource)]
-pub struct StarfieldState {
-    /// When we first spawned (the RA/Dec→horizon positions were for this UTC)
-    pub spawn_utc: DateTime<Utc>,
-
-    /// Our last "time override" instant, for smooth rotation updates
-    pub base_instant: Instant,
-    /// How far (radians) we've already rotated at base_instant
-    pub base_angle: f32,
-
-    /// Observer latitude & longitude (degrees)
-    pub lat_deg: f64,
-    pub lon_deg: f64,
-
-    /// Rotation axis in local horizon coords (unit Vec3)
-    pub axis: Vec3,
-    /// Sidereal rate: 2π radians per 86 164.0905 s
-    pub rate: f32,
+pub struct SkyLayers {
+    pub constellation_lines: bool,
+    pub milky_way: bool,
+    /// Radiants of the meteor showers active on the simulated date
+    pub meteor_radiants: bool,
+    /// Names of planets, the tracked satellite, the brighter named stars and
+    /// the deep-sky markers
+    pub labels: bool,
+    /// Chart symbols for the deep-sky catalog
+    pub dso_markers: bool,
+    /// N/E/S/W and the intercardinals along the horizon
+    pub cardinal_points: bool,
+    /// Meridian line through the zenith
+    pub meridian: bool,
+    /// Shade the sky the mount can't reach
+    pub slew_limits: bool,
+    /// Color the sky by its estimated brightness
+    pub sky_brightness: bool,
+    /// Every satellite in the TLE catalog, or the group picked in the menu
+    pub all_satellites: bool,
+    /// Faintest deep-sky object that gets a marker
+    pub dso_magnitude: f32,
+    /// Faintest named star that gets a label
+    pub label_magnitude: f32,
+    /// Faintest star drawn, whatever the zoom
+    pub limiting_magnitude: f32,
 }
 
-impl Default for StarfieldState {
+impl Default for SkyLayers {
     fn default() -> Self {
-        // will be overwritten in spawn_starfield()
-        StarfieldState {
-            spawn_utc: Utc::now(),
-            base_instant: Instant::now(),
-            base_angle: 0.0,
-            lat_deg: 0.0,
-            lon_deg: 0.0,
-            axis: Vec3::Y,
-            rate: (2.0 * PI as f32) / 86_164.0905_f32,
+        SkyLayers {
+            constellation_lines: true,
+            milky_way: true,
+            meteor_radiants: false,
+            labels: true,
+            dso_markers: true,
+            cardinal_points: true,
+            meridian: false,
+            slew_limits: true,
+            sky_brightness: false,
+            all_satellites: false,
+            dso_magnitude: 10.0,
+            label_magnitude: 2.0,
+            limiting_magnitude: FAINTEST_MAGNITUDE,
         }
     }
 }
 
+impl SkyLayers {
+    /// Faintest star shown: the user's limit, or less when the sky is bright
+    pub fn star_limit(&self, twilight: &Twilight) -> f32 {
+        self.limiting_magnitude.min(twilight.limiting_magnitude)
+    }
+}
+
+/// One constellation figure with its stars resolved against the catalog
+pub struct Constellation {
+    pub name: String,
+    /// Segment endpoints as (ra, dec) in radians
+    pub segments: Vec<[(f64, f64); 2]>,
+}
+
+#[derive(Resource, Default)]
+pub struct Constellations(pub Vec<Constellation>);
+
+/// Catalog positions by HR number plus the named stars, for lookups by name
+#[derive(Resource, Default)]
+pub struct StarIndex {
+    /// (ra, dec) in radians by HR number
+    pub positions: HashMap<u32, (f64, f64)>,
+    /// Visual magnitude by HR number
+    pub magnitudes: HashMap<u32, f32>,
+    /// Proper name and HR number of the named bright stars
+    pub names: Vec<(String, u32)>,
+}
+
+/// Marker on the constellation line mesh (a child of the root)
+#[derive(Component)]
+pub struct ConstellationLines;
+
+/// Marker on the Milky Way panorama sphere (a child of the root)
+#[derive(Component)]
+pub struct MilkyWay;
+
+/// Equirectangular panorama in equatorial coordinates: RA 24h at the left edge
+/// through 0h at the right, +90° dec at the top. Optional; the layer is skipped
+/// when the file is missing.
+const MILKY_WAY_TEXTURE: &str = "milkyway.jpg";
+/// Just outside the star sphere so the stars draw over it
+const MILKY_WAY_DISTANCE: f32 = 110_000.0;
+/// Panorama brightness with every star shown
+const MILKY_WAY_MAX_BRIGHTNESS: f32 = 0.6;
+
 pub struct StarfieldPlugin;
 impl Plugin for StarfieldPlugin {
     fn build(&self, app: &mut App) {
         app
             // events
             .add_message::<PlanetariumEvent>()
+            // overlays
+            .init_resource::<SkyLayers>()
+            .init_resource::<Constellations>()
+            .init_resource::<StarIndex>()
             // startup
             .add_systems(Startup, spawn_starfield)
             // runtime event handlers
             .add_systems(Update, (handle_set_location_events, handle_set_time_events))
-            // per-frame with ordering: follow_cam → rotate → billboard
             .add_systems(
                 Update,
                 (
-                    starfield_follow_camera,
-                    rotate_starfield_system.after(starfield_follow_camera),
-                    billboard_system.after(rotate_starfield_system),
+                    update_bright_stars.after(handle_set_location_events),
+                    update_constellation_lines.after(handle_set_location_events),
+                    update_milky_way.after(handle_set_location_events),
+                    draw_constellation_labels
+                        .after(rotate_starfield_system)
+                        .run_if(perspective_view),
                 ),
             );
     }
 }
 
-/// Compute the Julian Date (JD) from a UTC time.
-fn julian_date(time: DateTime<Utc>) -> f64 {
-    let unix = time.timestamp() as f64;
-    let sub = time.timestamp_subsec_nanos() as f64 * 1e-9;
-    2440587.5 + (unix + sub) / 86400.0
-}
-
-/// RA/Dec → local‐horizon unit vector (X=east, Y=up, Z=north)
-fn star_direction(time: DateTime<Utc>, lat: f64, lon: f64, ra: f64, dec: f64) -> Vec3 {
-    let jd = julian_date(time);
-    let t = (jd - 2451545.0) / 36525.0;
-    let gmst = (280.46061837 + 360.98564736629 * (jd - 2451545.0) + 0.000387933 * t * t
-        - t * t * t / 38710000.0)
-        .rem_euclid(360.0)
-        .to_radians();
-    let lst = (gmst + lon).rem_euclid(2.0 * PI);
-    let ha = (lst - ra).rem_euclid(2.0 * PI);
-
-    let east = dec.cos() * ha.sin();
-    let north = dec.cos() * ha.cos() * lat.sin() - dec.sin() * lat.cos();
-    let up = dec.cos() * ha.cos() * lat.cos() + dec.sin() * lat.sin();
-
-    // Bevy: X=east, Y=up, Z=–north  (so "forward" is towards the sky)
-    Vec3::new(east as f32, up as f32, -north as f32).normalize()
-}
-
-/// Map magnitude → quad scale
-pub fn magnitude_to_scale(mag: f32) -> f32 {
-    const MIN_MAG: f32 = -4.0;
-    const MAX_MAG: f32 = 10.0;
-    const OUT_MIN: f32 = 30.0;
-    const OUT_MAX: f32 = 10_000.0;
-
-    let m = mag.clamp(MIN_MAG, MAX_MAG);
-    let t = (MAX_MAG - m) / (MAX_MAG - MIN_MAG);
-    OUT_MIN * (OUT_MAX / OUT_MIN).powf(t)
-}
-
 /// Where your executable's `assets/BSC5` folder lives
-fn asset_base() -> PathBuf {
+pub(crate) fn asset_base() -> PathBuf {
     let exe = std::env::current_exe().expect("no exe path");
     exe.parent().unwrap().to_path_buf()
 }
 
 /// Spawn root + all stars at their **spawn** positions
-fn spawn_starfield(
+pub(crate) fn spawn_starfield(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut mats: ResMut<Assets<StandardMaterial>>,
@@ -130,30 +173,26 @@ fn spawn_starfield(
     let path = asset_base().join("assets").join("BSC5");
     let (_hdr, stars) = parse_catalog(path).unwrap();
 
-    // observer defaults (NYC)
+    // observer: the last site used, until the GUI sends one
     let now = Utc::now();
-    let lat = 40.7128_f64;
-    let lon = -74.0060_f64;
-
-    // build our StarfieldState
-    let axis = {
-        let lr = lat.to_radians();
-        Vec3::new(0.0, lr.sin() as f32, lr.cos() as f32)
-    };
-    let rate = (2.0 * PI as f32) / 86_164.0905_f32;
-
-    commands.insert_resource(StarfieldState {
-        spawn_utc: now,
-        base_instant: Instant::now(),
-        base_angle: 0.0,
-        lat_deg: lat,
-        lon_deg: lon,
-        axis,
-        rate,
-    });
+    let site = SiteLocation::load().unwrap_or_default();
+    let (lat, lon) = (site.lat_deg, site.lon_deg);
+
+    commands.insert_resource(StarfieldState::new(now, lat, lon, site.alt_m));
+
+    // resolve the constellation figures against the catalog before the stars are consumed
+    let positions: HashMap<u32, (f64, f64)> = stars
+        .iter()
+        .filter_map(|s| Some((s.catalog_number()?, (s.ra, s.dec))))
+        .collect();
+    let constellations = resolve_constellations(&positions);
+    let magnitudes: HashMap<u32, f32> = stars
+        .iter()
+        .filter_map(|s| Some((s.catalog_number()?, s.magnitudes[0])))
+        .collect();
 
-    let quad = meshes.add(Mesh::from(Rectangle::new(1.0, 1.0)));
     let texture = assets.load("star.png");
+    let star_mat = star_material(&mut mats, &texture);
 
     // spawn a single root
     let root = commands
@@ -163,145 +202,371 @@ fn spawn_starfield(
             StarfieldRoot,
         ))
         .id();
-    // now spawn each star as its child
-    let mut rng = rand::thread_rng();
-    for star in stars {
-        let dir = star_direction(now, lat.to_radians(), lon.to_radians(), star.ra, star.dec);
-        let pos = dir * 100_000.0;
-        let scale = magnitude_to_scale(star.magnitudes[0]);
-        let t: f32 = rng.gen();
-        let mix = Vec3::new(1.0, 0.8, 0.6).lerp(Vec3::new(0.6, 0.8, 1.0), t);
-        let color = Color::linear_rgb(mix.x * 100.0, mix.y * 100.0, mix.z * 100.0);
-
-        let mat = mats.add(StandardMaterial {
-            base_color_texture: Some(texture.clone()),
-            base_color: color,
-            emissive: color.into(),
+
+    // constellation lines, rotating with the stars
+    let lines = meshes.add(constellation_mesh(
+        &constellations,
+        now,
+        lat.to_radians(),
+        lon.to_radians(),
+    ));
+    let line_mat = mats.add(StandardMaterial {
+        base_color: Color::srgba(0.35, 0.5, 0.8, 0.5),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    commands.entity(root).with_children(|p| {
+        p.spawn((
+            Mesh3d(lines),
+            MeshMaterial3d(line_mat),
+            Transform::default(),
+            Visibility::default(),
+            ConstellationLines,
+        ));
+    });
+    commands.insert_resource(Constellations(constellations));
+
+    // Milky Way panorama behind the stars, if the texture is installed
+    if asset_base().join("assets").join(MILKY_WAY_TEXTURE).exists() {
+        let panorama = meshes.add(milky_way_mesh(now, lat.to_radians(), lon.to_radians()));
+        let panorama_mat = mats.add(StandardMaterial {
+            base_color_texture: Some(assets.load(MILKY_WAY_TEXTURE)),
+            base_color: Color::WHITE,
             alpha_mode: AlphaMode::Add,
             unlit: true,
+            cull_mode: None, // seen from inside
             ..default()
         });
-
         commands.entity(root).with_children(|p| {
             p.spawn((
-                Mesh3d(quad.clone()),        // mesh handle
-                MeshMaterial3d(mat.clone()), // material handle
-                Transform {
-                    translation: pos,
-                    scale: Vec3::splat(scale),
-                    ..Default::default()
-                },
-                Visibility::default(), // ensures the entity is rendered
-                StarData {
-                    ra: star.ra,
-                    dec: star.dec,
-                },
+                Mesh3d(panorama),
+                MeshMaterial3d(panorama_mat),
+                Transform::default(),
+                Visibility::default(),
+                MilkyWay,
             ));
         });
+    } else {
+        println!("No {MILKY_WAY_TEXTURE} in assets, Milky Way layer disabled");
     }
+
+    let names = match parse_star_names(STAR_NAMES) {
+        Ok(names) => names
+            .into_iter()
+            .filter(|(hr, _)| positions.contains_key(hr))
+            .map(|(hr, name)| (name, hr))
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to load star names: {e}");
+            Vec::new()
+        }
+    };
+    commands.insert_resource(StarIndex {
+        positions,
+        magnitudes,
+        names,
+    });
+
+    // stars are drawn a sky tile at a time, each tile one mesh under the root;
+    // update_bright_stars applies the limiting magnitude on the first frame
+    let tiles = split_into_tiles(stars.iter().map(|star| CloudStar {
+        ra: star.ra,
+        dec: star.dec,
+        magnitude: star.magnitudes[0],
+        color_index: star.color_index().unwrap_or(DEFAULT_COLOR_INDEX),
+    }));
+    commands.entity(root).with_children(|p| {
+        for tile in tiles {
+            let chunk = StarChunk { stars: tile };
+            let mesh = chunk.mesh(f32::INFINITY, now, lat.to_radians(), lon.to_radians());
+            p.spawn((
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(star_mat.clone()),
+                Transform::default(),
+                Visibility::default(),
+                chunk,
+                BrightStars,
+            ));
+        }
+    });
 }
 
-/// When you send a SetLocationEvent, recompute `axis` **and** every star's base position
-pub fn handle_set_location_events(
-    mut ev: MessageReader<PlanetariumEvent>,
-    mut state: ResMut<StarfieldState>,
-    mut q: Query<(&StarData, &mut Transform), Without<Camera3d>>,
-) {
-    for evt in ev.read() {
-        if let PlanetariumEvent::SetSiteLocation { lat_deg, lon_deg } = *evt {
-            // update state
-            state.lat_deg = lat_deg;
-            state.lon_deg = lon_deg;
-
-            let lr = lat_deg.to_radians();
-            state.axis = Vec3::new(0.0, lr.sin() as f32, lr.cos() as f32);
-
-            // recompute every star's initial translation
-            for (data, mut tf) in &mut q {
-                let dir = star_direction(
-                    state.spawn_utc,
-                    state.lat_deg.to_radians(),
-                    state.lon_deg.to_radians(),
-                    data.ra,
-                    data.dec,
-                );
-                tf.translation = dir * 100_000.0;
-            }
+/// Look up each figure's stars by HR number, dropping segments with unknown stars
+fn resolve_constellations(positions: &HashMap<u32, (f64, f64)>) -> Vec<Constellation> {
+    let figures = match parse_constellation_lines(CONSTELLATION_LINES) {
+        Ok(figures) => figures,
+        Err(e) => {
+            eprintln!("Failed to load constellation lines: {e}");
+            return Vec::new();
         }
-    }
+    };
+
+    figures
+        .into_iter()
+        .map(|figure| Constellation {
+            name: figure.name,
+            segments: figure
+                .segments
+                .iter()
+                .filter_map(|(a, b)| Some([*positions.get(a)?, *positions.get(b)?]))
+                .collect(),
+        })
+        .filter(|c| !c.segments.is_empty())
+        .collect()
 }
 
-/// When you send a SetTimeEvent, jump the rotation to that UTC
-fn handle_set_time_events(
-    mut ev: MessageReader<PlanetariumEvent>,
-    mut state: ResMut<StarfieldState>,
-) {
-    for evt in ev.read() {
-        if let PlanetariumEvent::SetTime { time } = *evt {
-            // how many seconds since spawn?
-            let delta_s = (time
-                .signed_duration_since(state.spawn_utc)
-                .num_milliseconds() as f32)
-                * 1e-3;
-            // set base_angle so that angle = rate * delta_s
-            state.base_angle = state.rate * delta_s;
-            state.base_instant = Instant::now();
+/// Line list through every constellation segment at the star sphere's radius
+fn constellation_mesh(
+    constellations: &[Constellation],
+    time: DateTime<Utc>,
+    lat: f64,
+    lon: f64,
+) -> Mesh {
+    let positions: Vec<[f32; 3]> = constellations
+        .iter()
+        .flat_map(|c| c.segments.iter().flatten())
+        .map(|&(ra, dec)| (star_direction(time, lat, lon, ra, dec) * 100_000.0).to_array())
+        .collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh
+}
+
+/// Sphere with its texture mapped by RA/Dec, placed like the stars
+fn milky_way_mesh(time: DateTime<Utc>, lat: f64, lon: f64) -> Mesh {
+    const SECTORS: u32 = 72;
+    const STACKS: u32 = 36;
+
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    for i in 0..=STACKS {
+        let v = i as f32 / STACKS as f32;
+        let dec = PI / 2.0 - v as f64 * PI;
+        for j in 0..=SECTORS {
+            let u = j as f32 / SECTORS as f32;
+            let ra = (1.0 - u as f64) * 2.0 * PI;
+            let dir = star_direction(time, lat, lon, ra, dec);
+            positions.push((dir * MILKY_WAY_DISTANCE).to_array());
+            uvs.push([u, v]);
         }
     }
+
+    let mut indices = Vec::new();
+    for i in 0..STACKS {
+        for j in 0..SECTORS {
+            let a = i * (SECTORS + 1) + j;
+            let b = a + SECTORS + 1;
+            indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// The panorama fades with the limiting magnitude: gone at naked-eye-from-the-city
+/// limits, full strength with every star shown
+fn milky_way_brightness(limiting_magnitude: f32) -> f32 {
+    let t = (limiting_magnitude - 3.0) / (FAINTEST_MAGNITUDE - 3.0);
+    t.clamp(0.0, 1.0) * MILKY_WAY_MAX_BRIGHTNESS
+}
+
+/// Root-local direction of a constellation's label: the mean of its stars
+fn constellation_center(c: &Constellation, state: &StarfieldState) -> Vec3 {
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    c.segments
+        .iter()
+        .flatten()
+        .map(|&(ra, dec)| star_direction(state.spawn_utc, lat, lon, ra, dec))
+        .sum::<Vec3>()
+        .normalize_or_zero()
 }
 
-/// Each frame: rotate the root by (base_angle + rate * elapsed_since_base)
-pub fn rotate_starfield_system(
+/// Rebuild the line mesh when the observer moves, and apply the layer toggle
+fn update_constellation_lines(
+    layers: Res<SkyLayers>,
     state: Res<StarfieldState>,
-    mut q: Query<&mut Transform, With<StarfieldRoot>>,
+    constellations: Res<Constellations>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q: Query<(&Mesh3d, &mut Visibility), With<ConstellationLines>>,
 ) {
-    let elapsed = state.base_instant.elapsed().as_secs_f32();
-    let angle = state.base_angle + state.rate * elapsed;
-    let mut tf = q.single_mut().unwrap();
-    tf.rotation = Quat::from_axis_angle(state.axis, -angle);
+    let Ok((mesh, mut vis)) = q.single_mut() else {
+        return;
+    };
+
+    if layers.is_changed() {
+        *vis = if layers.constellation_lines {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
+    if state.is_changed() {
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = constellation_mesh(
+                &constellations.0,
+                state.spawn_utc,
+                state.lat_deg.to_radians(),
+                state.lon_deg.to_radians(),
+            );
+        }
+    }
 }
 
-/// Keep the root positioned at the camera
-fn starfield_follow_camera(
-    cam_q: Query<&GlobalTransform, With<Camera>>,
-    mut star_q: Query<&mut Transform, With<StarfieldRoot>>,
+/// Rebuild the panorama when the observer moves, and apply the layer settings
+fn update_milky_way(
+    layers: Res<SkyLayers>,
+    twilight: Res<Twilight>,
+    state: Res<StarfieldState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    mut q: Query<(&Mesh3d, &MeshMaterial3d<StandardMaterial>, &mut Visibility), With<MilkyWay>>,
 ) {
-    // Try to grab exactly one camera; if it's not there yet, just return.
-    let cam_tf = match cam_q.single() {
-        Ok(tf) => tf,
-        Err(_) => return, // no camera spawned yet
+    let Ok((mesh, mat, mut vis)) = q.single_mut() else {
+        return;
     };
 
-    // Now propagate its position to all StarfieldRoot entities:
-    for mut star_tf in star_q.iter_mut() {
-        star_tf.translation = cam_tf.translation();
+    if layers.is_changed() || twilight.is_changed() {
+        *vis = if layers.milky_way {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if let Some(mat) = mats.get_mut(&mat.0) {
+            let brightness = milky_way_brightness(layers.star_limit(&twilight));
+            mat.base_color = Color::linear_rgb(brightness, brightness, brightness);
+        }
+    }
+
+    if state.is_changed() {
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = milky_way_mesh(
+                state.spawn_utc,
+                state.lat_deg.to_radians(),
+                state.lon_deg.to_radians(),
+            );
+        }
     }
 }
-/// Billboarding: make every star quad face the camera
-fn billboard_system(
-    cam_q: Query<&GlobalTransform, With<Camera>>,
-    root_q: Query<&GlobalTransform, With<StarfieldRoot>>,
-    mut stars: Query<&mut Transform, With<StarData>>,
+
+/// Constellation names at the center of each figure, painted behind the UI windows
+fn draw_constellation_labels(
+    layers: Res<SkyLayers>,
+    state: Res<StarfieldState>,
+    constellations: Res<Constellations>,
+    root_q: Query<&Transform, With<StarfieldRoot>>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
 ) {
-    // Fetch the camera's world rotation
-    let cam_tf = match cam_q.single() {
-        Ok(tf) => tf,
-        Err(_) => return, // no camera yet
+    if !layers.constellation_lines {
+        return;
+    }
+    let (Ok(root_tf), Ok((camera, cam_gtf, mut egui_context))) =
+        (root_q.single(), cam_q.single_mut())
+    else {
+        return;
     };
-    let cam_rot = cam_tf.compute_transform().rotation;
+    let ctx = egui_context.get_mut();
+    let painter = ctx.layer_painter(egui::LayerId::background());
 
-    // Fetch the starfield root's world rotation
-    let root_tf = match root_q.single() {
-        Ok(tf) => tf,
-        Err(_) => return, // root not spawned yet
-    };
-    let root_rot = root_tf.compute_transform().rotation;
+    for c in &constellations.0 {
+        let dir = root_tf.rotation * constellation_center(c, &state);
+        if dir.y < 0.0 {
+            continue; // below the horizon
+        }
+        let world = cam_gtf.translation() + dir * 100_000.0;
+        let Ok(pos) = camera.world_to_viewport(cam_gtf, world) else {
+            continue;
+        };
+        painter.text(
+            egui::pos2(pos.x, pos.y),
+            egui::Align2::CENTER_CENTER,
+            &c.name,
+            egui::FontId::proportional(12.0),
+            colors::egui::CONSTELLATION_LABEL,
+        );
+    }
+}
 
-    // Compute the local rotation that cancels out the root spin and applies the camera's orientation
-    let local_rot = root_rot.inverse() * cam_rot;
+/// Rebuild the star chunks when the observer moves or the limiting magnitude
+/// changes, hiding tiles with no star bright enough to show
+fn update_bright_stars(
+    mut commands: Commands,
+    layers: Res<SkyLayers>,
+    twilight: Res<Twilight>,
+    state: Res<StarfieldState>,
+    mut last: Local<Option<(f64, f64, f32)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut q: Query<(Entity, &StarChunk, &Mesh3d, &mut Visibility), With<BrightStars>>,
+) {
+    let limit = layers.star_limit(&twilight);
+    let key = (state.lat_deg, state.lon_deg, limit);
+    if *last == Some(key) {
+        return;
+    }
+    *last = Some(key);
+
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    for (entity, chunk, mesh, mut vis) in &mut q {
+        let rebuilt = chunk.mesh(limit, state.spawn_utc, lat, lon);
+        // an empty tile keeps its old mesh, hidden
+        let Some(aabb) = rebuilt.compute_aabb() else {
+            *vis = Visibility::Hidden;
+            continue;
+        };
+        *vis = Visibility::Inherited;
+        // refresh the bounds the tile is frustum culled with
+        commands.entity(entity).insert(aabb);
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = rebuilt;
+        }
+    }
+}
 
-    // Apply to every star quad
-    for mut tf in stars.iter_mut() {
-        tf.rotation = local_rot;
+/// When you send a SetLocationEvent, recompute `axis`; the stars follow in
+/// `update_bright_stars`
+pub fn handle_set_location_events(
+    mut ev: MessageReader<PlanetariumEvent>,
+    mut state: ResMut<StarfieldState>,
+) {
+    for evt in ev.read() {
+        if let PlanetariumEvent::SetSiteLocation {
+            lat_deg,
+            lon_deg,
+            alt_m,
+        } = *evt
+        {
+            // update state
+            state.set_site(lat_deg, lon_deg, alt_m);
+
+            // remember it for the next launch
+            let site = SiteLocation {
+                lat_deg,
+                lon_deg,
+                alt_m,
+            };
+            if let Err(e) = site.save() {
+                eprintln!("Failed to save site location: {e}");
+            }
+        }
+    }
+}
+
+/// When you send a SetTimeEvent, jump the rotation to that UTC
+fn handle_set_time_events(
+    mut ev: MessageReader<PlanetariumEvent>,
+    mut state: ResMut<StarfieldState>,
+) {
+    for evt in ev.read() {
+        if let PlanetariumEvent::SetTime { time } = *evt {
+            state.set_time(time);
+        }
     }
 }
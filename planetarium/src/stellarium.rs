@@ -0,0 +1,682 @@
+// src/stellarium.rs
+
+use base64::prelude::*;
+use bevy::prelude::*;
+use chrono::Utc;
+use protos::link;
+use std::f64::consts::PI;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+use crate::camera::CameraTarget;
+use crate::client::{self, SlewTarget};
+use crate::dso_catalog::DsoCatalog;
+use crate::events::PlanetariumEvent;
+use crate::planets::Planet;
+use crate::starfield::{equatorial_from_direction, julian_date, StarIndex, StarfieldState};
+use crate::target::MountPosition;
+use crate::ui::find_objects;
+
+/// Where Stellarium's Telescope Control plugin (and the apps that speak its
+/// protocol) can connect. Set to e.g. `0.0.0.0:10001` to accept phones on the
+/// LAN; the protocol has no authentication, so only on a trusted network.
+const ADDRESS_ENV: &str = "SIDEREAL_STELLARIUM_ADDRESS";
+const DEFAULT_ADDRESS: &str = "127.0.0.1:10001";
+/// Where tools written against Stellarium's RemoteControl plugin can connect.
+/// With a link token set they must send it as their password.
+const REMOTE_CONTROL_ADDRESS_ENV: &str = "SIDEREAL_REMOTE_CONTROL_ADDRESS";
+const DEFAULT_REMOTE_CONTROL_ADDRESS: &str = "127.0.0.1:8090";
+
+/// How often connected clients are told where the mount points
+const POSITION_INTERVAL: Duration = Duration::from_millis(500);
+
+/// "Goto" from the client: length, type, time, RA, Dec
+const GOTO_LENGTH: usize = 20;
+/// "Current position" to the client: the goto fields plus a status word
+const POSITION_LENGTH: usize = 24;
+/// Longest message we accept before giving up on a client
+const MAX_MESSAGE_LENGTH: usize = 256;
+/// Longest HTTP request line, header or body we accept
+const MAX_HTTP_LENGTH: usize = 8 * 1024;
+/// How long a focus by name waits for the planetarium to look the name up
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// RemoteControl actions that send the mount to the selection, one per
+/// telescope slot; there is only the one mount, so any of them slews it
+const SLEW_ACTION_PREFIX: &str = "actionMove_Telescope_To_Selection_";
+
+/// Mount pointing shared with the connection tasks, J2000 RA/Dec in radians
+static MOUNT: Mutex<Option<(f64, f64)>> = Mutex::new(None);
+/// What RemoteControl clients last focused, J2000 RA/Dec in radians
+static SELECTION: Mutex<Option<(f64, f64)>> = Mutex::new(None);
+
+/// Where a name lookup's J2000 RA/Dec (radians) goes, `None` if nothing matched
+pub type ObjectReply = UnboundedSender<Option<(f64, f64)>>;
+
+pub struct StellariumPlugin;
+impl Plugin for StellariumPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (share_mount_position, find_named_objects));
+    }
+}
+
+fn share_mount_position(mount: Res<MountPosition>) {
+    if !mount.is_changed() {
+        return;
+    }
+    if let Ok(mut shared) = MOUNT.lock() {
+        *shared = mount.0;
+    }
+}
+
+/// Answer RemoteControl lookups by name with the object search's best match
+fn find_named_objects(
+    mut events: MessageReader<PlanetariumEvent>,
+    state: Res<StarfieldState>,
+    planets: Query<(Entity, &Planet)>,
+    transforms: Query<&GlobalTransform>,
+    stars: Res<StarIndex>,
+    dso_catalog: Res<DsoCatalog>,
+    camera: Query<&GlobalTransform, With<Camera3d>>,
+) {
+    for evt in events.read() {
+        let PlanetariumEvent::FindObject { name, reply } = evt else {
+            continue;
+        };
+        let hit = find_objects(name, &planets, &stars, &dso_catalog)
+            .into_iter()
+            .next();
+        let position = hit.and_then(|hit| match hit.target {
+            CameraTarget::Equatorial { ra, dec } => Some((ra, dec)),
+            // planets are placed on the observer's horizon at the simulated time
+            CameraTarget::Entity(entity) => {
+                let direction = transforms.get(entity).ok()?.translation()
+                    - camera.single().ok()?.translation();
+                let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+                Some(equatorial_from_direction(
+                    state.current_utc(),
+                    lat,
+                    lon,
+                    direction,
+                ))
+            }
+            CameraTarget::Horizontal { .. } => None,
+        });
+        let _ = reply.send(position);
+    }
+}
+
+/// Full turn of RA is 2^32; ±90° of Dec is ±2^30
+fn encode_ra_dec(ra: f64, dec: f64) -> (u32, i32) {
+    let ra = (ra.rem_euclid(2.0 * PI) / (2.0 * PI) * 4_294_967_296.0) as u64 as u32;
+    let dec = (dec / (PI / 2.0) * 1_073_741_824.0) as i32;
+    (ra, dec)
+}
+
+fn decode_ra_dec(ra: u32, dec: i32) -> (f64, f64) {
+    (
+        ra as f64 / 4_294_967_296.0 * 2.0 * PI,
+        dec as f64 / 1_073_741_824.0 * (PI / 2.0),
+    )
+}
+
+/// "Current position" message for a mount at `ra`/`dec` (radians), stamped
+/// with `micros` since the Unix epoch
+fn position_message(ra: f64, dec: f64, micros: u64) -> [u8; POSITION_LENGTH] {
+    let (ra, dec) = encode_ra_dec(ra, dec);
+
+    let mut message = [0u8; POSITION_LENGTH];
+    message[0..2].copy_from_slice(&(POSITION_LENGTH as u16).to_le_bytes());
+    // type 0, then the time
+    message[4..12].copy_from_slice(&micros.to_le_bytes());
+    message[12..16].copy_from_slice(&ra.to_le_bytes());
+    message[16..20].copy_from_slice(&dec.to_le_bytes());
+    // status 0: ok
+    message
+}
+
+/// RA/Dec (radians) of a goto message, `None` for any other message type
+fn parse_goto(message: &[u8]) -> Option<(f64, f64)> {
+    if message.len() != GOTO_LENGTH || u16::from_le_bytes([message[2], message[3]]) != 0 {
+        return None;
+    }
+    let ra = u32::from_le_bytes(message[12..16].try_into().ok()?);
+    let dec = i32::from_le_bytes(message[16..20].try_into().ok()?);
+    Some(decode_ra_dec(ra, dec))
+}
+
+/// Ask the GUI to slew to J2000 `ra`/`dec` (radians). Slew requests carry
+/// J2000 like the rest of the planetarium; the GUI precesses them to the
+/// mount's equinox of date.
+fn slew_to(ra: f64, dec: f64) {
+    client::request_slew(SlewTarget::Equatorial {
+        ra_hours: ra.to_degrees() as f32 / 15.0,
+        dec_degrees: dec.to_degrees() as f32,
+    });
+}
+
+/// Serve the Stellarium telescope protocol and the RemoteControl HTTP API:
+/// gotos become slew requests to the GUI, and the mount position is reported
+/// back so the client can show it
+pub async fn run(events: Sender<PlanetariumEvent>) {
+    tokio::spawn(run_remote_control(events));
+
+    let address = std::env::var(ADDRESS_ENV).unwrap_or_else(|_| DEFAULT_ADDRESS.to_owned());
+    let listener = match TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Stellarium server failed to listen on {address}: {e}");
+            return;
+        }
+    };
+    info!("Stellarium telescope server listening on {address}");
+
+    loop {
+        match listener.accept().await {
+            Ok((socket, _)) => {
+                tokio::spawn(serve_client(socket));
+            }
+            Err(e) => warn!("Stellarium client failed to connect: {e}"),
+        }
+    }
+}
+
+async fn serve_client(socket: TcpStream) {
+    let (mut reader, mut writer) = socket.into_split();
+
+    let reporter = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POSITION_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let mount = MOUNT.lock().ok().and_then(|mount| *mount);
+            let Some((ra, dec)) = mount else {
+                continue;
+            };
+            let micros = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |t| t.as_micros() as u64);
+            if writer
+                .write_all(&position_message(ra, dec, micros))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    // every message starts with its own length, header included
+    let mut length = [0u8; 2];
+    while reader.read_exact(&mut length).await.is_ok() {
+        let length = u16::from_le_bytes(length) as usize;
+        if !(4..=MAX_MESSAGE_LENGTH).contains(&length) {
+            break;
+        }
+        let mut message = vec![0u8; length];
+        message[0..2].copy_from_slice(&(length as u16).to_le_bytes());
+        if reader.read_exact(&mut message[2..]).await.is_err() {
+            break;
+        }
+        if let Some((ra, dec)) = parse_goto(&message) {
+            slew_to(ra, dec);
+        }
+    }
+
+    reporter.abort();
+}
+
+/// Decode an `application/x-www-form-urlencoded` string (a query or a body)
+/// into its name/value pairs
+fn parse_form(form: &str) -> Vec<(String, String)> {
+    fn decode(component: &str) -> String {
+        let bytes = component.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'+' => decoded.push(b' '),
+                b'%' if i + 2 < bytes.len() => {
+                    let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                    match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                        Some(byte) => {
+                            decoded.push(byte);
+                            i += 2;
+                        }
+                        None => decoded.push(b'%'),
+                    }
+                }
+                byte => decoded.push(byte),
+            }
+            i += 1;
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
+    }
+
+    form.split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+/// RA/Dec (radians) of a RemoteControl `[x, y, z]` J2000 direction vector
+fn parse_position(position: &str) -> Option<(f64, f64)> {
+    let inner = position.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let xyz: Vec<f64> = inner
+        .split(',')
+        .map(|v| v.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let [x, y, z] = xyz[..] else {
+        return None;
+    };
+    let length = (x * x + y * y + z * z).sqrt();
+    if !length.is_finite() || length == 0.0 {
+        return None;
+    }
+    Some((y.atan2(x).rem_euclid(2.0 * PI), (z / length).asin()))
+}
+
+/// "RA 05h 12m 30s, Dec +20° 30' 00" (J2000)"
+fn describe(ra: f64, dec: f64) -> String {
+    let ra_seconds = (ra.to_degrees() / 15.0 * 3600.0).round() as i64 % (24 * 3600);
+    let dec_arcsec = (dec.to_degrees() * 3600.0).round() as i64;
+    format!(
+        "RA {:02}h {:02}m {:02}s, Dec {}{:02}° {:02}' {:02}\" (J2000)",
+        ra_seconds / 3600,
+        ra_seconds / 60 % 60,
+        ra_seconds % 60,
+        if dec_arcsec < 0 { '-' } else { '+' },
+        dec_arcsec.abs() / 3600,
+        dec_arcsec.abs() / 60 % 60,
+        dec_arcsec.abs() % 60
+    )
+}
+
+/// Whether an `Authorization` header carries the link token, as the password
+/// of HTTP Basic auth (like Stellarium's own) or as a bearer token
+fn authorized(expected: Option<&str>, authorization: Option<&str>) -> bool {
+    let Some(expected) = expected else {
+        return true;
+    };
+    let Some((scheme, credentials)) = authorization.and_then(|value| value.trim().split_once(' '))
+    else {
+        return false;
+    };
+    let credentials = credentials.trim();
+    if scheme.eq_ignore_ascii_case("bearer") {
+        credentials == expected
+    } else if scheme.eq_ignore_ascii_case("basic") {
+        // "user:password"; Stellarium leaves the user name empty
+        BASE64_STANDARD
+            .decode(credentials)
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .is_some_and(|decoded| {
+                decoded
+                    .split_once(':')
+                    .is_some_and(|(_, password)| password == expected)
+            })
+    } else {
+        false
+    }
+}
+
+/// J2000 RA/Dec (radians) of the object the planetarium's search finds for
+/// `name`, asked of the Bevy world through `events`
+async fn find_object(events: &Sender<PlanetariumEvent>, name: &str) -> Option<(f64, f64)> {
+    let (reply, mut found) = mpsc::unbounded_channel();
+    events
+        .send(PlanetariumEvent::FindObject {
+            name: name.to_owned(),
+            reply,
+        })
+        .ok()?;
+    tokio::time::timeout(LOOKUP_TIMEOUT, found.recv())
+        .await
+        .ok()
+        .flatten()
+        .flatten()
+}
+
+/// Status code, content type and body answering a RemoteControl request for
+/// `path` with the query and body `params`. `found` is where a focus
+/// request's `target` name was found, if it was.
+fn remote_control_response(
+    method: &str,
+    path: &str,
+    params: &[(String, String)],
+    found: Option<(f64, f64)>,
+) -> (u16, &'static str, String) {
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let text = |status, body: &str| (status, "text/plain; charset=utf-8", body.to_owned());
+
+    match (method, path) {
+        ("GET", "/api/main/status") => {
+            let selection = SELECTION.lock().ok().and_then(|selection| *selection);
+            let info = selection.map_or_else(String::new, |(ra, dec)| describe(ra, dec));
+            let body = format!(
+                r#"{{"selectioninfo":"{}","time":{{"jday":{},"timerate":{},"isTimeNow":true}}}}"#,
+                info.replace('"', "\\\""),
+                julian_date(Utc::now()),
+                1.0 / 86_400.0
+            );
+            (200, "application/json; charset=utf-8", body)
+        }
+        ("POST", "/api/main/focus") => {
+            // a named target, else a direction
+            let position = found.or_else(|| param("position").and_then(parse_position));
+            let Some(position) = position else {
+                return text(200, "false");
+            };
+            if let Ok(mut selection) = SELECTION.lock() {
+                *selection = Some(position);
+            }
+            text(200, "true")
+        }
+        ("POST", "/api/stelaction/do") => match param("id") {
+            Some(id) if id.starts_with(SLEW_ACTION_PREFIX) => {
+                let selection = SELECTION.lock().ok().and_then(|selection| *selection);
+                match selection {
+                    Some((ra, dec)) => {
+                        slew_to(ra, dec);
+                        text(200, "ok")
+                    }
+                    None => text(200, "nothing selected"),
+                }
+            }
+            Some(id) => text(400, &format!("unsupported action {id}")),
+            None => text(400, "need parameter: id"),
+        },
+        _ => text(404, "not found"),
+    }
+}
+
+/// Serve the subset of Stellarium's RemoteControl HTTP API that sends the
+/// mount somewhere: focus a J2000 direction, then run the telescope slew
+/// action on it
+async fn run_remote_control(events: Sender<PlanetariumEvent>) {
+    let token = std::env::var(link::TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.is_empty());
+    let address = std::env::var(REMOTE_CONTROL_ADDRESS_ENV)
+        .unwrap_or_else(|_| DEFAULT_REMOTE_CONTROL_ADDRESS.to_owned());
+    let listener = match TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Stellarium RemoteControl server failed to listen on {address}: {e}");
+            return;
+        }
+    };
+    info!("Stellarium RemoteControl server listening on {address}");
+
+    loop {
+        match listener.accept().await {
+            Ok((socket, _)) => {
+                let (events, token) = (events.clone(), token.clone());
+                tokio::spawn(async move {
+                    if let Err(e) = serve_http(socket, &events, token.as_deref()).await {
+                        debug!("RemoteControl request failed: {e}");
+                    }
+                });
+            }
+            Err(e) => warn!("RemoteControl client failed to connect: {e}"),
+        }
+    }
+}
+
+/// One line of an HTTP request, never reading more than `MAX_HTTP_LENGTH`
+async fn read_http_line<R: AsyncBufRead + Unpin>(reader: &mut R) -> std::io::Result<String> {
+    let mut line = String::new();
+    reader
+        .take(MAX_HTTP_LENGTH as u64)
+        .read_line(&mut line)
+        .await?;
+    if !line.ends_with('\n') {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "request cut short or line too long",
+        ));
+    }
+    Ok(line)
+}
+
+/// Answer one HTTP request, then close the connection
+async fn serve_http(
+    socket: TcpStream,
+    events: &Sender<PlanetariumEvent>,
+    token: Option<&str>,
+) -> std::io::Result<()> {
+    let invalid = |what: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, what);
+    let mut reader = BufReader::new(socket);
+
+    let request_line = read_http_line(&mut reader).await?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let (method, target) = (method.to_owned(), target.to_owned());
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    let mut from_browser = false;
+    loop {
+        let header = read_http_line(&mut reader).await?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| invalid("bad content length"))?;
+            } else if name.eq_ignore_ascii_case("authorization") {
+                authorization = Some(value.trim().to_owned());
+            } else if name.eq_ignore_ascii_case("origin") {
+                from_browser = true;
+            }
+        }
+    }
+    if content_length > MAX_HTTP_LENGTH {
+        return Err(invalid("body too long"));
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+    let mut params = parse_form(query);
+    params.extend(parse_form(&String::from_utf8_lossy(&body)));
+    let text = |status, body: &str| (status, "text/plain; charset=utf-8", body.to_owned());
+    let (status, content_type, body) = if from_browser {
+        // Browsers always send Origin and RemoteControl clients don't; no
+        // web page gets to slew the mount
+        text(403, "requests from web pages are refused")
+    } else if !authorized(token, authorization.as_deref()) {
+        text(401, "missing or wrong link token")
+    } else {
+        let target = params
+            .iter()
+            .find(|(name, _)| name == "target")
+            .map(|(_, target)| target.trim())
+            .filter(|target| !target.is_empty());
+        let found = match target {
+            Some(target) if path == "/api/main/focus" => find_object(events, target).await,
+            _ => None,
+        };
+        remote_control_response(&method, path, &params, found)
+    };
+
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        _ => "Not Found",
+    };
+    // ask for the token the way Stellarium asks for its password
+    let challenge = if status == 401 {
+        "WWW-Authenticate: Basic realm=\"Sidereal\"\r\n"
+    } else {
+        ""
+    };
+    let response = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n{challenge}Connection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let mut socket = reader.into_inner();
+    socket.write_all(response.as_bytes()).await?;
+    socket.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_message_layout() {
+        // RA 12h is half a turn, Dec +45° half of 2^31
+        let message = position_message(PI, PI / 4.0, 0x0102_0304_0506_0708);
+        assert_eq!(
+            message,
+            [
+                24, 0, // length
+                0, 0, // type
+                0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, // time
+                0x00, 0x00, 0x00, 0x80, // RA
+                0x00, 0x00, 0x00, 0x20, // Dec
+                0, 0, 0, 0, // status
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_goto() {
+        // RA 6h, Dec -45°
+        let mut message = vec![20, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8];
+        message.extend_from_slice(&0x4000_0000u32.to_le_bytes());
+        message.extend_from_slice(&(-0x2000_0000i32).to_le_bytes());
+        let (ra, dec) = parse_goto(&message).unwrap();
+        assert!((ra - PI / 2.0).abs() < 1e-12);
+        assert!((dec + PI / 4.0).abs() < 1e-12);
+
+        // another message type, and a truncated goto
+        message[2] = 1;
+        assert_eq!(parse_goto(&message), None);
+        assert_eq!(parse_goto(&message[..16]), None);
+    }
+
+    #[test]
+    fn test_goto_round_trip() {
+        // a position message read back as a goto, once its status is dropped
+        for &(ra, dec) in &[
+            (0.0, 0.0),
+            (1.234, 0.5),
+            (6.2, -1.2),
+            (3.0, PI / 2.0 - 1e-6),
+        ] {
+            let mut message = position_message(ra, dec, 0)[..GOTO_LENGTH].to_vec();
+            message[0] = GOTO_LENGTH as u8;
+            let (back_ra, back_dec) = parse_goto(&message).unwrap();
+            // one unit is well under a milliarcsecond
+            assert!(
+                (back_ra - ra).abs() < 1e-8,
+                "RA {ra} came back as {back_ra}"
+            );
+            assert!(
+                (back_dec - dec).abs() < 1e-8,
+                "Dec {dec} came back as {back_dec}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_form() {
+        assert_eq!(
+            parse_form("position=%5B1%2C0%2C0%5D&mode=mark+it&flag"),
+            vec![
+                ("position".to_owned(), "[1,0,0]".to_owned()),
+                ("mode".to_owned(), "mark it".to_owned()),
+                ("flag".to_owned(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let (ra, dec) = parse_position("[0, 1, 0]").unwrap();
+        assert!((ra - PI / 2.0).abs() < 1e-12 && dec.abs() < 1e-12);
+        let (ra, dec) = parse_position("[-0.5,0,0.5]").unwrap();
+        assert!((ra - PI).abs() < 1e-12 && (dec - PI / 4.0).abs() < 1e-12);
+        assert_eq!(parse_position("[0,0,0]"), None);
+        assert_eq!(parse_position("[1,0]"), None);
+        assert_eq!(parse_position("1,0,0"), None);
+    }
+
+    #[test]
+    fn test_authorized() {
+        // no token configured: anyone may connect
+        assert!(authorized(None, None));
+
+        let token = Some("s3cret");
+        // Basic ":s3cret" and "remote:s3cret"
+        assert!(authorized(token, Some("Basic OnMzY3JldA==")));
+        assert!(authorized(token, Some("basic cmVtb3RlOnMzY3JldA==")));
+        assert!(authorized(token, Some("Bearer s3cret")));
+
+        assert!(!authorized(token, None));
+        assert!(!authorized(token, Some("Bearer wrong")));
+        // Basic "s3cret", no password at all
+        assert!(!authorized(token, Some("Basic czNjcmV0")));
+        assert!(!authorized(token, Some("Basic not base64!")));
+        assert!(!authorized(token, Some("s3cret")));
+    }
+
+    #[tokio::test]
+    async fn test_read_http_line_is_bounded() {
+        let mut request = BufReader::new(&b"GET /api/main/status HTTP/1.1\r\nHost: x\r\n"[..]);
+        assert_eq!(
+            read_http_line(&mut request).await.unwrap(),
+            "GET /api/main/status HTTP/1.1\r\n"
+        );
+        assert_eq!(read_http_line(&mut request).await.unwrap(), "Host: x\r\n");
+        // the stream ending mid-request
+        assert!(read_http_line(&mut request).await.is_err());
+
+        // a line that never ends is refused once it reaches the limit
+        let mut endless = BufReader::new(tokio::io::repeat(b'a'));
+        assert!(read_http_line(&mut endless).await.is_err());
+    }
+
+    #[test]
+    fn test_focus_prefers_the_named_target() {
+        let focus = |params: &[(String, String)], found| {
+            remote_control_response("POST", "/api/main/focus", params, found).2
+        };
+        let selection = || SELECTION.lock().unwrap().unwrap();
+
+        // a name that was found wins over a direction
+        let with_position = parse_form("target=M31&position=%5B0%2C1%2C0%5D");
+        assert_eq!(focus(&with_position, Some((0.5, 0.25))), "true");
+        assert_eq!(selection(), (0.5, 0.25));
+
+        // a name that wasn't falls back to the direction
+        assert_eq!(focus(&with_position, None), "true");
+        let (ra, dec) = selection();
+        assert!((ra - PI / 2.0).abs() < 1e-12 && dec.abs() < 1e-12);
+
+        // and with neither there is nothing to focus
+        assert_eq!(focus(&parse_form("target=nowhere"), None), "false");
+    }
+}
@@ -0,0 +1,342 @@
+// src/sun_avoidance.rs
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use chrono::{DateTime, Utc};
+use protos::protos::SunWarning;
+use std::f64::consts::PI;
+use std::{fs, io, path::PathBuf};
+
+use crate::{
+    all_sky::perspective_view,
+    client::send_sun_warning,
+    colors,
+    gui_link::GuiLinkStatus,
+    satellite::{TrackedPass, TrackedSatellitePosition, PATH_DISTANCE},
+    site::config_file,
+    starfield::{
+        alt_az_direction, horizontal_from_hour_angle, hour_angle_from_horizontal,
+        local_sidereal_time, StarfieldState,
+    },
+    target::{MountPosition, SelectedTarget},
+    twilight::sun_equatorial,
+};
+
+/// Points along the slew from the mount to its target that are checked
+const SLEW_SAMPLES: usize = 32;
+/// Points around the drawn edge of the cone
+const CONE_SEGMENTS: usize = 96;
+
+/// Keep-out cone around the Sun, remembered between runs
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct SunAvoidance {
+    pub enabled: bool,
+    /// Closest (degrees) anything may come to the Sun's center
+    pub radius_deg: f64,
+}
+
+impl Default for SunAvoidance {
+    fn default() -> Self {
+        SunAvoidance {
+            enabled: true,
+            radius_deg: 30.0,
+        }
+    }
+}
+
+fn sun_avoidance_path() -> Option<PathBuf> {
+    config_file("planetarium_sun_avoidance.txt")
+}
+
+impl SunAvoidance {
+    /// The saved cone, if there is one: "enabled radius"
+    pub fn load() -> Option<SunAvoidance> {
+        let data = fs::read_to_string(sun_avoidance_path()?).ok()?;
+        let mut fields = data.split_whitespace();
+        Some(SunAvoidance {
+            enabled: fields.next()?.parse().ok()?,
+            radius_deg: fields.next()?.parse::<f64>().ok()?.clamp(0.0, 90.0),
+        })
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = sun_avoidance_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, format!("{} {}\n", self.enabled, self.radius_deg))
+    }
+}
+
+/// Something inside the cone
+#[derive(Debug, Clone, PartialEq)]
+pub struct SunIntrusion {
+    /// What it is, e.g. "Slew path"
+    pub what: &'static str,
+    /// Closest approach to the Sun (degrees)
+    pub separation_deg: f64,
+}
+
+/// The closest thing inside the cone at the last check, if any
+#[derive(Resource, Default, PartialEq)]
+pub struct SunWarningState(pub Option<SunIntrusion>);
+
+#[derive(Resource)]
+struct SunCheckTimer(Timer);
+
+pub struct SunAvoidancePlugin;
+impl Plugin for SunAvoidancePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SunAvoidance::load().unwrap_or_default())
+            .init_resource::<SunWarningState>()
+            .insert_resource(SunCheckTimer(Timer::from_seconds(
+                1.0,
+                TimerMode::Repeating,
+            )))
+            .add_systems(
+                Update,
+                (
+                    check_sun_avoidance,
+                    signal_sun_warning,
+                    draw_sun_warning,
+                    draw_sun_cone.run_if(perspective_view),
+                )
+                    .chain(),
+            )
+            .add_systems(Last, save_sun_avoidance);
+    }
+}
+
+/// Angle (degrees) between two RA/Dec positions (radians)
+fn separation(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let cos = a.1.sin() * b.1.sin() + a.1.cos() * b.1.cos() * (a.0 - b.0).cos();
+    cos.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+/// RA/Dec (radians) of something at `altitude`/`azimuth` (degrees) at `time`
+fn equatorial(
+    state: &StarfieldState,
+    time: DateTime<Utc>,
+    altitude: f64,
+    azimuth: f64,
+) -> (f64, f64) {
+    let (ha, dec) = hour_angle_from_horizontal(state.lat_deg.to_radians(), altitude, azimuth);
+    let ra = local_sidereal_time(time, state.lon_deg.to_radians()) - ha;
+    (ra, dec)
+}
+
+/// Where a mount driving both axes at once passes on its way from `from` to
+/// `to`, taking the short way round in RA
+fn slew_path(from: (f64, f64), to: (f64, f64)) -> impl Iterator<Item = (f64, f64)> {
+    let d_ra = (to.0 - from.0 + PI).rem_euclid(2.0 * PI) - PI;
+    (0..=SLEW_SAMPLES).map(move |i| {
+        let t = i as f64 / SLEW_SAMPLES as f64;
+        (from.0 + d_ra * t, from.1 + (to.1 - from.1) * t)
+    })
+}
+
+/// Once a second, and whenever the cone changes: find the closest of the
+/// tracked satellite, its pass, the mount, its target and the slew between
+/// them that comes inside the cone
+#[allow(clippy::too_many_arguments)]
+fn check_sun_avoidance(
+    time: Res<Time>,
+    mut timer: ResMut<SunCheckTimer>,
+    avoidance: Res<SunAvoidance>,
+    state: Res<StarfieldState>,
+    satellite: Res<TrackedSatellitePosition>,
+    pass: Res<TrackedPass>,
+    mount: Res<MountPosition>,
+    selected: Res<SelectedTarget>,
+    mut warning: ResMut<SunWarningState>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() && !avoidance.is_changed() {
+        return;
+    }
+    if !avoidance.enabled {
+        warning.set_if_neq(SunWarningState(None));
+        return;
+    }
+
+    let now = state.current_utc();
+    let sun = sun_equatorial(now);
+    let mut closest: Option<SunIntrusion> = None;
+    let mut consider = |what: &'static str, separation_deg: f64| {
+        if separation_deg < avoidance.radius_deg
+            && closest
+                .as_ref()
+                .is_none_or(|c| separation_deg < c.separation_deg)
+        {
+            closest = Some(SunIntrusion {
+                what,
+                separation_deg,
+            });
+        }
+    };
+
+    if let Some((alt, az)) = satellite.0 {
+        consider(
+            "Tracked satellite",
+            separation(sun, equatorial(&state, now, alt, az)),
+        );
+    }
+    // the rest of the pass, against the Sun as it will be then
+    if let Some(pass) = &pass.0 {
+        for point in pass.trajectory.iter().filter(|p| p.timestamp >= now) {
+            let position = equatorial(&state, point.timestamp, point.altitude, point.azimuth);
+            consider(
+                "Tracked pass",
+                separation(sun_equatorial(point.timestamp), position),
+            );
+        }
+    }
+    if let Some(target) = selected.0 {
+        consider("Mount target", separation(sun, target));
+    }
+    if let Some(position) = mount.0 {
+        consider("Mount", separation(sun, position));
+        if let Some(target) = selected.0 {
+            for point in slew_path(position, target) {
+                consider("Slew path", separation(sun, point));
+            }
+        }
+    }
+
+    warning.set_if_neq(SunWarningState(closest));
+}
+
+/// Tell the GUI when the warning starts, moves on to something else or
+/// clears, and bring a newly connected GUI up to date
+fn signal_sun_warning(
+    warning: Res<SunWarningState>,
+    avoidance: Res<SunAvoidance>,
+    gui_link: Res<GuiLinkStatus>,
+    mut last_sent: Local<Option<Option<&'static str>>>,
+) {
+    if !gui_link.connected {
+        return;
+    }
+    let what = warning.0.as_ref().map(|intrusion| intrusion.what);
+    if *last_sent == Some(what) && !gui_link.is_changed() {
+        return;
+    }
+    *last_sent = Some(what);
+    let message = match &warning.0 {
+        Some(intrusion) => SunWarning {
+            active: true,
+            description: intrusion.what.to_string(),
+            separation_degrees: intrusion.separation_deg as f32,
+            radius_degrees: avoidance.radius_deg as f32,
+        },
+        None => SunWarning {
+            radius_degrees: avoidance.radius_deg as f32,
+            ..default()
+        },
+    };
+    if let Err(e) = send_sun_warning(message) {
+        eprintln!("Failed to send Sun warning: {e}");
+    }
+}
+
+/// Red banner under the menu bar while something is inside the cone
+fn draw_sun_warning(
+    warning: Res<SunWarningState>,
+    avoidance: Res<SunAvoidance>,
+    mut cam_q: Query<&mut EguiContext, With<Camera3d>>,
+) {
+    let Some(intrusion) = &warning.0 else {
+        return;
+    };
+    let Ok(mut egui_context) = cam_q.single_mut() else {
+        return;
+    };
+    egui::Area::new(egui::Id::new("sun_warning"))
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 36.0))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(egui_context.get_mut(), |ui| {
+            egui::Frame::window(ui.style())
+                .fill(colors::egui::WINDOW_BACKGROUND)
+                .stroke(egui::Stroke::new(1.0, colors::egui::RED_TEXT))
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "Sun avoidance: {} {:.1}° from the Sun (limit {:.0}°)",
+                            intrusion.what, intrusion.separation_deg, avoidance.radius_deg
+                        ))
+                        .size(14.0)
+                        .color(colors::egui::RED_TEXT),
+                    );
+                });
+        });
+}
+
+/// The edge of the cone around the Sun, painted behind the UI windows
+fn draw_sun_cone(
+    avoidance: Res<SunAvoidance>,
+    state: Res<StarfieldState>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    if !avoidance.enabled {
+        return;
+    }
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let painter = egui_context
+        .get_mut()
+        .layer_painter(egui::LayerId::background());
+
+    let now = state.current_utc();
+    let lat = state.lat_deg.to_radians();
+    let (ra, dec) = sun_equatorial(now);
+    let ha = local_sidereal_time(now, state.lon_deg.to_radians()) - ra;
+    let (alt, az) = horizontal_from_hour_angle(lat, ha, dec);
+    let sun = alt_az_direction(alt, az);
+
+    let to_screen = |dir: Vec3| {
+        let world = cam_gtf.translation() + dir * PATH_DISTANCE;
+        let pos = camera.world_to_viewport(cam_gtf, world).ok()?;
+        Some(egui::pos2(pos.x, pos.y))
+    };
+    let stroke = egui::Stroke::new(1.5, colors::egui::RED_TEXT);
+
+    // circle of the given radius about the Sun's direction, in screen segments
+    // wherever both ends are in front of the camera
+    let (u, v) = sun.any_orthonormal_pair();
+    let radius = avoidance.radius_deg.to_radians() as f32;
+    let edge: Vec<Option<egui::Pos2>> = (0..=CONE_SEGMENTS)
+        .map(|i| {
+            let angle = i as f32 / CONE_SEGMENTS as f32 * std::f32::consts::TAU;
+            let offset = (u * angle.cos() + v * angle.sin()) * radius.sin();
+            to_screen(sun * radius.cos() + offset)
+        })
+        .collect();
+    for pair in edge.windows(2) {
+        if let [Some(a), Some(b)] = pair {
+            painter.line_segment([*a, *b], stroke);
+        }
+    }
+
+    if let Some(center) = to_screen(sun) {
+        painter.circle_stroke(center, 6.0, stroke);
+        painter.text(
+            center + egui::vec2(9.0, -9.0),
+            egui::Align2::LEFT_BOTTOM,
+            "Sun",
+            egui::FontId::proportional(12.0),
+            colors::egui::RED_TEXT,
+        );
+    }
+}
+
+/// Saved on every change; it only changes from the settings window
+fn save_sun_avoidance(avoidance: Res<SunAvoidance>) {
+    if !avoidance.is_changed() || avoidance.is_added() {
+        return;
+    }
+    if let Err(e) = avoidance.save() {
+        eprintln!("Failed to save Sun avoidance settings: {e}");
+    }
+}
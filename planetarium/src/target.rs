@@ -1,5 +1,5 @@
 use crate::events::PlanetariumEvent;
-use crate::starfield::StarfieldRoot;
+use crate::starfield::{star_direction, StarfieldRoot, StarfieldState};
 use bevy::prelude::*;
 
 #[derive(Component)]
@@ -16,7 +16,17 @@ impl Plugin for TargetPlugin {
         app.add_systems(Update, place_target_on_right_click);
         app.add_systems(PostUpdate, rescale_targets_system);
         app.add_message::<PlanetariumEvent>();
-        app.add_systems(Update, handle_set_mount_position_events);
+        app.init_resource::<MountPosition>();
+        app.init_resource::<SelectedTarget>();
+        app.add_systems(
+            Update,
+            (
+                handle_set_mount_position_events,
+                place_mount_marker,
+                place_selected_target_marker,
+            )
+                .chain(),
+        );
         app.add_systems(PostUpdate, orient_targets_to_camera);
     }
 }
@@ -271,61 +281,87 @@ pub fn spawn_tracking_target(
     )
 }
 
+/// Last mount pointing received over gRPC, J2000 RA/Dec in radians (the GUI
+/// precesses it from the mount's equinox of date)
+#[derive(Resource, Default)]
+pub struct MountPosition(pub Option<(f64, f64)>);
+
+/// Target the GUI last sent the mount to, J2000 RA/Dec in radians (the mount
+/// itself was sent it precessed to date)
+#[derive(Resource, Default)]
+pub struct SelectedTarget(pub Option<(f64, f64)>);
+
+fn hours_degrees_to_radians(ra_hours: f32, dec_deg: f32) -> (f64, f64) {
+    (
+        (ra_hours as f64 * 15.0).to_radians(),
+        (dec_deg as f64).to_radians(),
+    )
+}
+
+/// Store the latest mount position and selected target reported this frame
 pub fn handle_set_mount_position_events(
-    mut commands: Commands,
     mut ev: MessageReader<PlanetariumEvent>,
+    mut mount: ResMut<MountPosition>,
+    mut selected: ResMut<SelectedTarget>,
+) {
+    // Read only the last SetMountPosition of this frame
+    let mut last: Option<(f32, f32)> = None;
+    for evt in ev.read() {
+        match *evt {
+            PlanetariumEvent::SetMountPosition { ra_hours, dec_deg } => {
+                last = Some((ra_hours, dec_deg));
+            }
+            PlanetariumEvent::SetSelectedTarget { ra_hours, dec_deg } => {
+                selected.0 = Some(hours_degrees_to_radians(ra_hours, dec_deg));
+            }
+            _ => {}
+        }
+    }
+    if let Some((ra_hours, dec_deg)) = last {
+        mount.0 = Some(hours_degrees_to_radians(ra_hours, dec_deg));
+    }
+}
+
+/// Root-local position of a reticle on RA/Dec (radians)
+fn marker_position(state: &StarfieldState, ra: f64, dec: f64) -> Vec3 {
+    let distance = 100.0;
+    star_direction(
+        state.spawn_utc,
+        state.lat_deg.to_radians(),
+        state.lon_deg.to_radians(),
+        ra,
+        dec,
+    ) * distance
+}
+
+/// Put the mount reticle on the mount's RA/Dec, spawning it on the first report.
+/// Like the stars it sits at its spawn-time position under the rotating root.
+pub fn place_mount_marker(
+    mut commands: Commands,
+    mount: Res<MountPosition>,
+    state: Res<StarfieldState>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut mats: ResMut<Assets<StandardMaterial>>,
     assets: Res<AssetServer>,
     root_entity_q: Query<Entity, With<StarfieldRoot>>,
-    root_tf_q: Query<&GlobalTransform, With<StarfieldRoot>>,
     mut q: Query<(&Marker, &mut Transform)>,
-    camera_q: Query<&GlobalTransform, With<Camera>>,
 ) {
-    #[inline]
-    fn radec_dir_from_hours(ra_hours: f32, dec_deg: f32) -> Vec3 {
-        let ra = (ra_hours * 15.0).to_radians();
-        let dec = dec_deg.to_radians();
-        Vec3::new(dec.cos() * ra.cos(), dec.sin(), dec.cos() * ra.sin())
-    }
-
-    // 1) Read only the last SetMountPosition of this frame
-    let mut last: Option<(f32, f32)> = None;
-    for evt in ev.read() {
-        if let PlanetariumEvent::SetMountPosition { ra_hours, dec_deg } = *evt {
-            last = Some((ra_hours, dec_deg));
-        }
+    if !mount.is_changed() && !state.is_changed() {
+        return;
     }
-    let Some((ra_hours, dec_deg)) = last else {
+    let Some((ra, dec)) = mount.0 else {
         return;
     };
 
-    // 2) Compute local position/rotation under the rotating StarfieldRoot
-    let Ok(root_gtf) = root_tf_q.single() else {
-        return;
-    };
-    let world_to_root = root_gtf.affine().inverse();
-    let root_rot = root_gtf.compute_transform().rotation;
+    let pos_local = marker_position(&state, ra, dec);
 
-    let cam_rot = camera_q
-        .single()
-        .ok()
-        .map(|g| g.compute_transform().rotation);
-
-    let distance = 100.0;
-    let dir_world = radec_dir_from_hours(ra_hours, dec_deg).normalize();
-    let pos_world = dir_world * distance;
-    let pos_local = world_to_root.transform_point3(pos_world);
-    let rot_local = cam_rot.map_or(Quat::IDENTITY, |c| root_rot.inverse() * c);
-
-    // 3) Move existing mount target if present; otherwise spawn exactly one
+    // Move existing mount target if present; otherwise spawn exactly one.
+    // orient_targets_to_camera takes care of the rotation.
     if let Some((_, mut tf)) = q
         .iter_mut()
         .find(|(m, _)| matches!(*m, &Marker::MountTargetMarker))
-    // <-- fix
     {
         tf.translation = pos_local;
-        tf.rotation = rot_local;
     } else {
         let _ = spawn_mount_target(
             &mut commands,
@@ -334,7 +370,45 @@ pub fn handle_set_mount_position_events(
             &assets,
             &root_entity_q,
             pos_local,
-            rot_local,
+            Quat::IDENTITY,
+        );
+    }
+}
+
+/// Move the tracking target reticle to the target the GUI selected, the same
+/// reticle a right-click places
+pub fn place_selected_target_marker(
+    mut commands: Commands,
+    selected: Res<SelectedTarget>,
+    state: Res<StarfieldState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut mats: ResMut<Assets<StandardMaterial>>,
+    assets: Res<AssetServer>,
+    root_entity_q: Query<Entity, With<StarfieldRoot>>,
+    mut q: Query<(&Marker, &mut Transform)>,
+) {
+    if !selected.is_changed() && !state.is_changed() {
+        return;
+    }
+    let Some((ra, dec)) = selected.0 else {
+        return;
+    };
+    let pos_local = marker_position(&state, ra, dec);
+
+    if let Some((_, mut tf)) = q
+        .iter_mut()
+        .find(|(m, _)| matches!(*m, &Marker::TrackingTargetMarker))
+    {
+        tf.translation = pos_local;
+    } else {
+        let _ = spawn_tracking_target(
+            &mut commands,
+            &mut meshes,
+            &mut mats,
+            &assets,
+            &root_entity_q,
+            pos_local,
+            Quat::IDENTITY,
         );
     }
 }
@@ -0,0 +1,116 @@
+// src/twilight.rs
+
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    faint_stars::FAINTEST_MAGNITUDE,
+    starfield::{horizontal_coordinates, julian_date, StarfieldState},
+};
+
+/// Sky background by solar altitude (degrees), in linear RGB
+const SKY_COLORS: [(f64, [f32; 3]); 5] = [
+    (-18.0, [0.0, 0.0, 3.0 / 255.0]),
+    (-12.0, [0.004, 0.006, 0.02]),
+    (-6.0, [0.02, 0.04, 0.12]),
+    (0.0, [0.15, 0.25, 0.5]),
+    (10.0, [0.3, 0.5, 0.9]),
+];
+
+/// Faintest star visible against the sky by solar altitude
+const SKY_LIMITS: [(f64, f32); 5] = [
+    (-18.0, FAINTEST_MAGNITUDE),
+    (-12.0, 5.5),
+    (-6.0, 3.5),
+    (0.0, 0.5),
+    (10.0, -1.5),
+];
+
+/// How bright the sky is at the simulated time and place
+#[derive(Resource)]
+pub struct Twilight {
+    /// Solar altitude (degrees)
+    pub sun_altitude: f64,
+    /// Faintest star that shows through the sky glow
+    pub limiting_magnitude: f32,
+}
+
+impl Default for Twilight {
+    fn default() -> Self {
+        Twilight {
+            sun_altitude: -90.0,
+            limiting_magnitude: FAINTEST_MAGNITUDE,
+        }
+    }
+}
+
+impl Twilight {
+    pub fn phase(&self) -> &'static str {
+        match self.sun_altitude {
+            a if a >= 0.0 => "Day",
+            a if a >= -6.0 => "Civil twilight",
+            a if a >= -12.0 => "Nautical twilight",
+            a if a >= -18.0 => "Astronomical twilight",
+            _ => "Night",
+        }
+    }
+}
+
+pub struct TwilightPlugin;
+impl Plugin for TwilightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Twilight>()
+            .add_systems(Update, update_twilight);
+    }
+}
+
+/// Low-precision solar RA/Dec (radians), good to about a hundredth of a degree
+pub fn sun_equatorial(time: DateTime<Utc>) -> (f64, f64) {
+    let n = julian_date(time) - 2451545.0;
+    let mean_longitude = 280.460 + 0.9856474 * n;
+    let g = (357.528 + 0.9856003 * n).to_radians();
+    let ecliptic_longitude =
+        (mean_longitude + 1.915 * g.sin() + 0.020 * (2.0 * g).sin()).to_radians();
+    let obliquity = (23.439 - 0.0000004 * n).to_radians();
+
+    let ra = (obliquity.cos() * ecliptic_longitude.sin()).atan2(ecliptic_longitude.cos());
+    let dec = (obliquity.sin() * ecliptic_longitude.sin()).asin();
+    (ra.rem_euclid(2.0 * std::f64::consts::PI), dec)
+}
+
+/// Piecewise-linear lookup, clamped at both ends
+fn interpolate<const N: usize>(table: &[(f64, f32); N], x: f64) -> f32 {
+    let Some(i) = table.iter().position(|&(at, _)| at > x) else {
+        return table[N - 1].1;
+    };
+    if i == 0 {
+        return table[0].1;
+    }
+    let ((x0, y0), (x1, y1)) = (table[i - 1], table[i]);
+    y0 + (y1 - y0) * ((x - x0) / (x1 - x0)) as f32
+}
+
+/// Each frame: follow the sun, tint the sky and set how many stars show through
+fn update_twilight(
+    state: Res<StarfieldState>,
+    mut twilight: ResMut<Twilight>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    let now = state.current_utc();
+    let (ra, dec) = sun_equatorial(now);
+    let (sun_altitude, _) = horizontal_coordinates(now, state.lat_deg, state.lon_deg, ra, dec);
+
+    let channel = |c: usize| {
+        let table = SKY_COLORS.map(|(at, rgb)| (at, rgb[c]));
+        interpolate(&table, sun_altitude)
+    };
+    clear_color.0 = Color::linear_rgb(channel(0), channel(1), channel(2));
+
+    // The altitude is read on demand; only a visible change in the limit marks
+    // the resource changed, so star visibility isn't recomputed every frame
+    twilight.bypass_change_detection().sun_altitude = sun_altitude;
+    let limiting_magnitude = interpolate(&SKY_LIMITS, sun_altitude);
+    if (limiting_magnitude - twilight.limiting_magnitude).abs() > 0.05 {
+        twilight.limiting_magnitude = limiting_magnitude;
+    }
+}
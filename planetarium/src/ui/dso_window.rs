@@ -0,0 +1,325 @@
+use crate::camera::{CameraFollow, CameraTarget};
+use crate::colors;
+use crate::dso_catalog::{DsoCatalog, DsoEntry};
+use crate::dso_markers::SelectedDso;
+use crate::observing_list::{ObservingItem, ObservingList};
+use crate::starfield::{horizontal_coordinates, StarfieldState};
+use crate::ui::widgets::{
+    content_container_frame, planetarium_button, planetarium_text_input, window_frame,
+};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Rows listed at once; narrow the filter to find anything past this
+const MAX_ROWS: usize = 200;
+
+#[derive(Resource, Default)]
+pub struct DsoSearchState {
+    pub query: String,
+    pub above_horizon_only: bool,
+}
+
+pub fn render_dso_window(
+    mut search_state: ResMut<DsoSearchState>,
+    catalog: Res<DsoCatalog>,
+    starfield_state: Res<StarfieldState>,
+    mut follow: ResMut<CameraFollow>,
+    mut list: ResMut<ObservingList>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.dso_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    let now = starfield_state.current_utc();
+    let objects: Vec<(&DsoEntry, f64, f64)> = catalog
+        .objects
+        .iter()
+        .filter(|o| o.matches(&search_state.query))
+        .map(|o| {
+            let (alt, az) = horizontal_coordinates(
+                now,
+                starfield_state.lat_deg,
+                starfield_state.lon_deg,
+                o.ra,
+                o.dec,
+            );
+            (o, alt, az)
+        })
+        .filter(|&(_, alt, _)| !search_state.above_horizon_only || alt > 0.0)
+        .collect();
+
+    egui::Window::new(
+        egui::RichText::new("DSO Tracking")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(true)
+    .default_size([520.0, 700.0])
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.dso_window_open)
+    .show(ctx, |ui| {
+        ui.vertical(|ui| {
+            ui.set_width(ui.available_width());
+
+            // Filter section container
+            let w = ui.available_width();
+            ui.allocate_ui(egui::Vec2::new(w, 0.0), |ui| {
+                content_container_frame().show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(
+                        egui::RichText::new("Search Deep-Sky Objects")
+                            .size(14.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+                    let text_input_height = 22.0;
+                    ui.horizontal(|ui| {
+                        ui.add_sized(
+                            egui::vec2(0.0, text_input_height),
+                            egui::Label::new(
+                                egui::RichText::new("Name:")
+                                    .size(12.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                            ),
+                        );
+                        planetarium_text_input(
+                            ui,
+                            &mut search_state.query,
+                            200.0,
+                            text_input_height,
+                        );
+                        ui.checkbox(
+                            &mut search_state.above_horizon_only,
+                            egui::RichText::new("Above horizon")
+                                .size(12.0)
+                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                        );
+                    });
+
+                    if follow.tracking && follow.target.is_some() {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new("Tracking target")
+                                    .size(12.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                            );
+                            if planetarium_button(ui, "Stop", 60.0, 20.0).clicked() {
+                                follow.stop();
+                            }
+                        });
+                    }
+                });
+            });
+
+            // Results section container
+            let w = ui.available_width();
+            ui.allocate_ui(egui::Vec2::new(w, 0.0), |ui| {
+                content_container_frame().show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(
+                        egui::RichText::new(if objects.len() > MAX_ROWS {
+                            format!("{} objects (showing first {MAX_ROWS})", objects.len())
+                        } else {
+                            format!("{} objects", objects.len())
+                        })
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+
+                    let scroll_height = ui.available_height().max(140.0);
+                    ui.allocate_ui_with_layout(
+                        egui::vec2(ui.available_width(), scroll_height),
+                        egui::Layout::top_down(egui::Align::LEFT),
+                        |ui| {
+                            egui::ScrollArea::vertical()
+                                .auto_shrink([false, false])
+                                .show(ui, |ui| {
+                                    // Reserve space for scrollbar
+                                    ui.set_width(ui.available_width() - 20.0);
+                                    egui::Grid::new("dso_table")
+                                        .spacing(egui::vec2(8.0, 4.0))
+                                        .show(ui, |ui| {
+                                            for header in
+                                                ["Name", "Type", "Mag", "Const", "Alt", "Az", ""]
+                                            {
+                                                ui.strong(
+                                                    egui::RichText::new(header)
+                                                        .size(12.0)
+                                                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                                                );
+                                            }
+                                            ui.end_row();
+
+                                            for &(object, alt, az) in objects.iter().take(MAX_ROWS)
+                                            {
+                                                if let Some(item) =
+                                                    dso_row(ui, object, alt, az, &mut follow, &list)
+                                                {
+                                                    list.add(item);
+                                                }
+                                            }
+                                        });
+                                });
+                        },
+                    );
+                });
+            });
+        });
+    });
+}
+
+/// One table row; returns the object to put on the observing list if asked
+fn dso_row(
+    ui: &mut egui::Ui,
+    object: &DsoEntry,
+    alt: f64,
+    az: f64,
+    follow: &mut CameraFollow,
+    list: &ObservingList,
+) -> Option<ObservingItem> {
+    let name = match &object.common_name {
+        Some(common) => format!("{} ({common})", object.designation),
+        None => object.designation.clone(),
+    };
+    let magnitude = object
+        .magnitude
+        .map_or_else(|| "-".to_string(), |m| format!("{m:.1}"));
+    let cells = [
+        name,
+        object.kind.label().to_string(),
+        magnitude,
+        object.constellation.clone(),
+        format!("{alt:.1}°"),
+        format!("{az:.1}°"),
+    ];
+    for cell in cells {
+        ui.label(
+            egui::RichText::new(cell)
+                .size(12.0)
+                .color(colors::egui::WINDOW_TITLE_COLOR),
+        );
+    }
+
+    let target = CameraTarget::Equatorial {
+        ra: object.ra,
+        dec: object.dec,
+    };
+    let mut add = None;
+    ui.horizontal(|ui| {
+        if planetarium_button(ui, "Center", 60.0, 20.0).clicked() {
+            follow.center(target);
+        }
+        let tracking = follow.is_tracking(target);
+        let label = if tracking { "Stop" } else { "Track" };
+        if planetarium_button(ui, label, 60.0, 20.0).clicked() {
+            if tracking {
+                follow.stop();
+            } else {
+                follow.track(target);
+            }
+        }
+        let item = ObservingItem::Dso {
+            name: object.designation.clone(),
+            ra: object.ra,
+            dec: object.dec,
+        };
+        let listed = list.contains(&item);
+        ui.add_enabled_ui(!listed, |ui| {
+            let label = if listed { "Listed" } else { "+ List" };
+            if planetarium_button(ui, label, 60.0, 20.0).clicked() {
+                add = Some(item);
+            }
+        });
+    });
+    ui.end_row();
+    add
+}
+
+/// Details of the deep-sky marker clicked in the sky, with Center/Track
+pub fn render_selected_dso(
+    mut selected: ResMut<SelectedDso>,
+    catalog: Res<DsoCatalog>,
+    starfield_state: Res<StarfieldState>,
+    mut follow: ResMut<CameraFollow>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    let Some(object) = selected.0.and_then(|i| catalog.objects.get(i)) else {
+        return;
+    };
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    let (alt, az) = horizontal_coordinates(
+        starfield_state.current_utc(),
+        starfield_state.lat_deg,
+        starfield_state.lon_deg,
+        object.ra,
+        object.dec,
+    );
+    let mut lines = vec![object.kind.label().to_string()];
+    lines.extend(object.common_name.clone());
+    if let Some(magnitude) = object.magnitude {
+        lines.push(format!("Magnitude {magnitude:.1}"));
+    }
+    if let Some(size) = object.size_arcmin {
+        lines.push(format!("Size {size:.1}'"));
+    }
+    if !object.constellation.is_empty() {
+        lines.push(format!("Constellation {}", object.constellation));
+    }
+    lines.push(format!("Alt {alt:.1}°  Az {az:.1}°"));
+
+    let mut open = true;
+    egui::Window::new(
+        egui::RichText::new(&object.designation)
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .id(egui::Id::new("selected_dso_window"))
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            for line in lines {
+                ui.label(
+                    egui::RichText::new(line)
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+
+            let target = CameraTarget::Equatorial {
+                ra: object.ra,
+                dec: object.dec,
+            };
+            ui.horizontal(|ui| {
+                if planetarium_button(ui, "Center", 60.0, 20.0).clicked() {
+                    follow.center(target);
+                }
+                let tracking = follow.is_tracking(target);
+                let label = if tracking { "Stop" } else { "Track" };
+                if planetarium_button(ui, label, 60.0, 20.0).clicked() {
+                    if tracking {
+                        follow.stop();
+                    } else {
+                        follow.track(target);
+                    }
+                }
+            });
+        });
+    });
+    if !open {
+        selected.0 = None;
+    }
+}
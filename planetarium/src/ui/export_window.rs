@@ -0,0 +1,121 @@
+use crate::colors;
+use crate::events::PlanetariumEvent;
+use crate::export::{ExportRequest, MAX_EXPORT_SIZE};
+use crate::ui::widgets::{
+    content_container_frame, field_label, planetarium_button, planetarium_text_input, window_frame,
+};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::path::PathBuf;
+
+#[derive(Resource)]
+pub struct ExportWindowState {
+    pub width: String,
+    pub height: String,
+    /// Output file; left empty for a timestamped name
+    pub path: String,
+    pub include_pass: bool,
+    pub include_timestamp: bool,
+    pub error: Option<String>,
+}
+
+impl Default for ExportWindowState {
+    fn default() -> Self {
+        ExportWindowState {
+            width: "3840".to_string(),
+            height: "2160".to_string(),
+            path: String::new(),
+            include_pass: true,
+            include_timestamp: true,
+            error: None,
+        }
+    }
+}
+
+fn parse_size(text: &str, what: &str) -> Result<u32, String> {
+    match text.trim().parse::<u32>() {
+        Ok(value) if (1..=MAX_EXPORT_SIZE).contains(&value) => Ok(value),
+        _ => Err(format!(
+            "{what} must be between 1 and {MAX_EXPORT_SIZE} pixels"
+        )),
+    }
+}
+
+fn export_request(state: &ExportWindowState) -> Result<ExportRequest, String> {
+    let path = state.path.trim();
+    Ok(ExportRequest {
+        path: (!path.is_empty()).then(|| PathBuf::from(path)),
+        width: parse_size(&state.width, "Width")?,
+        height: parse_size(&state.height, "Height")?,
+        include_pass: state.include_pass,
+        include_timestamp: state.include_timestamp,
+        view: None,
+        reply: None,
+    })
+}
+
+pub fn render_export_window(
+    mut export_state: ResMut<ExportWindowState>,
+    mut events: MessageWriter<PlanetariumEvent>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.export_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    let mut exported = false;
+    egui::Window::new(
+        egui::RichText::new("Export View")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.export_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                field_label(ui, "Size (px):", 80.0);
+                planetarium_text_input(ui, &mut export_state.width, 60.0, 22.0);
+                planetarium_text_input(ui, &mut export_state.height, 60.0, 22.0);
+            });
+            ui.horizontal(|ui| {
+                field_label(ui, "File:", 80.0);
+                planetarium_text_input(ui, &mut export_state.path, 200.0, 22.0);
+            });
+
+            ui.checkbox(&mut export_state.include_pass, "Selected pass");
+            ui.checkbox(&mut export_state.include_timestamp, "Timestamp");
+
+            if planetarium_button(ui, "Export", 80.0, 22.0).clicked() {
+                match export_request(&export_state) {
+                    Ok(request) => {
+                        events.write(PlanetariumEvent::ExportView(request));
+                        export_state.error = None;
+                        exported = true;
+                    }
+                    Err(e) => export_state.error = Some(e),
+                }
+            }
+
+            if let Some(error) = &export_state.error {
+                ui.label(
+                    egui::RichText::new(format!("Error: {}", error))
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+        });
+    });
+
+    if exported {
+        menu_state.export_window_open = false;
+    }
+}
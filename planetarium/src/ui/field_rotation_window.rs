@@ -0,0 +1,150 @@
+use crate::colors;
+use crate::field_rotation::{accumulated_rotation, field_rotation_rate};
+use crate::starfield::{horizontal_coordinates, StarfieldState};
+use crate::target::{MountPosition, SelectedTarget};
+use crate::ui::widgets::{content_container_frame, planetarium_text_input, window_frame};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+#[derive(Resource)]
+pub struct FieldRotationWindowState {
+    /// Planned exposure length in seconds
+    pub exposure: String,
+}
+
+impl Default for FieldRotationWindowState {
+    fn default() -> Self {
+        FieldRotationWindowState {
+            exposure: "300".to_string(),
+        }
+    }
+}
+
+/// Table cells for one pointing: alt, az, rate and rotation over the exposure
+fn rotation_cells(
+    state: &StarfieldState,
+    position: Option<(f64, f64)>,
+    exposure: Option<f64>,
+) -> [String; 4] {
+    let Some((ra, dec)) = position else {
+        return ["-", "-", "-", "-"].map(String::from);
+    };
+    let (alt, az) =
+        horizontal_coordinates(state.current_utc(), state.lat_deg, state.lon_deg, ra, dec);
+    if alt < 0.0 {
+        return [
+            format!("{alt:.1}°"),
+            format!("{az:.1}°"),
+            "-".into(),
+            "-".into(),
+        ];
+    }
+    let rate = field_rotation_rate(state.lat_deg, alt, az) * 60.0;
+    let total = exposure.map_or_else(
+        || "-".to_string(),
+        |seconds| {
+            format!(
+                "{:.2}°",
+                accumulated_rotation(state.lat_deg, alt, az, seconds)
+            )
+        },
+    );
+    [
+        format!("{alt:.1}°"),
+        format!("{az:.1}°"),
+        format!("{rate:.3}°/min"),
+        total,
+    ]
+}
+
+pub fn render_field_rotation_window(
+    mut rotation_state: ResMut<FieldRotationWindowState>,
+    state: Res<StarfieldState>,
+    mount: Res<MountPosition>,
+    selected: Res<SelectedTarget>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.field_rotation_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    egui::Window::new(
+        egui::RichText::new("Field Rotation")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.field_rotation_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new("Exposure (s):")
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+                planetarium_text_input(ui, &mut rotation_state.exposure, 80.0, 22.0);
+            });
+            let exposure = rotation_state
+                .exposure
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|seconds| *seconds > 0.0);
+
+            egui::Grid::new("field_rotation_table")
+                .spacing(egui::vec2(8.0, 4.0))
+                .show(ui, |ui| {
+                    for header in ["", "Alt", "Az", "Rate", "Exposure"] {
+                        ui.strong(
+                            egui::RichText::new(header)
+                                .size(12.0)
+                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                        );
+                    }
+                    ui.end_row();
+
+                    // The mount is what actually turns under the camera; the
+                    // target is where it is headed next
+                    for (name, position) in [("Mount", mount.0), ("Target", selected.0)] {
+                        ui.label(
+                            egui::RichText::new(name)
+                                .size(12.0)
+                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                        );
+                        for cell in rotation_cells(&state, position, exposure) {
+                            ui.label(
+                                egui::RichText::new(cell)
+                                    .size(12.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                            );
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            if exposure.is_none() {
+                ui.label(
+                    egui::RichText::new("Error: Exposure must be a positive number")
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+            if mount.0.is_none() && selected.0.is_none() {
+                ui.label(
+                    egui::RichText::new("Waiting for a mount position from Sidereal")
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+        });
+    });
+}
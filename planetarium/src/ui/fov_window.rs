@@ -0,0 +1,142 @@
+use crate::colors;
+use crate::fov_overlay::FovOverlay;
+use crate::ui::widgets::{
+    content_container_frame, field_label, planetarium_button, planetarium_text_input, window_frame,
+};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+#[derive(Resource)]
+pub struct FovWindowState {
+    pub focal_length: String,
+    pub sensor_width: String,
+    pub sensor_height: String,
+    pub width_arcmin: String,
+    pub height_arcmin: String,
+    pub error: Option<String>,
+}
+
+impl Default for FovWindowState {
+    fn default() -> Self {
+        let overlay = FovOverlay::default();
+        FovWindowState {
+            focal_length: "600".to_string(),
+            sensor_width: "23.5".to_string(),
+            sensor_height: "15.6".to_string(),
+            width_arcmin: format!("{:.1}", overlay.width_arcmin),
+            height_arcmin: format!("{:.1}", overlay.height_arcmin),
+            error: None,
+        }
+    }
+}
+
+/// Parse a positive number from a text field
+fn parse_positive(text: &str, what: &str) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(value) if value > 0.0 => Ok(value),
+        _ => Err(format!("{what} must be a positive number")),
+    }
+}
+
+fn sensor_fov(state: &FovWindowState) -> Result<(f64, f64), String> {
+    Ok(FovOverlay::sensor_fov(
+        parse_positive(&state.focal_length, "Focal length")?,
+        parse_positive(&state.sensor_width, "Sensor width")?,
+        parse_positive(&state.sensor_height, "Sensor height")?,
+    ))
+}
+
+fn direct_fov(state: &FovWindowState) -> Result<(f64, f64), String> {
+    Ok((
+        parse_positive(&state.width_arcmin, "FOV width")?,
+        parse_positive(&state.height_arcmin, "FOV height")?,
+    ))
+}
+
+pub fn render_fov_window(
+    mut fov_state: ResMut<FovWindowState>,
+    mut overlay: ResMut<FovOverlay>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.fov_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    egui::Window::new(
+        egui::RichText::new("Sensor FOV")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.fov_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            let label = if overlay.visible {
+                "Hide Overlay"
+            } else {
+                "Show Overlay"
+            };
+            if planetarium_button(ui, label, 120.0, 22.0).clicked() {
+                overlay.visible = !overlay.visible;
+            }
+
+            // From the optics
+            ui.horizontal(|ui| {
+                field_label(ui, "Focal length (mm):", 110.0);
+                planetarium_text_input(ui, &mut fov_state.focal_length, 80.0, 22.0);
+            });
+            ui.horizontal(|ui| {
+                field_label(ui, "Sensor (mm):", 110.0);
+                planetarium_text_input(ui, &mut fov_state.sensor_width, 50.0, 22.0);
+                planetarium_text_input(ui, &mut fov_state.sensor_height, 50.0, 22.0);
+                if planetarium_button(ui, "Apply", 60.0, 22.0).clicked() {
+                    match sensor_fov(&fov_state) {
+                        Ok((width, height)) => {
+                            overlay.width_arcmin = width;
+                            overlay.height_arcmin = height;
+                            overlay.visible = true;
+                            fov_state.width_arcmin = format!("{width:.1}");
+                            fov_state.height_arcmin = format!("{height:.1}");
+                            fov_state.error = None;
+                        }
+                        Err(e) => fov_state.error = Some(e),
+                    }
+                }
+            });
+
+            // Or straight in arcminutes
+            ui.horizontal(|ui| {
+                field_label(ui, "FOV (arcmin):", 110.0);
+                planetarium_text_input(ui, &mut fov_state.width_arcmin, 50.0, 22.0);
+                planetarium_text_input(ui, &mut fov_state.height_arcmin, 50.0, 22.0);
+                if planetarium_button(ui, "Apply", 60.0, 22.0).clicked() {
+                    match direct_fov(&fov_state) {
+                        Ok((width, height)) => {
+                            overlay.width_arcmin = width;
+                            overlay.height_arcmin = height;
+                            overlay.visible = true;
+                            fov_state.error = None;
+                        }
+                        Err(e) => fov_state.error = Some(e),
+                    }
+                }
+            });
+
+            if let Some(error) = &fov_state.error {
+                ui.label(
+                    egui::RichText::new(format!("Error: {}", error))
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+        });
+    });
+}
@@ -1,10 +1,37 @@
+mod dso_window;
+mod export_window;
+mod field_rotation_window;
+mod fov_window;
+mod object_search;
+mod observing_list_window;
+mod planet_window;
 mod satellite_window;
+mod settings_window;
+mod shortcuts_window;
+mod slew_menu;
+mod small_body_window;
+mod status_bar;
+mod time_window;
 mod widgets;
 
+pub(crate) use object_search::find_objects;
+pub(crate) use satellite_window::TimeDisplay;
+
+use crate::all_satellites::AllSatellites;
+use crate::all_sky::AllSkyView;
+use crate::camera::{CameraFollow, CameraTarget};
+use crate::colors;
+use crate::dso_catalog::DsoCatalog;
+use crate::faint_stars::FAINTEST_MAGNITUDE;
+use crate::gui_link::GuiLinkStatus;
+use crate::measure::MeasureTool;
+use crate::night_vision::NightVision;
+use crate::starfield::SkyLayers;
+use crate::target::Marker;
 use crate::ui::widgets::planetarium_buttons::planetarium_menu_button_inner;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContext};
-use widgets::planetarium_menu_button;
+use widgets::{planetarium_button, planetarium_menu_button, planetarium_text_input};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MenuAction {
@@ -16,6 +43,16 @@ pub enum MenuAction {
 #[derive(Resource, Default)]
 pub struct MenuState {
     pub satellite_window_open: bool,
+    pub dso_window_open: bool,
+    pub planet_window_open: bool,
+    pub small_body_window_open: bool,
+    pub observing_list_window_open: bool,
+    pub time_window_open: bool,
+    pub fov_window_open: bool,
+    pub field_rotation_window_open: bool,
+    pub export_window_open: bool,
+    pub settings_window_open: bool,
+    pub shortcuts_window_open: bool,
 }
 
 pub struct MenuPlugin;
@@ -26,11 +63,45 @@ struct FontsConfigured(bool);
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<MenuState>()
+            .init_resource::<TimeDisplay>()
             .init_resource::<satellite_window::SatelliteSearchState>()
             .init_resource::<satellite_window::SearchResultChannel>()
+            .init_resource::<dso_window::DsoSearchState>()
+            .init_resource::<time_window::TimeControlState>()
+            .init_resource::<fov_window::FovWindowState>()
+            .init_resource::<field_rotation_window::FieldRotationWindowState>()
+            .init_resource::<export_window::ExportWindowState>()
+            .init_resource::<settings_window::SettingsWindowState>()
+            .init_resource::<slew_menu::SlewMenuState>()
+            .init_resource::<small_body_window::SmallBodyWindowState>()
+            .init_resource::<small_body_window::SmallBodyFetchChannel>()
+            .init_resource::<object_search::ObjectSearchState>()
+            .init_resource::<observing_list_window::ObservingListWindowState>()
+            .insert_resource(DsoCatalog::load())
             .init_resource::<FontsConfigured>()
-            .add_systems(Update, (setup_egui_fonts, render_menu_bar).chain())
-            .add_systems(Update, satellite_window::render_satellite_window);
+            .add_systems(
+                Update,
+                (
+                    setup_egui_fonts,
+                    render_menu_bar,
+                    status_bar::render_status_bar,
+                    object_search::render_object_search,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, satellite_window::render_satellite_window)
+            .add_systems(Update, dso_window::render_dso_window)
+            .add_systems(Update, dso_window::render_selected_dso)
+            .add_systems(Update, planet_window::render_planet_window)
+            .add_systems(Update, small_body_window::render_small_body_window)
+            .add_systems(Update, observing_list_window::render_observing_list_window)
+            .add_systems(Update, time_window::render_time_window)
+            .add_systems(Update, fov_window::render_fov_window)
+            .add_systems(Update, field_rotation_window::render_field_rotation_window)
+            .add_systems(Update, export_window::render_export_window)
+            .add_systems(Update, settings_window::render_settings_window)
+            .add_systems(Update, shortcuts_window::render_shortcuts_window)
+            .add_systems(Update, slew_menu::render_slew_menu);
     }
 }
 
@@ -102,22 +173,89 @@ fn configure_segoe_ui_font(ctx: &egui::Context) {
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_menu_bar(
     mut menu_state: ResMut<MenuState>,
+    mut all_sky: ResMut<AllSkyView>,
+    mut layers: ResMut<SkyLayers>,
+    mut all_satellites: ResMut<AllSatellites>,
+    mut night_vision: ResMut<NightVision>,
+    mut measure: ResMut<MeasureTool>,
+    mut follow: ResMut<CameraFollow>,
+    gui_link: Res<GuiLinkStatus>,
+    markers: Query<(Entity, &Marker)>,
     mut camera_query: Query<&mut EguiContext, With<Camera3d>>,
 ) {
+    // The mount reticle only exists once the GUI has reported a position
+    let mount = markers
+        .iter()
+        .find(|(_, m)| matches!(m, Marker::MountTargetMarker))
+        .map(|(entity, _)| entity);
+
     // Query for the camera with EguiContext directly
     if let Ok(mut egui_context) = camera_query.single_mut() {
         let ctx = egui_context.get_mut();
-        render_ui(ctx, &mut menu_state);
+        render_ui(
+            ctx,
+            &mut menu_state,
+            &mut all_sky,
+            &mut layers,
+            &mut all_satellites,
+            &mut night_vision,
+            &mut measure,
+            &mut follow,
+            gui_link.connected,
+            mount,
+        );
     }
 }
 
+/// Name prefix the All Satellites layer is limited to, with how it's loading
+fn all_satellites_group(ui: &mut egui::Ui, all_satellites: &mut ResMut<AllSatellites>) {
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new("Group")
+                .size(12.0)
+                .color(colors::egui::WINDOW_TITLE_COLOR),
+        );
+        planetarium_text_input(ui, &mut all_satellites.group, 100.0, 20.0);
+        if planetarium_button(ui, "Apply", 50.0, 20.0).clicked() {
+            all_satellites.request_reload();
+        }
+    });
+
+    let status = if all_satellites.is_loading() {
+        "Loading satellites...".to_string()
+    } else if let Some(error) = &all_satellites.error {
+        format!("Error: {error}")
+    } else {
+        let (loaded, above) = all_satellites.counts();
+        format!("{above} of {loaded} above the horizon")
+    };
+    ui.label(
+        egui::RichText::new(status)
+            .size(12.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    );
+}
+
 // In your menu items, close like this:
 fn close_popup(ui: &mut egui::Ui, id: egui::Id) {
     egui::Popup::close_id(ui.ctx(), id);
 }
-fn render_ui(ctx: &mut egui::Context, menu_state: &mut ResMut<MenuState>) {
+#[allow(clippy::too_many_arguments)]
+fn render_ui(
+    ctx: &mut egui::Context,
+    menu_state: &mut ResMut<MenuState>,
+    all_sky: &mut ResMut<AllSkyView>,
+    layers: &mut ResMut<SkyLayers>,
+    all_satellites: &mut ResMut<AllSatellites>,
+    night_vision: &mut ResMut<NightVision>,
+    measure: &mut ResMut<MeasureTool>,
+    follow: &mut ResMut<CameraFollow>,
+    gui_connected: bool,
+    mount: Option<Entity>,
+) {
     let menu_id = egui::Id::new("track_menu");
     let hover_id = egui::Id::new("track_button_hover");
 
@@ -139,7 +277,7 @@ fn render_ui(ctx: &mut egui::Context, menu_state: &mut ResMut<MenuState>) {
                     // DSO button
                     let dso_hover_id = egui::Id::new("dso_button_hover");
                     if planetarium_menu_button_inner(ui, dso_hover_id, "DSO", false).clicked() {
-                        // TODO: Implement DSO tracking
+                        menu_state.dso_window_open = true;
                         egui::Popup::close_id(ui.ctx(), menu_id);
                     }
 
@@ -147,9 +285,332 @@ fn render_ui(ctx: &mut egui::Context, menu_state: &mut ResMut<MenuState>) {
                     let planet_hover_id = egui::Id::new("planet_button_hover");
                     if planetarium_menu_button_inner(ui, planet_hover_id, "Planet", false).clicked()
                     {
-                        // TODO: Implement planet tracking
+                        menu_state.planet_window_open = true;
+                        egui::Popup::close_id(ui.ctx(), menu_id);
+                    }
+
+                    // Comet and asteroid button
+                    let comet_hover_id = egui::Id::new("comet_button_hover");
+                    if planetarium_menu_button_inner(ui, comet_hover_id, "Comet/Asteroid", false)
+                        .clicked()
+                    {
+                        menu_state.small_body_window_open = true;
+                        egui::Popup::close_id(ui.ctx(), menu_id);
+                    }
+
+                    // Everything added from the windows above, in one place
+                    let list_hover_id = egui::Id::new("observing_list_button_hover");
+                    if planetarium_menu_button_inner(ui, list_hover_id, "Observing List", false)
+                        .clicked()
+                    {
+                        menu_state.observing_list_window_open = true;
+                        egui::Popup::close_id(ui.ctx(), menu_id);
+                    }
+                });
+
+                // Time control window
+                let time_hover_id = egui::Id::new("time_button_hover");
+                if planetarium_menu_button_inner(
+                    ui,
+                    time_hover_id,
+                    "Time",
+                    menu_state.time_window_open,
+                )
+                .clicked()
+                {
+                    menu_state.time_window_open = !menu_state.time_window_open;
+                }
+
+                // Click two points for their separation and position angle
+                let measure_hover_id = egui::Id::new("measure_button_hover");
+                if planetarium_menu_button_inner(ui, measure_hover_id, "Measure", measure.active)
+                    .clicked()
+                {
+                    measure.toggle();
+                }
+
+                // Save the view to an image
+                let export_hover_id = egui::Id::new("export_button_hover");
+                if planetarium_menu_button_inner(
+                    ui,
+                    export_hover_id,
+                    "Export",
+                    menu_state.export_window_open,
+                )
+                .clicked()
+                {
+                    menu_state.export_window_open = !menu_state.export_window_open;
+                }
+
+                // Observer site
+                let settings_hover_id = egui::Id::new("settings_button_hover");
+                if planetarium_menu_button_inner(
+                    ui,
+                    settings_hover_id,
+                    "Settings",
+                    menu_state.settings_window_open,
+                )
+                .clicked()
+                {
+                    menu_state.settings_window_open = !menu_state.settings_window_open;
+                }
+
+                // Keyboard shortcut cheat sheet
+                let keys_hover_id = egui::Id::new("keys_button_hover");
+                if planetarium_menu_button_inner(
+                    ui,
+                    keys_hover_id,
+                    "Keys",
+                    menu_state.shortcuts_window_open,
+                )
+                .clicked()
+                {
+                    menu_state.shortcuts_window_open = !menu_state.shortcuts_window_open;
+                }
+
+                // Keep the live mount reticle centered
+                if let Some(mount) = mount {
+                    let target = CameraTarget::Entity(mount);
+                    let following = follow.is_tracking(target);
+                    let follow_hover_id = egui::Id::new("follow_mount_button_hover");
+                    if planetarium_menu_button_inner(ui, follow_hover_id, "Follow Mount", following)
+                        .clicked()
+                    {
+                        if following {
+                            follow.stop();
+                        } else {
+                            follow.track(target);
+                        }
+                    }
+                }
+
+                // Layer toggles; a highlighted entry is currently shown
+                let view_menu_id = egui::Id::new("view_menu");
+                let view_hover_id = egui::Id::new("view_button_hover");
+                planetarium_menu_button(ui, view_menu_id, view_hover_id, "View", |ui, menu_id| {
+                    // Zenith-centered circle of the whole sky, like the all-sky camera
+                    let all_sky_hover_id = egui::Id::new("all_sky_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        all_sky_hover_id,
+                        "All-Sky View",
+                        all_sky.active,
+                    )
+                    .clicked()
+                    {
+                        all_sky.active = !all_sky.active;
+                    }
+
+                    let lines_hover_id = egui::Id::new("constellation_lines_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        lines_hover_id,
+                        "Constellation Lines",
+                        layers.constellation_lines,
+                    )
+                    .clicked()
+                    {
+                        layers.constellation_lines = !layers.constellation_lines;
+                    }
+
+                    let milky_way_hover_id = egui::Id::new("milky_way_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        milky_way_hover_id,
+                        "Milky Way",
+                        layers.milky_way,
+                    )
+                    .clicked()
+                    {
+                        layers.milky_way = !layers.milky_way;
+                    }
+
+                    let labels_hover_id = egui::Id::new("labels_button_hover");
+                    if planetarium_menu_button_inner(ui, labels_hover_id, "Labels", layers.labels)
+                        .clicked()
+                    {
+                        layers.labels = !layers.labels;
+                    }
+
+                    let dso_markers_hover_id = egui::Id::new("dso_markers_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        dso_markers_hover_id,
+                        "Deep-Sky Objects",
+                        layers.dso_markers,
+                    )
+                    .clicked()
+                    {
+                        layers.dso_markers = !layers.dso_markers;
+                    }
+
+                    let cardinal_hover_id = egui::Id::new("cardinal_points_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        cardinal_hover_id,
+                        "Cardinal Points",
+                        layers.cardinal_points,
+                    )
+                    .clicked()
+                    {
+                        layers.cardinal_points = !layers.cardinal_points;
+                    }
+
+                    let meridian_hover_id = egui::Id::new("meridian_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        meridian_hover_id,
+                        "Meridian",
+                        layers.meridian,
+                    )
+                    .clicked()
+                    {
+                        layers.meridian = !layers.meridian;
+                    }
+
+                    let limits_hover_id = egui::Id::new("slew_limits_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        limits_hover_id,
+                        "Slew Limits",
+                        layers.slew_limits,
+                    )
+                    .clicked()
+                    {
+                        layers.slew_limits = !layers.slew_limits;
+                    }
+
+                    let brightness_hover_id = egui::Id::new("sky_brightness_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        brightness_hover_id,
+                        "Sky Brightness",
+                        layers.sky_brightness,
+                    )
+                    .clicked()
+                    {
+                        layers.sky_brightness = !layers.sky_brightness;
+                    }
+
+                    let meteor_hover_id = egui::Id::new("meteor_radiants_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        meteor_hover_id,
+                        "Meteor Radiants",
+                        layers.meteor_radiants,
+                    )
+                    .clicked()
+                    {
+                        layers.meteor_radiants = !layers.meteor_radiants;
+                    }
+
+                    // Everything in orbit right now, to put a name to a moving dot
+                    let all_satellites_hover_id = egui::Id::new("all_satellites_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        all_satellites_hover_id,
+                        "All Satellites",
+                        layers.all_satellites,
+                    )
+                    .clicked()
+                    {
+                        layers.all_satellites = !layers.all_satellites;
+                    }
+                    if layers.all_satellites {
+                        all_satellites_group(ui, all_satellites);
+                    }
+
+                    // Red-only display that won't spoil dark adaptation
+                    let night_hover_id = egui::Id::new("night_vision_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        night_hover_id,
+                        "Night Vision",
+                        night_vision.0,
+                    )
+                    .clicked()
+                    {
+                        night_vision.0 = !night_vision.0;
+                    }
+
+                    let fov_hover_id = egui::Id::new("sensor_fov_button_hover");
+                    if planetarium_menu_button_inner(ui, fov_hover_id, "Sensor FOV...", false)
+                        .clicked()
+                    {
+                        menu_state.fov_window_open = true;
                         egui::Popup::close_id(ui.ctx(), menu_id);
                     }
+
+                    // How fast an alt-az mount's field turns at the target
+                    let rotation_hover_id = egui::Id::new("field_rotation_button_hover");
+                    if planetarium_menu_button_inner(
+                        ui,
+                        rotation_hover_id,
+                        "Field Rotation...",
+                        false,
+                    )
+                    .clicked()
+                    {
+                        menu_state.field_rotation_window_open = true;
+                        egui::Popup::close_id(ui.ctx(), menu_id);
+                    }
+
+                    // Faintest star drawn; lower it for naked-eye planning or a weak GPU
+                    ui.separator();
+                    ui.label(
+                        egui::RichText::new("Limiting Magnitude")
+                            .size(12.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+                    let mut limit = layers.limiting_magnitude;
+                    if ui
+                        .add(egui::Slider::new(&mut limit, 1.0..=FAINTEST_MAGNITUDE).step_by(0.1))
+                        .changed()
+                    {
+                        layers.limiting_magnitude = limit;
+                    }
+
+                    // Faintest named star that gets a label
+                    ui.label(
+                        egui::RichText::new("Label Magnitude")
+                            .size(12.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+                    let mut label_limit = layers.label_magnitude;
+                    if ui
+                        .add(egui::Slider::new(&mut label_limit, -1.5..=6.0).step_by(0.1))
+                        .changed()
+                    {
+                        layers.label_magnitude = label_limit;
+                    }
+
+                    // Faintest deep-sky object that gets a marker
+                    ui.label(
+                        egui::RichText::new("DSO Magnitude")
+                            .size(12.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+                    let mut dso_limit = layers.dso_magnitude;
+                    if ui
+                        .add(egui::Slider::new(&mut dso_limit, 4.0..=15.0).step_by(0.1))
+                        .changed()
+                    {
+                        layers.dso_magnitude = dso_limit;
+                    }
+                });
+
+                // Link to sidereal_gui, at the far end of the bar
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let (color, status) = if gui_connected {
+                        (colors::egui::GREEN_TEXT, "Connected to Sidereal")
+                    } else {
+                        (colors::egui::RED_TEXT, "Not connected to Sidereal")
+                    };
+                    let (rect, response) =
+                        ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                    ui.painter().circle_filled(rect.center(), 5.0, color);
+                    response.on_hover_text(status);
+                    ui.label(egui::RichText::new("GUI").size(12.0).color(color));
                 });
             });
         });
@@ -0,0 +1,156 @@
+use crate::camera::{CameraFollow, CameraTarget};
+use crate::colors;
+use crate::dso_catalog::DsoCatalog;
+use crate::planets::Planet;
+use crate::starfield::StarIndex;
+use crate::ui::widgets::{content_container_frame, planetarium_text_input};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Suggestions listed under the search box
+const MAX_SUGGESTIONS: usize = 8;
+
+const SEARCH_WIDTH: f32 = 200.0;
+
+#[derive(Resource, Default)]
+pub struct ObjectSearchState {
+    pub query: String,
+}
+
+/// One search hit: what to show and where to point the camera
+pub(crate) struct SearchHit {
+    pub label: String,
+    pub target: CameraTarget,
+}
+
+/// Search box at the right end of the menu bar. Matches planets, star names,
+/// HR numbers ("HR 2061") and deep-sky objects, and slews the camera to the pick.
+pub fn render_object_search(
+    mut search_state: ResMut<ObjectSearchState>,
+    mut follow: ResMut<CameraFollow>,
+    planets: Query<(Entity, &Planet)>,
+    stars: Res<StarIndex>,
+    dso_catalog: Res<DsoCatalog>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    // Sit on top of the menu bar, right-aligned
+    let response = egui::Area::new(egui::Id::new("object_search"))
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-6.0, 3.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            planetarium_text_input(ui, &mut search_state.query, SEARCH_WIDTH, 22.0)
+        })
+        .inner;
+
+    let query = search_state.query.trim();
+    if query.is_empty() {
+        return;
+    }
+    let hits = find_objects(query, &planets, &stars, &dso_catalog);
+
+    // Enter picks the best match
+    let submitted = response.lost_focus() && ctx.input(|i| i.key_pressed(egui::Key::Enter));
+    let mut picked = hits.first().map(|hit| hit.target).filter(|_| submitted);
+
+    egui::Area::new(egui::Id::new("object_search_results"))
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-6.0, 28.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            content_container_frame().show(ui, |ui| {
+                ui.set_width(SEARCH_WIDTH - 8.0);
+                if hits.is_empty() {
+                    ui.label(
+                        egui::RichText::new("No matches")
+                            .size(12.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+                }
+                for hit in &hits {
+                    let label = egui::RichText::new(&hit.label)
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR);
+                    if ui
+                        .add(egui::Label::new(label).sense(egui::Sense::click()))
+                        .clicked()
+                    {
+                        picked = Some(hit.target);
+                    }
+                }
+            });
+        });
+
+    if let Some(target) = picked {
+        follow.center(target);
+        search_state.query.clear();
+    }
+}
+
+/// Planets, stars and deep-sky objects matching `query`, best first
+pub(crate) fn find_objects(
+    query: &str,
+    planets: &Query<(Entity, &Planet)>,
+    stars: &StarIndex,
+    dso_catalog: &DsoCatalog,
+) -> Vec<SearchHit> {
+    let needle = query.to_lowercase();
+    let mut hits = Vec::new();
+
+    for (entity, planet) in planets {
+        if planet.body.name().to_lowercase().starts_with(&needle) {
+            hits.push(SearchHit {
+                label: planet.body.name().to_string(),
+                target: CameraTarget::Entity(entity),
+            });
+        }
+    }
+
+    // "HR 2061", "hr2061" or a bare catalog number
+    let number = needle.strip_prefix("hr").unwrap_or(&needle).trim();
+    if let Some((hr, &(ra, dec))) = number
+        .parse::<u32>()
+        .ok()
+        .and_then(|hr| Some((hr, stars.positions.get(&hr)?)))
+    {
+        hits.push(SearchHit {
+            label: format!("HR {hr}"),
+            target: CameraTarget::Equatorial { ra, dec },
+        });
+    }
+
+    for (name, hr) in &stars.names {
+        if !name.to_lowercase().contains(&needle) {
+            continue;
+        }
+        if let Some(&(ra, dec)) = stars.positions.get(hr) {
+            hits.push(SearchHit {
+                label: format!("{name} (HR {hr})"),
+                target: CameraTarget::Equatorial { ra, dec },
+            });
+        }
+    }
+
+    for dso in dso_catalog.objects.iter().filter(|dso| dso.matches(query)) {
+        if hits.len() >= MAX_SUGGESTIONS {
+            break;
+        }
+        let label = match &dso.common_name {
+            Some(name) => format!("{} - {}", dso.designation, name),
+            None => format!("{} ({})", dso.designation, dso.kind.label()),
+        };
+        hits.push(SearchHit {
+            label,
+            target: CameraTarget::Equatorial {
+                ra: dso.ra,
+                dec: dso.dec,
+            },
+        });
+    }
+
+    hits.truncate(MAX_SUGGESTIONS);
+    hits
+}
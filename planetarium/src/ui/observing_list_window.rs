@@ -0,0 +1,191 @@
+use crate::camera::{CameraFollow, CameraTarget};
+use crate::colors;
+use crate::observing_list::{fixed_transit, horizontal_transit, ObservingItem, ObservingList};
+use crate::planets::Planet;
+use crate::satellite::observer_location;
+use crate::starfield::{horizontal_coordinates, StarfieldState};
+use crate::ui::widgets::{content_container_frame, planetarium_button, window_frame};
+use crate::ui::TimeDisplay;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use chrono::{DateTime, Utc};
+use overpass_planner::Propagator;
+
+#[derive(Resource, Default)]
+pub struct ObservingListWindowState {
+    /// Highest in the sky first, rather than in the order added
+    pub best_first: bool,
+}
+
+/// Where one list item is right now
+struct ListRow {
+    index: usize,
+    /// (altitude, azimuth) in degrees, `None` when it can't be worked out
+    alt_az: Option<(f64, f64)>,
+    /// Nearest meridian transit; satellites don't have one worth showing
+    transit: Option<DateTime<Utc>>,
+    target: Option<CameraTarget>,
+}
+
+fn list_row(
+    index: usize,
+    item: &ObservingItem,
+    state: &StarfieldState,
+    planets: &Query<(Entity, &Planet)>,
+) -> ListRow {
+    let now = state.current_utc();
+    match item {
+        ObservingItem::Dso { ra, dec, .. } => ListRow {
+            index,
+            alt_az: Some(horizontal_coordinates(
+                now,
+                state.lat_deg,
+                state.lon_deg,
+                *ra,
+                *dec,
+            )),
+            transit: Some(fixed_transit(state, *ra)),
+            target: Some(CameraTarget::Equatorial { ra: *ra, dec: *dec }),
+        },
+        ObservingItem::Planet(body) => {
+            let planet = planets.iter().find(|(_, planet)| planet.body == *body);
+            let alt_az = planet.and_then(|(_, planet)| planet.altitude.zip(planet.azimuth));
+            ListRow {
+                index,
+                alt_az,
+                transit: alt_az.map(|(alt, az)| horizontal_transit(state, alt, az)),
+                target: planet.map(|(entity, _)| CameraTarget::Entity(entity)),
+            }
+        }
+        ObservingItem::Satellite { propagator, .. } => {
+            let alt_az = propagator
+                .as_ref()
+                .and_then(|sat| sat.alt_az(observer_location(state), now).ok());
+            ListRow {
+                index,
+                alt_az,
+                transit: None,
+                // it moves on, so only where it is now
+                target: alt_az
+                    .filter(|&(alt, _)| alt > 0.0)
+                    .map(|(altitude, azimuth)| CameraTarget::Horizontal { altitude, azimuth }),
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_observing_list_window(
+    mut window_state: ResMut<ObservingListWindowState>,
+    mut list: ResMut<ObservingList>,
+    starfield_state: Res<StarfieldState>,
+    planets: Query<(Entity, &Planet)>,
+    time_display: Res<TimeDisplay>,
+    mut follow: ResMut<CameraFollow>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.observing_list_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    let mut rows: Vec<ListRow> = list
+        .items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| list_row(index, item, &starfield_state, &planets))
+        .collect();
+    if window_state.best_first {
+        // below the horizon, or unknown, sinks to the bottom
+        let altitude = |row: &ListRow| row.alt_az.map_or(f64::MIN, |(alt, _)| alt);
+        rows.sort_by(|a, b| altitude(b).total_cmp(&altitude(a)));
+    }
+
+    let mut remove = None;
+    egui::Window::new(
+        egui::RichText::new("Observing List")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.observing_list_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            ui.checkbox(
+                &mut window_state.best_first,
+                egui::RichText::new("Highest first")
+                    .size(12.0)
+                    .color(colors::egui::WINDOW_TITLE_COLOR),
+            );
+
+            if rows.is_empty() {
+                ui.label(
+                    egui::RichText::new(
+                        "Nothing listed yet. Add objects with + List in the Track windows.",
+                    )
+                    .size(12.0)
+                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+                return;
+            }
+
+            egui::Grid::new("observing_list_table")
+                .spacing(egui::vec2(8.0, 4.0))
+                .show(ui, |ui| {
+                    for header in ["Name", "Type", "Alt", "Az", "Transit", ""] {
+                        ui.strong(
+                            egui::RichText::new(header)
+                                .size(12.0)
+                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                        );
+                    }
+                    ui.end_row();
+
+                    for row in &rows {
+                        let item = &list.items[row.index];
+                        for cell in [
+                            item.name().to_string(),
+                            item.kind().to_string(),
+                            row.alt_az
+                                .map_or_else(|| "-".to_string(), |(alt, _)| format!("{alt:.1}°")),
+                            row.alt_az
+                                .map_or_else(|| "-".to_string(), |(_, az)| format!("{az:.1}°")),
+                            row.transit
+                                .map_or_else(|| "-".to_string(), |t| time_display.time(t)),
+                        ] {
+                            ui.label(
+                                egui::RichText::new(cell)
+                                    .size(12.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                            );
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(row.target.is_some(), |ui| {
+                                if planetarium_button(ui, "Center", 60.0, 20.0).clicked() {
+                                    if let Some(target) = row.target {
+                                        follow.center(target);
+                                    }
+                                }
+                            });
+                            if planetarium_button(ui, "Remove", 60.0, 20.0).clicked() {
+                                remove = Some(row.index);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    });
+
+    if let Some(index) = remove {
+        list.items.remove(index);
+    }
+}
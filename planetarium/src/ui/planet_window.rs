@@ -0,0 +1,112 @@
+use crate::camera::{CameraFollow, CameraTarget};
+use crate::colors;
+use crate::observing_list::{ObservingItem, ObservingList};
+use crate::planets::{Planet, PlanetEphemerisError};
+use crate::ui::widgets::{content_container_frame, planetarium_button, window_frame};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+pub fn render_planet_window(
+    planets: Query<(Entity, &Planet)>,
+    ephemeris_error: Res<PlanetEphemerisError>,
+    mut follow: ResMut<CameraFollow>,
+    mut list: ResMut<ObservingList>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.planet_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    // Keep the list in order from the Sun regardless of spawn order
+    let mut rows: Vec<(Entity, &Planet)> = planets.iter().collect();
+    rows.sort_by_key(|(_, planet)| {
+        overpass_planner::CelestialBody::PLANETS
+            .iter()
+            .position(|&body| body == planet.body)
+    });
+
+    egui::Window::new(
+        egui::RichText::new("Planet Tracking")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.planet_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            if let Some(error) = &ephemeris_error.0 {
+                ui.label(
+                    egui::RichText::new(format!("Error: {}", error))
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+
+            egui::Grid::new("planets_table")
+                .spacing(egui::vec2(8.0, 4.0))
+                .show(ui, |ui| {
+                    for header in ["Planet", "Alt", "Az", ""] {
+                        ui.strong(
+                            egui::RichText::new(header)
+                                .size(12.0)
+                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                        );
+                    }
+                    ui.end_row();
+
+                    for (entity, planet) in rows {
+                        for cell in [
+                            planet.body.name().to_string(),
+                            format_angle(planet.altitude),
+                            format_angle(planet.azimuth),
+                        ] {
+                            ui.label(
+                                egui::RichText::new(cell)
+                                    .size(12.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                            );
+                        }
+
+                        let target = CameraTarget::Entity(entity);
+                        ui.horizontal(|ui| {
+                            ui.add_enabled_ui(planet.altitude.is_some(), |ui| {
+                                if planetarium_button(ui, "Center", 60.0, 20.0).clicked() {
+                                    follow.center(target);
+                                }
+                                let tracking = follow.is_tracking(target);
+                                let label = if tracking { "Stop" } else { "Track" };
+                                if planetarium_button(ui, label, 60.0, 20.0).clicked() {
+                                    if tracking {
+                                        follow.stop();
+                                    } else {
+                                        follow.track(target);
+                                    }
+                                }
+                            });
+                            let item = ObservingItem::Planet(planet.body);
+                            let listed = list.contains(&item);
+                            ui.add_enabled_ui(!listed, |ui| {
+                                let label = if listed { "Listed" } else { "+ List" };
+                                if planetarium_button(ui, label, 60.0, 20.0).clicked() {
+                                    list.add(item);
+                                }
+                            });
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    });
+}
+
+fn format_angle(angle: Option<f64>) -> String {
+    angle.map_or_else(|| "-".to_string(), |a| format!("{a:.1}°"))
+}
@@ -1,12 +1,61 @@
+use crate::camera::{CameraFollow, CameraTarget};
+use crate::client;
 use crate::colors;
-use crate::starfield::StarfieldState;
-use crate::ui::widgets::{content_container_frame, planetarium_button, planetarium_text_input};
+use crate::observing_list::{ObservingItem, ObservingList};
+use crate::satellite::{
+    SelectedPass, TrackedPass, TrackedSatellite, TrackedSatelliteInfo, TrailPrediction,
+    MAX_PREDICTION_MINUTES,
+};
+use crate::starfield::{alt_az_direction, equatorial_from_direction, StarfieldState};
+use crate::ui::widgets::{
+    content_container_frame, planetarium_button, planetarium_text_input, window_frame,
+};
 use bevy::prelude::*;
 use bevy_egui::egui;
-use chrono::{DateTime, Duration, FixedOffset, Utc};
-use overpass_planner::{get_overpasses, get_satellite_name, ObserverLocation, Overpass};
+use chrono::{DateTime, Duration, Local, Utc};
+use overpass_planner::{
+    get_overpasses_with_progress, get_satellite_name,
+    tle::{fetch_tle, find_satellites, CACHE_DURATION_HOURS},
+    ObserverLocation, Overpass, PassKind, PropagationContext, Propagator,
+};
+use protos::protos::PassTrack;
 use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+
+/// Time between samples of the selected pass's sky path
+const PASS_PATH_INTERVAL_SECS: i64 = 10;
+
+/// Most name lookup matches listed; the rest need a longer name to reach
+const MAX_NAME_MATCHES: usize = 50;
+
+/// Which overpasses the table lists
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PassFilter {
+    /// Hide passes outside astronomical night
+    pub night_only: bool,
+    /// Hide passes where the satellite stays in Earth's shadow
+    pub lit_only: bool,
+    /// Lowest peak elevation (degrees) worth listing
+    pub min_max_elevation: f64,
+}
+
+impl Default for PassFilter {
+    fn default() -> Self {
+        PassFilter {
+            night_only: false,
+            lit_only: false,
+            min_max_elevation: 0.0,
+        }
+    }
+}
+
+impl PassFilter {
+    pub fn allows(&self, overpass: &Overpass) -> bool {
+        (!self.night_only || overpass.is_night)
+            && (!self.lit_only || overpass.is_lit)
+            && overpass.max_elevation >= self.min_max_elevation
+    }
+}
 
 #[derive(Resource)]
 pub struct SatelliteSearchState {
@@ -16,7 +65,21 @@ pub struct SatelliteSearchState {
     pub overpasses: Vec<Overpass>,
     pub selected_overpass: Option<usize>,
     pub search_in_progress: bool,
+    pub search_progress: f32,
     pub search_error: Option<String>,
+    /// SGP4 state for the searched satellite, used to sample pass paths
+    pub propagator: Option<Arc<PropagationContext>>,
+    /// TLE the propagator was built from, handed to the GUI with a tracked pass
+    pub tle: Option<String>,
+    /// When the overpasses were last asked for; they are asked for again once
+    /// the TLEs they came from are due for a refresh
+    pub searched_at: Option<DateTime<Utc>>,
+    pub filter: PassFilter,
+    /// Start of a satellite name to look up in the TLE catalog
+    pub name_input: String,
+    /// (NORAD ID, name) of every satellite matching the last name lookup
+    pub name_matches: Vec<(u32, String)>,
+    pub name_search_in_progress: bool,
 }
 
 impl Default for SatelliteSearchState {
@@ -28,7 +91,15 @@ impl Default for SatelliteSearchState {
             overpasses: Vec::new(),
             selected_overpass: None,
             search_in_progress: false,
+            search_progress: 0.0,
             search_error: None,
+            propagator: None,
+            tle: None,
+            searched_at: None,
+            filter: PassFilter::default(),
+            name_input: String::new(),
+            name_matches: Vec::new(),
+            name_search_in_progress: false,
         }
     }
 }
@@ -42,13 +113,25 @@ pub struct SearchResultChannel {
 
 #[derive(Debug, Clone)]
 pub enum SearchResult {
+    Progress {
+        fraction: f32,
+    },
     Success {
         overpasses: Vec<Overpass>,
         satellite_name: Option<String>,
+        propagator: Option<Arc<PropagationContext>>,
+        tle: Option<String>,
     },
     Error {
         message: String,
     },
+    /// Satellites found by a name lookup
+    NameMatches {
+        satellites: Vec<(u32, String)>,
+    },
+    NameError {
+        message: String,
+    },
 }
 
 impl Default for SearchResultChannel {
@@ -61,10 +144,18 @@ impl Default for SearchResultChannel {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn render_satellite_window(
     mut search_state: ResMut<SatelliteSearchState>,
     starfield_state: Res<StarfieldState>,
     search_channel: Res<SearchResultChannel>,
+    mut selected_pass: ResMut<SelectedPass>,
+    mut tracked_pass: ResMut<TrackedPass>,
+    mut follow: ResMut<CameraFollow>,
+    mut tracked: ResMut<TrackedSatellite>,
+    mut time_display: ResMut<TimeDisplay>,
+    mut prediction: ResMut<TrailPrediction>,
+    mut list: ResMut<ObservingList>,
     mut menu_state: ResMut<crate::ui::MenuState>,
     mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
 ) {
@@ -76,42 +167,102 @@ pub fn render_satellite_window(
     // Handle search results
     if let Ok(receiver) = search_channel.receiver.lock() {
         while let Ok(result) = receiver.try_recv() {
-            search_state.search_in_progress = false;
             match result {
+                SearchResult::Progress { fraction } => {
+                    search_state.search_progress = fraction;
+                }
                 SearchResult::Success {
                     overpasses,
                     satellite_name,
+                    propagator,
+                    tle,
                 } => {
+                    search_state.search_in_progress = false;
                     search_state.overpasses = overpasses;
                     search_state.satellite_name = satellite_name;
+                    search_state.propagator = propagator;
+                    search_state.tle = tle;
                     search_state.search_error = None;
+                    // a refresh keeps the selection and the pass being followed
+                    search_state.selected_overpass = selected_pass
+                        .0
+                        .as_ref()
+                        .and_then(|pass| find_pass(&search_state.overpasses, pass));
+                    if tracked.0.as_ref().map(|sat| sat.norad_id) != search_state.norad_id {
+                        stop_pass_tracking(&mut tracked_pass, &mut follow);
+                    }
+
+                    // Follow the searched satellite live in the sky
+                    tracked.0 = search_state
+                        .norad_id
+                        .zip(search_state.propagator.clone())
+                        .map(|(norad_id, propagator)| TrackedSatelliteInfo {
+                            norad_id,
+                            name: search_state.satellite_name.clone(),
+                            propagator,
+                        });
                 }
                 SearchResult::Error { message } => {
+                    search_state.search_in_progress = false;
                     search_state.search_error = Some(message);
                     search_state.overpasses.clear();
                     search_state.satellite_name = None;
+                    search_state.propagator = None;
+                    search_state.tle = None;
+                    tracked.0 = None;
+                    stop_pass_tracking(&mut tracked_pass, &mut follow);
+                }
+                SearchResult::NameMatches { satellites } => {
+                    search_state.name_search_in_progress = false;
+                    if satellites.is_empty() {
+                        search_state.search_error = Some(format!(
+                            "No satellite names start with \"{}\"",
+                            search_state.name_input.trim()
+                        ));
+                    }
+                    search_state.name_matches = satellites;
+                }
+                SearchResult::NameError { message } => {
+                    search_state.name_search_in_progress = false;
+                    search_state.name_matches.clear();
+                    search_state.search_error = Some(message);
                 }
             }
         }
     }
 
+    // Keep the list current through a long session, whether or not it's shown
+    let now = Utc::now();
+    if search_state
+        .overpasses
+        .iter()
+        .any(|pass| pass.end_time <= now)
+    {
+        search_state.overpasses.retain(|pass| pass.end_time > now);
+        if selected_pass
+            .0
+            .as_ref()
+            .is_some_and(|pass| pass.end_time <= now)
+        {
+            selected_pass.0 = None;
+        }
+        search_state.selected_overpass = selected_pass
+            .0
+            .as_ref()
+            .and_then(|pass| find_pass(&search_state.overpasses, pass));
+    }
+    let stale = search_state
+        .searched_at
+        .is_some_and(|at| now - at > Duration::hours(CACHE_DURATION_HOURS));
+    if stale && !search_state.search_in_progress {
+        refresh_overpasses(&mut search_state, &starfield_state, &search_channel);
+    }
+
     // Only show window if it's supposed to be open
     if !menu_state.satellite_window_open {
         return;
     }
 
-    // Customize window frame with background color and padding
-    let mut window_frame = egui::Frame::window(&ctx.style());
-    window_frame.fill = colors::egui::WINDOW_BACKGROUND;
-    // Set 4pt padding so containers have 8pt total spacing from window edges
-    // (4pt window padding + 4pt container outer margin = 8pt total)
-    window_frame.inner_margin = egui::Margin {
-        left: 4,
-        right: 4,
-        top: 4,
-        bottom: 4,
-    };
-
     egui::Window::new(
         egui::RichText::new("Satellite Tracking")
             .size(14.0)
@@ -120,7 +271,7 @@ pub fn render_satellite_window(
     .collapsible(false)
     .resizable(true)
     .default_size([400.0, 1000.0]) // 30% taller: 600 * 1.3 = 780
-    .frame(window_frame)
+    .frame(window_frame(ctx))
     .open(&mut menu_state.satellite_window_open)
     .show(ctx, |ui| {
         ui.vertical(|ui| {
@@ -165,54 +316,14 @@ pub fn render_satellite_window(
                                 planetarium_button(ui, "Search", 80.0, text_input_height);
 
                             if button_resp.clicked() {
-                                // Parse NORAD ID
                                 match search_state.norad_id_input.trim().parse::<u32>() {
-                                    Ok(norad_id) => {
-                                        search_state.norad_id = Some(norad_id);
-                                        search_state.search_in_progress = true;
-                                        search_state.search_error = None;
-                                        search_state.overpasses.clear();
-                                        search_state.selected_overpass = None;
-
-                                        // Spawn async task to fetch overpasses
-                                        let location = ObserverLocation {
-                                            latitude: starfield_state.lat_deg,
-                                            longitude: starfield_state.lon_deg,
-                                            altitude: 0.0, // Sea level
-                                        };
-                                        let time_window = Duration::hours(24);
-                                        // Clone the sender from the Mutex
-                                        let sender = {
-                                            let guard = search_channel.sender.lock().unwrap();
-                                            guard.clone()
-                                        };
-
-                                        std::thread::spawn(move || {
-                                            let rt = tokio::runtime::Runtime::new().unwrap();
-                                            rt.block_on(async move {
-                                                // Fetch satellite name and overpasses in parallel
-                                                let (overpasses_result, name_result) = tokio::join!(
-                                                    get_overpasses(norad_id, location, time_window),
-                                                    get_satellite_name(norad_id)
-                                                );
-
-                                                match overpasses_result {
-                                                    Ok(overpasses) => {
-                                                        let satellite_name = name_result.ok();
-                                                        let _ = sender.send(SearchResult::Success {
-                                                            overpasses,
-                                                            satellite_name,
-                                                        });
-                                                    }
-                                                    Err(e) => {
-                                                        let _ = sender.send(SearchResult::Error {
-                                                            message: format!("{}", e),
-                                                        });
-                                                    }
-                                                }
-                                            });
-                                        });
-                                    }
+                                    Ok(norad_id) => start_overpass_search(
+                                        norad_id,
+                                        &mut search_state,
+                                        &mut selected_pass,
+                                        &starfield_state,
+                                        &search_channel,
+                                    ),
                                     Err(_) => {
                                         search_state.search_error =
                                             Some("Invalid NORAD ID".to_string());
@@ -221,13 +332,114 @@ pub fn render_satellite_window(
                             }
                         });
 
-                        // Show satellite name if found
-                        if let Some(name) = &search_state.satellite_name {
+                        // Or look the satellite up by name
+                        ui.horizontal(|ui| {
+                            ui.add_sized(
+                                egui::vec2(0.0, text_input_height),
+                                egui::Label::new(
+                                    egui::RichText::new("Name:")
+                                        .size(12.0)
+                                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                                ),
+                            );
+                            let input = planetarium_text_input(
+                                ui,
+                                &mut search_state.name_input,
+                                150.0,
+                                text_input_height,
+                            );
+                            let entered = input.lost_focus()
+                                && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                            if planetarium_button(ui, "Find", 80.0, text_input_height).clicked()
+                                || entered
+                            {
+                                start_name_search(&mut search_state, &search_channel);
+                            }
+                        });
+
+                        if search_state.name_search_in_progress {
                             ui.label(
-                                egui::RichText::new(format!("Found satellite: {}", name))
+                                egui::RichText::new("Looking up names...")
                                     .size(12.0)
                                     .color(colors::egui::WINDOW_TITLE_COLOR),
                             );
+                            ui.ctx().request_repaint();
+                        } else if !search_state.name_matches.is_empty() {
+                            let mut picked = None;
+                            egui::ScrollArea::vertical()
+                                .id_salt("satellite_name_matches")
+                                .max_height(120.0)
+                                .show(ui, |ui| {
+                                    for (norad_id, name) in
+                                        search_state.name_matches.iter().take(MAX_NAME_MATCHES)
+                                    {
+                                        let selected = search_state.norad_id == Some(*norad_id);
+                                        if ui
+                                            .selectable_label(
+                                                selected,
+                                                egui::RichText::new(format!(
+                                                    "{} ({})",
+                                                    name, norad_id
+                                                ))
+                                                .size(12.0)
+                                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                                            )
+                                            .clicked()
+                                        {
+                                            picked = Some(*norad_id);
+                                        }
+                                    }
+                                });
+                            let count = search_state.name_matches.len();
+                            if count > MAX_NAME_MATCHES {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "Showing {} of {} matches; type more of the name",
+                                        MAX_NAME_MATCHES, count
+                                    ))
+                                    .size(11.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                                );
+                            }
+                            if let Some(norad_id) = picked {
+                                search_state.norad_id_input = norad_id.to_string();
+                                start_overpass_search(
+                                    norad_id,
+                                    &mut search_state,
+                                    &mut selected_pass,
+                                    &starfield_state,
+                                    &search_channel,
+                                );
+                            }
+                        }
+
+                        // Show satellite name if found
+                        if let Some(name) = &search_state.satellite_name {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(format!("Found satellite: {}", name))
+                                        .size(12.0)
+                                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                                );
+                                // the list keeps the TLE, to work out where it is later
+                                if let (Some(norad_id), Some(tle)) =
+                                    (search_state.norad_id, &search_state.tle)
+                                {
+                                    let item = ObservingItem::Satellite {
+                                        norad_id,
+                                        name: name.clone(),
+                                        tle: tle.clone(),
+                                        propagator: search_state.propagator.clone(),
+                                    };
+                                    let listed = list.contains(&item);
+                                    ui.add_enabled_ui(!listed, |ui| {
+                                        let label = if listed { "Listed" } else { "+ List" };
+                                        if planetarium_button(ui, label, 60.0, 20.0).clicked() {
+                                            list.add(item);
+                                        }
+                                    });
+                                }
+                            });
                         }
 
                         // Show error if any
@@ -255,24 +467,122 @@ pub fn render_satellite_window(
                                 .color(colors::egui::WINDOW_TITLE_COLOR),
                         );
 
+                        if let Some(searched_at) = search_state.searched_at {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "Updated {}",
+                                        time_display.time(searched_at)
+                                    ))
+                                    .size(11.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                                );
+                                if planetarium_button(ui, "Refresh", 80.0, 20.0).clicked()
+                                    && !search_state.search_in_progress
+                                {
+                                    refresh_overpasses(
+                                        &mut search_state,
+                                        &starfield_state,
+                                        &search_channel,
+                                    );
+                                }
+                            });
+                        }
+
                         // Show site location
                         ui.label(
                             egui::RichText::new(format!(
                                 "Site: {:.4}°N, {:.4}°E, {:.0}m",
                                 starfield_state.lat_deg,
                                 starfield_state.lon_deg,
-                                0.0
+                                starfield_state.alt_m
                             ))
                             .size(12.0)
                             .color(colors::egui::WINDOW_TITLE_COLOR),
                         );
 
-                        // Note about timezone
-                        ui.label(
-                            egui::RichText::new("All times shown in EST (UTC-5)")
+                        // Time zone of the pass times, with a switch to UTC
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "All times shown in {}",
+                                    time_display.describe()
+                                ))
                                 .size(11.0)
                                 .color(colors::egui::WINDOW_TITLE_COLOR),
-                        );
+                            );
+                            let (label, other) = match *time_display {
+                                TimeDisplay::Local => ("Show UTC", TimeDisplay::Utc),
+                                TimeDisplay::Utc => ("Show Local", TimeDisplay::Local),
+                            };
+                            if planetarium_button(ui, label, 80.0, 20.0).clicked() {
+                                *time_display = other;
+                            }
+                        });
+
+                        // How far ahead the dotted path of the tracked satellite reaches
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new("Predicted path (min):")
+                                    .size(11.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                            );
+                            let mut minutes = prediction.minutes;
+                            if ui
+                                .add(egui::Slider::new(&mut minutes, 0..=MAX_PREDICTION_MINUTES))
+                                .changed()
+                            {
+                                prediction.minutes = minutes;
+                            }
+                        });
+
+                        // Narrow the table down to the passes worth observing
+                        ui.horizontal(|ui| {
+                            let filter = &mut search_state.filter;
+                            ui.checkbox(&mut filter.night_only, "Night only");
+                            ui.checkbox(&mut filter.lit_only, "Lit only");
+                            ui.label(
+                                egui::RichText::new("Min elevation:")
+                                    .size(11.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                            );
+                            ui.add(
+                                egui::Slider::new(&mut filter.min_max_elevation, 0.0..=90.0)
+                                    .suffix("°")
+                                    .step_by(5.0),
+                            );
+                        });
+
+                        // The pass being followed, with a way to let it go
+                        let tracking_status = tracked_pass.0.as_ref().map(|pass| {
+                            let now = starfield_state.current_utc();
+                            if now < pass.start_time {
+                                let wait = (pass.start_time - now).num_seconds();
+                                format!(
+                                    "Tracking pass: rises in {:02}:{:02}:{:02}",
+                                    wait / 3600,
+                                    wait / 60 % 60,
+                                    wait % 60
+                                )
+                            } else {
+                                format!(
+                                    "Tracking pass: sets at {}",
+                                    time_display.time(pass.end_time)
+                                )
+                            }
+                        });
+                        if let Some(status) = tracking_status {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    egui::RichText::new(status)
+                                        .size(11.0)
+                                        .color(colors::egui::ACCENT_YELLOW),
+                                );
+                                if planetarium_button(ui, "Stop", 60.0, 20.0).clicked() {
+                                    stop_pass_tracking(&mut tracked_pass, &mut follow);
+                                }
+                            });
+                        }
 
                         if search_state.search_in_progress {
                             ui.label(
@@ -280,6 +590,11 @@ pub fn render_satellite_window(
                                     .size(12.0)
                                     .color(colors::egui::WINDOW_TITLE_COLOR),
                             );
+                            ui.add(
+                                egui::ProgressBar::new(search_state.search_progress)
+                                    .show_percentage(),
+                            );
+                            ui.ctx().request_repaint();
                         } else if search_state.overpasses.is_empty()
                             && search_state.search_error.is_none()
                         {
@@ -291,6 +606,23 @@ pub fn render_satellite_window(
                                 .color(colors::egui::WINDOW_TITLE_COLOR),
                             );
                         } else {
+                            let shown = search_state
+                                .overpasses
+                                .iter()
+                                .filter(|p| search_state.filter.allows(p))
+                                .count();
+                            if shown < search_state.overpasses.len() {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{} of {} passes shown",
+                                        shown,
+                                        search_state.overpasses.len()
+                                    ))
+                                    .size(11.0)
+                                    .color(colors::egui::WINDOW_TITLE_COLOR),
+                                );
+                            }
+
                             // Table using Grid layout
                             // Allocate dynamic height: use available space or minimum 100px
                             let available_height = ui.available_height();
@@ -306,6 +638,7 @@ pub fn render_satellite_window(
                                             ui.set_width(ui.available_width() - 20.0); // Reserve space for scrollbar
                                             let overpasses = search_state.overpasses.clone();
                                             let selected = search_state.selected_overpass;
+                                            let filter = search_state.filter;
 
                                             egui::Grid::new("overpasses_table")
                                                 .spacing(egui::vec2(8.0, 4.0))
@@ -375,8 +708,11 @@ pub fn render_satellite_window(
                                                      ui.end_row();
 
                                                     // Data rows
-                                                    for (index, overpass) in
-                                                        overpasses.iter().enumerate()
+                                                    // indices stay those of the full list
+                                                    for (index, overpass) in overpasses
+                                                        .iter()
+                                                        .enumerate()
+                                                        .filter(|(_, p)| filter.allows(p))
                                                     {
                                                         let is_selected = selected == Some(index);
                                                         let row_start_rect =
@@ -384,7 +720,7 @@ pub fn render_satellite_window(
 
                                                         // Date column
                                                         ui.label(
-                                                            egui::RichText::new(format_date(
+                                                            egui::RichText::new(time_display.date(
                                                                 overpass.start_time,
                                                             ))
                                                             .size(12.0)
@@ -395,7 +731,7 @@ pub fn render_satellite_window(
                                                         let response = if index == 0 {
                                                             // First item: use label instead of selectable_label
                                                             ui.label(
-                                                                egui::RichText::new(format_time(
+                                                                egui::RichText::new(time_display.time(
                                                                     overpass.start_time,
                                                                 ))
                                                                 .size(12.0)
@@ -405,7 +741,7 @@ pub fn render_satellite_window(
                                                             // Other items: selectable
                                                             ui.selectable_label(
                                                                 is_selected,
-                                                                egui::RichText::new(format_time(
+                                                                egui::RichText::new(time_display.time(
                                                                     overpass.start_time,
                                                                 ))
                                                                 .size(12.0)
@@ -415,10 +751,15 @@ pub fn render_satellite_window(
                                                         if index > 0 && response.clicked() {
                                                             search_state.selected_overpass =
                                                                 Some(index);
+                                                            selected_pass.0 = pass_with_path(
+                                                                overpass,
+                                                                search_state.propagator.as_deref(),
+                                                                &starfield_state,
+                                                            );
                                                         }
 
                                                         ui.label(
-                                                    egui::RichText::new(format_time(
+                                                    egui::RichText::new(time_display.time(
                                                         overpass.end_time,
                                                     ))
                                                     .size(12.0)
@@ -428,11 +769,26 @@ pub fn render_satellite_window(
                                                         let duration_min = (overpass.end_time
                                                             - overpass.start_time)
                                                             .num_minutes();
+                                                        let duration_text = match overpass.kind {
+                                                            PassKind::AlwaysVisible => {
+                                                                "Always up".to_string()
+                                                            }
+                                                            PassKind::NeverVisible => {
+                                                                "Never up".to_string()
+                                                            }
+                                                            PassKind::Normal
+                                                                if overpass.truncated_start
+                                                                    || overpass.truncated_end =>
+                                                            {
+                                                                // Clipped by the search window
+                                                                format!("{:.1}+ min", duration_min)
+                                                            }
+                                                            PassKind::Normal => {
+                                                                format!("{:.1} min", duration_min)
+                                                            }
+                                                        };
                                                         ui.label(
-                                                    egui::RichText::new(format!(
-                                                        "{:.1} min",
-                                                        duration_min
-                                                    ))
+                                                    egui::RichText::new(duration_text)
                                                     .size(12.0)
                                                     .color(colors::egui::WINDOW_TITLE_COLOR),
                                                 );
@@ -447,7 +803,7 @@ pub fn render_satellite_window(
                                                 );
 
                                                         ui.label(
-                                                    egui::RichText::new(format_time(
+                                                    egui::RichText::new(time_display.time(
                                                         overpass.midpoint_time,
                                                     ))
                                                     .size(12.0)
@@ -486,10 +842,19 @@ pub fn render_satellite_window(
                                                         )
                                                         .clicked()
                                                         {
-                                                            // TODO: Implement tracking
-                                                            println!(
-                                                                "Tracking overpass: {:?}",
-                                                                overpass
+                                                            search_state.selected_overpass =
+                                                                Some(index);
+                                                            selected_pass.0 = pass_with_path(
+                                                                overpass,
+                                                                search_state.propagator.as_deref(),
+                                                                &starfield_state,
+                                                            );
+                                                            track_pass(
+                                                                &search_state,
+                                                                &selected_pass,
+                                                                &starfield_state,
+                                                                &mut tracked_pass,
+                                                                &mut follow,
                                                             );
                                                         }
 
@@ -528,16 +893,257 @@ pub fn render_satellite_window(
     });
 }
 
-fn format_time(dt: DateTime<Utc>) -> String {
-    // Convert UTC to EST (UTC-5)
-    let est_offset = FixedOffset::east_opt(-5 * 3600).unwrap();
-    let est_time = dt.with_timezone(&est_offset);
-    est_time.format("%H:%M:%S").to_string()
+/// Look up the next day's overpasses of `norad_id` in the background; the
+/// satellite is followed in the sky once they arrive
+fn start_overpass_search(
+    norad_id: u32,
+    search_state: &mut SatelliteSearchState,
+    selected_pass: &mut SelectedPass,
+    starfield_state: &StarfieldState,
+    search_channel: &SearchResultChannel,
+) {
+    search_state.norad_id = Some(norad_id);
+    search_state.search_in_progress = true;
+    search_state.search_progress = 0.0;
+    search_state.search_error = None;
+    search_state.overpasses.clear();
+    search_state.selected_overpass = None;
+    selected_pass.0 = None;
+
+    spawn_overpass_search(norad_id, search_state, starfield_state, search_channel);
+}
+
+/// Ask for the current satellite's overpasses again, keeping the list up
+/// until the new one arrives
+fn refresh_overpasses(
+    search_state: &mut SatelliteSearchState,
+    starfield_state: &StarfieldState,
+    search_channel: &SearchResultChannel,
+) {
+    let Some(norad_id) = search_state.norad_id else {
+        return;
+    };
+    search_state.search_in_progress = true;
+    search_state.search_progress = 0.0;
+    search_state.search_error = None;
+    spawn_overpass_search(norad_id, search_state, starfield_state, search_channel);
+}
+
+/// The search itself, run on its own thread
+fn spawn_overpass_search(
+    norad_id: u32,
+    search_state: &mut SatelliteSearchState,
+    starfield_state: &StarfieldState,
+    search_channel: &SearchResultChannel,
+) {
+    // stamped at the start, so a failing search isn't retried every frame
+    search_state.searched_at = Some(Utc::now());
+
+    let location = ObserverLocation {
+        latitude: starfield_state.lat_deg,
+        longitude: starfield_state.lon_deg,
+        altitude: starfield_state.alt_m,
+    };
+    let time_window = Duration::hours(24);
+    // Clone the sender from the Mutex
+    let sender = {
+        let guard = search_channel.sender.lock().unwrap();
+        guard.clone()
+    };
+
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move {
+            // Fetch satellite name and overpasses in parallel
+            let progress_sender = sender.clone();
+            let (overpasses_result, name_result, tle_result) = tokio::join!(
+                get_overpasses_with_progress(norad_id, location, time_window, move |fraction| {
+                    let _ = progress_sender.send(SearchResult::Progress { fraction });
+                }),
+                get_satellite_name(norad_id),
+                fetch_tle(norad_id)
+            );
+
+            match overpasses_result {
+                Ok(overpasses) => {
+                    let satellite_name = name_result.ok();
+                    let tle = tle_result.ok();
+                    let propagator = tle
+                        .as_deref()
+                        .and_then(|tle| PropagationContext::from_tle(tle).ok())
+                        .map(Arc::new);
+                    let _ = sender.send(SearchResult::Success {
+                        overpasses,
+                        satellite_name,
+                        propagator,
+                        tle,
+                    });
+                }
+                Err(e) => {
+                    let _ = sender.send(SearchResult::Error {
+                        message: format!("{}", e),
+                    });
+                }
+            }
+        });
+    });
+}
+
+/// Follow the selected pass with the camera and ask the GUI to arm mount
+/// tracking for it
+fn track_pass(
+    search_state: &SatelliteSearchState,
+    selected_pass: &SelectedPass,
+    starfield_state: &StarfieldState,
+    tracked_pass: &mut TrackedPass,
+    follow: &mut CameraFollow,
+) {
+    let (Some(norad_id), Some(pass)) = (search_state.norad_id, &selected_pass.0) else {
+        return;
+    };
+    let Some(rise) = pass.trajectory.first() else {
+        return;
+    };
+    follow.track(CameraTarget::Horizontal {
+        altitude: rise.altitude,
+        azimuth: rise.azimuth,
+    });
+    tracked_pass.0 = Some(pass.clone());
+
+    let (ra, dec) = equatorial_from_direction(
+        pass.start_time,
+        starfield_state.lat_deg.to_radians(),
+        starfield_state.lon_deg.to_radians(),
+        alt_az_direction(rise.altitude, rise.azimuth),
+    );
+    let track = PassTrack {
+        norad_id,
+        name: search_state
+            .satellite_name
+            .clone()
+            .unwrap_or_else(|| format!("NORAD {}", norad_id)),
+        tle: search_state.tle.clone().unwrap_or_default(),
+        start: Some(client::timestamp(pass.start_time)),
+        end: Some(client::timestamp(pass.end_time)),
+        max_elevation_degrees: pass.max_elevation as f32,
+        rise_ra_hours: ra.to_degrees() as f32 / 15.0,
+        rise_dec_degrees: dec.to_degrees() as f32,
+    };
+    if let Err(e) = client::send_pass_track(track) {
+        eprintln!("Pass tracking request failed: {e}");
+    }
+}
+
+/// Where `pass` is in a freshly searched list, matched by its start time
+fn find_pass(overpasses: &[Overpass], pass: &Overpass) -> Option<usize> {
+    overpasses
+        .iter()
+        .position(|p| (p.start_time - pass.start_time).num_seconds().abs() < 60)
 }
 
-fn format_date(dt: DateTime<Utc>) -> String {
-    // Convert UTC to EST (UTC-5)
-    let est_offset = FixedOffset::east_opt(-5 * 3600).unwrap();
-    let est_time = dt.with_timezone(&est_offset);
-    est_time.format("%Y-%m-%d").to_string()
+/// Forget the tracked pass, and stop the camera if it was following it
+fn stop_pass_tracking(tracked_pass: &mut TrackedPass, follow: &mut CameraFollow) {
+    if tracked_pass.0.take().is_some()
+        && matches!(follow.target, Some(CameraTarget::Horizontal { .. }))
+    {
+        follow.stop();
+    }
+}
+
+/// Find the satellites whose names start with the entered text in the TLE
+/// catalog, to pick one from instead of typing its NORAD ID
+fn start_name_search(
+    search_state: &mut SatelliteSearchState,
+    search_channel: &SearchResultChannel,
+) {
+    let prefix = search_state.name_input.trim().to_string();
+    if prefix.is_empty() {
+        search_state.search_error = Some("Enter the start of a satellite name".to_string());
+        return;
+    }
+    search_state.name_search_in_progress = true;
+    search_state.name_matches.clear();
+    search_state.search_error = None;
+
+    let sender = {
+        let guard = search_channel.sender.lock().unwrap();
+        guard.clone()
+    };
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = match rt.block_on(find_satellites(&prefix)) {
+            Ok(found) => SearchResult::NameMatches {
+                satellites: found
+                    .into_iter()
+                    .map(|(norad_id, tle)| {
+                        let name = tle.lines().next().unwrap_or_default().trim().to_string();
+                        (norad_id, name)
+                    })
+                    .collect(),
+            },
+            Err(e) => SearchResult::NameError {
+                message: format!("{}", e),
+            },
+        };
+        let _ = sender.send(result);
+    });
+}
+
+/// The overpass with its sky path sampled, for drawing in the sky
+fn pass_with_path(
+    overpass: &Overpass,
+    propagator: Option<&PropagationContext>,
+    starfield_state: &StarfieldState,
+) -> Option<Overpass> {
+    let location = ObserverLocation {
+        latitude: starfield_state.lat_deg,
+        longitude: starfield_state.lon_deg,
+        altitude: starfield_state.alt_m,
+    };
+    let trajectory = propagator?
+        .trajectory(
+            location,
+            overpass.start_time,
+            overpass.end_time,
+            Duration::seconds(PASS_PATH_INTERVAL_SECS),
+        )
+        .ok()?;
+    Some(Overpass {
+        trajectory,
+        ..overpass.clone()
+    })
+}
+
+/// Zone that pass times are shown in
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeDisplay {
+    /// The system time zone
+    #[default]
+    Local,
+    Utc,
+}
+
+impl TimeDisplay {
+    fn format(&self, dt: DateTime<Utc>, fmt: &str) -> String {
+        match self {
+            TimeDisplay::Local => dt.with_timezone(&Local).format(fmt).to_string(),
+            TimeDisplay::Utc => dt.format(fmt).to_string(),
+        }
+    }
+
+    pub fn time(&self, dt: DateTime<Utc>) -> String {
+        self.format(dt, "%H:%M:%S")
+    }
+
+    pub fn date(&self, dt: DateTime<Utc>) -> String {
+        self.format(dt, "%Y-%m-%d")
+    }
+
+    /// "local time (UTC-05:00)" or "UTC"
+    pub fn describe(&self) -> String {
+        match self {
+            TimeDisplay::Local => format!("local time (UTC{})", Local::now().format("%:z")),
+            TimeDisplay::Utc => "UTC".to_string(),
+        }
+    }
 }
@@ -0,0 +1,273 @@
+use crate::colors;
+use crate::custom_catalog::StarCatalog;
+use crate::events::PlanetariumEvent;
+use crate::frame_rate::FrameRateSettings;
+use crate::light_pollution::{LightDome, LightPollution};
+use crate::starfield::StarfieldState;
+use crate::sun_avoidance::SunAvoidance;
+use crate::ui::widgets::{
+    content_container_frame, field_label, planetarium_button, planetarium_text_input, window_frame,
+};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use std::path::PathBuf;
+
+#[derive(Resource, Default)]
+pub struct SettingsWindowState {
+    pub latitude: String,
+    pub longitude: String,
+    pub altitude: String,
+    /// Custom star catalog file; empty for the bundled one
+    pub catalog_path: String,
+    /// Whether the fields hold the current site; refilled each time the window opens
+    pub filled: bool,
+    pub error: Option<String>,
+}
+
+/// Parse a number in `range` from a text field
+fn parse_in_range(
+    text: &str,
+    what: &str,
+    range: std::ops::RangeInclusive<f64>,
+) -> Result<f64, String> {
+    match text.trim().parse::<f64>() {
+        Ok(value) if range.contains(&value) => Ok(value),
+        _ => Err(format!(
+            "{what} must be between {} and {}",
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+fn site_event(state: &SettingsWindowState) -> Result<PlanetariumEvent, String> {
+    Ok(PlanetariumEvent::SetSiteLocation {
+        lat_deg: parse_in_range(&state.latitude, "Latitude", -90.0..=90.0)?,
+        lon_deg: parse_in_range(&state.longitude, "Longitude", -180.0..=180.0)?,
+        alt_m: parse_in_range(&state.altitude, "Altitude", -500.0..=9000.0)?,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_settings_window(
+    mut settings_state: ResMut<SettingsWindowState>,
+    starfield_state: Res<StarfieldState>,
+    mut catalog: ResMut<StarCatalog>,
+    mut pollution: ResMut<LightPollution>,
+    mut frame_rate: ResMut<FrameRateSettings>,
+    mut sun_avoidance: ResMut<SunAvoidance>,
+    mut events: MessageWriter<PlanetariumEvent>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.settings_window_open {
+        settings_state.filled = false;
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    if !settings_state.filled {
+        settings_state.latitude = format!("{:.5}", starfield_state.lat_deg);
+        settings_state.longitude = format!("{:.5}", starfield_state.lon_deg);
+        settings_state.altitude = format!("{:.0}", starfield_state.alt_m);
+        settings_state.catalog_path = catalog
+            .loaded
+            .as_ref()
+            .map_or_else(String::new, |(path, _)| path.display().to_string());
+        settings_state.error = None;
+        settings_state.filled = true;
+    }
+
+    egui::Window::new(
+        egui::RichText::new("Settings")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.settings_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            // Observer site; saved for the next launch and replaced whenever the
+            // GUI sends its own
+            ui.horizontal(|ui| {
+                field_label(ui, "Latitude (°N):", 110.0);
+                planetarium_text_input(ui, &mut settings_state.latitude, 100.0, 22.0);
+            });
+            ui.horizontal(|ui| {
+                field_label(ui, "Longitude (°E):", 110.0);
+                planetarium_text_input(ui, &mut settings_state.longitude, 100.0, 22.0);
+            });
+            ui.horizontal(|ui| {
+                field_label(ui, "Altitude (m):", 110.0);
+                planetarium_text_input(ui, &mut settings_state.altitude, 100.0, 22.0);
+            });
+
+            if planetarium_button(ui, "Apply", 80.0, 22.0).clicked() {
+                match site_event(&settings_state) {
+                    Ok(event) => {
+                        events.write(event);
+                        settings_state.error = None;
+                    }
+                    Err(e) => settings_state.error = Some(e),
+                }
+            }
+
+            if let Some(error) = &settings_state.error {
+                ui.label(
+                    egui::RichText::new(format!("Error: {}", error))
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+        });
+
+        // Light pollution, shown by View > Sky Brightness. Edited through
+        // copies so the overlay is only rebuilt when something changes.
+        content_container_frame().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                field_label(ui, "Bortle class:", 110.0);
+                let mut bortle = pollution.bortle;
+                if ui.add(egui::Slider::new(&mut bortle, 1..=9)).changed() {
+                    pollution.bortle = bortle;
+                }
+            });
+
+            let mut domes = pollution.domes.clone();
+            let mut removed = None;
+            for (i, dome) in domes.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    field_label(ui, &format!("Light dome {}:", i + 1), 110.0);
+                    ui.add(
+                        egui::DragValue::new(&mut dome.azimuth_deg)
+                            .range(0.0..=359.0)
+                            .suffix("° az"),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut dome.magnitudes)
+                            .range(0.0..=6.0)
+                            .speed(0.05)
+                            .suffix(" mag"),
+                    );
+                    if planetarium_button(ui, "Remove", 60.0, 20.0).clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = removed {
+                domes.remove(i);
+            }
+            if planetarium_button(ui, "Add Light Dome", 110.0, 22.0).clicked() {
+                domes.push(LightDome {
+                    azimuth_deg: 0.0,
+                    magnitudes: 1.0,
+                });
+            }
+            if domes != pollution.domes {
+                pollution.domes = domes;
+            }
+
+            let (azimuth, brightness) = pollution.darkest_azimuth(30.0);
+            ui.label(
+                egui::RichText::new(format!(
+                    "Darkest at 30° altitude: azimuth {:.0}° ({:.1} mag/arcsec²)",
+                    azimuth, brightness
+                ))
+                .size(12.0)
+                .color(colors::egui::WINDOW_TITLE_COLOR),
+            );
+        });
+
+        // Keeps the tracked target and slews clear of the Sun in daytime work;
+        // edited through a copy so the cone is only saved when it changes
+        content_container_frame().show(ui, |ui| {
+            let mut avoidance = *sun_avoidance;
+            ui.checkbox(
+                &mut avoidance.enabled,
+                egui::RichText::new("Warn near the Sun")
+                    .size(12.0)
+                    .color(colors::egui::WINDOW_TITLE_COLOR),
+            );
+            ui.horizontal(|ui| {
+                field_label(ui, "Avoidance radius:", 110.0);
+                ui.add_enabled(
+                    avoidance.enabled,
+                    egui::Slider::new(&mut avoidance.radius_deg, 5.0..=90.0).suffix("°"),
+                );
+            });
+            if avoidance != *sun_avoidance {
+                *sun_avoidance = avoidance;
+            }
+        });
+
+        // Bright stars from another catalog: ra,dec,magnitude[,b-v] text or
+        // BSC5-format binary. Swapped in without a restart and kept for the
+        // next launch.
+        content_container_frame().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                field_label(ui, "Star catalog:", 110.0);
+                planetarium_text_input(ui, &mut settings_state.catalog_path, 220.0, 22.0);
+            });
+            ui.horizontal(|ui| {
+                if planetarium_button(ui, "Load", 80.0, 22.0).clicked() {
+                    let path = settings_state.catalog_path.trim();
+                    if path.is_empty() {
+                        catalog.error = Some("Enter the path of a catalog file".to_string());
+                    } else {
+                        catalog.request(Some(PathBuf::from(path)));
+                    }
+                }
+                if planetarium_button(ui, "Default", 80.0, 22.0).clicked() {
+                    settings_state.catalog_path.clear();
+                    catalog.request(None);
+                }
+            });
+
+            let status = match &catalog.loaded {
+                Some((path, count)) => format!("{count} stars from {}", path.display()),
+                None => "Yale Bright Star Catalogue (bundled)".to_string(),
+            };
+            ui.label(
+                egui::RichText::new(status)
+                    .size(12.0)
+                    .color(colors::egui::WINDOW_TITLE_COLOR),
+            );
+            if let Some(error) = &catalog.error {
+                ui.label(
+                    egui::RichText::new(format!("Error: {}", error))
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+        });
+
+        // Keeps the GPU free for the rest of the imaging session
+        content_container_frame().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                field_label(ui, "Frame rate cap:", 110.0);
+                let mut max_fps = frame_rate.max_fps;
+                let slider = egui::Slider::new(&mut max_fps, 0..=144).custom_formatter(|fps, _| {
+                    match fps as u32 {
+                        0 => "Off".to_string(),
+                        fps => format!("{fps} fps"),
+                    }
+                });
+                if ui.add(slider).changed() {
+                    frame_rate.max_fps = max_fps;
+                }
+            });
+            let mut power_saving = frame_rate.power_saving;
+            if ui
+                .checkbox(&mut power_saving, "Slow down when idle")
+                .changed()
+            {
+                frame_rate.power_saving = power_saving;
+            }
+        });
+    });
+}
@@ -0,0 +1,65 @@
+use crate::colors;
+use crate::ui::widgets::{content_container_frame, window_frame};
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+/// Keys handled by `camera_keyboard_system`, plus the one that opens this window
+const SHORTCUTS: &[(&str, &str)] = &[
+    ("← → ↑ ↓", "Pan the view"),
+    ("+ / -", "Zoom in / out"),
+    ("Z", "Look at the zenith"),
+    ("N / S / E / W", "Face a cardinal point"),
+    ("Space", "Pause / resume tracking"),
+    ("Esc", "Leave the measure tool or close a menu"),
+    ("F1", "Show / hide this list"),
+];
+
+pub fn render_shortcuts_window(
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    if ctx.input(|i| i.key_pressed(egui::Key::F1)) {
+        menu_state.shortcuts_window_open = !menu_state.shortcuts_window_open;
+    }
+    if !menu_state.shortcuts_window_open {
+        return;
+    }
+
+    egui::Window::new(
+        egui::RichText::new("Keyboard Shortcuts")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.shortcuts_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            egui::Grid::new("shortcuts_grid")
+                .num_columns(2)
+                .spacing([16.0, 4.0])
+                .show(ui, |ui| {
+                    for (keys, action) in SHORTCUTS {
+                        ui.label(
+                            egui::RichText::new(*keys)
+                                .size(12.0)
+                                .strong()
+                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                        );
+                        ui.label(
+                            egui::RichText::new(*action)
+                                .size(12.0)
+                                .color(colors::egui::WINDOW_TITLE_COLOR),
+                        );
+                        ui.end_row();
+                    }
+                });
+        });
+    });
+}
@@ -0,0 +1,144 @@
+use crate::client::{request_slew, SlewTarget};
+use crate::planets::Planet;
+use crate::satellite::{TrackedSatellite, TrackedSatellitePosition};
+use crate::starfield::{alt_az_direction, equatorial_from_direction, StarfieldState};
+use crate::ui::widgets::content_container_frame;
+use crate::ui::widgets::planetarium_buttons::planetarium_menu_button_inner;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+/// How close (pixels) a right-click must be to an object to offer slewing to it
+const PICK_RADIUS: f32 = 15.0;
+
+/// Right-click menu for sending the mount somewhere
+#[derive(Resource, Default)]
+pub struct SlewMenuState {
+    /// Screen position the menu opened at, `None` while closed
+    pub position: Option<egui::Pos2>,
+    /// RA/Dec (radians) under the cursor
+    pub here: (f64, f64),
+    /// Object under the cursor, if any
+    pub object: Option<(String, SlewTarget)>,
+}
+
+fn equatorial_target(ra: f64, dec: f64) -> SlewTarget {
+    SlewTarget::Equatorial {
+        ra_hours: (ra.to_degrees() / 15.0) as f32,
+        dec_degrees: dec.to_degrees() as f32,
+    }
+}
+
+/// "05h 12m / +20° 30'"
+fn format_ra_dec(ra: f64, dec: f64) -> String {
+    let ra_minutes = (ra.to_degrees() / 15.0 * 60.0).round() as i64;
+    let dec_arcmin = (dec.to_degrees() * 60.0).round() as i64;
+    format!(
+        "{:02}h {:02}m / {}{:02}° {:02}'",
+        ra_minutes / 60 % 24,
+        ra_minutes % 60,
+        if dec_arcmin < 0 { '-' } else { '+' },
+        dec_arcmin.abs() / 60,
+        dec_arcmin.abs() % 60
+    )
+}
+
+/// Open on right-click in the sky; offers "Slew here" and, when the click is on a
+/// planet or the tracked satellite, "Slew to" that object
+pub fn render_slew_menu(
+    mut menu: ResMut<SlewMenuState>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    state: Res<StarfieldState>,
+    planets: Query<(&Planet, &GlobalTransform)>,
+    tracked: Res<TrackedSatellite>,
+    tracked_position: Res<TrackedSatellitePosition>,
+    mut cam_q: Query<(&Camera, &GlobalTransform, &mut EguiContext), With<Camera3d>>,
+) {
+    let Ok((camera, cam_gtf, mut egui_context)) = cam_q.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    if buttons.just_pressed(MouseButton::Right) && !ctx.is_pointer_over_area() {
+        let cursor = windows.single().ok().and_then(|w| w.cursor_position());
+        let ray = cursor.and_then(|c| camera.viewport_to_world(cam_gtf, c).ok());
+        if let (Some(cursor), Some(ray)) = (cursor, ray) {
+            // The world frame is the observer's horizon at the simulated time
+            let now = state.current_utc();
+            let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+            let to_equatorial = |dir: Vec3| equatorial_from_direction(now, lat, lon, dir);
+
+            let near_cursor = |world: Vec3| {
+                camera
+                    .world_to_viewport(cam_gtf, world)
+                    .is_ok_and(|pos| pos.distance(cursor) < PICK_RADIUS)
+            };
+
+            let satellite =
+                tracked
+                    .0
+                    .as_ref()
+                    .zip(tracked_position.0)
+                    .and_then(|(sat, (alt, az))| {
+                        let dir = alt_az_direction(alt, az);
+                        near_cursor(cam_gtf.translation() + dir * 1_000.0).then(|| {
+                            let (ra, dec) = to_equatorial(dir);
+                            let name = sat
+                                .name
+                                .clone()
+                                .unwrap_or_else(|| format!("NORAD {}", sat.norad_id));
+                            let target = SlewTarget::Satellite {
+                                ra_hours: (ra.to_degrees() / 15.0) as f32,
+                                dec_degrees: dec.to_degrees() as f32,
+                            };
+                            (name, target)
+                        })
+                    });
+            let planet = planets
+                .iter()
+                .filter(|(planet, _)| planet.altitude.is_some_and(|alt| alt > 0.0))
+                .find(|(_, gtf)| near_cursor(gtf.translation()))
+                .map(|(planet, gtf)| {
+                    let (ra, dec) = to_equatorial(gtf.translation() - cam_gtf.translation());
+                    (planet.body.name().to_string(), equatorial_target(ra, dec))
+                });
+
+            menu.position = Some(egui::pos2(cursor.x, cursor.y));
+            menu.here = to_equatorial(ray.direction.as_vec3());
+            menu.object = satellite.or(planet);
+        }
+    }
+
+    let Some(position) = menu.position else {
+        return;
+    };
+
+    let mut close = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+    let area = egui::Area::new(egui::Id::new("slew_menu"))
+        .fixed_pos(position)
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            content_container_frame().show(ui, |ui| {
+                let (ra, dec) = menu.here;
+                let here_hover_id = egui::Id::new("slew_here_button_hover");
+                let label = format!("Slew here ({})", format_ra_dec(ra, dec));
+                if planetarium_menu_button_inner(ui, here_hover_id, label, false).clicked() {
+                    request_slew(equatorial_target(ra, dec));
+                    close = true;
+                }
+
+                if let Some((name, target)) = &menu.object {
+                    let object_hover_id = egui::Id::new("slew_object_button_hover");
+                    let label = format!("Slew to {name}");
+                    if planetarium_menu_button_inner(ui, object_hover_id, label, false).clicked() {
+                        request_slew(*target);
+                        close = true;
+                    }
+                }
+            });
+        });
+
+    if close || area.response.clicked_elsewhere() {
+        menu.position = None;
+    }
+}
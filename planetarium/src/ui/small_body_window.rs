@@ -0,0 +1,280 @@
+use crate::camera::{CameraFollow, CameraTarget};
+use crate::colors;
+use crate::small_bodies::{PendingSmallBodies, SmallBody, SmallBodyError};
+use crate::starfield::StarfieldState;
+use crate::ui::widgets::{
+    content_container_frame, planetarium_button, planetarium_text_input, window_frame,
+};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use overpass_planner::{
+    fetch_mpc_elements, parse_mpc_elements, small_body_position, MpcSubset, ObserverLocation,
+    OrbitalElements,
+};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+
+#[derive(Resource)]
+pub struct SmallBodyWindowState {
+    /// MPCORB or CometEls.txt lines pasted by the user
+    pub elements_input: String,
+    /// Fetched bodies fainter than this are left out
+    pub fetch_limit: String,
+    pub fetch_in_progress: bool,
+    pub error: Option<String>,
+}
+
+impl Default for SmallBodyWindowState {
+    fn default() -> Self {
+        SmallBodyWindowState {
+            elements_input: String::new(),
+            fetch_limit: "12".to_string(),
+            fetch_in_progress: false,
+            error: None,
+        }
+    }
+}
+
+/// Results of background MPC downloads
+#[derive(Resource)]
+pub struct SmallBodyFetchChannel {
+    pub sender: Mutex<Sender<Result<Vec<OrbitalElements>, String>>>,
+    pub receiver: Mutex<Receiver<Result<Vec<OrbitalElements>, String>>>,
+}
+
+impl Default for SmallBodyFetchChannel {
+    fn default() -> Self {
+        let (tx, rx) = channel();
+        Self {
+            sender: Mutex::new(tx),
+            receiver: Mutex::new(rx),
+        }
+    }
+}
+
+/// Download one MPC subset in the background, keeping the bodies currently
+/// brighter than `limit` as seen from `location`
+fn start_fetch(
+    subset: MpcSubset,
+    limit: f64,
+    location: ObserverLocation,
+    now: chrono::DateTime<chrono::Utc>,
+    sender: Sender<Result<Vec<OrbitalElements>, String>>,
+) {
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt.block_on(fetch_mpc_elements(subset)).map(|bodies| {
+            bodies
+                .into_iter()
+                .filter(|elements| {
+                    small_body_position(elements, location, now)
+                        .ok()
+                        .and_then(|p| p.magnitude)
+                        .is_some_and(|m| m <= limit)
+                })
+                .collect()
+        });
+        let _ = sender.send(result.map_err(|e| e.to_string()));
+    });
+}
+
+fn format_value(value: Option<f64>, unit: &str) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{v:.1}{unit}"))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn render_small_body_window(
+    mut commands: Commands,
+    mut window_state: ResMut<SmallBodyWindowState>,
+    fetch_channel: Res<SmallBodyFetchChannel>,
+    mut pending: ResMut<PendingSmallBodies>,
+    position_error: Res<SmallBodyError>,
+    starfield_state: Res<StarfieldState>,
+    bodies: Query<(Entity, &SmallBody)>,
+    mut follow: ResMut<CameraFollow>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    // Handle finished downloads even while the window is closed
+    if let Ok(receiver) = fetch_channel.receiver.lock() {
+        while let Ok(result) = receiver.try_recv() {
+            window_state.fetch_in_progress = false;
+            match result {
+                Ok(found) if found.is_empty() => {
+                    window_state.error = Some("No bodies that bright right now".to_string());
+                }
+                Ok(found) => {
+                    pending.0.extend(found);
+                    window_state.error = None;
+                }
+                Err(e) => window_state.error = Some(e),
+            }
+        }
+    }
+
+    if !menu_state.small_body_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    let mut rows: Vec<(Entity, &SmallBody)> = bodies.iter().collect();
+    rows.sort_by(|a, b| a.1.elements.name.cmp(&b.1.elements.name));
+
+    egui::Window::new(
+        egui::RichText::new("Comets & Asteroids")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.small_body_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            ui.label(
+                egui::RichText::new("MPC elements (MPCORB or CometEls.txt lines):")
+                    .size(12.0)
+                    .color(colors::egui::WINDOW_TITLE_COLOR),
+            );
+            ui.add(
+                egui::TextEdit::multiline(&mut window_state.elements_input)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_rows(3)
+                    .desired_width(420.0),
+            );
+            if planetarium_button(ui, "Add", 80.0, 22.0).clicked() {
+                let parsed = parse_mpc_elements(&window_state.elements_input);
+                if parsed.is_empty() {
+                    window_state.error = Some("No MPC element lines found".to_string());
+                } else {
+                    pending.0.extend(parsed);
+                    window_state.elements_input.clear();
+                    window_state.error = None;
+                }
+            }
+
+            // Download from the MPC, keeping what is bright enough to see
+            ui.horizontal(|ui| {
+                ui.label(
+                    egui::RichText::new("Fetch brighter than mag:")
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+                planetarium_text_input(ui, &mut window_state.fetch_limit, 40.0, 22.0);
+                ui.add_enabled_ui(!window_state.fetch_in_progress, |ui| {
+                    for (label, subset) in [
+                        ("Comets", MpcSubset::Comets),
+                        ("NEAs", MpcSubset::NearEarthAsteroids),
+                    ] {
+                        if !planetarium_button(ui, label, 70.0, 22.0).clicked() {
+                            continue;
+                        }
+                        let Ok(limit) = window_state.fetch_limit.trim().parse::<f64>() else {
+                            window_state.error = Some("Magnitude must be a number".to_string());
+                            continue;
+                        };
+                        let Ok(sender) = fetch_channel.sender.lock() else {
+                            continue;
+                        };
+                        let location = ObserverLocation {
+                            latitude: starfield_state.lat_deg,
+                            longitude: starfield_state.lon_deg,
+                            altitude: starfield_state.alt_m,
+                        };
+                        start_fetch(
+                            subset,
+                            limit,
+                            location,
+                            starfield_state.current_utc(),
+                            sender.clone(),
+                        );
+                        window_state.fetch_in_progress = true;
+                        window_state.error = None;
+                    }
+                });
+            });
+            if window_state.fetch_in_progress {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(
+                        egui::RichText::new("Fetching from the MPC...")
+                            .size(12.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+                });
+            }
+
+            for error in [&window_state.error, &position_error.0]
+                .into_iter()
+                .flatten()
+            {
+                ui.label(
+                    egui::RichText::new(format!("Error: {}", error))
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("small_bodies_table")
+                        .spacing(egui::vec2(8.0, 4.0))
+                        .show(ui, |ui| {
+                            for header in ["Name", "Alt", "Az", "Mag", ""] {
+                                ui.strong(
+                                    egui::RichText::new(header)
+                                        .size(12.0)
+                                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                                );
+                            }
+                            ui.end_row();
+
+                            for (entity, body) in rows {
+                                for cell in [
+                                    body.elements.name.clone(),
+                                    format_value(body.altitude, "°"),
+                                    format_value(body.azimuth, "°"),
+                                    format_value(body.magnitude, ""),
+                                ] {
+                                    ui.label(
+                                        egui::RichText::new(cell)
+                                            .size(12.0)
+                                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                                    );
+                                }
+
+                                let target = CameraTarget::Entity(entity);
+                                ui.horizontal(|ui| {
+                                    ui.add_enabled_ui(body.altitude.is_some(), |ui| {
+                                        if planetarium_button(ui, "Center", 60.0, 20.0).clicked() {
+                                            follow.center(target);
+                                        }
+                                        let tracking = follow.is_tracking(target);
+                                        let label = if tracking { "Stop" } else { "Track" };
+                                        if planetarium_button(ui, label, 60.0, 20.0).clicked() {
+                                            if tracking {
+                                                follow.stop();
+                                            } else {
+                                                follow.track(target);
+                                            }
+                                        }
+                                    });
+                                    if planetarium_button(ui, "Remove", 60.0, 20.0).clicked() {
+                                        if follow.target == Some(target) {
+                                            follow.stop();
+                                        }
+                                        commands.entity(entity).despawn();
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+    });
+}
@@ -0,0 +1,89 @@
+use crate::all_sky::AllSkyView;
+use crate::colors;
+use crate::starfield::{
+    equatorial_from_direction, julian_date, local_sidereal_time, StarfieldState,
+};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use chrono::Local;
+use std::f64::consts::PI;
+
+/// "05h 12m 34s" for an angle in radians
+fn format_hours(radians: f64) -> String {
+    let seconds = (radians.rem_euclid(2.0 * PI).to_degrees() / 15.0 * 3600.0).round() as i64;
+    format!(
+        "{:02}h {:02}m {:02}s",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// "+20° 30' 15\"" for an angle in radians
+fn format_degrees(radians: f64) -> String {
+    let arcsec = (radians.to_degrees() * 3600.0).round() as i64;
+    format!(
+        "{}{:02}° {:02}' {:02}\"",
+        if arcsec < 0 { '-' } else { '+' },
+        arcsec.abs() / 3600,
+        arcsec.abs() / 60 % 60,
+        arcsec.abs() % 60
+    )
+}
+
+/// Bar along the bottom with the sky's time and where the view is pointed,
+/// for setting circles and manual mount operations
+pub fn render_status_bar(
+    state: Res<StarfieldState>,
+    all_sky: Res<AllSkyView>,
+    mut camera_query: Query<(&mut EguiContext, &Transform), With<Camera3d>>,
+) {
+    let Ok((mut egui_context, cam_tf)) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    let now = state.current_utc();
+    let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+    let mut fields = vec![
+        format!("LST {}", format_hours(local_sidereal_time(now, lon))),
+        format!("JD {:.5}", julian_date(now)),
+        format!("UTC {}", now.format("%Y-%m-%d %H:%M:%S")),
+        format!("Local {}", now.with_timezone(&Local).format("%H:%M:%S")),
+    ];
+
+    // The world frame is the observer's horizon at the simulated time; the
+    // all-sky chart has no single center worth reporting
+    if !all_sky.active {
+        let center = cam_tf.forward().as_vec3();
+        let alt = (center.y as f64).clamp(-1.0, 1.0).asin().to_degrees();
+        let az = (center.x as f64)
+            .atan2(-center.z as f64)
+            .to_degrees()
+            .rem_euclid(360.0);
+        let (ra, dec) = equatorial_from_direction(now, lat, lon, center);
+        fields.push(format!("Alt {alt:.2}°  Az {az:.2}°"));
+        fields.push(format!(
+            "RA {}  Dec {}",
+            format_hours(ra),
+            format_degrees(dec)
+        ));
+    }
+
+    egui::TopBottomPanel::bottom("status_bar")
+        .exact_height(22.0)
+        .show(ctx, |ui| {
+            ui.with_layout(egui::Layout::left_to_right(egui::Align::Center), |ui| {
+                for (i, field) in fields.into_iter().enumerate() {
+                    if i > 0 {
+                        ui.separator();
+                    }
+                    ui.label(
+                        egui::RichText::new(field)
+                            .size(12.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    );
+                }
+            });
+        });
+}
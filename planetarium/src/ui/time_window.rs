@@ -0,0 +1,134 @@
+use crate::colors;
+use crate::starfield::StarfieldState;
+use crate::twilight::Twilight;
+use crate::ui::widgets::{
+    content_container_frame, planetarium_button, planetarium_text_input, window_frame,
+};
+use bevy::prelude::*;
+use bevy_egui::egui;
+use chrono::{NaiveDateTime, Utc};
+
+/// Format accepted by the "Set" field
+const INPUT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Simulation speeds offered as buttons
+const RATES: [f64; 3] = [1.0, 10.0, 100.0];
+
+#[derive(Resource, Default)]
+pub struct TimeControlState {
+    pub time_input: String,
+    pub error: Option<String>,
+}
+
+pub fn render_time_window(
+    mut time_state: ResMut<TimeControlState>,
+    mut starfield_state: ResMut<StarfieldState>,
+    twilight: Res<Twilight>,
+    mut menu_state: ResMut<crate::ui::MenuState>,
+    mut camera_query: Query<&mut bevy_egui::EguiContext, With<bevy::prelude::Camera3d>>,
+) {
+    if !menu_state.time_window_open {
+        return;
+    }
+
+    let Ok(mut egui_context) = camera_query.single_mut() else {
+        return;
+    };
+    let ctx = egui_context.get_mut();
+
+    // Reads go through Deref, so the state is only marked changed by the buttons below
+    let now = starfield_state.current_utc();
+    let paused = starfield_state.paused;
+    let time_rate = starfield_state.time_rate;
+
+    egui::Window::new(
+        egui::RichText::new("Time Control")
+            .size(14.0)
+            .color(colors::egui::WINDOW_TITLE_COLOR),
+    )
+    .collapsible(false)
+    .resizable(false)
+    .frame(window_frame(ctx))
+    .open(&mut menu_state.time_window_open)
+    .show(ctx, |ui| {
+        content_container_frame().show(ui, |ui| {
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} UTC{}",
+                    now.format(INPUT_FORMAT),
+                    if paused {
+                        " (paused)".to_string()
+                    } else {
+                        format!(" ({time_rate}x)")
+                    }
+                ))
+                .size(14.0)
+                .color(colors::egui::WINDOW_TITLE_COLOR),
+            );
+            ui.label(
+                egui::RichText::new(format!(
+                    "Sun {:.1}°, {}",
+                    twilight.sun_altitude,
+                    twilight.phase()
+                ))
+                .size(12.0)
+                .color(colors::egui::WINDOW_TITLE_COLOR),
+            );
+
+            ui.horizontal(|ui| {
+                let label = if paused { "Resume" } else { "Pause" };
+                if planetarium_button(ui, label, 70.0, 22.0).clicked() {
+                    starfield_state.set_paused(!paused);
+                }
+                for rate in RATES {
+                    if planetarium_button(ui, format!("{rate}x"), 50.0, 22.0).clicked() {
+                        starfield_state.set_time_rate(rate);
+                    }
+                }
+                if planetarium_button(ui, "Now", 50.0, 22.0).clicked() {
+                    starfield_state.set_time(Utc::now());
+                    starfield_state.set_time_rate(1.0);
+                    starfield_state.set_paused(false);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add_sized(
+                    egui::vec2(0.0, 22.0),
+                    egui::Label::new(
+                        egui::RichText::new("UTC:")
+                            .size(12.0)
+                            .color(colors::egui::WINDOW_TITLE_COLOR),
+                    ),
+                );
+                if time_state.time_input.is_empty() {
+                    time_state.time_input = now.format(INPUT_FORMAT).to_string();
+                }
+                planetarium_text_input(ui, &mut time_state.time_input, 160.0, 22.0);
+                if planetarium_button(ui, "Set", 50.0, 22.0).clicked() {
+                    match NaiveDateTime::parse_from_str(time_state.time_input.trim(), INPUT_FORMAT)
+                    {
+                        Ok(time) => {
+                            starfield_state.set_time(time.and_utc());
+                            time_state.error = None;
+                        }
+                        Err(_) => {
+                            time_state.error = Some("Expected YYYY-MM-DD HH:MM:SS".to_string());
+                        }
+                    }
+                }
+            });
+
+            if let Some(error) = &time_state.error {
+                ui.label(
+                    egui::RichText::new(format!("Error: {}", error))
+                        .size(12.0)
+                        .color(colors::egui::WINDOW_TITLE_COLOR),
+                );
+            }
+        });
+    });
+
+    // keep the clock ticking while the window is open
+    ctx.request_repaint();
+}
@@ -2,8 +2,10 @@ pub(crate) mod content_container;
 pub(crate) mod planetarium_button;
 pub(crate) mod planetarium_buttons;
 pub(crate) mod planetarium_text_input;
+pub(crate) mod window;
 
 pub use content_container::content_container_frame;
 pub use planetarium_button::planetarium_button;
 pub use planetarium_buttons::planetarium_menu_button;
 pub use planetarium_text_input::planetarium_text_input;
+pub use window::{field_label, window_frame};
@@ -0,0 +1,37 @@
+use crate::colors;
+use bevy_egui::egui;
+
+/// Frame shared by the planetarium's floating windows: the window background
+/// with 4pt padding, so content containers (4pt outer margin) sit 8pt from
+/// the window edges.
+///
+/// Usage:
+/// ```rust
+/// egui::Window::new("Title").frame(window_frame(ctx)).show(ctx, |ui| {
+///     // content_container_frame() sections here
+/// });
+/// ```
+pub fn window_frame(ctx: &egui::Context) -> egui::Frame {
+    let mut frame = egui::Frame::window(&ctx.style());
+    frame.fill = colors::egui::WINDOW_BACKGROUND;
+    frame.inner_margin = egui::Margin {
+        left: 4,
+        right: 4,
+        top: 4,
+        bottom: 4,
+    };
+    frame
+}
+
+/// Label in front of a window's input field, `width` wide so the fields of
+/// one window line up
+pub fn field_label(ui: &mut egui::Ui, text: &str, width: f32) {
+    ui.add_sized(
+        egui::vec2(width, 22.0),
+        egui::Label::new(
+            egui::RichText::new(text)
+                .size(12.0)
+                .color(colors::egui::WINDOW_TITLE_COLOR),
+        ),
+    );
+}
@@ -0,0 +1,189 @@
+// src/view_state.rs
+
+use bevy::prelude::*;
+use std::fs;
+
+use crate::{
+    all_sky::AllSkyView,
+    camera::{
+        setup_camera, CameraFollow, CameraTarget, RotatingCamera, MAX_FOV, MAX_PITCH, MIN_FOV,
+    },
+    frame_rate::FrameRateSettings,
+    night_vision::NightVision,
+    planets::{spawn_planets, Planet},
+    site::config_file,
+    starfield::SkyLayers,
+};
+
+/// Saved when the window closes and restored on the next launch, so the view
+/// survives the GUI restarting the planetarium
+const VIEW_FILE: &str = "planetarium_view.txt";
+
+pub struct ViewStatePlugin;
+impl Plugin for ViewStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Startup,
+            restore_view.after(setup_camera).after(spawn_planets),
+        )
+        .add_systems(Last, save_view_on_exit);
+    }
+}
+
+/// `target` line for the last tracked object. The mount isn't saved: it only
+/// appears once the GUI reports its position.
+fn target_line(target: CameraTarget, planets: &Query<&Planet>) -> Option<String> {
+    match target {
+        CameraTarget::Equatorial { ra, dec } => Some(format!("target radec {ra} {dec}")),
+        CameraTarget::Entity(entity) => planets
+            .get(entity)
+            .ok()
+            .map(|planet| format!("target planet {}", planet.body.name())),
+        // a followed pass is over long before the next start
+        CameraTarget::Horizontal { .. } => None,
+    }
+}
+
+/// Resolve a saved `target` line's fields back to something to track
+fn parse_target(fields: &[&str], planets: &Query<(Entity, &Planet)>) -> Option<CameraTarget> {
+    match fields {
+        ["radec", ra, dec] => Some(CameraTarget::Equatorial {
+            ra: ra.parse().ok()?,
+            dec: dec.parse().ok()?,
+        }),
+        ["planet", name] => planets
+            .iter()
+            .find(|(_, planet)| planet.body.name() == *name)
+            .map(|(entity, _)| CameraTarget::Entity(entity)),
+        _ => None,
+    }
+}
+
+/// Write the view out as `key value` lines when the app is closing
+#[allow(clippy::too_many_arguments)]
+fn save_view_on_exit(
+    mut exit: MessageReader<AppExit>,
+    layers: Res<SkyLayers>,
+    all_sky: Res<AllSkyView>,
+    night_vision: Res<NightVision>,
+    frame_rate: Res<FrameRateSettings>,
+    follow: Res<CameraFollow>,
+    planets: Query<&Planet>,
+    cam_q: Query<(&RotatingCamera, &Projection), With<Camera3d>>,
+) {
+    if exit.read().next().is_none() {
+        return;
+    }
+    let Ok((rc, Projection::Perspective(persp))) = cam_q.single() else {
+        return;
+    };
+    let Some(path) = config_file(VIEW_FILE) else {
+        return;
+    };
+
+    let mut lines = vec![
+        format!("yaw {}", rc.yaw),
+        format!("pitch {}", rc.pitch),
+        format!("fov {}", persp.fov),
+        format!("constellation_lines {}", layers.constellation_lines),
+        format!("milky_way {}", layers.milky_way),
+        format!("meteor_radiants {}", layers.meteor_radiants),
+        format!("labels {}", layers.labels),
+        format!("dso_markers {}", layers.dso_markers),
+        format!("cardinal_points {}", layers.cardinal_points),
+        format!("meridian {}", layers.meridian),
+        format!("slew_limits {}", layers.slew_limits),
+        format!("sky_brightness {}", layers.sky_brightness),
+        format!("all_satellites {}", layers.all_satellites),
+        format!("dso_magnitude {}", layers.dso_magnitude),
+        format!("label_magnitude {}", layers.label_magnitude),
+        format!("limiting_magnitude {}", layers.limiting_magnitude),
+        format!("night_vision {}", night_vision.0),
+        format!("all_sky {}", all_sky.active),
+        format!("tracking {}", follow.tracking),
+        format!("max_fps {}", frame_rate.max_fps),
+        format!("power_saving {}", frame_rate.power_saving),
+    ];
+    lines.extend(
+        follow
+            .last_tracked
+            .and_then(|target| target_line(target, &planets)),
+    );
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, lines.join("\n") + "\n"));
+    if let Err(e) = result {
+        eprintln!("Failed to save view to {}: {e}", path.display());
+    }
+}
+
+/// Apply the saved view, if any. Unknown or malformed lines are skipped so an
+/// older or hand-edited file still restores what it can.
+fn restore_view(
+    mut layers: ResMut<SkyLayers>,
+    mut all_sky: ResMut<AllSkyView>,
+    mut night_vision: ResMut<NightVision>,
+    mut frame_rate: ResMut<FrameRateSettings>,
+    mut follow: ResMut<CameraFollow>,
+    planets: Query<(Entity, &Planet)>,
+    mut cam_q: Query<(&mut RotatingCamera, &mut Transform, &mut Projection), With<Camera3d>>,
+) {
+    let Some(data) = config_file(VIEW_FILE).and_then(|path| fs::read_to_string(path).ok()) else {
+        return;
+    };
+    let Ok((mut rc, mut t, mut projection)) = cam_q.single_mut() else {
+        return;
+    };
+    let Projection::Perspective(ref mut persp) = *projection else {
+        return;
+    };
+
+    let mut tracking = false;
+    for line in data.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [key, rest @ ..] = fields.as_slice() else {
+            continue;
+        };
+        let number = || rest.first().and_then(|v| v.parse::<f32>().ok());
+        let flag = || rest.first().and_then(|v| v.parse::<bool>().ok());
+        match *key {
+            "yaw" => rc.yaw = number().unwrap_or(rc.yaw),
+            "pitch" => rc.pitch = number().map_or(rc.pitch, |p| p.clamp(-MAX_PITCH, MAX_PITCH)),
+            "fov" => persp.fov = number().map_or(persp.fov, |f| f.clamp(MIN_FOV, MAX_FOV)),
+            "constellation_lines" => {
+                layers.constellation_lines = flag().unwrap_or(layers.constellation_lines)
+            }
+            "milky_way" => layers.milky_way = flag().unwrap_or(layers.milky_way),
+            "meteor_radiants" => layers.meteor_radiants = flag().unwrap_or(layers.meteor_radiants),
+            "labels" => layers.labels = flag().unwrap_or(layers.labels),
+            "dso_markers" => layers.dso_markers = flag().unwrap_or(layers.dso_markers),
+            "cardinal_points" => layers.cardinal_points = flag().unwrap_or(layers.cardinal_points),
+            "meridian" => layers.meridian = flag().unwrap_or(layers.meridian),
+            "slew_limits" => layers.slew_limits = flag().unwrap_or(layers.slew_limits),
+            "sky_brightness" => layers.sky_brightness = flag().unwrap_or(layers.sky_brightness),
+            "all_satellites" => layers.all_satellites = flag().unwrap_or(layers.all_satellites),
+            "dso_magnitude" => layers.dso_magnitude = number().unwrap_or(layers.dso_magnitude),
+            "label_magnitude" => {
+                layers.label_magnitude = number().unwrap_or(layers.label_magnitude)
+            }
+            "limiting_magnitude" => {
+                layers.limiting_magnitude = number().unwrap_or(layers.limiting_magnitude)
+            }
+            "night_vision" => night_vision.0 = flag().unwrap_or(night_vision.0),
+            "all_sky" => all_sky.active = flag().unwrap_or(all_sky.active),
+            "tracking" => tracking = flag().unwrap_or(false),
+            "max_fps" => frame_rate.max_fps = number().map_or(frame_rate.max_fps, |f| f as u32),
+            "power_saving" => frame_rate.power_saving = flag().unwrap_or(frame_rate.power_saving),
+            "target" => follow.last_tracked = parse_target(rest, &planets),
+            _ => {}
+        }
+    }
+    t.rotation = Quat::from_euler(EulerRot::YXZ, rc.yaw, rc.pitch, 0.0);
+
+    // a target that wasn't being tracked at exit is left for Space to resume
+    if let (true, Some(target)) = (tracking, follow.last_tracked) {
+        follow.track(target);
+    }
+}
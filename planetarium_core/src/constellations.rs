@@ -0,0 +1,59 @@
+//! Constellation figure module.
+//!
+//! The bundled stick figures, as segments between BSC5 stars by HR number.
+
+use std::io::{self, Error, ErrorKind};
+
+/// Stick figures for the well-known constellations, bundled with the crate.
+pub const CONSTELLATION_LINES: &str = include_str!("../assets/constellation_lines.csv");
+
+/// one constellation figure
+#[derive(Debug, Clone)]
+pub struct ConstellationFigure {
+    pub name: String,
+    /// Line segments between two stars, by BSC5 HR number
+    pub segments: Vec<(u32, u32)>,
+}
+
+/// Parse `Abbr;Name;Polylines` rows, where polylines are `|`-separated lists of
+/// space-separated HR numbers.
+pub fn parse_constellation_lines(data: &str) -> io::Result<Vec<ConstellationFigure>> {
+    let invalid = |line: &str| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("bad constellation line: {line}"),
+        )
+    };
+
+    let mut figures = Vec::new();
+    // skip comments and the header row
+    for line in data
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .skip(1)
+    {
+        let mut fields = line.split(';');
+        // the IAU abbreviation column is only there for people editing the file
+        let (Some(_abbreviation), Some(name), Some(polylines)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            return Err(invalid(line));
+        };
+
+        let mut segments = Vec::new();
+        for polyline in polylines.split('|') {
+            let stars = polyline
+                .split_whitespace()
+                .map(|hr| hr.parse::<u32>().map_err(|_| invalid(line)))
+                .collect::<io::Result<Vec<_>>>()?;
+            segments.extend(stars.windows(2).map(|pair| (pair[0], pair[1])));
+        }
+
+        figures.push(ConstellationFigure {
+            name: name.trim().to_string(),
+            segments,
+        });
+    }
+
+    Ok(figures)
+}
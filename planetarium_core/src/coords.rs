@@ -0,0 +1,111 @@
+//! Coordinate transform module.
+//!
+//! Conversions between RA/Dec, hour angle, altitude/azimuth and directions in
+//! the local horizon frame the sky is drawn in: X east, Y up and Z south, so a
+//! camera looking down –Z faces north. Angles are radians unless a name or doc
+//! says degrees.
+
+use bevy::math::Vec3;
+use chrono::{DateTime, Utc};
+use std::f64::consts::PI;
+
+/// Sidereal rate: 2π radians per 86 164.0905 s
+pub const SIDEREAL_RATE: f64 = 2.0 * PI / 86_164.090_5;
+
+/// Compute the Julian Date (JD) from a UTC time.
+pub fn julian_date(time: DateTime<Utc>) -> f64 {
+    let unix = time.timestamp() as f64;
+    let sub = time.timestamp_subsec_nanos() as f64 * 1e-9;
+    2440587.5 + (unix + sub) / 86400.0
+}
+
+/// Local sidereal time (radians) at longitude `lon` (radians)
+pub fn local_sidereal_time(time: DateTime<Utc>, lon: f64) -> f64 {
+    let jd = julian_date(time);
+    let t = (jd - 2451545.0) / 36525.0;
+    let gmst = (280.46061837 + 360.98564736629 * (jd - 2451545.0) + 0.000387933 * t * t
+        - t * t * t / 38710000.0)
+        .rem_euclid(360.0)
+        .to_radians();
+    (gmst + lon).rem_euclid(2.0 * PI)
+}
+
+/// RA/Dec → local‐horizon unit vector (X=east, Y=up, Z=north)
+pub fn star_direction(time: DateTime<Utc>, lat: f64, lon: f64, ra: f64, dec: f64) -> Vec3 {
+    let lst = local_sidereal_time(time, lon);
+    let ha = (lst - ra).rem_euclid(2.0 * PI);
+
+    let east = dec.cos() * ha.sin();
+    let north = dec.cos() * ha.cos() * lat.sin() - dec.sin() * lat.cos();
+    let up = dec.cos() * ha.cos() * lat.cos() + dec.sin() * lat.sin();
+
+    // Bevy: X=east, Y=up, Z=–north  (so "forward" is towards the sky)
+    Vec3::new(east as f32, up as f32, -north as f32).normalize()
+}
+
+/// Local‐horizon direction → (ra, dec) in radians; the inverse of `star_direction`
+pub fn equatorial_from_direction(time: DateTime<Utc>, lat: f64, lon: f64, dir: Vec3) -> (f64, f64) {
+    let (ha, dec) = hour_angle_declination(lat, dir);
+    let ra = (local_sidereal_time(time, lon) - ha).rem_euclid(2.0 * PI);
+    (ra, dec)
+}
+
+/// Local‐horizon direction → (hour angle, dec) in radians, which unlike RA
+/// don't change with time
+pub fn hour_angle_declination(lat: f64, dir: Vec3) -> (f64, f64) {
+    let dir = dir.normalize();
+    let (east, up, north) = (dir.x as f64, dir.y as f64, -dir.z as f64);
+    let dec = (up * lat.sin() - north * lat.cos()).clamp(-1.0, 1.0).asin();
+    let ha = east.atan2(up * lat.cos() + north * lat.sin());
+    (ha, dec)
+}
+
+/// (altitude, azimuth) in degrees → (hour angle, dec) in radians at latitude
+/// `lat` (radians), azimuth measured from north through east
+pub fn hour_angle_from_horizontal(lat: f64, altitude: f64, azimuth: f64) -> (f64, f64) {
+    let (alt, az) = (altitude.to_radians(), azimuth.to_radians());
+    let dec = (alt.sin() * lat.sin() + alt.cos() * lat.cos() * az.cos())
+        .clamp(-1.0, 1.0)
+        .asin();
+    let ha =
+        (-az.sin() * alt.cos()).atan2(alt.sin() * lat.cos() - alt.cos() * lat.sin() * az.cos());
+    (ha, dec)
+}
+
+/// (hour angle, dec) in radians → (altitude, azimuth) in degrees at latitude
+/// `lat` (radians); the inverse of `hour_angle_from_horizontal`
+pub fn horizontal_from_hour_angle(lat: f64, ha: f64, dec: f64) -> (f64, f64) {
+    let alt = (lat.sin() * dec.sin() + lat.cos() * dec.cos() * ha.cos())
+        .clamp(-1.0, 1.0)
+        .asin();
+    let az =
+        (-dec.cos() * ha.sin()).atan2(dec.sin() * lat.cos() - dec.cos() * lat.sin() * ha.cos());
+    (alt.to_degrees(), az.to_degrees().rem_euclid(360.0))
+}
+
+/// RA/Dec → (altitude, azimuth) in degrees, azimuth measured from north through east
+pub fn horizontal_coordinates(
+    time: DateTime<Utc>,
+    lat_deg: f64,
+    lon_deg: f64,
+    ra: f64,
+    dec: f64,
+) -> (f64, f64) {
+    let dir = star_direction(time, lat_deg.to_radians(), lon_deg.to_radians(), ra, dec);
+    let alt = (dir.y as f64).clamp(-1.0, 1.0).asin().to_degrees();
+    let az = (dir.x as f64)
+        .atan2(-dir.z as f64)
+        .to_degrees()
+        .rem_euclid(360.0);
+    (alt, az)
+}
+
+/// Alt/Az (degrees) → local‐horizon unit vector (X=east, Y=up, Z=–north)
+pub fn alt_az_direction(altitude: f64, azimuth: f64) -> Vec3 {
+    let (alt, az) = (altitude.to_radians(), azimuth.to_radians());
+    Vec3::new(
+        (alt.cos() * az.sin()) as f32,
+        alt.sin() as f32,
+        -(alt.cos() * az.cos()) as f32,
+    )
+}
@@ -0,0 +1,78 @@
+//! Planetarium Core
+//!
+//! The star catalogs, coordinate transforms and sky rendering behind the
+//! planetarium, as a library so other tools can draw the same sky.
+//!
+//! # Plugin API
+//!
+//! - [`sky::SkyPlugin`] keeps the [`sky::StarfieldRoot`] entity on the camera
+//!   and turned for the simulated time in the [`sky::StarfieldState`]
+//!   resource. Change the site and time through that resource.
+//! - [`scene::ScenePlugin`] adds the light the meshes are lit by.
+//!
+//! The app spawns the root itself and puts what should turn with the sky
+//! under it, placed for the state's `spawn_utc`. For stars, read a catalog
+//! with [`star_catalog`], group it with [`star_cloud::split_into_tiles`] and
+//! spawn one [`star_cloud::StarChunk`] mesh per tile:
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use chrono::Utc;
+//! use planetarium_core::{
+//!     scene::ScenePlugin,
+//!     sky::{SkyPlugin, StarfieldRoot, StarfieldState},
+//!     star_catalog::parse_catalog,
+//!     star_cloud::{split_into_tiles, star_material, CloudStar, StarChunk, DEFAULT_COLOR_INDEX},
+//! };
+//!
+//! fn spawn_sky(
+//!     mut commands: Commands,
+//!     mut meshes: ResMut<Assets<Mesh>>,
+//!     mut mats: ResMut<Assets<StandardMaterial>>,
+//!     assets: Res<AssetServer>,
+//! ) {
+//!     let state = StarfieldState::new(Utc::now(), 51.48, 0.0, 0.0);
+//!     let (lat, lon) = (state.lat_deg.to_radians(), state.lon_deg.to_radians());
+//!     let (_hdr, stars) = parse_catalog("assets/BSC5".into()).unwrap();
+//!     let material = star_material(&mut mats, &assets.load("star.png"));
+//!
+//!     commands.spawn(Camera3d::default());
+//!     let root = commands
+//!         .spawn((Transform::default(), Visibility::default(), StarfieldRoot))
+//!         .id();
+//!     let tiles = split_into_tiles(stars.iter().map(|star| CloudStar {
+//!         ra: star.ra,
+//!         dec: star.dec,
+//!         magnitude: star.magnitudes[0],
+//!         color_index: star.color_index().unwrap_or(DEFAULT_COLOR_INDEX),
+//!     }));
+//!     commands.entity(root).with_children(|p| {
+//!         for tile in tiles {
+//!             let chunk = StarChunk { stars: tile };
+//!             let mesh = chunk.mesh(6.5, state.spawn_utc, lat, lon);
+//!             p.spawn((
+//!                 Mesh3d(meshes.add(mesh)),
+//!                 MeshMaterial3d(material.clone()),
+//!                 chunk,
+//!             ));
+//!         }
+//!     });
+//!     commands.insert_resource(state);
+//! }
+//!
+//! App::new()
+//!     .add_plugins((DefaultPlugins, SkyPlugin, ScenePlugin))
+//!     .add_systems(Startup, spawn_sky)
+//!     .run();
+//! ```
+//!
+//! The [`coords`] functions place anything else: planets and satellites by
+//! altitude/azimuth with [`coords::alt_az_direction`], fixed objects by RA/Dec
+//! with [`coords::star_direction`].
+
+pub mod constellations;
+pub mod coords;
+pub mod scene;
+pub mod sky;
+pub mod star_catalog;
+pub mod star_cloud;
@@ -0,0 +1,29 @@
+//! Scene module.
+//!
+//! The light the planetarium's meshes are lit by.
+
+use bevy::prelude::*;
+
+/// Spawns the directional light at startup
+pub struct ScenePlugin;
+
+impl Plugin for ScenePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_ground);
+    }
+}
+
+fn spawn_ground(mut commands: Commands) {
+    // Directional light (the ground itself is in horizon.rs, and the compass
+    // points are painted by compass.rs)
+    commands.spawn((
+        DirectionalLight {
+            illuminance: 10_000.0,
+            shadows_enabled: true,
+            ..default()
+        },
+        Transform::from_translation(Vec3::new(0.0, 100.0, 0.0))
+            .with_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_4)),
+        Visibility::default(),
+    ));
+}
@@ -0,0 +1,160 @@
+//! Sky clock module.
+//!
+//! [`StarfieldState`] holds the observer's site and the simulated time, and
+//! [`SkyPlugin`] turns everything under the [`StarfieldRoot`] entity with it.
+//! Children of the root are placed once, at their positions for `spawn_utc`,
+//! and the root is rotated about the celestial pole from then on, so nothing
+//! is recomputed per star while time runs.
+
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+use std::{f64::consts::PI, time::Instant};
+
+use crate::coords::SIDEREAL_RATE;
+
+/// Marker on the root entity
+#[derive(Component)]
+pub struct StarfieldRoot;
+
+#[derive(Resource)]
+pub struct StarfieldState {
+    /// When we first spawned (the RA/Dec→horizon positions were for this UTC)
+    pub spawn_utc: DateTime<Utc>,
+
+    /// Our last "time override" instant, for smooth rotation updates
+    pub base_instant: Instant,
+    /// Simulated UTC at base_instant
+    pub base_utc: DateTime<Utc>,
+    /// Simulated seconds per real second (1.0 = real time)
+    pub time_rate: f64,
+    /// Whether simulated time is frozen at base_utc
+    pub paused: bool,
+
+    /// Observer latitude & longitude (degrees)
+    pub lat_deg: f64,
+    pub lon_deg: f64,
+    /// Observer altitude (meters above sea level)
+    pub alt_m: f64,
+
+    /// Rotation axis in local horizon coords (unit Vec3)
+    pub axis: Vec3,
+}
+
+impl Default for StarfieldState {
+    fn default() -> Self {
+        // replaced by whoever spawns the root, once the site is known
+        StarfieldState::new(Utc::now(), 0.0, 0.0, 0.0)
+    }
+}
+
+/// The celestial pole in local horizon coords at latitude `lat_deg`
+fn polar_axis(lat_deg: f64) -> Vec3 {
+    let lr = lat_deg.to_radians();
+    Vec3::new(0.0, lr.sin() as f32, lr.cos() as f32)
+}
+
+impl StarfieldState {
+    /// A sky spawned at `time` for the given site, running in real time
+    pub fn new(time: DateTime<Utc>, lat_deg: f64, lon_deg: f64, alt_m: f64) -> Self {
+        StarfieldState {
+            spawn_utc: time,
+            base_instant: Instant::now(),
+            base_utc: time,
+            time_rate: 1.0,
+            paused: false,
+            lat_deg,
+            lon_deg,
+            alt_m,
+            axis: polar_axis(lat_deg),
+        }
+    }
+
+    /// Move the observer. Children of the root keep their positions for the
+    /// old site until they are rebuilt.
+    pub fn set_site(&mut self, lat_deg: f64, lon_deg: f64, alt_m: f64) {
+        self.lat_deg = lat_deg;
+        self.lon_deg = lon_deg;
+        self.alt_m = alt_m;
+        self.axis = polar_axis(lat_deg);
+    }
+
+    /// The UTC instant the sky is currently showing
+    pub fn current_utc(&self) -> DateTime<Utc> {
+        if self.paused {
+            return self.base_utc;
+        }
+        let seconds = self.base_instant.elapsed().as_secs_f64() * self.time_rate;
+        self.base_utc + chrono::Duration::microseconds((seconds * 1e6) as i64)
+    }
+
+    /// How far (radians) the sky has turned since spawn_utc
+    pub fn angle(&self) -> f32 {
+        let elapsed = self.current_utc() - self.spawn_utc;
+        let seconds = elapsed.num_milliseconds() as f64 * 1e-3;
+        // in f64 so long jumps don't lose f32 precision
+        (SIDEREAL_RATE * seconds).rem_euclid(2.0 * PI) as f32
+    }
+
+    /// Jump simulated time to `time`, keeping the current rate
+    pub fn set_time(&mut self, time: DateTime<Utc>) {
+        self.base_utc = time;
+        self.base_instant = Instant::now();
+    }
+
+    /// Run simulated time at `rate` times real time from now on
+    pub fn set_time_rate(&mut self, rate: f64) {
+        let now = self.current_utc();
+        self.set_time(now);
+        self.time_rate = rate;
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        let now = self.current_utc();
+        self.set_time(now);
+        self.paused = paused;
+    }
+}
+
+/// Keeps the [`StarfieldRoot`] centered on the camera and turned to the
+/// simulated time. Spawning the root and its children is up to the app.
+pub struct SkyPlugin;
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        // per-frame with ordering: follow_cam → rotate
+        app.init_resource::<StarfieldState>().add_systems(
+            Update,
+            (
+                starfield_follow_camera,
+                rotate_starfield_system.after(starfield_follow_camera),
+            ),
+        );
+    }
+}
+
+/// Each frame: rotate the root by the sidereal angle since spawn at the simulated time
+pub fn rotate_starfield_system(
+    state: Res<StarfieldState>,
+    mut q: Query<&mut Transform, With<StarfieldRoot>>,
+) {
+    let angle = state.angle();
+    for mut tf in &mut q {
+        tf.rotation = Quat::from_axis_angle(state.axis, -angle);
+    }
+}
+
+/// Keep the root positioned at the camera
+pub fn starfield_follow_camera(
+    cam_q: Query<&GlobalTransform, With<Camera>>,
+    mut star_q: Query<&mut Transform, With<StarfieldRoot>>,
+) {
+    // Try to grab exactly one camera; if it's not there yet, just return.
+    let cam_tf = match cam_q.single() {
+        Ok(tf) => tf,
+        Err(_) => return, // no camera spawned yet
+    };
+
+    // Now propagate its position to all StarfieldRoot entities:
+    for mut star_tf in star_q.iter_mut() {
+        star_tf.translation = cam_tf.translation();
+    }
+}
@@ -0,0 +1,384 @@
+//! Star catalog module.
+//!
+//! Readers for the Yale Bright Star Catalogue (BSC5) binary format, Tycho-2
+//! records and plain `ra,dec,magnitude` text files, plus the bundled proper
+//! names of the bright stars.
+
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Error, ErrorKind, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+/// Proper names of bright stars, bundled with the crate.
+pub const STAR_NAMES: &str = include_str!("../assets/star_names.csv");
+
+/// Epoch flag inferred if either `starn` or `nmag` is negative.
+#[derive(Debug, Clone, Copy)]
+pub enum Epoch {
+    B1950,
+    J2000,
+}
+
+#[derive(Debug)]
+pub struct CatalogHeader {
+    pub star0: i32,
+    pub _star1: i32,
+    pub _starn: i32,
+    pub stnum: i32,
+    pub mprop: i32,
+    pub nmag: i32,
+    pub nbent: i32,
+    pub _epoch: Epoch,
+}
+
+#[derive(Debug)]
+pub enum StarId {
+    /// stored as a f32
+    Real(f32),
+    /// stored as an i32
+    Integer(i32),
+}
+
+/// one star entry
+#[derive(Debug)]
+pub struct StarEntry {
+    pub _sequence: i32,        // = raw_id - star0
+    pub id: Option<StarId>,    // None if stnum == 0 or stnum < 0
+    pub ra: f64,               // radians
+    pub dec: f64,              // radians
+    pub spectral_type: String, // 2‐char ASCII
+    pub magnitudes: Vec<f32>,  // each = raw_mag / 100.0
+    pub _proper_motion_ra: Option<f32>,
+    pub _proper_motion_dec: Option<f32>,
+    pub _radial_velocity: Option<f64>,
+    pub _name: Option<String>, // only if stnum < 0
+}
+
+impl StarEntry {
+    /// B−V estimated from the spectral type
+    pub fn color_index(&self) -> Option<f32> {
+        color_index_from_spectral_type(&self.spectral_type)
+    }
+
+    /// Catalog number, i.e. the HR number for the Yale Bright Star Catalogue
+    pub fn catalog_number(&self) -> Option<u32> {
+        match self.id {
+            Some(StarId::Real(f)) => Some(f as u32),
+            Some(StarId::Integer(i)) => u32::try_from(i).ok(),
+            None => None,
+        }
+    }
+}
+
+pub fn parse_catalog(path: PathBuf) -> io::Result<(CatalogHeader, Vec<StarEntry>)> {
+    let f = File::open(path)?;
+    let file_len = f.metadata()?.len();
+    let mut reader = BufReader::new(f);
+
+    // 1) Read header
+    let star0 = reader.read_i32::<LittleEndian>()?;
+    let _star1 = reader.read_i32::<LittleEndian>()?;
+    let _starn = reader.read_i32::<LittleEndian>()?;
+    let stnum = reader.read_i32::<LittleEndian>()?;
+    let mprop = reader.read_i32::<LittleEndian>()?;
+    let nmag_raw = reader.read_i32::<LittleEndian>()?;
+    let nbent = reader.read_i32::<LittleEndian>()?;
+
+    let _epoch = if _starn < 0 || nmag_raw < 0 {
+        Epoch::J2000
+    } else {
+        Epoch::B1950
+    };
+    let nmag = nmag_raw.abs();
+    let star_count = _starn.unsigned_abs() as usize;
+
+    // a file that isn't a catalog reads as a huge star count; catch it before
+    // allocating for it
+    if nmag == 0 || nbent <= 0 || (star_count as u64) * (nbent as u64) > file_len {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "not a BSC5-format star catalog",
+        ));
+    }
+
+    let header = CatalogHeader {
+        star0,
+        _star1,
+        _starn,
+        stnum,
+        mprop,
+        nmag,
+        nbent,
+        _epoch,
+    };
+
+    // 2) Read each star
+    let mut stars = Vec::with_capacity(star_count);
+    for _ in 0..star_count {
+        stars.push(read_star(&mut reader, &header)?);
+    }
+
+    Ok((header, stars))
+}
+
+/// Main-sequence B−V at subclass 0 of each spectral class, hottest first
+const CLASS_COLOR_INDEX: [(char, f32); 7] = [
+    ('O', -0.32),
+    ('B', -0.30),
+    ('A', 0.0),
+    ('F', 0.30),
+    ('G', 0.58),
+    ('K', 0.81),
+    ('M', 1.40),
+];
+/// B−V of a late M star, where the M subclasses run to
+const LATE_M_COLOR_INDEX: f32 = 2.0;
+
+/// Approximate B−V for a spectral type like `"K0"` or `"B9"`, as a main-sequence
+/// star of that type. Carbon and S stars are simply very red.
+pub fn color_index_from_spectral_type(spectral: &str) -> Option<f32> {
+    let mut chars = spectral.trim().chars();
+    let class = chars.next()?.to_ascii_uppercase();
+    let subclass = chars
+        .next()
+        .and_then(|c| c.to_digit(10))
+        .map_or(0.0, |d| d as f32);
+
+    match class {
+        'C' | 'N' | 'R' => return Some(LATE_M_COLOR_INDEX),
+        'S' => return Some(1.6),
+        _ => {}
+    }
+    let i = CLASS_COLOR_INDEX.iter().position(|&(c, _)| c == class)?;
+    let start = CLASS_COLOR_INDEX[i].1;
+    let end = CLASS_COLOR_INDEX
+        .get(i + 1)
+        .map_or(LATE_M_COLOR_INDEX, |&(_, b_v)| b_v);
+    Some(start + (end - start) * subclass / 10.0)
+}
+
+/// one star from an extended catalog, with only what the starfield needs
+#[derive(Debug, Clone, Copy)]
+pub struct CatalogStar {
+    pub ra: f64,  // radians
+    pub dec: f64, // radians
+    /// Visual magnitude
+    pub magnitude: f32,
+    /// B−V, when the catalog has both magnitudes
+    pub color_index: Option<f32>,
+}
+
+/// Parse one record of Tycho-2's `catalog.dat` (or one of its `tyc2.dat.NN` parts).
+///
+/// Records are `|`-separated; stars without a mean position fall back to the
+/// observed one. Johnson V and B−V are approximated from the Tycho BT/VT
+/// magnitudes.
+pub fn parse_tycho2_record(line: &str) -> Option<CatalogStar> {
+    let fields: Vec<&str> = line.split('|').collect();
+    let number = |i: usize| fields.get(i)?.trim().parse::<f64>().ok();
+
+    let (ra, dec) = match (number(2), number(3)) {
+        (Some(ra), Some(dec)) => (ra, dec),
+        _ => (number(24)?, number(25)?),
+    };
+    let (bt, vt) = (number(17), number(19));
+    let magnitude = match (bt, vt) {
+        (Some(bt), Some(vt)) => vt - 0.090 * (bt - vt),
+        (None, Some(vt)) => vt,
+        (Some(bt), None) => bt,
+        (None, None) => return None,
+    };
+
+    Some(CatalogStar {
+        ra: ra.to_radians(),
+        dec: dec.to_radians(),
+        magnitude: magnitude as f32,
+        color_index: bt.zip(vt).map(|(bt, vt)| (0.850 * (bt - vt)) as f32),
+    })
+}
+
+/// Stream a Tycho-2 file, handing stars to `on_batch` in chunks of `batch_size`
+/// so callers can start drawing before the whole file is read.
+pub fn read_tycho2(
+    path: &Path,
+    batch_size: usize,
+    mut on_batch: impl FnMut(Vec<CatalogStar>),
+) -> io::Result<()> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut batch = Vec::new();
+    for line in reader.lines() {
+        if let Some(star) = parse_tycho2_record(&line?) {
+            batch.push(star);
+        }
+        if batch.len() == batch_size {
+            on_batch(std::mem::take(&mut batch));
+        }
+    }
+    if !batch.is_empty() {
+        on_batch(batch);
+    }
+    Ok(())
+}
+
+/// Parse a plain-text star list: one `ra,dec,magnitude[,b-v]` row per star,
+/// RA and Dec in degrees. Blank lines, `#` comments and a header row are
+/// skipped; anything else that doesn't parse is an error naming the line.
+pub fn parse_star_csv(data: &str) -> io::Result<Vec<CatalogStar>> {
+    let mut stars = Vec::new();
+    let mut first_row = true;
+    for (i, line) in data.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let header_allowed = std::mem::replace(&mut first_row, false);
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let numbers: Option<Vec<f64>> = fields.iter().map(|f| f.parse().ok()).collect();
+        let (ra, dec, magnitude, b_v) = match numbers.as_deref() {
+            Some(&[ra, dec, magnitude]) => (ra, dec, magnitude, None),
+            Some(&[ra, dec, magnitude, b_v]) => (ra, dec, magnitude, Some(b_v)),
+            // a header naming the columns
+            None if header_allowed => continue,
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {}: expected ra,dec,magnitude[,b-v]", i + 1),
+                ))
+            }
+        };
+        if !(0.0..360.0).contains(&ra) || !(-90.0..=90.0).contains(&dec) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("line {}: RA or Dec out of range", i + 1),
+            ));
+        }
+        if !(-30.0..=30.0).contains(&magnitude) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("line {}: magnitude out of range", i + 1),
+            ));
+        }
+        stars.push(CatalogStar {
+            ra: ra.to_radians(),
+            dec: dec.to_radians(),
+            magnitude: magnitude as f32,
+            color_index: b_v.map(|b_v| b_v as f32),
+        });
+    }
+    Ok(stars)
+}
+
+/// Parse `HR;Name` rows into (HR number, name) pairs
+pub fn parse_star_names(data: &str) -> io::Result<Vec<(u32, String)>> {
+    data.lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .skip(1) // header
+        .map(|line| {
+            let (hr, name) = line
+                .split_once(';')
+                .and_then(|(hr, name)| Some((hr.trim().parse().ok()?, name.trim())))
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("bad star name line: {line}"),
+                    )
+                })?;
+            Ok((hr, name.to_string()))
+        })
+        .collect()
+}
+
+fn read_star<R: Read + Seek>(reader: &mut R, hdr: &CatalogHeader) -> io::Result<StarEntry> {
+    // mark start so we can skip to exactly nbent bytes
+    let start = reader.stream_position()?;
+
+    // 1) ID field (optional)
+    let raw_id = match hdr.stnum {
+        0 => None,
+        n if n > 0 && n != 4 => {
+            // Real*4 catalog number
+            Some(StarId::Real(reader.read_f32::<LittleEndian>()?))
+        }
+        4 => {
+            // Integer*4 catalog number
+            Some(StarId::Integer(reader.read_i32::<LittleEndian>()?))
+        }
+        _ => None, // <0 means no ID, name will follow later
+    };
+
+    // for sequence number:
+    let seq = raw_id.as_ref().map_or(0, |id| match id {
+        StarId::Real(f) => (*f as i32) - hdr.star0,
+        StarId::Integer(i) => *i - hdr.star0,
+    });
+
+    // 2) coordinates
+    let ra = reader.read_f64::<LittleEndian>()?;
+    let dec = reader.read_f64::<LittleEndian>()?;
+
+    // 3) spectral type (2 ASCII chars)
+    let mut sp_buf = [0u8; 2];
+    reader.read_exact(&mut sp_buf)?;
+    let spectral_type = String::from_utf8_lossy(&sp_buf).into_owned();
+
+    // 4) magnitudes
+    let mut magnitudes = Vec::with_capacity(hdr.nmag as usize);
+    for _ in 0..hdr.nmag {
+        // stored as Integer*2 = i16; value = mag * 100
+        let raw = reader.read_i16::<LittleEndian>()?;
+        magnitudes.push(raw as f32 / 100.0);
+    }
+
+    // 5) proper motions?
+    let (pm_ra, pm_dec) = if hdr.mprop >= 1 {
+        let xrpm = reader.read_f32::<LittleEndian>()?;
+        let xdpm = reader.read_f32::<LittleEndian>()?;
+        (Some(xrpm), Some(xdpm))
+    } else {
+        (None, None)
+    };
+
+    // 6) radial velocity?
+    let vel = if hdr.mprop >= 2 {
+        Some(reader.read_f64::<LittleEndian>()?)
+    } else {
+        None
+    };
+
+    // 7) object name if stnum < 0
+    let name = if hdr.stnum < 0 {
+        let name_len = (-hdr.stnum) as usize;
+        let mut buf = vec![0u8; name_len];
+        reader.read_exact(&mut buf)?;
+        Some(
+            String::from_utf8_lossy(&buf)
+                .trim_end_matches('\0')
+                .to_string(),
+        )
+    } else {
+        None
+    };
+
+    // 8) skip any padding so we end up exactly nbent bytes past `start`
+    let end = reader.stream_position()?;
+    let read_bytes = (end - start) as i64;
+    let to_skip = hdr.nbent as i64 - read_bytes;
+    if to_skip > 0 {
+        reader.seek(SeekFrom::Current(to_skip))?;
+    }
+
+    Ok(StarEntry {
+        _sequence: seq,
+        id: raw_id,
+        ra,
+        dec,
+        spectral_type,
+        magnitudes,
+        _proper_motion_ra: pm_ra,
+        _proper_motion_dec: pm_dec,
+        _radial_velocity: vel,
+        _name: name,
+    })
+}
@@ -0,0 +1,183 @@
+//! Star tile module.
+//!
+//! Stars are drawn a patch of sky at a time: [`split_into_tiles`] groups them
+//! into RA/Dec tiles and each [`StarChunk`] builds one mesh of camera-facing
+//! quads, so Bevy culls the tiles outside the view as a whole.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::mesh::{Indices, PrimitiveTopology};
+use bevy::prelude::*;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+use crate::coords::star_direction;
+
+/// Size (degrees) of the RA/Dec tiles stars are grouped into. Each tile is one
+/// mesh, so Bevy's frustum culling skips whole tiles outside the view.
+const TILE_DEG: f64 = 30.0;
+const RA_TILES: usize = (360.0 / TILE_DEG) as usize;
+const DEC_TILES: usize = (180.0 / TILE_DEG) as usize;
+
+/// Radius of the star sphere
+const STAR_DISTANCE: f32 = 100_000.0;
+
+/// Corners of each star's quad as (x, y, u, v), matching `Rectangle`
+const CORNERS: [(f32, f32, f32, f32); 4] = [
+    (0.5, 0.5, 1.0, 0.0),
+    (-0.5, 0.5, 0.0, 0.0),
+    (-0.5, -0.5, 0.0, 1.0),
+    (0.5, -0.5, 1.0, 1.0),
+];
+
+/// One star baked into a chunk mesh
+#[derive(Debug, Clone, Copy)]
+pub struct CloudStar {
+    pub ra: f64,
+    pub dec: f64,
+    /// Visual magnitude
+    pub magnitude: f32,
+    /// B−V color index, from blue (about −0.3) to red (about 2)
+    pub color_index: f32,
+}
+
+/// B−V assumed for stars the catalog has no color for, a sun-like white
+pub const DEFAULT_COLOR_INDEX: f32 = 0.6;
+
+/// How much of a star's color is kept against plain white; stars look far
+/// paler to the eye than their blackbody color
+const COLOR_SATURATION: f32 = 0.6;
+
+/// Effective temperature (kelvin) for a B−V color index (Ballesteros 2012)
+fn temperature(color_index: f32) -> f32 {
+    let b_v = color_index.clamp(-0.4, 2.0);
+    4600.0 * (1.0 / (0.92 * b_v + 1.7) + 1.0 / (0.92 * b_v + 0.62))
+}
+
+/// Linear RGB tint for a star, brightest channel at 1. The blackbody color
+/// comes from Tanner Helland's fit to the CIE color matching curves.
+pub fn star_color(color_index: f32) -> Vec3 {
+    let t = temperature(color_index) / 100.0;
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+    let g = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_17 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    let srgb = Color::srgb(
+        (r / 255.0).clamp(0.0, 1.0),
+        (g / 255.0).clamp(0.0, 1.0),
+        (b / 255.0).clamp(0.0, 1.0),
+    );
+    let linear = srgb.to_linear();
+    let color = Vec3::ONE.lerp(
+        Vec3::new(linear.red, linear.green, linear.blue),
+        COLOR_SATURATION,
+    );
+    color / color.max_element()
+}
+
+/// Map magnitude → quad scale
+pub fn magnitude_to_scale(mag: f32) -> f32 {
+    const MIN_MAG: f32 = -4.0;
+    const MAX_MAG: f32 = 10.0;
+    const OUT_MIN: f32 = 30.0;
+    const OUT_MAX: f32 = 10_000.0;
+
+    let m = mag.clamp(MIN_MAG, MAX_MAG);
+    let t = (MAX_MAG - m) / (MAX_MAG - MIN_MAG);
+    OUT_MIN * (OUT_MAX / OUT_MIN).powf(t)
+}
+
+/// A tile of sky drawn as a single mesh: one quad per star, each turned to
+/// face the sphere's center where the camera sits, so nothing is billboarded
+/// per frame. The stars are kept to rebuild the mesh when the observer moves.
+#[derive(Component)]
+pub struct StarChunk {
+    pub stars: Vec<CloudStar>,
+}
+
+impl StarChunk {
+    /// Quads for the stars down to `limit`, at their root-local positions for
+    /// `time` and the site (radians). Empty if every star is fainter.
+    pub fn mesh(&self, limit: f32, time: DateTime<Utc>, lat: f64, lon: f64) -> Mesh {
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+        let mut colors = Vec::new();
+        let mut indices = Vec::new();
+
+        for star in self.stars.iter().filter(|s| s.magnitude <= limit) {
+            let dir = star_direction(time, lat, lon, star.ra, star.dec);
+            let rotation = Quat::from_rotation_arc(Vec3::Z, -dir);
+            let scale = magnitude_to_scale(star.magnitude);
+            let (right, up) = (rotation * Vec3::X * scale, rotation * Vec3::Y * scale);
+            let center = dir * STAR_DISTANCE;
+            let tint = star_color(star.color_index);
+
+            let base = positions.len() as u32;
+            for (x, y, u, v) in CORNERS {
+                positions.push((center + right * x + up * y).to_array());
+                normals.push((-dir).to_array());
+                uvs.push([u, v]);
+                colors.push([tint.x, tint.y, tint.z, 1.0]);
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
+
+/// Tile a star falls in
+fn sky_tile(ra: f64, dec: f64) -> usize {
+    let ra_tile = (ra.to_degrees().rem_euclid(360.0) / TILE_DEG) as usize;
+    let dec_tile = ((dec.to_degrees() + 90.0) / TILE_DEG) as usize;
+    dec_tile.min(DEC_TILES - 1) * RA_TILES + ra_tile.min(RA_TILES - 1)
+}
+
+/// Group stars by sky tile, one chunk's worth per non-empty tile
+pub fn split_into_tiles(stars: impl IntoIterator<Item = CloudStar>) -> Vec<Vec<CloudStar>> {
+    let mut tiles: HashMap<usize, Vec<CloudStar>> = HashMap::new();
+    for star in stars {
+        tiles
+            .entry(sky_tile(star.ra, star.dec))
+            .or_default()
+            .push(star);
+    }
+    tiles.into_values().collect()
+}
+
+/// The one additive material every chunk draws with; the mesh's vertex colors
+/// carry each star's tint
+pub fn star_material(
+    mats: &mut Assets<StandardMaterial>,
+    texture: &Handle<Image>,
+) -> Handle<StandardMaterial> {
+    mats.add(StandardMaterial {
+        base_color_texture: Some(texture.clone()),
+        base_color: Color::linear_rgb(100.0, 100.0, 100.0),
+        alpha_mode: AlphaMode::Add,
+        unlit: true,
+        ..default()
+    })
+}
@@ -1,5 +1,36 @@
 // Include the compiled protobuf code
-pub mod protos {
-    tonic::include_proto!("planetarium");
+pub mod gui {
     tonic::include_proto!("gui");
 }
+
+pub mod planetarium {
+    tonic::include_proto!("planetarium");
+}
+
+/// Both packages in one namespace; planetarium.proto refers to gui.proto's
+/// types through `super::gui`, so they can't simply share a module
+pub mod protos {
+    pub use super::gui::*;
+    pub use super::planetarium::*;
+}
+
+/// How sidereal_gui hands its link settings to the planetarium it launches
+pub mod link {
+    use std::time::Duration;
+
+    /// host:port the planetarium listens on
+    pub const ADDRESS_ENV: &str = "SIDEREAL_LINK_ADDRESS";
+    /// Shared token; requests without it are refused
+    pub const TOKEN_ENV: &str = "SIDEREAL_LINK_TOKEN";
+    /// PEM certificate, turns on TLS together with the key
+    pub const TLS_CERT_ENV: &str = "SIDEREAL_LINK_TLS_CERT";
+    /// PEM private key for the certificate
+    pub const TLS_KEY_ENV: &str = "SIDEREAL_LINK_TLS_KEY";
+    /// Metadata key the token travels under
+    pub const TOKEN_METADATA_KEY: &str = "authorization";
+    pub const DEFAULT_ADDRESS: &str = "[::1]:50051";
+    /// HTTP/2 pings both ends send so a dead peer is noticed while idle
+    pub const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(5);
+    /// How long a ping may go unanswered before the connection is dropped
+    pub const KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(10);
+}
@@ -0,0 +1,442 @@
+// analysis/mod.rs
+
+use crate::fits::FitsImage;
+
+/// Pixels around a peak measured as part of its star
+const STAR_RADIUS: usize = 12;
+/// How far (in background sigmas) above the background a peak must be
+const DETECTION_SIGMA: f32 = 5.0;
+/// Pixels looked at when estimating the background, at most
+const SAMPLE_SIZE: usize = 100_000;
+/// Brightest stars kept; more adds time but little accuracy
+const MAX_STARS: usize = 200;
+/// FWHM of a Gaussian star over its standard deviation
+const FWHM_PER_SIGMA: f64 = 2.3548;
+
+/// A star found in a frame
+#[derive(Debug, Clone, Copy)]
+pub struct Star {
+    /// Flux-weighted centre (pixels)
+    pub x: f64,
+    pub y: f64,
+    /// Half-flux radius (pixels), as the flux-weighted mean distance from
+    /// the centre
+    pub hfr: f64,
+    /// Full width at half maximum (pixels), from the spread of the flux as
+    /// if the star were Gaussian
+    pub fwhm: f64,
+    /// 0 for a round star, towards 1 as it stretches out
+    pub eccentricity: f64,
+}
+
+/// What the stars in a frame say about focus and tracking
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    pub stars: usize,
+    /// Medians over the stars; `None` without any
+    pub hfr: Option<f64>,
+    pub fwhm: Option<f64>,
+    pub eccentricity: Option<f64>,
+}
+
+/// Sky level and its noise, from the median and MAD of a sample of pixels
+pub fn background(image: &FitsImage) -> (f32, f32) {
+    let stride = (image.pixels.len() / SAMPLE_SIZE).max(1);
+    let mut sample: Vec<f32> = image
+        .pixels
+        .iter()
+        .step_by(stride)
+        .copied()
+        .filter(|v| v.is_finite())
+        .collect();
+    if sample.is_empty() {
+        return (0.0, 0.0);
+    }
+    let middle = sample.len() / 2;
+    let median = *sample.select_nth_unstable_by(middle, f32::total_cmp).1;
+    for value in &mut sample {
+        *value = (*value - median).abs();
+    }
+    let mad = *sample.select_nth_unstable_by(middle, f32::total_cmp).1;
+    (median, 1.4826 * mad)
+}
+
+/// Centre, flux and HFR of the star around (`cx`, `cy`), measured within
+/// `radius` of it over `sky`. `None` if there's no flux there.
+pub fn measure_star(
+    image: &FitsImage,
+    cx: usize,
+    cy: usize,
+    radius: usize,
+    sky: f32,
+) -> Option<Star> {
+    let x_range = cx.saturating_sub(radius)..(cx + radius + 1).min(image.width);
+    let y_range = cy.saturating_sub(radius)..(cy + radius + 1).min(image.height);
+    let within = |x: usize, y: usize| {
+        let (dx, dy) = (x.abs_diff(cx), y.abs_diff(cy));
+        dx * dx + dy * dy <= radius * radius
+    };
+
+    let (mut flux, mut sum_x, mut sum_y) = (0.0f64, 0.0f64, 0.0f64);
+    for y in y_range.clone() {
+        for x in x_range.clone().filter(|&x| within(x, y)) {
+            let value = image.pixel(x, y)? - sky;
+            if value > 0.0 {
+                flux += value as f64;
+                sum_x += value as f64 * x as f64;
+                sum_y += value as f64 * y as f64;
+            }
+        }
+    }
+    if flux <= 0.0 {
+        return None;
+    }
+    let (x, y) = (sum_x / flux, sum_y / flux);
+
+    // flux-weighted mean distance from the centre, and second moments for
+    // the star's shape
+    let (mut weighted, mut xx, mut yy, mut xy) = (0.0, 0.0, 0.0, 0.0);
+    for py in y_range {
+        for px in x_range.clone().filter(|&px| within(px, py)) {
+            let value = image.pixel(px, py)? - sky;
+            if value > 0.0 {
+                let value = value as f64;
+                let (dx, dy) = (px as f64 - x, py as f64 - y);
+                weighted += value * dx.hypot(dy);
+                xx += value * dx * dx;
+                yy += value * dy * dy;
+                xy += value * dx * dy;
+            }
+        }
+    }
+    let (xx, yy, xy) = (xx / flux, yy / flux, xy / flux);
+    // variance along the star's long and short axes
+    let spread = ((xx - yy) / 2.0).hypot(xy);
+    let major = (xx + yy) / 2.0 + spread;
+    let minor = ((xx + yy) / 2.0 - spread).max(0.0);
+    let eccentricity = if major > 0.0 {
+        (1.0 - minor / major).sqrt()
+    } else {
+        0.0
+    };
+    Some(Star {
+        x,
+        y,
+        hfr: weighted / flux,
+        fwhm: FWHM_PER_SIGMA * ((xx + yy) / 2.0).sqrt(),
+        eccentricity,
+    })
+}
+
+/// Stars in a frame, brightest first: local peaks well above the background,
+/// away from the edges and from brighter peaks
+pub fn detect_stars(image: &FitsImage) -> Vec<Star> {
+    let (sky, noise) = background(image);
+    let threshold = sky + DETECTION_SIGMA * noise.max(f32::EPSILON);
+    let r = STAR_RADIUS;
+    if image.width <= 2 * r || image.height <= 2 * r {
+        return Vec::new();
+    }
+
+    let mut peaks: Vec<(usize, usize, f32)> = Vec::new();
+    for y in r..image.height - r {
+        let row = &image.pixels[y * image.width..(y + 1) * image.width];
+        for (x, &value) in row.iter().enumerate().take(image.width - r).skip(r) {
+            if value.is_nan() || value <= threshold {
+                continue;
+            }
+            let is_peak = (y - 1..=y + 1).all(|ny| {
+                (x - 1..=x + 1).all(|nx| {
+                    let other = image.pixels[ny * image.width + nx];
+                    // ties go to the first pixel, so a flat top counts once
+                    other < value || (other == value && (ny, nx) >= (y, x))
+                })
+            });
+            if is_peak {
+                peaks.push((x, y, value));
+            }
+        }
+    }
+    peaks.sort_unstable_by(|a, b| b.2.total_cmp(&a.2));
+
+    let mut stars: Vec<Star> = Vec::new();
+    for (x, y, _) in peaks {
+        if stars.len() >= MAX_STARS {
+            break;
+        }
+        // the rest of a brighter star, or too close to it to measure apart
+        let near = stars
+            .iter()
+            .any(|star| (star.x - x as f64).hypot(star.y - y as f64) < r as f64);
+        if near {
+            continue;
+        }
+        if let Some(star) = measure_star(image, x, y, r, sky) {
+            stars.push(star);
+        }
+    }
+    stars
+}
+
+/// Median of `values`, if there are any
+fn median(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let mut values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable_by(f64::total_cmp);
+    Some(values[values.len() / 2])
+}
+
+/// Median HFR of the stars, if there are any
+pub fn median_hfr(stars: &[Star]) -> Option<f64> {
+    median(stars.iter().map(|star| star.hfr))
+}
+
+/// Find the stars in a frame and sum up their size and shape
+pub fn frame_stats(image: &FitsImage) -> FrameStats {
+    let stars = detect_stars(image);
+    FrameStats {
+        stars: stars.len(),
+        hfr: median_hfr(&stars),
+        fwhm: median(stars.iter().map(|star| star.fwhm)),
+        eccentricity: median(stars.iter().map(|star| star.eccentricity)),
+    }
+}
+
+/// HFR = a·x² + b·x + c against focuser position x, fitted to an autofocus
+/// run
+#[derive(Debug, Clone, Copy)]
+pub struct FocusCurve {
+    a: f64,
+    b: f64,
+    c: f64,
+    /// Positions are fitted relative to this, to keep the squares small
+    offset: f64,
+}
+
+impl FocusCurve {
+    /// Least-squares parabola through (position, HFR) points. `None` unless
+    /// there are three or more points and the curve opens upwards.
+    pub fn fit(points: &[(f64, f64)]) -> Option<FocusCurve> {
+        if points.len() < 3 {
+            return None;
+        }
+        let offset = points.iter().map(|p| p.0).sum::<f64>() / points.len() as f64;
+        // sums of x^0..x^4 and y·x^0..y·x^2 for the normal equations
+        let mut sx = [0.0f64; 5];
+        let mut sy = [0.0f64; 3];
+        for &(x, y) in points {
+            let x = x - offset;
+            let mut power = 1.0;
+            for (i, sum) in sx.iter_mut().enumerate() {
+                *sum += power;
+                if i < 3 {
+                    sy[i] += y * power;
+                }
+                power *= x;
+            }
+        }
+        // [sx4 sx3 sx2] [a]   [sy2]
+        // [sx3 sx2 sx1] [b] = [sy1]
+        // [sx2 sx1 sx0] [c]   [sy0]
+        let m = [
+            [sx[4], sx[3], sx[2]],
+            [sx[3], sx[2], sx[1]],
+            [sx[2], sx[1], sx[0]],
+        ];
+        let rhs = [sy[2], sy[1], sy[0]];
+        let det = |m: [[f64; 3]; 3]| {
+            m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+                - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+                + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+        };
+        let d = det(m);
+        if d.abs() < f64::EPSILON {
+            return None;
+        }
+        // Cramer's rule
+        let solve = |column: usize| {
+            let mut replaced = m;
+            for (row, value) in rhs.iter().enumerate() {
+                replaced[row][column] = *value;
+            }
+            det(replaced) / d
+        };
+        let curve = FocusCurve {
+            a: solve(0),
+            b: solve(1),
+            c: solve(2),
+            offset,
+        };
+        (curve.a > 0.0).then_some(curve)
+    }
+
+    /// Position of the smallest HFR
+    pub fn best_position(&self) -> f64 {
+        self.offset - self.b / (2.0 * self.a)
+    }
+
+    pub fn hfr_at(&self, position: f64) -> f64 {
+        let x = position - self.offset;
+        self.a * x * x + self.b * x + self.c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SKY: f32 = 100.0;
+
+    /// A frame of flat sky with Gaussian stars at (x, y, peak, sigma x,
+    /// sigma y)
+    fn frame(width: usize, height: usize, stars: &[(f64, f64, f64, f64, f64)]) -> FitsImage {
+        let mut pixels = vec![SKY; width * height];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let (px, py) = ((i % width) as f64, (i / width) as f64);
+            for &(x, y, peak, sx, sy) in stars {
+                let (dx, dy) = ((px - x) / sx, (py - y) / sy);
+                *pixel += (peak * (-(dx * dx + dy * dy) / 2.0).exp()) as f32;
+            }
+        }
+        FitsImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// `image` with repeatable noise of up to ±2 added
+    fn noisy(mut image: FitsImage) -> FitsImage {
+        let mut state = 12_345u32;
+        for pixel in &mut image.pixels {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            *pixel += (state >> 16) as f32 / 65_536.0 * 4.0 - 2.0;
+        }
+        image
+    }
+
+    #[test]
+    fn test_background_median_and_mad() {
+        // half the pixels 1 above the sky, so the MAD is 1
+        let mut image = frame(10, 10, &[]);
+        for pixel in image.pixels.iter_mut().step_by(2) {
+            *pixel += 1.0;
+        }
+        image.pixels[0] = 1e6;
+        let (sky, noise) = background(&image);
+        assert_eq!(sky, SKY + 1.0);
+        assert!((noise - 1.4826).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_gaussian_star() {
+        let sigma = 2.0;
+        let image = frame(64, 64, &[(30.3, 33.6, 1000.0, sigma, sigma)]);
+        let star = measure_star(&image, 30, 34, STAR_RADIUS, SKY).unwrap();
+        assert!((star.x - 30.3).abs() < 0.01, "x {}", star.x);
+        assert!((star.y - 33.6).abs() < 0.01, "y {}", star.y);
+        // the mean distance from the centre of a 2D Gaussian is σ·√(π/2)
+        let hfr = sigma * (std::f64::consts::PI / 2.0).sqrt();
+        assert!((star.hfr - hfr).abs() < 0.02, "HFR {}", star.hfr);
+        assert!(
+            (star.fwhm - FWHM_PER_SIGMA * sigma).abs() < 0.02,
+            "FWHM {}",
+            star.fwhm
+        );
+        assert!(
+            star.eccentricity < 0.05,
+            "eccentricity {}",
+            star.eccentricity
+        );
+    }
+
+    #[test]
+    fn test_elongated_star() {
+        // twice as wide as it is tall: e = √(1 - 1/4)
+        let image = frame(64, 64, &[(32.0, 32.0, 1000.0, 3.0, 1.5)]);
+        let star = measure_star(&image, 32, 32, STAR_RADIUS, SKY).unwrap();
+        assert!(
+            (star.eccentricity - 0.75f64.sqrt()).abs() < 0.01,
+            "eccentricity {}",
+            star.eccentricity
+        );
+    }
+
+    #[test]
+    fn test_measure_star_without_flux() {
+        let image = frame(64, 64, &[]);
+        assert!(measure_star(&image, 32, 32, STAR_RADIUS, SKY).is_none());
+    }
+
+    #[test]
+    fn test_detect_stars_brightest_first() {
+        let mut image = noisy(frame(
+            100,
+            80,
+            &[
+                (30.0, 30.0, 500.0, 2.0, 2.0),
+                (70.0, 50.0, 2000.0, 2.0, 2.0),
+            ],
+        ));
+        // a saturated star with a flat top is still one star
+        for (x, y) in [(50, 20), (51, 20), (50, 21), (51, 21)] {
+            image.pixels[y * 100 + x] = 5000.0;
+        }
+        // too near the edge to measure
+        image.pixels[40 * 100 + 3] = 5000.0;
+
+        let stars = detect_stars(&image);
+        assert_eq!(stars.len(), 3);
+        let near = |star: &Star, x: f64, y: f64| (star.x - x).hypot(star.y - y) < 0.05;
+        assert!(near(&stars[0], 50.5, 20.5), "{:?}", stars[0]);
+        assert!(near(&stars[1], 70.0, 50.0), "{:?}", stars[1]);
+        assert!(near(&stars[2], 30.0, 30.0), "{:?}", stars[2]);
+
+        let stats = frame_stats(&image);
+        assert_eq!(stats.stars, 3);
+        assert!(stats.hfr.is_some());
+    }
+
+    #[test]
+    fn test_detect_stars_in_empty_frame() {
+        let image = noisy(frame(64, 64, &[]));
+        assert!(detect_stars(&image).is_empty());
+        assert!(frame_stats(&image).hfr.is_none());
+    }
+
+    #[test]
+    fn test_focus_curve_recovers_vertex() {
+        // HFR = 0.000002·(x - 12_340)² + 1.8
+        let points: Vec<(f64, f64)> = (0..9)
+            .map(|i| {
+                let x = 11_540.0 + i as f64 * 200.0;
+                (x, 0.000_002 * (x - 12_340.0).powi(2) + 1.8)
+            })
+            .collect();
+        let curve = FocusCurve::fit(&points).unwrap();
+        assert!((curve.best_position() - 12_340.0).abs() < 1e-6);
+        assert!((curve.hfr_at(12_340.0) - 1.8).abs() < 1e-9);
+        for &(x, y) in &points {
+            assert!((curve.hfr_at(x) - y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_focus_curve_rejects_downward_curve() {
+        let points: Vec<(f64, f64)> = (0..5)
+            .map(|i| {
+                let x = i as f64 * 100.0;
+                (x, 5.0 - 0.0001 * (x - 200.0).powi(2))
+            })
+            .collect();
+        assert!(FocusCurve::fit(&points).is_none());
+    }
+
+    #[test]
+    fn test_focus_curve_needs_three_distinct_points() {
+        assert!(FocusCurve::fit(&[(0.0, 3.0), (100.0, 2.0)]).is_none());
+        assert!(FocusCurve::fit(&[(100.0, 3.0), (100.0, 2.0), (100.0, 2.5)]).is_none());
+    }
+}
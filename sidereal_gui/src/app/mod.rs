@@ -7,16 +7,20 @@ use crate::gui::styles::button_style::sidereal_button;
 use crate::gui::styles::container_style::{content_container, ContainerLayer};
 use crate::gui::tabs::setup::{self, BubbleMessagePayload};
 use crate::gui::widgets::server_status::{server_status_widget, ServerStatus};
-use crate::indi_handler::{device_discovery_watcher, param_watcher, server_disconnect_watcher};
+use crate::indi_handler::{
+    device_discovery_watcher, mount, param_watcher, server_disconnect_watcher,
+};
+use crate::logging;
 use crate::model::{SiderealError, SiderealResult};
 use crate::planetarium_handler::{planetarium_receiver, planetarium_sender};
 use crate::{
     config::Config,
     gui::{
-        styles::{tab_style::tab_content, SIDEREAL_THEME},
+        styles::{self, tab_style::tab_content, SIDEREAL_THEME},
         tabs::{self, MainWindowState, Tab},
     },
 };
+use chrono::Utc;
 use iced::futures::SinkExt;
 use iced::widget::container;
 use iced::widget::{column, row, scrollable, Column, Space};
@@ -25,7 +29,9 @@ use iced::Alignment::{self};
 use iced::{stream, Settings, Subscription};
 use iced::{widget::text, Element, Length, Task};
 use once_cell::sync::OnceCell;
+use overpass_planner::precession;
 use planetarium_receiver::ForwardedRPC;
+use protos::protos::{set_tracking_target_request::TrackingType, PassTrack, SunWarning};
 use tokio::sync::{mpsc, Mutex};
 static RPC_RX: OnceCell<Arc<Mutex<Option<mpsc::UnboundedReceiver<ForwardedRPC>>>>> =
     OnceCell::new();
@@ -60,11 +66,16 @@ pub enum Message {
     Setup(tabs::setup::Message),
     Mount(tabs::mount::Message),
     Observatory(tabs::observatory::Message),
+    Safety(tabs::safety::Message),
+    Power(tabs::power::Message),
+    Passes(tabs::passes::Message),
     PlateSolve(tabs::plate_solve::Message),
     Capture(tabs::capture::Message),
+    Calibration(tabs::calibration::Message),
     Focus(tabs::focus::Message),
     Guide(tabs::guide::Message),
     Telescope(tabs::telescope::Message),
+    Log(tabs::log::Message),
     ConfigLoaded(Config),
     ErrorOccurred(SiderealError),
     ErrorCleared(),
@@ -75,14 +86,29 @@ pub enum Message {
     ModifyCameras(CameraMessage),
     AddServer(add_server::Message),
     ForwardedRPC(ForwardedRPC),
+    /// Arm a satellite pass for the mount to follow, its rise of date (a
+    /// forwarded one is precessed from J2000 first)
+    TrackPass(PassTrack),
+    CloseRequested(window::Id),
+    /// Conditions turned unsafe; the safety monitor is closing up. A low UPS
+    /// battery has the camera warmed up as well, to spare it the cooler.
+    SafetyShutdown {
+        reason: String,
+        low_battery: bool,
+    },
 }
 #[derive(Debug, Clone, Default)]
 pub struct ConnectedDevices {
     pub mount: Option<String>,
     pub camera: Option<String>,
+    pub guide_camera: Option<String>,
+    pub extra_cameras: Vec<String>,
     pub focuser: Option<String>,
     pub telescope_controller: Option<String>,
     pub roof_controller: Option<String>,
+    pub weather: Option<String>,
+    pub ups: Option<String>,
+    pub power_box: Option<String>,
 }
 
 #[derive(Default)]
@@ -90,8 +116,15 @@ pub struct MainWindow {
     state: MainWindowState,
     dialog: Option<DialogType>,
     server_status: ServerStatus,
+    planetarium_status: ServerStatus,
+    /// Satellite pass the mount is waiting for, chosen in the planetarium
+    armed_pass: Option<PassTrack>,
+    /// What the planetarium says is inside the Sun avoidance cone
+    sun_warning: Option<SunWarning>,
     connected_devices: ConnectedDevices,
     camera_manager: CameraManager,
+    /// Window to close once the camera has warmed up
+    closing: Option<window::Id>,
 }
 
 pub enum DialogType {
@@ -120,6 +153,15 @@ impl MainWindow {
             self.camera_manager
                 .subscription()
                 .map(Message::ModifyCameras),
+            self.state.capture.subscription().map(Message::Capture),
+            self.state.mount.subscription().map(Message::Mount),
+            self.state.safety.subscription().map(Message::Safety),
+            self.state.power.subscription().map(Message::Power),
+            self.state.telescope.subscription().map(Message::Telescope),
+            Subscription::run_with_id("session-log", logging::watch())
+                .map(tabs::log::Message::Logged)
+                .map(Message::Log),
+            window::close_requests().map(Message::CloseRequested),
             // NEW: gRPC → mpsc → Iced
             Subscription::run_with_id("grpc-forwarded-rpc", rpc_subscription_worker()),
         ])
@@ -129,6 +171,8 @@ impl MainWindow {
         // Build window settings (size + optional icon)
         let mut win = window::Settings {
             size: iced::Size::new(1200.0, 900.0),
+            // a cooled camera is warmed up before closing
+            exit_on_close_request: false,
             ..Default::default()
         };
 
@@ -170,31 +214,79 @@ impl MainWindow {
             Message::Observatory(msg) => {
                 return self.state.observatory.update(msg);
             }
+            Message::Safety(msg) => {
+                return self.state.safety.update(msg);
+            }
+            Message::Power(msg) => {
+                return self.state.power.update(msg);
+            }
+            Message::SafetyShutdown {
+                reason,
+                low_battery,
+            } => {
+                // the safety monitor parks the mount itself
+                self.state
+                    .mount
+                    .abandon_pass("Stopped: observatory unsafe".to_owned());
+                let status = format!("Stopped: {reason}");
+                let mut tasks = vec![
+                    self.state.capture.stop(status.clone()),
+                    self.state.guide.stop(status.clone()),
+                    self.state.calibration.stop(status),
+                ];
+                if low_battery && self.state.capture.cooler_on() {
+                    tasks.push(self.state.capture.warm_up());
+                }
+                return Task::batch(tasks);
+            }
+            Message::Passes(msg) => {
+                return self.state.passes.update(msg);
+            }
             Message::PlateSolve(msg) => {
-                self.state.plate_solve.update(msg);
+                return self.state.plate_solve.update(msg);
             }
             Message::Guide(msg) => {
-                self.state.guide.update(msg);
+                return self.state.guide.update(msg);
             }
             Message::Focus(msg) => {
-                self.state.focus.update(msg);
+                return self.state.focus.update(msg);
             }
             Message::Capture(msg) => {
-                self.state.capture.update(msg);
+                let warmed_up = matches!(msg, tabs::capture::Message::WarmedUp(_));
+                let task = self.state.capture.update(msg);
+                return match self.closing {
+                    Some(id) if warmed_up => Task::batch([task, window::close(id)]),
+                    _ => task,
+                };
+            }
+            Message::Calibration(msg) => {
+                return self.state.calibration.update(msg);
             }
             Message::Telescope(msg) => {
                 return self.state.telescope.update(msg);
             }
+            Message::Log(msg) => {
+                return self.state.log.update(msg);
+            }
             Message::ConfigLoaded(config) => {
                 self.state.setup.on_config_load(config.clone());
+                self.state.capture.on_config_load(&config);
+                self.state.focus.on_config_load(&config);
+                self.state.guide.on_config_load(&config);
+                self.state.plate_solve.on_config_load(&config);
+                self.state.power.on_config_load(&config);
+                self.state.telescope.on_config_load(&config);
+                let calibration = self.state.calibration.on_config_load(&config);
                 self.camera_manager.load_from_config(config.cameras);
                 // Automatically connect all cameras that were loaded from config
                 for camera_index in 0..self.camera_manager.cameras.len() {
                     self.camera_manager
                         .handle_message(CameraMessage::ConnectCamera(camera_index));
                 }
+                return calibration;
             }
             Message::ErrorOccurred(err) => {
+                tracing::error!("{err}");
                 self.dialog = Some(DialogType::Error(err.to_string()));
 
                 if let SiderealError::ServerConnectionError(_) = err {
@@ -203,12 +295,10 @@ impl MainWindow {
             }
             Message::ErrorCleared() => self.dialog = None,
             Message::LaunchPlanetarium => {
+                // the site goes over as soon as the link comes up
                 return Task::perform(
                     async {
                         planetarium_sender::launch_planetarium()
-                            .await
-                            .map_err(|e| e.to_string())?;
-                        planetarium_sender::set_site_location()
                             .await
                             .map_err(|e| e.to_string())
                     },
@@ -232,9 +322,13 @@ impl MainWindow {
             }
             Message::Noop => {}
             Message::ConnectedDeviceChange(connected_devices) => {
+                self.state.capture.on_camera_change(&connected_devices);
                 self.connected_devices = connected_devices;
             }
-            Message::IndiError(err) => self.dialog = Some(DialogType::Error(err.to_string())),
+            Message::IndiError(err) => {
+                tracing::error!("INDI: {err}");
+                self.dialog = Some(DialogType::Error(err.to_string()));
+            }
             Message::ModifyCameras(camera_message) => {
                 // Only save cameras when configuration changes, not on streaming/connection updates
                 let should_save = matches!(
@@ -284,13 +378,94 @@ impl MainWindow {
                     }
                 }
             }
-            Message::ForwardedRPC(_rpc) => {
-                println!("test message received");
+            Message::CloseRequested(id) => {
+                // asking a second time closes without waiting
+                if self.closing.is_some() || !self.state.capture.cooler_on() {
+                    return window::close(id);
+                }
+                tracing::info!("Warming the camera up before closing");
+                self.closing = Some(id);
+                self.state.active = Tab::Capture;
+                return self.state.capture.warm_up();
+            }
+            Message::ForwardedRPC(ForwardedRPC::LinkStatus(status)) => {
+                self.planetarium_status = status;
+            }
+            Message::ForwardedRPC(ForwardedRPC::SunWarning(warning)) => {
+                self.sun_warning = warning.active.then_some(warning);
+            }
+            Message::ForwardedRPC(ForwardedRPC::TrackPass(mut track)) => {
+                // the planetarium works in J2000, the mount of date
+                let (ra_hours, dec_deg) = precession::to_date(
+                    track.rise_ra_hours.into(),
+                    track.rise_dec_degrees.into(),
+                    Utc::now(),
+                );
+                track.rise_ra_hours = ra_hours as f32;
+                track.rise_dec_degrees = dec_deg as f32;
+                return self.update(Message::TrackPass(track));
+            }
+            Message::TrackPass(track) => {
+                // have the mount waiting where the satellite will rise
+                let (ra_hours, dec_deg) = (
+                    f64::from(track.rise_ra_hours),
+                    f64::from(track.rise_dec_degrees),
+                );
+                self.state.mount.arm_pass(track.clone());
+                self.armed_pass = Some(track);
+                return Task::perform(
+                    async move {
+                        mount::slew_to(ra_hours, dec_deg).await?;
+                        planetarium_sender::set_selected_target(ra_hours, dec_deg).await
+                    },
+                    |result| match result {
+                        Ok(()) => Message::Noop,
+                        Err(e) => Message::ErrorOccurred(e),
+                    },
+                );
+            }
+            Message::ForwardedRPC(ForwardedRPC::SetTrackingTargetRequest(request)) => {
+                let (ra_hours, dec_deg) = match request.tracking_type {
+                    Some(TrackingType::GenericTrack(target)) => {
+                        (target.ra_hours, target.dec_degrees)
+                    }
+                    Some(TrackingType::SatTrack(target)) => (target.ra_hours, target.dec_degrees),
+                    None => return Task::none(),
+                };
+                // the planetarium works in J2000, the mount of date
+                let (ra_hours, dec_deg) =
+                    precession::to_date(ra_hours.into(), dec_deg.into(), Utc::now());
+                return Task::perform(
+                    async move {
+                        mount::slew_to(ra_hours, dec_deg).await?;
+                        planetarium_sender::set_selected_target(ra_hours, dec_deg).await
+                    },
+                    |result| match result {
+                        Ok(()) => Message::Noop,
+                        Err(e) => Message::ErrorOccurred(e),
+                    },
+                );
             }
         }
         Task::none()
     }
 
+    /// The safety monitor's verdict for the sidebar, and whether it's unsafe,
+    /// once there's a roof for it to watch
+    fn safety_status(&self) -> Option<(&'static str, bool)> {
+        let safety = &self.state.safety;
+        if !safety.is_watching() {
+            return None;
+        }
+        let status = match (safety.is_unsafe(), safety.is_overridden()) {
+            (true, true) => "Unsafe (overridden)",
+            (true, false) => "Unsafe",
+            (false, true) => "Safe (overridden)",
+            (false, false) => "Safe",
+        };
+        Some((status, safety.is_unsafe()))
+    }
+
     fn view(&self) -> Element<Message> {
         let header = tabs::header(self.state.active, |t| Message::Tab(t));
 
@@ -302,11 +477,16 @@ impl MainWindow {
                 .map(Message::Setup),
             Tab::Mount => self.state.mount.view().map(Message::Mount),
             Tab::Observatory => self.state.observatory.view().map(Message::Observatory),
+            Tab::Safety => self.state.safety.view().map(Message::Safety),
+            Tab::Power => self.state.power.view().map(Message::Power),
+            Tab::Passes => self.state.passes.view().map(Message::Passes),
             Tab::PlateSolve => self.state.plate_solve.view().map(Message::PlateSolve),
             Tab::Guide => self.state.guide.view().map(Message::Guide),
             Tab::Focus => self.state.focus.view().map(Message::Focus),
             Tab::Capture => self.state.capture.view().map(Message::Capture),
+            Tab::Calibration => self.state.calibration.view().map(Message::Calibration),
             Tab::Telescope => self.state.telescope.view().map(Message::Telescope),
+            Tab::Log => self.state.log.view().map(Message::Log),
         };
 
         let content = tab_content(inner_content)
@@ -329,6 +509,67 @@ impl MainWindow {
                             ContainerLayer::Layer2
                         )
                         .width(Length::Fill),
+                        content_container(
+                            row![
+                                text("Planetarium:"),
+                                Space::with_width(Length::Fill),
+                                server_status_widget(&self.planetarium_status)
+                            ]
+                            .align_y(Alignment::Center)
+                            .spacing(10),
+                            ContainerLayer::Layer2
+                        )
+                        .width(Length::Fill),
+                        match &self.armed_pass {
+                            Some(pass) => column![content_container(
+                                row![
+                                    text("Armed Pass:"),
+                                    Space::with_width(Length::Fill),
+                                    text(format!("{} ({})", pass.name, pass.norad_id))
+                                ]
+                                .align_y(Alignment::Center)
+                                .spacing(10),
+                                ContainerLayer::Layer2
+                            )
+                            .width(Length::Fill)],
+                            None => Column::new(),
+                        },
+                        match self.safety_status() {
+                            Some((status, unsafe_now)) => column![content_container(
+                                row![
+                                    text("Safety:"),
+                                    Space::with_width(Length::Fill),
+                                    if unsafe_now {
+                                        text(status).color(styles::RED_TEXT)
+                                    } else {
+                                        text(status)
+                                    }
+                                ]
+                                .align_y(Alignment::Center)
+                                .spacing(10),
+                                ContainerLayer::Layer2
+                            )
+                            .width(Length::Fill)],
+                            None => Column::new(),
+                        },
+                        match &self.sun_warning {
+                            Some(warning) => column![content_container(
+                                row![
+                                    text("Sun Warning:").color(styles::RED_TEXT),
+                                    Space::with_width(Length::Fill),
+                                    text(format!(
+                                        "{} {:.1}° from the Sun",
+                                        warning.description, warning.separation_degrees
+                                    ))
+                                    .color(styles::RED_TEXT)
+                                ]
+                                .align_y(Alignment::Center)
+                                .spacing(10),
+                                ContainerLayer::Layer2
+                            )
+                            .width(Length::Fill)],
+                            None => Column::new(),
+                        },
                         container(
                             self.camera_manager
                                 .view_cameras()
@@ -369,6 +610,17 @@ impl MainWindow {
                                     )],
                                     None => Column::new(), // renders nothing
                                 },
+                                match &self.connected_devices.guide_camera {
+                                    Some(guide_camera) => column![content_container(
+                                        row![
+                                            text("Guide Camera:"),
+                                            Space::with_width(Length::Fill),
+                                            text(guide_camera)
+                                        ],
+                                        ContainerLayer::Layer3
+                                    )],
+                                    None => Column::new(), // renders nothing
+                                },
                                 match &self.connected_devices.focuser {
                                     Some(focuser) => column![content_container(
                                         row![
@@ -402,6 +654,39 @@ impl MainWindow {
                                     )],
                                     None => Column::new(), // renders nothing
                                 },
+                                match &self.connected_devices.weather {
+                                    Some(weather) => column![content_container(
+                                        row![
+                                            text("Weather Station:"),
+                                            Space::with_width(Length::Fill),
+                                            text(weather)
+                                        ],
+                                        ContainerLayer::Layer3
+                                    )],
+                                    None => Column::new(), // renders nothing
+                                },
+                                match &self.connected_devices.ups {
+                                    Some(ups) => column![content_container(
+                                        row![
+                                            text("UPS:"),
+                                            Space::with_width(Length::Fill),
+                                            text(ups)
+                                        ],
+                                        ContainerLayer::Layer3
+                                    )],
+                                    None => Column::new(), // renders nothing
+                                },
+                                match &self.connected_devices.power_box {
+                                    Some(power_box) => column![content_container(
+                                        row![
+                                            text("Power Box:"),
+                                            Space::with_width(Length::Fill),
+                                            text(power_box)
+                                        ],
+                                        ContainerLayer::Layer3
+                                    )],
+                                    None => Column::new(), // renders nothing
+                                },
                             ]
                             .spacing(5),
                             ContainerLayer::Layer2
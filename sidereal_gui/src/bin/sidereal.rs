@@ -1,7 +1,7 @@
 use sidereal_gui::app;
 
-use env_logger;
-use sidereal_gui::planetarium_handler::planetarium_receiver; // add `env_logger = "0.11"` in Cargo.toml (or similar)
+use sidereal_gui::logging;
+use sidereal_gui::planetarium_handler::planetarium_receiver;
 
 use crate::app::set_grpc_receiver;
 use gstreamer as gst; // add `gstreamer = "0.22"` (or latest) in Cargo.toml
@@ -10,8 +10,8 @@ use tokio::sync::mpsc;
 fn main() -> iced::Result {
     std::env::set_var("RUST_BACKTRACE", "1");
 
-    env_logger::init();
-    std::panic::set_hook(Box::new(|info| eprintln!("PANIC: {info}")));
+    logging::init();
+    std::panic::set_hook(Box::new(|info| tracing::error!("PANIC: {info}")));
 
     // On macOS, help GStreamer find its plugins
     #[cfg(target_os = "macos")]
@@ -44,7 +44,7 @@ fn main() -> iced::Result {
     }
 
     gst::init().unwrap_or_else(|e| {
-        eprintln!("Failed to initialize GStreamer: {}", e);
+        tracing::error!("Failed to initialize GStreamer: {}", e);
         #[cfg(target_os = "macos")]
         {
             eprintln!("On macOS, make sure GStreamer is installed via Homebrew:");
@@ -58,16 +58,9 @@ fn main() -> iced::Result {
         std::process::exit(1);
     });
 
+    // Commands arrive on the planetarium link once it is launched
     let (tx, rx) = mpsc::unbounded_channel::<ForwardedRPC>();
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
-        rt.block_on(async move {
-            if let Err(e) = planetarium_receiver::run(tx).await {
-                eprintln!("gRPC server error: {e}");
-            }
-        });
-    });
-
+    planetarium_receiver::set_forwarder(tx);
     set_grpc_receiver(rx);
 
     let mut settings = iced::Settings::default();
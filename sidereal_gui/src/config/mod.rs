@@ -2,7 +2,7 @@
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashMap, fmt, path::PathBuf, sync::Arc};
 use tokio::sync::RwLock;
 
 use crate::model::{SiderealError, SiderealResult};
@@ -45,12 +45,267 @@ pub struct CameraConfig {
     pub url: String,
 }
 
+/// Certificate and key the planetarium serves its link with. The GUI trusts
+/// the same certificate, so a self-signed one is enough.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LinkTlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Name the certificate was issued for
+    #[serde(default = "default_tls_domain")]
+    pub domain: String,
+}
+
+fn default_tls_domain() -> String {
+    "localhost".to_owned()
+}
+
+/// How the GUI and the planetarium talk to each other
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlanetariumLinkConfig {
+    /// Address the planetarium listens on, host:port
+    pub address: String,
+    /// Shared secret both sides must present; no auth when unset
+    pub token: Option<String>,
+    /// Plaintext when unset
+    pub tls: Option<LinkTlsConfig>,
+}
+
+impl Default for PlanetariumLinkConfig {
+    fn default() -> Self {
+        Self {
+            address: protos::link::DEFAULT_ADDRESS.to_owned(),
+            token: None,
+            tls: None,
+        }
+    }
+}
+
+/// Where the mount can point without hitting the pier or the roof; the
+/// planetarium shades the rest of the sky
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MountLimitsConfig {
+    pub min_altitude_degrees: f32,
+    pub max_altitude_degrees: f32,
+    pub min_dec_degrees: f32,
+    pub max_dec_degrees: f32,
+    /// How far past the meridian either way; 12 for no limit
+    pub max_hour_angle_hours: f32,
+}
+
+impl Default for MountLimitsConfig {
+    fn default() -> Self {
+        Self {
+            min_altitude_degrees: 0.0,
+            max_altitude_degrees: 90.0,
+            min_dec_degrees: -90.0,
+            max_dec_degrees: 90.0,
+            max_hour_angle_hours: 12.0,
+        }
+    }
+}
+
+/// Where captured frames are saved
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CaptureConfig {
+    pub directory: PathBuf,
+    /// Path of each frame under `directory`, without the extension. Filled
+    /// in from {date}, {target}, {filter}, {exposure}, {gain}, {binning},
+    /// {temp}, {camera} and {frame}.
+    pub file_template: String,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            directory: dirs_next::picture_dir()
+                .or_else(dirs_next::home_dir)
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("Sidereal"),
+            file_template: "{date}/{target}/{target}_{filter}_{exposure}s_{frame}".to_owned(),
+        }
+    }
+}
+
+/// Readout settings an imaging camera is put in before each sequence. `None`
+/// leaves the camera's own setting alone.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub struct CameraDefaults {
+    pub gain: Option<f64>,
+    pub offset: Option<f64>,
+    pub binning: Option<u32>,
+    /// Region read out; `None` for the whole sensor
+    pub roi: Option<Roi>,
+}
+
+/// Part of the sensor, in unbinned pixels from the top left
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Roi {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Where the calibration wizard files darks, bias and flats
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CalibrationConfig {
+    pub library: PathBuf,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            library: CaptureConfig::default().directory.join("Calibration"),
+        }
+    }
+}
+
+/// Focuser settings
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FocuserConfig {
+    /// Steps overshot when moving inward, so every move ends going outward
+    pub backlash_steps: u32,
+}
+
+/// Connection to PHD2 and how it dithers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Phd2Config {
+    /// PHD2's event server, host:port
+    pub address: String,
+    /// Largest dither (pixels) each way
+    pub dither_pixels: f64,
+    /// Guiding has settled once within this distance (pixels) of the lock
+    /// position...
+    pub settle_pixels: f64,
+    /// ...for this long
+    pub settle_secs: f64,
+    /// Give up on settling after this long
+    pub settle_timeout_secs: f64,
+}
+
+impl Default for Phd2Config {
+    fn default() -> Self {
+        Self {
+            address: "localhost:4400".to_owned(),
+            dither_pixels: 5.0,
+            settle_pixels: 1.5,
+            settle_secs: 10.0,
+            settle_timeout_secs: 60.0,
+        }
+    }
+}
+
+/// Program that plate solves
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SolverType {
+    Astap,
+    AstrometryNet,
+}
+
+impl fmt::Display for SolverType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverType::Astap => write!(f, "ASTAP"),
+            SolverType::AstrometryNet => write!(f, "astrometry.net"),
+        }
+    }
+}
+
+/// How frames are plate solved
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PlateSolveConfig {
+    pub solver: SolverType,
+    /// ASTAP's command line program
+    pub astap_path: PathBuf,
+    /// astrometry.net's solve-field
+    pub solve_field_path: PathBuf,
+    /// Arcseconds per (binned) pixel; 0 leaves it to the solver
+    pub pixel_scale_arcsec: f64,
+    /// How far (degrees) from the mount's position a near solve looks
+    pub search_radius_deg: f64,
+    /// Give up on a solve after this long
+    pub timeout_secs: f64,
+    /// Exposure of the frames taken to solve
+    pub exposure_secs: f64,
+    /// Centering stops once the frame is this close (arcminutes) to the target
+    #[serde(default = "default_center_tolerance")]
+    pub center_tolerance_arcmin: f64,
+    /// Slews centering makes before giving up
+    #[serde(default = "default_center_attempts")]
+    pub center_attempts: u32,
+}
+
+fn default_center_tolerance() -> f64 {
+    1.0
+}
+
+fn default_center_attempts() -> u32 {
+    5
+}
+
+impl Default for PlateSolveConfig {
+    fn default() -> Self {
+        Self {
+            solver: SolverType::Astap,
+            astap_path: PathBuf::from("astap"),
+            solve_field_path: PathBuf::from("solve-field"),
+            pixel_scale_arcsec: 0.0,
+            search_radius_deg: 10.0,
+            timeout_secs: 60.0,
+            exposure_secs: 5.0,
+            center_tolerance_arcmin: default_center_tolerance(),
+            center_attempts: default_center_attempts(),
+        }
+    }
+}
+
+/// How the telescope controller's heaters keep the optics clear of dew
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DewControlConfig {
+    /// Degrees (°C) above the dew point the optics are held
+    pub margin_celsius: f64,
+    /// Which of heaters 1-3 are switched automatically rather than by hand
+    pub auto_heaters: [bool; 3],
+}
+
+impl Default for DewControlConfig {
+    fn default() -> Self {
+        Self {
+            margin_celsius: 3.0,
+            auto_heaters: [false; 3],
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub location: Location,
     pub server_list: Vec<String>,
     pub selected_server: Option<String>,
     pub cameras: Vec<CameraConfig>,
+    #[serde(default)]
+    pub planetarium_link: PlanetariumLinkConfig,
+    #[serde(default)]
+    pub mount_limits: MountLimitsConfig,
+    #[serde(default)]
+    pub capture: CaptureConfig,
+    #[serde(default)]
+    pub focuser: FocuserConfig,
+    #[serde(default)]
+    pub phd2: Phd2Config,
+    #[serde(default)]
+    pub plate_solve: PlateSolveConfig,
+    #[serde(default)]
+    pub calibration: CalibrationConfig,
+    /// Readout settings for each INDI camera, by device name
+    #[serde(default)]
+    pub camera_defaults: HashMap<String, CameraDefaults>,
+    /// Names given to the power box's outlets, by outlet number
+    #[serde(default)]
+    pub outlet_names: HashMap<u32, String>,
+    #[serde(default)]
+    pub dew_control: DewControlConfig,
 }
 
 impl Default for Config {
@@ -64,6 +319,16 @@ impl Default for Config {
             server_list: vec![],
             cameras: vec![],
             selected_server: None,
+            planetarium_link: PlanetariumLinkConfig::default(),
+            mount_limits: MountLimitsConfig::default(),
+            capture: CaptureConfig::default(),
+            focuser: FocuserConfig::default(),
+            phd2: Phd2Config::default(),
+            plate_solve: PlateSolveConfig::default(),
+            calibration: CalibrationConfig::default(),
+            camera_defaults: HashMap::new(),
+            outlet_names: HashMap::new(),
+            dew_control: DewControlConfig::default(),
         }
     }
 }
@@ -148,6 +413,65 @@ impl Config {
         }
         Config::persist().await
     }
+    pub async fn set_capture(capture: CaptureConfig) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.capture = capture;
+        }
+        Config::persist().await
+    }
+    pub async fn set_focuser_backlash(backlash_steps: u32) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.focuser.backlash_steps = backlash_steps;
+        }
+        Config::persist().await
+    }
+    pub async fn set_phd2(phd2: Phd2Config) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.phd2 = phd2;
+        }
+        Config::persist().await
+    }
+    pub async fn set_plate_solve(plate_solve: PlateSolveConfig) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.plate_solve = plate_solve;
+        }
+        Config::persist().await
+    }
+    pub async fn set_calibration(calibration: CalibrationConfig) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.calibration = calibration;
+        }
+        Config::persist().await
+    }
+    pub async fn set_camera_defaults(
+        camera: String,
+        defaults: CameraDefaults,
+    ) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.camera_defaults.insert(camera, defaults);
+        }
+        Config::persist().await
+    }
+    pub async fn set_outlet_names(names: HashMap<u32, String>) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.outlet_names = names;
+        }
+        Config::persist().await
+    }
+    pub async fn set_dew_control(dew_control: DewControlConfig) -> SiderealResult<()> {
+        {
+            let mut guard = GLOBAL_CONFIG.write().await;
+            guard.dew_control = dew_control;
+        }
+        Config::persist().await
+    }
 }
 
 /// Global shared config, accessible asynchronously
@@ -0,0 +1,639 @@
+// fits/mod.rs
+
+use chrono::{DateTime, Duration, Local, Utc};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config::Config,
+    model::{SiderealError, SiderealResult},
+};
+
+/// FITS files are made of blocks this long
+const BLOCK_SIZE: usize = 2880;
+/// Each header card is one line this long
+const CARD_SIZE: usize = 80;
+
+/// What a frame is of, for IMAGETYP and where calibration frames are filed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameType {
+    Light,
+    Dark,
+    Bias,
+    Flat,
+}
+
+impl FrameType {
+    /// IMAGETYP as most stacking programs expect it
+    fn header_value(self) -> &'static str {
+        match self {
+            FrameType::Light => "Light Frame",
+            FrameType::Dark => "Dark Frame",
+            FrameType::Bias => "Bias Frame",
+            FrameType::Flat => "Flat Field",
+        }
+    }
+}
+
+impl fmt::Display for FrameType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameType::Light => write!(f, "Light"),
+            FrameType::Dark => write!(f, "Dark"),
+            FrameType::Bias => write!(f, "Bias"),
+            FrameType::Flat => write!(f, "Flat"),
+        }
+    }
+}
+
+/// What's known about a frame when it's saved, for its header and file name
+#[derive(Debug, Clone)]
+pub struct FrameMetadata {
+    pub frame_type: FrameType,
+    pub target: String,
+    pub exposure_secs: f64,
+    pub gain: Option<f64>,
+    pub binning: u32,
+    pub filter: Option<String>,
+    /// Sensor temperature (°C) at the end of the exposure
+    pub sensor_temp_c: Option<f64>,
+    /// Camera device name
+    pub instrument: Option<String>,
+    /// Where the mount was pointing, as (RA hours, Dec degrees)
+    pub position: Option<(f64, f64)>,
+    pub started: DateTime<Utc>,
+    pub finished: DateTime<Utc>,
+    /// Number of the frame within its step, from 1
+    pub frame: usize,
+}
+
+enum CardValue {
+    Text(String),
+    Number(f64, usize),
+    Integer(i64),
+}
+
+/// One 80-column header card: keyword, value and comment
+fn card(keyword: &str, value: CardValue, comment: &str) -> String {
+    let value = match value {
+        // strings start in column 11 and are at least 8 characters inside
+        // their quotes; numbers end in column 30
+        CardValue::Text(text) => {
+            // headers are plain ASCII
+            let text: String = text
+                .chars()
+                .map(|c| {
+                    if c.is_ascii() && !c.is_ascii_control() {
+                        c
+                    } else {
+                        '?'
+                    }
+                })
+                .collect();
+            format!("'{:<8}'", text.replace('\'', "''"))
+        }
+        CardValue::Number(number, decimals) => format!("{:>20.*}", decimals, number),
+        CardValue::Integer(number) => format!("{:>20}", number),
+    };
+    let mut card = format!("{:<8}= {} / {}", keyword, value, comment);
+    card.truncate(CARD_SIZE);
+    format!("{:<80}", card)
+}
+
+/// RA hours as "HH MM SS.ss"
+pub fn sexagesimal_ra(hours: f64) -> String {
+    // rounding up to 24h wraps to 0h
+    let total = (hours.rem_euclid(24.0) * 360_000.0).round() as i64 % 8_640_000;
+    format!(
+        "{:02} {:02} {:02}.{:02}",
+        total / 360_000,
+        total / 6_000 % 60,
+        total / 100 % 60,
+        total % 100
+    )
+}
+
+/// Dec degrees as "+DD MM SS.s"
+pub fn sexagesimal_dec(degrees: f64) -> String {
+    let sign = if degrees < 0.0 { '-' } else { '+' };
+    let total = (degrees.abs() * 36_000.0).round() as i64;
+    format!(
+        "{}{:02} {:02} {:02}.{}",
+        sign,
+        total / 36_000,
+        total / 600 % 60,
+        total / 10 % 60,
+        total % 10
+    )
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.3f").to_string()
+}
+
+/// The cards Sidereal writes, replacing any the driver wrote with the same
+/// keyword
+fn metadata_cards(meta: &FrameMetadata, config: &Config) -> Vec<String> {
+    use CardValue::*;
+    let mut cards = vec![
+        card(
+            "IMAGETYP",
+            Text(meta.frame_type.header_value().to_owned()),
+            "Type of frame",
+        ),
+        card("OBJECT", Text(meta.target.clone()), "Target"),
+        card(
+            "EXPTIME",
+            Number(meta.exposure_secs, 3),
+            "Exposure time (s)",
+        ),
+        card("XBINNING", Integer(meta.binning as i64), "Binning"),
+        card("YBINNING", Integer(meta.binning as i64), "Binning"),
+        card(
+            "DATE-OBS",
+            Text(timestamp(meta.started)),
+            "Exposure start (UTC)",
+        ),
+        card(
+            "DATE-END",
+            Text(timestamp(meta.finished)),
+            "Exposure end (UTC)",
+        ),
+        card(
+            "SITELAT",
+            Number(config.location.latitude as f64, 6),
+            "Site latitude (deg)",
+        ),
+        card(
+            "SITELONG",
+            Number(config.location.longitude as f64, 6),
+            "Site longitude (deg, east positive)",
+        ),
+        card(
+            "SITEELEV",
+            Number(config.location.altitude as f64, 1),
+            "Site elevation (m)",
+        ),
+        card("SWCREATE", Text("Sidereal".to_owned()), "Capture software"),
+    ];
+    if let Some(gain) = meta.gain {
+        cards.push(card("GAIN", Number(gain, 1), "Sensor gain"));
+    }
+    if let Some(filter) = &meta.filter {
+        cards.push(card("FILTER", Text(filter.clone()), "Filter"));
+    }
+    if let Some(temp) = meta.sensor_temp_c {
+        cards.push(card("CCD-TEMP", Number(temp, 2), "Sensor temperature (C)"));
+    }
+    if let Some(instrument) = &meta.instrument {
+        cards.push(card("INSTRUME", Text(instrument.clone()), "Camera"));
+    }
+    if let Some((ra, dec)) = meta.position {
+        cards.push(card("OBJCTRA", Text(sexagesimal_ra(ra)), "Mount RA (hms)"));
+        cards.push(card(
+            "OBJCTDEC",
+            Text(sexagesimal_dec(dec)),
+            "Mount Dec (dms)",
+        ));
+        cards.push(card("RA", Number(ra * 15.0, 6), "Mount RA (deg)"));
+        cards.push(card("DEC", Number(dec, 6), "Mount Dec (deg)"));
+    }
+    cards
+}
+
+/// Keyword of a header card
+fn keyword(card: &[u8]) -> String {
+    String::from_utf8_lossy(&card[..8]).trim_end().to_owned()
+}
+
+/// The primary header's cards before END, and where the data after it starts
+fn primary_header(data: &[u8]) -> SiderealResult<(&[u8], usize)> {
+    if !data.starts_with(b"SIMPLE  =") {
+        return Err(SiderealError::FormatError(
+            "The frame isn't a FITS file".to_owned(),
+        ));
+    }
+    let end = data
+        .chunks_exact(CARD_SIZE)
+        .position(|card| keyword(card) == "END")
+        .ok_or_else(|| SiderealError::FormatError("FITS header has no END card".to_owned()))?;
+    let header_len = ((end + 1) * CARD_SIZE).div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+    if data.len() < header_len {
+        return Err(SiderealError::FormatError(
+            "FITS header is truncated".to_owned(),
+        ));
+    }
+    Ok((&data[..end * CARD_SIZE], header_len))
+}
+
+/// Every numeric value in a FITS file's primary header, by keyword; enough
+/// to read a plate solver's WCS
+pub fn header_numbers(data: &[u8]) -> SiderealResult<HashMap<String, f64>> {
+    let (header, _) = primary_header(data)?;
+    Ok(header
+        .chunks_exact(CARD_SIZE)
+        .filter(|card| &card[8..10] == b"= ")
+        .filter_map(|card| {
+            let value = String::from_utf8_lossy(&card[10..]);
+            let number = value.split('/').next()?.trim().parse::<f64>().ok()?;
+            Some((keyword(card), number))
+        })
+        .collect())
+}
+
+/// `data` with the metadata cards in its primary header. The image data is
+/// left as the driver sent it.
+fn with_metadata(data: &[u8], cards: &[String]) -> SiderealResult<Vec<u8>> {
+    let (existing, header_len) = primary_header(data)?;
+
+    let ours: Vec<String> = cards.iter().map(|card| keyword(card.as_bytes())).collect();
+    let mut header: Vec<u8> = existing
+        .chunks_exact(CARD_SIZE)
+        .filter(|card| {
+            let keyword = keyword(card);
+            // blank padding cards go, they'd only end up between ours
+            let blank = keyword.is_empty() && card.iter().all(|&b| b == b' ');
+            !blank && !ours.contains(&keyword)
+        })
+        .flatten()
+        .copied()
+        .collect();
+    for card in cards {
+        header.extend_from_slice(card.as_bytes());
+    }
+    header.extend_from_slice(format!("{:<80}", "END").as_bytes());
+    header.resize(header.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE, b' ');
+
+    header.extend_from_slice(&data[header_len..]);
+    Ok(header)
+}
+
+/// A frame's pixel values, in the order the file has them
+#[derive(Debug, Clone)]
+pub struct FitsImage {
+    pub width: usize,
+    pub height: usize,
+    /// Row by row, scaled by BZERO/BSCALE to physical values (ADU)
+    pub pixels: Vec<f32>,
+}
+
+impl FitsImage {
+    pub fn pixel(&self, x: usize, y: usize) -> Option<f32> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y * self.width + x])
+        } else {
+            None
+        }
+    }
+}
+
+/// Read the image in a FITS file's primary HDU. Colour frames (three planes)
+/// are averaged to one.
+pub fn decode(data: &[u8]) -> SiderealResult<FitsImage> {
+    let (header, header_len) = primary_header(data)?;
+    let value = |wanted: &str| {
+        header
+            .chunks_exact(CARD_SIZE)
+            .find(|card| keyword(card) == wanted)
+            .and_then(|card| {
+                let value = String::from_utf8_lossy(&card[10..]);
+                value.split('/').next()?.trim().parse::<f64>().ok()
+            })
+    };
+    let invalid = |what: &str| SiderealError::FormatError(format!("FITS frame has {what}"));
+
+    let bitpix = value("BITPIX").ok_or_else(|| invalid("no BITPIX"))? as i64;
+    let naxis = value("NAXIS").unwrap_or(0.0) as usize;
+    if !(2..=3).contains(&naxis) {
+        return Err(invalid("no 2D image"));
+    }
+    let width = value("NAXIS1").unwrap_or(0.0) as usize;
+    let height = value("NAXIS2").unwrap_or(0.0) as usize;
+    let planes = if naxis == 3 {
+        value("NAXIS3").unwrap_or(1.0) as usize
+    } else {
+        1
+    };
+    let bzero = value("BZERO").unwrap_or(0.0);
+    let bscale = value("BSCALE").unwrap_or(1.0);
+
+    let bytes = (bitpix.unsigned_abs() / 8) as usize;
+    let read: fn(&[u8]) -> f64 = match bitpix {
+        8 => |b| b[0] as f64,
+        16 => |b| i16::from_be_bytes([b[0], b[1]]) as f64,
+        32 => |b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        -32 => |b| f32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        -64 => |b| f64::from_be_bytes(b[..8].try_into().unwrap()),
+        _ => return Err(invalid(&format!("unsupported BITPIX {bitpix}"))),
+    };
+    let plane_len = width * height;
+    let image = data
+        .get(header_len..header_len + plane_len * planes * bytes)
+        .filter(|_| plane_len > 0 && planes > 0)
+        .ok_or_else(|| invalid("less data than its header says"))?;
+
+    let mut pixels = vec![0.0f32; plane_len];
+    for plane in image.chunks_exact(plane_len * bytes) {
+        for (pixel, raw) in pixels.iter_mut().zip(plane.chunks_exact(bytes)) {
+            *pixel += ((bzero + bscale * read(raw)) / planes as f64) as f32;
+        }
+    }
+    Ok(FitsImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Keep a template value to characters that are safe in a file name
+fn sanitize(value: &str) -> String {
+    let value: String = value
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    value.trim_matches('.').to_owned()
+}
+
+/// Path of a frame from the file template. {date} is the date the night
+/// started on, so a night's frames stay together past midnight.
+fn frame_path(directory: &Path, template: &str, meta: &FrameMetadata) -> PathBuf {
+    let night = (meta.started.with_timezone(&Local) - Duration::hours(12)).date_naive();
+    let or = |value: String, fallback: &str| {
+        let value = sanitize(&value);
+        if value.is_empty() {
+            fallback.to_owned()
+        } else {
+            value
+        }
+    };
+    let relative = template
+        .replace("{date}", &night.format("%Y-%m-%d").to_string())
+        .replace("{target}", &or(meta.target.clone(), "Unnamed"))
+        .replace(
+            "{filter}",
+            &or(meta.filter.clone().unwrap_or_default(), "NoFilter"),
+        )
+        .replace("{exposure}", &or(meta.exposure_secs.to_string(), "0"))
+        .replace(
+            "{gain}",
+            &or(meta.gain.map(|g| g.to_string()).unwrap_or_default(), "NA"),
+        )
+        .replace("{binning}", &meta.binning.to_string())
+        .replace(
+            "{camera}",
+            &or(meta.instrument.clone().unwrap_or_default(), "Camera"),
+        )
+        .replace(
+            "{temp}",
+            &or(
+                meta.sensor_temp_c
+                    .map(|t| format!("{t:.0}C"))
+                    .unwrap_or_default(),
+                "NA",
+            ),
+        )
+        .replace("{frame}", &format!("{:04}", meta.frame));
+    directory.join(relative)
+}
+
+/// Write a frame from the camera to the capture directory with its metadata
+/// in the header, and return where it went. An existing file is never
+/// overwritten; a number is added to the name instead.
+pub async fn save_frame(data: &[u8], meta: &FrameMetadata) -> SiderealResult<PathBuf> {
+    let config = Config::get().await;
+    let base = frame_path(
+        &config.capture.directory,
+        &config.capture.file_template,
+        meta,
+    );
+    write_frame(data, meta, &config, base).await
+}
+
+/// Where each type of calibration frame goes in the library, by the settings
+/// it has to match the lights with
+fn calibration_template(frame_type: FrameType) -> Option<&'static str> {
+    match frame_type {
+        FrameType::Dark => {
+            Some("Darks/gain{gain}_bin{binning}_{exposure}s_{temp}/Dark_{date}_{frame}")
+        }
+        FrameType::Bias => Some("Bias/gain{gain}_bin{binning}_{temp}/Bias_{date}_{frame}"),
+        FrameType::Flat => Some("Flats/{filter}_gain{gain}_bin{binning}/Flat_{date}_{frame}"),
+        FrameType::Light => None,
+    }
+}
+
+/// Like [`save_frame`], but files the frame in the calibration library under
+/// `library` according to its type and settings
+pub async fn save_calibration_frame(
+    data: &[u8],
+    meta: &FrameMetadata,
+    library: &Path,
+) -> SiderealResult<PathBuf> {
+    let template = calibration_template(meta.frame_type).ok_or_else(|| {
+        SiderealError::FileError("Light frames go in the capture directory".to_owned())
+    })?;
+    let config = Config::get().await;
+    let base = frame_path(library, template, meta);
+    write_frame(data, meta, &config, base).await
+}
+
+/// Write a frame to `base` plus ".fits", numbering it rather than overwrite
+async fn write_frame(
+    data: &[u8],
+    meta: &FrameMetadata,
+    config: &Config,
+    base: PathBuf,
+) -> SiderealResult<PathBuf> {
+    let contents = with_metadata(data, &metadata_cards(meta, config))?;
+
+    // pushed rather than set as the extension, the name may have dots in it
+    let with_suffix = |suffix: &str| {
+        let mut name = base.clone().into_os_string();
+        name.push(suffix);
+        PathBuf::from(name)
+    };
+    let mut path = with_suffix(".fits");
+    let mut copy = 1;
+    while tokio::fs::try_exists(&path).await.unwrap_or(false) {
+        path = with_suffix(&format!("_{copy}.fits"));
+        copy += 1;
+    }
+
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| SiderealError::FileError(format!("{}: {e}", dir.display())))?;
+    }
+    tokio::fs::write(&path, contents)
+        .await
+        .map_err(|e| SiderealError::FileError(format!("{}: {e}", path.display())))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A primary header of these cards, END and padding, then `data`
+    fn fits(cards: &[String], data: &[u8]) -> Vec<u8> {
+        let mut file: Vec<u8> = cards.iter().flat_map(|c| c.bytes()).collect();
+        file.extend_from_slice(format!("{:<80}", "END").as_bytes());
+        file.resize(file.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE, b' ');
+        file.extend_from_slice(data);
+        file
+    }
+
+    fn driver_cards() -> Vec<String> {
+        vec![
+            format!("{:<80}", "SIMPLE  =                    T"),
+            card("BITPIX", CardValue::Integer(16), "Bits per pixel"),
+            card("NAXIS", CardValue::Integer(0), "No image"),
+            card("OBJECT", CardValue::Text("Driver".to_owned()), "Target"),
+            " ".repeat(CARD_SIZE),
+        ]
+    }
+
+    fn cards_of(header: &[u8]) -> Vec<String> {
+        header
+            .chunks_exact(CARD_SIZE)
+            .map(|card| String::from_utf8_lossy(card).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_card_layout() {
+        let text = card("OBJECT", CardValue::Text("M31".to_owned()), "Target");
+        assert_eq!(text.len(), CARD_SIZE);
+        assert_eq!(text.trim_end(), "OBJECT  = 'M31     ' / Target");
+
+        // numbers end in column 30
+        let number = card("EXPTIME", CardValue::Number(1.5, 3), "Exposure");
+        assert_eq!(&number[..30], "EXPTIME =                1.500");
+        let integer = card("XBINNING", CardValue::Integer(-2), "Binning");
+        assert_eq!(&integer[..30], "XBINNING=                   -2");
+
+        let long = card("COMMENT1", CardValue::Integer(1), &"x".repeat(100));
+        assert_eq!(long.len(), CARD_SIZE);
+    }
+
+    #[test]
+    fn test_card_quoting() {
+        let quoted = card("OBJECT", CardValue::Text("Barnard's".to_owned()), "");
+        assert!(quoted.starts_with("OBJECT  = 'Barnard''s' / "));
+        let ascii = card("OBJECT", CardValue::Text("M\u{b0}31\n".to_owned()), "");
+        assert!(ascii.starts_with("OBJECT  = 'M?31?   ' / "));
+    }
+
+    #[test]
+    fn test_sexagesimal_rounding_carry() {
+        assert_eq!(sexagesimal_ra(5.5), "05 30 00.00");
+        // 1h 59m 59.999s rounds up through the minutes and hours
+        assert_eq!(
+            sexagesimal_ra(1.0 + 59.0 / 60.0 + 59.999 / 3600.0),
+            "02 00 00.00"
+        );
+        assert_eq!(sexagesimal_ra(24.0 - 0.001 / 3600.0), "00 00 00.00");
+        assert_eq!(sexagesimal_ra(-1.0), "23 00 00.00");
+
+        assert_eq!(sexagesimal_dec(-0.5), "-00 30 00.0");
+        assert_eq!(
+            sexagesimal_dec(10.0 + 59.0 / 60.0 + 59.96 / 3600.0),
+            "+11 00 00.0"
+        );
+        assert_eq!(
+            sexagesimal_dec(-(45.0 + 29.0 / 60.0 + 59.99 / 3600.0)),
+            "-45 30 00.0"
+        );
+    }
+
+    #[test]
+    fn test_primary_header() {
+        let file = fits(&driver_cards(), &[1, 2, 3]);
+        let (header, header_len) = primary_header(&file).unwrap();
+        assert_eq!(header.len(), driver_cards().len() * CARD_SIZE);
+        assert_eq!(header_len, BLOCK_SIZE);
+        assert_eq!(&file[header_len..], &[1, 2, 3]);
+
+        // 36 cards with END fill exactly one block, 37 need two
+        let one_block = vec![driver_cards()[0].clone(); 35];
+        assert_eq!(
+            primary_header(&fits(&one_block, &[])).unwrap().1,
+            BLOCK_SIZE
+        );
+        let two_blocks = vec![driver_cards()[0].clone(); 36];
+        assert_eq!(
+            primary_header(&fits(&two_blocks, &[])).unwrap().1,
+            2 * BLOCK_SIZE
+        );
+    }
+
+    #[test]
+    fn test_primary_header_rejects_invalid() {
+        assert!(primary_header(b"not a FITS file").is_err());
+
+        let no_end: Vec<u8> = driver_cards().iter().flat_map(|c| c.bytes()).collect();
+        assert!(primary_header(&no_end).is_err());
+
+        // END is there, but the block it's in is cut short
+        let file = fits(&driver_cards(), &[]);
+        let truncated = &file[..file.len() - 1];
+        assert!(matches!(
+            primary_header(truncated),
+            Err(SiderealError::FormatError(_))
+        ));
+    }
+
+    #[test]
+    fn test_with_metadata_replaces_cards() {
+        let data = [7u8; 100];
+        let file = fits(&driver_cards(), &data);
+        let ours = vec![
+            card("OBJECT", CardValue::Text("M42".to_owned()), "Target"),
+            card("GAIN", CardValue::Number(100.0, 1), "Sensor gain"),
+        ];
+        let out = with_metadata(&file, &ours).unwrap();
+
+        let (header, header_len) = primary_header(&out).unwrap();
+        assert_eq!(header_len % BLOCK_SIZE, 0);
+        assert_eq!(&out[header_len..], &data);
+        // the driver's cards stay, without its OBJECT or blank padding,
+        // and ours follow them
+        assert_eq!(
+            cards_of(header),
+            vec![
+                driver_cards()[0].clone(),
+                driver_cards()[1].clone(),
+                driver_cards()[2].clone(),
+                ours[0].clone(),
+                ours[1].clone(),
+            ]
+        );
+        // padded with spaces after END
+        assert!(out[header.len() + CARD_SIZE..header_len]
+            .iter()
+            .all(|&b| b == b' '));
+    }
+
+    #[test]
+    fn test_with_metadata_grows_header() {
+        let file = fits(&driver_cards(), &[9; 10]);
+        let ours: Vec<String> = (0..40)
+            .map(|i| card(&format!("KEY{i}"), CardValue::Integer(i), ""))
+            .collect();
+        let out = with_metadata(&file, &ours).unwrap();
+        let (header, header_len) = primary_header(&out).unwrap();
+        assert_eq!(header.len(), (4 + 40) * CARD_SIZE);
+        assert_eq!(header_len, 2 * BLOCK_SIZE);
+        assert_eq!(&out[header_len..], &[9; 10]);
+    }
+}
@@ -0,0 +1,710 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::Utc;
+use iced::widget::{checkbox, column, container, progress_bar, row, text, Column};
+use iced::{task, Alignment, Element, Length, Task};
+
+use crate::app::Message as MainMessage;
+use crate::config::{CalibrationConfig, Config};
+use crate::fits::{self, FrameMetadata, FrameType};
+use crate::gui::styles::button_style::{sidereal_button, stop_track_button, track_button};
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::indi_handler::{camera, telescope_controller};
+use crate::model::{SiderealError, SiderealResult};
+
+/// Longest the lens cap may take to close
+const LENS_CAP_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest the flat light may take to switch
+const FLAT_LIGHT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Exposure of bias frames, as short as cameras generally go
+const BIAS_EXPOSURE_SECS: f64 = 0.001;
+/// Library folders, one per frame type
+const LIBRARY_FOLDERS: [&str; 3] = ["Darks", "Bias", "Flats"];
+
+#[derive(Debug, Clone, Copy)]
+pub enum CalibrationField {
+    Library,
+    Gains,
+    Binning,
+    DarkExposures,
+    DarkCount,
+    BiasCount,
+    FlatFilters,
+    FlatExposure,
+    FlatCount,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FieldChanged {
+        field: CalibrationField,
+        value: String,
+    },
+    DarksToggled(bool),
+    BiasToggled(bool),
+    FlatsToggled(bool),
+    SaveLibrary,
+    ScanLibrary,
+    LibraryScanned(Vec<LibrarySet>),
+    Start,
+    Abort,
+    FrameFinished(SiderealResult<PathBuf>),
+}
+
+/// Frames of one type at one set of settings
+#[derive(Debug, Clone, Copy)]
+struct CalibrationSet {
+    frame_type: FrameType,
+    count: u32,
+    exposure_secs: f64,
+    /// `None` leaves the camera's gain alone
+    gain: Option<f64>,
+    binning: u32,
+    /// Filter wheel slot, for flats
+    filter: Option<u32>,
+}
+
+impl CalibrationSet {
+    fn describe(&self) -> String {
+        let mut description = format!("{} {}", self.count, self.frame_type);
+        if self.frame_type != FrameType::Bias {
+            description += &format!(" {}s", self.exposure_secs);
+        }
+        if let Some(gain) = self.gain {
+            description += &format!(" gain {gain}");
+        }
+        description += &format!(" bin {}", self.binning);
+        if let Some(slot) = self.filter {
+            description += &format!(" filter {slot}");
+        }
+        description
+    }
+}
+
+/// A folder of the library and how many frames are in it
+#[derive(Debug, Clone)]
+pub struct LibrarySet {
+    name: String,
+    frames: usize,
+}
+
+/// The wizard's settings, as typed
+#[derive(Debug, Clone)]
+struct CalibrationInput {
+    library: String,
+    /// Comma separated; blank to leave the camera's gain alone
+    gains: String,
+    binning: String,
+    darks: bool,
+    /// Comma separated
+    dark_exposures: String,
+    dark_count: String,
+    bias: bool,
+    bias_count: String,
+    flats: bool,
+    /// Comma separated wheel slots; blank to leave the wheel where it is
+    flat_filters: String,
+    flat_exposure: String,
+    flat_count: String,
+}
+
+impl Default for CalibrationInput {
+    fn default() -> Self {
+        Self {
+            library: CalibrationConfig::default().library.display().to_string(),
+            gains: String::new(),
+            binning: "1".to_owned(),
+            darks: true,
+            dark_exposures: "60, 120, 300".to_owned(),
+            dark_count: "20".to_owned(),
+            bias: true,
+            bias_count: "50".to_owned(),
+            flats: false,
+            flat_filters: String::new(),
+            flat_exposure: "1".to_owned(),
+            flat_count: "20".to_owned(),
+        }
+    }
+}
+
+/// Every value in a comma separated list
+fn parse_list<T: std::str::FromStr>(what: &str, value: &str) -> SiderealResult<Vec<T>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.parse::<T>()
+                .map_err(|_| SiderealError::ParseError(format!("Invalid {what} `{item}`")))
+        })
+        .collect()
+}
+
+impl CalibrationInput {
+    /// The sets to take, flats first while the light is on, then bias and
+    /// darks with it off
+    fn plan(&self) -> SiderealResult<Vec<CalibrationSet>> {
+        let count = |what: &str, value: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| SiderealError::ParseError(format!("Invalid {what} `{value}`")))
+        };
+        let exposure = |value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|secs| secs.is_finite() && *secs > 0.0)
+                .ok_or_else(|| SiderealError::ParseError(format!("Invalid exposure `{value}`")))
+        };
+        let gains: Vec<Option<f64>> = match parse_list::<f64>("gain", &self.gains)? {
+            gains if gains.is_empty() => vec![None],
+            gains => gains.into_iter().map(Some).collect(),
+        };
+        let binning = self
+            .binning
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|bin| (1..=4).contains(bin))
+            .ok_or_else(|| {
+                SiderealError::ParseError(format!("Invalid binning `{}`", self.binning))
+            })?;
+        let set = |frame_type, count, exposure_secs, gain, filter| CalibrationSet {
+            frame_type,
+            count,
+            exposure_secs,
+            gain,
+            binning,
+            filter,
+        };
+
+        let mut sets = vec![];
+        if self.flats {
+            let flat_count = count("flat count", &self.flat_count)?;
+            let flat_exposure = exposure(&self.flat_exposure)?;
+            let filters: Vec<Option<u32>> =
+                match parse_list::<u32>("filter slot", &self.flat_filters)? {
+                    filters if filters.is_empty() => vec![None],
+                    filters => filters.into_iter().map(Some).collect(),
+                };
+            for &filter in &filters {
+                for &gain in &gains {
+                    sets.push(set(
+                        FrameType::Flat,
+                        flat_count,
+                        flat_exposure,
+                        gain,
+                        filter,
+                    ));
+                }
+            }
+        }
+        if self.bias {
+            let bias_count = count("bias count", &self.bias_count)?;
+            for &gain in &gains {
+                sets.push(set(
+                    FrameType::Bias,
+                    bias_count,
+                    BIAS_EXPOSURE_SECS,
+                    gain,
+                    None,
+                ));
+            }
+        }
+        if self.darks {
+            let dark_count = count("dark count", &self.dark_count)?;
+            let exposures = self
+                .dark_exposures
+                .split(',')
+                .filter(|item| !item.trim().is_empty())
+                .map(exposure)
+                .collect::<SiderealResult<Vec<f64>>>()?;
+            if exposures.is_empty() {
+                return Err(SiderealError::ParseError(
+                    "Give at least one dark exposure".to_owned(),
+                ));
+            }
+            for &gain in &gains {
+                for &secs in &exposures {
+                    sets.push(set(FrameType::Dark, dark_count, secs, gain, None));
+                }
+            }
+        }
+        if sets.is_empty() {
+            return Err(SiderealError::ParseError(
+                "Pick darks, bias or flats to take".to_owned(),
+            ));
+        }
+        Ok(sets)
+    }
+}
+
+/// Take one frame of `set` into the library; `frame` is its number within
+/// the set. At the first frame of a set the lens cap is closed, the flat
+/// light switched for the frame type and the camera set up.
+async fn take_calibration_frame(
+    set: CalibrationSet,
+    frame: usize,
+    configure: bool,
+    library: PathBuf,
+) -> SiderealResult<PathBuf> {
+    if configure {
+        telescope_controller::set_lens_cap(false).await?;
+        telescope_controller::wait_for_lens_cap(false, LENS_CAP_TIMEOUT).await?;
+        let light = set.frame_type == FrameType::Flat;
+        telescope_controller::set_flat_light(light).await?;
+        telescope_controller::wait_for_flat_light(light, FLAT_LIGHT_TIMEOUT).await?;
+        if let Some(gain) = set.gain {
+            camera::set_gain(gain).await?;
+        }
+        camera::set_binning(set.binning).await?;
+        if let Some(slot) = set.filter {
+            camera::set_filter(slot).await?;
+        }
+    }
+    let started = Utc::now();
+    let data = camera::expose(set.exposure_secs).await?;
+    let finished = Utc::now();
+
+    let filter = match set.filter {
+        Some(slot) => Some(
+            camera::filter_name(slot)
+                .await
+                .unwrap_or_else(|| slot.to_string()),
+        ),
+        None => None,
+    };
+    let meta = FrameMetadata {
+        frame_type: set.frame_type,
+        target: set.frame_type.to_string(),
+        exposure_secs: set.exposure_secs,
+        gain: match set.gain {
+            Some(gain) => Some(gain),
+            None => camera::gain().await,
+        },
+        binning: set.binning,
+        filter,
+        sensor_temp_c: camera::sensor_temperature().await,
+        instrument: camera::device_name().await,
+        position: None,
+        started,
+        finished,
+        frame,
+    };
+    fits::save_calibration_frame(&data, &meta, &library).await
+}
+
+/// Every set folder in the library and how many frames it holds
+async fn scan_library(library: PathBuf) -> Vec<LibrarySet> {
+    async fn count_frames(directory: &Path) -> usize {
+        let Ok(mut entries) = tokio::fs::read_dir(directory).await else {
+            return 0;
+        };
+        let mut frames = 0;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.path().extension().is_some_and(|ext| ext == "fits") {
+                frames += 1;
+            }
+        }
+        frames
+    }
+
+    let mut sets = vec![];
+    for folder in LIBRARY_FOLDERS {
+        let Ok(mut entries) = tokio::fs::read_dir(library.join(folder)).await else {
+            continue;
+        };
+        let mut found = vec![];
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.is_dir() {
+                found.push(LibrarySet {
+                    name: format!("{folder}/{}", entry.file_name().to_string_lossy()),
+                    frames: count_frames(&path).await,
+                });
+            }
+        }
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        sets.extend(found);
+    }
+    sets
+}
+
+/// The wizard working through its sets
+struct RunningCalibration {
+    sets: Vec<CalibrationSet>,
+    /// Index of the set being taken
+    set: usize,
+    /// Frames of that set already taken
+    frame: u32,
+    taken: u32,
+    library: PathBuf,
+    /// The frame in progress, to drop it on abort
+    handle: Option<task::Handle>,
+}
+
+impl RunningCalibration {
+    fn total(&self) -> u32 {
+        self.sets.iter().map(|set| set.count).sum()
+    }
+}
+
+#[derive(Default)]
+pub struct CalibrationState {
+    input: CalibrationInput,
+    running: Option<RunningCalibration>,
+    /// How the last run ended
+    status: Option<String>,
+    library: Vec<LibrarySet>,
+}
+
+/// Switch the flat light off once the wizard is done with it
+fn light_off() -> Task<MainMessage> {
+    Task::perform(
+        telescope_controller::set_flat_light(false),
+        |result| match result {
+            Ok(()) => MainMessage::Noop,
+            Err(e) => MainMessage::ErrorOccurred(e),
+        },
+    )
+}
+
+impl CalibrationState {
+    pub fn on_config_load(&mut self, config: &Config) -> Task<MainMessage> {
+        self.input.library = config.calibration.library.display().to_string();
+        self.scan()
+    }
+
+    fn scan(&self) -> Task<MainMessage> {
+        let library = PathBuf::from(self.input.library.trim());
+        Task::perform(scan_library(library), |sets| {
+            MainMessage::Calibration(Message::LibraryScanned(sets))
+        })
+    }
+
+    /// End the run, dropping the frame in progress
+    pub fn stop(&mut self, status: String) -> Task<MainMessage> {
+        let Some(run) = self.running.take() else {
+            return Task::none();
+        };
+        self.status = Some(status);
+        if let Some(handle) = run.handle {
+            handle.abort();
+        }
+        let abort = Task::perform(camera::abort_exposure(), |result| match result {
+            Ok(()) => MainMessage::Noop,
+            Err(e) => MainMessage::ErrorOccurred(e),
+        });
+        Task::batch([abort, light_off(), self.scan()])
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::FieldChanged { field, value } => {
+                let input = &mut self.input;
+                match field {
+                    CalibrationField::Library => input.library = value,
+                    CalibrationField::Gains => input.gains = value,
+                    CalibrationField::Binning => input.binning = value,
+                    CalibrationField::DarkExposures => input.dark_exposures = value,
+                    CalibrationField::DarkCount => input.dark_count = value,
+                    CalibrationField::BiasCount => input.bias_count = value,
+                    CalibrationField::FlatFilters => input.flat_filters = value,
+                    CalibrationField::FlatExposure => input.flat_exposure = value,
+                    CalibrationField::FlatCount => input.flat_count = value,
+                }
+            }
+            Message::DarksToggled(darks) => self.input.darks = darks,
+            Message::BiasToggled(bias) => self.input.bias = bias,
+            Message::FlatsToggled(flats) => self.input.flats = flats,
+            Message::SaveLibrary => {
+                let library = self.input.library.trim();
+                if library.is_empty() {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        "The calibration library can't be blank".to_owned(),
+                    )));
+                }
+                let calibration = CalibrationConfig {
+                    library: PathBuf::from(library),
+                };
+                return Task::batch([
+                    Task::perform(
+                        Config::set_calibration(calibration),
+                        |result| match result {
+                            Ok(()) => MainMessage::Noop,
+                            Err(e) => MainMessage::ErrorOccurred(e),
+                        },
+                    ),
+                    self.scan(),
+                ]);
+            }
+            Message::ScanLibrary => return self.scan(),
+            Message::LibraryScanned(sets) => self.library = sets,
+            Message::Start => {
+                if self.running.is_some() {
+                    return Task::none();
+                }
+                let sets = match self.input.plan() {
+                    Ok(sets) => sets,
+                    Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+                };
+                let library = self.input.library.trim();
+                if library.is_empty() {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        "The calibration library can't be blank".to_owned(),
+                    )));
+                }
+                self.status = None;
+                self.running = Some(RunningCalibration {
+                    sets,
+                    set: 0,
+                    frame: 0,
+                    taken: 0,
+                    library: PathBuf::from(library),
+                    handle: None,
+                });
+                return self.next_frame();
+            }
+            Message::Abort => return self.stop("Aborted".to_owned()),
+            Message::FrameFinished(result) => {
+                let Some(run) = &mut self.running else {
+                    return Task::none();
+                };
+                run.handle = None;
+                match result {
+                    Ok(_) => {
+                        run.frame += 1;
+                        run.taken += 1;
+                        return self.next_frame();
+                    }
+                    Err(e) => {
+                        self.running = None;
+                        self.status = Some(format!("Stopped: {e}"));
+                        return Task::batch([
+                            Task::done(MainMessage::ErrorOccurred(e)),
+                            light_off(),
+                            self.scan(),
+                        ]);
+                    }
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Start the next frame, or finish once every set is taken
+    fn next_frame(&mut self) -> Task<MainMessage> {
+        let Some(run) = &mut self.running else {
+            return Task::none();
+        };
+        while run
+            .sets
+            .get(run.set)
+            .is_some_and(|set| run.frame >= set.count)
+        {
+            run.set += 1;
+            run.frame = 0;
+        }
+        let Some(&set) = run.sets.get(run.set) else {
+            self.running = None;
+            self.status = Some("Calibration library complete; the lens cap is closed".to_owned());
+            return Task::batch([light_off(), self.scan()]);
+        };
+
+        let (task, handle) = Task::perform(
+            take_calibration_frame(
+                set,
+                run.frame as usize + 1,
+                run.frame == 0,
+                run.library.clone(),
+            ),
+            |result| MainMessage::Calibration(Message::FrameFinished(result)),
+        )
+        .abortable();
+        run.handle = Some(handle);
+        task
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let editable = self.running.is_none();
+        let input = &self.input;
+        let field = |label: &str, placeholder: &str, value: &str, field: CalibrationField| {
+            let text_input = sidereal_text_input(placeholder, value).width(Length::Fill);
+            row![
+                text(label.to_owned()).width(Length::Fixed(150.0)),
+                if editable {
+                    text_input.on_input(move |value| Message::FieldChanged { field, value })
+                } else {
+                    text_input
+                }
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+        };
+        let toggle = |label: &'static str, checked: bool, on_toggle: fn(bool) -> Message| {
+            checkbox(label, checked).on_toggle_maybe(editable.then_some(on_toggle))
+        };
+
+        let library = content_container(
+            column![
+                text("Library"),
+                field("Folder", "path", &input.library, CalibrationField::Library),
+                row![
+                    sidereal_button("Apply", Some(Message::SaveLibrary), editable)
+                        .width(Length::Fill),
+                    sidereal_button("Rescan", Some(Message::ScanLibrary), true).width(Length::Fill),
+                ]
+                .spacing(10),
+                if self.library.is_empty() {
+                    Column::new().push(text("No calibration frames yet").size(12))
+                } else {
+                    self.library
+                        .iter()
+                        .fold(Column::new().spacing(2), |list, set| {
+                            list.push(text(format!("{}: {} frames", set.name, set.frames)).size(12))
+                        })
+                },
+            ]
+            .spacing(10),
+            ContainerLayer::Layer1,
+        )
+        .width(Length::Fill);
+
+        let settings = content_container(
+            column![
+                text("Frames"),
+                field(
+                    "Gains",
+                    "comma separated; blank for the camera's",
+                    &input.gains,
+                    CalibrationField::Gains
+                ),
+                field("Binning", "1-4", &input.binning, CalibrationField::Binning),
+                toggle(
+                    "Flats (lens cap closed, flat light on)",
+                    input.flats,
+                    Message::FlatsToggled
+                ),
+                field(
+                    "Flat filters",
+                    "wheel slots, comma separated; blank for none",
+                    &input.flat_filters,
+                    CalibrationField::FlatFilters
+                ),
+                field(
+                    "Flat exposure (s)",
+                    "seconds",
+                    &input.flat_exposure,
+                    CalibrationField::FlatExposure
+                ),
+                field(
+                    "Flats per set",
+                    "frames",
+                    &input.flat_count,
+                    CalibrationField::FlatCount
+                ),
+                toggle("Bias (lens cap closed)", input.bias, Message::BiasToggled),
+                field(
+                    "Bias per set",
+                    "frames",
+                    &input.bias_count,
+                    CalibrationField::BiasCount
+                ),
+                toggle(
+                    "Darks (lens cap closed)",
+                    input.darks,
+                    Message::DarksToggled
+                ),
+                field(
+                    "Dark exposures (s)",
+                    "seconds, comma separated",
+                    &input.dark_exposures,
+                    CalibrationField::DarkExposures
+                ),
+                field(
+                    "Darks per set",
+                    "frames",
+                    &input.dark_count,
+                    CalibrationField::DarkCount
+                ),
+            ]
+            .spacing(10),
+            ContainerLayer::Layer1,
+        )
+        .width(Length::Fill);
+
+        let mut start_button = track_button(
+            container(text("Build Library"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if editable {
+            start_button = start_button.on_press(Message::Start);
+        }
+        let mut abort_button = stop_track_button(
+            container(text("Abort"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if !editable {
+            abort_button = abort_button.on_press(Message::Abort);
+        }
+
+        let mut run =
+            column![text("Run"), row![start_button, abort_button].spacing(10)].spacing(10);
+        match (&self.running, self.input.plan()) {
+            (Some(running), _) => {
+                let total = running.total();
+                run = run.push(progress_bar(0.0..=total as f32, running.taken as f32).height(10));
+                run = run.push(text(format!("{} of {} frames", running.taken, total)));
+                for (index, set) in running.sets.iter().enumerate() {
+                    let state = match index.cmp(&running.set) {
+                        std::cmp::Ordering::Less => "done".to_owned(),
+                        std::cmp::Ordering::Equal => format!("{} taken", running.frame),
+                        std::cmp::Ordering::Greater => "to come".to_owned(),
+                    };
+                    run = run.push(text(format!("{}: {state}", set.describe())).size(12));
+                }
+            }
+            (None, Ok(sets)) => {
+                let frames: u32 = sets.iter().map(|set| set.count).sum();
+                let secs: f64 = sets
+                    .iter()
+                    .map(|set| set.count as f64 * set.exposure_secs)
+                    .sum();
+                run = run.push(text(format!(
+                    "{} sets, {frames} frames, {:.0} min of exposure",
+                    sets.len(),
+                    secs / 60.0
+                )));
+                for set in &sets {
+                    run = run.push(text(set.describe()).size(12));
+                }
+            }
+            (None, Err(e)) => run = run.push(text(e.to_string()).size(12)),
+        }
+        if let Some(status) = &self.status {
+            run = run.push(text(status.clone()));
+        }
+
+        column![
+            library,
+            settings,
+            content_container(run, ContainerLayer::Layer1).width(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
@@ -1,15 +1,1518 @@
-use iced::widget::text;
-use iced::{Element, Length};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use chrono::Utc;
+
+use iced::widget::{
+    checkbox, column, container, progress_bar, row, scrollable, text, text_input::TextInput,
+    Column, Space,
+};
+use iced::{task, Alignment, Color, Element, Length, Subscription, Task};
+
+use crate::analysis::{self, FrameStats};
+use crate::app::{ConnectedDevices, Message as MainMessage};
+use crate::config::{CameraDefaults, CaptureConfig, Config, Roi};
+use crate::fits::{self, FitsImage, FrameMetadata, FrameType};
+use crate::gui::styles;
+use crate::gui::styles::button_style::{sidereal_button, stop_track_button, track_button};
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::gui::tabs::plate_solve;
+use crate::gui::widgets::image_preview::{ImagePreview, ImagePreviewMessage};
+use crate::gui::widgets::live_plot::{create_live_plot, live_plot, DataPoint, LivePlotData};
+use crate::indi_handler::camera::{self, CoolerStatus};
+use crate::indi_handler::mount;
+use crate::model::{SiderealError, SiderealResult};
+use crate::phd2_handler;
+
+/// How often the cooler's readings are fetched
+const COOLER_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Coldest and warmest target temperatures accepted (°C)
+const TARGET_TEMP_RANGE: std::ops::RangeInclusive<f64> = -50.0..=30.0;
+
+/// Median HFR this many times the sequence's best suggests focus has drifted
+const HFR_DRIFT: f64 = 1.2;
+/// Median eccentricity above which stars look trailed
+const ELONGATED: f64 = 0.6;
+
+/// Blank fields are left to the camera
+fn optional(value: &str) -> Option<&str> {
+    Some(value.trim()).filter(|value| !value.is_empty())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StepField {
+    Count,
+    Exposure,
+    Gain,
+    Binning,
+    Filter,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum CameraField {
+    Gain,
+    Offset,
+    Binning,
+    RoiX,
+    RoiY,
+    RoiWidth,
+    RoiHeight,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// Add a step to the sequence of camera `sequence` (0 for the main one)
+    AddStep(usize),
+    RemoveStep {
+        sequence: usize,
+        index: usize,
+    },
+    StepFieldChanged {
+        sequence: usize,
+        index: usize,
+        field: StepField,
+        value: String,
+    },
+    /// Have one of the other cameras shoot its own sequence alongside
+    SequenceEnabled(usize, bool),
+    TargetChanged(String),
+    DirectoryChanged(String),
+    FileTemplateChanged(String),
+    SaveFileSettings,
+    DitherEveryChanged(String),
+    Start,
+    Pause,
+    Resume,
+    Abort,
+    FrameFinished {
+        run: usize,
+        result: SiderealResult<SavedFrame>,
+    },
+    Dithered(SiderealResult<()>),
+    Tick,
+    Preview(ImagePreviewMessage),
+    CoolerToggled(bool),
+    TargetTempChanged(String),
+    SetTargetTemp,
+    TargetTempSet(SiderealResult<f64>),
+    /// Ramp the sensor up to ambient, then switch the cooler off
+    WarmUp,
+    WarmedUp(SiderealResult<()>),
+    PollCooler,
+    CoolerUpdated(Option<CoolerStatus>),
+    CameraFieldChanged {
+        field: CameraField,
+        value: String,
+    },
+    /// Fill the camera settings in from what the camera is set to now
+    ReadCamera,
+    CameraRead(SiderealResult<CameraDefaults>),
+    /// Keep the camera settings as this camera's defaults
+    SaveCameraDefaults,
+    FullFrame,
+}
+
+/// A frame written to disk, and its pixels for the preview and its star
+/// measurements if it could be read back
+#[derive(Debug, Clone)]
+pub struct SavedFrame {
+    path: PathBuf,
+    image: Option<Arc<FitsImage>>,
+    stats: Option<FrameStats>,
+}
+
+/// One line of the sequence editor, as typed
 #[derive(Debug, Clone)]
-pub enum Message {}
+struct StepInput {
+    count: String,
+    exposure: String,
+    /// Blank to leave the camera's gain alone
+    gain: String,
+    /// Blank to leave the camera's binning alone
+    binning: String,
+    /// Filter wheel slot; blank to leave the wheel where it is
+    filter: String,
+}
+
+impl Default for StepInput {
+    fn default() -> Self {
+        Self {
+            count: "10".to_owned(),
+            exposure: "60".to_owned(),
+            gain: String::new(),
+            binning: String::new(),
+            filter: String::new(),
+        }
+    }
+}
+
+/// N exposures at one set of camera settings
+#[derive(Debug, Clone, Copy)]
+struct CaptureStep {
+    count: u32,
+    exposure_secs: f64,
+    gain: Option<f64>,
+    binning: Option<u32>,
+    filter: Option<u32>,
+}
+
+impl StepInput {
+    /// Validate the line, called `step` in errors
+    fn parse(&self, step: &str) -> SiderealResult<CaptureStep> {
+        let invalid = |what: &str, value: &str| {
+            SiderealError::ParseError(format!("{step}: invalid {what} `{value}`"))
+        };
+        let count = self
+            .count
+            .trim()
+            .parse::<u32>()
+            .map_err(|_| invalid("frame count", &self.count))?;
+        let exposure_secs = self
+            .exposure
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|secs| secs.is_finite() && *secs > 0.0)
+            .ok_or_else(|| invalid("exposure", &self.exposure))?;
+        let gain = optional(&self.gain)
+            .map(|gain| gain.parse::<f64>().map_err(|_| invalid("gain", gain)))
+            .transpose()?;
+        let binning = optional(&self.binning)
+            .map(|bin| {
+                bin.parse::<u32>()
+                    .ok()
+                    .filter(|bin| (1..=4).contains(bin))
+                    .ok_or_else(|| invalid("binning", bin))
+            })
+            .transpose()?;
+        let filter = optional(&self.filter)
+            .map(|slot| {
+                slot.parse::<u32>()
+                    .ok()
+                    .filter(|slot| *slot > 0)
+                    .ok_or_else(|| invalid("filter slot", slot))
+            })
+            .transpose()?;
+
+        Ok(CaptureStep {
+            count,
+            exposure_secs,
+            gain,
+            binning,
+            filter,
+        })
+    }
+}
+
+/// The steps one camera shoots, as typed
+#[derive(Debug, Clone)]
+struct SequenceInput {
+    /// INDI device name; `None` for the main camera
+    camera: Option<String>,
+    /// Whether the camera shoots; the main camera always does
+    enabled: bool,
+    steps: Vec<StepInput>,
+}
 
-#[derive(Default)]
-pub struct CaptureState;
+impl SequenceInput {
+    fn new(camera: Option<String>) -> Self {
+        Self {
+            enabled: camera.is_none(),
+            camera,
+            steps: vec![StepInput::default()],
+        }
+    }
+
+    fn parse(&self) -> SiderealResult<Vec<CaptureStep>> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| match &self.camera {
+                Some(camera) => step.parse(&format!("{camera} step {}", i + 1)),
+                None => step.parse(&format!("Step {}", i + 1)),
+            })
+            .collect()
+    }
+}
+
+/// The Camera section, as typed
+#[derive(Debug, Clone, Default)]
+struct CameraInput {
+    gain: String,
+    offset: String,
+    binning: String,
+    roi_x: String,
+    roi_y: String,
+    roi_width: String,
+    roi_height: String,
+}
+
+impl CameraInput {
+    fn from_defaults(defaults: &CameraDefaults) -> Self {
+        let number = |value: Option<f64>| value.map(|value| value.to_string()).unwrap_or_default();
+        let roi = |part: fn(&Roi) -> u32| {
+            defaults
+                .roi
+                .map(|roi| part(&roi).to_string())
+                .unwrap_or_default()
+        };
+        Self {
+            gain: number(defaults.gain),
+            offset: number(defaults.offset),
+            binning: defaults
+                .binning
+                .map(|bin| bin.to_string())
+                .unwrap_or_default(),
+            roi_x: roi(|roi| roi.x),
+            roi_y: roi(|roi| roi.y),
+            roi_width: roi(|roi| roi.width),
+            roi_height: roi(|roi| roi.height),
+        }
+    }
+
+    fn parse(&self) -> SiderealResult<CameraDefaults> {
+        let invalid = |what: &str, value: &str| {
+            SiderealError::ParseError(format!("Invalid {what} `{value}`"))
+        };
+        let number = |what: &str, value: &str| {
+            optional(value)
+                .map(|value| {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|value| value.is_finite() && *value >= 0.0)
+                        .ok_or_else(|| invalid(what, value))
+                })
+                .transpose()
+        };
+        let pixels = |what: &str, value: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| invalid(what, value))
+        };
+
+        let binning = optional(&self.binning)
+            .map(|bin| {
+                bin.parse::<u32>()
+                    .ok()
+                    .filter(|bin| (1..=4).contains(bin))
+                    .ok_or_else(|| invalid("binning", bin))
+            })
+            .transpose()?;
+        let roi_fields = [&self.roi_x, &self.roi_y, &self.roi_width, &self.roi_height];
+        let roi = if roi_fields.iter().all(|field| optional(field).is_none()) {
+            None
+        } else {
+            let roi = Roi {
+                x: pixels("ROI x", &self.roi_x)?,
+                y: pixels("ROI y", &self.roi_y)?,
+                width: pixels("ROI width", &self.roi_width)?,
+                height: pixels("ROI height", &self.roi_height)?,
+            };
+            if roi.width == 0 || roi.height == 0 {
+                return Err(SiderealError::ParseError(
+                    "The ROI needs a width and height".to_owned(),
+                ));
+            }
+            Some(roi)
+        };
+
+        Ok(CameraDefaults {
+            gain: number("gain", &self.gain)?,
+            offset: number("offset", &self.offset)?,
+            binning,
+            roi,
+        })
+    }
+}
+
+/// Put the camera in the sequence's readout settings
+async fn apply_camera_defaults(defaults: CameraDefaults) -> SiderealResult<()> {
+    if let Some(gain) = defaults.gain {
+        camera::set_gain(gain).await?;
+    }
+    if let Some(offset) = defaults.offset {
+        camera::set_offset(offset).await?;
+    }
+    if let Some(binning) = defaults.binning {
+        camera::set_binning(binning).await?;
+    }
+    match defaults.roi {
+        Some(roi) => camera::set_subframe(roi.x, roi.y, roi.width, roi.height).await,
+        None => camera::reset_subframe().await,
+    }
+}
+
+/// What the camera is set to now, with a whole-sensor frame as no ROI
+async fn read_camera_settings() -> SiderealResult<CameraDefaults> {
+    let (width, height) = camera::sensor_size().await?;
+    let roi = camera::subframe()
+        .await
+        .filter(|&frame| frame != (0, 0, width, height))
+        .map(|(x, y, width, height)| Roi {
+            x,
+            y,
+            width,
+            height,
+        });
+    Ok(CameraDefaults {
+        gain: camera::gain().await,
+        offset: camera::offset().await,
+        binning: camera::binning().await,
+        roi,
+    })
+}
+
+/// One camera's part of a running sequence
+struct CameraRun {
+    /// INDI device name; `None` for the main camera
+    camera: Option<String>,
+    steps: Vec<CaptureStep>,
+    /// Readout settings applied before the first frame
+    readout: Option<CameraDefaults>,
+    /// Index of the step being shot
+    step: usize,
+    /// Frames of that step already taken
+    frame: u32,
+    /// Frames taken across all steps
+    taken: u32,
+    /// When the frame in progress was started; `None` between frames
+    exposing_since: Option<Instant>,
+    /// The frame in progress, to drop it on abort
+    handle: Option<task::Handle>,
+}
+
+impl CameraRun {
+    /// The step to shoot next, moving past finished ones; `None` when done
+    fn current_step(&mut self) -> Option<CaptureStep> {
+        while self
+            .steps
+            .get(self.step)
+            .is_some_and(|step| self.frame >= step.count)
+        {
+            self.step += 1;
+            self.frame = 0;
+        }
+        self.steps.get(self.step).copied()
+    }
+
+    fn total_secs(&self) -> f64 {
+        self.steps
+            .iter()
+            .map(|step| step.count as f64 * step.exposure_secs)
+            .sum()
+    }
+
+    /// Exposure time still to come, counting down through the current frame
+    fn remaining_secs(&self) -> f64 {
+        let to_come: f64 = self.steps[self.step..]
+            .iter()
+            .enumerate()
+            .map(|(i, step)| {
+                let frames = if i == 0 {
+                    step.count - self.frame
+                } else {
+                    step.count
+                };
+                frames as f64 * step.exposure_secs
+            })
+            .sum();
+        let exposed = match (self.exposing_since, self.steps.get(self.step)) {
+            (Some(since), Some(step)) => since.elapsed().as_secs_f64().min(step.exposure_secs),
+            _ => 0.0,
+        };
+        to_come - exposed
+    }
+}
+
+/// Every camera's sequence, started together
+struct RunningSequence {
+    /// The main camera first
+    runs: Vec<CameraRun>,
+    /// Dither through PHD2 after every this many frames of the main camera
+    dither_every: Option<u32>,
+    /// Start no new frames once those in progress are done
+    paused: bool,
+    /// A dither waiting for every camera to finish its frame
+    dither_due: bool,
+    /// The dither in progress, to drop it on abort
+    dithering: Option<task::Handle>,
+}
+
+/// Text box for one field of a sequence step; read-only while running
+fn step_input<'a>(
+    placeholder: &str,
+    value: &str,
+    (sequence, index): (usize, usize),
+    field: StepField,
+    editable: bool,
+) -> TextInput<'a, Message> {
+    let input = sidereal_text_input(placeholder, value).width(Length::Fill);
+    if editable {
+        input.on_input(move |value| Message::StepFieldChanged {
+            sequence,
+            index,
+            field,
+            value,
+        })
+    } else {
+        input
+    }
+}
+
+/// "1h 02m 03s", "2m 03s" or "45s"
+fn format_duration(secs: f64) -> String {
+    let secs = secs.max(0.0).round() as u64;
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, s) => format!("{h}h {m:02}m {s:02}s"),
+    }
+}
+
+/// Take one frame of `step` and save it; `frame` is its number within the step.
+/// `readout` is applied first, at the start of a sequence.
+async fn take_frame(
+    step: CaptureStep,
+    frame: usize,
+    target: String,
+    readout: Option<CameraDefaults>,
+    configure: bool,
+) -> SiderealResult<SavedFrame> {
+    if let Some(readout) = readout {
+        apply_camera_defaults(readout).await?;
+    }
+    if configure {
+        if let Some(gain) = step.gain {
+            camera::set_gain(gain).await?;
+        }
+        if let Some(binning) = step.binning {
+            camera::set_binning(binning).await?;
+        }
+        if let Some(slot) = step.filter {
+            camera::set_filter(slot).await?;
+        }
+    }
+    let started = Utc::now();
+    let data = camera::expose(step.exposure_secs).await?;
+    let finished = Utc::now();
+
+    let filter = match step.filter {
+        // the slot number stands in for wheels without names
+        Some(slot) => Some(
+            camera::filter_name(slot)
+                .await
+                .unwrap_or_else(|| slot.to_string()),
+        ),
+        None => None,
+    };
+    let meta = FrameMetadata {
+        frame_type: FrameType::Light,
+        target,
+        exposure_secs: step.exposure_secs,
+        gain: match step.gain {
+            Some(gain) => Some(gain),
+            None => camera::gain().await,
+        },
+        binning: match step.binning {
+            Some(binning) => binning,
+            None => camera::binning().await.unwrap_or(1),
+        },
+        filter,
+        sensor_temp_c: camera::sensor_temperature().await,
+        instrument: camera::device_name().await,
+        position: mount::position().await,
+        started,
+        finished,
+        frame,
+    };
+    let path = fits::save_frame(&data, &meta).await?;
+    let image = fits::decode(&data).ok().map(Arc::new);
+    let stats = image.as_deref().map(analysis::frame_stats);
+    Ok(SavedFrame { path, image, stats })
+}
+
+pub struct CaptureState {
+    /// The main camera's sequence first, then the other cameras'
+    sequences: Vec<SequenceInput>,
+    running: Option<RunningSequence>,
+    /// How the last sequence ended
+    status: Option<String>,
+    /// Name for the OBJECT header and {target} in file names
+    target: String,
+    directory: String,
+    file_template: String,
+    /// Frames between dithers; blank for none
+    dither_every: String,
+    /// Where the last frame from any camera was saved
+    last_saved: Option<PathBuf>,
+    preview: ImagePreview,
+    /// Latest cooler readings; `None` without a camera that reports them
+    cooler: Option<CoolerStatus>,
+    /// Target temperature as typed
+    target_temp: String,
+    /// The target last sent to the camera
+    target_setpoint: Option<f64>,
+    warming_up: bool,
+    temperature_plot: LivePlotData,
+    sensor_series: usize,
+    target_series: usize,
+    /// Plot times count from here
+    plot_start: Instant,
+    camera: CameraInput,
+    /// The imaging camera last connected, whose defaults are shown
+    camera_name: Option<String>,
+    /// Saved readout settings by camera
+    camera_defaults: HashMap<String, CameraDefaults>,
+    /// Star measurements of the main camera's last frame
+    frame_stats: Option<FrameStats>,
+    /// Smallest median HFR this sequence, to spot focus drifting from it
+    best_hfr: Option<f64>,
+    /// HFR and eccentricity by frame number this sequence
+    quality_plot: LivePlotData,
+    hfr_series: usize,
+    eccentricity_series: usize,
+}
+
+impl Default for CaptureState {
+    fn default() -> Self {
+        let files = CaptureConfig::default();
+        let mut temperature_plot = create_live_plot(1000, 20.0);
+        let sensor_series = temperature_plot.add_series("Sensor", Color::from_rgb(0.3, 0.7, 1.0));
+        let target_series = temperature_plot.add_series("Target", Color::from_rgb(1.0, 0.6, 0.3));
+        let mut quality_plot = create_live_plot(2000, 20.0).with_x_axis(f64::INFINITY, "");
+        let hfr_series = quality_plot.add_series("HFR (px)", Color::from_rgb(0.3, 0.7, 1.0));
+        let eccentricity_series =
+            quality_plot.add_series("Eccentricity", Color::from_rgb(1.0, 0.6, 0.3));
+        Self {
+            sequences: vec![SequenceInput::new(None)],
+            running: None,
+            status: None,
+            target: String::new(),
+            directory: files.directory.display().to_string(),
+            file_template: files.file_template,
+            dither_every: String::new(),
+            last_saved: None,
+            preview: ImagePreview::default(),
+            cooler: None,
+            target_temp: "-10".to_owned(),
+            target_setpoint: None,
+            warming_up: false,
+            temperature_plot,
+            sensor_series,
+            target_series,
+            plot_start: Instant::now(),
+            camera: CameraInput::default(),
+            camera_name: None,
+            camera_defaults: HashMap::new(),
+            frame_stats: None,
+            best_hfr: None,
+            quality_plot,
+            hfr_series,
+            eccentricity_series,
+        }
+    }
+}
 
 impl CaptureState {
-    pub fn update(&mut self, _message: Message) {}
-    pub fn view(&self) -> Element<'static, Message> {
-        text("Setup tab").width(Length::Fill).into()
+    pub fn on_config_load(&mut self, config: &Config) {
+        self.directory = config.capture.directory.display().to_string();
+        self.file_template = config.capture.file_template.clone();
+        self.camera_defaults = config.camera_defaults.clone();
+        if let Some(defaults) = self
+            .camera_name
+            .as_ref()
+            .and_then(|name| self.camera_defaults.get(name))
+        {
+            self.camera = CameraInput::from_defaults(defaults);
+        }
+    }
+
+    /// Show the saved settings of a newly connected imaging camera, and offer
+    /// sequences for any other cameras
+    pub fn on_camera_change(&mut self, devices: &ConnectedDevices) {
+        // devices drop out briefly while the server is polled, so cameras are
+        // only ever added, and only a different camera replaces what's typed
+        for name in devices.extra_cameras.iter().chain(&devices.guide_camera) {
+            if !self
+                .sequences
+                .iter()
+                .any(|sequence| sequence.camera.as_ref() == Some(name))
+            {
+                self.sequences.push(SequenceInput::new(Some(name.clone())));
+            }
+        }
+        let Some(name) = devices.camera.as_deref() else {
+            return;
+        };
+        if self.camera_name.as_deref() == Some(name) {
+            return;
+        }
+        self.camera_name = Some(name.to_owned());
+        if self.running.is_none() {
+            self.camera = self
+                .camera_defaults
+                .get(name)
+                .map(CameraInput::from_defaults)
+                .unwrap_or_default();
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::AddStep(sequence) => {
+                if let Some(sequence) = self.sequences.get_mut(sequence) {
+                    // a new step starts as a copy of the last one
+                    let step = sequence.steps.last().cloned().unwrap_or_default();
+                    sequence.steps.push(step);
+                }
+            }
+            Message::RemoveStep { sequence, index } => {
+                if let Some(sequence) = self.sequences.get_mut(sequence) {
+                    if sequence.steps.len() > 1 && index < sequence.steps.len() {
+                        sequence.steps.remove(index);
+                    }
+                }
+            }
+            Message::SequenceEnabled(sequence, enabled) => {
+                // the main camera can't be left out
+                if let Some(sequence) = self.sequences.get_mut(sequence).filter(|_| sequence > 0) {
+                    sequence.enabled = enabled;
+                }
+            }
+            Message::StepFieldChanged {
+                sequence,
+                index,
+                field,
+                value,
+            } => {
+                if let Some(step) = self
+                    .sequences
+                    .get_mut(sequence)
+                    .and_then(|sequence| sequence.steps.get_mut(index))
+                {
+                    match field {
+                        StepField::Count => step.count = value,
+                        StepField::Exposure => step.exposure = value,
+                        StepField::Gain => step.gain = value,
+                        StepField::Binning => step.binning = value,
+                        StepField::Filter => step.filter = value,
+                    }
+                }
+            }
+            Message::TargetChanged(target) => self.target = target,
+            Message::DirectoryChanged(directory) => self.directory = directory,
+            Message::FileTemplateChanged(template) => self.file_template = template,
+            Message::DitherEveryChanged(frames) => self.dither_every = frames,
+            Message::SaveFileSettings => {
+                let directory = self.directory.trim();
+                if directory.is_empty() || self.file_template.trim().is_empty() {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        "The capture directory and file name can't be blank".to_owned(),
+                    )));
+                }
+                let capture = CaptureConfig {
+                    directory: PathBuf::from(directory),
+                    file_template: self.file_template.trim().to_owned(),
+                };
+                return Task::perform(Config::set_capture(capture), |result| match result {
+                    Ok(()) => MainMessage::Noop,
+                    Err(e) => MainMessage::ErrorOccurred(e),
+                });
+            }
+            Message::Start => {
+                if self.running.is_some() {
+                    return Task::none();
+                }
+                let main_readout = match self.camera.parse() {
+                    Ok(readout) => readout,
+                    Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+                };
+                let mut runs = Vec::new();
+                for sequence in self.sequences.iter().filter(|sequence| sequence.enabled) {
+                    let steps = match sequence.parse() {
+                        Ok(steps) => steps,
+                        Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+                    };
+                    // the other cameras get their saved settings, if any
+                    let readout = match &sequence.camera {
+                        Some(camera) => self.camera_defaults.get(camera).copied(),
+                        None => Some(main_readout),
+                    };
+                    runs.push(CameraRun {
+                        camera: sequence.camera.clone(),
+                        steps,
+                        readout,
+                        step: 0,
+                        frame: 0,
+                        taken: 0,
+                        exposing_since: None,
+                        handle: None,
+                    });
+                }
+                if runs
+                    .iter()
+                    .flat_map(|run| &run.steps)
+                    .all(|step| step.count == 0)
+                {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        "The sequence has no frames to take".to_owned(),
+                    )));
+                }
+                let dither_every = match self.dither_every.trim() {
+                    "" => None,
+                    frames => match frames.parse::<u32>() {
+                        Ok(frames) if frames > 0 => Some(frames),
+                        _ => {
+                            return Task::done(MainMessage::ErrorOccurred(
+                                SiderealError::ParseError(format!(
+                                    "Invalid dither interval `{frames}`"
+                                )),
+                            ))
+                        }
+                    },
+                };
+                self.status = None;
+                self.last_saved = None;
+                self.frame_stats = None;
+                self.best_hfr = None;
+                self.quality_plot.clear();
+                self.running = Some(RunningSequence {
+                    runs,
+                    dither_every,
+                    paused: false,
+                    dither_due: false,
+                    dithering: None,
+                });
+                return self.next_frames();
+            }
+            Message::Pause => {
+                if let Some(sequence) = &mut self.running {
+                    sequence.paused = true;
+                }
+            }
+            Message::Resume => {
+                if let Some(sequence) = &mut self.running {
+                    sequence.paused = false;
+                    return self.next_frames();
+                }
+            }
+            Message::Abort => return self.stop("Aborted".to_owned()),
+            Message::FrameFinished { run, result } => {
+                let Some(sequence) = &mut self.running else {
+                    return Task::none();
+                };
+                let Some(camera_run) = sequence.runs.get_mut(run) else {
+                    return Task::none();
+                };
+                camera_run.exposing_since = None;
+                camera_run.handle = None;
+                let saved = match result {
+                    Ok(saved) => saved,
+                    Err(e) => {
+                        let stopped = self.stop(format!("Stopped: {e}"));
+                        return Task::batch([stopped, Task::done(MainMessage::ErrorOccurred(e))]);
+                    }
+                };
+                camera_run.frame += 1;
+                camera_run.taken += 1;
+                self.last_saved = Some(saved.path.clone());
+                // only the main camera's frames are previewed and solved
+                if camera_run.camera.is_some() {
+                    return self.next_frames();
+                }
+                let taken = camera_run.taken;
+                // no dither after the main camera's last frame
+                if camera_run.current_step().is_some()
+                    && sequence
+                        .dither_every
+                        .is_some_and(|every| taken.is_multiple_of(every))
+                {
+                    sequence.dither_due = true;
+                }
+                if let Some(image) = saved.image {
+                    self.preview.set_image(image);
+                }
+                if let Some(stats) = saved.stats {
+                    self.record_stats(taken, stats);
+                }
+                let solvable = Task::done(MainMessage::PlateSolve(
+                    plate_solve::Message::FrameSaved(saved.path),
+                ));
+                return Task::batch([solvable, self.next_frames()]);
+            }
+            Message::Dithered(result) => {
+                let Some(sequence) = &mut self.running else {
+                    return Task::none();
+                };
+                sequence.dithering = None;
+                if let Err(e) = result {
+                    let stopped = self.stop(format!("Stopped: {e}"));
+                    return Task::batch([stopped, Task::done(MainMessage::ErrorOccurred(e))]);
+                }
+                return self.next_frames();
+            }
+            Message::Tick => {}
+            Message::Preview(message) => self.preview.update(message),
+            Message::CoolerToggled(on) => {
+                if !on {
+                    self.target_setpoint = None;
+                }
+                return Task::perform(camera::set_cooler(on), |result| match result {
+                    Ok(()) => MainMessage::Capture(Message::PollCooler),
+                    Err(e) => MainMessage::ErrorOccurred(e),
+                });
+            }
+            Message::TargetTempChanged(target) => self.target_temp = target,
+            Message::SetTargetTemp => {
+                let target = match self.target_temp.trim().parse::<f64>() {
+                    Ok(target) if TARGET_TEMP_RANGE.contains(&target) => target,
+                    _ => {
+                        return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                            format!("Invalid target temperature `{}`", self.target_temp),
+                        )))
+                    }
+                };
+                return Task::perform(
+                    async move {
+                        camera::set_target_temperature(target)
+                            .await
+                            .map(|()| target)
+                    },
+                    |result| MainMessage::Capture(Message::TargetTempSet(result)),
+                );
+            }
+            Message::TargetTempSet(result) => match result {
+                Ok(target) => self.target_setpoint = Some(target),
+                Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+            },
+            Message::WarmUp => return self.warm_up(),
+            Message::WarmedUp(result) => {
+                self.warming_up = false;
+                if let Err(e) = result {
+                    return Task::done(MainMessage::ErrorOccurred(e));
+                }
+            }
+            Message::CameraFieldChanged { field, value } => {
+                let input = match field {
+                    CameraField::Gain => &mut self.camera.gain,
+                    CameraField::Offset => &mut self.camera.offset,
+                    CameraField::Binning => &mut self.camera.binning,
+                    CameraField::RoiX => &mut self.camera.roi_x,
+                    CameraField::RoiY => &mut self.camera.roi_y,
+                    CameraField::RoiWidth => &mut self.camera.roi_width,
+                    CameraField::RoiHeight => &mut self.camera.roi_height,
+                };
+                *input = value;
+            }
+            Message::FullFrame => {
+                self.camera.roi_x.clear();
+                self.camera.roi_y.clear();
+                self.camera.roi_width.clear();
+                self.camera.roi_height.clear();
+            }
+            Message::ReadCamera => {
+                return Task::perform(read_camera_settings(), |result| {
+                    MainMessage::Capture(Message::CameraRead(result))
+                });
+            }
+            Message::CameraRead(result) => match result {
+                Ok(defaults) => self.camera = CameraInput::from_defaults(&defaults),
+                Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+            },
+            Message::SaveCameraDefaults => {
+                let Some(name) = self.camera_name.clone() else {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ServerError(
+                        "No camera to save settings for".to_owned(),
+                    )));
+                };
+                let defaults = match self.camera.parse() {
+                    Ok(defaults) => defaults,
+                    Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+                };
+                self.camera_defaults.insert(name.clone(), defaults);
+                return Task::perform(Config::set_camera_defaults(name, defaults), |result| {
+                    match result {
+                        Ok(()) => MainMessage::Noop,
+                        Err(e) => MainMessage::ErrorOccurred(e),
+                    }
+                });
+            }
+            Message::PollCooler => {
+                return Task::perform(camera::cooler_status(), |status| {
+                    MainMessage::Capture(Message::CoolerUpdated(status))
+                });
+            }
+            Message::CoolerUpdated(status) => {
+                self.cooler = status;
+                let Some(status) = status else {
+                    return Task::none();
+                };
+                let timestamp = self.plot_start.elapsed().as_secs_f64();
+                self.temperature_plot.add_data_point(
+                    self.sensor_series,
+                    DataPoint {
+                        timestamp,
+                        value: status.temperature,
+                    },
+                );
+                if let (Some(true), Some(target)) = (status.on, self.target_setpoint) {
+                    self.temperature_plot.add_data_point(
+                        self.target_series,
+                        DataPoint {
+                            timestamp,
+                            value: target,
+                        },
+                    );
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Show the star measurements of the main camera's `frame`th frame
+    fn record_stats(&mut self, frame: u32, stats: FrameStats) {
+        let timestamp = frame as f64;
+        if let Some(hfr) = stats.hfr {
+            self.best_hfr = Some(self.best_hfr.map_or(hfr, |best| best.min(hfr)));
+            self.quality_plot.add_data_point(
+                self.hfr_series,
+                DataPoint {
+                    timestamp,
+                    value: hfr,
+                },
+            );
+        }
+        if let Some(eccentricity) = stats.eccentricity {
+            self.quality_plot.add_data_point(
+                self.eccentricity_series,
+                DataPoint {
+                    timestamp,
+                    value: eccentricity,
+                },
+            );
+        }
+        self.frame_stats = Some(stats);
+    }
+
+    /// Whether the sensor cooler is running, so it should be warmed up before
+    /// the camera is let go
+    pub fn cooler_on(&self) -> bool {
+        self.cooler.is_some_and(|cooler| cooler.on == Some(true))
+    }
+
+    /// Start the warm-up ramp, unless it's already going
+    pub fn warm_up(&mut self) -> Task<MainMessage> {
+        if self.warming_up {
+            return Task::none();
+        }
+        self.warming_up = true;
+        // the ramp moves the target from here on
+        self.target_setpoint = None;
+        Task::perform(camera::warm_up(), |result| {
+            MainMessage::Capture(Message::WarmedUp(result))
+        })
+    }
+
+    /// Start the next frame on every camera that's between frames, unless the
+    /// sequence is paused. A due dither waits until no camera is exposing, and
+    /// the sequence ends once every camera is done.
+    fn next_frames(&mut self) -> Task<MainMessage> {
+        let target = self.target.trim().to_owned();
+        let Some(sequence) = &mut self.running else {
+            return Task::none();
+        };
+        if sequence.paused || sequence.dithering.is_some() {
+            return Task::none();
+        }
+        if sequence.dither_due {
+            if sequence.runs.iter().any(|run| run.exposing_since.is_some()) {
+                return Task::none();
+            }
+            sequence.dither_due = false;
+            let (task, handle) = Task::perform(phd2_handler::dither(), |result| {
+                MainMessage::Capture(Message::Dithered(result))
+            })
+            .abortable();
+            sequence.dithering = Some(handle);
+            return task;
+        }
+
+        let mut tasks = Vec::new();
+        for (index, run) in sequence.runs.iter_mut().enumerate() {
+            if run.exposing_since.is_some() {
+                continue;
+            }
+            let Some(step) = run.current_step() else {
+                continue;
+            };
+            // the camera is set up at the start of the sequence, and for each step
+            let readout = run.readout.filter(|_| run.taken == 0);
+            let configure = run.frame == 0;
+            let frame = run.frame as usize + 1;
+            let (task, handle) = Task::perform(
+                camera::on_camera(
+                    run.camera.clone(),
+                    take_frame(step, frame, target.clone(), readout, configure),
+                ),
+                move |result| MainMessage::Capture(Message::FrameFinished { run: index, result }),
+            )
+            .abortable();
+            run.exposing_since = Some(Instant::now());
+            run.handle = Some(handle);
+            tasks.push(task);
+        }
+        if sequence.runs.iter().all(|run| run.exposing_since.is_none()) {
+            self.running = None;
+            self.status = Some("Sequence complete".to_owned());
+        }
+        Task::batch(tasks)
+    }
+
+    /// End the running sequence, dropping the frames in progress
+    pub fn stop(&mut self, status: String) -> Task<MainMessage> {
+        let Some(sequence) = self.running.take() else {
+            return Task::none();
+        };
+        self.status = Some(status);
+        if let Some(handle) = sequence.dithering {
+            handle.abort();
+        }
+        let mut tasks = Vec::new();
+        for run in sequence.runs {
+            let Some(handle) = run.handle else {
+                continue;
+            };
+            handle.abort();
+            tasks.push(Task::perform(
+                camera::on_camera(run.camera, camera::abort_exposure()),
+                |result| match result {
+                    Ok(()) => MainMessage::Noop,
+                    Err(e) => MainMessage::ErrorOccurred(e),
+                },
+            ));
+        }
+        Task::batch(tasks)
+    }
+
+    /// Redraws the progress once a second while frames are being taken, and
+    /// keeps the cooler readings current
+    pub fn subscription(&self) -> Subscription<Message> {
+        let exposing = self
+            .running
+            .as_ref()
+            .is_some_and(|sequence| sequence.runs.iter().any(|run| run.exposing_since.is_some()));
+        let progress = if exposing {
+            iced::time::every(Duration::from_secs(1)).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        };
+        Subscription::batch([
+            progress,
+            iced::time::every(COOLER_POLL_INTERVAL).map(|_| Message::PollCooler),
+        ])
+    }
+
+    fn camera_view(&self) -> Element<Message> {
+        let editable = self.running.is_none();
+        let field = |label: &'static str, value: &str, field: CameraField| {
+            let mut input = sidereal_text_input("camera", value).width(Length::Fill);
+            if editable {
+                input = input.on_input(move |value| Message::CameraFieldChanged { field, value });
+            }
+            column![text(label).size(12), input]
+                .spacing(5)
+                .width(Length::Fill)
+        };
+        let camera = match &self.camera_name {
+            Some(name) => format!("Applied to {name} before each sequence"),
+            None => "No camera connected".to_owned(),
+        };
+
+        column![
+            text("Camera"),
+            row![
+                field("Gain", &self.camera.gain, CameraField::Gain),
+                field("Offset", &self.camera.offset, CameraField::Offset),
+                field("Binning", &self.camera.binning, CameraField::Binning),
+            ]
+            .spacing(10),
+            row![
+                field("ROI x", &self.camera.roi_x, CameraField::RoiX),
+                field("ROI y", &self.camera.roi_y, CameraField::RoiY),
+                field("Width", &self.camera.roi_width, CameraField::RoiWidth),
+                field("Height", &self.camera.roi_height, CameraField::RoiHeight),
+            ]
+            .spacing(10),
+            text("A blank ROI reads out the whole sensor").size(12),
+            row![
+                sidereal_button("Full Frame", Some(Message::FullFrame), editable)
+                    .width(Length::Fill),
+                sidereal_button("Read from Camera", Some(Message::ReadCamera), editable)
+                    .width(Length::Fill),
+                sidereal_button(
+                    "Save as Defaults",
+                    Some(Message::SaveCameraDefaults),
+                    self.camera_name.is_some()
+                )
+                .width(Length::Fill),
+            ]
+            .spacing(10),
+            text(camera).size(12),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn quality_view(&self) -> Element<Message> {
+        let Some(stats) = self.frame_stats else {
+            return column![
+                text("Frame Quality"),
+                text("Measured on each frame from the main camera").size(12)
+            ]
+            .spacing(10)
+            .into();
+        };
+        let measure = |value: Option<f64>, precision: usize| match value {
+            Some(value) => format!("{value:.precision$}"),
+            None => "-".to_owned(),
+        };
+        let reading = format!(
+            "{} stars, HFR {} px, FWHM {} px, eccentricity {}",
+            stats.stars,
+            measure(stats.hfr, 2),
+            measure(stats.fwhm, 2),
+            measure(stats.eccentricity, 2)
+        );
+        let drifted = match (stats.hfr, self.best_hfr) {
+            (Some(hfr), Some(best)) => hfr > best * HFR_DRIFT,
+            _ => false,
+        };
+        let warning = if stats.stars == 0 {
+            text("No stars found: clouds, or badly out of focus?").color(styles::RED_TEXT)
+        } else if drifted {
+            text("HFR is up on the sequence's best: check focus").color(styles::AMBER_TEXT)
+        } else if stats.eccentricity.is_some_and(|e| e > ELONGATED) {
+            text("Stars are elongated: wind or tracking?").color(styles::AMBER_TEXT)
+        } else {
+            text("")
+        };
+
+        column![
+            text("Frame Quality"),
+            text(reading),
+            warning.size(12),
+            live_plot(&self.quality_plot)
+                .width(Length::Fill)
+                .height(Length::Fixed(180.0)),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn cooling_view(&self) -> Element<Message> {
+        let Some(cooler) = self.cooler else {
+            return column![text("Cooling"), text("No cooled camera connected").size(12)]
+                .spacing(10)
+                .into();
+        };
+        let controllable = !self.warming_up;
+        let reading = match cooler.power {
+            Some(power) => format!("Sensor {:.1} °C, cooler at {power:.0}%", cooler.temperature),
+            None => format!("Sensor {:.1} °C", cooler.temperature),
+        };
+        let status = if self.warming_up {
+            "Warming up".to_owned()
+        } else {
+            match self.target_setpoint {
+                Some(target) if cooler.on == Some(true) => format!("Cooling to {target:.1} °C"),
+                _ => String::new(),
+            }
+        };
+        let mut target_input = sidereal_text_input("°C", &self.target_temp);
+        if controllable {
+            target_input = target_input.on_input(Message::TargetTempChanged);
+        }
+
+        column![
+            text("Cooling"),
+            row![
+                checkbox("Cooler", cooler.on == Some(true))
+                    .on_toggle_maybe(controllable.then_some(Message::CoolerToggled)),
+                text("Target (°C)"),
+                target_input.width(Length::Fixed(80.0)),
+                sidereal_button("Set", Some(Message::SetTargetTemp), controllable),
+                sidereal_button(
+                    "Warm Up",
+                    Some(Message::WarmUp),
+                    controllable && cooler.on == Some(true)
+                ),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            text(reading),
+            text(status).size(12),
+            live_plot(&self.temperature_plot)
+                .width(Length::Fill)
+                .height(Length::Fixed(180.0)),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    /// The step editor of sequence `sequence`
+    fn steps_view(&self, sequence: usize, editable: bool) -> Column<Message> {
+        let steps = &self.sequences[sequence].steps;
+        let header = row![
+            text("Frames").width(Length::Fill),
+            text("Exposure (s)").width(Length::Fill),
+            text("Gain").width(Length::Fill),
+            text("Binning").width(Length::Fill),
+            text("Filter").width(Length::Fill),
+            text("").width(Length::Fixed(80.0)),
+        ]
+        .spacing(10);
+        let rows = Column::with_children(steps.iter().enumerate().map(|(index, step)| {
+            let at = (sequence, index);
+            row![
+                step_input("frames", &step.count, at, StepField::Count, editable),
+                step_input("seconds", &step.exposure, at, StepField::Exposure, editable),
+                step_input("unchanged", &step.gain, at, StepField::Gain, editable),
+                step_input("unchanged", &step.binning, at, StepField::Binning, editable),
+                step_input("unchanged", &step.filter, at, StepField::Filter, editable),
+                sidereal_button(
+                    "Remove",
+                    Some(Message::RemoveStep { sequence, index }),
+                    editable && steps.len() > 1,
+                )
+                .width(Length::Fixed(80.0)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+            .into()
+        }))
+        .spacing(5);
+
+        column![
+            header,
+            rows,
+            sidereal_button("Add Step", Some(Message::AddStep(sequence)), editable)
+                .width(Length::Fill),
+        ]
+        .spacing(10)
+    }
+
+    /// Sequences for the cameras other than the main one
+    fn other_cameras_view(&self, editable: bool) -> Element<Message> {
+        let cameras = self.sequences.iter().enumerate().skip(1).fold(
+            column![text("Other Cameras")],
+            |cameras, (index, sequence)| {
+                let name = sequence.camera.as_deref().unwrap_or_default();
+                let cameras = cameras.push(
+                    checkbox(format!("Shoot with {name}"), sequence.enabled).on_toggle_maybe(
+                        editable.then_some(move |enabled| Message::SequenceEnabled(index, enabled)),
+                    ),
+                );
+                if sequence.enabled {
+                    cameras.push(self.steps_view(index, editable))
+                } else {
+                    cameras
+                }
+            },
+        );
+        cameras
+            .push(text("Every camera starts together; dithers wait for all of them").size(12))
+            .spacing(10)
+            .into()
+    }
+
+    /// How far one camera has got, e.g. "step 1 of 2, frame 3 of 10"
+    fn run_progress(&self, run: &CameraRun) -> String {
+        let name = run
+            .camera
+            .as_deref()
+            .or(self.camera_name.as_deref())
+            .unwrap_or("Main camera");
+        match run.steps.get(run.step) {
+            Some(step) => format!(
+                "{name}: step {} of {}, frame {} of {}",
+                run.step + 1,
+                run.steps.len(),
+                (run.frame + 1).min(step.count),
+                step.count
+            ),
+            None => format!("{name}: done"),
+        }
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let editable = self.running.is_none();
+
+        let (progress, status, cameras) = match &self.running {
+            Some(sequence) => {
+                let total: f64 = sequence.runs.iter().map(CameraRun::total_secs).sum();
+                let remaining: f64 = sequence.runs.iter().map(CameraRun::remaining_secs).sum();
+                // the cameras shoot side by side, so the slowest one sets the ETA
+                let eta = sequence
+                    .runs
+                    .iter()
+                    .map(CameraRun::remaining_secs)
+                    .fold(0.0, f64::max);
+                let exposing = sequence.runs.iter().any(|run| run.exposing_since.is_some());
+                let state = if sequence.paused && exposing {
+                    "Pausing after the frames in progress".to_owned()
+                } else if sequence.paused {
+                    "Paused".to_owned()
+                } else if sequence.dithering.is_some() {
+                    "Dithering".to_owned()
+                } else {
+                    format!("ETA {}", format_duration(eta))
+                };
+                let cameras = sequence
+                    .runs
+                    .iter()
+                    .fold(Column::new().spacing(5), |cameras, run| {
+                        cameras.push(text(self.run_progress(run)).size(12))
+                    });
+                (
+                    (1.0 - remaining / total).clamp(0.0, 1.0) as f32,
+                    state,
+                    cameras,
+                )
+            }
+            None => (
+                0.0,
+                self.status.clone().unwrap_or_else(|| "Idle".to_owned()),
+                Column::new(),
+            ),
+        };
+
+        let pause_button = match &self.running {
+            Some(run) if run.paused => sidereal_button("Resume", Some(Message::Resume), true),
+            Some(_) => sidereal_button("Pause", Some(Message::Pause), true),
+            None => sidereal_button("Pause", None, false),
+        };
+        let mut start_button = track_button(
+            container(text("Start Sequence"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if editable {
+            start_button = start_button.on_press(Message::Start);
+        }
+        let mut abort_button = stop_track_button(
+            container(text("Abort"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if !editable {
+            abort_button = abort_button.on_press(Message::Abort);
+        }
+
+        let last_saved = match &self.last_saved {
+            Some(path) => format!("Last frame: {}", path.display()),
+            None => String::new(),
+        };
+        let target_input = sidereal_text_input("target name", &self.target);
+        let mut dither_input = sidereal_text_input("never", &self.dither_every);
+        if editable {
+            dither_input = dither_input.on_input(Message::DitherEveryChanged);
+        }
+        let mut directory_input = sidereal_text_input("directory", &self.directory);
+        let mut template_input = sidereal_text_input("file name", &self.file_template);
+        if editable {
+            directory_input = directory_input.on_input(Message::DirectoryChanged);
+            template_input = template_input.on_input(Message::FileTemplateChanged);
+        }
+
+        let other_cameras = if self.sequences.len() > 1 {
+            content_container(self.other_cameras_view(editable), ContainerLayer::Layer1)
+                .width(Length::Fill)
+                .into()
+        } else {
+            Element::from(Space::new(Length::Shrink, Length::Shrink))
+        };
+
+        let controls = column![
+            content_container(
+                column![
+                    text("Files"),
+                    row![
+                        text("Target").width(Length::Fixed(100.0)),
+                        target_input.on_input(Message::TargetChanged)
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                    row![
+                        text("Directory").width(Length::Fixed(100.0)),
+                        directory_input
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                    row![
+                        text("File name").width(Length::Fixed(100.0)),
+                        template_input
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                    text("{date} {target} {filter} {exposure} {gain} {camera} {frame}").size(12),
+                    sidereal_button("Apply", Some(Message::SaveFileSettings), editable)
+                        .width(Length::Fill),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+            content_container(self.camera_view(), ContainerLayer::Layer1).width(Length::Fill),
+            content_container(
+                column![
+                    text("Sequence"),
+                    self.steps_view(0, editable),
+                    row![
+                        text("Dither every (frames, PHD2)").width(Length::Fixed(200.0)),
+                        dither_input
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+            other_cameras,
+            content_container(
+                column![
+                    text("Progress"),
+                    progress_bar(0.0..=1.0, progress).height(Length::Fixed(12.0)),
+                    text(status),
+                    cameras,
+                    text(last_saved).size(12),
+                    row![start_button, pause_button.width(Length::Fill), abort_button].spacing(10),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+            content_container(self.quality_view(), ContainerLayer::Layer1).width(Length::Fill),
+            content_container(self.cooling_view(), ContainerLayer::Layer1).width(Length::Fill),
+        ]
+        .spacing(10);
+
+        row![
+            scrollable(controls).width(Length::FillPortion(2)),
+            container(self.preview.view().map(Message::Preview)).width(Length::FillPortion(3)),
+        ]
+        .spacing(10)
+        .into()
     }
 }
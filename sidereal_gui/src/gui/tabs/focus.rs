@@ -1,15 +1,648 @@
-use iced::widget::text;
-use iced::{Element, Length};
+use iced::widget::{column, container, row, text};
+use iced::{task, Alignment, Color, Element, Length, Task};
 
+use crate::analysis::{self, FocusCurve};
+use crate::app::Message as MainMessage;
+use crate::config::Config;
+use crate::fits;
+use crate::gui::styles::button_style::{sidereal_button, stop_track_button, track_button};
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::gui::widgets::live_plot::{create_live_plot, live_plot, DataPoint, LivePlotData};
+use crate::indi_handler::{camera, focuser};
+use crate::model::{SiderealError, SiderealResult};
+
+/// Points drawn along the fitted curve
+const CURVE_SAMPLES: usize = 50;
+
+#[derive(Debug, Clone, Copy)]
+pub enum AutofocusField {
+    Exposure,
+    StepSize,
+    Steps,
+    Subframe,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    /// From the focuser watcher; either may be missing from an update
+    FocuserUpdated {
+        position: Option<f64>,
+        temperature: Option<f64>,
+    },
+    ManualStepChanged(String),
+    MoveIn,
+    MoveOut,
+    GotoChanged(String),
+    Goto,
+    StopFocuser,
+    BacklashChanged(String),
+    SaveBacklash,
+    FieldChanged {
+        field: AutofocusField,
+        value: String,
+    },
+    StartAutofocus,
+    AbortAutofocus,
+    /// The focuser's position when the run started
+    Prepared(SiderealResult<f64>),
+    PointMeasured(SiderealResult<FocusPoint>),
+    /// Where the focuser was left
+    Finished(SiderealResult<f64>),
+}
+
+/// One exposure of an autofocus run
+#[derive(Debug, Clone, Copy)]
+pub struct FocusPoint {
+    position: f64,
+    /// Median HFR of the stars found; `None` if there were none
+    hfr: Option<f64>,
+    stars: usize,
+}
+
+/// Autofocus settings, as typed
 #[derive(Debug, Clone)]
-pub enum Message {}
+struct AutofocusInput {
+    exposure: String,
+    step_size: String,
+    steps: String,
+    /// Percent of the sensor, each way, read out around its centre
+    subframe: String,
+}
 
-#[derive(Default)]
-pub struct FocusState;
+impl Default for AutofocusInput {
+    fn default() -> Self {
+        Self {
+            exposure: "2".to_owned(),
+            step_size: "100".to_owned(),
+            steps: "9".to_owned(),
+            subframe: "50".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AutofocusSettings {
+    exposure_secs: f64,
+    step_size: f64,
+    steps: usize,
+    /// Fraction of the sensor, each way
+    subframe: f64,
+}
+
+impl AutofocusInput {
+    fn parse(&self) -> SiderealResult<AutofocusSettings> {
+        let invalid = |what: &str, value: &str| {
+            SiderealError::ParseError(format!("Invalid {what} `{value}`"))
+        };
+        let number = |what: &str, value: &str, valid: &dyn Fn(f64) -> bool| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && valid(*v))
+                .ok_or_else(|| invalid(what, value))
+        };
+        Ok(AutofocusSettings {
+            exposure_secs: number("exposure", &self.exposure, &|v| v > 0.0)?,
+            step_size: number("step size", &self.step_size, &|v| v >= 1.0)?.round(),
+            steps: number("number of points", &self.steps, &|v| {
+                (5.0..=50.0).contains(&v) && v.fract() == 0.0
+            })? as usize,
+            subframe: number("subframe", &self.subframe, &|v| (10.0..=100.0).contains(&v))? / 100.0,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Preparing,
+    Measuring,
+    /// Moving to best focus, or back where it started
+    Settling,
+}
+
+/// An autofocus run in progress
+struct AutofocusRun {
+    settings: AutofocusSettings,
+    phase: Phase,
+    /// Where the focuser was, to go back to if the run fails
+    start_position: Option<f64>,
+    positions: Vec<f64>,
+    points: Vec<FocusPoint>,
+    /// The step in progress, to drop it on abort
+    handle: Option<task::Handle>,
+}
+
+/// Move to `position` from below, the way every point of the run was
+/// approached so backlash is taken up the same, then read out the whole
+/// sensor again
+async fn settle(position: f64, step_size: f64) -> SiderealResult<()> {
+    let moved = async {
+        focuser::move_to(position - step_size).await?;
+        focuser::move_to(position).await
+    }
+    .await;
+    let reset = camera::reset_subframe().await;
+    moved.and(reset)
+}
+
+/// Read out the middle `subframe` of the sensor and report where the
+/// focuser is
+async fn prepare(subframe: f64) -> SiderealResult<f64> {
+    let position = focuser::position().await?;
+    let (width, height) = camera::sensor_size().await?;
+    let (sub_width, sub_height) = (
+        (width as f64 * subframe) as u32,
+        (height as f64 * subframe) as u32,
+    );
+    camera::set_subframe(
+        (width - sub_width) / 2,
+        (height - sub_height) / 2,
+        sub_width,
+        sub_height,
+    )
+    .await?;
+    Ok(position)
+}
+
+/// Take an exposure at `position` and measure its stars. `approach` backs
+/// off by that many steps first, for the first point of a run.
+async fn measure_point(
+    position: f64,
+    exposure_secs: f64,
+    approach: Option<f64>,
+) -> SiderealResult<FocusPoint> {
+    if let Some(back_off) = approach {
+        focuser::move_to(position - back_off).await?;
+    }
+    focuser::move_to(position).await?;
+    let data = camera::expose(exposure_secs).await?;
+    let image = fits::decode(&data)?;
+    let stars = analysis::detect_stars(&image);
+    Ok(FocusPoint {
+        position,
+        hfr: analysis::median_hfr(&stars),
+        stars: stars.len(),
+    })
+}
+
+/// Move the focuser, reporting only failures
+fn focuser_task(
+    movement: impl std::future::Future<Output = SiderealResult<()>> + Send + 'static,
+) -> Task<MainMessage> {
+    Task::perform(movement, |result| match result {
+        Ok(()) => MainMessage::Noop,
+        Err(e) => MainMessage::ErrorOccurred(e),
+    })
+}
+
+pub struct FocusState {
+    position: Option<f64>,
+    /// °C, for focusers with a probe
+    temperature: Option<f64>,
+    manual_step: String,
+    goto_position: String,
+    backlash: String,
+    autofocus_input: AutofocusInput,
+    autofocus: Option<AutofocusRun>,
+    /// How the last run ended
+    status: Option<String>,
+    curve: Option<FocusCurve>,
+    plot: LivePlotData,
+}
+
+const HFR_SERIES: usize = 0;
+const FIT_SERIES: usize = 1;
+
+impl Default for FocusState {
+    fn default() -> Self {
+        let mut plot = create_live_plot(200, 20.0).with_x_axis(f64::INFINITY, "");
+        plot.add_series("HFR", Color::from_rgb(0.3, 0.7, 1.0));
+        plot.add_series("Fit", Color::from_rgb(1.0, 0.6, 0.3));
+        Self {
+            position: None,
+            temperature: None,
+            manual_step: "100".to_owned(),
+            goto_position: String::new(),
+            backlash: "0".to_owned(),
+            autofocus_input: AutofocusInput::default(),
+            autofocus: None,
+            status: None,
+            curve: None,
+            plot,
+        }
+    }
+}
 
 impl FocusState {
-    pub fn update(&mut self, _message: Message) {}
-    pub fn view(&self) -> Element<'static, Message> {
-        text("Setup tab").width(Length::Fill).into()
+    pub fn on_config_load(&mut self, config: &Config) {
+        self.backlash = config.focuser.backlash_steps.to_string();
+    }
+
+    /// The manual step size, as a move in `direction` (1 out, -1 in)
+    fn manual_move(&self, direction: f64) -> Task<MainMessage> {
+        match self.manual_step.trim().parse::<u32>() {
+            Ok(steps) if steps > 0 => focuser_task(focuser::move_by(direction * steps as f64)),
+            _ => Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                format!("Invalid step size `{}`", self.manual_step),
+            ))),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::FocuserUpdated {
+                position,
+                temperature,
+            } => {
+                if position.is_some() {
+                    self.position = position;
+                }
+                if temperature.is_some() {
+                    self.temperature = temperature;
+                }
+            }
+            Message::ManualStepChanged(value) => self.manual_step = value,
+            Message::MoveIn => return self.manual_move(-1.0),
+            Message::MoveOut => return self.manual_move(1.0),
+            Message::GotoChanged(value) => self.goto_position = value,
+            Message::Goto => {
+                return match self.goto_position.trim().parse::<u32>() {
+                    Ok(position) => focuser_task(focuser::goto(position as f64)),
+                    Err(_) => Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        format!("Invalid focuser position `{}`", self.goto_position),
+                    ))),
+                };
+            }
+            Message::StopFocuser => return focuser_task(focuser::abort()),
+            Message::BacklashChanged(value) => self.backlash = value,
+            Message::SaveBacklash => {
+                return match self.backlash.trim().parse::<u32>() {
+                    Ok(steps) => focuser_task(Config::set_focuser_backlash(steps)),
+                    Err(_) => Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        format!("Invalid backlash `{}`", self.backlash),
+                    ))),
+                };
+            }
+            Message::FieldChanged { field, value } => {
+                let input = &mut self.autofocus_input;
+                match field {
+                    AutofocusField::Exposure => input.exposure = value,
+                    AutofocusField::StepSize => input.step_size = value,
+                    AutofocusField::Steps => input.steps = value,
+                    AutofocusField::Subframe => input.subframe = value,
+                }
+            }
+            Message::StartAutofocus => {
+                if self.autofocus.is_some() {
+                    return Task::none();
+                }
+                let settings = match self.autofocus_input.parse() {
+                    Ok(settings) => settings,
+                    Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+                };
+                self.status = None;
+                self.curve = None;
+                self.plot.clear();
+                let (task, handle) = Task::perform(prepare(settings.subframe), |result| {
+                    MainMessage::Focus(Message::Prepared(result))
+                })
+                .abortable();
+                self.autofocus = Some(AutofocusRun {
+                    settings,
+                    phase: Phase::Preparing,
+                    start_position: None,
+                    positions: Vec::new(),
+                    points: Vec::new(),
+                    handle: Some(handle),
+                });
+                return task;
+            }
+            Message::AbortAutofocus => {
+                let Some(run) = self.autofocus.take() else {
+                    return Task::none();
+                };
+                self.status = Some("Aborted".to_owned());
+                if let Some(handle) = run.handle {
+                    handle.abort();
+                }
+                let step_size = run.settings.step_size;
+                let start = run.start_position;
+                return Task::perform(
+                    async move {
+                        // nothing may be exposing; that's fine
+                        let _ = camera::abort_exposure().await;
+                        match start {
+                            Some(start) => settle(start, step_size).await,
+                            None => camera::reset_subframe().await,
+                        }
+                    },
+                    |result| match result {
+                        Ok(()) => MainMessage::Noop,
+                        Err(e) => MainMessage::ErrorOccurred(e),
+                    },
+                );
+            }
+            Message::Prepared(result) => {
+                let Some(run) = &mut self.autofocus else {
+                    return Task::none();
+                };
+                run.handle = None;
+                let start = match result {
+                    Ok(start) => start,
+                    Err(e) => return self.fail(e),
+                };
+                let AutofocusSettings {
+                    steps, step_size, ..
+                } = run.settings;
+                run.start_position = Some(start);
+                run.positions = (0..steps)
+                    .map(|i| (start + (i as f64 - (steps - 1) as f64 / 2.0) * step_size).round())
+                    .collect();
+                if run.positions[0] - step_size < 0.0 {
+                    return self.fail(SiderealError::ParseError(
+                        "The autofocus range goes below focuser position 0; move out or use \
+                         smaller steps"
+                            .to_owned(),
+                    ));
+                }
+                run.phase = Phase::Measuring;
+                return self.next_point();
+            }
+            Message::PointMeasured(result) => {
+                let Some(run) = &mut self.autofocus else {
+                    return Task::none();
+                };
+                run.handle = None;
+                match result {
+                    Ok(point) => {
+                        if let Some(hfr) = point.hfr {
+                            self.plot.add_data_point(
+                                HFR_SERIES,
+                                DataPoint {
+                                    timestamp: point.position,
+                                    value: hfr,
+                                },
+                            );
+                        }
+                        run.points.push(point);
+                        return self.next_point();
+                    }
+                    Err(e) => return self.fail(e),
+                }
+            }
+            Message::Finished(result) => {
+                self.autofocus = None;
+                match result {
+                    Ok(position) => {
+                        self.status = Some(match self.curve {
+                            Some(curve) => format!(
+                                "Focused at {position:.0}, HFR {:.2}",
+                                curve.hfr_at(position)
+                            ),
+                            None => format!("Focused at {position:.0}"),
+                        });
+                    }
+                    Err(e) => {
+                        self.status = Some(format!("Failed: {e}"));
+                        return Task::done(MainMessage::ErrorOccurred(e));
+                    }
+                }
+            }
+        }
+        Task::none()
+    }
+
+    /// Measure the next position of the run, or fit the curve once they're
+    /// all done
+    fn next_point(&mut self) -> Task<MainMessage> {
+        let Some(run) = &mut self.autofocus else {
+            return Task::none();
+        };
+        let index = run.points.len();
+        if let Some(&position) = run.positions.get(index) {
+            let approach = (index == 0).then_some(run.settings.step_size);
+            let (task, handle) = Task::perform(
+                measure_point(position, run.settings.exposure_secs, approach),
+                |result| MainMessage::Focus(Message::PointMeasured(result)),
+            )
+            .abortable();
+            run.handle = Some(handle);
+            return task;
+        }
+
+        let measured: Vec<(f64, f64)> = run
+            .points
+            .iter()
+            .filter_map(|point| Some((point.position, point.hfr?)))
+            .collect();
+        let (first, last) = (run.positions[0], run.positions[run.positions.len() - 1]);
+        let curve = match FocusCurve::fit(&measured) {
+            Some(curve) => curve,
+            None => {
+                return self.fail(SiderealError::ServerError(
+                    "Couldn't fit a focus curve; check that stars are visible".to_owned(),
+                ))
+            }
+        };
+        let best = curve.best_position().round();
+        if best < first || best > last {
+            return self.fail(SiderealError::ServerError(format!(
+                "Best focus ({best:.0}) is outside the range measured; start closer to focus"
+            )));
+        }
+
+        for i in 0..=CURVE_SAMPLES {
+            let position = first + (last - first) * i as f64 / CURVE_SAMPLES as f64;
+            self.plot.add_data_point(
+                FIT_SERIES,
+                DataPoint {
+                    timestamp: position,
+                    value: curve.hfr_at(position),
+                },
+            );
+        }
+        self.curve = Some(curve);
+        run.phase = Phase::Settling;
+        let step_size = run.settings.step_size;
+        Task::perform(
+            async move { settle(best, step_size).await.map(|()| best) },
+            |result| MainMessage::Focus(Message::Finished(result)),
+        )
+    }
+
+    /// End the run on `error`, putting the focuser back where it started
+    fn fail(&mut self, error: SiderealError) -> Task<MainMessage> {
+        let Some(run) = &mut self.autofocus else {
+            return Task::none();
+        };
+        run.phase = Phase::Settling;
+        let step_size = run.settings.step_size;
+        let start = run.start_position;
+        Task::perform(
+            async move {
+                match start {
+                    Some(start) => settle(start, step_size).await?,
+                    None => camera::reset_subframe().await?,
+                }
+                Err(error)
+            },
+            |result| MainMessage::Focus(Message::Finished(result)),
+        )
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let editable = self.autofocus.is_none();
+        let setting = |label: &str, placeholder: &str, value: &str, field: AutofocusField| {
+            let mut input = sidereal_text_input(placeholder, value).width(Length::Fill);
+            if editable {
+                input = input.on_input(move |value| Message::FieldChanged { field, value });
+            }
+            row![text(label.to_owned()).width(Length::Fixed(120.0)), input]
+                .align_y(Alignment::Center)
+                .spacing(10)
+        };
+
+        let status = match &self.autofocus {
+            Some(run) => match run.phase {
+                Phase::Preparing => "Preparing".to_owned(),
+                Phase::Measuring => {
+                    let index = run.points.len();
+                    let last = match run.points.last() {
+                        Some(FocusPoint {
+                            hfr: Some(hfr),
+                            stars,
+                            ..
+                        }) => format!(" (last: HFR {hfr:.2}, {stars} stars)"),
+                        Some(_) => " (last: no stars)".to_owned(),
+                        None => String::new(),
+                    };
+                    format!(
+                        "Point {} of {} at {:.0}{last}",
+                        index + 1,
+                        run.positions.len(),
+                        run.positions.get(index).copied().unwrap_or_default()
+                    )
+                }
+                Phase::Settling => "Moving focuser".to_owned(),
+            },
+            None => self.status.clone().unwrap_or_else(|| "Idle".to_owned()),
+        };
+
+        let mut start_button = track_button(
+            container(text("Start Autofocus"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if editable {
+            start_button = start_button.on_press(Message::StartAutofocus);
+        }
+        let mut abort_button = stop_track_button(
+            container(text("Abort"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if !editable {
+            abort_button = abort_button.on_press(Message::AbortAutofocus);
+        }
+
+        let mut step_input = sidereal_text_input("steps", &self.manual_step).width(Length::Fill);
+        let mut goto_input =
+            sidereal_text_input("position", &self.goto_position).width(Length::Fill);
+        if editable {
+            step_input = step_input.on_input(Message::ManualStepChanged);
+            goto_input = goto_input.on_input(Message::GotoChanged);
+        }
+        let reading = |value: Option<f64>, format: fn(f64) -> String| {
+            value.map(format).unwrap_or_else(|| "--".to_owned())
+        };
+        let focuser = content_container(
+            column![
+                text("Focuser"),
+                row![
+                    text(format!(
+                        "Position: {}",
+                        reading(self.position, |p| format!("{p:.0}"))
+                    ))
+                    .width(Length::Fill),
+                    text(format!(
+                        "Temperature: {}",
+                        reading(self.temperature, |t| format!("{t:.1} °C"))
+                    ))
+                    .width(Length::Fill),
+                ]
+                .spacing(10),
+                row![
+                    sidereal_button("In", Some(Message::MoveIn), editable).width(Length::Fill),
+                    step_input,
+                    sidereal_button("Out", Some(Message::MoveOut), editable).width(Length::Fill),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(10),
+                row![
+                    goto_input,
+                    sidereal_button("Go To", Some(Message::Goto), editable).width(Length::Fill),
+                    sidereal_button("Stop", Some(Message::StopFocuser), true).width(Length::Fill),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(10),
+                row![
+                    text("Backlash (steps)").width(Length::Fixed(120.0)),
+                    sidereal_text_input("steps", &self.backlash)
+                        .on_input(Message::BacklashChanged)
+                        .width(Length::Fill),
+                    sidereal_button("Apply", Some(Message::SaveBacklash), true).width(Length::Fill),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(10),
+            ]
+            .spacing(10),
+            ContainerLayer::Layer1,
+        )
+        .width(Length::Fill);
+
+        let input = &self.autofocus_input;
+        column![
+            focuser,
+            content_container(
+                column![
+                    text("Autofocus"),
+                    setting(
+                        "Exposure (s)",
+                        "seconds",
+                        &input.exposure,
+                        AutofocusField::Exposure
+                    ),
+                    setting(
+                        "Step size",
+                        "steps",
+                        &input.step_size,
+                        AutofocusField::StepSize
+                    ),
+                    setting("Points", "5-50", &input.steps, AutofocusField::Steps),
+                    setting(
+                        "Subframe (%)",
+                        "10-100",
+                        &input.subframe,
+                        AutofocusField::Subframe
+                    ),
+                    row![start_button, abort_button].spacing(10),
+                    text(status),
+                    live_plot(&self.plot)
+                        .width(Length::Fill)
+                        .height(Length::Fixed(300.0)),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill)
+        ]
+        .spacing(10)
+        .into()
     }
 }
@@ -1,15 +1,963 @@
-use iced::widget::text;
-use iced::{Element, Length};
+use std::{collections::VecDeque, sync::Arc, time::Instant};
 
+use iced::widget::{column, container, row, text};
+use iced::{task, Alignment, Color, Element, Length, Task};
+
+use crate::analysis::Star;
+use crate::app::Message as MainMessage;
+use crate::config::{Config, Phd2Config};
+use crate::fits::{self, FitsImage};
+use crate::gui::styles::button_style::{sidereal_button, stop_track_button, track_button};
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::gui::widgets::image_preview::{ImagePreview, ImagePreviewMessage};
+use crate::gui::widgets::live_plot::{create_live_plot, live_plot, DataPoint, LivePlotData};
+use crate::guiding::{
+    self, Calibration, CorrectionSettings, CALIBRATION_DISTANCE, MAX_CALIBRATION_STEPS,
+};
+use crate::indi_handler::camera;
+use crate::indi_handler::mount::{self, GuideDirection};
+use crate::model::{SiderealError, SiderealResult};
+use crate::phd2_handler::{self, Phd2Event};
+
+/// Frames the RMS error is taken over
+const RMS_FRAMES: usize = 50;
+/// After a dither, guiding has settled once the star is within this
+/// distance (pixels) of the new lock position...
+const SETTLE_DISTANCE: f64 = 1.5;
+/// ...for this many frames in a row
+const SETTLE_FRAMES: u32 = 3;
+
+#[derive(Debug, Clone, Copy)]
+pub enum GuideField {
+    Exposure,
+    CalibrationPulse,
+    RaAggressiveness,
+    DecAggressiveness,
+    MinMove,
+    MaxPulse,
+    Dither,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Phd2Field {
+    Address,
+    Dither,
+    SettlePixels,
+    SettleSecs,
+    SettleTimeout,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FieldChanged {
+        field: GuideField,
+        value: String,
+    },
+    StartLooping,
+    Calibrate,
+    StartGuiding,
+    Dither,
+    Stop,
+    /// A guide frame, taken after the last corrections were sent
+    FrameReady(SiderealResult<Arc<FitsImage>>),
+    Preview(ImagePreviewMessage),
+    Phd2FieldChanged {
+        field: Phd2Field,
+        value: String,
+    },
+    SavePhd2,
+    Phd2Connect,
+    Phd2Disconnect,
+    Phd2Guide,
+    Phd2Stop,
+    Phd2Dither,
+    Phd2(Phd2Event),
+}
+
+/// Guider settings, as typed
 #[derive(Debug, Clone)]
-pub enum Message {}
+struct GuideInput {
+    exposure: String,
+    calibration_pulse: String,
+    /// Percent of each error corrected per frame
+    ra_aggressiveness: String,
+    dec_aggressiveness: String,
+    min_move: String,
+    max_pulse: String,
+    dither: String,
+}
+
+impl Default for GuideInput {
+    fn default() -> Self {
+        Self {
+            exposure: "2".to_owned(),
+            calibration_pulse: "1000".to_owned(),
+            ra_aggressiveness: "70".to_owned(),
+            dec_aggressiveness: "70".to_owned(),
+            min_move: "0.15".to_owned(),
+            max_pulse: "2000".to_owned(),
+            dither: "5".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GuideSettings {
+    exposure_secs: f64,
+    /// Length (milliseconds) of each calibration pulse
+    calibration_pulse: f64,
+    /// Largest dither (pixels) each way
+    dither_pixels: f64,
+    correction: CorrectionSettings,
+}
+
+impl GuideInput {
+    fn parse(&self) -> SiderealResult<GuideSettings> {
+        let number = |what: &str, value: &str, valid: &dyn Fn(f64) -> bool| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && valid(*v))
+                .ok_or_else(|| SiderealError::ParseError(format!("Invalid {what} `{value}`")))
+        };
+        let percent = |v: f64| (0.0..=100.0).contains(&v);
+        Ok(GuideSettings {
+            exposure_secs: number("exposure", &self.exposure, &|v| v > 0.0)?,
+            calibration_pulse: number("calibration pulse", &self.calibration_pulse, &|v| v >= 1.0)?,
+            dither_pixels: number("dither amount", &self.dither, &|v| v >= 0.0)?,
+            correction: CorrectionSettings {
+                ra_aggressiveness: number("RA aggressiveness", &self.ra_aggressiveness, &percent)?
+                    / 100.0,
+                dec_aggressiveness: number(
+                    "Dec aggressiveness",
+                    &self.dec_aggressiveness,
+                    &percent,
+                )? / 100.0,
+                min_move: number("minimum move", &self.min_move, &|v| v >= 0.0)?,
+                max_pulse: number("maximum pulse", &self.max_pulse, &|v| v >= 1.0)?,
+            },
+        })
+    }
+}
+
+/// PHD2 settings, as typed
+#[derive(Debug, Clone)]
+struct Phd2Input {
+    address: String,
+    dither: String,
+    settle_pixels: String,
+    settle_secs: String,
+    settle_timeout: String,
+}
+
+impl From<&Phd2Config> for Phd2Input {
+    fn from(config: &Phd2Config) -> Self {
+        Self {
+            address: config.address.clone(),
+            dither: config.dither_pixels.to_string(),
+            settle_pixels: config.settle_pixels.to_string(),
+            settle_secs: config.settle_secs.to_string(),
+            settle_timeout: config.settle_timeout_secs.to_string(),
+        }
+    }
+}
+
+impl Phd2Input {
+    fn parse(&self) -> SiderealResult<Phd2Config> {
+        let number = |what: &str, value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .ok_or_else(|| SiderealError::ParseError(format!("Invalid {what} `{value}`")))
+        };
+        let address = self.address.trim();
+        if address.is_empty() {
+            return Err(SiderealError::ParseError(
+                "The PHD2 address can't be blank".to_owned(),
+            ));
+        }
+        Ok(Phd2Config {
+            address: address.to_owned(),
+            dither_pixels: number("dither amount", &self.dither)?,
+            settle_pixels: number("settle distance", &self.settle_pixels)?,
+            settle_secs: number("settle time", &self.settle_secs)?,
+            settle_timeout_secs: number("settle timeout", &self.settle_timeout)?,
+        })
+    }
+}
+
+/// Run a PHD2 call, reporting only failures
+fn phd2_task(
+    call: impl std::future::Future<Output = SiderealResult<()>> + Send + 'static,
+) -> Task<MainMessage> {
+    Task::perform(call, |result| match result {
+        Ok(()) => MainMessage::Noop,
+        Err(e) => MainMessage::ErrorOccurred(e),
+    })
+}
+
+/// Star movement over some length (milliseconds) of pulses on one axis
+#[derive(Debug, Clone, Copy)]
+struct AxisMove {
+    moved: (f64, f64),
+    millis: f64,
+}
+
+/// Where calibration is up to. Each axis is pulsed until the star has
+/// moved far enough, then pulsed back as many times.
+#[derive(Debug, Clone, Copy)]
+enum Stage {
+    West,
+    East { ra: AxisMove },
+    North { ra: AxisMove },
+    South { ra: AxisMove, dec: AxisMove },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CalibrationRun {
+    stage: Stage,
+    /// Where the star was before this axis's first pulse
+    origin: Option<(f64, f64)>,
+    /// Pulses sent out on this axis so far, or left to send coming back
+    pulses: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Mode {
+    /// Exposing and following the star, without correcting
+    Looping,
+    Calibrating(CalibrationRun),
+    Guiding {
+        /// Where the star is held
+        lock: (f64, f64),
+        /// Frames in a row near the lock position since the last dither,
+        /// until there are enough
+        settling: Option<u32>,
+    },
+}
+
+/// The guide loop in progress
+struct GuideLoop {
+    settings: GuideSettings,
+    mode: Mode,
+    /// The frame in progress, to drop it on stop
+    handle: Option<task::Handle>,
+}
+
+/// Send `pulses` one after the other, then take and decode a guide frame
+async fn guide_step(
+    pulses: Vec<(GuideDirection, f64)>,
+    exposure_secs: f64,
+) -> SiderealResult<Arc<FitsImage>> {
+    for (direction, millis) in pulses {
+        mount::pulse_guide(direction, millis).await?;
+    }
+    let data = camera::guide_expose(exposure_secs).await?;
+    Ok(Arc::new(fits::decode(&data)?))
+}
 
-#[derive(Default)]
-pub struct GuideState;
+fn opposite(direction: GuideDirection) -> GuideDirection {
+    match direction {
+        GuideDirection::North => GuideDirection::South,
+        GuideDirection::South => GuideDirection::North,
+        GuideDirection::East => GuideDirection::West,
+        GuideDirection::West => GuideDirection::East,
+    }
+}
+
+pub struct GuideState {
+    input: GuideInput,
+    running: Option<GuideLoop>,
+    /// The guide star, as last seen
+    star: Option<Star>,
+    /// Whether the last frame lost it
+    star_lost: bool,
+    calibration: Option<Calibration>,
+    /// Recent (RA, Dec) errors (pixels), for the RMS
+    recent_errors: VecDeque<(f64, f64)>,
+    /// When guiding started, for the plot's time axis
+    guiding_since: Instant,
+    /// How the last calibration or loop ended
+    status: Option<String>,
+    plot: LivePlotData,
+    preview: ImagePreview,
+    phd2_input: Phd2Input,
+    /// The event stream, while connecting or connected
+    phd2_connection: Option<task::Handle>,
+    /// PHD2's version, once it has said hello
+    phd2_version: Option<String>,
+    phd2_state: Option<String>,
+    /// PHD2's last alert, settling failure or reason for disconnecting
+    phd2_status: Option<String>,
+}
+
+const RA_SERIES: usize = 0;
+const DEC_SERIES: usize = 1;
+
+impl Default for GuideState {
+    fn default() -> Self {
+        let mut plot = create_live_plot(1000, 20.0).with_x_axis(300.0, "s");
+        plot.add_series("RA", Color::from_rgb(0.3, 0.7, 1.0));
+        plot.add_series("Dec", Color::from_rgb(1.0, 0.4, 0.4));
+        Self {
+            input: GuideInput::default(),
+            running: None,
+            star: None,
+            star_lost: false,
+            calibration: None,
+            recent_errors: VecDeque::new(),
+            guiding_since: Instant::now(),
+            status: None,
+            plot,
+            preview: ImagePreview::default(),
+            phd2_input: Phd2Input::from(&Phd2Config::default()),
+            phd2_connection: None,
+            phd2_version: None,
+            phd2_state: None,
+            phd2_status: None,
+        }
+    }
+}
 
 impl GuideState {
-    pub fn update(&mut self, _message: Message) {}
-    pub fn view(&self) -> Element<'static, Message> {
-        text("Setup tab").width(Length::Fill).into()
+    pub fn on_config_load(&mut self, config: &Config) {
+        self.phd2_input = Phd2Input::from(&config.phd2);
+    }
+
+    /// Stop the internal guider, or PHD2 if it's the one guiding
+    pub fn stop(&mut self, status: String) -> Task<MainMessage> {
+        if let Some(run) = self.running.take() {
+            if let Some(handle) = run.handle {
+                handle.abort();
+            }
+            self.status = Some(status);
+        }
+        if self.phd2_connection.is_some() {
+            return phd2_task(phd2_handler::stop());
+        }
+        Task::none()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::FieldChanged { field, value } => {
+                let input = &mut self.input;
+                match field {
+                    GuideField::Exposure => input.exposure = value,
+                    GuideField::CalibrationPulse => input.calibration_pulse = value,
+                    GuideField::RaAggressiveness => input.ra_aggressiveness = value,
+                    GuideField::DecAggressiveness => input.dec_aggressiveness = value,
+                    GuideField::MinMove => input.min_move = value,
+                    GuideField::MaxPulse => input.max_pulse = value,
+                    GuideField::Dither => input.dither = value,
+                }
+                // settings take effect from the next frame once they're valid
+                if let (Some(run), Ok(settings)) = (&mut self.running, self.input.parse()) {
+                    run.settings = settings;
+                }
+            }
+            Message::StartLooping => {
+                if self.running.is_some() {
+                    return Task::none();
+                }
+                if self.phd2_connection.is_some() {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::Phd2Error(
+                        "Disconnect from PHD2 before guiding here".to_owned(),
+                    )));
+                }
+                let settings = match self.input.parse() {
+                    Ok(settings) => settings,
+                    Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+                };
+                self.star = None;
+                self.star_lost = false;
+                self.status = None;
+                self.running = Some(GuideLoop {
+                    settings,
+                    mode: Mode::Looping,
+                    handle: None,
+                });
+                return self.next_frame(Vec::new());
+            }
+            Message::Calibrate => {
+                let Some(run) = &mut self.running else {
+                    return Task::none();
+                };
+                if self.star.is_none() {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ServerError(
+                        "No guide star to calibrate on".to_owned(),
+                    )));
+                }
+                self.calibration = None;
+                self.status = None;
+                run.mode = Mode::Calibrating(CalibrationRun {
+                    stage: Stage::West,
+                    origin: None,
+                    pulses: 0,
+                });
+            }
+            Message::StartGuiding => {
+                let Some(run) = &mut self.running else {
+                    return Task::none();
+                };
+                let (Some(star), Some(_)) = (self.star, self.calibration) else {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ServerError(
+                        "Guiding needs a guide star and a calibration".to_owned(),
+                    )));
+                };
+                self.status = None;
+                run.mode = Mode::Guiding {
+                    lock: (star.x, star.y),
+                    settling: None,
+                };
+                self.reset_errors();
+            }
+            Message::Dither => {
+                let Some(run) = &mut self.running else {
+                    return Task::none();
+                };
+                let Mode::Guiding { lock, settling } = &mut run.mode else {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ServerError(
+                        "Dithering needs guiding to be running".to_owned(),
+                    )));
+                };
+                let (dx, dy) = guiding::dither_offset(run.settings.dither_pixels);
+                *lock = (lock.0 + dx, lock.1 + dy);
+                *settling = Some(0);
+            }
+            Message::Stop => return self.stop("Stopped".to_owned()),
+            Message::FrameReady(result) => {
+                let Some(run) = &mut self.running else {
+                    return Task::none();
+                };
+                run.handle = None;
+                let image = match result {
+                    Ok(image) => image,
+                    Err(e) => {
+                        self.running = None;
+                        self.status = Some(format!("Failed: {e}"));
+                        return Task::done(MainMessage::ErrorOccurred(e));
+                    }
+                };
+                self.preview.set_image(image.clone());
+                let star = match self.star {
+                    Some(star) => guiding::find_star_near(&image, star.x, star.y),
+                    None => guiding::pick_star(&image),
+                };
+                self.star_lost = star.is_none();
+                let pulses = match star {
+                    Some(star) => {
+                        self.star = Some(star);
+                        self.corrections(star)
+                    }
+                    None => self.lost_star(),
+                };
+                return match pulses {
+                    Ok(pulses) => self.next_frame(pulses),
+                    Err(e) => {
+                        // calibration failed; keep looping so it can be tried again
+                        if let Some(run) = &mut self.running {
+                            run.mode = Mode::Looping;
+                        }
+                        self.status = Some(format!("Calibration failed: {e}"));
+                        Task::batch([
+                            self.next_frame(Vec::new()),
+                            Task::done(MainMessage::ErrorOccurred(e)),
+                        ])
+                    }
+                };
+            }
+            Message::Preview(message) => self.preview.update(message),
+            Message::Phd2FieldChanged { field, value } => {
+                let input = &mut self.phd2_input;
+                match field {
+                    Phd2Field::Address => input.address = value,
+                    Phd2Field::Dither => input.dither = value,
+                    Phd2Field::SettlePixels => input.settle_pixels = value,
+                    Phd2Field::SettleSecs => input.settle_secs = value,
+                    Phd2Field::SettleTimeout => input.settle_timeout = value,
+                }
+            }
+            Message::SavePhd2 => {
+                return match self.phd2_input.parse() {
+                    Ok(phd2) => phd2_task(Config::set_phd2(phd2)),
+                    Err(e) => Task::done(MainMessage::ErrorOccurred(e)),
+                };
+            }
+            Message::Phd2Connect => {
+                if self.phd2_connection.is_some() {
+                    return Task::none();
+                }
+                if self.running.is_some() {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::Phd2Error(
+                        "Stop the internal guider before connecting to PHD2".to_owned(),
+                    )));
+                }
+                let address = self.phd2_input.address.trim().to_owned();
+                self.phd2_status = None;
+                let (task, handle) = Task::run(phd2_handler::connect(address), |event| {
+                    MainMessage::Guide(Message::Phd2(event))
+                })
+                .abortable();
+                self.phd2_connection = Some(handle);
+                return task;
+            }
+            Message::Phd2Disconnect => {
+                if let Some(handle) = self.phd2_connection.take() {
+                    handle.abort();
+                }
+                self.phd2_version = None;
+                self.phd2_state = None;
+                self.phd2_status = Some("Disconnected".to_owned());
+                return Task::perform(phd2_handler::disconnect(), |()| MainMessage::Noop);
+            }
+            Message::Phd2Guide => return phd2_task(phd2_handler::start_guiding()),
+            Message::Phd2Stop => return phd2_task(phd2_handler::stop()),
+            Message::Phd2Dither => return phd2_task(phd2_handler::dither()),
+            Message::Phd2(event) => match event {
+                Phd2Event::Connected { version } => self.phd2_version = Some(version),
+                Phd2Event::AppState(state) => {
+                    if state == "Guiding" && self.phd2_state.as_deref() != Some("Guiding") {
+                        self.reset_errors();
+                    }
+                    self.phd2_state = Some(state);
+                }
+                Phd2Event::GuideStep { ra, dec } => self.record_error(ra, dec),
+                Phd2Event::StarLost => self.phd2_state = Some("Star lost".to_owned()),
+                Phd2Event::SettleDone { error } => {
+                    self.phd2_status = error.map(|e| format!("Settling failed: {e}"));
+                }
+                Phd2Event::Alert(message) => self.phd2_status = Some(message),
+                Phd2Event::Disconnected(reason) => {
+                    self.phd2_connection = None;
+                    self.phd2_version = None;
+                    self.phd2_state = None;
+                    self.phd2_status = Some(reason);
+                }
+            },
+        }
+        Task::none()
+    }
+
+    /// Expose the next guide frame after sending `pulses`
+    fn next_frame(&mut self, pulses: Vec<(GuideDirection, f64)>) -> Task<MainMessage> {
+        let Some(run) = &mut self.running else {
+            return Task::none();
+        };
+        let (task, handle) =
+            Task::perform(guide_step(pulses, run.settings.exposure_secs), |result| {
+                MainMessage::Guide(Message::FrameReady(result))
+            })
+            .abortable();
+        run.handle = Some(handle);
+        task
+    }
+
+    /// The frame had no star where the guide star was
+    fn lost_star(&mut self) -> SiderealResult<Vec<(GuideDirection, f64)>> {
+        let Some(run) = &mut self.running else {
+            return Ok(Vec::new());
+        };
+        match run.mode {
+            // pick a new star next frame
+            Mode::Looping => self.star = None,
+            Mode::Calibrating(_) => {
+                return Err(SiderealError::ServerError(
+                    "Lost the guide star; try a longer exposure or a brighter star".to_owned(),
+                ))
+            }
+            // hold still and keep looking where it was
+            Mode::Guiding { .. } => {}
+        }
+        Ok(Vec::new())
+    }
+
+    /// Pulses to send before the next frame, now that the star is at `star`
+    fn corrections(&mut self, star: Star) -> SiderealResult<Vec<(GuideDirection, f64)>> {
+        let Some(run) = &mut self.running else {
+            return Ok(Vec::new());
+        };
+        let position = (star.x, star.y);
+        match &mut run.mode {
+            Mode::Looping => Ok(Vec::new()),
+            Mode::Calibrating(calibration) => {
+                let pulse = run.settings.calibration_pulse;
+                let direction = match calibration.stage {
+                    Stage::West => GuideDirection::West,
+                    Stage::East { .. } => GuideDirection::East,
+                    Stage::North { .. } => GuideDirection::North,
+                    Stage::South { .. } => GuideDirection::South,
+                };
+                match calibration.stage {
+                    Stage::West | Stage::North { .. } => {
+                        let Some(origin) = calibration.origin else {
+                            calibration.origin = Some(position);
+                            calibration.pulses = 1;
+                            return Ok(vec![(direction, pulse)]);
+                        };
+                        let moved = (position.0 - origin.0, position.1 - origin.1);
+                        if moved.0.hypot(moved.1) < CALIBRATION_DISTANCE
+                            && calibration.pulses < MAX_CALIBRATION_STEPS
+                        {
+                            calibration.pulses += 1;
+                            return Ok(vec![(direction, pulse)]);
+                        }
+                        let axis = AxisMove {
+                            moved,
+                            millis: calibration.pulses as f64 * pulse,
+                        };
+                        calibration.stage = match calibration.stage {
+                            Stage::North { ra } => Stage::South { ra, dec: axis },
+                            _ => Stage::East { ra: axis },
+                        };
+                        // back the way it came, so the star ends up where it started
+                        calibration.pulses -= 1;
+                        Ok(vec![(opposite(direction), pulse)])
+                    }
+                    Stage::East { ra } => {
+                        if calibration.pulses > 0 {
+                            calibration.pulses -= 1;
+                            return Ok(vec![(direction, pulse)]);
+                        }
+                        calibration.stage = Stage::North { ra };
+                        calibration.origin = Some(position);
+                        calibration.pulses = 1;
+                        Ok(vec![(GuideDirection::North, pulse)])
+                    }
+                    Stage::South { ra, dec } => {
+                        if calibration.pulses > 0 {
+                            calibration.pulses -= 1;
+                            return Ok(vec![(direction, pulse)]);
+                        }
+                        let result = Calibration::new(ra.moved, ra.millis, dec.moved, dec.millis)?;
+                        self.status = Some(format!(
+                            "Calibrated: RA {:.2} px/s, Dec {:.2} px/s, axes {:.0}° apart",
+                            result.ra_rate(),
+                            result.dec_rate(),
+                            result.axis_angle()
+                        ));
+                        self.calibration = Some(result);
+                        run.mode = Mode::Looping;
+                        Ok(Vec::new())
+                    }
+                }
+            }
+            Mode::Guiding { lock, settling } => {
+                let Some(calibration) = self.calibration else {
+                    return Ok(Vec::new());
+                };
+                let (dx, dy) = (position.0 - lock.0, position.1 - lock.1);
+                if let Some(frames) = settling {
+                    if dx.hypot(dy) <= SETTLE_DISTANCE {
+                        *frames += 1;
+                        if *frames >= SETTLE_FRAMES {
+                            *settling = None;
+                        }
+                    } else {
+                        *frames = 0;
+                    }
+                }
+                let pulses = calibration.corrections(dx, dy, &run.settings.correction);
+                let (ra_error, dec_error) = calibration.errors(dx, dy);
+                self.record_error(ra_error, dec_error);
+                Ok(pulses)
+            }
+        }
+    }
+
+    /// Start the error plot and RMS over, as guiding starts
+    fn reset_errors(&mut self) {
+        self.plot.clear();
+        self.recent_errors.clear();
+        self.guiding_since = Instant::now();
+    }
+
+    /// Plot a guide star offset (pixels) along the RA and Dec axes
+    fn record_error(&mut self, ra_error: f64, dec_error: f64) {
+        let timestamp = self.guiding_since.elapsed().as_secs_f64();
+        for (series, value) in [(RA_SERIES, ra_error), (DEC_SERIES, dec_error)] {
+            self.plot
+                .add_data_point(series, DataPoint { timestamp, value });
+        }
+        self.recent_errors.push_back((ra_error, dec_error));
+        while self.recent_errors.len() > RMS_FRAMES {
+            self.recent_errors.pop_front();
+        }
+    }
+
+    /// RMS (pixels) of the recent RA and Dec errors
+    fn rms(&self) -> Option<(f64, f64)> {
+        if self.recent_errors.is_empty() {
+            return None;
+        }
+        let count = self.recent_errors.len() as f64;
+        let (ra, dec) = self
+            .recent_errors
+            .iter()
+            .fold((0.0, 0.0), |(ra, dec), (r, d)| (ra + r * r, dec + d * d));
+        Some(((ra / count).sqrt(), (dec / count).sqrt()))
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let setting = |label: &str, placeholder: &str, value: &str, field: GuideField| {
+            row![
+                text(label.to_owned()).width(Length::Fixed(150.0)),
+                sidereal_text_input(placeholder, value)
+                    .on_input(move |value| Message::FieldChanged { field, value })
+                    .width(Length::Fill)
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+        };
+
+        let mode = self.running.as_ref().map(|run| run.mode);
+        let star = match (self.star, self.star_lost) {
+            (_, true) => "Star lost".to_owned(),
+            (Some(star), false) => format!(
+                "Star at ({:.1}, {:.1}), HFR {:.2}",
+                star.x, star.y, star.hfr
+            ),
+            (None, false) => "No star".to_owned(),
+        };
+        let status = match mode {
+            None => self.status.clone().unwrap_or_else(|| "Idle".to_owned()),
+            Some(Mode::Looping) => match &self.status {
+                Some(status) => format!("Looping. {status}"),
+                None => "Looping".to_owned(),
+            },
+            Some(Mode::Calibrating(calibration)) => match calibration.stage {
+                Stage::West => format!("Calibrating RA (West pulse {})", calibration.pulses),
+                Stage::North { .. } => {
+                    format!("Calibrating Dec (North pulse {})", calibration.pulses)
+                }
+                Stage::East { .. } | Stage::South { .. } => {
+                    "Calibrating (returning the star)".to_owned()
+                }
+            },
+            Some(Mode::Guiding {
+                settling: Some(_), ..
+            }) => "Settling after dither".to_owned(),
+            Some(Mode::Guiding { .. }) => "Guiding".to_owned(),
+        };
+        let rms = match self.rms() {
+            Some((ra, dec)) => format!(
+                "RMS error: RA {ra:.2} px, Dec {dec:.2} px, total {:.2} px",
+                ra.hypot(dec)
+            ),
+            None => "RMS error: --".to_owned(),
+        };
+
+        let looping = mode.is_some();
+        let idle_loop = matches!(mode, Some(Mode::Looping));
+        let guiding = matches!(mode, Some(Mode::Guiding { .. }));
+        let mut loop_button = track_button(
+            container(text("Loop"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if !looping && self.phd2_connection.is_none() {
+            loop_button = loop_button.on_press(Message::StartLooping);
+        }
+        let mut stop_button = stop_track_button(
+            container(text("Stop"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if looping {
+            stop_button = stop_button.on_press(Message::Stop);
+        }
+
+        let phd2_setting = |label: &str, placeholder: &str, value: &str, field: Phd2Field| {
+            row![
+                text(label.to_owned()).width(Length::Fixed(150.0)),
+                sidereal_text_input(placeholder, value)
+                    .on_input(move |value| Message::Phd2FieldChanged { field, value })
+                    .width(Length::Fill)
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+        };
+        let connected = self.phd2_version.is_some();
+        let phd2_status = match (&self.phd2_connection, &self.phd2_version) {
+            (None, _) => "Not connected".to_owned(),
+            (Some(_), None) => "Connecting".to_owned(),
+            (Some(_), Some(version)) => format!(
+                "PHD2 {version}: {}",
+                self.phd2_state.as_deref().unwrap_or("Connected")
+            ),
+        };
+        let phd2_input = &self.phd2_input;
+        let phd2 = content_container(
+            column![
+                text("PHD2"),
+                phd2_setting(
+                    "Address",
+                    "host:port",
+                    &phd2_input.address,
+                    Phd2Field::Address
+                ),
+                phd2_setting(
+                    "Dither (px)",
+                    "pixels",
+                    &phd2_input.dither,
+                    Phd2Field::Dither
+                ),
+                phd2_setting(
+                    "Settle within (px)",
+                    "pixels",
+                    &phd2_input.settle_pixels,
+                    Phd2Field::SettlePixels
+                ),
+                phd2_setting(
+                    "Settle for (s)",
+                    "seconds",
+                    &phd2_input.settle_secs,
+                    Phd2Field::SettleSecs
+                ),
+                phd2_setting(
+                    "Settle timeout (s)",
+                    "seconds",
+                    &phd2_input.settle_timeout,
+                    Phd2Field::SettleTimeout
+                ),
+                row![
+                    sidereal_button(
+                        "Connect",
+                        Some(Message::Phd2Connect),
+                        self.phd2_connection.is_none() && !looping
+                    )
+                    .width(Length::Fill),
+                    sidereal_button(
+                        "Disconnect",
+                        Some(Message::Phd2Disconnect),
+                        self.phd2_connection.is_some()
+                    )
+                    .width(Length::Fill),
+                    sidereal_button("Apply", Some(Message::SavePhd2), true).width(Length::Fill),
+                ]
+                .spacing(10),
+                row![
+                    sidereal_button("Guide", Some(Message::Phd2Guide), connected)
+                        .width(Length::Fill),
+                    sidereal_button("Dither", Some(Message::Phd2Dither), connected)
+                        .width(Length::Fill),
+                    sidereal_button("Stop", Some(Message::Phd2Stop), connected).width(Length::Fill),
+                ]
+                .spacing(10),
+                text(phd2_status),
+                text(self.phd2_status.clone().unwrap_or_default()).size(12),
+            ]
+            .spacing(10),
+            ContainerLayer::Layer1,
+        )
+        .width(Length::Fill);
+
+        let input = &self.input;
+        let controls = column![
+            content_container(
+                column![
+                    text("Guide Camera"),
+                    setting(
+                        "Exposure (s)",
+                        "seconds",
+                        &input.exposure,
+                        GuideField::Exposure
+                    ),
+                    setting(
+                        "Calibration pulse (ms)",
+                        "milliseconds",
+                        &input.calibration_pulse,
+                        GuideField::CalibrationPulse
+                    ),
+                    setting(
+                        "RA aggressiveness (%)",
+                        "0-100",
+                        &input.ra_aggressiveness,
+                        GuideField::RaAggressiveness
+                    ),
+                    setting(
+                        "Dec aggressiveness (%)",
+                        "0-100",
+                        &input.dec_aggressiveness,
+                        GuideField::DecAggressiveness
+                    ),
+                    setting(
+                        "Minimum move (px)",
+                        "pixels",
+                        &input.min_move,
+                        GuideField::MinMove
+                    ),
+                    setting(
+                        "Maximum pulse (ms)",
+                        "milliseconds",
+                        &input.max_pulse,
+                        GuideField::MaxPulse
+                    ),
+                    setting("Dither (px)", "pixels", &input.dither, GuideField::Dither),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+            content_container(
+                column![
+                    text("Guiding"),
+                    row![
+                        loop_button,
+                        sidereal_button(
+                            "Calibrate",
+                            Some(Message::Calibrate),
+                            idle_loop && self.star.is_some()
+                        )
+                        .width(Length::Fill),
+                        sidereal_button(
+                            "Guide",
+                            Some(Message::StartGuiding),
+                            idle_loop && self.star.is_some() && self.calibration.is_some()
+                        )
+                        .width(Length::Fill),
+                        sidereal_button("Dither", Some(Message::Dither), guiding)
+                            .width(Length::Fill),
+                        stop_button,
+                    ]
+                    .spacing(10),
+                    text(status),
+                    text(star).size(12),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+            phd2,
+            content_container(
+                column![
+                    text("Guide Error"),
+                    text(rms),
+                    live_plot(&self.plot)
+                        .width(Length::Fill)
+                        .height(Length::Fixed(250.0)),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+        ]
+        .spacing(10)
+        .width(Length::FillPortion(2));
+
+        row![
+            controls,
+            container(self.preview.view().map(Message::Preview)).width(Length::FillPortion(3)),
+        ]
+        .spacing(10)
+        .into()
     }
 }
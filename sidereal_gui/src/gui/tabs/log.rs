@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use iced::widget::{column, row, scrollable, text, Column};
+use iced::{Alignment, Element, Font, Length, Task};
+use tracing::Level;
+
+use crate::app::Message as MainMessage;
+use crate::gui::styles;
+use crate::gui::styles::button_style::sidereal_button;
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::picklist_style::sidereal_picklist;
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::logging::{self, LogEntry};
+
+/// Entries kept for the session; the log file has the rest
+const MAX_ENTRIES: usize = 5000;
+/// Most entries drawn at once, newest last
+const MAX_SHOWN: usize = 500;
+
+/// Least severe level shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 5] = [
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    fn level(self) -> Level {
+        match self {
+            LogLevel::Error => Level::ERROR,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Trace => Level::TRACE,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.level())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Logged(LogEntry),
+    LevelSelected(LogLevel),
+    SearchChanged(String),
+    Clear,
+}
+
+pub struct LogState {
+    entries: VecDeque<LogEntry>,
+    level: LogLevel,
+    search: String,
+}
+
+impl Default for LogState {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            level: LogLevel::Info,
+            search: String::new(),
+        }
+    }
+}
+
+impl LogState {
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::Logged(entry) => {
+                if self.entries.len() == MAX_ENTRIES {
+                    self.entries.pop_front();
+                }
+                self.entries.push_back(entry);
+            }
+            Message::LevelSelected(level) => self.level = level,
+            Message::SearchChanged(search) => self.search = search,
+            Message::Clear => self.entries.clear(),
+        }
+        Task::none()
+    }
+
+    fn shows(&self, entry: &LogEntry, search: &str) -> bool {
+        // more severe levels compare as lower
+        entry.level <= self.level.level()
+            && (search.is_empty()
+                || entry.message.to_lowercase().contains(search)
+                || entry.target.to_lowercase().contains(search))
+    }
+
+    fn entry_line(entry: &LogEntry) -> Element<'_, Message> {
+        let line = text(format!(
+            "{} {:>5} {}: {}",
+            entry.time.format("%H:%M:%S%.3f"),
+            entry.level,
+            entry.target,
+            entry.message
+        ))
+        .size(12)
+        .font(Font::MONOSPACE);
+        match entry.level {
+            Level::ERROR => line.color(styles::RED_TEXT),
+            Level::WARN => line.color(styles::AMBER_TEXT),
+            Level::INFO => line,
+            _ => line.color(styles::BACKGROUND_TEXT_COLOR),
+        }
+        .into()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let search = self.search.trim().to_lowercase();
+        let mut shown: Vec<&LogEntry> = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| self.shows(entry, &search))
+            .take(MAX_SHOWN)
+            .collect();
+        shown.reverse();
+        let lines = shown
+            .into_iter()
+            .fold(Column::new().spacing(2), |lines, entry| {
+                lines.push(Self::entry_line(entry))
+            });
+        let session_log = match logging::session_log() {
+            Some(path) => format!("Session log: {}", path.display()),
+            None => "No session log file this time".to_owned(),
+        };
+
+        column![
+            content_container(
+                column![
+                    text("Log"),
+                    row![
+                        text("Level"),
+                        sidereal_picklist(
+                            LogLevel::ALL.to_vec(),
+                            Some(self.level),
+                            Message::LevelSelected
+                        )
+                        .width(Length::Fixed(120.0)),
+                        sidereal_text_input("Search", &self.search)
+                            .on_input(Message::SearchChanged)
+                            .width(Length::Fill),
+                        sidereal_button("Clear", Some(Message::Clear), !self.entries.is_empty()),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                    text(session_log).size(12),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+            content_container(
+                scrollable(lines)
+                    .anchor_bottom()
+                    .width(Length::Fill)
+                    .height(Length::Fill),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill)
+            .height(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
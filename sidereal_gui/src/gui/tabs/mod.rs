@@ -1,23 +1,33 @@
 use iced::widget::{column, row};
 use iced::{Element, Length};
 
+pub mod calibration;
 pub mod capture;
 pub mod focus;
 pub mod guide;
+pub mod log;
 pub mod mount;
 pub mod observatory;
+pub mod passes;
 pub mod plate_solve;
+pub mod power;
+pub mod safety;
 pub mod setup;
 pub mod telescope;
 
 use crate::gui::styles::tab_style::tab_button;
 
+use self::calibration::CalibrationState;
 use self::capture::CaptureState;
 use self::focus::FocusState;
 use self::guide::GuideState;
+use self::log::LogState;
 use self::mount::MountState;
 use self::observatory::ObservatoryState;
+use self::passes::PassesState;
 use self::plate_solve::PlateSolveState;
+use self::power::PowerState;
+use self::safety::SafetyState;
 use self::setup::SetupState;
 use self::telescope::TelescopeState;
 
@@ -26,11 +36,16 @@ pub enum Tab {
     Mount,
     Setup,
     Observatory,
+    Safety,
+    Power,
+    Passes,
     PlateSolve,
     Guide,
     Focus,
     Capture,
+    Calibration,
     Telescope,
+    Log,
 }
 
 impl Default for Tab {
@@ -45,11 +60,16 @@ pub struct MainWindowState {
     pub mount: MountState,
     pub setup: SetupState,
     pub observatory: ObservatoryState,
+    pub safety: SafetyState,
+    pub power: PowerState,
+    pub passes: PassesState,
     pub plate_solve: PlateSolveState,
     pub guide: GuideState,
     pub focus: FocusState,
     pub capture: CaptureState,
+    pub calibration: CalibrationState,
     pub telescope: TelescopeState,
+    pub log: LogState,
 }
 
 pub fn header<F, M>(active: Tab, on_select: F) -> Element<'static, M>
@@ -70,12 +90,17 @@ where
     column![row![
         tab_button("Setup", Tab::Setup),
         tab_button("Mount", Tab::Mount),
+        tab_button("Passes", Tab::Passes),
         tab_button("Observatory", Tab::Observatory),
+        tab_button("Safety", Tab::Safety),
+        tab_button("Power", Tab::Power),
         tab_button("Plate Solve", Tab::PlateSolve),
         tab_button("Focus", Tab::Focus),
         tab_button("Capture", Tab::Capture),
+        tab_button("Calibration", Tab::Calibration),
         tab_button("Guide", Tab::Guide),
-        tab_button("Telescope", Tab::Telescope)
+        tab_button("Telescope", Tab::Telescope),
+        tab_button("Log", Tab::Log)
     ]
     .spacing(5)
     .width(Length::Fill),]
@@ -1,14 +1,32 @@
-use iced::widget::{checkbox, column, container, row, slider, text, Space};
-use iced::{Alignment, Element, Length, Task};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use overpass_planner::ObserverLocation;
+use protos::protos::PassTrack;
+
+use iced::widget::{checkbox, column, container, progress_bar, row, slider, text, Space};
+use iced::{task, Alignment, Element, Length, Subscription, Task};
 
 use crate::app::Message as MainMessage;
+use crate::config::Config;
 use crate::gui::styles::button_style::{sidereal_button, stop_track_button, track_button};
 use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::picklist_style::sidereal_picklist;
 use crate::gui::styles::text_input_style::sidereal_text_input;
 use crate::gui::widgets::mount_steer_button::{
     ButtonDirection, MountMoveMessage, MountSteerButton,
 };
+use crate::indi_handler::mount::{self, TrackMode};
+use crate::model::tracking_manager::{
+    self, Setpoint, TrackingCommand, TrackingManager, TrackingOptions, TrackingState,
+};
+use crate::model::{SiderealError, SiderealResult};
 use crate::planetarium_handler::planetarium_sender;
+use crate::plate_solver;
+
+/// Longest a GOTO may take before it's given up on
+const GOTO_TIMEOUT: Duration = Duration::from_secs(300);
+
 #[derive(Debug, Clone)]
 pub enum Message {
     Noop,
@@ -23,11 +41,185 @@ pub enum Message {
         index: usize,
         message: MountMoveMessage,
     },
+    TargetRaChanged(String),
+    TargetDecChanged(String),
+    Goto,
+    AbortGoto,
+    GotoFinished(SiderealResult<()>),
+    ParkUpdated(bool),
+    /// The mount's slew rates, slowest first, and which is in use
+    SlewRatesUpdated {
+        rates: Vec<String>,
+        selected: Option<usize>,
+    },
+    Park,
+    Unpark,
+    SlewRateChanged(f32),
+    SlewRateReleased,
+    TrackingUpdated(bool),
+    /// `None` for a mode Sidereal doesn't offer
+    TrackModeUpdated(Option<TrackMode>),
+    TrackRateUpdated {
+        ra_arcsec: f64,
+        dec_arcsec: f64,
+    },
+    TrackingToggled(bool),
+    TrackModeSelected(TrackMode),
+    CustomRaRateChanged(String),
+    CustomDecRateChanged(String),
+    ApplyCustomRate,
+    LeapfrogToggled(bool),
+    LeapfrogDistanceChanged(String),
+    PauseAtHorizonToggled(bool),
+    RateTrackingToggled(bool),
+    HorizonDistanceChanged(String),
+    PassReady(SiderealResult<Vec<Setpoint>>),
+    PassTick,
+    PassCommandDone(SiderealResult<()>),
+}
+
+/// A GOTO in progress
+struct Slew {
+    handle: task::Handle,
+    /// RA hours, Dec degrees
+    from: (f64, f64),
+    to: (f64, f64),
+}
+
+/// The armed pass being followed
+struct PassFollow {
+    manager: TrackingManager,
+    /// Whether a command is still with the mount, so the next waits for it
+    commanding: bool,
+    /// The latest command held back while the mount was busy; newer setpoints
+    /// replace older ones rather than queueing behind them
+    pending: Option<TrackingCommand>,
+    /// Following by track rates, which have to be put back afterwards
+    track_rate: bool,
+}
+
+impl PassFollow {
+    /// Send the pending command, unless the mount is still busy with the last
+    fn dispatch(&mut self) -> Task<MainMessage> {
+        if self.commanding {
+            return Task::none();
+        }
+        let Some(command) = self.pending.take() else {
+            return Task::none();
+        };
+        self.commanding = true;
+        let track_rate = self.track_rate;
+        Task::perform(
+            async move {
+                match command {
+                    TrackingCommand::SlewTo { ra_hours, dec_deg } => {
+                        mount::slew_to(ra_hours, dec_deg).await
+                    }
+                    TrackingCommand::SetRate {
+                        ra_arcsec,
+                        dec_arcsec,
+                    } => mount::set_track_rate(ra_arcsec, dec_arcsec).await,
+                    TrackingCommand::Stop => {
+                        mount::abort_motion().await?;
+                        if track_rate {
+                            mount::set_track_mode(TrackMode::Sidereal).await?;
+                        }
+                        Ok(())
+                    }
+                }
+            },
+            |result| MainMessage::Mount(Message::PassCommandDone(result)),
+        )
+    }
+}
+
+fn timestamp(timestamp: Option<&prost_types::Timestamp>) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp?;
+    DateTime::from_timestamp(timestamp.seconds, timestamp.nanos.max(0) as u32)
+}
+
+/// Hours or degrees from "12 34 56.7", "12:34:56.7", "12h34m56.7s",
+/// "-5°12'30\"" or plain decimal
+fn parse_sexagesimal(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let negative = value.starts_with('-');
+    let parts: Vec<f64> = value
+        .trim_start_matches(['+', '-'])
+        .split(|c: char| c.is_whitespace() || ":hmsd°'\"".contains(c))
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            part.parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+        })
+        .collect::<Option<_>>()?;
+    let magnitude = match parts[..] {
+        [whole] => whole,
+        // minutes and seconds can't reach 60
+        [whole, minutes] if minutes < 60.0 => whole + minutes / 60.0,
+        [whole, minutes, seconds] if minutes < 60.0 && seconds < 60.0 => {
+            whole + minutes / 60.0 + seconds / 3600.0
+        }
+        _ => return None,
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+fn parse_ra(value: &str) -> SiderealResult<f64> {
+    parse_sexagesimal(value)
+        .filter(|ra| (0.0..24.0).contains(ra))
+        .ok_or_else(|| {
+            SiderealError::ParseError(format!(
+                "Invalid RA `{value}`; expected hours 0-24 as HH MM SS or decimal"
+            ))
+        })
+}
+
+fn parse_dec(value: &str) -> SiderealResult<f64> {
+    parse_sexagesimal(value)
+        .filter(|dec| (-90.0..=90.0).contains(dec))
+        .ok_or_else(|| {
+            SiderealError::ParseError(format!(
+                "Invalid Dec `{value}`; expected degrees ±90 as ±DD MM SS or decimal"
+            ))
+        })
 }
 
 pub struct MountState {
     mount_ra: String,
     mount_dec: String,
+    /// RA hours, Dec degrees, as last reported
+    position: Option<(f64, f64)>,
+    target_ra: String,
+    target_dec: String,
+    slew: Option<Slew>,
+    /// Unknown until the mount reports it, or if it can't park
+    parked: Option<bool>,
+    slew_rates: Vec<String>,
+    /// Index into `slew_rates` the slider is at
+    slew_rate: usize,
+    tracking: Option<bool>,
+    track_mode: Option<TrackMode>,
+    /// Custom rates (arcseconds per second), as typed
+    custom_ra_rate: String,
+    custom_dec_rate: String,
+    /// The pass the planetarium asked to follow
+    armed_pass: Option<PassTrack>,
+    leapfrog: bool,
+    /// Seconds ahead along the pass, as typed
+    leapfrog_distance: String,
+    pause_at_horizon: bool,
+    /// Degrees above the horizon, as typed
+    horizon_distance: String,
+    /// Follow passes by streaming track rates rather than slewing
+    rate_tracking: bool,
+    /// Whether the mount takes custom track rates
+    supports_track_rate: bool,
+    following: Option<PassFollow>,
+    /// Whether the pass's path is being worked out, before following it
+    preparing: bool,
+    /// Why following stopped, or that it's being set up
+    pass_status: Option<String>,
     mount_steer_buttons: Vec<MountSteerButton>,
 }
 
@@ -36,31 +228,146 @@ impl Default for MountState {
         Self {
             mount_ra: Default::default(),
             mount_dec: Default::default(),
+            position: None,
+            target_ra: Default::default(),
+            target_dec: Default::default(),
+            slew: None,
+            parked: None,
+            slew_rates: Vec::new(),
+            slew_rate: 0,
+            tracking: None,
+            track_mode: None,
+            custom_ra_rate: Default::default(),
+            custom_dec_rate: Default::default(),
+            armed_pass: None,
+            leapfrog: true,
+            leapfrog_distance: "30".to_owned(),
+            pause_at_horizon: false,
+            horizon_distance: "10".to_owned(),
+            rate_tracking: false,
+            supports_track_rate: false,
+            following: None,
+            preparing: false,
+            pass_status: None,
             mount_steer_buttons: (0..9).map(|_| MountSteerButton::default()).collect(),
         }
     }
 }
 
 impl MountState {
+    /// Have `pass` followed when tracking starts
+    pub fn arm_pass(&mut self, pass: PassTrack) {
+        self.armed_pass = Some(pass);
+        self.pass_status = None;
+    }
+
+    /// Stop following a pass without commanding the mount, for when something
+    /// else is taking it over
+    pub fn abandon_pass(&mut self, status: String) {
+        self.preparing = false;
+        if self.following.take().is_some() {
+            self.pass_status = Some(status);
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match &self.following {
+            Some(_) => iced::time::every(Duration::from_secs(1)).map(|_| Message::PassTick),
+            None => Subscription::none(),
+        }
+    }
+
+    fn tracking_options(&self) -> SiderealResult<TrackingOptions> {
+        let parse = |what: &str, value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .ok_or_else(|| SiderealError::ParseError(format!("Invalid {what} `{value}`")))
+        };
+        Ok(TrackingOptions {
+            leapfrog: self.leapfrog,
+            leapfrog_secs: parse("leapfrog distance", &self.leapfrog_distance)?,
+            pause_at_horizon: self.pause_at_horizon,
+            horizon_deg: parse("horizon distance", &self.horizon_distance)?,
+            track_rate: self.rate_tracking && self.supports_track_rate,
+        })
+    }
+
     pub fn update(&mut self, message: Message) -> Task<MainMessage> {
         match message {
             Message::Noop => {}
             Message::SetSetPoint(_) => todo!(),
-            Message::StartTracking => todo!(),
-            Message::StopTracking => todo!(),
+            Message::StartTracking => {
+                if self.following.is_some() || self.preparing {
+                    return Task::none();
+                }
+                let Some(pass) = &self.armed_pass else {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        "No pass to follow; pick one in the Passes tab or the planetarium"
+                            .to_owned(),
+                    )));
+                };
+                if let Err(e) = self.tracking_options() {
+                    return Task::done(MainMessage::ErrorOccurred(e));
+                }
+                let (Some(start), Some(end)) =
+                    (timestamp(pass.start.as_ref()), timestamp(pass.end.as_ref()))
+                else {
+                    return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                        "The pass has no start or end time".to_owned(),
+                    )));
+                };
+                let tle = pass.tle.clone();
+                self.preparing = true;
+                self.pass_status = Some(format!("Working out {}'s path", pass.name));
+                return Task::perform(
+                    async move {
+                        let location = Config::get().await.location;
+                        let location = ObserverLocation {
+                            latitude: location.latitude.into(),
+                            longitude: location.longitude.into(),
+                            altitude: location.altitude.into(),
+                        };
+                        tracking_manager::pass_setpoints(&tle, location, start, end)
+                    },
+                    |result| MainMessage::Mount(Message::PassReady(result)),
+                );
+            }
+            Message::StopTracking => {
+                self.preparing = false;
+                self.pass_status = Some("Stopped".to_owned());
+                if let Some(follow) = self.following.take() {
+                    return Task::perform(
+                        async move {
+                            mount::abort_motion().await?;
+                            if follow.track_rate {
+                                mount::set_track_mode(TrackMode::Sidereal).await?;
+                            }
+                            Ok(())
+                        },
+                        |result| match result {
+                            Ok(()) => MainMessage::Noop,
+                            Err(e) => MainMessage::ErrorOccurred(e),
+                        },
+                    );
+                }
+            }
             Message::CoordsUpdated { ra_hours, dec_deg } => {
                 self.mount_ra = ra_hours.to_string();
                 self.mount_dec = dec_deg.to_string();
+                self.position = Some((ra_hours, dec_deg));
                 return Task::perform(
                     async move {
-                        planetarium_sender::set_mount_position(ra_hours as f32, dec_deg as f32)
+                        planetarium_sender::set_mount_position(ra_hours, dec_deg)
                             .await
                             .map_err(|e| e.to_string())
                     },
                     |result| match result {
                         Ok(_) => MainMessage::Noop, // or whatever message you want on success
                         Err(e) => {
-                            println!("failed to send mount position to planetarium: {}", e);
+                            tracing::debug!("failed to send mount position to planetarium: {}", e);
                             MainMessage::Noop
                         }
                     },
@@ -69,9 +376,307 @@ impl MountState {
             Message::MountMove { index, message } => {
                 return self.mount_steer_buttons[index].update(message);
             }
+            Message::TargetRaChanged(value) => self.target_ra = value,
+            Message::TargetDecChanged(value) => self.target_dec = value,
+            Message::Goto => {
+                if self.slew.is_some() {
+                    return Task::none();
+                }
+                let target = match (parse_ra(&self.target_ra), parse_dec(&self.target_dec)) {
+                    (Ok(ra_hours), Ok(dec_deg)) => (ra_hours, dec_deg),
+                    (Err(e), _) | (_, Err(e)) => return Task::done(MainMessage::ErrorOccurred(e)),
+                };
+                let (ra_hours, dec_deg) = target;
+                let (task, handle) = Task::perform(
+                    async move {
+                        mount::slew_to(ra_hours, dec_deg).await?;
+                        mount::wait_for_slew(GOTO_TIMEOUT).await?;
+                        planetarium_sender::set_selected_target(ra_hours, dec_deg)
+                            .await
+                    },
+                    |result| MainMessage::Mount(Message::GotoFinished(result)),
+                )
+                .abortable();
+                self.slew = Some(Slew {
+                    handle,
+                    from: self.position.unwrap_or(target),
+                    to: target,
+                });
+                return task;
+            }
+            Message::AbortGoto => {
+                if let Some(slew) = self.slew.take() {
+                    slew.handle.abort();
+                    return Task::perform(mount::abort_motion(), |result| match result {
+                        Ok(()) => MainMessage::Noop,
+                        Err(e) => MainMessage::ErrorOccurred(e),
+                    });
+                }
+            }
+            Message::GotoFinished(result) => {
+                self.slew = None;
+                if let Err(e) = result {
+                    return Task::done(MainMessage::ErrorOccurred(e));
+                }
+            }
+            Message::ParkUpdated(parked) => self.parked = Some(parked),
+            Message::SlewRatesUpdated { rates, selected } => {
+                self.slew_rates = rates;
+                if let Some(selected) = selected {
+                    self.slew_rate = selected;
+                }
+            }
+            Message::Park | Message::Unpark => {
+                let parked = matches!(message, Message::Park);
+                return Task::perform(mount::park(parked), |result| match result {
+                    Ok(()) => MainMessage::Noop,
+                    Err(e) => MainMessage::ErrorOccurred(e),
+                });
+            }
+            Message::SlewRateChanged(value) => self.slew_rate = value.round() as usize,
+            // only sent once the slider's let go, rather than for every step dragged through
+            Message::SlewRateReleased => {
+                let Some(rate) = self.slew_rates.get(self.slew_rate).cloned() else {
+                    return Task::none();
+                };
+                return Task::perform(mount::set_slew_rate(rate), |result| match result {
+                    Ok(()) => MainMessage::Noop,
+                    Err(e) => MainMessage::ErrorOccurred(e),
+                });
+            }
+            Message::TrackingUpdated(tracking) => self.tracking = Some(tracking),
+            Message::TrackModeUpdated(mode) => self.track_mode = mode,
+            Message::TrackRateUpdated {
+                ra_arcsec,
+                dec_arcsec,
+            } => {
+                self.supports_track_rate = true;
+                // filled in from the mount until typed over
+                if self.custom_ra_rate.is_empty() && self.custom_dec_rate.is_empty() {
+                    self.custom_ra_rate = ra_arcsec.to_string();
+                    self.custom_dec_rate = dec_arcsec.to_string();
+                }
+            }
+            Message::TrackingToggled(tracking) => {
+                return Task::perform(mount::set_tracking(tracking), |result| match result {
+                    Ok(()) => MainMessage::Noop,
+                    Err(e) => MainMessage::ErrorOccurred(e),
+                });
+            }
+            Message::TrackModeSelected(mode) => {
+                return Task::perform(mount::set_track_mode(mode), |result| match result {
+                    Ok(()) => MainMessage::Noop,
+                    Err(e) => MainMessage::ErrorOccurred(e),
+                });
+            }
+            Message::CustomRaRateChanged(value) => self.custom_ra_rate = value,
+            Message::CustomDecRateChanged(value) => self.custom_dec_rate = value,
+            Message::ApplyCustomRate => {
+                let parse = |what: &str, value: &str| {
+                    value
+                        .trim()
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|rate| rate.is_finite())
+                        .ok_or_else(|| {
+                            SiderealError::ParseError(format!("Invalid {what} rate `{value}`"))
+                        })
+                };
+                let rates = parse("RA", &self.custom_ra_rate)
+                    .and_then(|ra| Ok((ra, parse("Dec", &self.custom_dec_rate)?)));
+                return match rates {
+                    Ok((ra, dec)) => {
+                        Task::perform(mount::set_track_rate(ra, dec), |result| match result {
+                            Ok(()) => MainMessage::Noop,
+                            Err(e) => MainMessage::ErrorOccurred(e),
+                        })
+                    }
+                    Err(e) => Task::done(MainMessage::ErrorOccurred(e)),
+                };
+            }
+            Message::LeapfrogToggled(leapfrog) => self.leapfrog = leapfrog,
+            Message::LeapfrogDistanceChanged(value) => self.leapfrog_distance = value,
+            Message::PauseAtHorizonToggled(pause) => self.pause_at_horizon = pause,
+            Message::RateTrackingToggled(rate_tracking) => self.rate_tracking = rate_tracking,
+            Message::HorizonDistanceChanged(value) => self.horizon_distance = value,
+            Message::PassReady(result) => {
+                // stopped while the path was worked out
+                if !std::mem::take(&mut self.preparing) {
+                    return Task::none();
+                }
+                let manager = result.and_then(|setpoints| {
+                    let options = self.tracking_options()?;
+                    Ok((
+                        TrackingManager::new(setpoints, options)?,
+                        options.track_rate,
+                    ))
+                });
+                match manager {
+                    Ok((manager, track_rate)) => {
+                        self.pass_status = None;
+                        self.following = Some(PassFollow {
+                            manager,
+                            commanding: false,
+                            pending: None,
+                            track_rate,
+                        });
+                        return Task::done(MainMessage::Mount(Message::PassTick));
+                    }
+                    Err(e) => {
+                        self.pass_status = Some(format!("Couldn't follow the pass: {e}"));
+                        return Task::done(MainMessage::ErrorOccurred(e));
+                    }
+                }
+            }
+            Message::PassTick => {
+                let Some(follow) = &mut self.following else {
+                    return Task::none();
+                };
+                // the manager moves on every tick, busy mount or not
+                if let Some(command) = follow.manager.update(Utc::now()) {
+                    let catching_up = matches!(
+                        (follow.pending, command),
+                        (
+                            Some(TrackingCommand::SlewTo { .. }),
+                            TrackingCommand::SetRate { .. }
+                        )
+                    );
+                    // a slew to catch the satellite isn't dropped for rates
+                    // that only make sense once the mount is there
+                    if !catching_up {
+                        follow.pending = Some(command);
+                    }
+                }
+                return follow.dispatch();
+            }
+            Message::PassCommandDone(result) => {
+                let Some(follow) = &mut self.following else {
+                    return Task::none();
+                };
+                follow.commanding = false;
+                if let Err(e) = result {
+                    self.following = None;
+                    self.pass_status = Some(format!("Stopped following: {e}"));
+                    return Task::done(MainMessage::ErrorOccurred(e));
+                }
+                if follow.pending.is_some() {
+                    return follow.dispatch();
+                }
+                if follow.manager.state() == TrackingState::Finished {
+                    self.following = None;
+                    self.pass_status = Some("Pass over".to_owned());
+                }
+            }
         }
         Task::none()
     }
+
+    fn mount_state(&self) -> &'static str {
+        match (&self.slew, self.parked, self.tracking) {
+            (Some(_), _, _) => "SLEWING",
+            (None, Some(true), _) => "PARKED",
+            (None, _, Some(true)) => "TRACKING",
+            (None, _, _) => "IDLE",
+        }
+    }
+
+    /// What pass following is up to
+    fn pass_summary(&self) -> String {
+        let name = self
+            .armed_pass
+            .as_ref()
+            .map_or("", |pass| pass.name.as_str());
+        let Some(follow) = &self.following else {
+            return match (&self.pass_status, &self.armed_pass) {
+                (Some(status), _) => status.clone(),
+                (None, Some(pass)) => format!("Armed: {} ({})", pass.name, pass.norad_id),
+                (None, None) => "No pass armed".to_owned(),
+            };
+        };
+        let now = Utc::now();
+        match (follow.manager.state(), follow.manager.current(now)) {
+            (TrackingState::Tracking, Some(point)) => format!(
+                "Following {name} at {:.1}° altitude, {:.1}° azimuth",
+                point.altitude, point.azimuth
+            ),
+            (TrackingState::Paused, _) => {
+                format!("Paused until {name} is back above the horizon limit")
+            }
+            (TrackingState::Finished, _) => "Pass over".to_owned(),
+            _ => format!("Waiting for {name} to rise"),
+        }
+    }
+
+    /// Tracking on/off, the rate and custom rates
+    fn tracking_controls(&self) -> Element<Message> {
+        column![
+            text("Tracking Rate"),
+            row![
+                checkbox("Tracking", self.tracking == Some(true))
+                    .on_toggle(Message::TrackingToggled),
+                sidereal_picklist(
+                    TrackMode::ALL.to_vec(),
+                    self.track_mode,
+                    Message::TrackModeSelected
+                ),
+                text("RA (\"/s):"),
+                sidereal_text_input("15.041", &self.custom_ra_rate)
+                    .on_input(Message::CustomRaRateChanged)
+                    .width(Length::Fill),
+                text("DEC (\"/s):"),
+                sidereal_text_input("0", &self.custom_dec_rate)
+                    .on_input(Message::CustomDecRateChanged)
+                    .width(Length::Fill),
+                sidereal_button("Set Custom", Some(Message::ApplyCustomRate), true),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+            .width(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    /// Slew and abort buttons, with how far the slew has got
+    fn goto_controls(&self) -> Element<Message> {
+        let mut goto_button = track_button(
+            container(text("Slew to Target"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        let mut abort_button = stop_track_button(
+            container(text("Abort Slew"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        let Some(slew) = &self.slew else {
+            goto_button = goto_button.on_press(Message::Goto);
+            return row![goto_button, abort_button].spacing(10).into();
+        };
+        abort_button = abort_button.on_press(Message::AbortGoto);
+
+        let total = plate_solver::separation_deg(slew.from, slew.to);
+        let remaining = self.position.map_or(total, |position| {
+            plate_solver::separation_deg(position, slew.to)
+        });
+        let progress = if total > 0.0 {
+            (1.0 - remaining / total).clamp(0.0, 1.0) as f32
+        } else {
+            1.0
+        };
+        column![
+            row![goto_button, abort_button].spacing(10),
+            progress_bar(0.0..=1.0, progress).height(Length::Fixed(12.0)),
+            text(format!("{remaining:.1}° to go")),
+        ]
+        .spacing(5)
+        .into()
+    }
+
     pub fn view(&self) -> Element<Message> {
         let layout = row![
             column![
@@ -86,7 +691,17 @@ impl MountState {
                                     Space::with_width(Length::Fill),
                                     text("Mount State:"),
                                     content_container(
-                                        row![text("SLEWING")],
+                                        row![text(self.mount_state())],
+                                        ContainerLayer::Layer3
+                                    ),
+                                    Space::with_width(Length::Fill),
+                                    text("Track Rate:"),
+                                    content_container(
+                                        row![text(match (self.tracking, self.track_mode) {
+                                            (Some(false), _) => "OFF".to_owned(),
+                                            (_, Some(mode)) => mode.to_string().to_uppercase(),
+                                            (_, None) => "UNKNOWN".to_owned(),
+                                        })],
                                         ContainerLayer::Layer3
                                     ),
                                     Space::with_width(Length::Fill),
@@ -110,7 +725,8 @@ impl MountState {
                                         .width(Length::Fill)
                                     ],
                                     ContainerLayer::Layer3
-                                )
+                                ),
+                                content_container(self.tracking_controls(), ContainerLayer::Layer3),
                             ]
                             .spacing(10)
                             .padding([5, 1]),
@@ -128,22 +744,30 @@ impl MountState {
                             column![
                                 text("Tracking Settings"),
                                 row![
-                                    checkbox("Leapfrog Target", false).width(Length::Fixed(90.0)),
+                                    checkbox("Leapfrog Target", self.leapfrog)
+                                        .on_toggle(Message::LeapfrogToggled)
+                                        .width(Length::Fixed(90.0)),
                                     content_container(
                                         row![
-                                            text("Distance"),
-                                            sidereal_text_input("TEST", "TEST").width(Length::Fill)
+                                            text("Distance (s)"),
+                                            sidereal_text_input("seconds", &self.leapfrog_distance)
+                                                .on_input(Message::LeapfrogDistanceChanged)
+                                                .width(Length::Fill)
                                         ]
                                         .spacing(10)
                                         .align_y(Alignment::Center),
                                         ContainerLayer::Layer3
                                     )
                                     .width(Length::Fill),
-                                    checkbox("Pause at Horizon", false).width(Length::Fixed(90.0)),
+                                    checkbox("Pause at Horizon", self.pause_at_horizon)
+                                        .on_toggle(Message::PauseAtHorizonToggled)
+                                        .width(Length::Fixed(90.0)),
                                     content_container(
                                         row![
-                                            text("Distance"),
-                                            sidereal_text_input("TEST", "TEST").width(Length::Fill)
+                                            text("Distance (°)"),
+                                            sidereal_text_input("degrees", &self.horizon_distance)
+                                                .on_input(Message::HorizonDistanceChanged)
+                                                .width(Length::Fill)
                                         ]
                                         .spacing(10)
                                         .align_y(Alignment::Center),
@@ -152,18 +776,36 @@ impl MountState {
                                 ]
                                 .spacing(10)
                                 .align_y(Alignment::Center),
+                                checkbox("Follow with Track Rates", self.rate_tracking)
+                                    .on_toggle_maybe(
+                                        self.supports_track_rate
+                                            .then_some(Message::RateTrackingToggled)
+                                    ),
                                 content_container(
                                     column![
                                         text("Target"),
                                         row![
                                             text("RA:"),
-                                            sidereal_text_input("TEST", "TEST").width(Length::Fill),
+                                            sidereal_text_input(
+                                                "HH MM SS or hours",
+                                                &self.target_ra
+                                            )
+                                            .on_input(Message::TargetRaChanged)
+                                            .on_submit(Message::Goto)
+                                            .width(Length::Fill),
                                             text("DEC:"),
-                                            sidereal_text_input("TEST", "TEST").width(Length::Fill)
+                                            sidereal_text_input(
+                                                "±DD MM SS or degrees",
+                                                &self.target_dec
+                                            )
+                                            .on_input(Message::TargetDecChanged)
+                                            .on_submit(Message::Goto)
+                                            .width(Length::Fill)
                                         ]
                                         .align_y(Alignment::Center)
                                         .spacing(10)
-                                        .width(Length::Fill)
+                                        .width(Length::Fill),
+                                        self.goto_controls(),
                                     ]
                                     .spacing(10),
                                     ContainerLayer::Layer3
@@ -192,9 +834,10 @@ impl MountState {
                                     .align_y(Alignment::Center)
                             )
                             .width(Length::Fill)
-                            .on_press(Message::StartTracking),
+                            .on_press(Message::StopTracking),
                         ]
-                        .spacing(10)
+                        .spacing(10),
+                        text(self.pass_summary()),
                     ],
                     ContainerLayer::Layer1
                 )
@@ -269,22 +912,36 @@ impl MountState {
                     .spacing(3),
                     column![
                         text("Movement Speed"),
-                        slider(0.0..=1.0, 0.5, |_| Message::Noop).width(Length::Fill),
+                        slider(
+                            0.0..=self.slew_rates.len().saturating_sub(1) as f32,
+                            self.slew_rate as f32,
+                            Message::SlewRateChanged
+                        )
+                        .step(1.0)
+                        .on_release(Message::SlewRateReleased)
+                        .width(Length::Fill),
+                        text(
+                            self.slew_rates
+                                .get(self.slew_rate)
+                                .map_or("Unknown", String::as_str)
+                                .to_owned()
+                        )
+                        .size(12),
                         sidereal_button(
                             container(text("Park Scope"))
                                 .width(Length::Fill)
                                 .align_x(Alignment::Center)
                                 .align_y(Alignment::Center),
-                            None,
-                            true,
+                            Some(Message::Park),
+                            self.parked != Some(true),
                         ),
                         sidereal_button(
                             container(text("Unpark Scope"))
                                 .width(Length::Fill)
                                 .align_x(Alignment::Center)
                                 .align_y(Alignment::Center),
-                            None,
-                            true,
+                            Some(Message::Unpark),
+                            self.parked != Some(false),
                         )
                     ]
                     .spacing(10)
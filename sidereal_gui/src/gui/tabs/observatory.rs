@@ -1,13 +1,66 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
 use crate::app::Message as MainMessage;
+use crate::gui::styles;
 use crate::gui::styles::button_style::sidereal_button;
 use crate::gui::styles::container_style::{content_container, ContainerLayer};
 use crate::gui::widgets::indicator::{indicator, IndicatorColor};
 use crate::indi_handler::roof_controller;
-use crate::model::SiderealResult;
-use iced::widget::{column, row, text, Space};
+use crate::model::{SiderealError, SiderealResult};
+use iced::widget::{column, progress_bar, row, text, Space};
 use iced::{Alignment, Element, Length, Task};
 
 const BUTTON_WIDTH: f32 = 120.0;
+/// How long the roof can sit still part way through a move before it's
+/// taken to have stalled
+const STALL_TIMEOUT: Duration = Duration::from_secs(10);
+/// Smallest change in position, in percent, that counts as moving
+const MIN_MOVEMENT: f64 = 0.5;
+
+/// What the roof is doing, from the controller's ROOF_STATE
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoofState {
+    Opening,
+    Open,
+    Closing,
+    Closed,
+}
+
+impl RoofState {
+    pub fn from_telemetry(state: u8) -> Option<Self> {
+        match state {
+            1 => Some(RoofState::Opening),
+            2 => Some(RoofState::Open),
+            3 => Some(RoofState::Closing),
+            4 => Some(RoofState::Closed),
+            _ => None,
+        }
+    }
+
+    fn is_moving(self) -> bool {
+        matches!(self, RoofState::Opening | RoofState::Closing)
+    }
+}
+
+impl fmt::Display for RoofState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RoofState::Opening => "Opening",
+            RoofState::Open => "Open",
+            RoofState::Closing => "Closing",
+            RoofState::Closed => "Closed",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Where the roof last moved to during the move under way, and when
+struct Movement {
+    state: RoofState,
+    position: f64,
+    since: Instant,
+}
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -16,7 +69,10 @@ pub enum Message {
         is_armed: bool,
         roof_is_open: bool,
         roof_is_closed: bool,
+        /// Percent open
         roof_position: f64,
+        /// `None` when stopped part way, or the controller didn't say
+        roof_state: Option<RoofState>,
         lock_engaged: bool,
         voltage_5v: f64,
         voltage_12v: f64,
@@ -25,6 +81,8 @@ pub enum Message {
         limit_u2: bool,
         limit_l1: bool,
         limit_l2: bool,
+        /// The rain/cloud sensor is tripped
+        raining: bool,
     },
     ArmSystem,
     DisarmSystem,
@@ -42,6 +100,10 @@ pub struct ObservatoryState {
     roof_is_open: bool,
     roof_is_closed: bool,
     roof_position: f64,
+    roof_state: Option<RoofState>,
+    movement: Option<Movement>,
+    /// Whether the move under way has stopped making progress
+    stalled: bool,
     lock_engaged: bool,
     voltage_5v: f64,
     voltage_12v: f64,
@@ -50,6 +112,7 @@ pub struct ObservatoryState {
     limit_u2: bool,
     limit_l1: bool,
     limit_l2: bool,
+    raining: bool,
 }
 
 impl ObservatoryState {
@@ -60,6 +123,7 @@ impl ObservatoryState {
                 roof_is_open,
                 roof_is_closed,
                 roof_position,
+                roof_state,
                 lock_engaged,
                 voltage_5v,
                 voltage_12v,
@@ -68,11 +132,13 @@ impl ObservatoryState {
                 limit_u2,
                 limit_l1,
                 limit_l2,
+                raining,
             } => {
                 self.is_armed = is_armed;
                 self.roof_is_open = roof_is_open;
                 self.roof_is_closed = roof_is_closed;
                 self.roof_position = roof_position;
+                self.roof_state = roof_state;
                 self.lock_engaged = lock_engaged;
                 self.voltage_5v = voltage_5v;
                 self.voltage_12v = voltage_12v;
@@ -81,7 +147,8 @@ impl ObservatoryState {
                 self.limit_u2 = limit_u2;
                 self.limit_l1 = limit_l1;
                 self.limit_l2 = limit_l2;
-                Task::none()
+                self.raining = raining;
+                self.watch_movement()
             }
             Message::ArmSystem => Task::perform(
                 async { roof_controller::arm_system().await },
@@ -167,6 +234,57 @@ impl ObservatoryState {
         }
     }
 
+    /// Notice the roof stalling part way through a move
+    fn watch_movement(&mut self) -> Task<MainMessage> {
+        let Some(state) = self.roof_state.filter(|state| state.is_moving()) else {
+            self.movement = None;
+            self.stalled = false;
+            return Task::none();
+        };
+        let position = self.roof_position;
+        let still_since = self.movement.as_ref().and_then(|movement| {
+            (movement.state == state && (position - movement.position).abs() < MIN_MOVEMENT)
+                .then_some(movement.since)
+        });
+        let Some(since) = still_since else {
+            self.movement = Some(Movement {
+                state,
+                position,
+                since: Instant::now(),
+            });
+            self.stalled = false;
+            return Task::none();
+        };
+        if self.stalled || since.elapsed() < STALL_TIMEOUT {
+            return Task::none();
+        }
+        self.stalled = true;
+        Task::done(MainMessage::ErrorOccurred(SiderealError::ServerError(
+            format!(
+                "The roof stalled while {}: it hasn't moved from {position:.0}% in {}s",
+                state.to_string().to_lowercase(),
+                STALL_TIMEOUT.as_secs()
+            ),
+        )))
+    }
+
+    fn motion_view(&self) -> Element<'static, Message> {
+        let state = match (self.stalled, self.roof_state) {
+            (true, _) => text("Stalled").color(styles::RED_TEXT),
+            (false, Some(state)) => text(state.to_string()),
+            (false, None) => text("Stopped"),
+        };
+        row![
+            state.width(Length::Fixed(BUTTON_WIDTH)),
+            progress_bar(0.0..=100.0, self.roof_position.clamp(0.0, 100.0) as f32)
+                .height(Length::Fixed(12.0)),
+            text(format!("{:.0}%", self.roof_position)),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10)
+        .into()
+    }
+
     pub fn view(&self) -> Element<'static, Message> {
         let buttons_enabled = self.is_armed;
 
@@ -239,19 +357,29 @@ impl ObservatoryState {
                     ContainerLayer::Layer2
                 ),
                 content_container(
-                    row![
-                        open_roof_btn,
-                        stop_roof_btn,
-                        close_roof_btn,
-                        Space::with_width(Length::Fill),
-                        text("Roof Open:"),
-                        indicator(if self.roof_is_open {
-                            IndicatorColor::Green
-                        } else {
-                            IndicatorColor::Red
-                        }),
+                    column![
+                        row![
+                            open_roof_btn,
+                            stop_roof_btn,
+                            close_roof_btn,
+                            Space::with_width(Length::Fill),
+                            text("Rain:"),
+                            indicator(if self.raining {
+                                IndicatorColor::Red
+                            } else {
+                                IndicatorColor::Green
+                            }),
+                            text("Roof Open:"),
+                            indicator(if self.roof_is_open {
+                                IndicatorColor::Green
+                            } else {
+                                IndicatorColor::Red
+                            }),
+                        ]
+                        .align_y(Alignment::Center)
+                        .spacing(10),
+                        self.motion_view(),
                     ]
-                    .align_y(Alignment::Center)
                     .spacing(10),
                     ContainerLayer::Layer2
                 ),
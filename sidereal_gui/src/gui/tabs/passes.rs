@@ -0,0 +1,391 @@
+use chrono::{DateTime, Local, Utc};
+use iced::widget::{checkbox, column, container, row, text, Column, Space};
+use iced::{task, Alignment, Element, Length, Task};
+use overpass_planner::{
+    find_overpasses, tle, ObserverLocation, Overpass, PassCriteria, PassKind, PropagationContext,
+    Propagator,
+};
+use protos::protos::PassTrack;
+
+use crate::app::Message as MainMessage;
+use crate::config::Config;
+use crate::gui::styles::button_style::{sidereal_button, stop_track_button, track_button};
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::model::tracking_manager;
+use crate::model::{SiderealError, SiderealResult};
+
+/// Most satellites a group search plans for, so it finishes in reasonable time
+const MAX_SATELLITES: usize = 50;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SatelliteChanged(String),
+    HoursChanged(String),
+    MinElevationChanged(String),
+    NightOnlyToggled(bool),
+    Search,
+    Abort,
+    Found(SiderealResult<Vec<PlannedPass>>),
+    /// Hand the pass at this index to the mount to follow
+    TrackPass(usize),
+}
+
+/// An upcoming pass and what the mount needs to follow it
+#[derive(Debug, Clone)]
+pub struct PlannedPass {
+    norad_id: u32,
+    name: String,
+    tle: String,
+    overpass: Overpass,
+    /// Where the satellite rises (RA hours, Dec degrees, of date)
+    rise: (f64, f64),
+}
+
+impl PlannedPass {
+    fn track(&self) -> PassTrack {
+        let timestamp = |time: DateTime<Utc>| prost_types::Timestamp {
+            seconds: time.timestamp(),
+            nanos: time.timestamp_subsec_nanos() as i32,
+        };
+        PassTrack {
+            norad_id: self.norad_id,
+            name: self.name.clone(),
+            tle: self.tle.clone(),
+            start: Some(timestamp(self.overpass.start_time)),
+            end: Some(timestamp(self.overpass.end_time)),
+            max_elevation_degrees: self.overpass.max_elevation as f32,
+            rise_ra_hours: self.rise.0 as f32,
+            rise_dec_degrees: self.rise.1 as f32,
+        }
+    }
+}
+
+pub struct PassesState {
+    /// NORAD ID, or the start of the names of a group of satellites
+    satellite: String,
+    /// How far ahead to look, as typed
+    hours: String,
+    min_elevation: String,
+    night_only: bool,
+    /// The search in progress, to drop it on abort
+    searching: Option<task::Handle>,
+    passes: Vec<PlannedPass>,
+    /// Why the last search failed, or what it found
+    status: Option<String>,
+}
+
+impl Default for PassesState {
+    fn default() -> Self {
+        Self {
+            satellite: String::new(),
+            hours: "24".to_owned(),
+            min_elevation: "20".to_owned(),
+            night_only: true,
+            searching: None,
+            passes: vec![],
+            status: None,
+        }
+    }
+}
+
+/// Every pass of `satellites` (NORAD ID and TLE) over `location` in the next
+/// `hours` that meets `criteria`, soonest first
+fn plan_passes(
+    satellites: Vec<(u32, String)>,
+    location: ObserverLocation,
+    hours: f64,
+    criteria: PassCriteria,
+) -> SiderealResult<Vec<PlannedPass>> {
+    let start = Utc::now();
+    let end = start + chrono::Duration::seconds((hours * 3600.0) as i64);
+    let mut passes = vec![];
+    let mut failure = None;
+    for (norad_id, tle) in &satellites {
+        let plan = || -> overpass_planner::OverpassPlannerResult<Vec<PlannedPass>> {
+            let ctx = PropagationContext::from_tle(tle)?;
+            ctx.check_not_decayed(start)?;
+            // the name line, when the elements come with one
+            let name = tle
+                .lines()
+                .next()
+                .map(str::trim)
+                .filter(|line| !line.starts_with("1 "))
+                .map_or_else(|| format!("NORAD {norad_id}"), str::to_owned);
+            find_overpasses(&ctx, location, start, end)?
+                .into_iter()
+                .filter(|overpass| overpass.kind == PassKind::Normal && criteria.matches(overpass))
+                .map(|overpass| {
+                    let (altitude, azimuth) = ctx.alt_az(location, overpass.start_time)?;
+                    let rise = tracking_manager::equatorial_from_horizontal(
+                        overpass.start_time,
+                        location,
+                        altitude,
+                        azimuth,
+                    );
+                    Ok(PlannedPass {
+                        norad_id: *norad_id,
+                        name: name.clone(),
+                        tle: tle.clone(),
+                        overpass,
+                        rise,
+                    })
+                })
+                .collect()
+        };
+        // decayed or malformed elements are left out of a group
+        match plan() {
+            Ok(found) => passes.extend(found),
+            Err(e) => failure = failure.or(Some(e)),
+        }
+    }
+    if let Some(e) = failure.filter(|_| passes.is_empty()) {
+        return Err(SiderealError::ServerError(format!(
+            "Can't plan passes: {e}"
+        )));
+    }
+    passes.sort_by_key(|pass| pass.overpass.start_time);
+    Ok(passes)
+}
+
+impl PassesState {
+    fn search(&mut self) -> Task<MainMessage> {
+        if self.searching.is_some() {
+            return Task::none();
+        }
+        let parse = |what: &str, value: &str| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && *v >= 0.0)
+                .ok_or_else(|| SiderealError::ParseError(format!("Invalid {what} `{value}`")))
+        };
+        let query = self.satellite.trim().to_owned();
+        let inputs = parse("search window", &self.hours)
+            .and_then(|hours| Ok((hours, parse("minimum elevation", &self.min_elevation)?)));
+        let (hours, min_elevation) = match inputs {
+            Ok(_) if query.is_empty() => {
+                return Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                    "Enter a NORAD ID or the name of a group of satellites".to_owned(),
+                )))
+            }
+            Ok(inputs) => inputs,
+            Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+        };
+        let criteria = PassCriteria {
+            min_elevation,
+            require_night: self.night_only,
+            require_lit: false,
+        };
+
+        self.status = None;
+        self.passes.clear();
+        let (task, handle) = Task::perform(
+            async move {
+                let tle_error = |e: overpass_planner::OverpassPlannerError| {
+                    SiderealError::ServerError(format!("Can't get elements for {query}: {e}"))
+                };
+                let satellites = match query.parse::<u32>() {
+                    Ok(norad_id) => {
+                        let tle = tle::fetch_tle(norad_id).await.map_err(tle_error)?;
+                        vec![(norad_id, tle)]
+                    }
+                    Err(_) => tle::find_satellites(&query).await.map_err(tle_error)?,
+                };
+                if satellites.is_empty() {
+                    return Err(SiderealError::ParseError(format!(
+                        "No satellites named {query}..."
+                    )));
+                }
+                if satellites.len() > MAX_SATELLITES {
+                    return Err(SiderealError::ParseError(format!(
+                        "{} satellites named {query}..., more than {MAX_SATELLITES}; \
+                         narrow the name down",
+                        satellites.len()
+                    )));
+                }
+                let location = Config::get().await.location;
+                let location = ObserverLocation {
+                    latitude: location.latitude.into(),
+                    longitude: location.longitude.into(),
+                    altitude: location.altitude.into(),
+                };
+                tokio::task::spawn_blocking(move || {
+                    plan_passes(satellites, location, hours, criteria)
+                })
+                .await
+                .map_err(|e| SiderealError::ServerError(format!("Pass search failed: {e}")))?
+            },
+            |result| MainMessage::Passes(Message::Found(result)),
+        )
+        .abortable();
+        self.searching = Some(handle);
+        task
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::SatelliteChanged(satellite) => self.satellite = satellite,
+            Message::HoursChanged(hours) => self.hours = hours,
+            Message::MinElevationChanged(min_elevation) => self.min_elevation = min_elevation,
+            Message::NightOnlyToggled(night_only) => self.night_only = night_only,
+            Message::Search => return self.search(),
+            Message::Abort => {
+                if let Some(handle) = self.searching.take() {
+                    handle.abort();
+                    self.status = Some("Aborted".to_owned());
+                }
+            }
+            Message::Found(result) => {
+                self.searching = None;
+                match result {
+                    Ok(passes) => {
+                        self.status = passes
+                            .is_empty()
+                            .then(|| "No passes meet the filters".to_owned());
+                        self.passes = passes;
+                    }
+                    Err(e) => {
+                        self.status = Some(e.to_string());
+                        return Task::done(MainMessage::ErrorOccurred(e));
+                    }
+                }
+            }
+            Message::TrackPass(index) => {
+                if let Some(pass) = self.passes.get(index) {
+                    return Task::done(MainMessage::TrackPass(pass.track()));
+                }
+            }
+        }
+        Task::none()
+    }
+
+    fn pass_row(index: usize, pass: &PlannedPass) -> Element<'_, Message> {
+        let overpass = &pass.overpass;
+        let local = |time: DateTime<Utc>| time.with_timezone(&Local);
+        let minutes = (overpass.end_time - overpass.start_time).num_seconds() as f64 / 60.0;
+        let mut conditions = vec![];
+        if overpass.is_night {
+            conditions.push("night");
+        }
+        if overpass.is_lit {
+            conditions.push("sunlit");
+        }
+        let upcoming = overpass.start_time > Utc::now();
+        content_container(
+            row![
+                column![
+                    text(format!("{} ({})", pass.name, pass.norad_id)),
+                    text(format!(
+                        "{} – {}, {:.1} min",
+                        local(overpass.start_time).format("%a %b %d %H:%M:%S"),
+                        local(overpass.end_time).format("%H:%M:%S"),
+                        minutes
+                    ))
+                    .size(12),
+                ]
+                .spacing(2),
+                Space::with_width(Length::Fill),
+                text(format!("{:.0}° max", overpass.max_elevation)),
+                text(conditions.join(", ")).width(Length::Fixed(90.0)),
+                sidereal_button("Track this pass", Some(Message::TrackPass(index)), upcoming),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            ContainerLayer::Layer2,
+        )
+        .width(Length::Fill)
+        .into()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let idle = self.searching.is_none();
+        let field =
+            |label: &str, placeholder: &str, value: &str, on_input: fn(String) -> Message| {
+                row![
+                    text(label.to_owned()).width(Length::Fixed(150.0)),
+                    sidereal_text_input(placeholder, value)
+                        .on_input(on_input)
+                        .on_submit(Message::Search)
+                        .width(Length::Fill)
+                ]
+                .align_y(Alignment::Center)
+                .spacing(10)
+            };
+
+        let mut search_button = track_button(
+            container(text("Find Passes"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if idle {
+            search_button = search_button.on_press(Message::Search);
+        }
+        let mut abort_button = stop_track_button(
+            container(text("Abort"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if !idle {
+            abort_button = abort_button.on_press(Message::Abort);
+        }
+
+        let search = content_container(
+            column![
+                text("Pass Search"),
+                field(
+                    "Satellite",
+                    "NORAD ID or group name, e.g. 25544 or STARLINK",
+                    &self.satellite,
+                    Message::SatelliteChanged
+                ),
+                field(
+                    "Look ahead (h)",
+                    "hours",
+                    &self.hours,
+                    Message::HoursChanged
+                ),
+                field(
+                    "Min elevation (°)",
+                    "degrees",
+                    &self.min_elevation,
+                    Message::MinElevationChanged
+                ),
+                checkbox("Night passes only", self.night_only).on_toggle(Message::NightOnlyToggled),
+                row![search_button, abort_button].spacing(10),
+            ]
+            .spacing(10),
+            ContainerLayer::Layer1,
+        )
+        .width(Length::Fill);
+
+        let status = match (&self.searching, &self.status) {
+            (Some(_), _) => "Searching...".to_owned(),
+            (None, Some(status)) => status.clone(),
+            (None, None) => format!("{} passes", self.passes.len()),
+        };
+        let passes = self
+            .passes
+            .iter()
+            .enumerate()
+            .fold(Column::new().spacing(5), |list, (index, pass)| {
+                list.push(Self::pass_row(index, pass))
+            });
+
+        column![
+            search,
+            content_container(
+                column![text("Upcoming Passes"), text(status), passes].spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
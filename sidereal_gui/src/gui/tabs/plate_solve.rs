@@ -1,15 +1,619 @@
-use iced::{Element, Length};
-use iced::widget::text;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
+use chrono::Utc;
+use iced::widget::{checkbox, column, container, row, text};
+use iced::{task, Alignment, Element, Length, Task};
+use overpass_planner::precession;
+
+use crate::app::Message as MainMessage;
+use crate::config::{Config, PlateSolveConfig, SolverType};
+use crate::fits;
+use crate::gui::styles::button_style::{sidereal_button, stop_track_button, track_button};
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::picklist_style::sidereal_picklist;
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::indi_handler::{camera, mount};
+use crate::model::{SiderealError, SiderealResult};
+use crate::plate_solver::{self, Solution};
+
+#[derive(Debug, Clone, Copy)]
+pub enum SolveField {
+    SolverPath,
+    PixelScale,
+    SearchRadius,
+    Timeout,
+    Exposure,
+    CenterTolerance,
+    CenterAttempts,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    SolverSelected(SolverType),
+    FieldChanged {
+        field: SolveField,
+        value: String,
+    },
+    SaveSettings,
+    BlindToggled(bool),
+    /// A frame the Capture tab saved, to solve on request
+    FrameSaved(PathBuf),
+    SolveLatest,
+    CaptureAndSolve,
+    Abort,
+    Solved(SiderealResult<SolvedFrame>),
+    SyncMount,
+    Synced(SiderealResult<()>),
+    /// Slew back to where the mount was pointing for the last solve, re-solve
+    /// and repeat until the frame is centred there
+    Center,
+    CenterStepDone(SiderealResult<SolvedFrame>),
+}
+
+/// Longest a centering slew may take
+const SLEW_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// A frame and where it was pointing
 #[derive(Debug, Clone)]
-pub enum Message {}
+pub struct SolvedFrame {
+    path: PathBuf,
+    solution: Solution,
+    /// Whether the whole sky was searched, rather than around the mount
+    blind: bool,
+    took: Duration,
+    /// Where the mount said it was pointing (RA hours, Dec degrees, of date)
+    pointing: Option<(f64, f64)>,
+}
+
+impl SolvedFrame {
+    /// How far (arcminutes) the frame's centre is from `target` (RA hours,
+    /// Dec degrees, of date)
+    fn error_arcmin(&self, target: (f64, f64)) -> f64 {
+        let solution = &self.solution;
+        let centre = precession::to_date(solution.ra_hours, solution.dec_deg, Utc::now());
+        plate_solver::separation_deg(centre, target) * 60.0
+    }
+}
+
+/// Closed-loop centering in progress
+#[derive(Debug, Clone, Copy)]
+struct Centering {
+    /// RA hours, Dec degrees, of date
+    target: (f64, f64),
+    slews: u32,
+}
+
+/// Solver settings, as typed
+#[derive(Debug, Clone)]
+struct SolveInput {
+    solver: SolverType,
+    astap_path: String,
+    solve_field_path: String,
+    pixel_scale: String,
+    search_radius: String,
+    timeout: String,
+    exposure: String,
+    center_tolerance: String,
+    center_attempts: String,
+}
+
+impl From<&PlateSolveConfig> for SolveInput {
+    fn from(config: &PlateSolveConfig) -> Self {
+        Self {
+            solver: config.solver,
+            astap_path: config.astap_path.display().to_string(),
+            solve_field_path: config.solve_field_path.display().to_string(),
+            pixel_scale: config.pixel_scale_arcsec.to_string(),
+            search_radius: config.search_radius_deg.to_string(),
+            timeout: config.timeout_secs.to_string(),
+            exposure: config.exposure_secs.to_string(),
+            center_tolerance: config.center_tolerance_arcmin.to_string(),
+            center_attempts: config.center_attempts.to_string(),
+        }
+    }
+}
+
+impl SolveInput {
+    fn parse(&self) -> SiderealResult<PlateSolveConfig> {
+        let number = |what: &str, value: &str, valid: &dyn Fn(f64) -> bool| {
+            value
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|v| v.is_finite() && valid(*v))
+                .ok_or_else(|| SiderealError::ParseError(format!("Invalid {what} `{value}`")))
+        };
+        let path = |what: &str, value: &str| {
+            Some(value.trim())
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+                .ok_or_else(|| SiderealError::ParseError(format!("The {what} can't be blank")))
+        };
+        Ok(PlateSolveConfig {
+            solver: self.solver,
+            astap_path: path("ASTAP path", &self.astap_path)?,
+            solve_field_path: path("solve-field path", &self.solve_field_path)?,
+            pixel_scale_arcsec: number("pixel scale", &self.pixel_scale, &|v| v >= 0.0)?,
+            search_radius_deg: number("search radius", &self.search_radius, &|v| {
+                v > 0.0 && v <= 180.0
+            })?,
+            timeout_secs: number("timeout", &self.timeout, &|v| v >= 1.0)?,
+            exposure_secs: number("exposure", &self.exposure, &|v| v > 0.0)?,
+            center_tolerance_arcmin: number("centering tolerance", &self.center_tolerance, &|v| {
+                v > 0.0
+            })?,
+            center_attempts: self
+                .center_attempts
+                .trim()
+                .parse::<u32>()
+                .ok()
+                .filter(|attempts| *attempts > 0)
+                .ok_or_else(|| {
+                    SiderealError::ParseError(format!(
+                        "Invalid number of centering slews `{}`",
+                        self.center_attempts
+                    ))
+                })?,
+        })
+    }
+
+    /// The path of the solver in use
+    fn solver_path(&mut self) -> &mut String {
+        match self.solver {
+            SolverType::Astap => &mut self.astap_path,
+            SolverType::AstrometryNet => &mut self.solve_field_path,
+        }
+    }
+}
+
+/// Take a frame with the main camera and keep it where the solver can read it
+async fn capture_frame(exposure_secs: f64) -> SiderealResult<PathBuf> {
+    let data = camera::expose(exposure_secs).await?;
+    let path = std::env::temp_dir().join("sidereal_solve.fits");
+    tokio::fs::write(&path, data.as_slice())
+        .await
+        .map_err(|e| SiderealError::FileError(format!("{}: {e}", path.display())))?;
+    Ok(path)
+}
+
+/// Solve `path` around where the mount is pointing, or across the whole sky
+/// if `blind` or the mount's position isn't known
+async fn solve_frame(
+    path: PathBuf,
+    blind: bool,
+    config: PlateSolveConfig,
+) -> SiderealResult<SolvedFrame> {
+    let started = Instant::now();
+    let pointing = mount::position().await;
+    let hint = pointing.filter(|_| !blind);
+    let solution = plate_solver::solve(&path, hint, &config).await?;
+    Ok(SolvedFrame {
+        path,
+        solution,
+        blind: hint.is_none(),
+        took: started.elapsed(),
+        pointing,
+    })
+}
 
-#[derive(Default)]
-pub struct PlateSolveState;
+/// One round of centering: tell the mount where it's really pointing, slew
+/// back to `target` and solve a fresh frame there
+async fn center_step(
+    solution: Solution,
+    target: (f64, f64),
+    config: PlateSolveConfig,
+) -> SiderealResult<SolvedFrame> {
+    let (ra_hours, dec_deg) = precession::to_date(solution.ra_hours, solution.dec_deg, Utc::now());
+    mount::sync_to(ra_hours, dec_deg).await?;
+    mount::slew_to(target.0, target.1).await?;
+    mount::wait_for_slew(SLEW_TIMEOUT).await?;
+    let path = capture_frame(config.exposure_secs).await?;
+    solve_frame(path, false, config).await
+}
+
+pub struct PlateSolveState {
+    input: SolveInput,
+    blind: bool,
+    /// The last frame the Capture tab saved
+    latest_capture: Option<PathBuf>,
+    /// The solve in progress, to drop it on abort
+    solving: Option<task::Handle>,
+    /// What's being done, while solving
+    activity: &'static str,
+    last_solve: Option<SolvedFrame>,
+    centering: Option<Centering>,
+    /// Why the last solve failed, or that it was aborted
+    status: Option<String>,
+}
+
+impl Default for PlateSolveState {
+    fn default() -> Self {
+        Self {
+            input: SolveInput::from(&PlateSolveConfig::default()),
+            blind: false,
+            latest_capture: None,
+            solving: None,
+            activity: "",
+            last_solve: None,
+            centering: None,
+            status: None,
+        }
+    }
+}
 
 impl PlateSolveState {
-    pub fn update(&mut self, _message: Message) {}
-    pub fn view(&self) -> Element<'static, Message> {
-        text("Setup tab").width(Length::Fill).into()
+    pub fn on_config_load(&mut self, config: &Config) {
+        self.input = SolveInput::from(&config.plate_solve);
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::SolverSelected(solver) => self.input.solver = solver,
+            Message::FieldChanged { field, value } => {
+                let input = &mut self.input;
+                match field {
+                    SolveField::SolverPath => *input.solver_path() = value,
+                    SolveField::PixelScale => input.pixel_scale = value,
+                    SolveField::SearchRadius => input.search_radius = value,
+                    SolveField::Timeout => input.timeout = value,
+                    SolveField::Exposure => input.exposure = value,
+                    SolveField::CenterTolerance => input.center_tolerance = value,
+                    SolveField::CenterAttempts => input.center_attempts = value,
+                }
+            }
+            Message::SaveSettings => {
+                return match self.input.parse() {
+                    Ok(config) => {
+                        Task::perform(Config::set_plate_solve(config), |result| match result {
+                            Ok(()) => MainMessage::Noop,
+                            Err(e) => MainMessage::ErrorOccurred(e),
+                        })
+                    }
+                    Err(e) => Task::done(MainMessage::ErrorOccurred(e)),
+                };
+            }
+            Message::BlindToggled(blind) => self.blind = blind,
+            Message::FrameSaved(path) => self.latest_capture = Some(path),
+            Message::SolveLatest => {
+                let Some(path) = self.latest_capture.clone() else {
+                    return Task::none();
+                };
+                return self.start(
+                    "Solving",
+                    Message::Solved,
+                    move |config, blind| async move { solve_frame(path, blind, config).await },
+                );
+            }
+            Message::CaptureAndSolve => {
+                return self.start(
+                    "Exposing and solving",
+                    Message::Solved,
+                    |config, blind| async move {
+                        let path = capture_frame(config.exposure_secs).await?;
+                        solve_frame(path, blind, config).await
+                    },
+                );
+            }
+            Message::Abort => {
+                if let Some(handle) = self.solving.take() {
+                    handle.abort();
+                    self.centering = None;
+                    self.status = Some("Aborted".to_owned());
+                }
+            }
+            Message::Solved(result) => {
+                self.solving = None;
+                match result {
+                    Ok(solved) => {
+                        self.status = None;
+                        self.last_solve = Some(solved);
+                    }
+                    Err(e) => {
+                        self.status = Some(format!("Failed: {e}"));
+                        return Task::done(MainMessage::ErrorOccurred(e));
+                    }
+                }
+            }
+            Message::SyncMount => {
+                let Some(solved) = &self.last_solve else {
+                    return Task::none();
+                };
+                let solution = solved.solution;
+                let (ra_hours, dec_deg) =
+                    precession::to_date(solution.ra_hours, solution.dec_deg, Utc::now());
+                self.status = Some("Syncing".to_owned());
+                return Task::perform(mount::sync_to(ra_hours, dec_deg), |result| {
+                    MainMessage::PlateSolve(Message::Synced(result))
+                });
+            }
+            Message::Synced(result) => match result {
+                Ok(()) => self.status = Some("Mount synced to the solve".to_owned()),
+                Err(e) => {
+                    self.status = Some(format!("Sync failed: {e}"));
+                    return Task::done(MainMessage::ErrorOccurred(e));
+                }
+            },
+            Message::Center => {
+                let Some((solution, target)) = self
+                    .last_solve
+                    .as_ref()
+                    .and_then(|solved| Some((solved.solution, solved.pointing?)))
+                else {
+                    return Task::none();
+                };
+                let task = self.start("Centering", Message::CenterStepDone, move |config, _| {
+                    center_step(solution, target, config)
+                });
+                if self.solving.is_some() {
+                    self.centering = Some(Centering { target, slews: 1 });
+                }
+                return task;
+            }
+            Message::CenterStepDone(result) => {
+                self.solving = None;
+                let Some(mut centering) = self.centering.take() else {
+                    return Task::none();
+                };
+                let solved = match result {
+                    Ok(solved) => solved,
+                    Err(e) => {
+                        self.status = Some(format!("Centering failed: {e}"));
+                        return Task::done(MainMessage::ErrorOccurred(e));
+                    }
+                };
+                let error = solved.error_arcmin(centering.target);
+                let solution = solved.solution;
+                self.last_solve = Some(solved);
+                let config = match self.input.parse() {
+                    Ok(config) => config,
+                    Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+                };
+                if error <= config.center_tolerance_arcmin {
+                    self.status = Some(format!(
+                        "Centred to {error:.1}' after {} slew{}",
+                        centering.slews,
+                        if centering.slews == 1 { "" } else { "s" }
+                    ));
+                    return Task::none();
+                }
+                if centering.slews >= config.center_attempts {
+                    let e = SiderealError::ServerError(format!(
+                        "Still {error:.1}' off target after {} slews",
+                        centering.slews
+                    ));
+                    self.status = Some(format!("Centering failed: {e}"));
+                    return Task::done(MainMessage::ErrorOccurred(e));
+                }
+                centering.slews += 1;
+                let target = centering.target;
+                let task = self.start("Centering", Message::CenterStepDone, move |config, _| {
+                    center_step(solution, target, config)
+                });
+                self.centering = Some(centering);
+                return task;
+            }
+        }
+        Task::none()
+    }
+
+    /// Run `solve` with the settings as typed, unless a solve is already
+    /// running, reporting how it went with `done`
+    fn start<F, Fut>(
+        &mut self,
+        activity: &'static str,
+        done: fn(SiderealResult<SolvedFrame>) -> Message,
+        solve: F,
+    ) -> Task<MainMessage>
+    where
+        F: FnOnce(PlateSolveConfig, bool) -> Fut,
+        Fut: std::future::Future<Output = SiderealResult<SolvedFrame>> + Send + 'static,
+    {
+        if self.solving.is_some() {
+            return Task::none();
+        }
+        let config = match self.input.parse() {
+            Ok(config) => config,
+            Err(e) => return Task::done(MainMessage::ErrorOccurred(e)),
+        };
+        self.status = None;
+        self.activity = activity;
+        let (task, handle) = Task::perform(solve(config, self.blind), move |result| {
+            MainMessage::PlateSolve(done(result))
+        })
+        .abortable();
+        self.solving = Some(handle);
+        task
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let idle = self.solving.is_none();
+        let setting = |label: &str, placeholder: &str, value: &str, field: SolveField| {
+            row![
+                text(label.to_owned()).width(Length::Fixed(150.0)),
+                sidereal_text_input(placeholder, value)
+                    .on_input(move |value| Message::FieldChanged { field, value })
+                    .width(Length::Fill)
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+        };
+        let input = &self.input;
+        let solver_path = match input.solver {
+            SolverType::Astap => &input.astap_path,
+            SolverType::AstrometryNet => &input.solve_field_path,
+        };
+
+        let settings = content_container(
+            column![
+                text("Solver"),
+                row![
+                    text("Solver").width(Length::Fixed(150.0)),
+                    sidereal_picklist(
+                        vec![SolverType::Astap, SolverType::AstrometryNet],
+                        Some(input.solver),
+                        Message::SolverSelected,
+                    ),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(10),
+                setting("Program", "path", solver_path, SolveField::SolverPath),
+                setting(
+                    "Pixel scale (\"/px)",
+                    "0 for unknown",
+                    &input.pixel_scale,
+                    SolveField::PixelScale
+                ),
+                setting(
+                    "Search radius (°)",
+                    "degrees",
+                    &input.search_radius,
+                    SolveField::SearchRadius
+                ),
+                setting(
+                    "Timeout (s)",
+                    "seconds",
+                    &input.timeout,
+                    SolveField::Timeout
+                ),
+                setting(
+                    "Exposure (s)",
+                    "seconds",
+                    &input.exposure,
+                    SolveField::Exposure
+                ),
+                setting(
+                    "Centred within (')",
+                    "arcminutes",
+                    &input.center_tolerance,
+                    SolveField::CenterTolerance
+                ),
+                setting(
+                    "Centering slews",
+                    "attempts",
+                    &input.center_attempts,
+                    SolveField::CenterAttempts
+                ),
+                sidereal_button("Apply", Some(Message::SaveSettings), true).width(Length::Fill),
+            ]
+            .spacing(10),
+            ContainerLayer::Layer1,
+        )
+        .width(Length::Fill);
+
+        let mut capture_button = track_button(
+            container(text("Capture & Solve"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if idle {
+            capture_button = capture_button.on_press(Message::CaptureAndSolve);
+        }
+        let mut abort_button = stop_track_button(
+            container(text("Abort"))
+                .width(Length::Fill)
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Center),
+        )
+        .width(Length::Fill);
+        if !idle {
+            abort_button = abort_button.on_press(Message::Abort);
+        }
+        let latest = match &self.latest_capture {
+            Some(path) => format!("Latest capture: {}", path.display()),
+            None => "No captures yet".to_owned(),
+        };
+        let status = match (&self.solving, &self.status) {
+            (Some(_), _) => format!("{}...", self.activity),
+            (None, Some(status)) => status.clone(),
+            (None, None) => "Idle".to_owned(),
+        };
+
+        let mut actions = column![
+            text("Solve"),
+            checkbox("Blind solve (search the whole sky)", self.blind)
+                .on_toggle(Message::BlindToggled),
+            row![
+                sidereal_button(
+                    "Solve Latest Capture",
+                    Some(Message::SolveLatest),
+                    idle && self.latest_capture.is_some()
+                )
+                .width(Length::Fill),
+                capture_button,
+                abort_button,
+            ]
+            .spacing(10),
+            text(latest).size(12),
+            text(status),
+        ]
+        .spacing(10);
+
+        if let Some(solved) = &self.last_solve {
+            let solution = &solved.solution;
+            let reading = |label: &str, value: String| {
+                row![
+                    text(label.to_owned()).width(Length::Fixed(150.0)),
+                    text(value)
+                ]
+                .spacing(10)
+            };
+            let off_target = match solved.pointing {
+                Some(pointing) => format!("{:.1}'", solved.error_arcmin(pointing)),
+                None => "Mount position unknown".to_owned(),
+            };
+            actions = actions.push(
+                content_container(
+                    column![
+                        text(format!(
+                            "{} solve in {:.1}s",
+                            if solved.blind { "Blind" } else { "Near" },
+                            solved.took.as_secs_f64()
+                        )),
+                        reading("RA (J2000)", fits::sexagesimal_ra(solution.ra_hours)),
+                        reading("Dec (J2000)", fits::sexagesimal_dec(solution.dec_deg)),
+                        reading("Rotation", format!("{:.2}°", solution.rotation_deg)),
+                        reading(
+                            "Pixel scale",
+                            format!("{:.3}\"/px", solution.pixel_scale_arcsec)
+                        ),
+                        reading(
+                            "Field",
+                            format!(
+                                "{:.2}° × {:.2}°",
+                                solution.field_width_deg, solution.field_height_deg
+                            )
+                        ),
+                        reading("Off target", off_target),
+                        text(solved.path.display().to_string()).size(12),
+                        row![
+                            sidereal_button("Sync Mount", Some(Message::SyncMount), idle)
+                                .width(Length::Fill),
+                            sidereal_button(
+                                "Slew to Target & Re-solve",
+                                Some(Message::Center),
+                                idle && solved.pointing.is_some()
+                            )
+                            .width(Length::Fill),
+                        ]
+                        .spacing(10),
+                    ]
+                    .spacing(5),
+                    ContainerLayer::Layer2,
+                )
+                .width(Length::Fill),
+            );
+        }
+
+        column![
+            settings,
+            content_container(actions, ContainerLayer::Layer1).width(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
     }
 }
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use iced::widget::{checkbox, column, row, slider, text, Column, Space};
+use iced::{Alignment, Color, Element, Length, Subscription, Task};
+
+use crate::app::Message as MainMessage;
+use crate::config::Config;
+use crate::gui::styles::button_style::sidereal_button;
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::text_input_style::sidereal_text_input;
+use crate::gui::widgets::live_plot::{create_live_plot, live_plot, DataPoint, LivePlotData};
+use crate::indi_handler::power_box::{self, Outlet, PowerBoxStatus};
+use crate::model::SiderealResult;
+
+/// How often the power box is read
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// Colors the current plot's series take in turn
+const SERIES_COLORS: [Color; 6] = [
+    Color::from_rgb(0.3, 0.7, 1.0),
+    Color::from_rgb(1.0, 0.6, 0.3),
+    Color::from_rgb(0.4, 0.9, 0.4),
+    Color::from_rgb(0.9, 0.4, 0.9),
+    Color::from_rgb(1.0, 0.9, 0.3),
+    Color::from_rgb(0.6, 0.6, 1.0),
+];
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Poll,
+    Updated(Option<PowerBoxStatus>),
+    OutletToggled(u32, bool),
+    OutletNameChanged(u32, String),
+    SaveNames,
+    /// A dew heater slider moved, by index into the box's ports
+    DewDutyChanged(usize, f32),
+    DewDutyReleased,
+    Applied(SiderealResult<()>),
+}
+
+pub struct PowerState {
+    status: Option<PowerBoxStatus>,
+    /// Outlet names as being edited, by outlet number
+    outlet_names: HashMap<u32, String>,
+    /// Dew port slider being dragged, and where it is
+    dragging: Option<(usize, f32)>,
+    current_plot: LivePlotData,
+    /// Series of the current plot, by outlet number, or by port letter for
+    /// the dew heaters
+    outlet_series: HashMap<u32, usize>,
+    dew_series: HashMap<&'static str, usize>,
+    /// Plot times count from here
+    plot_start: Instant,
+}
+
+impl Default for PowerState {
+    fn default() -> Self {
+        Self {
+            status: None,
+            outlet_names: HashMap::new(),
+            dragging: None,
+            current_plot: create_live_plot(1000, 20.0),
+            outlet_series: HashMap::new(),
+            dew_series: HashMap::new(),
+            plot_start: Instant::now(),
+        }
+    }
+}
+
+impl PowerState {
+    pub fn on_config_load(&mut self, config: &Config) {
+        self.outlet_names = config.outlet_names.clone();
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        iced::time::every(POLL_INTERVAL).map(|_| Message::Poll)
+    }
+
+    /// What outlet `outlet` is called here, falling back on the driver's label
+    fn outlet_name(&self, outlet: &Outlet) -> String {
+        self.outlet_names
+            .get(&outlet.number)
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .or(outlet.label.as_deref())
+            .map_or_else(|| format!("Outlet {}", outlet.number), str::to_owned)
+    }
+
+    fn next_color(&self) -> Color {
+        SERIES_COLORS[self.current_plot.series.len() % SERIES_COLORS.len()]
+    }
+
+    /// Add the currents of `status` to the plot, naming each outlet's series
+    /// as it's called now
+    fn plot_currents(&mut self, status: &PowerBoxStatus) {
+        let timestamp = self.plot_start.elapsed().as_secs_f64();
+        for outlet in &status.outlets {
+            let name = self.outlet_name(outlet);
+            let series = match self.outlet_series.get(&outlet.number) {
+                Some(&series) => series,
+                None => {
+                    let series = self.current_plot.add_series(&name, self.next_color());
+                    self.outlet_series.insert(outlet.number, series);
+                    series
+                }
+            };
+            if let Some(plotted) = self.current_plot.series_mut(series) {
+                plotted.name = name;
+            }
+            if let Some(current) = outlet.current {
+                self.current_plot.add_data_point(
+                    series,
+                    DataPoint {
+                        timestamp,
+                        value: current,
+                    },
+                );
+            }
+        }
+        for port in &status.dew_ports {
+            let series = match self.dew_series.get(port.channel) {
+                Some(&series) => series,
+                None => {
+                    let name = format!("Dew {}", port.channel);
+                    let series = self.current_plot.add_series(name, self.next_color());
+                    self.dew_series.insert(port.channel, series);
+                    series
+                }
+            };
+            if let Some(current) = port.current {
+                self.current_plot.add_data_point(
+                    series,
+                    DataPoint {
+                        timestamp,
+                        value: current,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::Poll => {
+                return Task::perform(power_box::status(), |status| {
+                    MainMessage::Power(Message::Updated(status))
+                })
+            }
+            Message::Updated(status) => {
+                if let Some(status) = &status {
+                    self.plot_currents(status);
+                }
+                self.status = status;
+            }
+            Message::OutletToggled(number, on) => {
+                return Task::perform(power_box::set_outlet(number, on), |result| {
+                    MainMessage::Power(Message::Applied(result))
+                })
+            }
+            Message::OutletNameChanged(number, name) => {
+                self.outlet_names.insert(number, name);
+            }
+            Message::SaveNames => {
+                let names = self
+                    .outlet_names
+                    .iter()
+                    .filter(|(_, name)| !name.trim().is_empty())
+                    .map(|(number, name)| (*number, name.trim().to_owned()))
+                    .collect();
+                return Task::perform(Config::set_outlet_names(names), |result| {
+                    MainMessage::Power(Message::Applied(result))
+                });
+            }
+            Message::DewDutyChanged(index, duty) => self.dragging = Some((index, duty)),
+            // only sent once the slider's let go, rather than for every step dragged through
+            Message::DewDutyReleased => {
+                let Some((index, duty)) = self.dragging.take() else {
+                    return Task::none();
+                };
+                let Some(port) = self
+                    .status
+                    .as_ref()
+                    .and_then(|status| status.dew_ports.get(index))
+                else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    power_box::set_dew_duty(port.channel, duty.into()),
+                    |result| MainMessage::Power(Message::Applied(result)),
+                );
+            }
+            Message::Applied(Ok(())) => return self.update(Message::Poll),
+            Message::Applied(Err(e)) => return Task::done(MainMessage::ErrorOccurred(e)),
+        }
+        Task::none()
+    }
+
+    fn outlets_view(&self, status: &PowerBoxStatus) -> Element<Message> {
+        let outlets = status
+            .outlets
+            .iter()
+            .fold(Column::new().spacing(10), |outlets, outlet| {
+                let number = outlet.number;
+                let placeholder = outlet
+                    .label
+                    .clone()
+                    .unwrap_or_else(|| format!("Outlet {number}"));
+                let name = self.outlet_names.get(&number).map_or("", String::as_str);
+                let current = match outlet.current {
+                    Some(current) => format!("{current:.2} A"),
+                    None => "-".to_owned(),
+                };
+                outlets.push(
+                    row![
+                        text(format!("{number}")).width(Length::Fixed(20.0)),
+                        sidereal_text_input(&placeholder, name)
+                            .on_input(move |name| Message::OutletNameChanged(number, name))
+                            .width(Length::Fill),
+                        checkbox("On", outlet.on)
+                            .on_toggle(move |on| Message::OutletToggled(number, on)),
+                        text(current).width(Length::Fixed(70.0)),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            });
+
+        column![
+            row![
+                text("Outlets"),
+                Space::with_width(Length::Fill),
+                sidereal_button("Save Names", Some(Message::SaveNames), true),
+            ]
+            .align_y(Alignment::Center),
+            outlets,
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn dew_view(&self, status: &PowerBoxStatus) -> Element<Message> {
+        let ports = status.dew_ports.iter().enumerate().fold(
+            Column::new().spacing(10),
+            |ports, (index, port)| {
+                let duty = match self.dragging {
+                    Some((dragged, duty)) if dragged == index => duty,
+                    _ => port.duty_percent as f32,
+                };
+                let current = match port.current {
+                    Some(current) => format!("{current:.2} A"),
+                    None => "-".to_owned(),
+                };
+                ports.push(
+                    row![
+                        text(format!("Dew {}", port.channel)).width(Length::Fixed(60.0)),
+                        slider(0.0..=100.0, duty, move |duty| {
+                            Message::DewDutyChanged(index, duty)
+                        })
+                        .step(1.0)
+                        .on_release(Message::DewDutyReleased)
+                        .width(Length::Fill),
+                        text(format!("{duty:.0}%")).width(Length::Fixed(50.0)),
+                        text(current).width(Length::Fixed(70.0)),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            },
+        );
+
+        column![text("Dew Heaters"), ports].spacing(10).into()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        let Some(status) = &self.status else {
+            return column![content_container(
+                column![text("Power Box"), text("No power box connected").size(12)].spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill)]
+            .into();
+        };
+        let reading = |value: Option<f64>, unit: &str| match value {
+            Some(value) => format!("{value:.2} {unit}"),
+            None => "-".to_owned(),
+        };
+
+        column![
+            content_container(
+                row![
+                    text("Power Box"),
+                    Space::with_width(Length::Fill),
+                    text(format!(
+                        "{}, {} total",
+                        reading(status.voltage, "V"),
+                        reading(status.total_current, "A")
+                    )),
+                ]
+                .align_y(Alignment::Center),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+            content_container(self.outlets_view(status), ContainerLayer::Layer1)
+                .width(Length::Fill),
+            content_container(self.dew_view(status), ContainerLayer::Layer1).width(Length::Fill),
+            content_container(
+                column![
+                    text("Current (A)"),
+                    live_plot(&self.current_plot)
+                        .width(Length::Fill)
+                        .height(Length::Fixed(220.0)),
+                ]
+                .spacing(10),
+                ContainerLayer::Layer1
+            )
+            .width(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
@@ -0,0 +1,625 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use iced::widget::{checkbox, column, progress_bar, row, scrollable, text, Column, Space};
+use iced::{Alignment, Element, Font, Length, Subscription, Task};
+
+use crate::app::Message as MainMessage;
+use crate::gui::styles;
+use crate::gui::styles::button_style::sidereal_button;
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::tabs::observatory::RoofState;
+use crate::gui::widgets::indicator::{indicator, IndicatorColor};
+use crate::indi_handler::ups::{self, UpsStatus};
+use crate::indi_handler::weather::{self, WeatherStatus};
+use crate::indi_handler::{mount, roof_controller, CONNECTED_DEVICES, INDI_CLIENT};
+use crate::model::SiderealResult;
+
+/// How often conditions are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Lowest the roof controller's 12V supply may sag to before the mains or
+/// batteries are suspect
+const MIN_SUPPLY_VOLTAGE: f64 = 11.5;
+/// UPS charge left, in percent, at which the observatory is shut straight away
+/// and the camera warmed up to spare the battery
+const LOW_BATTERY_PERCENT: f64 = 30.0;
+/// How long a power or connectivity problem has to last before the
+/// observatory is shut, so a dropout doesn't end the night
+const GRACE_PERIOD: Duration = Duration::from_secs(30);
+/// Longest the mount gets to park
+const PARK_TIMEOUT: Duration = Duration::from_secs(180);
+/// How long to give a close command before sending it again while the roof
+/// still isn't reported closed
+const ROOF_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// Events kept in the history
+const MAX_EVENTS: usize = 200;
+
+/// Something the observatory is only safe to run while it holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Condition {
+    Weather,
+    Power,
+    Battery,
+    Connectivity,
+}
+
+impl Condition {
+    const ALL: [Condition; 4] = [
+        Condition::Weather,
+        Condition::Power,
+        Condition::Battery,
+        Condition::Connectivity,
+    ];
+
+    /// How long the condition has to be unsafe before it's acted on; the
+    /// weather station does its own smoothing, and a flat battery can't wait
+    fn grace(self) -> Duration {
+        match self {
+            Condition::Weather | Condition::Battery => Duration::ZERO,
+            Condition::Power | Condition::Connectivity => GRACE_PERIOD,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Condition::Weather => "Weather",
+            Condition::Power => "Power",
+            Condition::Battery => "Battery",
+            Condition::Connectivity => "Connectivity",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// What one condition looks like, and why
+#[derive(Debug, Clone, PartialEq)]
+enum Assessment {
+    Safe(String),
+    /// Safe, but worth keeping an eye on
+    Caution(String),
+    Unsafe(String),
+    /// Nothing to judge it by, which doesn't count against the observatory
+    Unknown(String),
+}
+
+impl Assessment {
+    fn detail(&self) -> &str {
+        match self {
+            Assessment::Safe(detail)
+            | Assessment::Caution(detail)
+            | Assessment::Unsafe(detail)
+            | Assessment::Unknown(detail) => detail,
+        }
+    }
+
+    fn is_unsafe(&self) -> bool {
+        matches!(self, Assessment::Unsafe(_))
+    }
+}
+
+/// Everything the conditions are judged on, read in one go
+#[derive(Debug, Clone)]
+pub struct Readings {
+    weather_station: bool,
+    weather: Option<WeatherStatus>,
+    supply_voltage: Option<f64>,
+    ups: Option<UpsStatus>,
+    server_connected: bool,
+    roof_connected: bool,
+    roof_state: Option<RoofState>,
+}
+
+impl Readings {
+    fn on_mains(&self) -> Option<bool> {
+        self.ups.and_then(|ups| ups.on_mains)
+    }
+
+    fn assess(&self, condition: Condition) -> Assessment {
+        match condition {
+            Condition::Weather => match self.weather {
+                Some(WeatherStatus::Ok) => {
+                    Assessment::Safe("Weather station reports OK".to_owned())
+                }
+                Some(WeatherStatus::Warning) => {
+                    Assessment::Caution("Weather station warns of worsening weather".to_owned())
+                }
+                Some(WeatherStatus::Alert) => {
+                    Assessment::Unsafe("Weather station reports danger".to_owned())
+                }
+                None if self.weather_station => {
+                    Assessment::Unknown("No verdict from the weather station yet".to_owned())
+                }
+                None => Assessment::Unknown("No weather station connected".to_owned()),
+            },
+            Condition::Power => match (self.on_mains(), self.supply_voltage) {
+                (Some(false), _) => Assessment::Unsafe("Mains power lost, on the UPS".to_owned()),
+                (_, Some(volts)) if volts < MIN_SUPPLY_VOLTAGE => {
+                    Assessment::Unsafe(format!("12V supply down to {volts:.2} V"))
+                }
+                (_, Some(volts)) => Assessment::Safe(format!("12V supply at {volts:.2} V")),
+                (Some(true), None) => Assessment::Safe("On mains power".to_owned()),
+                (None, None) => Assessment::Unknown("No UPS or supply reading".to_owned()),
+            },
+            Condition::Battery => match self.ups.and_then(|ups| ups.battery_percent) {
+                Some(charge) if charge <= LOW_BATTERY_PERCENT => {
+                    Assessment::Unsafe(format!("UPS battery down to {charge:.0}%"))
+                }
+                Some(charge) if self.on_mains() == Some(false) => {
+                    Assessment::Caution(format!("UPS battery at {charge:.0}% and draining"))
+                }
+                Some(charge) => Assessment::Safe(format!("UPS battery at {charge:.0}%")),
+                None if self.ups.is_some() => {
+                    Assessment::Unknown("No charge reading from the UPS".to_owned())
+                }
+                None => Assessment::Unknown("No UPS connected".to_owned()),
+            },
+            Condition::Connectivity => {
+                if !self.server_connected {
+                    Assessment::Unsafe("Lost the INDI server".to_owned())
+                } else if !self.roof_connected {
+                    Assessment::Unsafe("Roof controller isn't responding".to_owned())
+                } else {
+                    Assessment::Safe("INDI server and roof controller are up".to_owned())
+                }
+            }
+        }
+    }
+}
+
+async fn read_conditions() -> Readings {
+    let (weather_station, roof_connected) = {
+        let devices = CONNECTED_DEVICES.read().await;
+        (devices.weather.is_some(), devices.roof_controller.is_some())
+    };
+    Readings {
+        weather_station,
+        weather: weather::status().await,
+        supply_voltage: roof_controller::supply_voltage().await,
+        ups: ups::status().await,
+        server_connected: INDI_CLIENT.read().await.is_some(),
+        roof_connected,
+        roof_state: roof_controller::roof_state().await,
+    }
+}
+
+/// Something the monitor saw or did
+#[derive(Debug, Clone)]
+struct SafetyEvent {
+    time: DateTime<Local>,
+    /// Shown in red: the observatory was unsafe or couldn't be shut
+    alarm: bool,
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Check,
+    Checked(Readings),
+    /// Keep watching but stop shutting the observatory down
+    OverrideToggled(bool),
+    MountParked(SiderealResult<()>),
+    RoofClosed(SiderealResult<()>),
+    ClearHistory,
+}
+
+#[derive(Default)]
+pub struct SafetyState {
+    /// Where each condition stood at the last check
+    assessments: Vec<(Condition, Assessment)>,
+    /// When each unsafe condition turned so
+    unsafe_since: HashMap<Condition, Instant>,
+    /// Whether the roof controller has been seen; until then there's nothing
+    /// to protect
+    watching: bool,
+    /// Whether the observatory has been shut for the current spell of bad
+    /// conditions
+    tripped: bool,
+    /// Whether the camera has been warmed up for a low battery this spell
+    battery_tripped: bool,
+    /// Whether the INDI server was reachable at the last check; without it
+    /// nothing can be shut
+    server_connected: bool,
+    /// Whether being unable to act without the INDI server has been logged
+    unreachable_logged: bool,
+    roof_state: Option<RoofState>,
+    ups: Option<UpsStatus>,
+    overridden: bool,
+    parking: bool,
+    closing_roof: bool,
+    /// When the roof was last told to close this spell
+    last_close: Option<Instant>,
+    events: VecDeque<SafetyEvent>,
+}
+
+impl SafetyState {
+    pub fn subscription(&self) -> Subscription<Message> {
+        iced::time::every(CHECK_INTERVAL).map(|_| Message::Check)
+    }
+
+    /// Whether a condition has been unsafe for long enough to act on
+    pub fn is_unsafe(&self) -> bool {
+        !self.reasons(Instant::now()).is_empty()
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.watching
+    }
+
+    pub fn is_overridden(&self) -> bool {
+        self.overridden
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<MainMessage> {
+        match message {
+            Message::Check => {
+                return Task::perform(read_conditions(), |readings| {
+                    MainMessage::Safety(Message::Checked(readings))
+                })
+            }
+            Message::Checked(readings) => return self.on_readings(readings),
+            Message::OverrideToggled(overridden) => {
+                self.overridden = overridden;
+                if overridden {
+                    self.record(
+                        true,
+                        "Override on: the observatory won't be shut automatically".to_owned(),
+                    );
+                } else {
+                    self.record(false, "Override off".to_owned());
+                    return self.act();
+                }
+            }
+            Message::MountParked(Ok(())) => {
+                self.parking = false;
+                self.record(false, "Mount parked".to_owned());
+            }
+            Message::MountParked(Err(e)) => {
+                self.parking = false;
+                self.record(
+                    true,
+                    format!("The mount didn't park, closing the roof regardless: {e}"),
+                );
+                return Task::done(MainMessage::ErrorOccurred(e));
+            }
+            Message::RoofClosed(result) => {
+                self.closing_roof = false;
+                match result {
+                    Ok(()) => self.record(false, "Roof closing".to_owned()),
+                    Err(e) => {
+                        self.record(true, format!("Couldn't close the roof: {e}"));
+                        return Task::done(MainMessage::ErrorOccurred(e));
+                    }
+                }
+            }
+            Message::ClearHistory => self.events.clear(),
+        }
+        Task::none()
+    }
+
+    fn on_readings(&mut self, readings: Readings) -> Task<MainMessage> {
+        if readings.roof_connected && !self.watching {
+            self.watching = true;
+            self.record(
+                false,
+                "Roof controller found; watching conditions".to_owned(),
+            );
+        }
+        self.ups = readings.ups;
+        self.server_connected = readings.server_connected;
+        self.roof_state = readings.roof_state;
+        let now = Instant::now();
+        let previous = std::mem::take(&mut self.assessments);
+        for condition in Condition::ALL {
+            let assessment = readings.assess(condition);
+            let before = previous
+                .iter()
+                .find(|(c, _)| *c == condition)
+                .map(|(_, a)| a);
+            // only note a condition changing kind, not every new reading
+            let changed =
+                before.map(std::mem::discriminant) != Some(std::mem::discriminant(&assessment));
+            if changed && before.is_some() {
+                self.record(
+                    assessment.is_unsafe(),
+                    format!("{condition}: {}", assessment.detail()),
+                );
+            }
+            if assessment.is_unsafe() {
+                self.unsafe_since.entry(condition).or_insert(now);
+            } else {
+                self.unsafe_since.remove(&condition);
+            }
+            self.assessments.push((condition, assessment));
+        }
+        if self.tripped && self.unsafe_since.is_empty() {
+            self.tripped = false;
+            self.battery_tripped = false;
+            self.last_close = None;
+            self.record(false, "Conditions are safe again".to_owned());
+        }
+        self.act()
+    }
+
+    /// What has been unsafe for longer than its grace period
+    fn reasons(&self, now: Instant) -> Vec<(Condition, String)> {
+        self.assessments
+            .iter()
+            .filter(|(condition, _)| {
+                self.unsafe_since
+                    .get(condition)
+                    .is_some_and(|since| now.duration_since(*since) >= condition.grace())
+            })
+            .map(|(condition, assessment)| (*condition, assessment.detail().to_owned()))
+            .collect()
+    }
+
+    /// Shut the observatory if it's unsafe and hasn't been already, and keep
+    /// at the roof until it's closed
+    fn act(&mut self) -> Task<MainMessage> {
+        let now = Instant::now();
+        let reasons = self.reasons(now);
+        if reasons.is_empty() || !self.watching || self.overridden {
+            self.unreachable_logged = false;
+            return Task::none();
+        }
+        if !self.server_connected {
+            // every step of a shutdown is an INDI command
+            if !self.unreachable_logged {
+                self.unreachable_logged = true;
+                self.record(
+                    true,
+                    "Unsafe, but nothing can be parked or closed without the INDI server"
+                        .to_owned(),
+                );
+            }
+            return Task::none();
+        }
+        self.unreachable_logged = false;
+        let low_battery = reasons
+            .iter()
+            .any(|(condition, _)| *condition == Condition::Battery);
+        let reason = reasons
+            .iter()
+            .map(|(condition, detail)| format!("{condition}: {detail}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if self.tripped {
+            let mut tasks = Vec::new();
+            // already shut, but a battery running flat still wants the camera warmed up
+            if low_battery && !self.battery_tripped {
+                self.battery_tripped = true;
+                self.record(true, "UPS battery low, warming the camera up".to_owned());
+                tasks.push(Task::done(MainMessage::SafetyShutdown {
+                    reason,
+                    low_battery,
+                }));
+            }
+            if self.roof_needs_closing(now) {
+                self.record(
+                    true,
+                    "The roof still isn't closed, closing it again".to_owned(),
+                );
+                tasks.push(self.close_roof(now));
+            }
+            return Task::batch(tasks);
+        }
+        self.tripped = true;
+        self.battery_tripped = low_battery;
+        self.record(true, format!("Unsafe, shutting the observatory: {reason}"));
+        self.parking = true;
+        // the roof doesn't wait on the mount, which may be missing or refuse to park
+        Task::batch([
+            Task::done(MainMessage::SafetyShutdown {
+                reason,
+                low_battery,
+            }),
+            Task::perform(
+                async {
+                    // stops a slew or pass in progress before parking
+                    mount::abort_motion().await?;
+                    mount::park(true).await?;
+                    mount::wait_for_park(PARK_TIMEOUT).await
+                },
+                |result| MainMessage::Safety(Message::MountParked(result)),
+            ),
+            self.close_roof(now),
+        ])
+    }
+
+    /// Whether the roof should be told to close again: it isn't reported
+    /// closed or closing, and the last attempt has had its chance
+    fn roof_needs_closing(&self, now: Instant) -> bool {
+        !self.closing_roof
+            && !matches!(
+                self.roof_state,
+                Some(RoofState::Closed | RoofState::Closing)
+            )
+            && self
+                .last_close
+                .is_none_or(|at| now.duration_since(at) >= ROOF_RETRY_INTERVAL)
+    }
+
+    fn close_roof(&mut self, now: Instant) -> Task<MainMessage> {
+        self.closing_roof = true;
+        self.last_close = Some(now);
+        Task::perform(roof_controller::close_roof(), |result| {
+            MainMessage::Safety(Message::RoofClosed(result))
+        })
+    }
+
+    fn record(&mut self, alarm: bool, message: String) {
+        if alarm {
+            tracing::warn!("Safety: {message}");
+        } else {
+            tracing::info!("Safety: {message}");
+        }
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(SafetyEvent {
+            time: Local::now(),
+            alarm,
+            message,
+        });
+    }
+
+    fn status_view(&self) -> Element<Message> {
+        let (color, status) = if !self.watching {
+            (IndicatorColor::Yellow, "Waiting for the roof controller")
+        } else if self.is_unsafe() {
+            (IndicatorColor::Red, "Unsafe")
+        } else if !self.unsafe_since.is_empty() {
+            (
+                IndicatorColor::Yellow,
+                "Unsafe conditions, within the grace period",
+            )
+        } else {
+            (IndicatorColor::Green, "Safe")
+        };
+        let action = match (self.parking, self.closing_roof, self.overridden) {
+            (true, true, _) => text("Parking the mount and closing the roof..."),
+            (true, false, _) => text("Parking the mount..."),
+            (false, true, _) => text("Closing the roof..."),
+            (false, false, true) => {
+                text("Overridden: nothing is shut down automatically").color(styles::AMBER_TEXT)
+            }
+            (false, false, false) => text(""),
+        };
+
+        column![
+            text("Safety Monitor"),
+            row![indicator(color), text(status)]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            row![
+                checkbox("Override", self.overridden).on_toggle(Message::OverrideToggled),
+                Space::with_width(Length::Fill),
+                action,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            text("When unsafe, the sequence is stopped, the mount parked and the roof closed")
+                .size(12),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn conditions_view(&self) -> Element<Message> {
+        let lines = self.assessments.iter().fold(
+            Column::new().spacing(10),
+            |lines, (condition, assessment)| {
+                let color = match assessment {
+                    Assessment::Safe(_) => IndicatorColor::Green,
+                    Assessment::Caution(_) | Assessment::Unknown(_) => IndicatorColor::Yellow,
+                    Assessment::Unsafe(_) => IndicatorColor::Red,
+                };
+                lines.push(
+                    row![
+                        indicator(color),
+                        text(condition.to_string()).width(Length::Fixed(120.0)),
+                        text(assessment.detail().to_owned()),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                )
+            },
+        );
+
+        column![text("Conditions"), lines].spacing(10).into()
+    }
+
+    fn power_view(&self) -> Element<Message> {
+        let Some(ups) = self.ups else {
+            return column![text("Power"), text("No UPS connected").size(12)]
+                .spacing(10)
+                .into();
+        };
+        let mains = match ups.on_mains {
+            Some(true) => text("On mains"),
+            Some(false) => text("On battery").color(styles::RED_TEXT),
+            None => text("Unknown"),
+        };
+        let level = |label: &'static str, percent: Option<f64>| {
+            row![
+                text(label).width(Length::Fixed(80.0)),
+                progress_bar(0.0..=100.0, percent.unwrap_or(0.0).clamp(0.0, 100.0) as f32)
+                    .height(Length::Fixed(12.0)),
+                text(match percent {
+                    Some(percent) => format!("{percent:.0}%"),
+                    None => "-".to_owned(),
+                })
+                .width(Length::Fixed(50.0)),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        };
+
+        column![
+            text("Power"),
+            row![text("Mains").width(Length::Fixed(80.0)), mains]
+                .spacing(10)
+                .align_y(Alignment::Center),
+            level("Battery", ups.battery_percent),
+            level("Load", ups.load_percent),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn history_view(&self) -> Element<Message> {
+        let lines = self
+            .events
+            .iter()
+            .fold(Column::new().spacing(2), |lines, event| {
+                let line = text(format!(
+                    "{} {}",
+                    event.time.format("%Y-%m-%d %H:%M:%S"),
+                    event.message
+                ))
+                .size(12)
+                .font(Font::MONOSPACE);
+                lines.push(if event.alarm {
+                    line.color(styles::RED_TEXT)
+                } else {
+                    line
+                })
+            });
+
+        column![
+            row![
+                text("History"),
+                Space::with_width(Length::Fill),
+                sidereal_button(
+                    "Clear",
+                    Some(Message::ClearHistory),
+                    !self.events.is_empty()
+                ),
+            ]
+            .align_y(Alignment::Center),
+            scrollable(lines)
+                .anchor_bottom()
+                .width(Length::Fill)
+                .height(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    pub fn view(&self) -> Element<Message> {
+        column![
+            content_container(self.status_view(), ContainerLayer::Layer1).width(Length::Fill),
+            content_container(self.conditions_view(), ContainerLayer::Layer1).width(Length::Fill),
+            content_container(self.power_view(), ContainerLayer::Layer1).width(Length::Fill),
+            content_container(self.history_view(), ContainerLayer::Layer1)
+                .width(Length::Fill)
+                .height(Length::Fill),
+        ]
+        .spacing(10)
+        .into()
+    }
+}
@@ -1,19 +1,112 @@
 use crate::app::Message as MainMessage;
+use crate::config::{Config, DewControlConfig};
 use crate::gui::styles::button_style::sidereal_button;
 use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::text_input_style::sidereal_text_input;
 use crate::gui::widgets::indicator::{indicator, IndicatorColor};
 use crate::gui::widgets::live_plot::{create_live_plot, live_plot, DataPoint, LivePlotData};
-use crate::indi_handler::telescope_controller;
-use crate::model::SiderealResult;
-use iced::widget::{column, container, row, text, Space};
-use iced::{Alignment, Color, Element, Length, Task};
-use std::time::SystemTime;
+use crate::indi_handler::telescope_controller::{self, HeaterPid};
+use crate::indi_handler::weather::{self, Ambient};
+use crate::model::{SiderealError, SiderealResult};
+use iced::widget::{checkbox, column, container, row, text, Column, Space};
+use iced::{Alignment, Color, Element, Length, Subscription, Task};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the weather station's temperature and humidity, and the
+/// heaters' settings, are read
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+/// Degrees (°C) an automatic heater goes past its target before switching
+/// off, so it doesn't chatter around it
+const DEW_HYSTERESIS: f64 = 0.5;
+/// An automatic heater command the controller hasn't carried out by now is
+/// sent again
+const COMMAND_RETRY: Duration = Duration::from_secs(15);
+
+/// Dew point (°C) of air at `temperature` (°C) and relative `humidity` (%),
+/// by the Magnus formula
+fn dew_point(temperature: f64, humidity: f64) -> Option<f64> {
+    const B: f64 = 17.62;
+    const C: f64 = 243.12;
+    if humidity <= 0.0 {
+        return None;
+    }
+    let gamma = (humidity.min(100.0) / 100.0).ln() + B * temperature / (C + temperature);
+    Some(C * gamma / (B - gamma))
+}
+
+/// Switch heater `index` (from 0) on or off
+fn heater_task(index: usize, enabled: bool) -> Task<MainMessage> {
+    Task::perform(
+        async move {
+            match index {
+                0 => telescope_controller::set_heater1(enabled).await,
+                1 => telescope_controller::set_heater2(enabled).await,
+                _ => telescope_controller::set_heater3(enabled).await,
+            }
+        },
+        |result: SiderealResult<()>| match result {
+            Ok(_) => MainMessage::Noop,
+            Err(e) => MainMessage::ErrorOccurred(e),
+        },
+    )
+}
+
+/// One of a heater's PID settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PidField {
+    Setpoint,
+    Kp,
+    Ki,
+    Kd,
+}
+
+/// A heater's PID settings as being edited
+#[derive(Debug, Clone, Default)]
+struct PidInput {
+    setpoint: String,
+    kp: String,
+    ki: String,
+    kd: String,
+}
+
+impl PidInput {
+    fn from_pid(pid: &HeaterPid) -> Self {
+        Self {
+            setpoint: pid.setpoint.to_string(),
+            kp: pid.kp.to_string(),
+            ki: pid.ki.to_string(),
+            kd: pid.kd.to_string(),
+        }
+    }
+
+    fn parse(&self) -> Option<HeaterPid> {
+        Some(HeaterPid {
+            setpoint: self.setpoint.trim().parse().ok()?,
+            kp: self.kp.trim().parse().ok()?,
+            ki: self.ki.trim().parse().ok()?,
+            kd: self.kd.trim().parse().ok()?,
+        })
+    }
+}
+
+/// How a heater is being switched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HeaterMode {
+    /// Held above the dew point by Sidereal
+    Auto,
+    /// Left as it was last enabled or disabled here
+    Manual,
+    /// Forced by the controller's own switch, which automatic control leaves be
+    Override,
+}
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Noop,
     TelemetryUpdate {
         ambient_temp: f64,
+        /// Relative, in percent, for controllers with a humidity sensor
+        ambient_humidity: Option<f64>,
         heater1_temp: f64,
         heater2_temp: f64,
         heater3_temp: f64,
@@ -38,6 +131,22 @@ pub enum Message {
     Heater2Disable,
     Heater3Enable,
     Heater3Disable,
+    Poll,
+    WeatherUpdated(Option<Ambient>),
+    /// Settings of heaters 1-3, for those the controller regulates
+    PidsRead([Option<HeaterPid>; 3]),
+    PidFieldChanged {
+        heater: usize,
+        field: PidField,
+        value: String,
+    },
+    /// Send a heater's (by index from 0) edited settings to the controller
+    ApplyPid(usize),
+    /// A heater (by index from 0) put under automatic dew control or taken
+    /// off it
+    HeaterAutoToggled(usize, bool),
+    DewMarginChanged(String),
+    SaveDewMargin,
 }
 
 pub struct TelescopeState {
@@ -48,6 +157,9 @@ pub struct TelescopeState {
     heater2_series: usize,
     #[allow(dead_code)]
     heater3_series: usize,
+    // Reference line indices for the plotted heaters' setpoints
+    heater1_setpoint: usize,
+    heater2_setpoint: usize,
     start_time: SystemTime,
     // Current telemetry values
     ambient_temp: f64,
@@ -64,6 +176,19 @@ pub struct TelescopeState {
     heater1_manual_override: bool,
     heater2_manual_override: bool,
     heater3_manual_override: bool,
+    ambient_humidity: Option<f64>,
+    /// Ambient from the weather station, preferred over the controller's
+    weather: Option<Ambient>,
+    dew_control: DewControlConfig,
+    dew_margin: String,
+    /// Dew point (°C) the automatic heaters are held above
+    dew_point: Option<f64>,
+    /// What each automatic heater was last told to do and when, until the
+    /// controller reports it done
+    heater_commands: [Option<(bool, Instant)>; 3],
+    /// Heater settings as the controller last reported them
+    pids: [Option<HeaterPid>; 3],
+    pid_inputs: [PidInput; 3],
 }
 
 impl Default for TelescopeState {
@@ -77,6 +202,10 @@ impl Default for TelescopeState {
         let heater1_series = plot.add_series("Primary Heater", Color::from_rgb(1.0, 0.3, 0.3));
         let heater2_series = plot.add_series("Secondary Heater", Color::from_rgb(1.0, 0.6, 0.3));
         let heater3_series = plot.add_series("Heater 3", Color::from_rgb(0.3, 1.0, 0.3)); // Keep for compatibility but won't be displayed
+        let heater1_setpoint =
+            plot.add_reference("Primary Setpoint", Color::from_rgb(1.0, 0.3, 0.3));
+        let heater2_setpoint =
+            plot.add_reference("Secondary Setpoint", Color::from_rgb(1.0, 0.6, 0.3));
 
         Self {
             plot,
@@ -84,6 +213,8 @@ impl Default for TelescopeState {
             heater1_series,
             heater2_series,
             heater3_series,
+            heater1_setpoint,
+            heater2_setpoint,
             start_time: SystemTime::now(),
             ambient_temp: 0.0,
             heater1_temp: 0.0,
@@ -99,16 +230,125 @@ impl Default for TelescopeState {
             heater1_manual_override: false,
             heater2_manual_override: false,
             heater3_manual_override: false,
+            ambient_humidity: None,
+            weather: None,
+            dew_control: DewControlConfig::default(),
+            dew_margin: DewControlConfig::default().margin_celsius.to_string(),
+            dew_point: None,
+            heater_commands: [None; 3],
+            pids: [None; 3],
+            pid_inputs: Default::default(),
         }
     }
 }
 
 impl TelescopeState {
+    pub fn on_config_load(&mut self, config: &Config) {
+        self.dew_control = config.dew_control.clone();
+        self.dew_margin = config.dew_control.margin_celsius.to_string();
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        iced::time::every(POLL_INTERVAL).map(|_| Message::Poll)
+    }
+
+    /// Air the dew point is worked out from: the weather station's, or
+    /// failing that what the controller measures itself
+    fn ambient(&self) -> Option<Ambient> {
+        self.weather.or_else(|| {
+            self.ambient_humidity.map(|humidity| Ambient {
+                temperature: self.ambient_temp,
+                humidity,
+            })
+        })
+    }
+
+    /// Temperature, whether it's on, and whether the controller's own switch
+    /// overrides it, for heater `index` (from 0)
+    fn heater(&self, index: usize) -> (f64, bool, bool) {
+        match index {
+            0 => (
+                self.heater1_temp,
+                self.heater1_on,
+                self.heater1_manual_override,
+            ),
+            1 => (
+                self.heater2_temp,
+                self.heater2_on,
+                self.heater2_manual_override,
+            ),
+            _ => (
+                self.heater3_temp,
+                self.heater3_on,
+                self.heater3_manual_override,
+            ),
+        }
+    }
+
+    fn heater_mode(&self, index: usize) -> HeaterMode {
+        let (_, _, overridden) = self.heater(index);
+        if overridden {
+            HeaterMode::Override
+        } else if self.dew_control.auto_heaters[index] {
+            HeaterMode::Auto
+        } else {
+            HeaterMode::Manual
+        }
+    }
+
+    /// Switch the automatic heaters to hold their optics the configured
+    /// margin above the dew point
+    fn control_heaters(&mut self) -> Task<MainMessage> {
+        self.dew_point = self
+            .ambient()
+            .and_then(|ambient| dew_point(ambient.temperature, ambient.humidity));
+        let Some(dew_point) = self.dew_point else {
+            return Task::none();
+        };
+        let target = dew_point + self.dew_control.margin_celsius;
+
+        let mut tasks = Vec::new();
+        for index in 0..3 {
+            let (temperature, on, _) = self.heater(index);
+            if matches!(self.heater_commands[index], Some((commanded, _)) if commanded == on) {
+                self.heater_commands[index] = None;
+            }
+            if self.heater_mode(index) != HeaterMode::Auto {
+                continue;
+            }
+            let want_on = if on {
+                temperature < target + DEW_HYSTERESIS
+            } else {
+                temperature < target
+            };
+            let pending = matches!(
+                self.heater_commands[index],
+                Some((commanded, sent)) if commanded == want_on && sent.elapsed() < COMMAND_RETRY
+            );
+            if want_on != on && !pending {
+                self.heater_commands[index] = Some((want_on, Instant::now()));
+                tasks.push(heater_task(index, want_on));
+            }
+        }
+        Task::batch(tasks)
+    }
+
+    fn save_dew_control(&self) -> Task<MainMessage> {
+        Task::perform(
+            Config::set_dew_control(self.dew_control.clone()),
+            |result| match result {
+                Ok(_) => MainMessage::Noop,
+                Err(e) => MainMessage::ErrorOccurred(e),
+            },
+        )
+    }
+
     pub fn update(&mut self, message: Message) -> Task<MainMessage> {
         match message {
             Message::Noop => Task::none(),
             Message::TelemetryUpdate {
                 ambient_temp,
+                ambient_humidity,
                 heater1_temp,
                 heater2_temp,
                 heater3_temp,
@@ -124,6 +364,7 @@ impl TelescopeState {
                 heater3_manual_override,
             } => {
                 self.ambient_temp = ambient_temp;
+                self.ambient_humidity = ambient_humidity;
                 self.heater1_temp = heater1_temp;
                 self.heater2_temp = heater2_temp;
                 self.heater3_temp = heater3_temp;
@@ -165,7 +406,7 @@ impl TelescopeState {
                     },
                 );
 
-                Task::none()
+                self.control_heaters()
             }
             Message::LensCapOpen => Task::perform(
                 async { telescope_controller::set_lens_cap(true).await },
@@ -237,8 +478,172 @@ impl TelescopeState {
                     Err(e) => MainMessage::ErrorOccurred(e),
                 },
             ),
+            Message::Poll => Task::batch([
+                Task::perform(weather::ambient(), |ambient| {
+                    MainMessage::Telescope(Message::WeatherUpdated(ambient))
+                }),
+                Task::perform(
+                    async {
+                        [
+                            telescope_controller::heater_pid(1).await,
+                            telescope_controller::heater_pid(2).await,
+                            telescope_controller::heater_pid(3).await,
+                        ]
+                    },
+                    |pids| MainMessage::Telescope(Message::PidsRead(pids)),
+                ),
+            ]),
+            Message::PidsRead(pids) => {
+                // edits stand until the controller's settings change under them
+                for (index, pid) in pids.iter().enumerate() {
+                    if *pid != self.pids[index] {
+                        if let Some(pid) = pid {
+                            self.pid_inputs[index] = PidInput::from_pid(pid);
+                        }
+                    }
+                }
+                self.pids = pids;
+                self.plot
+                    .set_reference(self.heater1_setpoint, pids[0].map(|pid| pid.setpoint));
+                self.plot
+                    .set_reference(self.heater2_setpoint, pids[1].map(|pid| pid.setpoint));
+                Task::none()
+            }
+            Message::PidFieldChanged {
+                heater,
+                field,
+                value,
+            } => {
+                let input = &mut self.pid_inputs[heater];
+                match field {
+                    PidField::Setpoint => input.setpoint = value,
+                    PidField::Kp => input.kp = value,
+                    PidField::Ki => input.ki = value,
+                    PidField::Kd => input.kd = value,
+                }
+                Task::none()
+            }
+            Message::ApplyPid(heater) => match self.pid_inputs[heater].parse() {
+                Some(pid) => Task::perform(
+                    telescope_controller::set_heater_pid(heater as u8 + 1, pid),
+                    |result| match result {
+                        Ok(_) => MainMessage::Telescope(Message::Poll),
+                        Err(e) => MainMessage::ErrorOccurred(e),
+                    },
+                ),
+                None => Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                    format!("Invalid PID settings for heater {}", heater + 1),
+                ))),
+            },
+            Message::WeatherUpdated(ambient) => {
+                self.weather = ambient;
+                Task::none()
+            }
+            Message::HeaterAutoToggled(index, auto) => {
+                self.dew_control.auto_heaters[index] = auto;
+                self.heater_commands[index] = None;
+                Task::batch([self.save_dew_control(), self.control_heaters()])
+            }
+            Message::DewMarginChanged(value) => {
+                self.dew_margin = value;
+                Task::none()
+            }
+            Message::SaveDewMargin => match self.dew_margin.trim().parse::<f64>() {
+                Ok(margin) if margin >= 0.0 => {
+                    self.dew_control.margin_celsius = margin;
+                    Task::batch([self.save_dew_control(), self.control_heaters()])
+                }
+                _ => Task::done(MainMessage::ErrorOccurred(SiderealError::ParseError(
+                    format!("Invalid dew margin `{}`", self.dew_margin),
+                ))),
+            },
         }
     }
+    /// What heater `index` is switched by, with automatic control green,
+    /// switching by hand yellow and the controller's override red
+    fn mode_indicator(&self, index: usize) -> Element<'static, Message> {
+        let (mode, color) = match self.heater_mode(index) {
+            HeaterMode::Auto => ("Auto", IndicatorColor::Green),
+            HeaterMode::Manual => ("Manual", IndicatorColor::Yellow),
+            HeaterMode::Override => ("Manual Override", IndicatorColor::Red),
+        };
+        row![text(format!("Mode: {mode}")), indicator(color)]
+            .align_y(Alignment::Center)
+            .spacing(10)
+            .into()
+    }
+
+    /// Setpoint and gains of heater `index`, if the controller regulates it
+    fn pid_view(&self, index: usize) -> Element<'static, Message> {
+        if self.pids[index].is_none() {
+            return Column::new().into();
+        }
+        let input = &self.pid_inputs[index];
+        let field = |label: &'static str, field: PidField, value: &str| {
+            row![
+                text(label),
+                sidereal_text_input(label, value)
+                    .on_input(move |value| Message::PidFieldChanged {
+                        heater: index,
+                        field,
+                        value,
+                    })
+                    .width(Length::Fixed(70.0)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(5)
+        };
+
+        row![
+            field("Setpoint (°C)", PidField::Setpoint, &input.setpoint),
+            field("Kp", PidField::Kp, &input.kp),
+            field("Ki", PidField::Ki, &input.ki),
+            field("Kd", PidField::Kd, &input.kd),
+            Space::with_width(Length::Fill),
+            sidereal_button("Apply", Some(Message::ApplyPid(index)), true),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10)
+        .into()
+    }
+
+    fn dew_view(&self) -> Element<'static, Message> {
+        let conditions = match (self.ambient(), self.dew_point) {
+            (Some(ambient), Some(dew_point)) => format!(
+                "Ambient {:.1}°C at {:.0}% RH ({}), dew point {:.1}°C",
+                ambient.temperature,
+                ambient.humidity,
+                if self.weather.is_some() {
+                    "weather station"
+                } else {
+                    "controller"
+                },
+                dew_point
+            ),
+            _ => "No humidity reading, dew point unknown".to_owned(),
+        };
+
+        content_container(
+            column![
+                text("Dew Control"),
+                row![
+                    text(conditions).size(12),
+                    Space::with_width(Length::Fill),
+                    text("Margin (°C)"),
+                    sidereal_text_input("°C", &self.dew_margin)
+                        .on_input(Message::DewMarginChanged)
+                        .width(Length::Fixed(80.0)),
+                    sidereal_button("Apply", Some(Message::SaveDewMargin), true),
+                ]
+                .align_y(Alignment::Center)
+                .spacing(10),
+            ]
+            .spacing(10),
+            ContainerLayer::Layer2,
+        )
+        .into()
+    }
+
     pub fn view(&self) -> Element<'static, Message> {
         let _lens_cap_state_text = if self.lens_cap_open { "Open" } else { "Closed" };
         let _flat_light_state_text = if self.flat_light_on { "On" } else { "Off" };
@@ -349,6 +754,7 @@ impl TelescopeState {
             content_container(
                 column![
                     text("Heaters"),
+                    self.dew_view(),
                     content_container(
                         column![
                             text("Heater 1"),
@@ -358,7 +764,7 @@ impl TelescopeState {
                                         .align_x(Alignment::Center)
                                         .align_y(Alignment::Center),
                                     Some(Message::Heater1Enable),
-                                    true,
+                                    !self.dew_control.auto_heaters[0],
                                 )
                                 .width(Length::Fixed(80.0)),
                                 sidereal_button(
@@ -366,9 +772,11 @@ impl TelescopeState {
                                         .align_x(Alignment::Center)
                                         .align_y(Alignment::Center),
                                     Some(Message::Heater1Disable),
-                                    true,
+                                    !self.dew_control.auto_heaters[0],
                                 )
                                 .width(Length::Fixed(80.0)),
+                                checkbox("Auto", self.dew_control.auto_heaters[0])
+                                    .on_toggle(|auto| Message::HeaterAutoToggled(0, auto)),
                                 Space::with_width(Length::Fill),
                                 text("Enabled:"),
                                 indicator(if self.heater1_on {
@@ -382,16 +790,12 @@ impl TelescopeState {
                                 } else {
                                     IndicatorColor::Red
                                 }),
-                                text("Manual Override:"),
-                                indicator(if self.heater1_manual_override {
-                                    IndicatorColor::Green
-                                } else {
-                                    IndicatorColor::Red
-                                }),
+                                self.mode_indicator(0),
                             ]
                             .align_y(Alignment::Center)
                             .spacing(10)
-                            .width(Length::Fill)
+                            .width(Length::Fill),
+                            self.pid_view(0),
                         ]
                         .spacing(10),
                         ContainerLayer::Layer2
@@ -405,7 +809,7 @@ impl TelescopeState {
                                         .align_x(Alignment::Center)
                                         .align_y(Alignment::Center),
                                     Some(Message::Heater2Enable),
-                                    true,
+                                    !self.dew_control.auto_heaters[1],
                                 )
                                 .width(Length::Fixed(80.0)),
                                 sidereal_button(
@@ -413,9 +817,11 @@ impl TelescopeState {
                                         .align_x(Alignment::Center)
                                         .align_y(Alignment::Center),
                                     Some(Message::Heater2Disable),
-                                    true,
+                                    !self.dew_control.auto_heaters[1],
                                 )
                                 .width(Length::Fixed(80.0)),
+                                checkbox("Auto", self.dew_control.auto_heaters[1])
+                                    .on_toggle(|auto| Message::HeaterAutoToggled(1, auto)),
                                 Space::with_width(Length::Fill),
                                 text("Enabled:"),
                                 indicator(if self.heater2_on {
@@ -429,16 +835,12 @@ impl TelescopeState {
                                 } else {
                                     IndicatorColor::Red
                                 }),
-                                text("Manual Override:"),
-                                indicator(if self.heater2_manual_override {
-                                    IndicatorColor::Green
-                                } else {
-                                    IndicatorColor::Red
-                                }),
+                                self.mode_indicator(1),
                             ]
                             .align_y(Alignment::Center)
                             .spacing(10)
-                            .width(Length::Fill)
+                            .width(Length::Fill),
+                            self.pid_view(1),
                         ]
                         .spacing(10),
                         ContainerLayer::Layer2
@@ -452,7 +854,7 @@ impl TelescopeState {
                                         .align_x(Alignment::Center)
                                         .align_y(Alignment::Center),
                                     Some(Message::Heater3Enable),
-                                    true,
+                                    !self.dew_control.auto_heaters[2],
                                 )
                                 .width(Length::Fixed(80.0)),
                                 sidereal_button(
@@ -460,9 +862,11 @@ impl TelescopeState {
                                         .align_x(Alignment::Center)
                                         .align_y(Alignment::Center),
                                     Some(Message::Heater3Disable),
-                                    true,
+                                    !self.dew_control.auto_heaters[2],
                                 )
                                 .width(Length::Fixed(80.0)),
+                                checkbox("Auto", self.dew_control.auto_heaters[2])
+                                    .on_toggle(|auto| Message::HeaterAutoToggled(2, auto)),
                                 Space::with_width(Length::Fill),
                                 text("Enabled:"),
                                 indicator(if self.heater3_on {
@@ -476,16 +880,12 @@ impl TelescopeState {
                                 } else {
                                     IndicatorColor::Red
                                 }),
-                                text("Manual Override:"),
-                                indicator(if self.heater3_manual_override {
-                                    IndicatorColor::Green
-                                } else {
-                                    IndicatorColor::Red
-                                }),
+                                self.mode_indicator(2),
                             ]
                             .align_y(Alignment::Center)
                             .spacing(10)
-                            .width(Length::Fill)
+                            .width(Length::Fill),
+                            self.pid_view(2),
                         ]
                         .spacing(10),
                         ContainerLayer::Layer2
@@ -0,0 +1,460 @@
+use iced::widget::canvas::{self, Canvas, Geometry, Path, Program, Stroke, Text};
+use iced::widget::image::Handle;
+use iced::widget::{column, container, row, text};
+use iced::{
+    alignment, mouse, Alignment, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme,
+};
+use std::sync::Arc;
+
+use crate::fits::FitsImage;
+use crate::gui::styles::button_style::sidereal_button;
+use crate::gui::styles::container_style::{content_container, ContainerLayer};
+use crate::gui::styles::{ACCENT_COLOR, BACKGROUND_COLOR, TEXT_COLOR};
+
+/// Pixels looked at when picking the stretch, at most
+const SAMPLE_SIZE: usize = 250_000;
+const HISTOGRAM_BINS: usize = 256;
+/// Steps in the lookup table from pixel value to display brightness
+const LUT_SIZE: usize = 4096;
+/// Where the asinh stretch puts the sky background
+const ASINH_BACKGROUND: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stretch {
+    Linear,
+    Asinh,
+}
+
+/// Messages produced by the ImagePreview component.
+#[derive(Debug, Clone)]
+pub enum ImagePreviewMessage {
+    SetStretch(Stretch),
+    /// Show the frame 1:1, centred on this image pixel
+    ZoomTo(Point),
+    ZoomToFit,
+}
+
+/// Display range and curve picked from the frame's statistics
+#[derive(Debug, Clone, Copy)]
+struct Levels {
+    black: f32,
+    white: f32,
+    /// Asinh softening; 0 for a straight line
+    beta: f32,
+}
+
+impl Levels {
+    /// Display brightness (0..1) of a pixel value
+    fn apply(&self, value: f32) -> f32 {
+        let t = ((value - self.black) / (self.white - self.black)).clamp(0.0, 1.0);
+        if self.beta > 0.0 {
+            (self.beta * t).asinh() / self.beta.asinh()
+        } else {
+            t
+        }
+    }
+}
+
+/// Value at fraction `p` of the way through sorted `values`
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    let index = ((sorted.len() - 1) as f32 * p).round() as usize;
+    sorted[index]
+}
+
+/// The asinh softening that brings `background` (0..1 of the display range)
+/// up to ASINH_BACKGROUND
+fn asinh_beta(background: f32) -> f32 {
+    if background <= 0.0 || background >= ASINH_BACKGROUND {
+        return 0.0;
+    }
+    // the curve rises at every point as beta grows, so bisect (in log space)
+    let (mut low, mut high) = (1e-3f32.ln(), 1e6f32.ln());
+    for _ in 0..40 {
+        let beta = ((low + high) / 2.0).exp();
+        if (beta * background).asinh() / beta.asinh() < ASINH_BACKGROUND {
+            low = (low + high) / 2.0;
+        } else {
+            high = (low + high) / 2.0;
+        }
+    }
+    ((low + high) / 2.0).exp()
+}
+
+/// Auto-stretch levels from a sorted sample of the frame
+fn auto_levels(sorted: &[f32], stretch: Stretch) -> Levels {
+    let (black, white, beta) = match stretch {
+        // clip the faintest and brightest 0.1%
+        Stretch::Linear => (percentile(sorted, 0.001), percentile(sorted, 0.999), 0.0),
+        // black just under the background noise, everything up to the
+        // brightest star kept, the background lifted to be seen
+        Stretch::Asinh => {
+            let median = percentile(sorted, 0.5);
+            let mut deviations: Vec<f32> = sorted.iter().map(|v| (v - median).abs()).collect();
+            deviations.sort_unstable_by(f32::total_cmp);
+            let sigma = 1.4826 * percentile(&deviations, 0.5);
+            let black = (median - 2.8 * sigma).max(sorted[0]);
+            let white = sorted[sorted.len() - 1];
+            let background = (median - black) / (white - black).max(f32::EPSILON);
+            (black, white, asinh_beta(background))
+        }
+    };
+    Levels {
+        black,
+        white: if white > black { white } else { black + 1.0 },
+        beta,
+    }
+}
+
+/// The last frame taken, stretched for display, with its histogram
+pub struct ImagePreview {
+    image: Option<Arc<FitsImage>>,
+    /// Up to SAMPLE_SIZE of the frame's pixels, sorted
+    sample: Vec<f32>,
+    stretch: Stretch,
+    levels: Levels,
+    handle: Option<Handle>,
+    histogram: Vec<u32>,
+    /// Image pixel at the centre of the view when shown 1:1; `None` to fit
+    zoom: Option<Point>,
+}
+
+impl Default for ImagePreview {
+    fn default() -> Self {
+        Self {
+            image: None,
+            sample: Vec::new(),
+            stretch: Stretch::Asinh,
+            levels: Levels {
+                black: 0.0,
+                white: 1.0,
+                beta: 0.0,
+            },
+            handle: None,
+            histogram: vec![0; HISTOGRAM_BINS],
+            zoom: None,
+        }
+    }
+}
+
+impl ImagePreview {
+    /// Show a new frame, keeping the stretch mode and zoom
+    pub fn set_image(&mut self, image: Arc<FitsImage>) {
+        let stride = (image.pixels.len() / SAMPLE_SIZE).max(1);
+        self.sample = image
+            .pixels
+            .iter()
+            .step_by(stride)
+            .copied()
+            .filter(|v| v.is_finite())
+            .collect();
+        self.sample.sort_unstable_by(f32::total_cmp);
+
+        self.histogram = vec![0; HISTOGRAM_BINS];
+        if let (Some(&min), Some(&max)) = (self.sample.first(), self.sample.last()) {
+            let scale = (HISTOGRAM_BINS - 1) as f32 / (max - min).max(f32::EPSILON);
+            for &value in image.pixels.iter().filter(|v| v.is_finite()) {
+                let bin = ((value - min) * scale).clamp(0.0, (HISTOGRAM_BINS - 1) as f32);
+                self.histogram[bin as usize] += 1;
+            }
+        }
+
+        if self
+            .zoom
+            .is_some_and(|c| c.x >= image.width as f32 || c.y >= image.height as f32)
+        {
+            self.zoom = None;
+        }
+        self.image = Some(image);
+        self.render();
+    }
+
+    pub fn update(&mut self, message: ImagePreviewMessage) {
+        match message {
+            ImagePreviewMessage::SetStretch(stretch) => {
+                self.stretch = stretch;
+                self.render();
+            }
+            ImagePreviewMessage::ZoomTo(center) => self.zoom = Some(center),
+            ImagePreviewMessage::ZoomToFit => self.zoom = None,
+        }
+    }
+
+    /// Redraw the frame with the current stretch
+    fn render(&mut self) {
+        let Some(image) = &self.image else {
+            return;
+        };
+        if self.sample.is_empty() {
+            self.handle = None;
+            return;
+        }
+        self.levels = auto_levels(&self.sample, self.stretch);
+
+        let lut: Vec<u8> = (0..LUT_SIZE)
+            .map(|i| {
+                let t = i as f32 / (LUT_SIZE - 1) as f32;
+                let value = self.levels.black + t * (self.levels.white - self.levels.black);
+                (self.levels.apply(value) * 255.0).round() as u8
+            })
+            .collect();
+        let scale = (LUT_SIZE - 1) as f32 / (self.levels.white - self.levels.black);
+        let mut rgba = Vec::with_capacity(image.pixels.len() * 4);
+        for &value in &image.pixels {
+            let index = ((value - self.levels.black) * scale).clamp(0.0, (LUT_SIZE - 1) as f32);
+            let grey = lut[index as usize];
+            rgba.extend_from_slice(&[grey, grey, grey, 255]);
+        }
+        self.handle = Some(Handle::from_rgba(
+            image.width as u32,
+            image.height as u32,
+            rgba,
+        ));
+    }
+
+    pub fn view(&self) -> Element<'_, ImagePreviewMessage> {
+        let (Some(image), Some(handle)) = (&self.image, &self.handle) else {
+            return content_container(
+                container(text("No frame yet"))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_x(Alignment::Center)
+                    .align_y(Alignment::Center),
+                ContainerLayer::Layer1,
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into();
+        };
+
+        let frame = Canvas::new(FrameCanvas {
+            image,
+            handle,
+            zoom: self.zoom,
+        })
+        .width(Length::Fill)
+        .height(Length::Fill);
+        let histogram = Canvas::new(HistogramCanvas {
+            histogram: &self.histogram,
+            range: (
+                self.sample.first().copied().unwrap_or(0.0),
+                self.sample.last().copied().unwrap_or(1.0),
+            ),
+            levels: self.levels,
+        })
+        .width(Length::Fill)
+        .height(Length::Fixed(80.0));
+
+        let zoom_button = match self.zoom {
+            Some(_) => sidereal_button("Fit", Some(ImagePreviewMessage::ZoomToFit), true),
+            None => sidereal_button(
+                "1:1",
+                Some(ImagePreviewMessage::ZoomTo(Point::new(
+                    image.width as f32 / 2.0,
+                    image.height as f32 / 2.0,
+                ))),
+                true,
+            ),
+        };
+        let controls = row![
+            sidereal_button(
+                "Linear",
+                Some(ImagePreviewMessage::SetStretch(Stretch::Linear)),
+                self.stretch != Stretch::Linear,
+            ),
+            sidereal_button(
+                "Asinh",
+                Some(ImagePreviewMessage::SetStretch(Stretch::Asinh)),
+                self.stretch != Stretch::Asinh,
+            ),
+            zoom_button,
+            text(format!(
+                "{}×{}, black {:.0}, white {:.0}",
+                image.width, image.height, self.levels.black, self.levels.white
+            ))
+            .size(12),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10);
+
+        content_container(
+            column![frame, histogram, controls].spacing(10),
+            ContainerLayer::Layer1,
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// The stretched frame, with the value of the pixel under the cursor.
+/// Left-clicking shows it 1:1 around the clicked pixel, right-clicking fits it
+/// to the view again.
+struct FrameCanvas<'a> {
+    image: &'a FitsImage,
+    handle: &'a Handle,
+    zoom: Option<Point>,
+}
+
+impl FrameCanvas<'_> {
+    /// Where the frame is drawn within `size`, and screen pixels per image pixel
+    fn placement(&self, size: Size) -> (Rectangle, f32) {
+        let (width, height) = (self.image.width as f32, self.image.height as f32);
+        match self.zoom {
+            Some(center) => (
+                Rectangle::new(
+                    Point::new(size.width / 2.0 - center.x, size.height / 2.0 - center.y),
+                    Size::new(width, height),
+                ),
+                1.0,
+            ),
+            None => {
+                let scale = (size.width / width).min(size.height / height);
+                let shown = Size::new(width * scale, height * scale);
+                (
+                    Rectangle::new(
+                        Point::new(
+                            (size.width - shown.width) / 2.0,
+                            (size.height - shown.height) / 2.0,
+                        ),
+                        shown,
+                    ),
+                    scale,
+                )
+            }
+        }
+    }
+
+    /// Image pixel under the cursor, if it's over the frame
+    fn pixel_at(&self, bounds: Rectangle, cursor: mouse::Cursor) -> Option<(usize, usize)> {
+        let position = cursor.position_in(bounds)?;
+        let (shown, scale) = self.placement(bounds.size());
+        let x = (position.x - shown.x) / scale;
+        let y = (position.y - shown.y) / scale;
+        if x < 0.0 || y < 0.0 || x >= self.image.width as f32 || y >= self.image.height as f32 {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+}
+
+impl Program<ImagePreviewMessage> for FrameCanvas<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), BACKGROUND_COLOR);
+        let (shown, _) = self.placement(bounds.size());
+        frame.draw_image(shown, self.handle);
+
+        if let Some((x, y)) = self.pixel_at(bounds, cursor) {
+            let value = self.image.pixel(x, y).unwrap_or(f32::NAN);
+            let readout = Point::new(8.0, bounds.height - 28.0);
+            frame.fill_rectangle(
+                readout,
+                Size::new(200.0, 20.0),
+                Color::from_rgba(0.0, 0.0, 0.0, 0.6),
+            );
+            frame.fill_text(Text {
+                content: format!("x {x}, y {y}: {value:.1}"),
+                position: Point::new(readout.x + 6.0, readout.y + 10.0),
+                size: iced::Pixels(12.0),
+                color: Color::WHITE,
+                vertical_alignment: alignment::Vertical::Center,
+                ..Text::default()
+            });
+        }
+        vec![frame.into_geometry()]
+    }
+
+    fn update(
+        &self,
+        _state: &mut Self::State,
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> (canvas::event::Status, Option<ImagePreviewMessage>) {
+        let Some((x, y)) = self.pixel_at(bounds, cursor) else {
+            return (canvas::event::Status::Ignored, None);
+        };
+        let message = match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                ImagePreviewMessage::ZoomTo(Point::new(x as f32, y as f32))
+            }
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)) => {
+                ImagePreviewMessage::ZoomToFit
+            }
+            _ => return (canvas::event::Status::Ignored, None),
+        };
+        (canvas::event::Status::Captured, Some(message))
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        if self.pixel_at(bounds, cursor).is_some() {
+            mouse::Interaction::Crosshair
+        } else {
+            mouse::Interaction::default()
+        }
+    }
+}
+
+/// Counts of pixel values from darkest to brightest (log scale), with the
+/// stretch's black and white points marked
+struct HistogramCanvas<'a> {
+    histogram: &'a [u32],
+    /// Pixel values of the first and last bins
+    range: (f32, f32),
+    levels: Levels,
+}
+
+impl Program<ImagePreviewMessage> for HistogramCanvas<'_> {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        frame.fill_rectangle(Point::ORIGIN, bounds.size(), BACKGROUND_COLOR);
+
+        let peak = self.histogram.iter().copied().max().unwrap_or(0);
+        if peak > 0 {
+            let peak = (peak as f32).ln_1p();
+            let bar_width = bounds.width / self.histogram.len() as f32;
+            for (i, &count) in self.histogram.iter().enumerate() {
+                let height = (count as f32).ln_1p() / peak * bounds.height;
+                frame.fill_rectangle(
+                    Point::new(i as f32 * bar_width, bounds.height - height),
+                    Size::new(bar_width, height),
+                    TEXT_COLOR,
+                );
+            }
+        }
+
+        let (min, max) = self.range;
+        let span = (max - min).max(f32::EPSILON);
+        for level in [self.levels.black, self.levels.white] {
+            let x = ((level - min) / span).clamp(0.0, 1.0) * bounds.width;
+            frame.stroke(
+                &Path::line(Point::new(x, 0.0), Point::new(x, bounds.height)),
+                Stroke::default().with_width(1.5).with_color(ACCENT_COLOR),
+            );
+        }
+        vec![frame.into_geometry()]
+    }
+}
@@ -37,20 +37,52 @@ impl PlotSeries {
     }
 }
 
+/// A constant level drawn dashed across the plot, e.g. a setpoint
+#[derive(Debug, Clone)]
+pub struct ReferenceLine {
+    pub name: String,
+    pub color: Color,
+    /// Not drawn while unset
+    pub value: Option<f64>,
+}
+
 /// Plot data container - stores only data, no rendering state
 #[derive(Debug, Clone)]
 pub struct LivePlotData {
     pub series: Vec<PlotSeries>,
+    pub references: Vec<ReferenceLine>,
     pub max_points: usize,
     pub padding: f32,
+    /// Span of X shown, back from the latest point
+    pub x_window: f64,
+    /// Appended to the X-axis labels
+    pub x_unit: String,
 }
 
 impl LivePlotData {
     pub fn new(max_points: usize, padding: f32) -> Self {
         Self {
             series: Vec::new(),
+            references: Vec::new(),
             max_points,
             padding,
+            x_window: 1800.0, // 30 minutes
+            x_unit: "s".to_owned(),
+        }
+    }
+
+    /// Plot against something other than time, e.g. focuser position with
+    /// an unlimited window and no unit
+    pub fn with_x_axis(mut self, window: f64, unit: impl Into<String>) -> Self {
+        self.x_window = window;
+        self.x_unit = unit.into();
+        self
+    }
+
+    /// Drop every series' points, keeping the series
+    pub fn clear(&mut self) {
+        for series in &mut self.series {
+            series.data.clear();
         }
     }
 
@@ -60,7 +92,6 @@ impl LivePlotData {
         id
     }
 
-    #[allow(dead_code)]
     pub fn series_mut(&mut self, index: usize) -> Option<&mut PlotSeries> {
         self.series.get_mut(index)
     }
@@ -70,6 +101,23 @@ impl LivePlotData {
             series.add_point(point, self.max_points);
         }
     }
+
+    /// Add a reference line, unset until given a value
+    pub fn add_reference(&mut self, name: impl Into<String>, color: Color) -> usize {
+        let id = self.references.len();
+        self.references.push(ReferenceLine {
+            name: name.into(),
+            color,
+            value: None,
+        });
+        id
+    }
+
+    pub fn set_reference(&mut self, index: usize, value: Option<f64>) {
+        if let Some(reference) = self.references.get_mut(index) {
+            reference.value = value;
+        }
+    }
 }
 
 /// The canvas program
@@ -105,8 +153,6 @@ impl<Message> Program<Message> for LivePlotProgram {
         let plot_x = left_padding;
         let plot_y = top_padding;
 
-        const TIME_WINDOW: f64 = 1800.0; // 30 minutes
-
         // Find max timestamp
         let mut absolute_max_time = f64::NEG_INFINITY;
         let mut has_any_data = false;
@@ -120,7 +166,7 @@ impl<Message> Program<Message> for LivePlotProgram {
         }
 
         let window_start = if has_any_data && absolute_max_time != f64::NEG_INFINITY {
-            absolute_max_time - TIME_WINDOW
+            absolute_max_time - self.data.x_window
         } else {
             0.0
         };
@@ -149,6 +195,11 @@ impl<Message> Program<Message> for LivePlotProgram {
                 if min_time == f64::INFINITY {
                     (0.0, 100.0, window_start.max(0.0), absolute_max_time)
                 } else {
+                    // keep the reference lines in view
+                    for value in self.data.references.iter().filter_map(|r| r.value) {
+                        min_val = min_val.min(value);
+                        max_val = max_val.max(value);
+                    }
                     let val_range = max_val - min_val;
                     let val_padding = if val_range > 0.0 {
                         val_range * 0.1
@@ -246,6 +297,31 @@ impl<Message> Program<Message> for LivePlotProgram {
             }
         }
 
+        // Draw reference lines
+        for reference in &self.data.references {
+            let Some(value) = reference.value else {
+                continue;
+            };
+            let val_range = max_val - min_val;
+            if val_range <= 0.0 || value < min_val || value > max_val {
+                continue;
+            }
+            let y = plot_y + plot_height * (1.0 - ((value - min_val) / val_range) as f32);
+            let line = Path::line(Point::new(plot_x, y), Point::new(plot_x + plot_width, y));
+            plot_frame.stroke(
+                &line,
+                Stroke {
+                    line_dash: canvas::LineDash {
+                        segments: &[6.0, 4.0],
+                        offset: 0,
+                    },
+                    ..Stroke::default()
+                        .with_width(1.5)
+                        .with_color(reference.color)
+                },
+            );
+        }
+
         // Axis labels
         let label_color = Color::from_rgba(0.8, 0.8, 0.8, 1.0);
         let label_size = iced::Pixels(12.0);
@@ -271,7 +347,7 @@ impl<Message> Program<Message> for LivePlotProgram {
             let time = min_time + (max_time - min_time) * (i as f64 / 5.0);
             let x = plot_x + (plot_width * (i as f32 / 5.0));
             let mut text = Text {
-                content: format!("{:.1}s", time),
+                content: format!("{:.1}{}", time, self.data.x_unit),
                 position: Point::new(x, plot_y + plot_height + 15.0),
                 size: label_size,
                 color: label_color,
@@ -308,6 +384,35 @@ impl<Message> Program<Message> for LivePlotProgram {
 
             legend_y += 18.0;
         }
+        for reference in &self.data.references {
+            if reference.value.is_none() {
+                continue;
+            }
+
+            // Dash for a dashed line
+            let indicator = Path::line(
+                Point::new(legend_x - 5.0, legend_y),
+                Point::new(legend_x + 5.0, legend_y),
+            );
+            plot_frame.stroke(
+                &indicator,
+                Stroke::default()
+                    .with_width(2.0)
+                    .with_color(reference.color),
+            );
+
+            let mut text = Text {
+                content: reference.name.clone(),
+                position: Point::new(legend_x + 10.0, legend_y),
+                size: label_size,
+                color: label_color,
+                ..Text::default()
+            };
+            text.vertical_alignment = alignment::Vertical::Center;
+            plot_frame.fill_text(text);
+
+            legend_y += 18.0;
+        }
 
         let plot_geom = plot_frame.into_geometry();
         vec![grid, plot_geom]
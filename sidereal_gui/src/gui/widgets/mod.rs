@@ -1,5 +1,6 @@
 // pub(crate) mod dial;
 pub(crate) mod allsky;
+pub(crate) mod image_preview;
 pub(crate) mod indicator;
 pub(crate) mod live_plot;
 pub(crate) mod mount_steer_button;
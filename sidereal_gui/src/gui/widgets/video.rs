@@ -309,8 +309,8 @@ impl IpCamera {
                     .saturating_mul(height as usize)
                     .saturating_mul(4);
                 if rgba.len() != expected || width == 0 || height == 0 {
-                    eprintln!(
-                        "[ui] dropping malformed frame: got {}, expected {} ({}x{})",
+                    tracing::warn!(
+                        "dropping malformed frame: got {}, expected {} ({}x{})",
                         rgba.len(),
                         expected,
                         width,
@@ -324,7 +324,7 @@ impl IpCamera {
                     match std::panic::catch_unwind(|| Handle::from_rgba(width, height, rgba)) {
                         Ok(h) => h,
                         Err(_) => {
-                            eprintln!("[ui] Handle::from_rgba panicked; dropping frame");
+                            tracing::warn!("Handle::from_rgba panicked; dropping frame");
                             return; // drop
                         }
                     };
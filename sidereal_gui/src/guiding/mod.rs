@@ -0,0 +1,352 @@
+// guiding/mod.rs
+
+use crate::{
+    analysis::{self, Star},
+    fits::FitsImage,
+    indi_handler::mount::GuideDirection,
+    model::{SiderealError, SiderealResult},
+};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// How far (pixels) the guide star is looked for around where it last was
+const SEARCH_RADIUS: usize = 16;
+/// Pixels around its peak measured as part of the guide star
+const STAR_RADIUS: usize = 8;
+/// How far (in background sigmas) above the background the star must peak
+const DETECTION_SIGMA: f32 = 5.0;
+/// Star movement (pixels) each calibration axis aims for
+pub const CALIBRATION_DISTANCE: f64 = 15.0;
+/// Pulses per axis before calibration settles for the distance it has
+pub const MAX_CALIBRATION_STEPS: u32 = 12;
+/// Less movement than this (pixels) means the axis isn't moving the star
+const MIN_CALIBRATION_DISTANCE: f64 = 3.0;
+/// Axes closer together than this (degrees) can't be told apart
+const MIN_AXIS_ANGLE: f64 = 30.0;
+
+/// The star to guide on: the brightest one with room around it to move
+pub fn pick_star(image: &FitsImage) -> Option<Star> {
+    let margin = (2 * SEARCH_RADIUS) as f64;
+    let stars = analysis::detect_stars(image);
+    stars
+        .iter()
+        .find(|star| {
+            star.x > margin
+                && star.y > margin
+                && star.x < image.width as f64 - margin
+                && star.y < image.height as f64 - margin
+        })
+        .or(stars.first())
+        .copied()
+}
+
+/// The guide star near (`x`, `y`), if it's still there
+pub fn find_star_near(image: &FitsImage, x: f64, y: f64) -> Option<Star> {
+    let (sky, noise) = analysis::background(image);
+    let threshold = sky + DETECTION_SIGMA * noise.max(f32::EPSILON);
+    let (cx, cy) = (x.round().max(0.0) as usize, y.round().max(0.0) as usize);
+
+    // brightest pixel in the search box
+    let mut peak: Option<(usize, usize, f32)> = None;
+    for py in cy.saturating_sub(SEARCH_RADIUS)..(cy + SEARCH_RADIUS + 1).min(image.height) {
+        for px in cx.saturating_sub(SEARCH_RADIUS)..(cx + SEARCH_RADIUS + 1).min(image.width) {
+            let Some(value) = image.pixel(px, py) else {
+                continue;
+            };
+            let brighter = !matches!(peak, Some((_, _, brightest)) if brightest >= value);
+            if value > threshold && brighter {
+                peak = Some((px, py, value));
+            }
+        }
+    }
+    let (px, py, _) = peak?;
+    analysis::measure_star(image, px, py, STAR_RADIUS, sky)
+}
+
+/// How the mount moves the guide star, measured by pulsing each axis
+#[derive(Debug, Clone, Copy)]
+pub struct Calibration {
+    /// Star movement (pixels) per millisecond of West pulse
+    ra: (f64, f64),
+    /// Star movement (pixels) per millisecond of North pulse
+    dec: (f64, f64),
+}
+
+impl Calibration {
+    /// From the star's movement over `ra_millis` of West pulses and
+    /// `dec_millis` of North pulses. Errors if either axis barely moved the
+    /// star or they moved it the same way.
+    pub fn new(
+        ra_moved: (f64, f64),
+        ra_millis: f64,
+        dec_moved: (f64, f64),
+        dec_millis: f64,
+    ) -> SiderealResult<Calibration> {
+        let distance = |moved: (f64, f64)| moved.0.hypot(moved.1);
+        if distance(ra_moved) < MIN_CALIBRATION_DISTANCE {
+            return Err(SiderealError::ServerError(
+                "RA pulses didn't move the star; check the mount's guide rate".to_owned(),
+            ));
+        }
+        if distance(dec_moved) < MIN_CALIBRATION_DISTANCE {
+            return Err(SiderealError::ServerError(
+                "Dec pulses didn't move the star; check the mount's guide rate".to_owned(),
+            ));
+        }
+        let calibration = Calibration {
+            ra: (ra_moved.0 / ra_millis, ra_moved.1 / ra_millis),
+            dec: (dec_moved.0 / dec_millis, dec_moved.1 / dec_millis),
+        };
+        let angle = calibration.axis_angle();
+        if !(MIN_AXIS_ANGLE..=180.0 - MIN_AXIS_ANGLE).contains(&angle) {
+            return Err(SiderealError::ServerError(format!(
+                "RA and Dec moved the star almost the same way ({angle:.0}° apart)"
+            )));
+        }
+        Ok(calibration)
+    }
+
+    /// Star movement (pixels per second) at guide rate in RA
+    pub fn ra_rate(&self) -> f64 {
+        self.ra.0.hypot(self.ra.1) * 1000.0
+    }
+
+    /// Star movement (pixels per second) at guide rate in Dec
+    pub fn dec_rate(&self) -> f64 {
+        self.dec.0.hypot(self.dec.1) * 1000.0
+    }
+
+    /// Angle (degrees) between the RA and Dec axes on the sensor; 90 for a
+    /// well-behaved mount
+    pub fn axis_angle(&self) -> f64 {
+        let cross = self.ra.0 * self.dec.1 - self.ra.1 * self.dec.0;
+        let dot = self.ra.0 * self.dec.0 + self.ra.1 * self.dec.1;
+        cross.abs().atan2(dot).to_degrees()
+    }
+
+    /// Milliseconds of West and North pulse that would move the star by
+    /// (`dx`, `dy`)
+    fn pulses_for(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let det = self.ra.0 * self.dec.1 - self.ra.1 * self.dec.0;
+        (
+            (dx * self.dec.1 - dy * self.dec.0) / det,
+            (self.ra.0 * dy - self.ra.1 * dx) / det,
+        )
+    }
+
+    /// The star's offset (`dx`, `dy`) from the lock position split into RA
+    /// and Dec (pixels along each axis, positive West and North)
+    pub fn errors(&self, dx: f64, dy: f64) -> (f64, f64) {
+        let (west, north) = self.pulses_for(dx, dy);
+        (
+            west * self.ra_rate() / 1000.0,
+            north * self.dec_rate() / 1000.0,
+        )
+    }
+
+    /// Pulses that bring the star back from (`dx`, `dy`) off the lock
+    /// position
+    pub fn corrections(
+        &self,
+        dx: f64,
+        dy: f64,
+        settings: &CorrectionSettings,
+    ) -> Vec<(GuideDirection, f64)> {
+        let (west, north) = self.pulses_for(dx, dy);
+        let (ra_error, dec_error) = self.errors(dx, dy);
+        let mut pulses = Vec::new();
+        if ra_error.abs() >= settings.min_move {
+            let millis = (west.abs() * settings.ra_aggressiveness).min(settings.max_pulse);
+            let direction = if west > 0.0 {
+                GuideDirection::East
+            } else {
+                GuideDirection::West
+            };
+            pulses.push((direction, millis));
+        }
+        if dec_error.abs() >= settings.min_move {
+            let millis = (north.abs() * settings.dec_aggressiveness).min(settings.max_pulse);
+            let direction = if north > 0.0 {
+                GuideDirection::South
+            } else {
+                GuideDirection::North
+            };
+            pulses.push((direction, millis));
+        }
+        pulses
+    }
+}
+
+/// How hard corrections push back
+#[derive(Debug, Clone, Copy)]
+pub struct CorrectionSettings {
+    /// Fraction of each error corrected per frame
+    pub ra_aggressiveness: f64,
+    pub dec_aggressiveness: f64,
+    /// Errors smaller than this (pixels) are left alone as seeing
+    pub min_move: f64,
+    /// Longest pulse (milliseconds) sent at once
+    pub max_pulse: f64,
+}
+
+/// A random offset of up to `amount` pixels each way, to move the lock
+/// position by between frames
+pub fn dither_offset(amount: f64) -> (f64, f64) {
+    // a fresh RandomState is randomly keyed, which is all the randomness needed
+    let random = || {
+        let unit = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        (unit * 2.0 - 1.0) * amount
+    };
+    (random(), random())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GuideDirection::*;
+
+    /// Full corrections for anything over 0.1 pixel, up to 5 s
+    const SETTINGS: CorrectionSettings = CorrectionSettings {
+        ra_aggressiveness: 1.0,
+        dec_aggressiveness: 1.0,
+        min_move: 0.1,
+        max_pulse: 5000.0,
+    };
+
+    /// A calibration from 2 s pulses, West moving the star 20 pixels along
+    /// `angle` degrees and North 10 pixels a quarter turn on; the other way
+    /// round when `mirrored`
+    fn calibrate(angle: f64, mirrored: bool) -> Calibration {
+        let (sin, cos) = angle.to_radians().sin_cos();
+        let ra = (20.0 * cos, 20.0 * sin);
+        let dec = if mirrored {
+            (10.0 * sin, -10.0 * cos)
+        } else {
+            (-10.0 * sin, 10.0 * cos)
+        };
+        Calibration::new(ra, 2000.0, dec, 2000.0).unwrap()
+    }
+
+    /// Where `west` and `north` milliseconds of pulses move the star
+    fn drift(calibration: &Calibration, west: f64, north: f64) -> (f64, f64) {
+        (
+            calibration.ra.0 * west + calibration.dec.0 * north,
+            calibration.ra.1 * west + calibration.dec.1 * north,
+        )
+    }
+
+    fn assert_pulses(actual: &[(GuideDirection, f64)], expected: &[(GuideDirection, f64)]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+        for (&(direction, millis), &(expected_direction, expected_millis)) in
+            actual.iter().zip(expected)
+        {
+            assert_eq!(direction, expected_direction, "{actual:?}");
+            assert!((millis - expected_millis).abs() < 1e-6, "{actual:?}");
+        }
+    }
+
+    #[test]
+    fn test_calibration_rates() {
+        for mirrored in [false, true] {
+            let calibration = calibrate(30.0, mirrored);
+            assert!((calibration.ra_rate() - 10.0).abs() < 1e-9);
+            assert!((calibration.dec_rate() - 5.0).abs() < 1e-9);
+            assert!((calibration.axis_angle() - 90.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_calibration_rejects_bad_axes() {
+        // RA barely moved the star
+        assert!(Calibration::new((1.0, 1.0), 2000.0, (0.0, 10.0), 2000.0).is_err());
+        // Dec barely moved the star
+        assert!(Calibration::new((20.0, 0.0), 2000.0, (0.0, -2.0), 2000.0).is_err());
+        // both moved it the same way, or straight back
+        assert!(Calibration::new((20.0, 0.0), 2000.0, (10.0, 2.0), 2000.0).is_err());
+        assert!(Calibration::new((20.0, 0.0), 2000.0, (-10.0, -2.0), 2000.0).is_err());
+        // 45° apart is enough
+        assert!(Calibration::new((20.0, 0.0), 2000.0, (10.0, 10.0), 2000.0).is_ok());
+    }
+
+    #[test]
+    fn test_corrections_undo_drift() {
+        for angle in [0.0, 30.0, 200.0] {
+            let calibration = calibrate(angle, false);
+            // the star drifted as 1 s of West and 0.4 s of South would move it
+            let (dx, dy) = drift(&calibration, 1000.0, -400.0);
+            let (ra_error, dec_error) = calibration.errors(dx, dy);
+            assert!((ra_error - 10.0).abs() < 1e-9 && (dec_error + 2.0).abs() < 1e-9);
+            assert_pulses(
+                &calibration.corrections(dx, dy, &SETTINGS),
+                &[(East, 1000.0), (North, 400.0)],
+            );
+
+            let (dx, dy) = drift(&calibration, -300.0, 600.0);
+            assert_pulses(
+                &calibration.corrections(dx, dy, &SETTINGS),
+                &[(West, 300.0), (South, 600.0)],
+            );
+        }
+    }
+
+    #[test]
+    fn test_corrections_with_mirrored_calibration() {
+        let normal = calibrate(30.0, false);
+        let mirrored = calibrate(30.0, true);
+        // the same offset on the sensor is the opposite way in Dec when the
+        // image is mirrored, but the same way in RA
+        let (dx, dy) = drift(&normal, 500.0, 800.0);
+        assert_pulses(
+            &normal.corrections(dx, dy, &SETTINGS),
+            &[(East, 500.0), (South, 800.0)],
+        );
+        assert_pulses(
+            &mirrored.corrections(dx, dy, &SETTINGS),
+            &[(East, 500.0), (North, 800.0)],
+        );
+
+        let (dx, dy) = drift(&mirrored, -500.0, 800.0);
+        assert_pulses(
+            &mirrored.corrections(dx, dy, &SETTINGS),
+            &[(West, 500.0), (South, 800.0)],
+        );
+    }
+
+    #[test]
+    fn test_corrections_settings() {
+        let calibration = calibrate(0.0, false);
+        let (dx, dy) = drift(&calibration, 2000.0, 100.0);
+        let settings = CorrectionSettings {
+            ra_aggressiveness: 0.5,
+            dec_aggressiveness: 0.8,
+            ..SETTINGS
+        };
+        assert_pulses(
+            &calibration.corrections(dx, dy, &settings),
+            &[(East, 1000.0), (South, 80.0)],
+        );
+
+        // longer pulses are cut short
+        let settings = CorrectionSettings {
+            max_pulse: 1500.0,
+            ..SETTINGS
+        };
+        assert_pulses(
+            &calibration.corrections(dx, dy, &settings),
+            &[(East, 1500.0), (South, 100.0)],
+        );
+
+        // 100 ms of Dec is only 0.5 pixel, under the minimum move
+        let settings = CorrectionSettings {
+            min_move: 1.0,
+            ..SETTINGS
+        };
+        assert_pulses(
+            &calibration.corrections(dx, dy, &settings),
+            &[(East, 2000.0)],
+        );
+        assert!(calibration.corrections(0.0, 0.0, &SETTINGS).is_empty());
+    }
+}
@@ -1,4 +1,38 @@
+use super::{send_numbers, CONNECTED_DEVICES};
+use crate::model::{SiderealError, SiderealResult};
+use iced::futures::StreamExt;
 use indi::client::active_device::ActiveDevice;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::time;
+
+tokio::task_local! {
+    /// The camera commands go to inside `on_camera`, in place of the main one
+    static SELECTED_CAMERA: String;
+}
+
+/// How long past its duration an exposure may take to download before it's
+/// given up on
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+/// The warm-up ramp raises the target temperature this much at a time...
+const WARM_UP_STEP_C: f64 = 2.0;
+/// ...this often
+const WARM_UP_STEP_INTERVAL: Duration = Duration::from_secs(60);
+/// Cooler power (%) at or below which the sensor has reached ambient
+const WARM_UP_IDLE_POWER: f64 = 5.0;
+/// Where the ramp ends for cameras that don't report cooler power
+const WARM_UP_CEILING_C: f64 = 20.0;
+/// How close the sensor has to get to a step's setpoint before the next step
+const WARM_UP_TOLERANCE_C: f64 = 0.5;
+/// How often the sensor is read while waiting for it to reach a step
+const WARM_UP_POLL: Duration = Duration::from_secs(5);
+/// Longest a step waits for the sensor, so a camera that can't get there
+/// still warms up
+const WARM_UP_STEP_LIMIT: Duration = Duration::from_secs(300);
 
 /// Camera-specific helper functions
 ///
@@ -14,11 +48,346 @@ use indi::client::active_device::ActiveDevice;
 ///   - Distinguish between server disconnection and device disappearance
 ///   - Never fail or error out when device is missing
 ///
-/// Get the active camera device if available
-#[allow(dead_code)]
-pub fn get_camera() -> Option<ActiveDevice> {
-    // Note: This is a blocking read, but should be fine for occasional access
-    // For async access, use CONNECTED_DEVICES.read().await in async contexts
-    None // TODO: Implement when needed
+/// Get the active camera device if available, or the one `on_camera` picked
+async fn get_camera() -> SiderealResult<ActiveDevice> {
+    // cloned so the device list isn't held locked through a long exposure
+    let devices = CONNECTED_DEVICES.read().await;
+    if let Ok(name) = SELECTED_CAMERA.try_with(String::clone) {
+        for camera in devices.extra_cameras.iter().chain(&devices.guide_camera) {
+            if *camera.lock().await.get_name() == name {
+                return Ok(camera.clone());
+            }
+        }
+        return Err(SiderealError::ServerError(format!(
+            "Camera {name} not available"
+        )));
+    }
+    devices.camera.clone().ok_or_else(|| {
+        SiderealError::ServerError(
+            "Camera device not available. Please ensure the device is connected to the INDI server."
+                .to_owned(),
+        )
+    })
+}
+
+/// Get the active guide camera device if available
+async fn get_guide_camera() -> SiderealResult<ActiveDevice> {
+    let devices = CONNECTED_DEVICES.read().await;
+    devices.guide_camera.clone().ok_or_else(|| {
+        SiderealError::ServerError(
+            "Guide camera not available. Please ensure it is connected to the INDI server."
+                .to_owned(),
+        )
+    })
+}
+
+/// Run `commands` against the camera named `name` instead of the main imaging
+/// camera; `None` leaves them on the main camera
+pub async fn on_camera<F: Future>(name: Option<String>, commands: F) -> F::Output {
+    match name {
+        Some(name) => SELECTED_CAMERA.scope(name, commands).await,
+        None => commands.await,
+    }
+}
+
+/// Set the sensor gain
+pub async fn set_gain(gain: f64) -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    camera
+        .change("CCD_GAIN", vec![("GAIN", gain)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Setting gain failed: {:?}", e)))?;
+    Ok(())
+}
+
+/// Bin `binning`×`binning` pixels together
+pub async fn set_binning(binning: u32) -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    let binning = binning as f64;
+    camera
+        .change(
+            "CCD_BINNING",
+            vec![("HOR_BIN", binning), ("VER_BIN", binning)],
+        )
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Setting binning failed: {:?}", e)))?;
+    Ok(())
+}
+
+/// Set the sensor offset (black level)
+pub async fn set_offset(offset: f64) -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    camera
+        .change("CCD_OFFSET", vec![("OFFSET", offset)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Setting offset failed: {:?}", e)))?;
+    Ok(())
+}
+
+/// Turn the camera's filter wheel to `slot` (numbered from 1)
+pub async fn set_filter(slot: u32) -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    camera
+        .change("FILTER_SLOT", vec![("FILTER_SLOT_VALUE", slot as f64)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Changing filter failed: {:?}", e)))?;
+    Ok(())
+}
+
+/// Sensor size in unbinned pixels
+pub async fn sensor_size() -> SiderealResult<(u32, u32)> {
+    match (
+        read_number("CCD_INFO", "CCD_MAX_X").await,
+        read_number("CCD_INFO", "CCD_MAX_Y").await,
+    ) {
+        (Some(width), Some(height)) => Ok((width as u32, height as u32)),
+        _ => Err(SiderealError::ServerError(
+            "Camera hasn't reported its sensor size".to_owned(),
+        )),
+    }
+}
+
+/// Read out only part of the sensor, in unbinned pixels from the top left
+pub async fn set_subframe(x: u32, y: u32, width: u32, height: u32) -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    camera
+        .change(
+            "CCD_FRAME",
+            vec![
+                ("X", x as f64),
+                ("Y", y as f64),
+                ("WIDTH", width as f64),
+                ("HEIGHT", height as f64),
+            ],
+        )
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Setting subframe failed: {:?}", e)))?;
+    Ok(())
+}
+
+/// The part of the sensor being read out, as (x, y, width, height) in unbinned
+/// pixels
+pub async fn subframe() -> Option<(u32, u32, u32, u32)> {
+    Some((
+        read_number("CCD_FRAME", "X").await? as u32,
+        read_number("CCD_FRAME", "Y").await? as u32,
+        read_number("CCD_FRAME", "WIDTH").await? as u32,
+        read_number("CCD_FRAME", "HEIGHT").await? as u32,
+    ))
+}
+
+/// Read out the whole sensor again
+pub async fn reset_subframe() -> SiderealResult<()> {
+    let (width, height) = sensor_size().await?;
+    set_subframe(0, 0, width, height).await
+}
+
+/// Take one exposure of `seconds` and return the frame the driver sends back,
+/// as the file it came in (normally FITS)
+pub async fn expose(seconds: f64) -> SiderealResult<Arc<Vec<u8>>> {
+    expose_on(get_camera().await?, seconds).await
+}
+
+/// Take one exposure of `seconds` with the guide camera
+pub async fn guide_expose(seconds: f64) -> SiderealResult<Arc<Vec<u8>>> {
+    expose_on(get_guide_camera().await?, seconds).await
+}
+
+async fn expose_on(camera: ActiveDevice, seconds: f64) -> SiderealResult<Arc<Vec<u8>>> {
+    // frames only come over the connection once asked for
+    camera
+        .enable_blob(Some("CCD1"), indi::BlobEnable::Also)
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Enabling frames failed: {:?}", e)))?;
+    let blob_param = camera
+        .get_parameter("CCD1")
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    // the frame the driver sent last, to tell the new one from it
+    let previous = blob_param
+        .lock()
+        .await
+        .get_values::<HashMap<String, indi::Blob>>()
+        .ok()
+        .and_then(|blobs| blobs.get("CCD1")?.value.clone());
+    let mut changes = blob_param.subscribe().await;
+
+    camera
+        .change("CCD_EXPOSURE", vec![("CCD_EXPOSURE_VALUE", seconds)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Starting exposure failed: {:?}", e)))?;
+
+    // the exposure is done when a frame other than the last one arrives
+    let finished = async {
+        while let Some(Ok(param_arc)) = changes.next().await {
+            if let Ok(blobs) = param_arc.get_values::<HashMap<String, indi::Blob>>() {
+                if let Some(blob) = blobs.get("CCD1") {
+                    if let Some(data) = &blob.value {
+                        if !previous.as_ref().is_some_and(|old| Arc::ptr_eq(old, data)) {
+                            return Ok(data.clone());
+                        }
+                    }
+                }
+            }
+        }
+        Err(SiderealError::ServerError(
+            "Lost the camera during the exposure".to_owned(),
+        ))
+    };
+    time::timeout(
+        Duration::from_secs_f64(seconds) + DOWNLOAD_TIMEOUT,
+        finished,
+    )
+    .await
+    .map_err(|_| SiderealError::ServerError("Exposure timed out".to_owned()))?
+}
+
+/// A number the camera reports, if it has it
+async fn read_number(param: &str, element: &str) -> Option<f64> {
+    let camera = get_camera().await.ok()?;
+    let param_notify = camera.get_parameter(param).await.ok()?;
+    let param = param_notify.lock().await;
+    let map = param.get_values::<HashMap<String, indi::Number>>().ok()?;
+    Some(map.get(element)?.value.into())
+}
+
+/// Sensor temperature (°C), for cameras with a sensor probe
+pub async fn sensor_temperature() -> Option<f64> {
+    read_number("CCD_TEMPERATURE", "CCD_TEMPERATURE_VALUE").await
+}
+
+/// Whether the sensor cooler is on, for cameras with one
+pub async fn cooler_on() -> Option<bool> {
+    let camera = get_camera().await.ok()?;
+    let param_notify = camera.get_parameter("CCD_COOLER").await.ok()?;
+    let param = param_notify.lock().await;
+    let switches = param.get_values::<HashMap<String, indi::Switch>>().ok()?;
+    Some(matches!(
+        switches.get("COOLER_ON")?.value,
+        indi::SwitchState::On
+    ))
+}
+
+/// How hard the cooler is working, as a percentage of full power
+pub async fn cooler_power() -> Option<f64> {
+    read_number("CCD_COOLER_POWER", "CCD_COOLER_VALUE").await
+}
+
+/// What the camera's cooler is doing
+#[derive(Debug, Clone, Copy)]
+pub struct CoolerStatus {
+    /// Sensor temperature (°C)
+    pub temperature: f64,
+    pub on: Option<bool>,
+    /// Percent of full power, for drivers that report it
+    pub power: Option<f64>,
+}
+
+/// The cooler's readings, if the camera has a sensor probe
+pub async fn cooler_status() -> Option<CoolerStatus> {
+    Some(CoolerStatus {
+        temperature: sensor_temperature().await?,
+        on: cooler_on().await,
+        power: cooler_power().await,
+    })
+}
+
+/// Switch the sensor cooler on or off
+pub async fn set_cooler(on: bool) -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    let element = if on { "COOLER_ON" } else { "COOLER_OFF" };
+    camera
+        .change("CCD_COOLER", vec![(element, true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Switching cooler failed: {:?}", e)))?;
+    Ok(())
 }
 
+/// Cool (or warm) the sensor to `celsius`; most drivers switch the cooler on
+/// to get there
+pub async fn set_target_temperature(celsius: f64) -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    // the sensor takes minutes to get there, so don't wait for it to read `celsius`
+    send_numbers(
+        &camera,
+        "CCD_TEMPERATURE",
+        vec![("CCD_TEMPERATURE_VALUE", celsius)],
+    )
+    .await
+    .map_err(|e| SiderealError::ServerError(format!("Setting target temperature failed: {e}")))
+}
+
+/// Bring the sensor up to ambient a step at a time and then switch the cooler
+/// off, rather than letting it warm all at once and frost over
+pub async fn warm_up() -> SiderealResult<()> {
+    if cooler_on().await == Some(true) {
+        let mut target = sensor_temperature().await.ok_or_else(|| {
+            SiderealError::ServerError("Camera hasn't reported its temperature".to_owned())
+        })?;
+        // once the cooler is idling the sensor is as warm as its surroundings
+        while target < WARM_UP_CEILING_C
+            && !cooler_power()
+                .await
+                .is_some_and(|power| power <= WARM_UP_IDLE_POWER)
+        {
+            target = (target + WARM_UP_STEP_C).min(WARM_UP_CEILING_C);
+            tracing::debug!("Warming camera to {target:.1} °C");
+            set_target_temperature(target).await?;
+            let step_started = Instant::now();
+            time::sleep(WARM_UP_STEP_INTERVAL).await;
+            // a sensor lagging behind gets longer before the next step
+            while step_started.elapsed() < WARM_UP_STEP_LIMIT
+                && !sensor_temperature()
+                    .await
+                    .is_some_and(|celsius| (celsius - target).abs() <= WARM_UP_TOLERANCE_C)
+            {
+                time::sleep(WARM_UP_POLL).await;
+            }
+        }
+    }
+    set_cooler(false).await
+}
+
+/// The gain the camera is set to
+pub async fn gain() -> Option<f64> {
+    read_number("CCD_GAIN", "GAIN").await
+}
+
+/// The offset the camera is set to
+pub async fn offset() -> Option<f64> {
+    read_number("CCD_OFFSET", "OFFSET").await
+}
+
+/// The binning the camera is set to
+pub async fn binning() -> Option<u32> {
+    read_number("CCD_BINNING", "HOR_BIN")
+        .await
+        .map(|binning| binning as u32)
+}
+
+/// Name the filter wheel has for `slot`
+pub async fn filter_name(slot: u32) -> Option<String> {
+    let camera = get_camera().await.ok()?;
+    let param_notify = camera.get_parameter("FILTER_NAME").await.ok()?;
+    let param = param_notify.lock().await;
+    let names = param.get_values::<HashMap<String, indi::Text>>().ok()?;
+    let name = names.get(&format!("FILTER_SLOT_NAME_{slot}"))?.value.trim();
+    (!name.is_empty()).then(|| name.to_owned())
+}
+
+/// The camera's INDI device name
+pub async fn device_name() -> Option<String> {
+    let camera = get_camera().await.ok()?;
+    let name = camera.lock().await.get_name().clone();
+    Some(name)
+}
+
+/// Stop the exposure in progress, if any
+pub async fn abort_exposure() -> SiderealResult<()> {
+    let camera = get_camera().await?;
+    camera
+        .change("CCD_ABORT_EXPOSURE", vec![("ABORT", true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Aborting exposure failed: {:?}", e)))?;
+    Ok(())
+}
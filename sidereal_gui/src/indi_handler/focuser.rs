@@ -1,4 +1,17 @@
+use super::CONNECTED_DEVICES;
+use crate::{
+    app::Message,
+    config::Config,
+    gui::tabs::focus::Message as FocusMessage,
+    model::{SiderealError, SiderealResult},
+};
+use iced::futures::{stream, Sink, SinkExt, StreamExt};
 use indi::client::active_device::ActiveDevice;
+use std::{collections::HashMap, time::Duration};
+use tokio::time;
+
+/// Longest a focuser may take to reach a position
+const MOVE_TIMEOUT: Duration = Duration::from_secs(120);
 
 /// Focuser-specific helper functions
 ///
@@ -15,10 +28,150 @@ use indi::client::active_device::ActiveDevice;
 ///   - Never fail or error out when device is missing
 ///
 /// Get the active focuser device if available
-#[allow(dead_code)]
-pub fn get_focuser() -> Option<ActiveDevice> {
-    // Note: This is a blocking read, but should be fine for occasional access
-    // For async access, use CONNECTED_DEVICES.read().await in async contexts
-    None // TODO: Implement when needed
+async fn get_focuser() -> SiderealResult<ActiveDevice> {
+    // cloned so the device list isn't held locked through a long move
+    let devices = CONNECTED_DEVICES.read().await;
+    devices.focuser.clone().ok_or_else(|| {
+        SiderealError::ServerError(
+            "Focuser device not available. Please ensure the device is connected to the INDI server."
+                .to_owned(),
+        )
+    })
+}
+
+/// Absolute position (steps) the focuser last reported
+pub async fn position() -> SiderealResult<f64> {
+    let focuser = get_focuser().await?;
+    let param_notify = focuser
+        .get_parameter("ABS_FOCUS_POSITION")
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    let param = param_notify.lock().await;
+    param
+        .get_values::<HashMap<String, indi::Number>>()
+        .ok()
+        .and_then(|map| Some(map.get("FOCUS_ABSOLUTE_POSITION")?.value.into()))
+        .ok_or_else(|| SiderealError::ServerError("Focuser has no position".to_owned()))
+}
+
+/// Move to an absolute position, returning once the focuser is there
+pub async fn move_to(target: f64) -> SiderealResult<()> {
+    let focuser = get_focuser().await?;
+    let target = target.round().max(0.0);
+    let param_notify = focuser
+        .get_parameter("ABS_FOCUS_POSITION")
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    if position().await? == target {
+        return Ok(());
+    }
+    let mut changes = param_notify.subscribe().await;
+
+    focuser
+        .change(
+            "ABS_FOCUS_POSITION",
+            vec![("FOCUS_ABSOLUTE_POSITION", target)],
+        )
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Moving focuser failed: {:?}", e)))?;
+
+    let arrived = async {
+        while let Some(Ok(param_arc)) = changes.next().await {
+            if let Ok(map) = param_arc.get_values::<HashMap<String, indi::Number>>() {
+                if let Some(position) = map.get("FOCUS_ABSOLUTE_POSITION") {
+                    let position: f64 = position.value.into();
+                    if (position - target).abs() < 0.5 {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+        Err(SiderealError::ServerError(
+            "Lost the focuser during the move".to_owned(),
+        ))
+    };
+    time::timeout(MOVE_TIMEOUT, arrived)
+        .await
+        .map_err(|_| SiderealError::ServerError("Focuser move timed out".to_owned()))?
+}
+
+/// Move to an absolute position, overshooting inward moves by the configured
+/// backlash so the focuser always arrives going outward
+pub async fn goto(target: f64) -> SiderealResult<()> {
+    let backlash = Config::get().await.focuser.backlash_steps as f64;
+    let target = target.round().max(0.0);
+    if backlash > 0.0 && target < position().await? {
+        move_to((target - backlash).max(0.0)).await?;
+    }
+    move_to(target).await
+}
+
+/// Move `steps` outward, or inward if negative
+pub async fn move_by(steps: f64) -> SiderealResult<()> {
+    goto(position().await? + steps).await
+}
+
+/// Stop the focuser where it is
+pub async fn abort() -> SiderealResult<()> {
+    let focuser = get_focuser().await?;
+    focuser
+        .change("FOCUS_ABORT_MOTION", vec![("ABORT", true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Stopping focuser failed: {:?}", e)))?;
+    Ok(())
 }
 
+/// Watch the focuser's position and temperature and send them to the UI
+/// This function runs until the connection is lost
+///
+/// Unlike the mount, an idle focuser reports nothing, so no telemetry time is
+/// kept for it and it isn't dropped for being quiet.
+pub async fn watch_focuser<S>(focuser: ActiveDevice, output: &mut S)
+where
+    S: Sink<Message> + Unpin,
+{
+    let position_notify = match focuser.get_parameter("ABS_FOCUS_POSITION").await {
+        Ok(p) => p,
+        Err(_) => {
+            // If the focuser can't report a position, just return
+            return;
+        }
+    };
+    // not every focuser has a temperature probe
+    let mut changes = match focuser.get_parameter("FOCUS_TEMPERATURE").await {
+        Ok(temperature_notify) => stream::select(
+            position_notify.subscribe().await,
+            temperature_notify.subscribe().await,
+        )
+        .left_stream(),
+        Err(_) => position_notify.subscribe().await.right_stream(),
+    };
+
+    // Event loop - both parameters arrive on the one stream
+    loop {
+        match changes.next().await {
+            Some(Ok(param_arc)) => {
+                if let Ok(map) = param_arc.get_values::<HashMap<String, indi::Number>>() {
+                    let position = map.get("FOCUS_ABSOLUTE_POSITION").map(|n| n.value.into());
+                    let temperature = map.get("TEMPERATURE").map(|n| n.value.into());
+                    if position.is_some() || temperature.is_some() {
+                        let _ = output
+                            .send(Message::Focus(FocusMessage::FocuserUpdated {
+                                position,
+                                temperature,
+                            }))
+                            .await;
+                    }
+                }
+            }
+            Some(Err(_)) => {
+                // Stream error - connection lost
+                break;
+            }
+            None => {
+                // Stream ended - connection lost
+                break;
+            }
+        }
+    }
+}
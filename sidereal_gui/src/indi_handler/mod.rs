@@ -4,10 +4,11 @@ use crate::{
     model::{SiderealError, SiderealResult},
 };
 use iced::{
-    futures::{Sink, SinkExt, Stream},
+    futures::{future, Sink, SinkExt, Stream, StreamExt},
     stream,
 };
 use indi::client::active_device::ActiveDevice;
+use indi::serialization::ToCommand;
 use once_cell::sync::Lazy;
 use std::{
     collections::HashMap,
@@ -23,13 +24,17 @@ use tokio::{
 pub mod camera;
 pub mod focuser;
 pub mod mount;
+pub mod power_box;
 pub mod roof_controller;
 pub mod telescope_controller;
+pub mod ups;
+pub mod weather;
 
 // INDI interface bitmasks (common values)
 const IF_TELESCOPE: u32 = 0x0001; // mount
 const IF_CCD: u32 = 0x0002; // camera
 const IF_FOCUSER: u32 = 0x0008; // focuser
+const IF_WEATHER: u32 = 0x0080; // weather station
 
 /// INDI client instance wrapper
 pub struct IndiClientInstance {
@@ -46,9 +51,15 @@ pub static INDI_CLIENT: Lazy<SharedIndiClient> = Lazy::new(|| Arc::new(RwLock::n
 pub struct ServerInstance {
     pub mount: Option<ActiveDevice>,
     pub camera: Option<ActiveDevice>,
+    pub guide_camera: Option<ActiveDevice>,
+    /// Cameras past the imaging and guide cameras, e.g. a wide-field imager
+    pub extra_cameras: Vec<ActiveDevice>,
     pub focuser: Option<ActiveDevice>,
     pub telescope_controller: Option<ActiveDevice>,
     pub roof_controller: Option<ActiveDevice>,
+    pub weather: Option<ActiveDevice>,
+    pub ups: Option<ActiveDevice>,
+    pub power_box: Option<ActiveDevice>,
 }
 
 impl Default for ServerInstance {
@@ -56,9 +67,14 @@ impl Default for ServerInstance {
         Self {
             mount: None,
             camera: None,
+            guide_camera: None,
+            extra_cameras: Vec::new(),
             focuser: None,
             telescope_controller: None,
             roof_controller: None,
+            weather: None,
+            ups: None,
+            power_box: None,
         }
     }
 }
@@ -99,7 +115,47 @@ pub async fn connect_to_server(ip_addr: String) -> SiderealResult<()> {
     Ok(())
 }
 
-/// Discover and connect to all available devices (mount, camera, focuser, telescope controller)
+/// Longest a device gets to answer a new value before it's given up on
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Send new values for a number property and wait for the device to take
+/// them, busy working towards them or already done, rather than for it to
+/// report them exactly. A mount or cooler only ever gets close to what it's
+/// asked for, so waiting on equality would always time out.
+pub async fn send_numbers(
+    device: &ActiveDevice,
+    property: &str,
+    values: Vec<(&str, f64)>,
+) -> SiderealResult<()> {
+    let param = device
+        .get_parameter(property)
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    // subscribed before sending so the answer can't be missed
+    let mut answers = param.changes();
+    let name = device.lock().await.get_name().clone();
+    device
+        .send(values.to_command(name, property.to_owned()))
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    match time::timeout(ANSWER_TIMEOUT, answers.next()).await {
+        Ok(Some(Ok(answer))) => match answer.get_state() {
+            indi::PropertyState::Alert => Err(SiderealError::ServerError(format!(
+                "The device refused the new {property}"
+            ))),
+            _ => Ok(()),
+        },
+        Ok(_) => Err(SiderealError::ServerError(format!(
+            "Lost {property} while waiting for the device to answer"
+        ))),
+        Err(_) => Err(SiderealError::ServerError(format!(
+            "The device didn't answer the new {property} within {:.0}s",
+            ANSWER_TIMEOUT.as_secs_f64()
+        ))),
+    }
+}
+
+/// Discover and connect to all available devices (mount, cameras, focuser, telescope controller,
+/// roof controller, weather station, UPS, power box)
 /// This function always succeeds - it just returns what devices are currently available.
 /// If no devices are found, it still updates the cache and sends an empty device list.
 pub async fn find_connected_devices<S>(mut out: S) -> SiderealResult<()>
@@ -115,15 +171,31 @@ where
     };
 
     // ---- 1) Scan under locks: collect *names* only ----
-    let (mount_name, camera_name, focuser_name, telescope_controller_name, roof_controller_name) = {
+    let (
+        mount_name,
+        camera_name,
+        guide_camera_name,
+        extra_camera_names,
+        focuser_name,
+        telescope_controller_name,
+        roof_controller_name,
+        weather_name,
+        ups_name,
+        power_box_name,
+    ) = {
         let devices = client_instance.client.get_devices();
         let map = devices.lock().await;
 
         let mut mount_name: Option<String> = None;
         let mut camera_name: Option<String> = None;
+        let mut guide_camera_name: Option<String> = None;
+        let mut extra_camera_names: Vec<String> = Vec::new();
         let mut focuser_name: Option<String> = None;
         let mut telescope_controller_name: Option<String> = None;
         let mut roof_controller_name: Option<String> = None;
+        let mut weather_name: Option<String> = None;
+        let mut ups_name: Option<String> = None;
+        let mut power_box_name: Option<String> = None;
 
         for (name, dev_mx) in map.iter() {
             let dev = dev_mx.lock().await;
@@ -147,21 +219,48 @@ where
                 telescope_controller_name = Some(name.clone());
             } else if roof_controller_name.is_none() && name == "Roof Controller" {
                 roof_controller_name = Some(name.clone());
+            } else if ups_name.is_none() && params.contains_key("UPS_STATUS") {
+                // UPS drivers go by the property rather than an interface bit
+                ups_name = Some(name.clone());
             } else if mount_name.is_none() && (iface_mask & IF_TELESCOPE) != 0 {
                 mount_name = Some(name.clone());
-            } else if camera_name.is_none() && (iface_mask & IF_CCD) != 0 {
-                camera_name = Some(name.clone());
+            } else if (camera_name.is_none() || guide_camera_name.is_none())
+                && (iface_mask & IF_CCD) != 0
+            {
+                // a camera named for guiding guides; otherwise the first
+                // camera found images and the second guides
+                let named_guider = name.to_lowercase().contains("guide");
+                if camera_name.is_none() && !(named_guider && guide_camera_name.is_none()) {
+                    camera_name = Some(name.clone());
+                } else {
+                    guide_camera_name = Some(name.clone());
+                }
+            } else if (iface_mask & IF_CCD) != 0 {
+                extra_camera_names.push(name.clone());
             } else if focuser_name.is_none() && (iface_mask & IF_FOCUSER) != 0 {
                 focuser_name = Some(name.clone());
+            } else if weather_name.is_none() && (iface_mask & IF_WEATHER) != 0 {
+                weather_name = Some(name.clone());
+            }
+
+            // a power box can be a focuser and weather station too, so it's
+            // looked for whatever else the device was taken for
+            if power_box_name.is_none() && params.contains_key("POWER_CONTROL") {
+                power_box_name = Some(name.clone());
             }
         }
 
         (
             mount_name,
             camera_name,
+            guide_camera_name,
+            extra_camera_names,
             focuser_name,
             telescope_controller_name,
             roof_controller_name,
+            weather_name,
+            ups_name,
+            power_box_name,
         )
         // all guards dropped here
     };
@@ -171,9 +270,14 @@ where
     let mut result = ServerInstance::default();
     let mut final_mount_name: Option<String> = None;
     let mut final_camera_name: Option<String> = None;
+    let mut final_guide_camera_name: Option<String> = None;
+    let mut final_extra_camera_names: Vec<String> = Vec::new();
     let mut final_focuser_name: Option<String> = None;
     let mut final_telescope_controller_name: Option<String> = None;
     let mut final_roof_controller_name: Option<String> = None;
+    let mut final_weather_name: Option<String> = None;
+    let mut final_ups_name: Option<String> = None;
+    let mut final_power_box_name: Option<String> = None;
 
     // Helper to connect to device and verify it's reachable
     // Reduced timeouts for faster discovery
@@ -211,7 +315,18 @@ where
     }
 
     // Check all devices in parallel for faster discovery
-    let (mount_result, camera_result, focuser_result, telescope_controller_result, roof_controller_result) = tokio::join!(
+    let (
+        mount_result,
+        camera_result,
+        guide_camera_result,
+        extra_camera_results,
+        focuser_result,
+        telescope_controller_result,
+        roof_controller_result,
+        weather_result,
+        ups_result,
+        power_box_result,
+    ) = tokio::join!(
         async {
             if let Some(n) = mount_name.clone() {
                 match time::timeout(
@@ -254,6 +369,38 @@ where
                 None
             }
         },
+        async {
+            if let Some(n) = guide_camera_name.clone() {
+                match time::timeout(
+                    Duration::from_millis(300),
+                    client_instance.client.get_device::<()>(&n),
+                )
+                .await
+                {
+                    Ok(Ok(dev)) => {
+                        if connect_and_verify_device(&dev).await {
+                            Some((dev, n))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            }
+        },
+        future::join_all(extra_camera_names.iter().map(|n| {
+            let client = &client_instance.client;
+            async move {
+                match time::timeout(Duration::from_millis(300), client.get_device::<()>(n)).await {
+                    Ok(Ok(dev)) => connect_and_verify_device(&dev)
+                        .await
+                        .then(|| (dev, n.clone())),
+                    _ => None,
+                }
+            }
+        })),
         async {
             if let Some(n) = focuser_name.clone() {
                 match time::timeout(
@@ -316,6 +463,69 @@ where
             } else {
                 None
             }
+        },
+        async {
+            if let Some(n) = weather_name.clone() {
+                match time::timeout(
+                    Duration::from_millis(300),
+                    client_instance.client.get_device::<()>(&n),
+                )
+                .await
+                {
+                    Ok(Ok(dev)) => {
+                        if connect_and_verify_device(&dev).await {
+                            Some((dev, n))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            }
+        },
+        async {
+            if let Some(n) = ups_name.clone() {
+                match time::timeout(
+                    Duration::from_millis(300),
+                    client_instance.client.get_device::<()>(&n),
+                )
+                .await
+                {
+                    Ok(Ok(dev)) => {
+                        if connect_and_verify_device(&dev).await {
+                            Some((dev, n))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            }
+        },
+        async {
+            if let Some(n) = power_box_name.clone() {
+                match time::timeout(
+                    Duration::from_millis(300),
+                    client_instance.client.get_device::<()>(&n),
+                )
+                .await
+                {
+                    Ok(Ok(dev)) => {
+                        if connect_and_verify_device(&dev).await {
+                            Some((dev, n))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            }
         }
     );
 
@@ -328,6 +538,14 @@ where
         result.camera = Some(dev);
         final_camera_name = Some(name);
     }
+    if let Some((dev, name)) = guide_camera_result {
+        result.guide_camera = Some(dev);
+        final_guide_camera_name = Some(name);
+    }
+    for (dev, name) in extra_camera_results.into_iter().flatten() {
+        result.extra_cameras.push(dev);
+        final_extra_camera_names.push(name);
+    }
     if let Some((dev, name)) = focuser_result {
         result.focuser = Some(dev);
         final_focuser_name = Some(name);
@@ -340,6 +558,18 @@ where
         result.roof_controller = Some(dev);
         final_roof_controller_name = Some(name);
     }
+    if let Some((dev, name)) = weather_result {
+        result.weather = Some(dev);
+        final_weather_name = Some(name);
+    }
+    if let Some((dev, name)) = ups_result {
+        result.ups = Some(dev);
+        final_ups_name = Some(name);
+    }
+    if let Some((dev, name)) = power_box_result {
+        result.power_box = Some(dev);
+        final_power_box_name = Some(name);
+    }
 
     // ---- 3) Always update the cache and send device change message ----
     // Only send device names for devices that we successfully resolved and verified
@@ -355,9 +585,14 @@ where
         .send(Message::ConnectedDeviceChange(ConnectedDevices {
             mount: final_mount_name,
             camera: final_camera_name,
+            guide_camera: final_guide_camera_name,
+            extra_cameras: final_extra_camera_names,
             focuser: final_focuser_name,
             telescope_controller: final_telescope_controller_name,
             roof_controller: final_roof_controller_name,
+            weather: final_weather_name,
+            ups: final_ups_name,
+            power_box: final_power_box_name,
         }))
         .await;
 
@@ -475,7 +710,26 @@ pub fn param_watcher() -> impl Stream<Item = Message> {
                             .is_ok()
                         {
                             let mut channel_sink = ChannelSink { tx };
-                            mount::watch_coordinates(device, &mut channel_sink).await;
+                            mount::watch_mount(device, &mut channel_sink).await;
+                        }
+                    })
+                },
+            },
+            DeviceWatcherConfig {
+                device_id: "focuser",
+                get_device: |devices| devices.focuser.clone(),
+                clear_device: |devices| devices.focuser = None,
+                get_connected_name: |cd| cd.focuser.clone(),
+                set_connected_name: |cd, name| cd.focuser = name,
+                spawn_watcher: |device, tx| {
+                    tokio::spawn(async move {
+                        if device
+                            .change("CONNECTION", vec![("CONNECT", true)])
+                            .await
+                            .is_ok()
+                        {
+                            let mut channel_sink = ChannelSink { tx };
+                            focuser::watch_focuser(device, &mut channel_sink).await;
                         }
                     })
                 },
@@ -592,9 +846,14 @@ pub fn param_watcher() -> impl Stream<Item = Message> {
                             let mut connected_devices = ConnectedDevices {
                                 mount: None,
                                 camera: None,
+                                guide_camera: None,
+                                extra_cameras: Vec::new(),
                                 focuser: None,
                                 telescope_controller: None,
                                 roof_controller: None,
+                                weather: None,
+                                ups: None,
+                                power_box: None,
                             };
                             (config.set_connected_name)(&mut connected_devices, None);
                             let _ = output.send(Message::ConnectedDeviceChange(connected_devices)).await;
@@ -4,11 +4,16 @@ use crate::{
     gui::tabs::mount::Message as MountMessage,
     model::{SiderealError, SiderealResult},
 };
-use iced::futures::{Sink, SinkExt, StreamExt};
+use iced::futures::{stream, Sink, SinkExt, StreamExt};
 use indi::client::active_device::ActiveDevice;
-use std::{collections::HashMap, time::Instant};
+use std::{
+    collections::HashMap,
+    fmt,
+    time::{Duration, Instant},
+};
+use tokio::time;
 
-use super::CONNECTED_DEVICES;
+use super::{send_numbers, CONNECTED_DEVICES};
 
 /// Move the mount in a specific direction
 pub async fn move_mount(direction: String, subdirection: String) -> SiderealResult<()> {
@@ -28,6 +33,127 @@ pub async fn move_mount(direction: String, subdirection: String) -> SiderealResu
     }
 }
 
+/// Slew the mount to a position and keep tracking it once there
+pub async fn slew_to(ra_hours: f64, dec_deg: f64) -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    mount
+        .change("ON_COORD_SET", vec![("TRACK", true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    // the mount never reports exactly what it was sent, so this returns once
+    // it's slewing; wait_for_slew waits out the rest
+    send_numbers(
+        mount,
+        "EQUATORIAL_EOD_COORD",
+        vec![("RA", ra_hours), ("DEC", dec_deg)],
+    )
+    .await
+}
+
+/// Stop a slew where the mount is
+pub async fn abort_motion() -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    mount
+        .change("TELESCOPE_ABORT_MOTION", vec![("ABORT", true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Abort failed: {:?}", e)))?;
+    Ok(())
+}
+
+/// Tell the mount it's pointing at a position, e.g. one found by plate
+/// solving, correcting its pointing model there
+pub async fn sync_to(ra_hours: f64, dec_deg: f64) -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    mount
+        .change("ON_COORD_SET", vec![("SYNC", true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    send_numbers(
+        mount,
+        "EQUATORIAL_EOD_COORD",
+        vec![("RA", ra_hours), ("DEC", dec_deg)],
+    )
+    .await
+    .map_err(|e| SiderealError::ServerError(format!("Sync failed: {e}")))
+}
+
+/// Wait for `property` to stop being busy, up to `timeout`; `doing` says what
+/// the mount is busy with
+async fn wait_while_busy(property: &str, doing: &str, timeout: Duration) -> SiderealResult<()> {
+    let mount = CONNECTED_DEVICES.read().await.mount.clone();
+    let Some(mount) = mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    let param_notify = mount
+        .get_parameter(property)
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    let started = Instant::now();
+    loop {
+        match *param_notify.lock().await.get_state() {
+            indi::PropertyState::Busy => {}
+            indi::PropertyState::Alert => {
+                return Err(SiderealError::ServerError(format!(
+                    "The mount reported a problem while {doing}"
+                )))
+            }
+            _ => return Ok(()),
+        }
+        if started.elapsed() > timeout {
+            return Err(SiderealError::ServerError(format!(
+                "The mount was still {doing} after {:.0}s",
+                timeout.as_secs_f64()
+            )));
+        }
+        time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+/// Wait for a slew to finish, up to `timeout`
+pub async fn wait_for_slew(timeout: Duration) -> SiderealResult<()> {
+    // the coordinates stay busy for as long as the mount is slewing
+    wait_while_busy("EQUATORIAL_EOD_COORD", "slewing", timeout).await
+}
+
+/// Wait for the mount to reach its park position, up to `timeout`
+pub async fn wait_for_park(timeout: Duration) -> SiderealResult<()> {
+    wait_while_busy("TELESCOPE_PARK", "parking", timeout).await
+}
+
+/// Where the mount is pointing, as (RA hours, Dec degrees), if it's
+/// connected and has reported it
+pub async fn position() -> Option<(f64, f64)> {
+    let mount = CONNECTED_DEVICES.read().await.mount.clone()?;
+    let param_notify = match mount.get_parameter("EQUATORIAL_EOD_COORD").await {
+        Ok(p) => p,
+        Err(_) => mount.get_parameter("EQUATORIAL_COORD").await.ok()?,
+    };
+    let param = param_notify.lock().await;
+    let map = param.get_values::<HashMap<String, indi::Number>>().ok()?;
+    Some((map.get("RA")?.value.into(), map.get("DEC")?.value.into()))
+}
+
 /// Stop all mount movement
 pub async fn stop_move() {
     let devices = CONNECTED_DEVICES.read().await;
@@ -36,32 +162,209 @@ pub async fn stop_move() {
             .change("TELESCOPE_MOTION_NS", vec![("MOTION_NORTH", false)])
             .await
         {
-            println!("{:?}", e);
+            tracing::warn!("Stopping the mount failed: {:?}", e);
         }
         if let Err(e) = mount
             .change("TELESCOPE_MOTION_NS", vec![("MOTION_SOUTH", false)])
             .await
         {
-            println!("{:?}", e);
+            tracing::warn!("Stopping the mount failed: {:?}", e);
         }
         if let Err(e) = mount
             .change("TELESCOPE_MOTION_WE", vec![("MOTION_WEST", false)])
             .await
         {
-            println!("{:?}", e);
+            tracing::warn!("Stopping the mount failed: {:?}", e);
         }
         if let Err(e) = mount
             .change("TELESCOPE_MOTION_WE", vec![("MOTION_EAST", false)])
             .await
         {
-            println!("{:?}", e);
+            tracing::warn!("Stopping the mount failed: {:?}", e);
+        }
+    }
+}
+
+/// Direction the mount is nudged in by a guide pulse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuideDirection {
+    North,
+    South,
+    East,
+    West,
+}
+
+/// Nudge the mount at guide rate for `millis`, returning once the pulse is over
+pub async fn pulse_guide(direction: GuideDirection, millis: f64) -> SiderealResult<()> {
+    // cloned so the device list isn't held locked through the pulse
+    let mount = CONNECTED_DEVICES.read().await.mount.clone();
+    let Some(mount) = mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    let (param, element) = match direction {
+        GuideDirection::North => ("TELESCOPE_TIMED_GUIDE_NS", "TIMED_GUIDE_N"),
+        GuideDirection::South => ("TELESCOPE_TIMED_GUIDE_NS", "TIMED_GUIDE_S"),
+        GuideDirection::East => ("TELESCOPE_TIMED_GUIDE_WE", "TIMED_GUIDE_E"),
+        GuideDirection::West => ("TELESCOPE_TIMED_GUIDE_WE", "TIMED_GUIDE_W"),
+    };
+    mount
+        .change(param, vec![(element, millis.round())])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("Guide pulse failed: {:?}", e)))?;
+    // the driver takes the pulse straight away and runs it in the background
+    time::sleep(Duration::from_secs_f64(millis.max(0.0) / 1000.0)).await;
+    Ok(())
+}
+
+/// Park the mount, or unpark it
+pub async fn park(parked: bool) -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    let element = if parked { "PARK" } else { "UNPARK" };
+    mount
+        .change("TELESCOPE_PARK", vec![(element, true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    Ok(())
+}
+
+/// Set the speed manual slews move at, by one of the rates the mount reported
+pub async fn set_slew_rate(rate: String) -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    mount
+        .change("TELESCOPE_SLEW_RATE", vec![(rate.as_str(), true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    Ok(())
+}
+
+/// Rate the mount tracks the sky at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackMode {
+    Sidereal,
+    Lunar,
+    Solar,
+    /// The rates set by `set_track_rate`
+    Custom,
+}
+
+impl TrackMode {
+    pub const ALL: [TrackMode; 4] = [
+        TrackMode::Sidereal,
+        TrackMode::Lunar,
+        TrackMode::Solar,
+        TrackMode::Custom,
+    ];
+
+    fn element(self) -> &'static str {
+        match self {
+            TrackMode::Sidereal => "TRACK_SIDEREAL",
+            TrackMode::Lunar => "TRACK_LUNAR",
+            TrackMode::Solar => "TRACK_SOLAR",
+            TrackMode::Custom => "TRACK_CUSTOM",
+        }
+    }
+}
+
+impl fmt::Display for TrackMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackMode::Sidereal => write!(f, "Sidereal"),
+            TrackMode::Lunar => write!(f, "Lunar"),
+            TrackMode::Solar => write!(f, "Solar"),
+            TrackMode::Custom => write!(f, "Custom"),
         }
     }
 }
 
-/// Watch for mount coordinate updates and send them to the UI
-/// This function runs until the connection is lost
-pub async fn watch_coordinates<S>(mount: ActiveDevice, output: &mut S)
+/// Switch to tracking at `mode`
+pub async fn set_track_mode(mode: TrackMode) -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    mount
+        .change("TELESCOPE_TRACK_MODE", vec![(mode.element(), true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    Ok(())
+}
+
+/// Track at custom rates (arcseconds per second; RA is 15.041 for sidereal)
+pub async fn set_track_rate(ra_arcsec: f64, dec_arcsec: f64) -> SiderealResult<()> {
+    set_track_mode(TrackMode::Custom).await?;
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    mount
+        .change(
+            "TELESCOPE_TRACK_RATE",
+            vec![("TRACK_RATE_RA", ra_arcsec), ("TRACK_RATE_DE", dec_arcsec)],
+        )
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    Ok(())
+}
+
+/// Start or stop tracking
+pub async fn set_tracking(tracking: bool) -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    let Some(mount) = &devices.mount else {
+        return Err(SiderealError::ServerError(
+            "Mount device not available. Please ensure the mount is connected to the INDI server."
+                .to_owned(),
+        ));
+    };
+    let element = if tracking { "TRACK_ON" } else { "TRACK_OFF" };
+    mount
+        .change("TELESCOPE_TRACK_STATE", vec![(element, true)])
+        .await
+        .map_err(|e| SiderealError::ServerError(format!("{:?}", e)))?;
+    Ok(())
+}
+
+/// Where a slew rate sorts, slowest first. Drivers name their rates either
+/// like "SLEW_GUIDE" or by multiple of sidereal, like "16x".
+fn slew_rate_order(name: &str) -> (u8, f64) {
+    match name {
+        "SLEW_GUIDE" => (0, 0.0),
+        "SLEW_CENTERING" => (0, 1.0),
+        "SLEW_FIND" => (0, 2.0),
+        "SLEW_MAX" => (0, 3.0),
+        _ => {
+            let digits: String = name
+                .chars()
+                .take_while(|c| c.is_ascii_digit() || *c == '.')
+                .collect();
+            digits.parse().map_or((2, 0.0), |multiple| (1, multiple))
+        }
+    }
+}
+
+/// Watch for mount coordinate, park, slew rate and tracking updates and send
+/// them to the UI. This function runs until the connection is lost
+pub async fn watch_mount<S>(mount: ActiveDevice, output: &mut S)
 where
     S: Sink<Message> + Unpin,
 {
@@ -77,8 +380,20 @@ where
         },
     };
 
-    // Subscribe to parameter changes
-    let mut changes = param_notify.subscribe().await;
+    // Subscribe to parameter changes; not every mount has all of these
+    let mut subscriptions = vec![param_notify.subscribe().await];
+    for name in [
+        "TELESCOPE_PARK",
+        "TELESCOPE_SLEW_RATE",
+        "TELESCOPE_TRACK_STATE",
+        "TELESCOPE_TRACK_MODE",
+        "TELESCOPE_TRACK_RATE",
+    ] {
+        if let Ok(notify) = mount.get_parameter(name).await {
+            subscriptions.push(notify.subscribe().await);
+        }
+    }
+    let mut changes = stream::select_all(subscriptions);
 
     // Event loop - just process data, timeout is handled by generic param_watcher
     loop {
@@ -98,7 +413,43 @@ where
                                 dec_deg: dec.value.into(),
                             }))
                             .await;
+                    } else if let (Some(ra), Some(dec)) =
+                        (map.get("TRACK_RATE_RA"), map.get("TRACK_RATE_DE"))
+                    {
+                        let _ = output
+                            .send(Message::Mount(MountMessage::TrackRateUpdated {
+                                ra_arcsec: ra.value.into(),
+                                dec_arcsec: dec.value.into(),
+                            }))
+                            .await;
                     }
+                } else if let Ok(map) = param_arc.get_values::<HashMap<String, indi::Switch>>() {
+                    let on = |switch: &indi::Switch| matches!(switch.value, indi::SwitchState::On);
+                    let mode = TrackMode::ALL
+                        .into_iter()
+                        .find(|mode| map.get(mode.element()).is_some_and(on));
+                    let message = match (map.get("PARK"), map.get("TRACK_ON")) {
+                        (Some(park), _) => MountMessage::ParkUpdated(on(park)),
+                        (None, Some(tracking)) => MountMessage::TrackingUpdated(on(tracking)),
+                        // the mode switches are the only others named TRACK_*
+                        _ if map.keys().any(|key| key.starts_with("TRACK_")) => {
+                            MountMessage::TrackModeUpdated(mode)
+                        }
+                        _ => {
+                            let mut rates: Vec<&String> = map.keys().collect();
+                            rates.sort_by(|a, b| {
+                                slew_rate_order(a)
+                                    .partial_cmp(&slew_rate_order(b))
+                                    .unwrap_or(std::cmp::Ordering::Equal)
+                                    .then(a.cmp(b))
+                            });
+                            MountMessage::SlewRatesUpdated {
+                                selected: rates.iter().position(|rate| on(&map[*rate])),
+                                rates: rates.into_iter().cloned().collect(),
+                            }
+                        }
+                    };
+                    let _ = output.send(Message::Mount(message)).await;
                 }
             }
             Some(Err(_)) => {
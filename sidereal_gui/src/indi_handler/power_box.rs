@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use indi::client::active_device::ActiveDevice;
+
+use super::CONNECTED_DEVICES;
+use crate::model::{SiderealError, SiderealResult};
+
+/// Dew heater ports a box may have, by the letter the driver gives them
+const DEW_CHANNELS: [&str; 3] = ["A", "B", "C"];
+
+/// One of the box's switchable outlets
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outlet {
+    /// Numbered from 1, as on the box
+    pub number: u32,
+    /// What the driver calls it, if it names its outlets
+    pub label: Option<String>,
+    pub on: bool,
+    /// Amps drawn
+    pub current: Option<f64>,
+}
+
+/// One of the box's PWM dew heater ports
+#[derive(Debug, Clone, PartialEq)]
+pub struct DewPort {
+    /// Letter the driver gives the port
+    pub channel: &'static str,
+    pub duty_percent: f64,
+    /// Amps drawn
+    pub current: Option<f64>,
+}
+
+/// Everything a Pegasus UPB-style power box reports
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PowerBoxStatus {
+    pub voltage: Option<f64>,
+    /// Amps drawn by the whole box
+    pub total_current: Option<f64>,
+    pub outlets: Vec<Outlet>,
+    pub dew_ports: Vec<DewPort>,
+}
+
+async fn power_box() -> SiderealResult<ActiveDevice> {
+    CONNECTED_DEVICES
+        .read()
+        .await
+        .power_box
+        .clone()
+        .ok_or_else(|| {
+            SiderealError::ServerError(
+                "Power box not available. Please ensure it is connected to the INDI server."
+                    .to_owned(),
+            )
+        })
+}
+
+/// The values of a number property, empty if the box doesn't have it
+async fn numbers(device: &ActiveDevice, property: &str) -> HashMap<String, f64> {
+    let Ok(param_notify) = device.get_parameter(property).await else {
+        return HashMap::new();
+    };
+    let param = param_notify.lock().await;
+    match param.get_values::<HashMap<String, indi::Number>>() {
+        Ok(map) => map
+            .iter()
+            .map(|(name, number)| (name.clone(), number.value.into()))
+            .collect(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Which of a switch property's switches are on, empty if the box doesn't
+/// have it
+async fn switches(device: &ActiveDevice, property: &str) -> HashMap<String, bool> {
+    let Ok(param_notify) = device.get_parameter(property).await else {
+        return HashMap::new();
+    };
+    let param = param_notify.lock().await;
+    match param.get_values::<HashMap<String, indi::Switch>>() {
+        Ok(map) => map
+            .iter()
+            .map(|(name, switch)| (name.clone(), matches!(switch.value, indi::SwitchState::On)))
+            .collect(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// The labels the driver gives its outlets, if it has any
+async fn outlet_labels(device: &ActiveDevice) -> HashMap<String, String> {
+    let Ok(param_notify) = device.get_parameter("POWER_CONTROL_LABEL").await else {
+        return HashMap::new();
+    };
+    let param = param_notify.lock().await;
+    match param.get_values::<HashMap<String, indi::Text>>() {
+        Ok(map) => map
+            .iter()
+            .map(|(name, label)| (name.clone(), label.value.clone()))
+            .collect(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// What the power box reports, if one is connected
+pub async fn status() -> Option<PowerBoxStatus> {
+    let device = power_box().await.ok()?;
+    let outlet_states = switches(&device, "POWER_CONTROL").await;
+    let outlet_currents = numbers(&device, "POWER_CURRENT").await;
+    let labels = outlet_labels(&device).await;
+    let sensors = numbers(&device, "POWER_SENSORS").await;
+    let duty = numbers(&device, "DEW_PWM").await;
+    let dew_currents = numbers(&device, "DEW_CURRENT").await;
+
+    // outlets are numbered from 1 with no gaps
+    let outlets = (1..)
+        .map_while(|number| {
+            let on = *outlet_states.get(&format!("POWER_CONTROL_{number}"))?;
+            Some(Outlet {
+                number,
+                label: labels
+                    .get(&format!("POWER_LABEL_{number}"))
+                    .filter(|label| !label.trim().is_empty())
+                    .cloned(),
+                on,
+                current: outlet_currents
+                    .get(&format!("POWER_CURRENT_{number}"))
+                    .copied(),
+            })
+        })
+        .collect();
+    let dew_ports = DEW_CHANNELS
+        .into_iter()
+        .filter_map(|channel| {
+            Some(DewPort {
+                channel,
+                duty_percent: *duty.get(&format!("DEW_{channel}"))?,
+                current: dew_currents.get(&format!("DEW_CURRENT_{channel}")).copied(),
+            })
+        })
+        .collect();
+
+    Some(PowerBoxStatus {
+        voltage: sensors.get("SENSOR_VOLTAGE").copied(),
+        total_current: sensors.get("SENSOR_CURRENT").copied(),
+        outlets,
+        dew_ports,
+    })
+}
+
+/// Switch outlet `number` on or off
+pub async fn set_outlet(number: u32, on: bool) -> SiderealResult<()> {
+    let device = power_box().await?;
+    let element = format!("POWER_CONTROL_{number}");
+    device
+        .change("POWER_CONTROL", vec![(element.as_str(), on)])
+        .await
+        .map_err(|e| {
+            SiderealError::ServerError(format!("Switching outlet {number} failed: {:?}", e))
+        })?;
+    Ok(())
+}
+
+/// Set the duty cycle of dew heater port `channel`, in percent
+pub async fn set_dew_duty(channel: &'static str, percent: f64) -> SiderealResult<()> {
+    let device = power_box().await?;
+    let element = format!("DEW_{channel}");
+    device
+        .change(
+            "DEW_PWM",
+            vec![(element.as_str(), percent.clamp(0.0, 100.0))],
+        )
+        .await
+        .map_err(|e| {
+            SiderealError::ServerError(format!("Setting dew heater {channel} failed: {:?}", e))
+        })?;
+    Ok(())
+}
@@ -1,34 +1,40 @@
 use super::TELEMETRY_TIMES;
 use crate::{
     app::Message,
-    gui::tabs::observatory::Message as ObservatoryMessage,
+    gui::tabs::observatory::{Message as ObservatoryMessage, RoofState},
     model::{SiderealError, SiderealResult},
 };
 use iced::futures::{Sink, SinkExt, StreamExt};
 use indi::client::active_device::ActiveDevice;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
 
 use super::CONNECTED_DEVICES;
 
+/// Telemetry element for the rain/cloud sensor input, nonzero while it's tripped
+const RAIN_SENSOR: &str = "RAIN_SENSOR";
+/// How often the close is sent again while rain finds the roof still open
+const RAIN_CLOSE_RETRY: Duration = Duration::from_secs(10);
+
 /// Arm the roof controller system
 pub async fn arm_system() -> SiderealResult<()> {
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending ARM command to INDI driver");
-            println!("[Roof Controller] Command: ARM_CONTROL property, switch: ARM = true");
+            info!("Sending ARM command to INDI driver");
+            debug!("Command: ARM_CONTROL property, switch: ARM = true");
             device
                 .change("ARM_CONTROL", vec![("ARM", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Arm command failed: {:?}", e);
+                    error!("Arm command failed: {:?}", e);
                     SiderealError::ServerError(format!("Arm control failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] ARM command sent successfully");
+            info!("ARM command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Arm command failed: device not available");
+            warn!("Arm command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -41,20 +47,20 @@ pub async fn disarm_system() -> SiderealResult<()> {
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending DISARM command to INDI driver");
-            println!("[Roof Controller] Command: ARM_CONTROL property, switch: DISARM = true");
+            info!("Sending DISARM command to INDI driver");
+            debug!("Command: ARM_CONTROL property, switch: DISARM = true");
             device
                 .change("ARM_CONTROL", vec![("DISARM", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Disarm command failed: {:?}", e);
+                    error!("Disarm command failed: {:?}", e);
                     SiderealError::ServerError(format!("Disarm control failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] DISARM command sent successfully");
+            info!("DISARM command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Disarm command failed: device not available");
+            warn!("Disarm command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -62,25 +68,31 @@ pub async fn disarm_system() -> SiderealResult<()> {
     }
 }
 
-/// Open the roof
+/// Open the roof, unless the rain sensor is tripped
 pub async fn open_roof() -> SiderealResult<()> {
+    if rain_detected().await == Some(true) {
+        warn!("Open roof command refused: rain detected");
+        return Err(SiderealError::ServerError(
+            "Rain detected. The roof stays closed until the rain sensor clears.".to_owned(),
+        ));
+    }
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending OPEN ROOF command to INDI driver");
-            println!("[Roof Controller] Command: ROOF_CONTROL property, switch: ROOF_OPEN = true");
+            info!("Sending OPEN ROOF command to INDI driver");
+            debug!("Command: ROOF_CONTROL property, switch: ROOF_OPEN = true");
             device
                 .change("ROOF_CONTROL", vec![("ROOF_OPEN", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Open roof command failed: {:?}", e);
+                    error!("Open roof command failed: {:?}", e);
                     SiderealError::ServerError(format!("Roof open failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] OPEN ROOF command sent successfully");
+            info!("OPEN ROOF command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Open roof command failed: device not available");
+            warn!("Open roof command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -93,20 +105,20 @@ pub async fn close_roof() -> SiderealResult<()> {
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending CLOSE ROOF command to INDI driver");
-            println!("[Roof Controller] Command: ROOF_CONTROL property, switch: ROOF_CLOSE = true");
+            info!("Sending CLOSE ROOF command to INDI driver");
+            debug!("Command: ROOF_CONTROL property, switch: ROOF_CLOSE = true");
             device
                 .change("ROOF_CONTROL", vec![("ROOF_CLOSE", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Close roof command failed: {:?}", e);
+                    error!("Close roof command failed: {:?}", e);
                     SiderealError::ServerError(format!("Roof close failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] CLOSE ROOF command sent successfully");
+            info!("CLOSE ROOF command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Close roof command failed: device not available");
+            warn!("Close roof command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -119,20 +131,20 @@ pub async fn stop_roof() -> SiderealResult<()> {
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending STOP ROOF command to INDI driver");
-            println!("[Roof Controller] Command: ROOF_CONTROL property, switch: ROOF_STOP = true");
+            info!("Sending STOP ROOF command to INDI driver");
+            debug!("Command: ROOF_CONTROL property, switch: ROOF_STOP = true");
             device
                 .change("ROOF_CONTROL", vec![("ROOF_STOP", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Stop roof command failed: {:?}", e);
+                    error!("Stop roof command failed: {:?}", e);
                     SiderealError::ServerError(format!("Roof stop failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] STOP ROOF command sent successfully");
+            info!("STOP ROOF command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Stop roof command failed: device not available");
+            warn!("Stop roof command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -145,22 +157,20 @@ pub async fn engage_lock() -> SiderealResult<()> {
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending ENGAGE LOCK command to INDI driver");
-            println!(
-                "[Roof Controller] Command: LOCK_CONTROL property, switch: LOCK_ENGAGE = true"
-            );
+            info!("Sending ENGAGE LOCK command to INDI driver");
+            debug!("Command: LOCK_CONTROL property, switch: LOCK_ENGAGE = true");
             device
                 .change("LOCK_CONTROL", vec![("LOCK_ENGAGE", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Engage lock command failed: {:?}", e);
+                    error!("Engage lock command failed: {:?}", e);
                     SiderealError::ServerError(format!("Lock engage failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] ENGAGE LOCK command sent successfully");
+            info!("ENGAGE LOCK command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Engage lock command failed: device not available");
+            warn!("Engage lock command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -173,22 +183,20 @@ pub async fn disengage_lock() -> SiderealResult<()> {
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending DISENGAGE LOCK command to INDI driver");
-            println!(
-                "[Roof Controller] Command: LOCK_CONTROL property, switch: LOCK_DISENGAGE = true"
-            );
+            info!("Sending DISENGAGE LOCK command to INDI driver");
+            debug!("Command: LOCK_CONTROL property, switch: LOCK_DISENGAGE = true");
             device
                 .change("LOCK_CONTROL", vec![("LOCK_DISENGAGE", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Disengage lock command failed: {:?}", e);
+                    error!("Disengage lock command failed: {:?}", e);
                     SiderealError::ServerError(format!("Lock disengage failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] DISENGAGE LOCK command sent successfully");
+            info!("DISENGAGE LOCK command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Disengage lock command failed: device not available");
+            warn!("Disengage lock command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -201,20 +209,20 @@ pub async fn stop_lock() -> SiderealResult<()> {
     let devices = CONNECTED_DEVICES.read().await;
     match &devices.roof_controller {
         Some(device) => {
-            println!("[Roof Controller] Sending STOP LOCK command to INDI driver");
-            println!("[Roof Controller] Command: LOCK_CONTROL property, switch: LOCK_STOP = true");
+            info!("Sending STOP LOCK command to INDI driver");
+            debug!("Command: LOCK_CONTROL property, switch: LOCK_STOP = true");
             device
                 .change("LOCK_CONTROL", vec![("LOCK_STOP", true)])
                 .await
                 .map_err(|e| {
-                    println!("[Roof Controller] Stop lock command failed: {:?}", e);
+                    error!("Stop lock command failed: {:?}", e);
                     SiderealError::ServerError(format!("Lock stop failed: {:?}", e))
                 })?;
-            println!("[Roof Controller] STOP LOCK command sent successfully");
+            info!("STOP LOCK command sent successfully");
             Ok(())
         }
         None => {
-            println!("[Roof Controller] Stop lock command failed: device not available");
+            warn!("Stop lock command failed: device not available");
             Err(SiderealError::ServerError(
                 "Roof Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
             ))
@@ -222,6 +230,46 @@ pub async fn stop_lock() -> SiderealResult<()> {
     }
 }
 
+/// One value from the controller's telemetry, as it last reported it
+async fn telemetry_value(element: &str) -> Option<f64> {
+    let device = CONNECTED_DEVICES.read().await.roof_controller.clone()?;
+    let param_notify = device.get_parameter("TELEMETRY").await.ok()?;
+    let param = param_notify.lock().await;
+    let map = param
+        .get_values::<std::collections::HashMap<String, indi::Number>>()
+        .ok()?;
+    Some(map.get(element)?.value.into())
+}
+
+/// The controller's 12V supply
+pub async fn supply_voltage() -> Option<f64> {
+    telemetry_value("VOLTAGE_12V").await
+}
+
+/// What the roof is doing, as the controller last reported it
+pub async fn roof_state() -> Option<RoofState> {
+    telemetry_value("ROOF_STATE")
+        .await
+        .and_then(|state| RoofState::from_telemetry(state as u8))
+}
+
+/// Whether the rain/cloud sensor is tripped, if the controller has one
+pub async fn rain_detected() -> Option<bool> {
+    telemetry_value(RAIN_SENSOR).await.map(|value| value != 0.0)
+}
+
+/// Close the roof because of rain, straight through `device` so it doesn't
+/// wait on anything else
+async fn close_for_rain(device: &ActiveDevice) {
+    warn!("Rain detected with the roof open, closing it");
+    if let Err(e) = device
+        .change("ROOF_CONTROL", vec![("ROOF_CLOSE", true)])
+        .await
+    {
+        error!("Closing the roof for rain failed: {:?}", e);
+    }
+}
+
 /// Watch telemetry from the roof controller
 pub async fn watch_telemetry<S>(device: ActiveDevice, output: &mut S)
 where
@@ -239,6 +287,10 @@ where
     // Subscribe to telemetry updates
     let mut changes = param_notify.subscribe().await;
 
+    // rain interlock, kept here so it works whatever the rest of the app is doing
+    let mut was_raining = false;
+    let mut last_rain_close: Option<Instant> = None;
+
     // Event loop - just process data, timeout is handled by generic param_watcher
     loop {
         match changes.next().await {
@@ -295,6 +347,33 @@ where
                         roof_state_val.map(|s| s == 1 || s == 2).unwrap_or(false); // 1=opening, 2=open
                     let roof_is_closed_val =
                         roof_state_val.map(|s| s == 3 || s == 4).unwrap_or(false); // 3=closing, 4=closed
+                    let raining = map
+                        .get(RAIN_SENSOR)
+                        .map(|n| {
+                            let val: f64 = n.value.into();
+                            val != 0.0
+                        })
+                        .unwrap_or(false);
+                    // closed or closing already
+                    let roof_shut = roof_state_val.map(|s| s == 3 || s == 4).unwrap_or(true);
+                    if raining && !roof_shut {
+                        let retry_due = last_rain_close
+                            .map(|last| last.elapsed() > RAIN_CLOSE_RETRY)
+                            .unwrap_or(true);
+                        if retry_due {
+                            close_for_rain(&device).await;
+                            last_rain_close = Some(Instant::now());
+                        }
+                        if !was_raining {
+                            let _ = output
+                                .send(Message::IndiError(
+                                    "Rain detected with the roof open. Closing the roof."
+                                        .to_owned(),
+                                ))
+                                .await;
+                        }
+                    }
+                    was_raining = raining;
                     let roof_position_val = map
                         .get("POSITION")
                         .map(|n| {
@@ -309,6 +388,7 @@ where
                             roof_is_open: roof_is_open_val,
                             roof_is_closed: roof_is_closed_val,
                             roof_position: roof_position_val,
+                            roof_state: roof_state_val.and_then(RoofState::from_telemetry),
                             lock_engaged: lock_state.map(|s| s == 1).unwrap_or(false),
                             voltage_5v: voltage_5v.unwrap_or(0.0),
                             voltage_12v: voltage_12v.unwrap_or(0.0),
@@ -317,6 +397,7 @@ where
                             limit_u2: limit_u2.map(|s| s != 0).unwrap_or(false),
                             limit_l1: limit_l1.map(|s| s != 0).unwrap_or(false),
                             limit_l2: limit_l2.map(|s| s != 0).unwrap_or(false),
+                            raining,
                         }))
                         .await;
                 }
@@ -6,7 +6,11 @@ use crate::{
 };
 use iced::futures::{Sink, SinkExt, StreamExt};
 use indi::client::active_device::ActiveDevice;
-use std::{collections::HashMap, time::Instant};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use tokio::time;
 
 use super::CONNECTED_DEVICES;
 
@@ -118,6 +122,125 @@ pub async fn set_flat_light(on: bool) -> SiderealResult<()> {
     }
 }
 
+/// A heater's closed loop settings on the controller, from its
+/// `HEATERn_PID` property
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeaterPid {
+    /// °C the heater is regulated to
+    pub setpoint: f64,
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+}
+
+/// Setpoint and gains of heater `heater` (1-3), if the controller has them
+pub async fn heater_pid(heater: u8) -> Option<HeaterPid> {
+    let device = CONNECTED_DEVICES
+        .read()
+        .await
+        .telescope_controller
+        .clone()?;
+    let param_notify = device
+        .get_parameter(&format!("HEATER{heater}_PID"))
+        .await
+        .ok()?;
+    let param = param_notify.lock().await;
+    let map = param.get_values::<HashMap<String, indi::Number>>().ok()?;
+    let value = |element: &str| map.get(element).map(|n| -> f64 { n.value.into() });
+    Some(HeaterPid {
+        setpoint: value("SETPOINT")?,
+        kp: value("KP")?,
+        ki: value("KI")?,
+        kd: value("KD")?,
+    })
+}
+
+/// Set the setpoint and gains of heater `heater` (1-3)
+pub async fn set_heater_pid(heater: u8, pid: HeaterPid) -> SiderealResult<()> {
+    let devices = CONNECTED_DEVICES.read().await;
+    match &devices.telescope_controller {
+        Some(device) => {
+            device
+                .change(
+                    &format!("HEATER{heater}_PID"),
+                    vec![
+                        ("SETPOINT", pid.setpoint),
+                        ("KP", pid.kp),
+                        ("KI", pid.ki),
+                        ("KD", pid.kd),
+                    ],
+                )
+                .await
+                .map_err(|e| {
+                    SiderealError::ServerError(format!(
+                        "Heater{heater} PID settings failed: {:?}",
+                        e
+                    ))
+                })?;
+            Ok(())
+        }
+        None => Err(SiderealError::ServerError(
+            "Telescope Controller device not available. Please ensure the device is connected to the INDI server.".to_owned(),
+        )),
+    }
+}
+
+/// Whether one of the controller's accessories is open or on, from the
+/// `*_REAL_STATE` value in its telemetry
+async fn accessory_state(element: &str) -> Option<bool> {
+    let device = CONNECTED_DEVICES
+        .read()
+        .await
+        .telescope_controller
+        .clone()?;
+    let param_notify = device.get_parameter("TELEMETRY").await.ok()?;
+    let param = param_notify.lock().await;
+    let map = param.get_values::<HashMap<String, indi::Number>>().ok()?;
+    let value: f64 = map.get(element)?.value.into();
+    Some(value != 0.0)
+}
+
+/// Wait for the controller to report `element` as `on`, i.e. for the
+/// accessory to finish moving or switching; `waiting_for` describes it
+async fn wait_for_accessory(
+    element: &str,
+    on: bool,
+    waiting_for: &str,
+    timeout: Duration,
+) -> SiderealResult<()> {
+    let started = Instant::now();
+    while accessory_state(element).await != Some(on) {
+        if started.elapsed() > timeout {
+            return Err(SiderealError::ServerError(format!(
+                "Gave up waiting for the {waiting_for} after {:.0}s",
+                timeout.as_secs_f64()
+            )));
+        }
+        time::sleep(Duration::from_millis(500)).await;
+    }
+    Ok(())
+}
+
+/// Wait until the lens cap is fully open or closed
+pub async fn wait_for_lens_cap(open: bool, timeout: Duration) -> SiderealResult<()> {
+    let waiting_for = if open {
+        "lens cap to open"
+    } else {
+        "lens cap to close"
+    };
+    wait_for_accessory("LENS_CAP_REAL_STATE", open, waiting_for, timeout).await
+}
+
+/// Wait until the flat light is on or off
+pub async fn wait_for_flat_light(on: bool, timeout: Duration) -> SiderealResult<()> {
+    let waiting_for = if on {
+        "flat light to come on"
+    } else {
+        "flat light to go off"
+    };
+    wait_for_accessory("FLAT_LIGHT_REAL_STATE", on, waiting_for, timeout).await
+}
+
 /// Watch for telemetry updates and send them to the UI
 /// This function runs until the connection is lost
 pub async fn watch_telemetry<S>(device: ActiveDevice, output: &mut S)
@@ -149,6 +272,7 @@ where
 
                     // Extract telemetry values - convert Sexagesimal to f64
                     let ambient_temp = map.get("AMBIENT_TEMP").map(|n| n.value.into());
+                    let ambient_humidity = map.get("AMBIENT_HUMIDITY").map(|n| n.value.into());
                     let heater1_temp = map.get("HEATER1_TEMP").map(|n| n.value.into());
                     let heater2_temp = map.get("HEATER2_TEMP").map(|n| n.value.into());
                     let heater3_temp = map.get("HEATER3_TEMP").map(|n| n.value.into());
@@ -200,6 +324,7 @@ where
                     let _ = output
                         .send(Message::Telescope(TelescopeMessage::TelemetryUpdate {
                             ambient_temp: ambient_temp.unwrap_or(0.0),
+                            ambient_humidity,
                             heater1_temp: heater1_temp.unwrap_or(0.0),
                             heater2_temp: heater2_temp.unwrap_or(0.0),
                             heater3_temp: heater3_temp.unwrap_or(0.0),
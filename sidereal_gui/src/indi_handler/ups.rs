@@ -0,0 +1,26 @@
+use std::collections::HashMap;
+
+use super::CONNECTED_DEVICES;
+
+/// What a UPS reports in its UPS_STATUS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpsStatus {
+    /// `false` while running on the battery
+    pub on_mains: Option<bool>,
+    pub battery_percent: Option<f64>,
+    pub load_percent: Option<f64>,
+}
+
+/// The UPS's state, if one is connected and has reported it
+pub async fn status() -> Option<UpsStatus> {
+    let device = CONNECTED_DEVICES.read().await.ups.clone()?;
+    let param_notify = device.get_parameter("UPS_STATUS").await.ok()?;
+    let param = param_notify.lock().await;
+    let map = param.get_values::<HashMap<String, indi::Number>>().ok()?;
+    let value = |element: &str| map.get(element).map(|n| -> f64 { n.value.into() });
+    Some(UpsStatus {
+        on_mains: value("MAINS").map(|mains| mains != 0.0),
+        battery_percent: value("BATTERY_PERCENT"),
+        load_percent: value("LOAD_PERCENT"),
+    })
+}
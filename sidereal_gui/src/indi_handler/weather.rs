@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use super::CONNECTED_DEVICES;
+
+/// What the weather station makes of conditions overall
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherStatus {
+    Ok,
+    /// A parameter is in its warning range
+    Warning,
+    /// A parameter is in its danger range
+    Alert,
+}
+
+/// The weather station's verdict, from the state of its WEATHER_STATUS
+/// lights, if one is connected and has made one
+pub async fn status() -> Option<WeatherStatus> {
+    let device = CONNECTED_DEVICES.read().await.weather.clone()?;
+    let param_notify = device.get_parameter("WEATHER_STATUS").await.ok()?;
+    // the driver sets the vector to the worst of its lights
+    let status = match *param_notify.lock().await.get_state() {
+        indi::PropertyState::Ok => WeatherStatus::Ok,
+        indi::PropertyState::Busy => WeatherStatus::Warning,
+        indi::PropertyState::Alert => WeatherStatus::Alert,
+        indi::PropertyState::Idle => return None,
+    };
+    Some(status)
+}
+
+/// Air temperature and humidity outside
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ambient {
+    /// °C
+    pub temperature: f64,
+    /// Relative, in percent
+    pub humidity: f64,
+}
+
+/// Temperature and humidity from the weather station's WEATHER_PARAMETERS,
+/// if one is connected and reports both
+pub async fn ambient() -> Option<Ambient> {
+    let device = CONNECTED_DEVICES.read().await.weather.clone()?;
+    let param_notify = device.get_parameter("WEATHER_PARAMETERS").await.ok()?;
+    let param = param_notify.lock().await;
+    let map = param.get_values::<HashMap<String, indi::Number>>().ok()?;
+    let value = |element: &str| map.get(element).map(|n| -> f64 { n.value.into() });
+    Some(Ambient {
+        temperature: value("WEATHER_TEMPERATURE")?,
+        humidity: value("WEATHER_HUMIDITY")?,
+    })
+}
@@ -1,6 +1,12 @@
 pub mod app;
+mod analysis;
 mod config;
+mod fits;
 mod gui;
+mod guiding;
 pub mod indi_handler;
+pub mod logging;
 mod model;
+mod phd2_handler;
+mod plate_solver;
 pub mod planetarium_handler;
@@ -0,0 +1,200 @@
+use std::{
+    fmt::{self, Write as _},
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Local};
+use iced::futures::SinkExt;
+use iced::stream;
+use once_cell::sync::OnceCell;
+use tokio::sync::mpsc;
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{
+    filter::Targets,
+    layer::{Context, SubscriberExt},
+    util::SubscriberInitExt,
+    Layer,
+};
+
+/// Session logs kept, this one included; older ones are deleted at startup
+const MAX_SESSION_LOGS: usize = 20;
+/// What gets logged unless RUST_LOG says otherwise
+const DEFAULT_FILTER: &str = "warn,sidereal_gui=debug,overpass_planner=info";
+
+static SESSION_LOG: OnceCell<PathBuf> = OnceCell::new();
+static PANEL_TX: OnceCell<mpsc::UnboundedSender<LogEntry>> = OnceCell::new();
+static PANEL_RX: OnceCell<Arc<tokio::sync::Mutex<Option<mpsc::UnboundedReceiver<LogEntry>>>>> =
+    OnceCell::new();
+
+/// One logged event, as the Log tab shows it
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Message and fields of an event, as "message key=value ..."
+#[derive(Default)]
+struct EventText {
+    message: String,
+    /// Where a record from the `log` crate came from
+    log_target: Option<String>,
+}
+
+impl Visit for EventText {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "log.target" => self.log_target = Some(value.to_owned()),
+            name if name.starts_with("log.") => {}
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name().starts_with("log.") {
+            return;
+        }
+        if !self.message.is_empty() {
+            self.message.push(' ');
+        }
+        let _ = match field.name() {
+            "message" => write!(self.message, "{value:?}"),
+            name => write!(self.message, "{name}={value:?}"),
+        };
+    }
+}
+
+/// Hands every event to the Log tab
+struct PanelLayer;
+
+impl<S: Subscriber> Layer<S> for PanelLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let Some(tx) = PANEL_TX.get() else {
+            return;
+        };
+        let mut text = EventText::default();
+        event.record(&mut text);
+        let metadata = event.metadata();
+        let _ = tx.send(LogEntry {
+            time: Local::now(),
+            level: *metadata.level(),
+            target: text
+                .log_target
+                .unwrap_or_else(|| metadata.target().to_owned()),
+            message: text.message,
+        });
+    }
+}
+
+fn log_directory() -> PathBuf {
+    dirs_next::data_local_dir()
+        .or_else(dirs_next::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("sidereal")
+        .join("logs")
+}
+
+/// Delete all but the newest session logs, leaving room for `keep` more
+fn prune_session_logs(directory: &Path, keep: usize) -> std::io::Result<()> {
+    let mut logs: Vec<PathBuf> = std::fs::read_dir(directory)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("session-") && name.ends_with(".log"))
+        })
+        .collect();
+    // the names are timestamped, so they sort oldest first
+    logs.sort();
+    let excess = logs.len().saturating_sub(keep);
+    for path in &logs[..excess] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Start a new session log
+fn open_session_log() -> std::io::Result<(PathBuf, File)> {
+    let directory = log_directory();
+    std::fs::create_dir_all(&directory)?;
+    prune_session_logs(&directory, MAX_SESSION_LOGS - 1)?;
+    let path = directory.join(format!(
+        "session-{}.log",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let file = File::create(&path)?;
+    Ok((path, file))
+}
+
+/// Send logging to stderr, this session's log file and the Log tab. RUST_LOG
+/// narrows or widens it with the usual `target=level` directives.
+pub fn init() {
+    let filter = std::env::var("RUST_LOG")
+        .ok()
+        .and_then(|directives| directives.parse::<Targets>().ok())
+        .unwrap_or_else(|| DEFAULT_FILTER.parse().expect("default log filter is valid"));
+
+    let (tx, rx) = mpsc::unbounded_channel();
+    let _ = PANEL_TX.set(tx);
+    let _ = PANEL_RX.set(Arc::new(tokio::sync::Mutex::new(Some(rx))));
+
+    let (file_layer, failure) = match open_session_log() {
+        Ok((path, file)) => {
+            let _ = SESSION_LOG.set(path);
+            let layer = tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+                .with_filter(filter.clone());
+            (Some(layer), None)
+        }
+        Err(e) => (None, Some(e)),
+    };
+
+    let registry = tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_filter(filter.clone()),
+        )
+        .with(file_layer)
+        .with(PanelLayer.with_filter(filter));
+    if let Err(e) = registry.try_init() {
+        eprintln!("Logging is already set up: {e}");
+        return;
+    }
+
+    match (session_log(), failure) {
+        (Some(path), _) => tracing::info!("Logging this session to {}", path.display()),
+        (None, Some(e)) => tracing::warn!("No session log this time: {e}"),
+        (None, None) => {}
+    }
+}
+
+/// This session's log file, if one could be written
+pub fn session_log() -> Option<&'static Path> {
+    SESSION_LOG.get().map(PathBuf::as_path)
+}
+
+/// Everything logged from startup on, for the Log tab
+pub fn watch() -> impl iced::futures::Stream<Item = LogEntry> {
+    stream::channel(256, |mut output| async move {
+        let Some(holder) = PANEL_RX.get().cloned() else {
+            return;
+        };
+        let Some(mut rx) = holder.lock().await.take() else {
+            return;
+        };
+        while let Some(entry) = rx.recv().await {
+            if output.send(entry).await.is_err() {
+                break;
+            }
+        }
+    })
+}
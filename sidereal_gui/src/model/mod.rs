@@ -21,4 +21,8 @@ pub enum SiderealError {
     FormatError(String),
     #[error("gRPC Error: {0}")]
     GrpcError(String),
+    #[error("FileError: {0}")]
+    FileError(String),
+    #[error("Phd2Error: {0}")]
+    Phd2Error(String),
 }
@@ -1,19 +1,440 @@
-#[allow(dead_code)]
-enum TrackingMode {
-    Generic,
-    Planet,
-    Lunar,
-    Solar,
-    Satellite,
-}
-
-#[allow(dead_code)]
-enum TrackingState {
-    Idle,
+use chrono::{DateTime, Duration, Utc};
+use overpass_planner::{ObserverLocation, PropagationContext, Propagator};
+
+use crate::model::{SiderealError, SiderealResult};
+
+/// Spacing of the setpoints along a pass
+const SETPOINT_INTERVAL: Duration = Duration::seconds(1);
+/// RA rate (arcseconds per second) that holds a star still
+const SIDEREAL_RATE_ARCSEC: f64 = 15.041_067;
+
+/// Where the satellite is at one moment of a pass
+#[derive(Debug, Clone, Copy)]
+pub struct Setpoint {
+    pub time: DateTime<Utc>,
+    /// Degrees above the horizon
+    pub altitude: f64,
+    /// Degrees from North through East
+    pub azimuth: f64,
+    /// Of date, as the mount points
+    pub ra_hours: f64,
+    pub dec_deg: f64,
+}
+
+/// Setpoints every second of the pass from `start` to `end` of the satellite
+/// whose elements are `tle`
+pub fn pass_setpoints(
+    tle: &str,
+    location: ObserverLocation,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> SiderealResult<Vec<Setpoint>> {
+    if end <= start {
+        return Err(SiderealError::ParseError(
+            "The pass ends before it starts".to_owned(),
+        ));
+    }
+    let propagator = PropagationContext::from_tle(tle)
+        .map_err(|e| SiderealError::ParseError(format!("Can't follow the pass: {e}")))?;
+    let trajectory = propagator
+        .trajectory(location, start, end, SETPOINT_INTERVAL)
+        .map_err(|e| SiderealError::ServerError(format!("Can't follow the pass: {e}")))?;
+    Ok(trajectory
+        .into_iter()
+        .map(|position| {
+            let (ra_hours, dec_deg) = equatorial_from_horizontal(
+                position.timestamp,
+                location,
+                position.altitude,
+                position.azimuth,
+            );
+            Setpoint {
+                time: position.timestamp,
+                altitude: position.altitude,
+                azimuth: position.azimuth,
+                ra_hours,
+                dec_deg,
+            }
+        })
+        .collect())
+}
+
+/// (RA hours, Dec degrees) of date seen at `altitude`/`azimuth` degrees
+pub fn equatorial_from_horizontal(
+    time: DateTime<Utc>,
+    location: ObserverLocation,
+    altitude: f64,
+    azimuth: f64,
+) -> (f64, f64) {
+    let lat = location.latitude.to_radians();
+    let (alt, az) = (altitude.to_radians(), azimuth.to_radians());
+    let dec = (alt.sin() * lat.sin() + alt.cos() * lat.cos() * az.cos())
+        .clamp(-1.0, 1.0)
+        .asin();
+    let hour_angle =
+        (-az.sin() * alt.cos()).atan2(alt.sin() * lat.cos() - alt.cos() * lat.sin() * az.cos());
+
+    // Greenwich mean sidereal time (degrees) from days since J2000, then local
+    let days = time.timestamp_millis() as f64 / 86_400_000.0 - 10_957.5;
+    let gmst = 280.460_618_37 + 360.985_647_366_29 * days;
+    let lst = (gmst + location.longitude).to_radians();
+    let ra = (lst - hour_angle).to_degrees().rem_euclid(360.0) / 15.0;
+    (ra, dec.to_degrees())
+}
+
+/// How a pass is followed
+#[derive(Debug, Clone, Copy)]
+pub struct TrackingOptions {
+    /// Slew ahead of the satellite and wait for it to arrive, rather than
+    /// chase it
+    pub leapfrog: bool,
+    /// How far ahead (seconds along the pass) each leapfrog goes
+    pub leapfrog_secs: f64,
+    /// Hold the mount still while the satellite is below `horizon_deg`
+    pub pause_at_horizon: bool,
+    pub horizon_deg: f64,
+    /// Follow by continuously changing the mount's track rates, for mounts
+    /// that take custom rates; leapfrogs only to catch the satellite up
+    pub track_rate: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackingState {
+    /// At the start of the pass, waiting for the satellite
+    Waiting,
     Tracking,
+    /// Holding still until the satellite is back above the horizon limit
+    Paused,
+    Finished,
 }
-#[allow(dead_code)]
-struct TrackingManager {
+
+/// What the mount is to do next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrackingCommand {
+    SlewTo {
+        ra_hours: f64,
+        dec_deg: f64,
+    },
+    /// Track at these rates (arcseconds per second, as `TELESCOPE_TRACK_RATE`
+    /// takes them)
+    SetRate {
+        ra_arcsec: f64,
+        dec_arcsec: f64,
+    },
+    /// Stop moving with the satellite, holding still against the stars
+    Stop,
+}
+
+/// Drives the mount through a pass, one command at a time
+#[derive(Debug, Clone)]
+pub struct TrackingManager {
+    setpoints: Vec<Setpoint>,
+    options: TrackingOptions,
     state: TrackingState,
-    mode: TrackingMode,
+    /// Time of the setpoint the mount was last sent to
+    aimed_at: Option<DateTime<Utc>>,
+    /// Time of the setpoint whose rates the mount was last given
+    rated_at: Option<DateTime<Utc>>,
+}
+
+impl TrackingManager {
+    pub fn new(setpoints: Vec<Setpoint>, options: TrackingOptions) -> SiderealResult<Self> {
+        let manager = Self {
+            setpoints,
+            options,
+            state: TrackingState::Waiting,
+            aimed_at: None,
+            rated_at: None,
+        };
+        if !manager.setpoints.iter().any(|point| manager.visible(point)) {
+            return Err(SiderealError::ParseError(
+                "The satellite never gets above the horizon limit".to_owned(),
+            ));
+        }
+        Ok(manager)
+    }
+
+    pub fn state(&self) -> TrackingState {
+        self.state
+    }
+
+    /// Where the satellite is at `now`, while the pass is on
+    pub fn current(&self, now: DateTime<Utc>) -> Option<&Setpoint> {
+        if self.ended(now) {
+            return None;
+        }
+        let index = self.setpoints.partition_point(|point| point.time <= now);
+        index.checked_sub(1).map(|index| &self.setpoints[index])
+    }
+
+    fn ended(&self, now: DateTime<Utc>) -> bool {
+        self.setpoints.last().is_none_or(|last| now >= last.time)
+    }
+
+    fn visible(&self, point: &Setpoint) -> bool {
+        !self.options.pause_at_horizon || point.altitude >= self.options.horizon_deg
+    }
+
+    /// The setpoint `seconds` after `now`, or the last one if the pass is
+    /// over by then
+    fn ahead(&self, now: DateTime<Utc>, seconds: f64) -> &Setpoint {
+        let when = now + Duration::milliseconds((seconds * 1000.0) as i64);
+        let index = self.setpoints.partition_point(|point| point.time < when);
+        &self.setpoints[index.min(self.setpoints.len() - 1)]
+    }
+
+    /// Track rates that keep up with the satellite from the setpoint at
+    /// `index` to the next
+    fn rates_at(&self, index: usize) -> (f64, f64) {
+        let index = index.min(self.setpoints.len().saturating_sub(2));
+        let (Some(from), Some(to)) = (self.setpoints.get(index), self.setpoints.get(index + 1))
+        else {
+            return (SIDEREAL_RATE_ARCSEC, 0.0);
+        };
+        let seconds = (to.time - from.time).num_milliseconds() as f64 / 1000.0;
+        // the short way round, across 0h
+        let ra_hours = (to.ra_hours - from.ra_hours + 12.0).rem_euclid(24.0) - 12.0;
+        let ra_arcsec = ra_hours * 15.0 * 3600.0 / seconds;
+        let dec_arcsec = (to.dec_deg - from.dec_deg) * 3600.0 / seconds;
+        // the RA axis turns against the stars' motion to hold still, and
+        // against the satellite's on top of that
+        (SIDEREAL_RATE_ARCSEC - ra_arcsec, dec_arcsec)
+    }
+
+    /// Move on to `now`, returning what the mount should do if anything
+    pub fn update(&mut self, now: DateTime<Utc>) -> Option<TrackingCommand> {
+        let slew_to = |point: &Setpoint| TrackingCommand::SlewTo {
+            ra_hours: point.ra_hours,
+            dec_deg: point.dec_deg,
+        };
+        if self.state == TrackingState::Finished {
+            return None;
+        }
+        if self.ended(now) {
+            self.state = TrackingState::Finished;
+            return Some(TrackingCommand::Stop);
+        }
+        let Some(current) = self.current(now).copied() else {
+            // wait where the satellite first comes into view
+            if self.aimed_at.is_some() {
+                return None;
+            }
+            let first = *self.setpoints.iter().find(|point| self.visible(point))?;
+            self.aimed_at = Some(first.time);
+            return Some(slew_to(&first));
+        };
+
+        if !self.visible(&current) {
+            if self.state == TrackingState::Paused {
+                return None;
+            }
+            let was_tracking = self.state == TrackingState::Tracking;
+            self.state = TrackingState::Paused;
+            if !was_tracking {
+                // still waiting where the satellite comes into view
+                return None;
+            }
+            // it reappears somewhere else, so the mount will have to go there
+            self.aimed_at = None;
+            self.rated_at = None;
+            return Some(TrackingCommand::Stop);
+        }
+        self.state = TrackingState::Tracking;
+
+        if self.options.track_rate {
+            match self.aimed_at {
+                // waiting for the satellite to reach the mount
+                Some(aimed_at) if aimed_at > now => return None,
+                Some(_) => {}
+                None => {
+                    let target = *self.ahead(now, self.options.leapfrog_secs);
+                    self.aimed_at = Some(target.time);
+                    return Some(slew_to(&target));
+                }
+            }
+            if self.rated_at == Some(current.time) {
+                return None;
+            }
+            self.rated_at = Some(current.time);
+            let index = self.setpoints.partition_point(|point| point.time <= now) - 1;
+            let (ra_arcsec, dec_arcsec) = self.rates_at(index);
+            Some(TrackingCommand::SetRate {
+                ra_arcsec,
+                dec_arcsec,
+            })
+        } else if self.options.leapfrog {
+            // leap again once the satellite has caught up with the mount
+            if self.aimed_at.is_some_and(|aimed_at| aimed_at > now) {
+                return None;
+            }
+            let target = *self.ahead(now, self.options.leapfrog_secs);
+            self.aimed_at = Some(target.time);
+            Some(slew_to(&target))
+        } else {
+            if self.aimed_at == Some(current.time) {
+                return None;
+            }
+            self.aimed_at = Some(current.time);
+            Some(slew_to(&current))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn base() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 2, 0, 0).unwrap()
+    }
+
+    fn at(seconds: f64) -> DateTime<Utc> {
+        base() + Duration::milliseconds((seconds * 1000.0) as i64)
+    }
+
+    /// One setpoint a second, at these altitudes, with RA stepping 0.01h
+    fn setpoints(altitudes: &[f64]) -> Vec<Setpoint> {
+        altitudes
+            .iter()
+            .enumerate()
+            .map(|(i, &altitude)| Setpoint {
+                time: at(i as f64),
+                altitude,
+                azimuth: 180.0,
+                ra_hours: 10.0 + i as f64 * 0.01,
+                dec_deg: 20.0 + i as f64 * 0.1,
+            })
+            .collect()
+    }
+
+    fn options() -> TrackingOptions {
+        TrackingOptions {
+            leapfrog: false,
+            leapfrog_secs: 3.0,
+            pause_at_horizon: true,
+            horizon_deg: 10.0,
+            track_rate: false,
+        }
+    }
+
+    fn slew_to(point: &Setpoint) -> Option<TrackingCommand> {
+        Some(TrackingCommand::SlewTo {
+            ra_hours: point.ra_hours,
+            dec_deg: point.dec_deg,
+        })
+    }
+
+    #[test]
+    fn test_chase_waits_tracks_pauses_and_finishes() {
+        let points = setpoints(&[20.0, 20.0, 20.0, 5.0, 5.0, 20.0, 20.0, 20.0]);
+        let mut manager = TrackingManager::new(points.clone(), options()).unwrap();
+
+        // before the pass the mount goes to where it starts
+        assert_eq!(manager.update(at(-10.0)), slew_to(&points[0]));
+        assert_eq!(manager.state(), TrackingState::Waiting);
+        assert_eq!(manager.update(at(-5.0)), None);
+
+        // already there when the satellite arrives
+        assert_eq!(manager.update(at(0.2)), None);
+        assert_eq!(manager.state(), TrackingState::Tracking);
+        assert_eq!(manager.update(at(1.2)), slew_to(&points[1]));
+        assert_eq!(manager.update(at(1.7)), None);
+
+        // below the horizon limit
+        assert_eq!(manager.update(at(3.2)), Some(TrackingCommand::Stop));
+        assert_eq!(manager.state(), TrackingState::Paused);
+        assert_eq!(manager.update(at(4.2)), None);
+
+        // back above it
+        assert_eq!(manager.update(at(5.2)), slew_to(&points[5]));
+        assert_eq!(manager.state(), TrackingState::Tracking);
+
+        assert_eq!(manager.update(at(7.0)), Some(TrackingCommand::Stop));
+        assert_eq!(manager.state(), TrackingState::Finished);
+        assert_eq!(manager.update(at(8.0)), None);
+    }
+
+    #[test]
+    fn test_never_visible_is_rejected() {
+        assert!(TrackingManager::new(setpoints(&[5.0, 8.0, 5.0]), options()).is_err());
+    }
+
+    #[test]
+    fn test_leapfrog_reaims_once_the_satellite_catches_up() {
+        let points = setpoints(&[20.0; 10]);
+        let options = TrackingOptions {
+            leapfrog: true,
+            ..options()
+        };
+        let mut manager = TrackingManager::new(points.clone(), options).unwrap();
+
+        assert_eq!(manager.update(at(0.0)), slew_to(&points[3]));
+        assert_eq!(manager.update(at(1.0)), None);
+        assert_eq!(manager.update(at(2.5)), None);
+        // the satellite has reached the mount, so it leaps again
+        assert_eq!(manager.update(at(3.0)), slew_to(&points[6]));
+        assert_eq!(manager.update(at(5.0)), None);
+        assert_eq!(manager.update(at(6.0)), slew_to(&points[9]));
+    }
+
+    #[test]
+    fn test_rates_take_the_short_way_across_0h() {
+        let mut points = setpoints(&[20.0, 20.0, 20.0]);
+        points[0].ra_hours = 23.999;
+        points[1].ra_hours = 0.001;
+        points[0].dec_deg = 45.0;
+        points[1].dec_deg = 45.01;
+        let manager = TrackingManager::new(points, options()).unwrap();
+
+        let (ra_arcsec, dec_arcsec) = manager.rates_at(0);
+        // 0.002h in a second, not 23.998h backwards
+        assert!((ra_arcsec - (SIDEREAL_RATE_ARCSEC - 108.0)).abs() < 1e-6);
+        assert!((dec_arcsec - 36.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_track_rate_catches_up_then_streams_rates() {
+        let points = setpoints(&[20.0; 10]);
+        let options = TrackingOptions {
+            track_rate: true,
+            ..options()
+        };
+        let mut manager = TrackingManager::new(points.clone(), options).unwrap();
+
+        assert_eq!(manager.update(at(0.0)), slew_to(&points[3]));
+        assert_eq!(manager.update(at(2.0)), None);
+        let (ra_arcsec, dec_arcsec) = manager.rates_at(3);
+        assert_eq!(
+            manager.update(at(3.0)),
+            Some(TrackingCommand::SetRate {
+                ra_arcsec,
+                dec_arcsec
+            })
+        );
+        // one rate per setpoint
+        assert_eq!(manager.update(at(3.5)), None);
+    }
+
+    #[test]
+    fn test_equatorial_from_horizontal() {
+        let location = ObserverLocation {
+            latitude: 45.0,
+            longitude: -75.0,
+            altitude: 0.0,
+        };
+        // the zenith is at the observer's latitude
+        let (_, dec) = equatorial_from_horizontal(base(), location, 90.0, 0.0);
+        assert!((dec - 45.0).abs() < 1e-9);
+        // and the pole is due north, as high as the latitude
+        let (_, dec) = equatorial_from_horizontal(base(), location, 45.0, 0.0);
+        assert!((dec - 90.0).abs() < 1e-9);
+
+        // due south the RA is the local sidereal time, which gains on UTC
+        let (ra, _) = equatorial_from_horizontal(base(), location, 30.0, 180.0);
+        let (later, _) =
+            equatorial_from_horizontal(base() + Duration::hours(1), location, 30.0, 180.0);
+        let gained = (later - ra).rem_euclid(24.0);
+        assert!((gained - 1.002_738).abs() < 1e-4);
+    }
 }
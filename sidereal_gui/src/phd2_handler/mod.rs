@@ -0,0 +1,270 @@
+use crate::{
+    config::Config,
+    model::{SiderealError, SiderealResult},
+};
+use iced::{
+    futures::{SinkExt, Stream},
+    stream,
+};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{tcp::OwnedWriteHalf, TcpStream},
+    sync::{oneshot, Mutex},
+    time,
+};
+
+/// How long to wait for PHD2 to accept the connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long PHD2 may take to answer a call
+const CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Something PHD2 reported, in the terms the Guide tab shows it
+#[derive(Debug, Clone)]
+pub enum Phd2Event {
+    Connected {
+        version: String,
+    },
+    /// PHD2's state, e.g. "Looping", "Calibrating", "Guiding" or "Stopped"
+    AppState(String),
+    /// Guide star offset (pixels) along the RA and Dec axes
+    GuideStep {
+        ra: f64,
+        dec: f64,
+    },
+    StarLost,
+    /// A dither or the start of guiding settled, or failed to
+    SettleDone {
+        error: Option<String>,
+    },
+    Alert(String),
+    Disconnected(String),
+}
+
+type Reply = Result<Value, String>;
+
+/// The open connection, for making calls on
+struct Phd2Client {
+    writer: OwnedWriteHalf,
+    next_id: u64,
+    /// Calls waiting on their replies, by id
+    pending: HashMap<u64, oneshot::Sender<Reply>>,
+    /// Waiting for the next SettleDone
+    settle_waiters: Vec<oneshot::Sender<Result<(), String>>>,
+}
+
+type SharedPhd2Client = Arc<Mutex<Option<Phd2Client>>>;
+
+/// The PHD2 connection, while there is one
+static PHD2_CLIENT: Lazy<SharedPhd2Client> = Lazy::new(|| Arc::new(Mutex::new(None)));
+
+fn not_connected() -> SiderealError {
+    SiderealError::Phd2Error("Not connected to PHD2".to_owned())
+}
+
+/// Connect to PHD2 at `address` (host:port) and stream its events until the
+/// connection closes, ending with `Disconnected`
+pub fn connect(address: String) -> impl Stream<Item = Phd2Event> {
+    stream::channel(100, move |mut output| async move {
+        let socket = match time::timeout(CONNECT_TIMEOUT, TcpStream::connect(&address)).await {
+            Ok(Ok(socket)) => socket,
+            Ok(Err(e)) => {
+                let _ = output
+                    .send(Phd2Event::Disconnected(format!(
+                        "Couldn't connect to PHD2 at {address}: {e}"
+                    )))
+                    .await;
+                return;
+            }
+            Err(_) => {
+                let _ = output
+                    .send(Phd2Event::Disconnected(format!(
+                        "Timed out connecting to PHD2 at {address}"
+                    )))
+                    .await;
+                return;
+            }
+        };
+        let (reader, writer) = socket.into_split();
+        *PHD2_CLIENT.lock().await = Some(Phd2Client {
+            writer,
+            next_id: 1,
+            pending: HashMap::new(),
+            settle_waiters: Vec::new(),
+        });
+
+        // replies and events arrive one JSON object per line
+        let mut lines = BufReader::new(reader).lines();
+        let reason = loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                        continue;
+                    };
+                    if let Some(event) = handle_message(&message).await {
+                        let _ = output.send(event).await;
+                    }
+                }
+                Ok(None) => break "PHD2 closed the connection".to_owned(),
+                Err(e) => break format!("Lost the connection to PHD2: {e}"),
+            }
+        };
+        // dropping the client fails any calls still waiting
+        *PHD2_CLIENT.lock().await = None;
+        let _ = output.send(Phd2Event::Disconnected(reason)).await;
+    })
+}
+
+/// Hand a reply to its call, or turn an event into what the UI shows
+async fn handle_message(message: &Value) -> Option<Phd2Event> {
+    if let Some(id) = message.get("id").and_then(Value::as_u64) {
+        let reply = match message.get("error") {
+            Some(error) => Err(error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("unknown error")
+                .to_owned()),
+            None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+        };
+        let mut client = PHD2_CLIENT.lock().await;
+        if let Some(waiter) = client.as_mut().and_then(|c| c.pending.remove(&id)) {
+            let _ = waiter.send(reply);
+        }
+        return None;
+    }
+
+    let text = |key: &str| {
+        message
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    };
+    let number = |key: &str| message.get(key).and_then(Value::as_f64).unwrap_or_default();
+    let state = |state: &str| Some(Phd2Event::AppState(state.to_owned()));
+    match message.get("Event")?.as_str()? {
+        "Version" => Some(Phd2Event::Connected {
+            version: text("PHDVersion"),
+        }),
+        "AppState" => Some(Phd2Event::AppState(text("State"))),
+        "StartCalibration" => state("Calibrating"),
+        "StartGuiding" => state("Guiding"),
+        "LoopingExposures" => state("Looping"),
+        "GuidingStopped" | "LoopingExposuresStopped" => state("Stopped"),
+        "Paused" => state("Paused"),
+        "GuideStep" => Some(Phd2Event::GuideStep {
+            ra: number("RADistanceRaw"),
+            dec: number("DECDistanceRaw"),
+        }),
+        "StarLost" => Some(Phd2Event::StarLost),
+        "SettleDone" => {
+            let failed = message.get("Status").and_then(Value::as_i64) != Some(0);
+            let error = failed.then(|| text("Error"));
+            let mut client = PHD2_CLIENT.lock().await;
+            if let Some(client) = client.as_mut() {
+                for waiter in client.settle_waiters.drain(..) {
+                    let _ = waiter.send(error.clone().map_or(Ok(()), Err));
+                }
+            }
+            Some(Phd2Event::SettleDone { error })
+        }
+        "Alert" => Some(Phd2Event::Alert(text("Msg"))),
+        _ => None,
+    }
+}
+
+/// Drop the connection
+pub async fn disconnect() {
+    *PHD2_CLIENT.lock().await = None;
+}
+
+/// Call `method` and wait for PHD2's reply
+async fn call(method: &str, params: Value) -> SiderealResult<Value> {
+    let reply = {
+        let mut client = PHD2_CLIENT.lock().await;
+        let client = client.as_mut().ok_or_else(not_connected)?;
+        let id = client.next_id;
+        client.next_id += 1;
+        let (sender, reply) = oneshot::channel();
+        client.pending.insert(id, sender);
+        let request = json!({ "method": method, "params": params, "id": id });
+        client
+            .writer
+            .write_all(format!("{request}\r\n").as_bytes())
+            .await
+            .map_err(|e| SiderealError::Phd2Error(format!("Sending `{method}` failed: {e}")))?;
+        reply
+    };
+    match time::timeout(CALL_TIMEOUT, reply).await {
+        Ok(Ok(Ok(result))) => Ok(result),
+        Ok(Ok(Err(message))) => Err(SiderealError::Phd2Error(format!(
+            "PHD2 refused `{method}`: {message}"
+        ))),
+        Ok(Err(_)) => Err(SiderealError::Phd2Error(
+            "Lost the connection to PHD2".to_owned(),
+        )),
+        Err(_) => Err(SiderealError::Phd2Error(format!(
+            "PHD2 didn't answer `{method}`"
+        ))),
+    }
+}
+
+/// The settling criteria PHD2 is given
+async fn settle_params() -> (Value, Duration) {
+    let phd2 = Config::get().await.phd2;
+    (
+        json!({
+            "pixels": phd2.settle_pixels,
+            "time": phd2.settle_secs,
+            "timeout": phd2.settle_timeout_secs,
+        }),
+        Duration::from_secs_f64(phd2.settle_timeout_secs.max(0.0)),
+    )
+}
+
+/// Start guiding, calibrating first if PHD2 has no calibration. Returns
+/// without waiting for guiding to settle.
+pub async fn start_guiding() -> SiderealResult<()> {
+    let (settle, _) = settle_params().await;
+    call("guide", json!({ "settle": settle, "recalibrate": false })).await?;
+    Ok(())
+}
+
+/// Stop guiding and looping
+pub async fn stop() -> SiderealResult<()> {
+    call("stop_capture", json!({})).await?;
+    Ok(())
+}
+
+/// Dither by the configured amount and wait for guiding to settle
+pub async fn dither() -> SiderealResult<()> {
+    let amount = Config::get().await.phd2.dither_pixels;
+    let (settle, timeout) = settle_params().await;
+    let settled = {
+        let mut client = PHD2_CLIENT.lock().await;
+        let client = client.as_mut().ok_or_else(not_connected)?;
+        let (sender, settled) = oneshot::channel();
+        client.settle_waiters.push(sender);
+        settled
+    };
+    call(
+        "dither",
+        json!({ "amount": amount, "raOnly": false, "settle": settle }),
+    )
+    .await?;
+    // PHD2 gives up on its own at the timeout; this is in case it never says
+    match time::timeout(timeout + CALL_TIMEOUT, settled).await {
+        Ok(Ok(Ok(()))) => Ok(()),
+        Ok(Ok(Err(message))) => Err(SiderealError::Phd2Error(format!(
+            "Guiding didn't settle after dithering: {message}"
+        ))),
+        Ok(Err(_)) => Err(SiderealError::Phd2Error(
+            "Lost the connection to PHD2".to_owned(),
+        )),
+        Err(_) => Err(SiderealError::Phd2Error(
+            "Guiding didn't settle after dithering".to_owned(),
+        )),
+    }
+}
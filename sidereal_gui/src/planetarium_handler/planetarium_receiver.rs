@@ -1,62 +1,33 @@
-// src/server.rs
+// src/planetarium_receiver.rs
 
-use protos::protos::sidereal_server::{Sidereal, SiderealServer};
+use once_cell::sync::OnceCell;
+use protos::protos::{PassTrack, SetTrackingTargetRequest, SunWarning};
 use tokio::sync::mpsc;
-use tonic::{transport::Server, Request, Response, Status};
 
-use protos::protos::{SetTrackingTargetResponse, SetTrackingTargetRequest};
-
-use crate::model::{SiderealError, SiderealResult};
+use crate::gui::widgets::server_status::ServerStatus;
 
+/// Commands the planetarium sends up the link, and the state of the link
+/// itself, handed on to the iced app
 #[derive(Debug, Clone)]
 pub enum ForwardedRPC {
     SetTrackingTargetRequest(SetTrackingTargetRequest),
+    /// A satellite pass chosen with Track in the planetarium
+    TrackPass(PassTrack),
+    /// The tracked target or a planned slew came near the Sun, or cleared it
+    SunWarning(SunWarning),
+    LinkStatus(ServerStatus),
 }
 
-#[derive(Clone)]
-struct SiderealServerInstance {
-    tx: mpsc::UnboundedSender<ForwardedRPC>,
-}
+static FORWARD_TX: OnceCell<mpsc::UnboundedSender<ForwardedRPC>> = OnceCell::new();
 
-impl SiderealServerInstance {
-    pub fn new(tx: mpsc::UnboundedSender<ForwardedRPC>) -> Self {
-        Self { tx }
-    }
+/// Where commands from the planetarium go; set once at startup
+pub fn set_forwarder(tx: mpsc::UnboundedSender<ForwardedRPC>) {
+    let _ = FORWARD_TX.set(tx);
 }
 
-#[tonic::async_trait]
-impl Sidereal for SiderealServerInstance {
-    async fn set_tracking_target(
-        &self,
-        request: Request<SetTrackingTargetRequest>,
-    ) -> Result<Response<SetTrackingTargetResponse>, Status> {
-        let cmd = request.into_inner();
-
-        // Forward to Iced; if GUI is gone, report gracefully.
-        if self
-            .tx
-            .send(ForwardedRPC::SetTrackingTargetRequest(cmd.clone()))
-            .is_err()
-        {
-            return Ok(Response::new(SetTrackingTargetResponse {
-                description: "GUI not available".into(),
-            }));
-        }
-
-        Ok(Response::new(SetTrackingTargetResponse {
-            description: "success".into(),
-        }))
+/// Pass a command on to the app; if the GUI is gone there is nobody to tell
+pub(crate) fn forward(rpc: ForwardedRPC) {
+    if let Some(tx) = FORWARD_TX.get() {
+        let _ = tx.send(rpc);
     }
 }
-
-pub async fn run(tx: mpsc::UnboundedSender<ForwardedRPC>) -> SiderealResult<()> {
-    let addr = "[::1]:50052"
-        .parse()
-        .map_err(|e: std::net::AddrParseError| SiderealError::GrpcError(e.to_string()))?;
-    println!("gRPC server listening on {}", addr);
-    Server::builder()
-        .add_service(SiderealServer::new(SiderealServerInstance::new(tx)))
-        .serve(addr)
-        .await
-        .map_err(|e| SiderealError::GrpcError(e.to_string()))
-}
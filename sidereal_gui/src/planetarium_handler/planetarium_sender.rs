@@ -1,37 +1,69 @@
+use chrono::Utc;
 use once_cell::sync::Lazy;
+use overpass_planner::precession;
+use protos::link;
 use protos::protos::{
-    planetarium_client::PlanetariumClient, SetLocationRequest, SetMountLocationRequest,
+    gui_message::Payload, planetarium_client::PlanetariumClient, planetarium_message, GuiMessage,
+    MountLimits, PlanetariumMessage, SelectedTarget, SetLocationRequest, SetMountLocationRequest,
 };
 #[cfg(unix)]
 use std::os::unix::process::CommandExt;
 use std::{
     io,
     process::{Child, Command, Stdio},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::sync::{mpsc, Mutex};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tonic::{
+    metadata::AsciiMetadataValue,
+    transport::{Certificate, Channel, ClientTlsConfig, Endpoint},
+    Request, Streaming,
 };
-use tokio::sync::Mutex;
-use tonic::transport::Channel;
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
+use super::planetarium_receiver::{self, ForwardedRPC};
 use crate::{
-    config::GLOBAL_CONFIG,
+    config::{PlanetariumLinkConfig, GLOBAL_CONFIG},
+    gui::widgets::server_status::ServerStatus,
     model::{SiderealError, SiderealResult},
 };
 
 /// A global place to store our planetarium child handle.
 static PLANETARIUM_PROCESS: Lazy<Mutex<Option<Child>>> = Lazy::new(|| Mutex::new(None));
-static PLANETARIUM_CLIENT: Lazy<Mutex<Option<PlanetariumClient<Channel>>>> =
+/// Sending half of the link stream to the running planetarium
+static PLANETARIUM_LINK: Lazy<Mutex<Option<mpsc::UnboundedSender<GuiMessage>>>> =
     Lazy::new(|| Mutex::new(None));
+/// Id for the next message on the link, echoed in the planetarium's ack
+static NEXT_MESSAGE_ID: AtomicU64 = AtomicU64::new(1);
+/// Bumped on every launch so the previous planetarium's link task stands down
+static LINK_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// First wait before reconnecting; it doubles after each failure up to the max
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_millis(250);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(8);
 
 /// Spawn & detach the process, returning its Child handle.
-fn spawn_and_detach(path: &str) -> io::Result<Child> {
+fn spawn_and_detach(path: &str, settings: &PlanetariumLinkConfig) -> io::Result<Child> {
     let mut binding = Command::new(path);
     let cmd = binding
         .stdin(Stdio::null())
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit());
 
+    // the planetarium serves the link the way our config says
+    cmd.env(link::ADDRESS_ENV, &settings.address);
+    if let Some(token) = &settings.token {
+        cmd.env(link::TOKEN_ENV, token);
+    }
+    if let Some(tls) = &settings.tls {
+        cmd.env(link::TLS_CERT_ENV, &tls.cert_path)
+            .env(link::TLS_KEY_ENV, &tls.key_path);
+    }
+
     #[cfg(unix)]
     {
         cmd.before_exec(|| {
@@ -55,7 +87,6 @@ fn spawn_and_detach(path: &str) -> io::Result<Child> {
 /// Launches “planetarium” only if our tracked process has exited (or wasn’t started yet).
 pub async fn launch_planetarium() -> io::Result<()> {
     let mut planetarium_lock = PLANETARIUM_PROCESS.lock().await;
-    let mut client_lock = PLANETARIUM_CLIENT.lock().await;
     // If we have a child, see if it's still running
     if let Some(child) = planetarium_lock.as_mut() {
         match child.try_wait()? {
@@ -71,49 +102,219 @@ pub async fn launch_planetarium() -> io::Result<()> {
     }
 
     // Spawn and store the new handle
-    let child = spawn_and_detach("planetarium")?;
-    *client_lock = Some(
-        PlanetariumClient::connect("http://[::1]:50051")
-            .await
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?,
-    );
-
+    let settings = GLOBAL_CONFIG.read().await.planetarium_link.clone();
+    let token = settings
+        .token
+        .as_ref()
+        .map(|token| AsciiMetadataValue::try_from(format!("Bearer {token}")))
+        .transpose()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+    let child = spawn_and_detach("planetarium", &settings)?;
     *planetarium_lock = Some(child);
+
+    let generation = LINK_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+    tokio::spawn(maintain_link(generation, settings, token));
     Ok(())
 }
 
-pub async fn set_site_location() -> SiderealResult<()> {
-    let guard = GLOBAL_CONFIG.write().await;
-    let mut client_lock = PLANETARIUM_CLIENT.lock().await;
-    if let Some(client) = client_lock.as_mut() {
-        let request = SetLocationRequest {
-            latitude: guard.location.latitude,
-            longitude: guard.location.longitude,
-            altitude: guard.location.altitude,
-        };
-        let response = client
-            .set_location(request)
-            .await
-            .map_err(|e| SiderealError::ServerError(e.to_string()))?;
-        println!("{}", response.into_inner().description);
+/// Whether the planetarium we launched is still running
+async fn planetarium_running() -> bool {
+    let mut planetarium_lock = PLANETARIUM_PROCESS.lock().await;
+    planetarium_lock
+        .as_mut()
+        .is_some_and(|child| matches!(child.try_wait(), Ok(None)))
+}
+
+/// Keep a link open for as long as the planetarium runs, reconnecting with
+/// backoff whenever it drops, and tell the app how it's going
+async fn maintain_link(
+    generation: u64,
+    settings: PlanetariumLinkConfig,
+    token: Option<AsciiMetadataValue>,
+) {
+    let report = |status| planetarium_receiver::forward(ForwardedRPC::LinkStatus(status));
+    let mut backoff = RECONNECT_BACKOFF_MIN;
+    report(ServerStatus::Connecting);
+
+    while LINK_GENERATION.load(Ordering::Relaxed) == generation && planetarium_running().await {
+        match open_link(&settings, token.clone()).await {
+            Ok((link, inbound)) => {
+                *PLANETARIUM_LINK.lock().await = Some(link.clone());
+                report(ServerStatus::Connected);
+                backoff = RECONNECT_BACKOFF_MIN;
+
+                // a restarted planetarium has forgotten the site and the limits
+                if let Err(e) = set_site_location().await {
+                    tracing::warn!("planetarium link: {e}");
+                }
+                if let Err(e) = set_mount_limits().await {
+                    tracing::warn!("planetarium link: {e}");
+                }
+                read_link(inbound).await;
+
+                // forget the link unless a relaunch has already replaced it
+                let mut link_lock = PLANETARIUM_LINK.lock().await;
+                if link_lock
+                    .as_ref()
+                    .is_some_and(|current| current.same_channel(&link))
+                {
+                    *link_lock = None;
+                }
+                report(ServerStatus::ConnectionLost);
+            }
+            // refused while the planetarium is still starting; only worth
+            // mentioning once it keeps failing
+            Err(e) if backoff == RECONNECT_BACKOFF_MAX => tracing::warn!("planetarium link: {e}"),
+            Err(_) => {}
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(RECONNECT_BACKOFF_MAX);
     }
 
-    Ok(())
+    if LINK_GENERATION.load(Ordering::Relaxed) == generation {
+        report(ServerStatus::Disconnected);
+    }
 }
 
-pub async fn set_mount_position(ra_hours: f32, dec_deg: f32) -> SiderealResult<()> {
-    let mut client_lock = PLANETARIUM_CLIENT.lock().await;
-    if let Some(client) = client_lock.as_mut() {
-        let request = SetMountLocationRequest {
-            ra: ra_hours,
-            dec: dec_deg,
+/// Our sending half of the link stream and the planetarium's replies
+type Link = (
+    mpsc::UnboundedSender<GuiMessage>,
+    Streaming<PlanetariumMessage>,
+);
+
+/// Connect and open the link stream
+async fn open_link(
+    settings: &PlanetariumLinkConfig,
+    token: Option<AsciiMetadataValue>,
+) -> Result<Link, Box<dyn std::error::Error + Send + Sync>> {
+    let channel = connect(settings).await?;
+    let mut client =
+        PlanetariumClient::with_interceptor(channel, move |mut request: Request<()>| {
+            if let Some(token) = &token {
+                request
+                    .metadata_mut()
+                    .insert(link::TOKEN_METADATA_KEY, token.clone());
+            }
+            Ok(request)
+        });
+
+    // One stream for the session instead of a request per update
+    let (tx, rx) = mpsc::unbounded_channel();
+    let inbound = client
+        .link(UnboundedReceiverStream::new(rx))
+        .await?
+        .into_inner();
+    Ok((tx, inbound))
+}
+
+/// Open a channel to the planetarium, over TLS when it's configured
+async fn connect(
+    settings: &PlanetariumLinkConfig,
+) -> Result<Channel, Box<dyn std::error::Error + Send + Sync>> {
+    let scheme = if settings.tls.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    // pings notice a planetarium that hung or vanished without closing
+    let mut endpoint = Endpoint::from_shared(format!("{scheme}://{}", settings.address))?
+        .connect_timeout(link::KEEPALIVE_TIMEOUT)
+        .http2_keep_alive_interval(link::KEEPALIVE_INTERVAL)
+        .keep_alive_timeout(link::KEEPALIVE_TIMEOUT)
+        .keep_alive_while_idle(true);
+    if let Some(tls) = &settings.tls {
+        // trust the planetarium's own certificate rather than the system roots
+        let certificate = Certificate::from_pem(std::fs::read(&tls.cert_path)?);
+        endpoint = endpoint.tls_config(
+            ClientTlsConfig::new()
+                .ca_certificate(certificate)
+                .domain_name(tls.domain.clone()),
+        )?;
+    }
+    Ok(endpoint.connect().await?)
+}
+
+/// Handle acks and commands from the planetarium until the link closes
+async fn read_link(mut inbound: Streaming<PlanetariumMessage>) {
+    while let Ok(Some(message)) = inbound.message().await {
+        match message.payload {
+            Some(planetarium_message::Payload::Ack(ack)) if !ack.ok => {
+                tracing::warn!(
+                    "planetarium rejected message {}: {}",
+                    ack.id,
+                    ack.description
+                );
+            }
+            Some(planetarium_message::Payload::Slew(request)) => {
+                planetarium_receiver::forward(ForwardedRPC::SetTrackingTargetRequest(request));
+            }
+            Some(planetarium_message::Payload::TrackPass(track)) => {
+                planetarium_receiver::forward(ForwardedRPC::TrackPass(track));
+            }
+            Some(planetarium_message::Payload::SunWarning(warning)) => {
+                planetarium_receiver::forward(ForwardedRPC::SunWarning(warning));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Queue `payload` on the link; a no-op while the planetarium isn't running
+async fn send(payload: Payload) -> SiderealResult<()> {
+    let link_lock = PLANETARIUM_LINK.lock().await;
+    if let Some(link) = link_lock.as_ref() {
+        let message = GuiMessage {
+            id: NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed),
+            payload: Some(payload),
         };
-        let response = client
-            .set_mount_location(request)
-            .await
-            .map_err(|e| SiderealError::ServerError(e.to_string()))?;
-        println!("{}", response.into_inner().description);
+        link.send(message)
+            .map_err(|_| SiderealError::ServerError("planetarium link closed".to_owned()))?;
     }
 
     Ok(())
 }
+
+pub async fn set_site_location() -> SiderealResult<()> {
+    let guard = GLOBAL_CONFIG.read().await;
+    send(Payload::Location(SetLocationRequest {
+        latitude: guard.location.latitude,
+        longitude: guard.location.longitude,
+        altitude: guard.location.altitude,
+    }))
+    .await
+}
+
+/// Let the planetarium shade the sky the mount can't reach
+pub async fn set_mount_limits() -> SiderealResult<()> {
+    let limits = GLOBAL_CONFIG.read().await.mount_limits.clone();
+    send(Payload::MountLimits(MountLimits {
+        min_altitude_degrees: limits.min_altitude_degrees,
+        max_altitude_degrees: limits.max_altitude_degrees,
+        min_dec_degrees: limits.min_dec_degrees,
+        max_dec_degrees: limits.max_dec_degrees,
+        max_hour_angle_hours: limits.max_hour_angle_hours,
+    }))
+    .await
+}
+
+/// Show where the mount is pointing (of date, as the mount reports it) in the
+/// planetarium, which works in J2000
+pub async fn set_mount_position(ra_hours: f64, dec_deg: f64) -> SiderealResult<()> {
+    let (ra_hours, dec_deg) = precession::to_j2000(ra_hours, dec_deg, Utc::now());
+    send(Payload::MountPosition(SetMountLocationRequest {
+        ra: ra_hours as f32,
+        dec: dec_deg as f32,
+    }))
+    .await
+}
+
+/// Show the target the mount is being sent to (of date) in the planetarium,
+/// which works in J2000
+pub async fn set_selected_target(ra_hours: f64, dec_deg: f64) -> SiderealResult<()> {
+    let (ra_hours, dec_deg) = precession::to_j2000(ra_hours, dec_deg, Utc::now());
+    send(Payload::SelectedTarget(SelectedTarget {
+        ra_hours: ra_hours as f32,
+        dec_degrees: dec_deg as f32,
+    }))
+    .await
+}
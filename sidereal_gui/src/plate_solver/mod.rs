@@ -0,0 +1,444 @@
+// plate_solver/mod.rs
+
+use crate::{
+    config::{PlateSolveConfig, SolverType},
+    fits,
+    model::{SiderealError, SiderealResult},
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Output,
+    time::Duration,
+};
+use tokio::{process::Command, time};
+
+/// Where a frame was pointing, from a plate solve
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+    /// Centre of the frame, J2000
+    pub ra_hours: f64,
+    pub dec_deg: f64,
+    /// Angle (degrees, 0-360) of the frame's up direction, East of North
+    pub rotation_deg: f64,
+    pub pixel_scale_arcsec: f64,
+    pub field_width_deg: f64,
+    pub field_height_deg: f64,
+}
+
+/// Solve the FITS frame at `path`. With `hint` (RA hours, Dec degrees) the
+/// solver only searches around it; without, it searches the whole sky.
+pub async fn solve(
+    path: &Path,
+    hint: Option<(f64, f64)>,
+    config: &PlateSolveConfig,
+) -> SiderealResult<Solution> {
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| SiderealError::FileError(format!("{}: {e}", path.display())))?;
+    let header = fits::header_numbers(&data)?;
+    let (width, height) = match (header.get("NAXIS1"), header.get("NAXIS2")) {
+        (Some(&width), Some(&height)) if width > 0.0 && height > 0.0 => (width, height),
+        _ => {
+            return Err(SiderealError::FormatError(
+                "The frame to solve has no image size".to_owned(),
+            ))
+        }
+    };
+
+    let wcs = match config.solver {
+        SolverType::Astap => solve_astap(path, hint, height, config).await?,
+        SolverType::AstrometryNet => solve_astrometry_net(path, hint, config).await?,
+    };
+    solution_from_wcs(&wcs, width, height)
+}
+
+/// Angle (degrees) between two (RA hours, Dec degrees) positions
+pub fn separation_deg(a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (ra1, dec1) = ((a.0 * 15.0).to_radians(), a.1.to_radians());
+    let (ra2, dec2) = ((b.0 * 15.0).to_radians(), b.1.to_radians());
+    // haversine, which holds up for the small angles centering works with
+    let h = ((dec2 - dec1) / 2.0).sin().powi(2)
+        + dec1.cos() * dec2.cos() * ((ra2 - ra1) / 2.0).sin().powi(2);
+    (2.0 * h.sqrt().min(1.0).asin()).to_degrees()
+}
+
+/// Run the solver, giving up after the configured timeout. Both solvers
+/// report their result through files, so the output is only for explaining
+/// why those are missing.
+async fn run(
+    program: &Path,
+    args: Vec<String>,
+    config: &PlateSolveConfig,
+) -> SiderealResult<Output> {
+    let mut command = Command::new(program);
+    // dropped on timeout, which must stop the solver too
+    command.args(&args).kill_on_drop(true);
+    let timeout = Duration::from_secs_f64(config.timeout_secs.max(1.0));
+    time::timeout(timeout, command.output())
+        .await
+        .map_err(|_| {
+            SiderealError::ServerError(format!("No solution within {:.0}s", timeout.as_secs_f64()))
+        })?
+        .map_err(|e| {
+            SiderealError::ServerError(format!(
+                "Couldn't run {}: {e}. Check the solver path.",
+                program.display()
+            ))
+        })
+}
+
+/// The last thing the solver printed, or how it exited if nothing
+fn last_words(output: &Output) -> String {
+    [&output.stderr, &output.stdout]
+        .iter()
+        .find_map(|printed| {
+            let printed = String::from_utf8_lossy(printed);
+            let line = printed.lines().rev().find(|line| !line.trim().is_empty())?;
+            Some(line.trim().to_owned())
+        })
+        .unwrap_or_else(|| output.status.to_string())
+}
+
+/// Solve with ASTAP, which writes its result beside the frame as an .ini
+async fn solve_astap(
+    path: &Path,
+    hint: Option<(f64, f64)>,
+    height: f64,
+    config: &PlateSolveConfig,
+) -> SiderealResult<HashMap<String, f64>> {
+    let ini = path.with_extension("ini");
+    // a result from an earlier solve mustn't be mistaken for this one's
+    let _ = tokio::fs::remove_file(&ini).await;
+
+    let mut args = vec!["-f".to_owned(), path.display().to_string()];
+    match hint {
+        Some((ra_hours, dec_deg)) => args.extend([
+            "-ra".to_owned(),
+            ra_hours.to_string(),
+            // ASTAP takes south polar distance rather than Dec
+            "-spd".to_owned(),
+            (dec_deg + 90.0).to_string(),
+            "-r".to_owned(),
+            config.search_radius_deg.to_string(),
+        ]),
+        None => args.extend(["-r".to_owned(), "180".to_owned()]),
+    }
+    // field height (degrees); 0 has ASTAP work it out from the header
+    let fov = config.pixel_scale_arcsec * height / 3600.0;
+    args.extend([
+        "-fov".to_owned(),
+        fov.to_string(),
+        "-z".to_owned(),
+        "0".to_owned(),
+    ]);
+    let output = run(&config.astap_path, args, config).await?;
+
+    let contents = tokio::fs::read_to_string(&ini).await.map_err(|_| {
+        SiderealError::ServerError(format!(
+            "ASTAP didn't produce a result: {}",
+            last_words(&output)
+        ))
+    })?;
+    let entries: HashMap<&str, &str> = contents
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim(), value.trim()))
+        })
+        .collect();
+    if entries.get("PLTSOLVD") != Some(&"T") {
+        let reason = entries
+            .get("ERROR")
+            .or(entries.get("WARNING"))
+            .filter(|reason| !reason.is_empty())
+            .copied()
+            .unwrap_or("no stars matched");
+        return Err(SiderealError::ServerError(format!(
+            "ASTAP found no solution: {reason}"
+        )));
+    }
+    Ok(entries
+        .into_iter()
+        .filter_map(|(key, value)| Some((key.to_owned(), value.parse::<f64>().ok()?)))
+        .collect())
+}
+
+/// Solve with astrometry.net's solve-field, which writes a WCS header file
+/// when it succeeds
+async fn solve_astrometry_net(
+    path: &Path,
+    hint: Option<(f64, f64)>,
+    config: &PlateSolveConfig,
+) -> SiderealResult<HashMap<String, f64>> {
+    let dir = std::env::temp_dir().join("sidereal_solve");
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| SiderealError::FileError(format!("{}: {e}", dir.display())))?;
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "frame".to_owned());
+    let wcs: PathBuf = dir.join(format!("{stem}.wcs"));
+    let _ = tokio::fs::remove_file(&wcs).await;
+
+    let mut args: Vec<String> = [
+        "--overwrite",
+        "--no-plots",
+        "--crpix-center",
+        "--new-fits",
+        "none",
+        "--cpulimit",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    args.push(config.timeout_secs.round().max(1.0).to_string());
+    args.extend(["--dir".to_owned(), dir.display().to_string()]);
+    if let Some((ra_hours, dec_deg)) = hint {
+        args.extend([
+            "--ra".to_owned(),
+            (ra_hours * 15.0).to_string(),
+            "--dec".to_owned(),
+            dec_deg.to_string(),
+            "--radius".to_owned(),
+            config.search_radius_deg.to_string(),
+        ]);
+    }
+    if config.pixel_scale_arcsec > 0.0 {
+        args.extend([
+            "--scale-units".to_owned(),
+            "arcsecperpix".to_owned(),
+            "--scale-low".to_owned(),
+            (config.pixel_scale_arcsec * 0.9).to_string(),
+            "--scale-high".to_owned(),
+            (config.pixel_scale_arcsec * 1.1).to_string(),
+        ]);
+    }
+    args.push(path.display().to_string());
+    let output = run(&config.solve_field_path, args, config).await?;
+
+    let data = tokio::fs::read(&wcs).await.map_err(|_| {
+        SiderealError::ServerError(format!(
+            "astrometry.net found no solution: {}",
+            last_words(&output)
+        ))
+    })?;
+    fits::header_numbers(&data)
+}
+
+/// The frame's centre, rotation and scale from its WCS (TAN projection)
+fn solution_from_wcs(
+    wcs: &HashMap<String, f64>,
+    width: f64,
+    height: f64,
+) -> SiderealResult<Solution> {
+    let get = |key: &str| {
+        wcs.get(key)
+            .copied()
+            .ok_or_else(|| SiderealError::FormatError(format!("The solver's result has no {key}")))
+    };
+    let (crval1, crval2) = (get("CRVAL1")?, get("CRVAL2")?);
+    let (crpix1, crpix2) = (get("CRPIX1")?, get("CRPIX2")?);
+    // older headers give the scale and rotation instead of the CD matrix
+    let [cd11, cd12, cd21, cd22] = match (get("CD1_1"), get("CD1_2"), get("CD2_1"), get("CD2_2")) {
+        (Ok(cd11), Ok(cd12), Ok(cd21), Ok(cd22)) => [cd11, cd12, cd21, cd22],
+        _ => {
+            let (cdelt1, cdelt2) = (get("CDELT1")?, get("CDELT2")?);
+            let rotation = wcs.get("CROTA2").copied().unwrap_or(0.0).to_radians();
+            [
+                cdelt1 * rotation.cos(),
+                -cdelt2 * rotation.sin(),
+                cdelt1 * rotation.sin(),
+                cdelt2 * rotation.cos(),
+            ]
+        }
+    };
+
+    // the centre pixel, offset from the reference pixel onto the sky
+    let (dx, dy) = ((width + 1.0) / 2.0 - crpix1, (height + 1.0) / 2.0 - crpix2);
+    let x = (cd11 * dx + cd12 * dy).to_radians();
+    let y = (cd21 * dx + cd22 * dy).to_radians();
+    let (ra0, dec0) = (crval1.to_radians(), crval2.to_radians());
+    let d = dec0.cos() - y * dec0.sin();
+    let ra = (ra0 + x.atan2(d)).to_degrees().rem_euclid(360.0);
+    let dec = (dec0.sin() + y * dec0.cos()).atan2(x.hypot(d)).to_degrees();
+
+    let scale = (cd11 * cd22 - cd12 * cd21).abs().sqrt() * 3600.0;
+    Ok(Solution {
+        ra_hours: ra / 15.0,
+        dec_deg: dec,
+        // up, one pixel along y, is (CD1_2, CD2_2) on the sky
+        rotation_deg: cd12.atan2(cd22).to_degrees().rem_euclid(360.0),
+        pixel_scale_arcsec: scale,
+        field_width_deg: width * scale / 3600.0,
+        field_height_deg: height * scale / 3600.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f64 = 4000.0;
+    const HEIGHT: f64 = 3000.0;
+    /// 1.5"/px, in degrees
+    const SCALE: f64 = 1.5 / 3600.0;
+    /// The solver's reference point, (RA, Dec) in degrees
+    const REFERENCE: (f64, f64) = (150.0, 30.0);
+    /// Where the frame's centre is, (RA, Dec) in degrees
+    const CENTRE: (f64, f64) = (150.4, 30.3);
+
+    /// Standard coordinates (degrees) of `point` in a TAN projection about
+    /// `reference`
+    fn project(point: (f64, f64), reference: (f64, f64)) -> (f64, f64) {
+        let (ra, dec) = (point.0.to_radians(), point.1.to_radians());
+        let (ra0, dec0) = (reference.0.to_radians(), reference.1.to_radians());
+        let cos_c = dec0.sin() * dec.sin() + dec0.cos() * dec.cos() * (ra - ra0).cos();
+        let xi = dec.cos() * (ra - ra0).sin() / cos_c;
+        let eta = (dec0.cos() * dec.sin() - dec0.sin() * dec.cos() * (ra - ra0).cos()) / cos_c;
+        (xi.to_degrees(), eta.to_degrees())
+    }
+
+    /// A WCS with this CD matrix, its reference pixel placed so the frame's
+    /// centre lands on `CENTRE`
+    fn wcs(cd: [f64; 4]) -> HashMap<String, f64> {
+        let (xi, eta) = project(CENTRE, REFERENCE);
+        let det = cd[0] * cd[3] - cd[1] * cd[2];
+        let dx = (cd[3] * xi - cd[1] * eta) / det;
+        let dy = (cd[0] * eta - cd[2] * xi) / det;
+        HashMap::from([
+            ("CRVAL1".to_owned(), REFERENCE.0),
+            ("CRVAL2".to_owned(), REFERENCE.1),
+            ("CRPIX1".to_owned(), (WIDTH + 1.0) / 2.0 - dx),
+            ("CRPIX2".to_owned(), (HEIGHT + 1.0) / 2.0 - dy),
+        ])
+    }
+
+    fn cd_wcs(cd: [f64; 4]) -> HashMap<String, f64> {
+        let mut wcs = wcs(cd);
+        for (key, value) in ["CD1_1", "CD1_2", "CD2_1", "CD2_2"].into_iter().zip(cd) {
+            wcs.insert(key.to_owned(), value);
+        }
+        wcs
+    }
+
+    /// CD matrix of a frame whose up is `rotation` degrees East of North;
+    /// unflipped, East is to the left of up
+    fn cd(rotation: f64, flipped: bool) -> [f64; 4] {
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let x = if flipped { SCALE } else { -SCALE };
+        [x * cos, SCALE * sin, -x * sin, SCALE * cos]
+    }
+
+    /// Position angle (degrees, East of North) of `b` from `a`, both
+    /// (RA hours, Dec degrees)
+    fn position_angle(a: (f64, f64), b: (f64, f64)) -> f64 {
+        let (ra1, dec1) = ((a.0 * 15.0).to_radians(), a.1.to_radians());
+        let (ra2, dec2) = ((b.0 * 15.0).to_radians(), b.1.to_radians());
+        let y = (ra2 - ra1).sin() * dec2.cos();
+        let x = dec1.cos() * dec2.sin() - dec1.sin() * dec2.cos() * (ra2 - ra1).cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// Difference of two angles in degrees, either way round the circle
+    fn angle_error(a: f64, b: f64) -> f64 {
+        ((a - b + 180.0).rem_euclid(360.0) - 180.0).abs()
+    }
+
+    /// Check the solution of `wcs` against the frame it was made for: its
+    /// centre, scale and rotation, and that rows and columns run the way the
+    /// rotation and `flipped` say on the sky
+    fn check(wcs: &HashMap<String, f64>, rotation: f64, flipped: bool) {
+        let solution = solution_from_wcs(wcs, WIDTH, HEIGHT).unwrap();
+        let centre = (solution.ra_hours, solution.dec_deg);
+        assert!(
+            (solution.ra_hours * 15.0 - CENTRE.0).abs() < 1e-9,
+            "{solution:?}"
+        );
+        assert!((solution.dec_deg - CENTRE.1).abs() < 1e-9, "{solution:?}");
+        assert!((solution.pixel_scale_arcsec - 1.5).abs() < 1e-9);
+        assert!((solution.field_width_deg - WIDTH * SCALE).abs() < 1e-9);
+        assert!((solution.field_height_deg - HEIGHT * SCALE).abs() < 1e-9);
+        assert!(
+            angle_error(solution.rotation_deg, rotation) < 1e-6,
+            "rotation {} for {rotation}",
+            solution.rotation_deg
+        );
+
+        // a frame 200 pixels taller or wider is centred 100 pixels further
+        // up or right. The meridians there have turned by Δα·sin(δ), 0.2°,
+        // from the reference point the CD matrix is for.
+        let above = solution_from_wcs(wcs, WIDTH, HEIGHT + 200.0).unwrap();
+        let up = position_angle(centre, (above.ra_hours, above.dec_deg));
+        assert!(angle_error(up, rotation) < 0.5, "up is at {up}");
+        let right = solution_from_wcs(wcs, WIDTH + 200.0, HEIGHT).unwrap();
+        let right = position_angle(centre, (right.ra_hours, right.dec_deg));
+        let expected = rotation + if flipped { 90.0 } else { -90.0 };
+        assert!(angle_error(right, expected) < 0.5, "right is at {right}");
+    }
+
+    #[test]
+    fn test_solution_from_cd_matrix() {
+        check(&cd_wcs(cd(0.0, false)), 0.0, false);
+        check(&cd_wcs(cd(30.0, false)), 30.0, false);
+        check(&cd_wcs(cd(250.0, false)), 250.0, false);
+    }
+
+    #[test]
+    fn test_solution_from_flipped_cd_matrix() {
+        check(&cd_wcs(cd(0.0, true)), 0.0, true);
+        check(&cd_wcs(cd(120.0, true)), 120.0, true);
+    }
+
+    #[test]
+    fn test_solution_from_cdelt_and_crota2() {
+        for (crota2, flipped) in [(0.0, false), (30.0, false), (30.0, true)] {
+            // CROTA2 turns the frame counterclockwise, North away from up
+            let (cdelt1, cdelt2) = (if flipped { SCALE } else { -SCALE }, SCALE);
+            let (sin, cos) = f64::to_radians(crota2).sin_cos();
+            let mut wcs = wcs([cdelt1 * cos, -cdelt2 * sin, cdelt1 * sin, cdelt2 * cos]);
+            wcs.insert("CDELT1".to_owned(), cdelt1);
+            wcs.insert("CDELT2".to_owned(), cdelt2);
+            wcs.insert("CROTA2".to_owned(), crota2);
+            check(&wcs, -crota2, flipped);
+        }
+    }
+
+    #[test]
+    fn test_solution_without_crota2() {
+        let mut wcs = wcs(cd(0.0, false));
+        wcs.insert("CDELT1".to_owned(), -SCALE);
+        wcs.insert("CDELT2".to_owned(), SCALE);
+        check(&wcs, 0.0, false);
+    }
+
+    #[test]
+    fn test_solution_missing_keys() {
+        let mut no_scale = wcs(cd(0.0, false));
+        assert!(solution_from_wcs(&no_scale, WIDTH, HEIGHT).is_err());
+        no_scale.insert("CDELT1".to_owned(), -SCALE);
+        assert!(solution_from_wcs(&no_scale, WIDTH, HEIGHT).is_err());
+
+        let mut no_reference = cd_wcs(cd(0.0, false));
+        no_reference.remove("CRVAL2");
+        assert!(solution_from_wcs(&no_reference, WIDTH, HEIGHT).is_err());
+    }
+
+    #[test]
+    fn test_separation_deg() {
+        assert_eq!(separation_deg((5.0, 20.0), (5.0, 20.0)), 0.0);
+        assert!((separation_deg((0.0, 0.0), (6.0, 0.0)) - 90.0).abs() < 1e-9);
+        assert!((separation_deg((3.0, 0.0), (3.0, -90.0)) - 90.0).abs() < 1e-9);
+        assert!((separation_deg((0.0, 0.0), (12.0, 0.0)) - 180.0).abs() < 1e-9);
+        // over the pole
+        assert!((separation_deg((0.0, 89.0), (12.0, 89.0)) - 2.0).abs() < 1e-9);
+        // across 0h, where two arcminutes of RA at Dec 60 are about one
+        let minute = 1.0 / 60.0 / 15.0;
+        let across = separation_deg((24.0 - minute, 60.0), (minute, 60.0));
+        assert!((across * 60.0 - 1.0).abs() < 1e-6, "{across}");
+        // one arcsecond apart in Dec
+        let close = separation_deg((10.0, 45.0), (10.0, 45.0 + 1.0 / 3600.0));
+        assert!((close * 3600.0 - 1.0).abs() < 1e-6, "{close}");
+    }
+}